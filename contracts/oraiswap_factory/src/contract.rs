@@ -11,11 +11,12 @@ use oraiswap::error::ContractError;
 use oraiswap::querier::query_pair_info_from_pair;
 use oraiswap::response::MsgInstantiateContractResponse;
 
-use crate::state::{read_pairs, Config, CONFIG, PAIRS};
+use crate::state::{read_asset_whitelist, read_pairs, Config, ASSET_WHITELIST, CONFIG, PAIRS};
 
 use oraiswap::asset::{pair_key, AssetInfo, PairInfo, PairInfoRaw};
 use oraiswap::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, PairsResponse, QueryMsg,
+    AssetWhitelistResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, PairsResponse,
+    QueryMsg,
 };
 use oraiswap::pair::{InstantiateMsg as PairInstantiateMsg, DEFAULT_COMMISSION_RATE};
 
@@ -36,6 +37,7 @@ pub fn instantiate(
         commission_rate: msg
             .commission_rate
             .unwrap_or(DEFAULT_COMMISSION_RATE.to_string()),
+        whitelist_enabled: false,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -55,7 +57,16 @@ pub fn execute(
             owner,
             token_code_id,
             pair_code_id,
-        } => execute_update_config(deps, env, info, owner, token_code_id, pair_code_id),
+            whitelist_enabled,
+        } => execute_update_config(
+            deps,
+            env,
+            info,
+            owner,
+            token_code_id,
+            pair_code_id,
+            whitelist_enabled,
+        ),
         ExecuteMsg::CreatePair {
             asset_infos,
             pair_admin,
@@ -66,6 +77,9 @@ pub fn execute(
             new_code_id,
             msg,
         } => migrate_pair(deps, env, info, contract_addr, new_code_id, msg),
+        ExecuteMsg::UpdateAssetWhitelist { add, remove } => {
+            execute_update_asset_whitelist(deps, info, add, remove)
+        }
     }
 }
 
@@ -102,6 +116,7 @@ pub fn execute_update_config(
     owner: Option<String>,
     token_code_id: Option<u64>,
     pair_code_id: Option<u64>,
+    whitelist_enabled: Option<bool>,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
 
@@ -122,11 +137,42 @@ pub fn execute_update_config(
         config.pair_code_id = pair_code_id;
     }
 
+    if let Some(whitelist_enabled) = whitelist_enabled {
+        config.whitelist_enabled = whitelist_enabled;
+    }
+
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+// Only owner can execute it
+pub fn execute_update_asset_whitelist(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<AssetInfo>,
+    remove: Vec<AssetInfo>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for asset_info in add {
+        let raw_info = asset_info.to_raw(deps.api)?;
+        ASSET_WHITELIST.save(deps.storage, raw_info.as_bytes(), &raw_info)?;
+    }
+
+    for asset_info in remove {
+        let raw_info = asset_info.to_raw(deps.api)?;
+        ASSET_WHITELIST.remove(deps.storage, raw_info.as_bytes());
+    }
+
+    Ok(Response::new().add_attribute("action", "update_asset_whitelist"))
+}
+
 // Anyone can execute it to create swap pair
 pub fn execute_create_pair(
     deps: DepsMut,
@@ -141,6 +187,16 @@ pub fn execute_create_pair(
         asset_infos[1].to_raw(deps.api)?,
     ];
 
+    if config.whitelist_enabled {
+        for (asset_info, raw_info) in asset_infos.iter().zip(raw_infos.iter()) {
+            if !ASSET_WHITELIST.has(deps.storage, raw_info.as_bytes()) {
+                return Err(ContractError::AssetNotWhitelisted {
+                    asset: asset_info.to_string(),
+                });
+            }
+        }
+    }
+
     let pair_key = pair_key(&raw_infos);
 
     // can not update pair once updated
@@ -276,6 +332,9 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::Pairs { start_after, limit } => {
             to_binary(&query_pairs(deps, start_after, limit)?)
         }
+        QueryMsg::AssetWhitelist { start_after, limit } => {
+            to_binary(&query_asset_whitelist(deps, start_after, limit)?)
+        }
     }
 }
 
@@ -286,6 +345,7 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         owner: deps.api.addr_humanize(&state.owner)?,
         token_code_id: state.token_code_id,
         pair_code_id: state.pair_code_id,
+        whitelist_enabled: state.whitelist_enabled,
     };
 
     Ok(resp)
@@ -320,6 +380,21 @@ pub fn query_pairs(
     Ok(resp)
 }
 
+pub fn query_asset_whitelist(
+    deps: Deps,
+    start_after: Option<AssetInfo>,
+    limit: Option<u32>,
+) -> StdResult<AssetWhitelistResponse> {
+    let start_after = match start_after {
+        Some(asset_info) => Some(asset_info.to_raw(deps.api)?),
+        None => None,
+    };
+
+    let asset_infos = read_asset_whitelist(deps.storage, deps.api, start_after, limit)?;
+
+    Ok(AssetWhitelistResponse { asset_infos })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
     Ok(Response::default())
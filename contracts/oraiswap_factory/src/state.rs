@@ -2,7 +2,7 @@ use cosmwasm_schema::cw_serde;
 
 use cosmwasm_std::{Api, CanonicalAddr, Order, StdResult, Storage};
 use cw_storage_plus::{Bound, Item, Map};
-use oraiswap::asset::{AssetInfoRaw, PairInfo, PairInfoRaw};
+use oraiswap::asset::{AssetInfo, AssetInfoRaw, PairInfo, PairInfoRaw};
 
 #[cw_serde]
 pub struct Config {
@@ -11,6 +11,8 @@ pub struct Config {
     pub pair_code_id: u64,
     pub token_code_id: u64,
     pub commission_rate: String,
+    #[serde(default)]
+    pub whitelist_enabled: bool,
 }
 
 // put the length bytes at the first for compatibility with legacy singleton store
@@ -19,9 +21,31 @@ pub const CONFIG: Item<Config> = Item::new("\u{0}\u{6}config");
 // store temporary pair info while waiting for deployment
 pub const PAIRS: Map<&[u8], PairInfoRaw> = Map::new("pairs");
 
+// assets allowed in CreatePair while the whitelist is enabled, keyed by asset info bytes
+pub const ASSET_WHITELIST: Map<&[u8], AssetInfoRaw> = Map::new("asset_whitelist");
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
+
+pub fn read_asset_whitelist(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    start_after: Option<AssetInfoRaw>,
+    limit: Option<u32>,
+) -> StdResult<Vec<AssetInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|asset_info| Bound::ExclusiveRaw(asset_info.as_bytes().to_vec()));
+
+    ASSET_WHITELIST
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            v.to_normal(api)
+        })
+        .collect::<StdResult<Vec<AssetInfo>>>()
+}
 pub fn read_pairs(
     storage: &dyn Storage,
     api: &dyn Api,
@@ -87,6 +111,7 @@ mod test {
                 pair_code_id: 1,
                 token_code_id: 1,
                 commission_rate: DEFAULT_COMMISSION_RATE.to_string(),
+                whitelist_enabled: false,
             },
         )
         .unwrap();
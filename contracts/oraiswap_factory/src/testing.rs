@@ -1,10 +1,11 @@
 use cosmwasm_std::Addr;
-use oraiswap::asset::{AssetInfo, PairInfo};
+use oraiswap::asset::{AssetInfo, PairInfo, ORAI_DENOM};
 
 use oraiswap::create_entry_points_testing;
+use oraiswap::factory::{AssetWhitelistResponse, ExecuteMsg, QueryMsg};
 use oraiswap::pair::DEFAULT_COMMISSION_RATE;
 use oraiswap::querier::query_pair_info_from_pair;
-use oraiswap::testing::MockApp;
+use oraiswap::testing::{MockApp, APP_OWNER};
 
 #[test]
 fn create_pair() {
@@ -90,3 +91,140 @@ fn add_pair() {
     let pair_res = app.query_pair(asset_infos.clone()).unwrap();
     assert_eq!(pair_res, pair_info);
 }
+
+#[test]
+fn create_pair_with_asset_whitelist() {
+    let mut app = MockApp::new(&[]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(create_entry_points_testing!(crate).with_reply(crate::contract::reply)),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair).with_reply(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let contract_addr1 = app.create_token("assetA");
+    let contract_addr2 = app.create_token("assetB");
+
+    let orai = AssetInfo::NativeToken {
+        denom: ORAI_DENOM.to_string(),
+    };
+    let asset_a = AssetInfo::Token {
+        contract_addr: contract_addr1,
+    };
+    let asset_b = AssetInfo::Token {
+        contract_addr: contract_addr2,
+    };
+
+    let factory_addr = app.factory_addr.clone();
+
+    // only owner can manage the whitelist
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        factory_addr.clone(),
+        &ExecuteMsg::UpdateAssetWhitelist {
+            add: vec![orai.clone()],
+            remove: vec![],
+        },
+        &[],
+    );
+    app.assert_fail(res);
+
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        factory_addr.clone(),
+        &ExecuteMsg::UpdateAssetWhitelist {
+            add: vec![orai.clone(), asset_a.clone()],
+            remove: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        factory_addr.clone(),
+        &ExecuteMsg::UpdateConfig {
+            owner: None,
+            token_code_id: None,
+            pair_code_id: None,
+            whitelist_enabled: Some(true),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let res: AssetWhitelistResponse = app
+        .query(
+            factory_addr.clone(),
+            &QueryMsg::AssetWhitelist {
+                start_after: None,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.asset_infos.len(), 1);
+    let res: AssetWhitelistResponse = app
+        .query(
+            factory_addr.clone(),
+            &QueryMsg::AssetWhitelist {
+                start_after: res.asset_infos.last().cloned(),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.asset_infos.len(), 1);
+
+    // asset_b is not whitelisted
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        factory_addr.clone(),
+        &ExecuteMsg::CreatePair {
+            asset_infos: [orai.clone(), asset_b.clone()],
+            pair_admin: None,
+        },
+        &[],
+    );
+    app.assert_fail(res);
+
+    // both assets are whitelisted
+    assert!(app.create_pair([orai.clone(), asset_a.clone()]).is_some());
+
+    // removed assets are no longer accepted
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        factory_addr.clone(),
+        &ExecuteMsg::UpdateAssetWhitelist {
+            add: vec![],
+            remove: vec![asset_a.clone()],
+        },
+        &[],
+    )
+    .unwrap();
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        factory_addr.clone(),
+        &ExecuteMsg::CreatePair {
+            asset_infos: [asset_a.clone(), asset_b.clone()],
+            pair_admin: None,
+        },
+        &[],
+    );
+    app.assert_fail(res);
+
+    // disabling the whitelist restores open pair creation
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        factory_addr.clone(),
+        &ExecuteMsg::UpdateConfig {
+            owner: None,
+            token_code_id: None,
+            pair_code_id: None,
+            whitelist_enabled: Some(false),
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(app.create_pair([orai, asset_b]).is_some());
+}
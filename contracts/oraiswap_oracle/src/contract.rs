@@ -1,22 +1,26 @@
 use cosmwasm_std::{entry_point, Coin};
 
 use cosmwasm_std::{
-    to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdError,
-    StdResult, Uint128,
+    to_binary, Addr, Binary, CanonicalAddr, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, Uint128,
 };
 
 use oraiswap::asset::ORAI_DENOM;
 use oraiswap::oracle::{
     ContractInfo, ContractInfoResponse, ExchangeRateItem, ExchangeRateResponse,
-    ExchangeRatesResponse, ExecuteMsg, MigrateMsg, OracleContractQuery, OracleExchangeQuery,
-    OracleTreasuryQuery, QueryMsg, TaxCapResponse, TaxRateResponse,
+    ExchangeRatesResponse, ExecuteMsg, GuardianAction, GuardianActionResponse, GuardiansResponse,
+    MigrateMsg, OracleContractQuery, OracleExchangeQuery, OracleTreasuryQuery, QueryMsg,
+    TaxCapResponse, TaxRateResponse,
 };
 
 use oraiswap::error::ContractError;
 use oraiswap::oracle::InstantiateMsg;
 
 // use crate::msg::{ExecuteMsg, InstantiateMsg};
-use crate::state::{CONTRACT_INFO, EXCHANGE_RATES, TAX_CAP, TAX_RATE};
+use crate::state::{
+    GuardianConfig, PendingAction, CONTRACT_INFO, EXCHANGE_RATES, GUARDIAN_ACTIONS,
+    GUARDIAN_CONFIG, LAST_ACTION_ID, TAX_CAP, TAX_RATE,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:oraiswap_oracle";
@@ -59,7 +63,7 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
@@ -72,6 +76,16 @@ pub fn execute(
         ExecuteMsg::UpdateTaxCap { cap, denom } => execute_update_tax_cap(deps, info, denom, cap),
         ExecuteMsg::UpdateTaxRate { rate } => execute_update_tax_rate(deps, info, rate),
         ExecuteMsg::UpdateAdmin { admin } => execute_update_admin(deps, info, admin),
+        ExecuteMsg::UpdateGuardians {
+            guardians,
+            threshold,
+            approval_window,
+        } => execute_update_guardians(deps, info, guardians, threshold, approval_window),
+        ExecuteMsg::ProposeAction { action } => execute_propose_action(deps, env, info, action),
+        ExecuteMsg::ApproveAction { action_id } => {
+            execute_approve_action(deps, env, info, action_id)
+        }
+        ExecuteMsg::CancelAction { action_id } => execute_cancel_action(deps, info, action_id),
     }
 }
 
@@ -129,6 +143,7 @@ pub fn execute_update_admin(
     if contract_info.admin.ne(&sender_addr) {
         return Err(ContractError::Unauthorized {});
     }
+    assert_not_guarded(deps.as_ref())?;
 
     // update new admin
     contract_info.admin = deps.api.addr_canonicalize(admin.as_str())?;
@@ -169,12 +184,216 @@ pub fn execute_delete_exchange_rate(
     if contract_info.admin.ne(&sender_addr) {
         return Err(ContractError::Unauthorized {});
     }
+    assert_not_guarded(deps.as_ref())?;
 
     EXCHANGE_RATES.remove(deps.storage, denom.as_bytes());
 
     Ok(Response::default())
 }
 
+pub fn execute_update_guardians(
+    deps: DepsMut,
+    info: MessageInfo,
+    guardians: Vec<Addr>,
+    threshold: u32,
+    approval_window: u64,
+) -> Result<Response, ContractError> {
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // check authorized
+    if contract_info.admin.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+    assert_not_guarded(deps.as_ref())?;
+
+    let config = guardian_config(deps.as_ref(), guardians, threshold, approval_window)?;
+    GUARDIAN_CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "update_guardians"))
+}
+
+fn guardian_config(
+    deps: Deps,
+    guardians: Vec<Addr>,
+    threshold: u32,
+    approval_window: u64,
+) -> Result<GuardianConfig, ContractError> {
+    let mut guardians = guardians
+        .iter()
+        .map(|guardian| deps.api.addr_canonicalize(guardian.as_str()))
+        .collect::<StdResult<Vec<_>>>()?;
+    guardians.sort();
+    guardians.dedup();
+
+    if !guardians.is_empty() && (threshold == 0 || threshold as usize > guardians.len()) {
+        return Err(ContractError::InvalidGuardianThreshold {
+            threshold,
+            guardians: guardians.len() as u32,
+        });
+    }
+
+    Ok(GuardianConfig {
+        guardians,
+        threshold,
+        approval_window,
+    })
+}
+
+// the admin key alone can not undo what the guardians hold together
+fn assert_not_guarded(deps: Deps) -> Result<(), ContractError> {
+    match GUARDIAN_CONFIG.may_load(deps.storage)? {
+        Some(config) if !config.guardians.is_empty() => {
+            Err(ContractError::GuardianActionRequired {})
+        }
+        _ => Ok(()),
+    }
+}
+
+fn assert_guardian(
+    deps: Deps,
+    sender: &Addr,
+) -> Result<(GuardianConfig, CanonicalAddr), ContractError> {
+    let config = GUARDIAN_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::Unauthorized {})?;
+    let sender_addr = deps.api.addr_canonicalize(sender.as_str())?;
+
+    if !config.guardians.contains(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok((config, sender_addr))
+}
+
+pub fn execute_propose_action(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: GuardianAction,
+) -> Result<Response, ContractError> {
+    let (config, sender_addr) = assert_guardian(deps.as_ref(), &info.sender)?;
+    // a guardian set that can never be applied is refused before it gathers approvals
+    if let GuardianAction::UpdateGuardians {
+        guardians,
+        threshold,
+        approval_window,
+    } = &action
+    {
+        guardian_config(
+            deps.as_ref(),
+            guardians.clone(),
+            *threshold,
+            *approval_window,
+        )?;
+    }
+
+    let action_id = LAST_ACTION_ID.may_load(deps.storage)?.unwrap_or_default() + 1;
+    LAST_ACTION_ID.save(deps.storage, &action_id)?;
+
+    let pending_action = PendingAction {
+        proposer: sender_addr.clone(),
+        action,
+        approvals: vec![sender_addr],
+        expires_at: env.block.time.seconds() + config.approval_window,
+    };
+
+    let response = Response::new().add_attributes(vec![
+        ("action", "propose_action".to_string()),
+        ("action_id", action_id.to_string()),
+    ]);
+
+    execute_if_approved(deps, &config, action_id, pending_action, response)
+}
+
+pub fn execute_approve_action(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action_id: u64,
+) -> Result<Response, ContractError> {
+    let (config, sender_addr) = assert_guardian(deps.as_ref(), &info.sender)?;
+    let mut pending_action = GUARDIAN_ACTIONS.load(deps.storage, action_id)?;
+
+    if env.block.time.seconds() > pending_action.expires_at {
+        return Err(ContractError::GuardianActionExpired { action_id });
+    }
+
+    // a guardian approving again does not count twice
+    if !pending_action.approvals.contains(&sender_addr) {
+        pending_action.approvals.push(sender_addr);
+    }
+
+    let response = Response::new().add_attributes(vec![
+        ("action", "approve_action".to_string()),
+        ("action_id", action_id.to_string()),
+    ]);
+
+    execute_if_approved(deps, &config, action_id, pending_action, response)
+}
+
+pub fn execute_cancel_action(
+    deps: DepsMut,
+    info: MessageInfo,
+    action_id: u64,
+) -> Result<Response, ContractError> {
+    let pending_action = GUARDIAN_ACTIONS.load(deps.storage, action_id)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    if pending_action.proposer.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    GUARDIAN_ACTIONS.remove(deps.storage, action_id);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "cancel_action".to_string()),
+        ("action_id", action_id.to_string()),
+    ]))
+}
+
+// only approvals of current guardians count, the guardian set may change while the action is pending
+fn execute_if_approved(
+    deps: DepsMut,
+    config: &GuardianConfig,
+    action_id: u64,
+    pending_action: PendingAction,
+    response: Response,
+) -> Result<Response, ContractError> {
+    let approvals = pending_action
+        .approvals
+        .iter()
+        .filter(|approval| config.guardians.contains(approval))
+        .count();
+
+    if approvals < config.threshold as usize {
+        GUARDIAN_ACTIONS.save(deps.storage, action_id, &pending_action)?;
+        return Ok(response);
+    }
+
+    GUARDIAN_ACTIONS.remove(deps.storage, action_id);
+    match pending_action.action {
+        GuardianAction::UpdateAdmin { admin } => {
+            let mut contract_info = CONTRACT_INFO.load(deps.storage)?;
+            contract_info.admin = deps.api.addr_canonicalize(admin.as_str())?;
+            CONTRACT_INFO.save(deps.storage, &contract_info)?;
+        }
+        GuardianAction::DeleteExchangeRate { denom } => {
+            EXCHANGE_RATES.remove(deps.storage, denom.as_bytes());
+        }
+        GuardianAction::UpdateGuardians {
+            guardians,
+            threshold,
+            approval_window,
+        } => {
+            let config = guardian_config(deps.as_ref(), guardians, threshold, approval_window)?;
+            GUARDIAN_CONFIG.save(deps.storage, &config)?;
+        }
+    }
+
+    Ok(response.add_attribute("executed", "true"))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -205,6 +424,10 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             OracleContractQuery::RewardPool { denom } => {
                 to_binary(&query_contract_balance(deps, env, denom)?)
             }
+            OracleContractQuery::Guardians {} => to_binary(&query_guardians(deps)?),
+            OracleContractQuery::GuardianAction { action_id } => {
+                to_binary(&query_guardian_action(deps, action_id)?)
+            }
         },
     }
 }
@@ -285,6 +508,42 @@ pub fn query_contract_info(deps: Deps) -> StdResult<ContractInfoResponse> {
     })
 }
 
+pub fn query_guardians(deps: Deps) -> StdResult<GuardiansResponse> {
+    let config = GUARDIAN_CONFIG
+        .may_load(deps.storage)?
+        .unwrap_or(GuardianConfig {
+            guardians: vec![],
+            threshold: 0,
+            approval_window: 0,
+        });
+
+    Ok(GuardiansResponse {
+        guardians: config
+            .guardians
+            .iter()
+            .map(|guardian| deps.api.addr_humanize(guardian))
+            .collect::<StdResult<Vec<Addr>>>()?,
+        threshold: config.threshold,
+        approval_window: config.approval_window,
+    })
+}
+
+pub fn query_guardian_action(deps: Deps, action_id: u64) -> StdResult<GuardianActionResponse> {
+    let pending_action = GUARDIAN_ACTIONS.load(deps.storage, action_id)?;
+
+    Ok(GuardianActionResponse {
+        action_id,
+        proposer: deps.api.addr_humanize(&pending_action.proposer)?,
+        action: pending_action.action,
+        approvals: pending_action
+            .approvals
+            .iter()
+            .map(|approval| deps.api.addr_humanize(approval))
+            .collect::<StdResult<Vec<Addr>>>()?,
+        expires_at: pending_action.expires_at,
+    })
+}
+
 /// query_contract_balance: return native balance, currently only Orai denom
 pub fn query_contract_balance(deps: Deps, env: Env, denom: String) -> StdResult<Coin> {
     deps.querier.query_balance(env.contract.address, &denom)
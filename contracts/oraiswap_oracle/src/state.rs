@@ -1,6 +1,7 @@
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{CanonicalAddr, Decimal, Uint128};
 use cw_storage_plus::{Item, Map};
-use oraiswap::oracle::ContractInfo;
+use oraiswap::oracle::{ContractInfo, GuardianAction};
 
 // put the length bytes at the first for compatibility with legacy singleton store
 pub const CONTRACT_INFO: Item<ContractInfo> = Item::new("\u{0}\u{13}contract_info");
@@ -10,3 +11,23 @@ pub const TAX_CAP: Map<&[u8], Uint128> = Map::new("tax_cap");
 /// Exchange rate of denom to Orai
 /// (QUOTE_DENOM / ORAI)  / (BASE_DENOM / ORAI) = QUOTE_DENOM / BASE_DENOM
 pub const EXCHANGE_RATES: Map<&[u8], Decimal> = Map::new("exchange_rates");
+
+#[cw_serde]
+pub struct GuardianConfig {
+    pub guardians: Vec<CanonicalAddr>,
+    pub threshold: u32,
+    pub approval_window: u64,
+}
+
+#[cw_serde]
+pub struct PendingAction {
+    pub proposer: CanonicalAddr,
+    pub action: GuardianAction,
+    pub approvals: Vec<CanonicalAddr>,
+    pub expires_at: u64,
+}
+
+pub const GUARDIAN_CONFIG: Item<GuardianConfig> = Item::new("guardian_config");
+/// Actions waiting for guardian approvals, removed once executed or cancelled
+pub const GUARDIAN_ACTIONS: Map<u64, PendingAction> = Map::new("guardian_actions");
+pub const LAST_ACTION_ID: Item<u64> = Item::new("last_action_id");
@@ -3,7 +3,10 @@ use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Uint128,
 
 use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
 use oraiswap::create_entry_points_testing;
-use oraiswap::oracle::{ExecuteMsg, OracleContract};
+use oraiswap::oracle::{
+    ContractInfoResponse, ExecuteMsg, GuardianAction, GuardianActionResponse, GuardiansResponse,
+    OracleContract, OracleContractQuery, QueryMsg,
+};
 use oraiswap::testing::{MockApp, APP_OWNER};
 
 fn setup_contract() -> MockApp {
@@ -24,22 +27,14 @@ fn setup_contract() -> MockApp {
 fn proper_initialization() {
     let mut app = setup_contract();
 
-    let msg = ExecuteMsg::UpdateExchangeRate {
-        denom: "usdt".to_string(),
-        exchange_rate: Decimal::percent(10), // 1 orai = 10 usdt
-    };
+    // 1 orai = 10 usdt, 1 orai = 100 airi
+    app.set_exchange_rates(&[
+        (&"usdt".to_string(), Decimal::percent(10)),
+        (&"airi".to_string(), Decimal::percent(1)),
+    ]);
 
     let oracle_contract = OracleContract(app.oracle_addr.clone());
 
-    let _res = app
-        .execute(
-            Addr::unchecked(APP_OWNER),
-            app.oracle_addr.clone(),
-            &msg,
-            &[],
-        )
-        .unwrap();
-
     let exchange_rate_res = oracle_contract
         .query_exchange_rate(
             &app.as_querier(),
@@ -50,20 +45,6 @@ fn proper_initialization() {
 
     assert_eq!("10", exchange_rate_res.item.exchange_rate.to_string());
 
-    let msg = ExecuteMsg::UpdateExchangeRate {
-        denom: "airi".to_string(),
-        exchange_rate: Decimal::percent(1), // 1 orai = 100 airi
-    };
-
-    let _res = app
-        .execute(
-            Addr::unchecked(APP_OWNER),
-            app.oracle_addr.clone(),
-            &msg,
-            &[],
-        )
-        .unwrap();
-
     let exchange_rate_res = oracle_contract
         .query_exchange_rate(&app.as_querier(), "airi".to_string(), "usdt".to_string())
         .unwrap();
@@ -72,6 +53,64 @@ fn proper_initialization() {
     assert_eq!("10", exchange_rate_res.item.exchange_rate.to_string());
 }
 
+#[test]
+fn exchange_rates_and_tax() {
+    let mut app = setup_contract();
+
+    app.set_exchange_rates(&[
+        (&"usdt".to_string(), Decimal::percent(10)),
+        (&"airi".to_string(), Decimal::percent(1)),
+    ]);
+    app.set_tax(
+        Decimal::permille(3),
+        &[(&"usdt".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let oracle_contract = OracleContract(app.oracle_addr.clone());
+
+    // the batched query answers the same as the single ones
+    let res = oracle_contract
+        .query_exchange_rates(
+            &app.as_querier(),
+            "airi".to_string(),
+            vec!["usdt".to_string(), ORAI_DENOM.to_string()],
+        )
+        .unwrap();
+    assert_eq!(res.base_denom, "airi");
+    for item in res.items {
+        let single = oracle_contract
+            .query_exchange_rate(&app.as_querier(), "airi".to_string(), item.quote_denom)
+            .unwrap();
+        assert_eq!(single.item.exchange_rate, item.exchange_rate);
+    }
+
+    assert_eq!(
+        oracle_contract
+            .query_tax_rate(&app.as_querier())
+            .unwrap()
+            .rate,
+        Decimal::permille(3)
+    );
+    assert_eq!(
+        oracle_contract
+            .query_tax_cap(&app.as_querier(), "usdt".to_string())
+            .unwrap()
+            .cap,
+        Uint128::from(1000000u128)
+    );
+
+    // a price can be moved by setting it again
+    app.set_exchange_rates(&[(&"usdt".to_string(), Decimal::percent(20))]);
+    let res = oracle_contract
+        .query_exchange_rate(
+            &app.as_querier(),
+            "usdt".to_string(),
+            ORAI_DENOM.to_string(),
+        )
+        .unwrap();
+    assert_eq!(res.item.exchange_rate, Decimal::from_ratio(5u128, 1u128));
+}
+
 #[test]
 fn tax_cap_notfound() {
     let app = setup_contract();
@@ -193,3 +232,306 @@ fn test_asset() {
         })
     );
 }
+
+#[test]
+fn guardian_rotate_admin() {
+    let mut app = setup_contract();
+    let oracle_addr = app.oracle_addr.clone();
+
+    // only admin can set guardians, and the threshold must be reachable
+    let res = app.execute(
+        Addr::unchecked("guardian1"),
+        oracle_addr.clone(),
+        &ExecuteMsg::UpdateGuardians {
+            guardians: vec![Addr::unchecked("guardian1")],
+            threshold: 1,
+            approval_window: 100,
+        },
+        &[],
+    );
+    app.assert_fail(res);
+    let res = app.execute(
+        Addr::unchecked(APP_OWNER),
+        oracle_addr.clone(),
+        &ExecuteMsg::UpdateGuardians {
+            guardians: vec![Addr::unchecked("guardian1"), Addr::unchecked("guardian2")],
+            threshold: 3,
+            approval_window: 100,
+        },
+        &[],
+    );
+    app.assert_fail(res);
+
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        oracle_addr.clone(),
+        &ExecuteMsg::UpdateGuardians {
+            guardians: vec![
+                Addr::unchecked("guardian1"),
+                Addr::unchecked("guardian2"),
+                Addr::unchecked("guardian3"),
+            ],
+            threshold: 2,
+            approval_window: 100,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // non guardian can not propose
+    let action = GuardianAction::UpdateAdmin {
+        admin: Addr::unchecked("new_admin"),
+    };
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        oracle_addr.clone(),
+        &ExecuteMsg::ProposeAction {
+            action: action.clone(),
+        },
+        &[],
+    );
+    app.assert_fail(res);
+
+    app.execute(
+        Addr::unchecked("guardian1"),
+        oracle_addr.clone(),
+        &ExecuteMsg::ProposeAction { action },
+        &[],
+    )
+    .unwrap();
+
+    // approving twice from the proposer is ignored
+    app.execute(
+        Addr::unchecked("guardian1"),
+        oracle_addr.clone(),
+        &ExecuteMsg::ApproveAction { action_id: 1 },
+        &[],
+    )
+    .unwrap();
+    let res: GuardianActionResponse = app
+        .query(
+            oracle_addr.clone(),
+            &QueryMsg::Contract(OracleContractQuery::GuardianAction { action_id: 1 }),
+        )
+        .unwrap();
+    assert_eq!(res.approvals, vec![Addr::unchecked("guardian1")]);
+
+    let info: ContractInfoResponse = app
+        .query(
+            oracle_addr.clone(),
+            &QueryMsg::Contract(OracleContractQuery::ContractInfo {}),
+        )
+        .unwrap();
+    assert_eq!(info.admin, Addr::unchecked(APP_OWNER));
+
+    // second distinct approval reaches the threshold and executes the action
+    app.execute(
+        Addr::unchecked("guardian2"),
+        oracle_addr.clone(),
+        &ExecuteMsg::ApproveAction { action_id: 1 },
+        &[],
+    )
+    .unwrap();
+    let info: ContractInfoResponse = app
+        .query(
+            oracle_addr.clone(),
+            &QueryMsg::Contract(OracleContractQuery::ContractInfo {}),
+        )
+        .unwrap();
+    assert_eq!(info.admin, Addr::unchecked("new_admin"));
+
+    // executed action is gone
+    let res: Result<GuardianActionResponse, _> = app.query(
+        oracle_addr.clone(),
+        &QueryMsg::Contract(OracleContractQuery::GuardianAction { action_id: 1 }),
+    );
+    assert!(res.is_err());
+}
+
+#[test]
+fn guardian_action_expire_and_cancel() {
+    let mut app = setup_contract();
+    let oracle_addr = app.oracle_addr.clone();
+
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        oracle_addr.clone(),
+        &ExecuteMsg::UpdateExchangeRate {
+            denom: "usdt".to_string(),
+            exchange_rate: Decimal::percent(10),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        oracle_addr.clone(),
+        &ExecuteMsg::UpdateGuardians {
+            guardians: vec![Addr::unchecked("guardian1"), Addr::unchecked("guardian2")],
+            threshold: 2,
+            approval_window: 10,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let action = GuardianAction::DeleteExchangeRate {
+        denom: "usdt".to_string(),
+    };
+    for _ in 0..3 {
+        app.execute(
+            Addr::unchecked("guardian1"),
+            oracle_addr.clone(),
+            &ExecuteMsg::ProposeAction {
+                action: action.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // each block moves 5 seconds, the first action is out of its window
+    let res = app.execute(
+        Addr::unchecked("guardian2"),
+        oracle_addr.clone(),
+        &ExecuteMsg::ApproveAction { action_id: 1 },
+        &[],
+    );
+    app.assert_fail(res);
+
+    // only the proposer can cancel
+    let res = app.execute(
+        Addr::unchecked("guardian2"),
+        oracle_addr.clone(),
+        &ExecuteMsg::CancelAction { action_id: 3 },
+        &[],
+    );
+    app.assert_fail(res);
+    app.execute(
+        Addr::unchecked("guardian1"),
+        oracle_addr.clone(),
+        &ExecuteMsg::CancelAction { action_id: 3 },
+        &[],
+    )
+    .unwrap();
+    let res = app.execute(
+        Addr::unchecked("guardian2"),
+        oracle_addr.clone(),
+        &ExecuteMsg::ApproveAction { action_id: 3 },
+        &[],
+    );
+    app.assert_fail(res);
+
+    // exchange rate is still there
+    let oracle_contract = OracleContract(oracle_addr);
+    assert!(oracle_contract
+        .query_exchange_rate(
+            &app.as_querier(),
+            "usdt".to_string(),
+            ORAI_DENOM.to_string()
+        )
+        .is_ok());
+}
+
+#[test]
+fn guardians_hold_admin_actions() {
+    let mut app = setup_contract();
+    let oracle_addr = app.oracle_addr.clone();
+
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        oracle_addr.clone(),
+        &ExecuteMsg::UpdateGuardians {
+            guardians: vec![Addr::unchecked("guardian1"), Addr::unchecked("guardian2")],
+            threshold: 2,
+            approval_window: 100,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the admin key alone can no longer rotate itself, delete rates or replace the guardians
+    for msg in [
+        ExecuteMsg::UpdateAdmin {
+            admin: Addr::unchecked("new_admin"),
+        },
+        ExecuteMsg::DeleteExchangeRate {
+            denom: ORAI_DENOM.to_string(),
+        },
+        ExecuteMsg::UpdateGuardians {
+            guardians: vec![],
+            threshold: 0,
+            approval_window: 0,
+        },
+    ] {
+        let res = app.execute(Addr::unchecked(APP_OWNER), oracle_addr.clone(), &msg, &[]);
+        app.assert_fail(res);
+    }
+
+    // a guardian set that could never approve anything is refused at proposal
+    let res = app.execute(
+        Addr::unchecked("guardian1"),
+        oracle_addr.clone(),
+        &ExecuteMsg::ProposeAction {
+            action: GuardianAction::UpdateGuardians {
+                guardians: vec![Addr::unchecked("guardian3")],
+                threshold: 2,
+                approval_window: 100,
+            },
+        },
+        &[],
+    );
+    app.assert_fail(res);
+
+    app.execute(
+        Addr::unchecked("guardian1"),
+        oracle_addr.clone(),
+        &ExecuteMsg::ProposeAction {
+            action: GuardianAction::UpdateGuardians {
+                guardians: vec![Addr::unchecked("guardian2"), Addr::unchecked("guardian3")],
+                threshold: 1,
+                approval_window: 50,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked("guardian2"),
+        oracle_addr.clone(),
+        &ExecuteMsg::ApproveAction { action_id: 1 },
+        &[],
+    )
+    .unwrap();
+
+    let mut res: GuardiansResponse = app
+        .query(
+            oracle_addr.clone(),
+            &QueryMsg::Contract(OracleContractQuery::Guardians {}),
+        )
+        .unwrap();
+    // stored in canonical address order
+    res.guardians.sort();
+    assert_eq!(
+        res,
+        GuardiansResponse {
+            guardians: vec![Addr::unchecked("guardian2"), Addr::unchecked("guardian3")],
+            threshold: 1,
+            approval_window: 50,
+        }
+    );
+
+    // guardian1 left the set
+    let res = app.execute(
+        Addr::unchecked("guardian1"),
+        oracle_addr.clone(),
+        &ExecuteMsg::ProposeAction {
+            action: GuardianAction::UpdateAdmin {
+                admin: Addr::unchecked("new_admin"),
+            },
+        },
+        &[],
+    );
+    app.assert_fail(res);
+}
@@ -718,7 +718,7 @@ pub fn assert_max_spread(
     Ok(())
 }
 
-fn assert_slippage_tolerance(
+pub fn assert_slippage_tolerance(
     slippage_tolerance: &Option<Decimal>,
     deposits: &[Uint128; 2],
     pools: &[Asset; 2],
@@ -729,17 +729,29 @@ fn assert_slippage_tolerance(
             return Err(ContractError::InvalidExceedOneSlippage {});
         }
 
-        let one_minus_slippage_tolerance = Decimal256::one() - slippage_tolerance;
         let deposits: [Uint256; 2] = [deposits[0].into(), deposits[1].into()];
         let pools: [Uint256; 2] = [pools[0].amount.into(), pools[1].amount.into()];
 
-        // Ensure each prices are not dropped as much as slippage tolerance rate
-        if Decimal256::from_ratio(deposits[0], deposits[1]) * one_minus_slippage_tolerance
-            > Decimal256::from_ratio(pools[0], pools[1])
-            || Decimal256::from_ratio(deposits[1], deposits[0]) * one_minus_slippage_tolerance
-                > Decimal256::from_ratio(pools[1], pools[0])
-        {
-            return Err(ContractError::MaxSlippageAssertion {});
+        // compare deposits[0] / deposits[1] with pools[0] / pools[1] by cross multiplication,
+        // swapping the assets only swaps the two sides so the outcome does not depend on the order
+        let deposit_side = deposits[0] * pools[1];
+        let pool_side = deposits[1] * pools[0];
+        let (min_side, max_side) = if deposit_side < pool_side {
+            (deposit_side, pool_side)
+        } else {
+            (pool_side, deposit_side)
+        };
+
+        // the deviation is the larger of both one-sided deviations: 1 - min(d / p, p / d). It
+        // exceeds the tolerance when min_side < max_side * (1 - tolerance), which stays in
+        // integers, the sides of large pools are too big for a decimal ratio
+        if min_side < max_side * (Decimal256::one() - slippage_tolerance) {
+            return Err(ContractError::MaxSlippageAssertion {
+                deposit_ratio: Decimal256::checked_from_ratio(deposits[0], deposits[1])
+                    .unwrap_or(Decimal256::MAX),
+                pool_ratio: Decimal256::checked_from_ratio(pools[0], pools[1])
+                    .unwrap_or(Decimal256::MAX),
+            });
         }
     }
 
@@ -1,10 +1,17 @@
 use cosmwasm_std::testing::MOCK_CONTRACT_ADDR;
-use cosmwasm_std::{attr, to_binary, Addr, Coin, Decimal, Uint128};
+use cosmwasm_std::{attr, to_binary, Addr, Coin, Decimal, Decimal256, Uint128};
 use cw20::Cw20ReceiveMsg;
 use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
 use oraiswap::create_entry_points_testing;
-use oraiswap::pair::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, PairResponse};
+use oraiswap::error::ContractError;
+use oraiswap::pair::{
+    compute_offer_amount, compute_swap, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PairResponse,
+};
+use oraiswap::test_vectors::{REVERSE_SWAP_VECTORS, SWAP_VECTORS};
 use oraiswap::testing::{MockApp, ATOM_DENOM};
+use std::str::FromStr;
+
+use crate::contract::assert_slippage_tolerance;
 
 #[test]
 fn provide_liquidity_both_native() {
@@ -395,7 +402,7 @@ fn withdraw_liquidity() {
         log_refund_assets,
         &attr(
             "refund_assets",
-            format!("100{}, 100{}", ORAI_DENOM, liquidity_addr)
+            format!("100{}, 100 {} (cw20)", ORAI_DENOM, liquidity_addr)
         )
     );
 }
@@ -619,3 +626,128 @@ fn test_pool_whitelist_for_trader() {
     )
     .unwrap();
 }
+
+#[test]
+fn test_slippage_tolerance_symmetric() {
+    let pool = |amounts: [u128; 2]| -> [Asset; 2] {
+        [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: amounts[0].into(),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ATOM_DENOM.to_string(),
+                },
+                amount: amounts[1].into(),
+            },
+        ]
+    };
+
+    // deposit ratio 1.2 against pool ratio 1, the deviation is 1 - 1 / 1.2 ~= 16.7%
+    let deposits = [Uint128::from(120u128), Uint128::from(100u128)];
+    assert_eq!(
+        assert_slippage_tolerance(&Some(Decimal::percent(10)), &deposits, &pool([1000, 1000])),
+        Err(ContractError::MaxSlippageAssertion {
+            deposit_ratio: Decimal256::percent(120),
+            pool_ratio: Decimal256::one(),
+        })
+    );
+    assert!(
+        assert_slippage_tolerance(&Some(Decimal::percent(17)), &deposits, &pool([1000, 1000]))
+            .is_ok()
+    );
+
+    // same deviation in the other direction gives the same outcome
+    let deposits = [Uint128::from(100u128), Uint128::from(120u128)];
+    assert!(
+        assert_slippage_tolerance(&Some(Decimal::percent(10)), &deposits, &pool([1000, 1000]))
+            .is_err()
+    );
+    assert!(
+        assert_slippage_tolerance(&Some(Decimal::percent(17)), &deposits, &pool([1000, 1000]))
+            .is_ok()
+    );
+
+    // the cross products of full pools do not fit a decimal ratio and are still compared
+    let max = Uint128::MAX;
+    assert!(assert_slippage_tolerance(
+        &Some(Decimal::percent(1)),
+        &[max, max],
+        &pool([max.u128(); 2])
+    )
+    .is_ok());
+    assert!(assert_slippage_tolerance(
+        &Some(Decimal::percent(10)),
+        &[max, max / Uint128::from(2u128)],
+        &pool([max.u128(); 2])
+    )
+    .is_err());
+
+    // swapping the order of the assets never changes the accept / reject outcome
+    let mut seed = 0x2545f4914f6cdd1du64;
+    let mut next = |max: u64| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed % max + 1
+    };
+    for _ in 0..1000 {
+        let deposits = [next(1_000_000) as u128, next(1_000_000) as u128];
+        let pools = [next(1_000_000_000) as u128, next(1_000_000_000) as u128];
+        let tolerance = Some(Decimal::permille(next(1000)));
+
+        let res = assert_slippage_tolerance(
+            &tolerance,
+            &[deposits[0].into(), deposits[1].into()],
+            &pool(pools),
+        );
+        let swapped_res = assert_slippage_tolerance(
+            &tolerance,
+            &[deposits[1].into(), deposits[0].into()],
+            &pool([pools[1], pools[0]]),
+        );
+        assert_eq!(res.is_ok(), swapped_res.is_ok());
+    }
+}
+
+#[test]
+fn swap_math_vectors() {
+    for v in SWAP_VECTORS {
+        let commission_rate = Decimal256::from_str(v.commission_rate).unwrap();
+        let (return_amount, spread_amount, commission_amount) = compute_swap(
+            v.offer_pool.into(),
+            v.ask_pool.into(),
+            v.offer_amount.into(),
+            commission_rate,
+        )
+        .unwrap();
+        assert_eq!(return_amount, Uint128::from(v.return_amount));
+        assert_eq!(spread_amount, Uint128::from(v.spread_amount));
+        assert_eq!(commission_amount, Uint128::from(v.commission_amount));
+
+        // the offer priced at the spot price is split into return, spread and commission
+        let spot_price = Decimal::from_ratio(v.spot_price.0, v.spot_price.1);
+        assert_eq!(spot_price, Decimal::from_ratio(v.ask_pool, v.offer_pool));
+        assert_eq!(
+            Uint128::from(v.offer_amount) * spot_price,
+            return_amount + spread_amount + commission_amount
+        );
+    }
+
+    for v in REVERSE_SWAP_VECTORS {
+        let commission_rate = Decimal256::from_str(v.commission_rate).unwrap();
+        let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+            v.offer_pool.into(),
+            v.ask_pool.into(),
+            v.ask_amount.into(),
+            commission_rate,
+        )
+        .unwrap();
+        assert_eq!(offer_amount, Uint128::from(v.offer_amount));
+        assert_eq!(spread_amount, Uint128::from(v.spread_amount));
+        assert_eq!(commission_amount, Uint128::from(v.commission_amount));
+    }
+}
@@ -5,7 +5,8 @@ use cosmwasm_std::{
 };
 
 use crate::state::{
-    read_config, read_last_distributed, store_config, store_last_distributed, Config,
+    read_config, read_last_distributed, read_schedule, store_config, store_last_distributed,
+    store_schedule, Config,
 };
 
 use oraiswap::staking::{
@@ -14,8 +15,8 @@ use oraiswap::staking::{
 use oraiswap::staking::{QueryMsg as StakingQueryMsg, RewardMsg};
 
 use oraiswap::rewarder::{
-    ConfigResponse, DistributionInfoResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
-    RewardAmountPerSecondResponse,
+    compute_schedule_emission, ConfigResponse, DistributionInfoResponse, ExecuteMsg,
+    InstantiateMsg, MigrateMsg, QueryMsg, RewardAmountPerSecondResponse, ScheduleResponse,
 };
 
 // 600 seconds default
@@ -51,6 +52,10 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             staking_contract,
             distribution_interval,
         } => update_config(deps, info, owner, staking_contract, distribution_interval),
+        ExecuteMsg::UpdateSchedule {
+            staking_token,
+            schedule,
+        } => update_schedule(deps, env, info, staking_token, schedule),
 
         ExecuteMsg::Distribute { staking_tokens } => distribute(deps, env, staking_tokens),
     }
@@ -90,6 +95,70 @@ pub fn update_config(
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+/// UpdateSchedule
+/// Owner can replace the emission schedule of a pool, rewards accrued under the old schedule are distributed first
+pub fn update_schedule(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_token: Addr,
+    schedule: Vec<(u64, u64, Uint128)>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    for (start_time, end_time, _) in schedule.iter() {
+        if start_time >= end_time {
+            return Err(StdError::generic_err(
+                "schedule start time must be before end time",
+            ));
+        }
+    }
+    // the emission adds the segments up, so each one has to start after the previous one ended
+    if schedule.windows(2).any(|pair| pair[0].1 > pair[1].0) {
+        return Err(StdError::generic_err(
+            "schedule segments must be sorted and must not overlap",
+        ));
+    }
+
+    let staking_contract = deps.api.addr_humanize(&config.staking_contract)?;
+    let now = env.block.time.seconds();
+    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?.to_vec();
+
+    // settle accrual of the current schedule before replacing it
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let Ok(last_distributed) = read_last_distributed(deps.storage, &asset_key) {
+        let distribution_amount = _compute_distribution_amount(
+            deps.as_ref(),
+            staking_contract.clone(),
+            staking_token.clone(),
+            last_distributed,
+            now,
+        )?;
+        if !distribution_amount.is_zero() {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: staking_contract.to_string(),
+                msg: to_binary(&StakingExecuteMsg::DepositReward {
+                    rewards: vec![RewardMsg {
+                        staking_token: staking_token.clone(),
+                        total_accumulation_amount: distribution_amount,
+                    }],
+                })?,
+                funds: vec![],
+            }));
+        }
+    }
+    store_last_distributed(deps.storage, &asset_key, now)?;
+    store_schedule(deps.storage, &asset_key, &schedule)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes([
+        ("action", "update_schedule"),
+        ("staking_token", staking_token.as_str()),
+    ]))
+}
+
 /// Distribute
 /// Anyone can execute distribute operation to distribute
 pub fn distribute(deps: DepsMut, env: Env, staking_tokens: Vec<Addr>) -> StdResult<Response> {
@@ -112,20 +181,19 @@ pub fn distribute(deps: DepsMut, env: Env, staking_tokens: Vec<Addr>) -> StdResu
         // store last distributed
         store_last_distributed(deps.storage, &asset_key, now)?;
 
-        // reward amount per second for a pool
-        let reward_amount = _read_pool_reward_per_sec(
-            &deps.querier,
+        // get total reward amount for a pool
+        let distribution_amount = _compute_distribution_amount(
+            deps.as_ref(),
             staking_contract.clone(),
             staking_token.clone(),
+            last_distributed,
+            now,
         )?;
         // no need to create a new distribute msg if the reward amount is 0
-        if reward_amount.is_zero() {
+        if distribution_amount.is_zero() {
             continue;
         }
 
-        // get total reward amount for a pool
-        let distribution_amount = Uint128::from(reward_amount.u128() * (last_time_elapsed as u128));
-
         // we will accumulate all rewards of a pool into a reward info pool. After that, we will re-calculate the percent of each reward token later in withdraw reward
         rewards.push(RewardMsg {
             staking_token,
@@ -152,6 +220,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::RewardAmountPerSec { staking_token } => {
             to_binary(&query_reward_amount_per_sec(deps, staking_token)?)
         }
+        QueryMsg::Schedule { staking_token } => to_binary(&query_schedule(deps, staking_token)?),
     }
 }
 
@@ -191,6 +260,34 @@ pub fn query_reward_amount_per_sec(
     Ok(RewardAmountPerSecondResponse { reward_amount })
 }
 
+pub fn query_schedule(deps: Deps, staking_token: Addr) -> StdResult<ScheduleResponse> {
+    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?.to_vec();
+    let schedule = read_schedule(deps.storage, &asset_key)?.unwrap_or_default();
+
+    Ok(ScheduleResponse { schedule })
+}
+
+// integrate the schedule across the segments spanned since last distributed,
+// pools without a schedule keep the flat rate from the staking contract
+fn _compute_distribution_amount(
+    deps: Deps,
+    staking_contract: Addr,
+    staking_token: Addr,
+    last_distributed: u64,
+    now: u64,
+) -> StdResult<Uint128> {
+    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?.to_vec();
+    if let Some(schedule) = read_schedule(deps.storage, &asset_key)? {
+        return compute_schedule_emission(&schedule, last_distributed, now);
+    }
+
+    // reward amount per second for a pool
+    let reward_amount = _read_pool_reward_per_sec(&deps.querier, staking_contract, staking_token)?;
+    Ok(Uint128::from(
+        reward_amount.u128() * ((now - last_distributed) as u128),
+    ))
+}
+
 fn _read_pool_reward_per_sec(
     querier: &QuerierWrapper,
     staking_contract: Addr,
@@ -1,9 +1,10 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
+use cosmwasm_std::{CanonicalAddr, StdResult, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_LAST_DISTRIBUTED: &[u8] = b"last_distributed";
+static KEY_SCHEDULE: &[u8] = b"schedule";
 
 #[cw_serde]
 pub struct Config {
@@ -32,3 +33,18 @@ pub fn store_last_distributed(
 pub fn read_last_distributed(storage: &dyn Storage, asset_key: &[u8]) -> StdResult<u64> {
     ReadonlyBucket::new(storage, KEY_LAST_DISTRIBUTED).load(asset_key)
 }
+
+pub fn store_schedule(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    schedule: &Vec<(u64, u64, Uint128)>,
+) -> StdResult<()> {
+    Bucket::new(storage, KEY_SCHEDULE).save(asset_key, schedule)
+}
+
+pub fn read_schedule(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+) -> StdResult<Option<Vec<(u64, u64, Uint128)>>> {
+    ReadonlyBucket::new(storage, KEY_SCHEDULE).may_load(asset_key)
+}
@@ -1,9 +1,13 @@
-use crate::contract::{instantiate, query_config};
+use crate::contract::{execute, instantiate, query_config};
 use cosmwasm_std::{
     testing::{mock_dependencies, mock_env, mock_info},
-    Addr,
+    to_binary, Addr, StdError, SubMsg, Timestamp, Uint128, WasmMsg,
 };
-use oraiswap::rewarder::{ConfigResponse, InstantiateMsg};
+use oraiswap::rewarder::{
+    compute_schedule_emission, genesis_halving_schedule, ConfigResponse, ExecuteMsg,
+    InstantiateMsg, HALVING_PERIOD,
+};
+use oraiswap::staking::{ExecuteMsg as StakingExecuteMsg, RewardMsg};
 
 #[test]
 fn proper_initialization() {
@@ -29,3 +33,164 @@ fn proper_initialization() {
         }
     );
 }
+
+#[test]
+fn test_halving_schedule_distribution() {
+    let mut deps = mock_dependencies();
+
+    let msg = InstantiateMsg {
+        staking_contract: Addr::unchecked("staking"),
+        distribution_interval: Some(100),
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let mut env = mock_env();
+    let start = env.block.time.seconds();
+    // 100 per second for the first 90 days, then halving
+    let schedule = genesis_halving_schedule(start, Uint128::from(100u128), 3);
+    assert_eq!(
+        schedule,
+        vec![
+            (start, start + HALVING_PERIOD, Uint128::from(100u128)),
+            (
+                start + HALVING_PERIOD,
+                start + 2 * HALVING_PERIOD,
+                Uint128::from(50u128)
+            ),
+            (
+                start + 2 * HALVING_PERIOD,
+                start + 3 * HALVING_PERIOD,
+                Uint128::from(25u128)
+            ),
+        ]
+    );
+
+    // only owner can update the schedule
+    let msg = ExecuteMsg::UpdateSchedule {
+        staking_token: Addr::unchecked("staking_token"),
+        schedule: schedule.clone(),
+    };
+    let _err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+
+    // set schedule 100 seconds before the first halving, nothing to settle yet
+    env.block.time = Timestamp::from_seconds(start + HALVING_PERIOD - 100);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    // settlement window spans the boundary: 100s at 100 + 200s at 50
+    env.block.time = env.block.time.plus_seconds(300);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::Distribute {
+            staking_tokens: vec![Addr::unchecked("staking_token")],
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&StakingExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking_token"),
+                    total_accumulation_amount: Uint128::from(20000u128),
+                }],
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    // changing the schedule mid-flight settles the accrual of the old one first
+    env.block.time = env.block.time.plus_seconds(10);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateSchedule {
+            staking_token: Addr::unchecked("staking_token"),
+            schedule: vec![],
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&StakingExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking_token"),
+                    total_accumulation_amount: Uint128::from(500u128),
+                }],
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+}
+
+#[test]
+fn test_schedule_validation() {
+    let mut deps = mock_dependencies();
+
+    let msg = InstantiateMsg {
+        staking_contract: Addr::unchecked("staking"),
+        distribution_interval: Some(100),
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let start = mock_env().block.time.seconds();
+    let update_schedule = |schedule: Vec<(u64, u64, Uint128)>| ExecuteMsg::UpdateSchedule {
+        staking_token: Addr::unchecked("staking_token"),
+        schedule,
+    };
+
+    // overlapping or unsorted segments would emit twice for the same seconds
+    for schedule in [
+        vec![
+            (start, start + 100, Uint128::from(100u128)),
+            (start + 50, start + 150, Uint128::from(50u128)),
+        ],
+        vec![
+            (start + 100, start + 200, Uint128::from(50u128)),
+            (start, start + 100, Uint128::from(100u128)),
+        ],
+    ] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            update_schedule(schedule),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("schedule segments must be sorted and must not overlap")
+        );
+    }
+
+    // back to back segments are fine
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        update_schedule(vec![
+            (start, start + 100, Uint128::from(100u128)),
+            (start + 100, start + 200, Uint128::from(50u128)),
+        ]),
+    )
+    .unwrap();
+
+    // an emission past Uint128 is an error, not a wrapped amount
+    let schedule = vec![(start, start + 2, Uint128::MAX)];
+    assert!(compute_schedule_emission(&schedule, start, start + 1).is_ok());
+    assert!(compute_schedule_emission(&schedule, start, start + 2).is_err());
+}
@@ -7,8 +7,8 @@ use cosmwasm_std::{
 };
 use oraiswap::error::ContractError;
 
-use crate::operations::{execute_swap_operation, execute_swap_operations};
-use crate::state::{Config, CONFIG};
+use crate::operations::{assert_operations, execute_swap_operation, execute_swap_operations};
+use crate::state::{Config, ADMIN, CONFIG, ROUTES};
 
 use cw20::Cw20ReceiveMsg;
 use oraiswap::asset::{Asset, AssetInfo, PairInfo};
@@ -16,7 +16,7 @@ use oraiswap::oracle::OracleContract;
 use oraiswap::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
 use oraiswap::querier::{query_pair_config, query_pair_info};
 use oraiswap::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, RouteResponse,
     SimulateSwapOperationsResponse, SwapOperation,
 };
 
@@ -24,7 +24,7 @@ use oraiswap::router::{
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
     CONFIG.save(
@@ -34,12 +34,19 @@ pub fn instantiate(
             factory_addr_v2: deps.api.addr_canonicalize(msg.factory_addr_v2.as_str())?,
         },
     )?;
+    ADMIN.save(
+        deps.storage,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+    )?;
 
     Ok(Response::default())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    if let Some(admin) = msg.admin {
+        ADMIN.save(deps.storage, &deps.api.addr_canonicalize(&admin)?)?;
+    }
     Ok(Response::default())
 }
 
@@ -75,13 +82,41 @@ pub fn execute(
             minimum_receive,
             receiver.into(),
         ),
+        ExecuteMsg::SetRoute {
+            offer_asset_info,
+            ask_asset_info,
+            operations,
+        } => set_route(deps, info, offer_asset_info, ask_asset_info, operations),
+        ExecuteMsg::RemoveRoute {
+            offer_asset_info,
+            ask_asset_info,
+        } => remove_route(deps, info, offer_asset_info, ask_asset_info),
+        ExecuteMsg::ExecuteSwap {
+            offer_asset_info,
+            ask_asset_info,
+            amount,
+            minimum_receive,
+            to,
+        } => {
+            if !offer_asset_info.is_native_token() {
+                return Err(ContractError::MustProvideNativeToken {});
+            }
+            Asset {
+                info: offer_asset_info.clone(),
+                amount,
+            }
+            .assert_sent_native_token_balance(&info)?;
+
+            let operations = load_route(deps.as_ref(), &offer_asset_info, &ask_asset_info)?;
+            execute_swap_operations(deps, env, info.sender, operations, minimum_receive, to)
+        }
     }
 }
 
 pub fn receive_cw20(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     let sender = deps.api.addr_validate(&cw20_msg.sender)?;
@@ -96,7 +131,124 @@ pub fn receive_cw20(
             let receiver = to.map_or(None, |addr| deps.api.addr_validate(addr.as_str()).ok());
             execute_swap_operations(deps, env, sender, operations, minimum_receive, receiver)
         }
+        Cw20HookMsg::ExecuteSwap {
+            ask_asset_info,
+            minimum_receive,
+            to,
+        } => {
+            let offer_asset_info = AssetInfo::Token {
+                contract_addr: info.sender,
+            };
+            let operations = load_route(deps.as_ref(), &offer_asset_info, &ask_asset_info)?;
+            let receiver = to.map_or(None, |addr| deps.api.addr_validate(addr.as_str()).ok());
+            execute_swap_operations(deps, env, sender, operations, minimum_receive, receiver)
+        }
+    }
+}
+
+fn assert_admin(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    let admin = ADMIN.may_load(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(sender.as_str())?;
+
+    if admin != Some(sender_raw) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
+fn route_key(
+    deps: Deps,
+    offer_asset_info: &AssetInfo,
+    ask_asset_info: &AssetInfo,
+) -> StdResult<(Vec<u8>, Vec<u8>)> {
+    Ok((
+        offer_asset_info.to_vec(deps.api)?,
+        ask_asset_info.to_vec(deps.api)?,
+    ))
+}
+
+fn load_route(
+    deps: Deps,
+    offer_asset_info: &AssetInfo,
+    ask_asset_info: &AssetInfo,
+) -> Result<Vec<SwapOperation>, ContractError> {
+    let (offer_key, ask_key) = route_key(deps, offer_asset_info, ask_asset_info)?;
+    ROUTES
+        .may_load(deps.storage, (&offer_key, &ask_key))?
+        .ok_or_else(|| ContractError::RouteNotFound {
+            offer: offer_asset_info.to_string(),
+            ask: ask_asset_info.to_string(),
+        })
+}
+
+pub fn set_route(
+    deps: DepsMut,
+    info: MessageInfo,
+    offer_asset_info: AssetInfo,
+    ask_asset_info: AssetInfo,
+    operations: Vec<SwapOperation>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    if operations.is_empty() {
+        return Err(ContractError::NoSwapOperation {});
+    }
+    assert_operations(&operations)?;
+
+    // each hop must start from the asset the previous hop returns
+    let mut current_asset_info = offer_asset_info.clone();
+    for operation in operations.iter() {
+        match operation {
+            SwapOperation::OraiSwap {
+                offer_asset_info,
+                ask_asset_info,
+            } => {
+                if !offer_asset_info.eq(&current_asset_info) {
+                    return Err(ContractError::InvalidRoute {
+                        offer: offer_asset_info.to_string(),
+                        ask: ask_asset_info.to_string(),
+                    });
+                }
+                current_asset_info = ask_asset_info.clone();
+            }
+        }
     }
+    if !current_asset_info.eq(&ask_asset_info) {
+        return Err(ContractError::InvalidRoute {
+            offer: offer_asset_info.to_string(),
+            ask: ask_asset_info.to_string(),
+        });
+    }
+
+    let (offer_key, ask_key) = route_key(deps.as_ref(), &offer_asset_info, &ask_asset_info)?;
+    ROUTES.save(deps.storage, (&offer_key, &ask_key), &operations)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "set_route"),
+        ("offer_asset_info", &offer_asset_info.to_string()),
+        ("ask_asset_info", &ask_asset_info.to_string()),
+    ]))
+}
+
+pub fn remove_route(
+    deps: DepsMut,
+    info: MessageInfo,
+    offer_asset_info: AssetInfo,
+    ask_asset_info: AssetInfo,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    // make sure the route exists before removing it
+    load_route(deps.as_ref(), &offer_asset_info, &ask_asset_info)?;
+    let (offer_key, ask_key) = route_key(deps.as_ref(), &offer_asset_info, &ask_asset_info)?;
+    ROUTES.remove(deps.storage, (&offer_key, &ask_key));
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "remove_route"),
+        ("offer_asset_info", &offer_asset_info.to_string()),
+        ("ask_asset_info", &ask_asset_info.to_string()),
+    ]))
 }
 
 fn assert_minium_receive(
@@ -127,9 +279,24 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             offer_amount,
             operations,
         } => to_binary(&simulate_swap_operations(deps, offer_amount, operations)?),
+        QueryMsg::Route {
+            offer_asset_info,
+            ask_asset_info,
+        } => to_binary(&query_route(deps, offer_asset_info, ask_asset_info)?),
     }
 }
 
+pub fn query_route(
+    deps: Deps,
+    offer_asset_info: AssetInfo,
+    ask_asset_info: AssetInfo,
+) -> StdResult<RouteResponse> {
+    let operations = load_route(deps, &offer_asset_info, &ask_asset_info)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    Ok(RouteResponse { operations })
+}
+
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let state = CONFIG.load(deps.storage)?;
     let resp = ConfigResponse {
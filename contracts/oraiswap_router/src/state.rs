@@ -1,7 +1,8 @@
 use cosmwasm_schema::cw_serde;
 
 use cosmwasm_std::CanonicalAddr;
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
+use oraiswap::router::SwapOperation;
 
 #[cw_serde]
 pub struct Config {
@@ -12,6 +13,11 @@ pub struct Config {
 // put the length bytes at the first for compatibility with legacy singleton store
 pub const CONFIG: Item<Config> = Item::new("\u{0}\u{6}config");
 
+pub const ADMIN: Item<CanonicalAddr> = Item::new("admin");
+
+// canonical routes registered by admin, keyed by (offer asset, ask asset)
+pub const ROUTES: Map<(&[u8], &[u8]), Vec<SwapOperation>> = Map::new("routes");
+
 #[cfg(test)]
 mod test {
     use super::*;
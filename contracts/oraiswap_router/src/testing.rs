@@ -2,7 +2,8 @@ use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
 use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
 use oraiswap::create_entry_points_testing;
 use oraiswap::router::{
-    ExecuteMsg, InstantiateMsg, QueryMsg, SimulateSwapOperationsResponse, SwapOperation,
+    ExecuteMsg, InstantiateMsg, QueryMsg, RouteResponse, SimulateSwapOperationsResponse,
+    SwapOperation,
 };
 
 use oraiswap::testing::{MockApp, ATOM_DENOM};
@@ -443,3 +444,228 @@ fn execute_swap_operations() {
     )
     .unwrap();
 }
+
+#[test]
+fn execute_swap_with_registered_route() {
+    let mut app = MockApp::new(&[(
+        &"addr0000".to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )]);
+
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(
+            create_entry_points_testing!(oraiswap_factory)
+                .with_reply(oraiswap_factory::contract::reply),
+        ),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair).with_reply(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let orai = AssetInfo::NativeToken {
+        denom: ORAI_DENOM.to_string(),
+    };
+    let atom = AssetInfo::NativeToken {
+        denom: ATOM_DENOM.to_string(),
+    };
+
+    let pair_addr = app.create_pair([orai.clone(), atom.clone()]).unwrap();
+
+    app.execute(
+        Addr::unchecked("addr0000"),
+        pair_addr.clone(),
+        &oraiswap::pair::ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: atom.clone(),
+                    amount: Uint128::from(100000u128),
+                },
+                Asset {
+                    info: orai.clone(),
+                    amount: Uint128::from(100000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(100000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(100000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+    let router_addr = app
+        .instantiate(
+            code_id,
+            Addr::unchecked("admin"),
+            &InstantiateMsg {
+                factory_addr: app.factory_addr.clone(),
+                factory_addr_v2: Addr::unchecked("addr0000_v2"),
+            },
+            &[],
+            "router",
+        )
+        .unwrap();
+
+    let swap_msg = ExecuteMsg::ExecuteSwap {
+        offer_asset_info: orai.clone(),
+        ask_asset_info: atom.clone(),
+        amount: Uint128::from(1000u128),
+        minimum_receive: None,
+        to: None,
+    };
+    let funds = [Coin {
+        denom: ORAI_DENOM.to_string(),
+        amount: Uint128::from(1000u128),
+    }];
+
+    // no route registered yet, caller must provide operations explicitly
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        router_addr.clone(),
+        &swap_msg,
+        &funds,
+    );
+    app.assert_fail(res);
+    let res: Result<RouteResponse, _> = app.query(
+        router_addr.clone(),
+        &QueryMsg::Route {
+            offer_asset_info: orai.clone(),
+            ask_asset_info: atom.clone(),
+        },
+    );
+    assert!(res.is_err());
+
+    let operations = vec![SwapOperation::OraiSwap {
+        offer_asset_info: orai.clone(),
+        ask_asset_info: atom.clone(),
+    }];
+    let set_route_msg = ExecuteMsg::SetRoute {
+        offer_asset_info: orai.clone(),
+        ask_asset_info: atom.clone(),
+        operations: operations.clone(),
+    };
+
+    // only admin can register routes
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        router_addr.clone(),
+        &set_route_msg,
+        &[],
+    );
+    app.assert_fail(res);
+
+    // route must end with the ask asset
+    let res = app.execute(
+        Addr::unchecked("admin"),
+        router_addr.clone(),
+        &ExecuteMsg::SetRoute {
+            offer_asset_info: atom.clone(),
+            ask_asset_info: orai.clone(),
+            operations: operations.clone(),
+        },
+        &[],
+    );
+    app.assert_fail(res);
+
+    app.execute(
+        Addr::unchecked("admin"),
+        router_addr.clone(),
+        &set_route_msg,
+        &[],
+    )
+    .unwrap();
+
+    let res: RouteResponse = app
+        .query(
+            router_addr.clone(),
+            &QueryMsg::Route {
+                offer_asset_info: orai.clone(),
+                ask_asset_info: atom.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.operations, operations);
+
+    // amount must match the sent funds
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        router_addr.clone(),
+        &ExecuteMsg::ExecuteSwap {
+            offer_asset_info: orai.clone(),
+            ask_asset_info: atom.clone(),
+            amount: Uint128::from(999u128),
+            minimum_receive: None,
+            to: None,
+        },
+        &funds,
+    );
+    app.assert_fail(res);
+
+    let prev_balance = app
+        .query_balance(Addr::unchecked("addr0000"), ATOM_DENOM.to_string())
+        .unwrap();
+    app.execute(
+        Addr::unchecked("addr0000"),
+        router_addr.clone(),
+        &swap_msg,
+        &funds,
+    )
+    .unwrap();
+    let balance = app
+        .query_balance(Addr::unchecked("addr0000"), ATOM_DENOM.to_string())
+        .unwrap();
+    assert!(balance > prev_balance);
+
+    // remove route, swap falls back to explicit operations
+    app.execute(
+        Addr::unchecked("admin"),
+        router_addr.clone(),
+        &ExecuteMsg::RemoveRoute {
+            offer_asset_info: orai.clone(),
+            ask_asset_info: atom.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        router_addr.clone(),
+        &swap_msg,
+        &funds,
+    );
+    app.assert_fail(res);
+
+    app.execute(
+        Addr::unchecked("addr0000"),
+        router_addr.clone(),
+        &ExecuteMsg::ExecuteSwapOperations {
+            operations,
+            minimum_receive: None,
+            to: None,
+        },
+        &funds,
+    )
+    .unwrap();
+}
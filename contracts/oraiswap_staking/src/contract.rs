@@ -3,40 +3,114 @@ use cosmwasm_std::entry_point;
 use oraiswap::error::ContractError;
 
 use crate::legacy::v1::{
-    old_read_all_is_migrated_key_parsed, old_read_all_pool_infos, old_read_all_rewards_per_sec,
-    old_rewards_read_all, old_stakers_read,
+    old_read_all_is_migrated_key_parsed, old_read_all_pool_info_keys, old_read_all_pool_infos,
+    old_read_all_rewards_per_sec, old_rewards_read_all, old_stakers_read,
 };
+use crate::legacy::v2::migrate_store_step;
 use crate::rewards::{
-    deposit_reward, process_reward_assets, query_all_reward_infos, query_reward_info,
-    withdraw_reward, withdraw_reward_others,
+    accrue_distribution, close_epoch, deposit_native_reward, deposit_proxy_reward, deposit_reward,
+    deposit_reward_by_weights, deposit_reward_weights, finalize_pool_epoch, index_to_decimal,
+    owner_deposit_reward, process_reward_assets, query_all_reward_infos, query_batch_reward_info,
+    query_distribution_info, query_distribution_schedule, query_operator, query_pool_epoch,
+    query_pool_reward_rate, query_reward_index_history, query_reward_info, query_simulate_bond,
+    query_simulate_withdraw, query_staker_info, query_staker_reward_infos, read_asset_decimals,
+    reallocate_orphaned_rewards, scheduled_amount, set_operator, sweep_abandoned_rewards,
+    update_boost, withdraw_and_swap, withdraw_reward, withdraw_reward_others,
+};
+use crate::staking::{
+    auto_stake, auto_stake_hook, bond, bond_native, claim, compound, compound_hook, instant_unbond,
+    migrate_bond, migrate_bond_hook, migrate_received_bond, migrate_received_bond_hook,
+    prune_reward_infos, query_bond_amount_at, query_referral_stats, query_stakers,
+    query_stale_stakers, query_unbonding, query_unbonding_claims, record_referral, transfer_bond,
+    transfer_staking_token, unbond,
 };
-use crate::staking::{auto_stake, auto_stake_hook, bond, unbond};
 use crate::state::{
-    read_all_pool_infos, read_config, read_finish_migrate_store_status, read_pool_info,
-    read_rewards_per_sec, remove_pool_info, stakers_read, store_config,
-    store_finish_migrate_store_status, store_pool_info, store_rewards_per_sec, Config,
-    MigrationParams, PoolInfo,
+    append_pool_repair, is_native_staking_token, is_on_staker_list, may_bond,
+    move_reward_index_history, move_staker_lists, pool_repairs_read, read_all_pool_infos,
+    read_config, read_deprecated_staking_token, read_finish_migrate_store_status, read_is_migrated,
+    read_migration_cursor, read_ownership_proposal, read_pool_deposits, read_pool_epoch,
+    read_pool_info, read_pool_infos, read_range_prefixed, read_rewards_per_sec,
+    read_staker_records, read_state, read_total_unbonding, remove_deprecated_staking_token,
+    remove_ownership_proposal, remove_pool_alias, remove_pool_deposits, remove_pool_epoch,
+    remove_pool_info, remove_rewards_per_sec, resolve_pool_key, rewards_read, staker_list_store,
+    stakers_read, staking_token_addr, staking_token_key, store_config,
+    store_deprecated_staking_token, store_finish_migrate_store_status, store_migration_cursor,
+    store_native_staking_token, store_ownership_proposal, store_pool_alias, store_pool_deposits,
+    store_pool_epoch, store_pool_info, store_rewards_per_sec, store_state, update_state,
+    AssetDecimalsRaw, Config, EpochInfo, MigrationCursor, MigrationParams, OwnershipProposal,
+    PoolInfo, PoolRepair, ProxyReward, RewardInfo, StakingTokenBucket, State,
+    PREFIX_POOL_BLACKLIST, PREFIX_POOL_INFO, PREFIX_POOL_WHITELIST, PREFIX_REWARD,
+    PREFIX_UNBONDING,
 };
 
+use std::collections::BTreeMap;
+
+use cosmwasm_schema::serde::de::IgnoredAny;
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Api, Binary, CanonicalAddr, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128,
+    attr, from_binary, to_binary, Addr, Api, Binary, CanonicalAddr, Decimal, Decimal256, Deps,
+    DepsMut, Env, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsgResult,
+    Uint128, WasmMsg,
+};
+use oraiswap::asset::{Asset, AssetInfo, AssetInfoRaw, AssetRaw, ORAI_DENOM};
+use oraiswap::querier::{
+    calc_range_start, page_limit, parse_order, query_pair_info, query_pair_info_from_pair,
+    query_token_balance, query_token_info,
 };
-use oraiswap::asset::{Asset, AssetRaw, ORAI_DENOM};
 use oraiswap::staking::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, OldStoreType,
-    PoolInfoResponse, QueryMsg, QueryPoolInfoResponse, RewardsPerSecResponse,
+    CanBondResponse, ConfigResponse, ContractInfoResponse, Cw20HookMsg, DeprecatedBondsResponse,
+    ExecuteMsg, ExportRecord, ExportSegment, ExportStateResponse, InstantiateMsg, LockTier,
+    MigrateMsg, MigrationProgressResponse, OldStoreType, OrphanedRewardResponse, PoolConfigUpdate,
+    PoolFieldChange, PoolInfoByStakingTokenResponse, PoolInfoPatch, PoolInfoResponse,
+    PoolMigrationStatus, PoolRepairResponse, ProxyRewardResponse, QueryMsg, QueryPoolInfoResponse,
+    ReconcileResponse, RegisterAssetEntry, RewardInfoByStakingTokenResponse, RewardInfoExport,
+    RewardsPerSecResponse, StateResponse, SudoMsg, UnbondingEntry,
 };
 
-use cw20::Cw20ReceiveMsg;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:oraiswap_staking";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const NATIVE_DECIMALS: u8 = 6;
+const MAX_INSTANT_UNBOND_PENALTY_PERCENT: u64 = 20;
+const MAX_WITHDRAW_FEE_PERCENT: u64 = 10;
+// hex characters of the sha256 trace hash in ibc/ denoms
+const IBC_DENOM_HASH_LEN: usize = 64;
+// every settlement walks the proxy rewards of the pool
+const MAX_PROXY_REWARDS: usize = 5;
+// each bond change sends a message to every hook
+const MAX_HOOKS: usize = 5;
+// replies to hook messages, which are only answered when they fail
+pub const HOOK_REPLY_ID: u64 = 1;
+// entries each pool whitelist and blacklist can hold
+const MAX_STAKER_LIST_LEN: u32 = 500;
+// each deprecation finalizes the epoch and moves the pool within the migrate call
+const MAX_MIGRATE_DEPRECATIONS: usize = 10;
+// legacy pools and stakers moved by one MigrateStep
+const DEFAULT_MIGRATE_STEP_LIMIT: u32 = 30;
+const MAX_MIGRATE_STEP_LIMIT: u32 = 100;
+const DEFAULT_POOL_INFOS_LIMIT: u32 = 30;
+const MAX_POOL_INFOS_LIMIT: u32 = 100;
+const DEFAULT_REPAIR_HISTORY_LIMIT: u32 = 10;
+const MAX_REPAIR_HISTORY_LIMIT: u32 = 30;
+// records of an ExportState page, reward infos are the largest of them
+const DEFAULT_EXPORT_LIMIT: u32 = 10;
+const MAX_EXPORT_LIMIT: u32 = 30;
+// every entry queries the TokenInfo of its staking token
+const MAX_REGISTER_ASSETS: usize = 20;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
+    // default base_denom pass to factory is orai token
+    let base_denom = msg.base_denom.unwrap_or(ORAI_DENOM.to_string());
+    validate_denom(&base_denom)?;
     store_config(
         deps.storage,
         &Config {
@@ -46,52 +120,350 @@ pub fn instantiate(
             rewarder: deps.api.addr_canonicalize(msg.rewarder.as_str())?,
             oracle_addr: deps.api.addr_canonicalize(msg.oracle_addr.as_str())?,
             factory_addr: deps.api.addr_canonicalize(msg.factory_addr.as_str())?,
-            // default base_denom pass to factory is orai token
-            base_denom: msg.base_denom.unwrap_or(ORAI_DENOM.to_string()),
+            base_denom,
+            paused: false,
+            penalty_collector: None,
+            reward_distributors: vec![],
+            distribution_schedule: vec![],
+            last_distributed: 0,
+            withdraw_fee_rate: Decimal::zero(),
+            fee_collector: None,
+            bond_checkpoint_retention: 0,
+            dust_threshold: Uint128::zero(),
+            boost_contract: None,
+            hooks: vec![],
+            community_pool: None,
+            reward_activity_since: env.block.time.seconds(),
         },
     )?;
+    store_state(deps.storage, &State::default())?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     // set to true to enable normal execute handling when instantiate
     store_finish_migrate_store_status(deps.storage, true)?;
 
     Ok(Response::default())
 }
 
+// sent by the chain governance module, mirrors the owner operations without the owner check
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    let res = match msg {
+        SudoMsg::Pause {} => _update_paused(deps, true),
+        SudoMsg::Unpause {} => _update_paused(deps, false),
+        SudoMsg::UpdateRewardsPerSec {
+            staking_token,
+            assets,
+        } => _update_rewards_per_sec(deps, env, staking_token, assets),
+        SudoMsg::UpdateRewardWeights { weights } => _update_reward_weights(deps, env, weights),
+    }?;
+
+    Ok(res.add_attribute("authority", "sudo"))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // while the store is moved over only MigrateStep and the owner's UpdateConfig go through
+    if !matches!(
+        msg,
+        ExecuteMsg::MigrateStep { .. } | ExecuteMsg::UpdateConfig { .. }
+    ) {
+        validate_migrate_store_status(deps.storage)?;
+    }
     match msg {
-        ExecuteMsg::Receive(msg) => receive_cw20(deps, info, msg),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::UpdateConfig {
             rewarder,
-            owner,
             migrate_store_status,
-        } => update_config(deps, info, owner, rewarder, migrate_store_status),
+            penalty_collector,
+            reward_distributors,
+            withdraw_fee_rate,
+            fee_collector,
+            oracle_addr,
+            factory_addr,
+            base_denom,
+            community_pool,
+        } => update_config(
+            deps,
+            info,
+            rewarder,
+            migrate_store_status,
+            penalty_collector,
+            reward_distributors,
+            withdraw_fee_rate,
+            fee_collector,
+            oracle_addr,
+            factory_addr,
+            base_denom,
+            community_pool,
+        ),
+        ExecuteMsg::ProposeNewOwner { owner, expires_at } => {
+            propose_new_owner(deps, env, info, owner, expires_at)
+        }
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+        ExecuteMsg::Pause {} => update_paused(deps, info, true),
+        ExecuteMsg::Unpause {} => update_paused(deps, info, false),
         ExecuteMsg::UpdateRewardsPerSec {
             staking_token,
             assets,
-        } => update_rewards_per_sec(deps, info, staking_token, assets),
-        ExecuteMsg::DepositReward { rewards } => deposit_reward(deps, info, rewards),
-        ExecuteMsg::RegisterAsset { staking_token } => register_asset(deps, info, staking_token),
+        } => update_rewards_per_sec(deps, env, info, staking_token, assets),
+        ExecuteMsg::DepositReward { rewards } => deposit_reward(deps, env, info, rewards),
+        ExecuteMsg::OwnerDepositReward { rewards, force } => {
+            owner_deposit_reward(deps, env, info, rewards, force)
+        }
+        ExecuteMsg::DepositNativeReward { rewards } => {
+            deposit_native_reward(deps, env, info, rewards)
+        }
+        ExecuteMsg::RegisterAsset {
+            staking_token,
+            min_bond_amount,
+        } => register_asset(deps, info, staking_token, min_bond_amount),
+        ExecuteMsg::RegisterAssets { assets } => register_assets(deps, info, assets),
+        ExecuteMsg::RegisterAssetFromFactory {
+            asset_info,
+            quote_asset,
+            min_bond_amount,
+        } => register_asset_from_factory(deps, info, asset_info, quote_asset, min_bond_amount),
+        ExecuteMsg::RegisterNativeAsset {
+            denom,
+            min_bond_amount,
+        } => register_native_asset(deps, info, denom, min_bond_amount),
+        ExecuteMsg::UnregisterAsset { staking_token } => {
+            unregister_asset(deps, info, staking_token)
+        }
+        ExecuteMsg::UpdatePoolEpoch {
+            staking_token,
+            epoch_length,
+        } => update_pool_epoch(deps, env, info, staking_token, epoch_length),
+        ExecuteMsg::UpdateUnbondingPeriod {
+            staking_token,
+            unbonding_period,
+        } => update_unbonding_period(deps, info, staking_token, unbonding_period),
+        ExecuteMsg::UpdateInstantUnbondPenalty {
+            staking_token,
+            penalty_rate,
+        } => update_instant_unbond_penalty(deps, info, staking_token, penalty_rate),
+        ExecuteMsg::UpdateMinBondAmount {
+            staking_token,
+            min_bond_amount,
+        } => update_min_bond_amount(deps, info, staking_token, min_bond_amount),
+        ExecuteMsg::UpdateMinCompoundReward {
+            staking_token,
+            min_compound_reward,
+        } => update_min_compound_reward(deps, info, staking_token, min_compound_reward),
+        ExecuteMsg::UpdateBondCap {
+            staking_token,
+            bond_cap,
+        } => update_bond_cap(deps, info, staking_token, bond_cap),
+        ExecuteMsg::UpdateMaxBondPerStaker {
+            staking_token,
+            max_bond_per_staker,
+        } => update_max_bond_per_staker(deps, info, staking_token, max_bond_per_staker),
+        ExecuteMsg::UpdateLockTiers {
+            staking_token,
+            lock_tiers,
+        } => update_lock_tiers(deps, info, staking_token, lock_tiers),
+        ExecuteMsg::AppendDistributionSchedule { schedule } => {
+            append_distribution_schedule(deps, env, info, schedule)
+        }
+        ExecuteMsg::UpdateRewardWeights { weights } => {
+            update_reward_weights(deps, env, info, weights)
+        }
+        ExecuteMsg::UpdateRewardWindow {
+            staking_token,
+            reward_start_time,
+            reward_end_time,
+        } => update_reward_window(
+            deps,
+            info,
+            staking_token,
+            reward_start_time,
+            reward_end_time,
+        ),
+        ExecuteMsg::AddProxyReward {
+            staking_token,
+            reward_token,
+        } => add_proxy_reward(deps, info, staking_token, reward_token),
+        ExecuteMsg::UpdatePoolConfig {
+            staking_token,
+            params,
+        } => update_pool_config(deps, info, staking_token, params),
+        ExecuteMsg::UpdatePoolStatus {
+            staking_token,
+            frozen,
+        } => update_pool_status(deps, info, staking_token, frozen),
+        ExecuteMsg::UpdateBondCheckpointRetention { retention_blocks } => {
+            update_bond_checkpoint_retention(deps, info, retention_blocks)
+        }
+        ExecuteMsg::UpdateDustThreshold { dust_threshold } => {
+            update_dust_threshold(deps, info, dust_threshold)
+        }
+        ExecuteMsg::UpdateBoostContract { boost_contract } => {
+            update_boost_contract(deps, info, boost_contract)
+        }
+        ExecuteMsg::AddHook { contract_addr } => add_hook(deps, info, contract_addr),
+        ExecuteMsg::RemoveHook { contract_addr } => remove_hook(deps, info, contract_addr),
+        ExecuteMsg::RescueFunds {
+            asset,
+            amount,
+            recipient,
+        } => rescue_funds(deps, env, info, asset, amount, recipient),
+        ExecuteMsg::SweepExcess {
+            staking_token,
+            recipient,
+        } => sweep_excess(deps, env, info, staking_token, recipient),
+        ExecuteMsg::RepairPoolInfo {
+            staking_token,
+            patch,
+            reason,
+        } => repair_pool_info(deps, env, info, staking_token, patch, reason),
+        ExecuteMsg::UpdateWhitelist {
+            staking_token,
+            enabled,
+            add,
+            remove,
+        } => update_staker_list(
+            deps,
+            info,
+            staking_token,
+            PREFIX_POOL_WHITELIST,
+            enabled,
+            add,
+            remove,
+        ),
+        ExecuteMsg::UpdateBlacklist {
+            staking_token,
+            add,
+            remove,
+        } => update_staker_list(
+            deps,
+            info,
+            staking_token,
+            PREFIX_POOL_BLACKLIST,
+            None,
+            add,
+            remove,
+        ),
         ExecuteMsg::DeprecateStakingToken {
             staking_token,
             new_staking_token,
-        } => deprecate_staking_token(deps, info, staking_token, new_staking_token),
+            grace_period,
+        } => deprecate_staking_token(
+            deps,
+            env,
+            info,
+            staking_token,
+            new_staking_token,
+            grace_period,
+        ),
+        ExecuteMsg::StartStakingTokenTransition {
+            staking_token,
+            new_staking_token,
+        } => start_staking_token_transition(deps, info, staking_token, new_staking_token),
+        ExecuteMsg::BondNative { staking_token } => bond_native(deps, env, info, staking_token),
+        ExecuteMsg::BondFromAllowance {
+            staking_token,
+            amount,
+        } => bond_from_allowance(deps, env, info, staking_token, amount),
         ExecuteMsg::Unbond {
             staking_token,
             amount,
-        } => unbond(deps, env, info.sender, staking_token, amount),
-        ExecuteMsg::Withdraw { staking_token } => withdraw_reward(deps, env, info, staking_token),
+            withdraw_rewards,
+            recipient,
+            unbond_msg,
+        } => unbond(
+            deps,
+            env,
+            info.sender,
+            staking_token,
+            amount,
+            withdraw_rewards.unwrap_or(false),
+            recipient,
+            unbond_msg,
+        ),
+        ExecuteMsg::InstantUnbond {
+            staking_token,
+            amount,
+        } => instant_unbond(deps, env, info.sender, staking_token, amount),
+        ExecuteMsg::TransferBond {
+            staking_token,
+            amount,
+            recipient,
+        } => transfer_bond(deps, env, info.sender, staking_token, amount, recipient),
+        ExecuteMsg::Claim { staking_token } => claim(deps, env, info.sender, staking_token),
+        ExecuteMsg::UpdateBoost {
+            staker_addr,
+            staking_token,
+        } => update_boost(deps, env, staker_addr, staking_token),
+        ExecuteMsg::Withdraw {
+            staking_token,
+            ibc_recipient,
+            amounts,
+            recipient,
+            staker_addr,
+        } => withdraw_reward(
+            deps,
+            env,
+            info,
+            staking_token,
+            ibc_recipient,
+            amounts,
+            recipient,
+            staker_addr,
+        ),
+        ExecuteMsg::SetOperator { operator } => set_operator(deps, info, operator),
+        ExecuteMsg::ReallocateOrphanedRewards {
+            from_asset,
+            to_assets,
+        } => reallocate_orphaned_rewards(deps, env, info, from_asset, to_assets),
+        ExecuteMsg::CloseEpoch { staking_token } => close_epoch(deps, env, staking_token),
+        ExecuteMsg::MigrateStep { limit } => migrate_step(deps, limit),
+        ExecuteMsg::PruneRewardInfos { limit } => prune_reward_infos(deps, limit),
         ExecuteMsg::WithdrawOthers {
             staking_token,
             staker_addrs,
         } => withdraw_reward_others(deps, env, info, staker_addrs, staking_token),
+        ExecuteMsg::SweepAbandonedRewards {
+            staking_token,
+            stakers,
+            min_idle_seconds,
+        } => sweep_abandoned_rewards(deps, env, info, staking_token, stakers, min_idle_seconds),
+        ExecuteMsg::WithdrawAndSwap {
+            staking_token,
+            target_asset,
+            belief_price,
+            max_spread,
+        } => withdraw_and_swap(
+            deps,
+            env,
+            info,
+            staking_token,
+            target_asset,
+            belief_price,
+            max_spread,
+        ),
         ExecuteMsg::AutoStake {
             assets,
             slippage_tolerance,
-        } => auto_stake(deps, env, info, assets, slippage_tolerance),
+            minimum_lp_amount,
+        } => auto_stake(
+            deps,
+            env,
+            info,
+            assets,
+            slippage_tolerance,
+            minimum_lp_amount,
+        ),
         ExecuteMsg::AutoStakeHook {
             staking_token,
             staker_addr,
             prev_staking_token_amount,
+            minimum_lp_amount,
         } => auto_stake_hook(
             deps,
             env,
@@ -99,269 +471,2947 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             staking_token,
             staker_addr,
             prev_staking_token_amount,
+            minimum_lp_amount,
+        ),
+        ExecuteMsg::MigrateBond {
+            staking_token,
+            slippage_tolerance,
+        } => migrate_bond(deps, env, info, staking_token, slippage_tolerance),
+        ExecuteMsg::MigrateBondHook {
+            staker_addr,
+            staking_token,
+            pair_addr,
+            amount,
+            prev_balances,
+            slippage_tolerance,
+        } => migrate_bond_hook(
+            deps,
+            env,
+            info,
+            staker_addr,
+            staking_token,
+            pair_addr,
+            amount,
+            prev_balances,
+            slippage_tolerance,
+        ),
+        ExecuteMsg::MigrateReceivedBondHook {
+            staker_addr,
+            staking_token,
+            pair_addr,
+            prev_balances,
+        } => migrate_received_bond_hook(
+            deps,
+            env,
+            info,
+            staker_addr,
+            staking_token,
+            pair_addr,
+            prev_balances,
+        ),
+        ExecuteMsg::Compound {
+            asset_infos,
+            slippage_tolerance,
+        } => compound(deps, env, info, asset_infos, slippage_tolerance),
+        ExecuteMsg::CompoundHook {
+            staker_addr,
+            staking_token,
+            pair_addr,
+            assets,
+            prev_ask_balance,
+            slippage_tolerance,
+        } => compound_hook(
+            deps,
+            env,
+            info,
+            staker_addr,
+            staking_token,
+            pair_addr,
+            assets,
+            prev_ask_balance,
+            slippage_tolerance,
         ),
     }
 }
 
 pub fn receive_cw20(
-    deps: DepsMut,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     validate_migrate_store_status(deps.storage)?;
-    match from_binary(&cw20_msg.msg) {
-        Ok(Cw20HookMsg::Bond {}) => {
-            // check permission
-            let token_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
-
-            let pool_info = read_pool_info(deps.storage, &token_raw)?;
-
-            // only staking token contract can execute this message
-            // if user is trying to bond old token, return friendly error message
-            if let Some(params) = pool_info.migration_params {
-                if params.deprecated_staking_token == token_raw {
-                    let staking_token_addr = deps.api.addr_humanize(&pool_info.staking_token)?;
-                    return Err(StdError::generic_err(format!(
-                        "The staking token for this asset has been migrated to {}",
-                        staking_token_addr
-                    )));
-                }
+    if cw20_msg.amount.is_zero() {
+        return Err(ContractError::ZeroCw20HookAmount {});
+    }
+    match parse_hook_msg(&cw20_msg.msg)? {
+        Cw20HookMsg::Bond {
+            lock_duration,
+            referrer,
+        } => {
+            // the recorded volume reverts with the bond when the sender is not bondable
+            if let Some(referrer) = referrer {
+                record_referral(
+                    deps.branch(),
+                    &referrer,
+                    &cw20_msg.sender,
+                    &info.sender,
+                    cw20_msg.amount,
+                )?;
             }
+            receive_bond(deps, env, info, cw20_msg, None, lock_duration)
+        }
+        Cw20HookMsg::BondFor { staker_addr } => {
+            receive_bond(deps, env, info, cw20_msg, Some(staker_addr), None)
+        }
+        Cw20HookMsg::DepositRewardWeights { weights } => deposit_reward_weights(
+            deps,
+            env,
+            info.sender,
+            Addr::unchecked(cw20_msg.sender),
+            cw20_msg.amount,
+            weights,
+        ),
+        Cw20HookMsg::DepositReward {
+            stream_over_seconds,
+            expected_epoch,
+        } => deposit_reward_by_weights(
+            deps,
+            env,
+            info.sender,
+            Addr::unchecked(cw20_msg.sender),
+            cw20_msg.amount,
+            stream_over_seconds,
+            expected_epoch,
+        ),
+        Cw20HookMsg::DepositProxyReward { staking_token } => {
+            deposit_proxy_reward(deps, info.sender, cw20_msg.amount, staking_token)
+        }
+    }
+}
+
+// tells a missing message, an unknown variant and a known variant with bad fields apart so
+// integrators see what is wrong with the payload
+fn parse_hook_msg(msg: &Binary) -> Result<Cw20HookMsg, ContractError> {
+    if msg.iter().all(|b| b.is_ascii_whitespace()) {
+        return Err(ContractError::MissingCw20HookMessage {});
+    }
+    let err = match from_binary::<Cw20HookMsg>(msg) {
+        Ok(hook_msg) => return Ok(hook_msg),
+        Err(err) => err,
+    };
+    let variants: BTreeMap<String, IgnoredAny> = match from_binary::<Option<_>>(msg) {
+        Ok(Some(variants)) => variants,
+        Ok(None) => return Err(ContractError::MissingCw20HookMessage {}),
+        Err(_) => return Err(ContractError::InvalidCw20HookMessage {}),
+    };
+    let mut variants = variants.into_keys();
+    let variant = match (variants.next(), variants.next()) {
+        (None, _) => return Err(ContractError::MissingCw20HookMessage {}),
+        (Some(variant), None) => variant,
+        _ => return Err(ContractError::InvalidCw20HookMessage {}),
+    };
+    let reason = match err {
+        StdError::ParseErr { msg, .. } => msg,
+        err => err.to_string(),
+    };
+    if reason.starts_with("unknown variant") {
+        Err(ContractError::UnknownCw20HookMessage { variant })
+    } else {
+        Err(ContractError::MalformedCw20HookMessage { variant, reason })
+    }
+}
+
+// the position belongs to staker_addr when given, otherwise to the sender of the tokens
+fn receive_bond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+    staker_addr: Option<Addr>,
+    lock_duration: Option<u64>,
+) -> Result<Response, ContractError> {
+    // within the grace period the deprecated token is moved into the new pair instead
+    let token_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if let Some(pool_key) = read_deprecated_staking_token(deps.storage, &token_raw)? {
+        let grace_period_end = read_pool_info(deps.storage, &pool_key)?
+            .migration_params
+            .and_then(|params| params.grace_period_end);
+        if grace_period_end.map_or(false, |end| env.block.time.seconds() < end) {
+            let staker_addr = match staker_addr {
+                Some(staker_addr) => deps.api.addr_validate(staker_addr.as_str())?,
+                None => Addr::unchecked(cw20_msg.sender),
+            };
+            let staking_token = staking_token_addr(deps.storage, deps.api, &pool_key)?;
+            return migrate_received_bond(
+                deps,
+                env,
+                staker_addr,
+                staking_token,
+                cw20_msg.amount,
+                info.sender,
+            );
+        }
+    }
+    // only staking token contract can execute this message
+    assert_cw20_bondable(deps.as_ref(), &info.sender)?;
 
-            bond(
+    match staker_addr {
+        Some(staker_addr) => {
+            let staker_addr = deps.api.addr_validate(staker_addr.as_str())?;
+            let res = bond(
                 deps,
-                Addr::unchecked(cw20_msg.sender),
+                env,
+                staker_addr,
                 info.sender,
                 cw20_msg.amount,
-            )
+                lock_duration,
+            )?;
+            Ok(res.add_attribute("funder", cw20_msg.sender))
+        }
+        None => bond(
+            deps,
+            env,
+            Addr::unchecked(cw20_msg.sender),
+            info.sender,
+            cw20_msg.amount,
+            lock_duration,
+        ),
+    }
+}
+
+// bonds like the cw20 hook, pulling the tokens from an allowance the sender gave this contract
+fn bond_from_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_token: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    if amount.is_zero() {
+        return Err(ContractError::ZeroBondAmount {});
+    }
+    assert_cw20_bondable(deps.as_ref(), &staking_token)?;
+
+    let transfer_msg = WasmMsg::Execute {
+        contract_addr: staking_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+            owner: info.sender.to_string(),
+            recipient: env.contract.address.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    };
+    let res = bond(deps, env, info.sender, staking_token, amount, None)?;
+
+    Ok(res.add_message(transfer_msg))
+}
+
+fn assert_cw20_bondable(deps: Deps, staking_token: &Addr) -> Result<(), ContractError> {
+    if is_native_staking_token(deps.storage, staking_token.as_bytes()) {
+        return Err(ContractError::InvalidStakingToken {});
+    }
+    let token_raw = deps.api.addr_canonicalize(staking_token.as_str())?;
+
+    // if user is trying to bond old token, return friendly error message
+    if let Some(pool_key) = read_deprecated_staking_token(deps.storage, &token_raw)? {
+        return Err(ContractError::StakingTokenMigrated {
+            new_token: staking_token_addr(deps.storage, deps.api, &pool_key)?.to_string(),
+        });
+    }
+    read_registered_pool_info(deps.storage, staking_token, &token_raw)?;
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    rewarder: Option<Addr>,
+    migrate_store_status: Option<bool>,
+    penalty_collector: Option<Addr>,
+    reward_distributors: Option<Vec<Addr>>,
+    withdraw_fee_rate: Option<Decimal>,
+    fee_collector: Option<Addr>,
+    oracle_addr: Option<Addr>,
+    factory_addr: Option<Addr>,
+    base_denom: Option<String>,
+    community_pool: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let mut config = read_config(deps.storage)?;
+
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(rewarder) = rewarder {
+        config.rewarder = deps.api.addr_canonicalize(rewarder.as_str())?;
+    }
+
+    if let Some(penalty_collector) = penalty_collector {
+        config.penalty_collector = Some(deps.api.addr_canonicalize(penalty_collector.as_str())?);
+    }
+
+    if let Some(reward_distributors) = reward_distributors {
+        config.reward_distributors = reward_distributors
+            .iter()
+            .map(|distributor| deps.api.addr_canonicalize(distributor.as_str()))
+            .collect::<StdResult<_>>()?;
+    }
+
+    if let Some(fee_collector) = fee_collector {
+        config.fee_collector = Some(deps.api.addr_canonicalize(fee_collector.as_str())?);
+    }
+
+    if let Some(withdraw_fee_rate) = withdraw_fee_rate {
+        config.withdraw_fee_rate = withdraw_fee_rate;
+    }
+
+    let mut attrs = vec![("action", "update_config".to_string())];
+    if let Some(oracle_addr) = oracle_addr {
+        config.oracle_addr = deps.api.addr_canonicalize(oracle_addr.as_str())?;
+        attrs.push(("oracle_addr", oracle_addr.to_string()));
+    }
+
+    if let Some(factory_addr) = factory_addr {
+        config.factory_addr = deps.api.addr_canonicalize(factory_addr.as_str())?;
+        attrs.push(("factory_addr", factory_addr.to_string()));
+    }
+
+    // pools whose rewards_per_sec still use the old denom stop taking native deposits
+    if let Some(base_denom) = base_denom {
+        validate_denom(&base_denom)?;
+        attrs.push(("base_denom", base_denom.clone()));
+        config.base_denom = base_denom;
+    }
+
+    if let Some(community_pool) = community_pool {
+        config.community_pool = Some(deps.api.addr_canonicalize(community_pool.as_str())?);
+        attrs.push(("community_pool", community_pool.to_string()));
+    }
+
+    if let Some(migrate_store_status) = migrate_store_status {
+        store_finish_migrate_store_status(deps.storage, migrate_store_status)?;
+    }
+
+    validate_config(&config)?;
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(attrs))
+}
+
+// checked against the whole config, so a rate and its collector can be set in either order
+fn validate_config(config: &Config) -> Result<(), ContractError> {
+    let max = Decimal::percent(MAX_WITHDRAW_FEE_PERCENT);
+    if config.withdraw_fee_rate > max {
+        return Err(ContractError::WithdrawFeeTooHigh { max });
+    }
+    if !config.withdraw_fee_rate.is_zero() && config.fee_collector.is_none() {
+        return Err(ContractError::FeeCollectorNotSet {});
+    }
+
+    Ok(())
+}
+
+// the sdk denom format, and for ibc/ denoms the uppercase hex hash of the trace
+fn validate_denom(denom: &str) -> Result<(), ContractError> {
+    let valid = match denom.strip_prefix("ibc/") {
+        Some(hash) => {
+            hash.len() == IBC_DENOM_HASH_LEN
+                && hash
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || ('A'..='F').contains(&c))
+        }
+        None => {
+            (3..=128).contains(&denom.len())
+                && denom.starts_with(|c: char| c.is_ascii_alphabetic())
+                && denom
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c))
         }
-        Err(_) => Err(StdError::generic_err("invalid cw20 hook message")),
+    };
+    if !valid {
+        return Err(ContractError::InvalidBaseDenom {
+            denom: denom.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+fn update_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let config = read_config(deps.storage)?;
+
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    _update_paused(deps, paused)
+}
+
+fn _update_paused(deps: DepsMut, paused: bool) -> Result<Response, ContractError> {
+    let mut config = read_config(deps.storage)?;
+    config.paused = paused;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", if paused { "pause" } else { "unpause" }))
+}
+
+fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Addr,
+    expires_at: u64,
+) -> Result<Response, ContractError> {
+    let config = read_config(deps.storage)?;
+
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if expires_at <= env.block.time.seconds() {
+        return Err(ContractError::InvalidOwnershipExpiry {});
+    }
+
+    // a new proposal replaces the previous one
+    store_ownership_proposal(
+        deps.storage,
+        &OwnershipProposal {
+            owner: deps.api.addr_canonicalize(owner.as_str())?,
+            expires_at,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "propose_new_owner"),
+        ("owner", owner.as_str()),
+        ("expires_at", &expires_at.to_string()),
+    ]))
+}
+
+fn cancel_ownership_proposal(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = read_config(deps.storage)?;
+
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    remove_ownership_proposal(deps.storage);
+    Ok(Response::new().add_attribute("action", "cancel_ownership_proposal"))
+}
+
+fn accept_ownership(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let proposal = match read_ownership_proposal(deps.storage)? {
+        Some(proposal) if proposal.owner == sender => proposal,
+        _ => return Err(ContractError::NoOwnershipProposal {}),
+    };
+
+    if env.block.time.seconds() > proposal.expires_at {
+        return Err(ContractError::OwnershipProposalExpired {
+            expires_at: proposal.expires_at,
+        });
+    }
+
+    let mut config = read_config(deps.storage)?;
+    config.owner = proposal.owner;
+    store_config(deps.storage, &config)?;
+    remove_ownership_proposal(deps.storage);
+
+    Ok(Response::new().add_attributes([
+        ("action", "accept_ownership"),
+        ("owner", info.sender.as_str()),
+    ]))
+}
+
+// need to withdraw all rewards of the stakers belong to the pool
+// may need to call withdraw from backend side by querying all stakers with pagination in case out of gas
+fn update_rewards_per_sec(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_token: Addr,
+    assets: Vec<Asset>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    _update_rewards_per_sec(deps, env, staking_token, assets)
+}
+
+fn _update_rewards_per_sec(
+    deps: DepsMut,
+    env: Env,
+    staking_token: Addr,
+    assets: Vec<Asset>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?.to_vec();
+
+    // stakers of a transition staking token share the pool rewards too
+    let mut staking_keys = vec![asset_key.clone()];
+    if let Ok(pool_info) = read_pool_info(deps.storage, &asset_key) {
+        staking_keys.extend(
+            pool_info
+                .transition_staking_tokens
+                .into_iter()
+                .map(|bucket| bucket.staking_token.to_vec()),
+        );
+    }
+
+    for staking_key in staking_keys {
+        // withdraw all rewards for all stakers from this pool
+        let staker_addrs = stakers_read(deps.storage, &staking_key)
+            .range(None, None, Order::Ascending)
+            .map(|item| {
+                let (k, _) = item?;
+                Ok(CanonicalAddr::from(k))
+            })
+            .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+
+        // withdraw reward for each staker
+        for staker_addr_raw in staker_addrs {
+            process_reward_assets(
+                deps.storage,
+                &staker_addr_raw,
+                &Some(staking_key.clone()),
+                false,
+                env.block.time.seconds(),
+            )?;
+        }
+    }
+
+    // convert assets to raw_assets
+    let raw_assets = assets
+        .into_iter()
+        .map(|w| Ok(w.to_raw(deps.api)?))
+        .collect::<StdResult<Vec<AssetRaw>>>()?;
+
+    // cache the reward decimals so queries don't have to ask the reward tokens
+    if let Ok(mut pool_info) = read_pool_info(deps.storage, &asset_key) {
+        cache_reward_decimals(deps.as_ref(), &mut pool_info, &raw_assets)?;
+        store_pool_info(deps.storage, &asset_key, &pool_info)?;
+    }
+
+    store_rewards_per_sec(deps.storage, &asset_key, raw_assets)?;
+
+    Ok(Response::new().add_attribute("action", "update_rewards_per_sec"))
+}
+
+// decimals already cached are kept, so assets still pending withdraw from older rewards stay known
+fn cache_reward_decimals(
+    deps: Deps,
+    pool_info: &mut PoolInfo,
+    reward_assets: &[AssetRaw],
+) -> StdResult<()> {
+    for asset in reward_assets {
+        if pool_info
+            .reward_decimals
+            .iter()
+            .any(|cached| cached.info.eq(&asset.info))
+        {
+            continue;
+        }
+        if let Some(decimals) = query_asset_decimals(deps, &asset.info)? {
+            pool_info.reward_decimals.push(AssetDecimalsRaw {
+                info: asset.info.clone(),
+                decimals,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// native denoms of the chain use 6 decimals, a token that can not answer TokenInfo is left uncached
+fn query_asset_decimals(deps: Deps, asset_info: &AssetInfoRaw) -> StdResult<Option<u8>> {
+    match asset_info {
+        AssetInfoRaw::NativeToken { .. } => Ok(Some(NATIVE_DECIMALS)),
+        AssetInfoRaw::Token { contract_addr } => {
+            let contract_addr = deps.api.addr_humanize(contract_addr)?;
+            Ok(query_token_info(&deps.querier, contract_addr)
+                .ok()
+                .map(|token_info| token_info.decimals))
+        }
+    }
+}
+
+fn register_asset(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    min_bond_amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    store_cw20_pool(deps, &staking_token, min_bond_amount)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "register_asset"),
+        ("staking_token", staking_token.as_str()),
+    ]))
+}
+
+// the liquidity token is read from the pair the factory knows rather than typed in by the owner
+fn register_asset_from_factory(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    quote_asset: Option<AssetInfo>,
+    min_bond_amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let quote_asset = quote_asset.unwrap_or(AssetInfo::NativeToken {
+        denom: config.base_denom,
+    });
+    let factory_addr = deps.api.addr_humanize(&config.factory_addr)?;
+    let pair_addr = query_pair_info(
+        &deps.querier,
+        factory_addr,
+        &[asset_info.clone(), quote_asset.clone()],
+    )
+    .map_err(|_| ContractError::FactoryPairNotFound {
+        asset: asset_info.to_string(),
+        quote: quote_asset.to_string(),
+    })?
+    .contract_addr;
+    let staking_token =
+        query_pair_info_from_pair(&deps.querier, pair_addr.clone())?.liquidity_token;
+
+    store_cw20_pool(deps, &staking_token, min_bond_amount)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "register_asset_from_factory"),
+        ("pair", pair_addr.as_str()),
+        ("staking_token", staking_token.as_str()),
+    ]))
+}
+
+// every entry is checked before the first pool is stored, the error names the first invalid one
+fn register_assets(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    assets: Vec<RegisterAssetEntry>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+    if assets.is_empty() || assets.len() > MAX_REGISTER_ASSETS {
+        return Err(ContractError::InvalidRegisterAssetsLength {
+            max: MAX_REGISTER_ASSETS,
+        });
+    }
+
+    let mut pools: Vec<(CanonicalAddr, String, Uint128)> = Vec::with_capacity(assets.len());
+    for (index, entry) in assets.iter().enumerate() {
+        let invalid = |err: ContractError| ContractError::InvalidRegisterAssetsEntry {
+            index,
+            reason: err.to_string(),
+        };
+        let (asset_key, symbol) =
+            validate_cw20_pool(deps.as_ref(), &entry.staking_token).map_err(invalid)?;
+        if pools.iter().any(|(key, _, _)| *key == asset_key) {
+            return Err(ContractError::InvalidRegisterAssetsEntry {
+                index,
+                reason: format!("{} is listed twice", entry.staking_token),
+            });
+        }
+        pools.push((asset_key, symbol, entry.min_bond_amount.unwrap_or_default()));
+    }
+
+    for (asset_key, symbol, min_bond_amount) in pools {
+        store_new_pool(deps.branch(), asset_key, min_bond_amount, Some(symbol))?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "register_assets")
+        .add_attributes(
+            assets
+                .iter()
+                .map(|entry| ("staking_token", entry.staking_token.as_str())),
+        ))
+}
+
+fn store_cw20_pool(
+    deps: DepsMut,
+    staking_token: &Addr,
+    min_bond_amount: Option<Uint128>,
+) -> Result<(), ContractError> {
+    let (asset_key, symbol) = validate_cw20_pool(deps.as_ref(), staking_token)?;
+    store_new_pool(
+        deps,
+        asset_key,
+        min_bond_amount.unwrap_or_default(),
+        Some(symbol),
+    )
+}
+
+// the asset key of a staking token with no pool yet, with the symbol its cw20 contract answers
+fn validate_cw20_pool(
+    deps: Deps,
+    staking_token: &Addr,
+) -> Result<(CanonicalAddr, String), ContractError> {
+    // query asset_key from AssetInfo
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    if read_pool_info(deps.storage, &asset_key).is_ok() {
+        return Err(ContractError::AssetRegistered {});
+    }
+
+    let symbol = query_staking_token_symbol(deps, staking_token)?;
+    Ok((asset_key, symbol))
+}
+
+// a mistyped address would otherwise only show up once bonds never match the pool
+fn query_staking_token_symbol(deps: Deps, staking_token: &Addr) -> Result<String, ContractError> {
+    let invalid_token = || ContractError::InvalidStakingTokenContract {
+        staking_token: staking_token.to_string(),
+    };
+    let token_info =
+        query_token_info(&deps.querier, staking_token.clone()).map_err(|_| invalid_token())?;
+    if token_info.symbol.is_empty() || token_info.decimals == 0 {
+        return Err(invalid_token());
+    }
+    Ok(token_info.symbol)
+}
+
+fn register_native_asset(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    min_bond_amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // the denom must not already name a cw20 pool either
+    let cw20_registered = deps
+        .api
+        .addr_canonicalize(&denom)
+        .map_or(false, |key| read_pool_info(deps.storage, &key).is_ok());
+    if cw20_registered || is_native_staking_token(deps.storage, denom.as_bytes()) {
+        return Err(ContractError::AssetRegistered {});
+    }
+
+    store_native_staking_token(deps.storage, &denom)?;
+    store_new_pool(
+        deps,
+        CanonicalAddr::from(denom.as_bytes()),
+        min_bond_amount.unwrap_or_default(),
+        None,
+    )?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "register_native_asset"),
+        ("staking_token", denom.as_str()),
+    ]))
+}
+
+fn store_new_pool(
+    deps: DepsMut,
+    asset_key: CanonicalAddr,
+    min_bond_amount: Uint128,
+    symbol: Option<String>,
+) -> Result<(), ContractError> {
+    let mut pool_info = PoolInfo {
+        staking_token: asset_key.clone(),
+        total_bond_amount: Uint128::zero(),
+        reward_index: Decimal256::zero(),
+        pending_reward: Uint128::zero(),
+        migration_params: None,
+        transition_staking_tokens: vec![],
+        reward_decimals: vec![],
+        unbonding_period: 0,
+        frozen: false,
+        instant_unbond_penalty: Decimal::zero(),
+        min_bond_amount,
+        min_compound_reward: Uint128::zero(),
+        bond_cap: None,
+        max_bond_per_staker: None,
+        lock_tiers: vec![],
+        total_lock_boost: Uint128::zero(),
+        total_boost_shortfall: Uint128::zero(),
+        reward_weight: Decimal::zero(),
+        reward_start_time: None,
+        reward_end_time: None,
+        proxy_rewards: vec![],
+        symbol,
+        staker_count: Some(0),
+        lifetime_bonded: Uint128::zero(),
+        lifetime_unbonded: Uint128::zero(),
+        lifetime_rewards_deposited: Uint128::zero(),
+        lifetime_rewards_claimed: Uint128::zero(),
+        whitelist_enabled: false,
+        whitelist_len: 0,
+        blacklist_len: 0,
+        orphaned_reward: Uint128::zero(),
+        max_index_increase_per_deposit: Decimal::zero(),
+        unbond_cooldown: 0,
+        reward_streams: vec![],
+        last_streamed: 0,
+        reward_stream_seconds: 0,
+        deposit_epoch: 0,
+    };
+    // rewards per sec may be set before the pool is registered
+    if let Ok(rewards_per_sec) = read_rewards_per_sec(deps.storage, &asset_key) {
+        cache_reward_decimals(deps.as_ref(), &mut pool_info, &rewards_per_sec)?;
+    }
+
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+    update_state(deps.storage, |state| state.pool_count += 1)?;
+    Ok(())
+}
+
+fn unregister_asset(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+
+    // positions of a deprecated staking token still count as bonded
+    let total_bond_amount = pool_info.total_bond_amount
+        + pool_info
+            .migration_params
+            .as_ref()
+            .map_or(Uint128::zero(), |params| params.deprecated_total_bond);
+    if !total_bond_amount.is_zero() {
+        return Err(ContractError::PoolHasBonds { total_bond_amount });
+    }
+
+    // rewards deposited into an open epoch are not distributed yet either
+    let pending_reward = pool_info.pending_reward
+        + read_pool_epoch(deps.storage, &asset_key)?
+            .map_or(Uint128::zero(), |epoch| epoch.accumulated_reward);
+    if !pending_reward.is_zero() {
+        return Err(ContractError::PoolHasPendingReward { pending_reward });
+    }
+
+    // stakers that unbonded without withdrawing still need the pool to withdraw, including
+    // the ones of a staking token in transition
+    let pool_key = pool_info.staking_token.clone();
+    let mut bonded_keys = vec![pool_key.clone()];
+    bonded_keys.extend(
+        pool_info
+            .transition_staking_tokens
+            .iter()
+            .map(|bucket| bucket.staking_token.clone()),
+    );
+    bonded_keys.extend(
+        pool_info
+            .migration_params
+            .as_ref()
+            .map(|params| params.deprecated_staking_token.clone()),
+    );
+    // a withdraw removes the reward info but leaves the staker listed, so look up the former
+    for key in bonded_keys.iter() {
+        for item in stakers_read(deps.storage, key).range(None, None, Order::Ascending) {
+            let (staker, _) = item?;
+            if rewards_read(deps.storage, &staker).may_load(key)?.is_some() {
+                return Err(ContractError::PoolHasStakers {});
+            }
+        }
+    }
+
+    remove_rewards_per_sec(deps.storage, &pool_key);
+    remove_pool_epoch(deps.storage, &pool_key);
+    remove_pool_deposits(deps.storage, &pool_key);
+    move_reward_index_history(deps.storage, &pool_key, None)?;
+    move_staker_lists(deps.storage, &pool_key, None)?;
+    remove_pool_info(deps.storage, &pool_key)?;
+    update_state(deps.storage, |state| {
+        state.pool_count = state.pool_count.saturating_sub(1)
+    })?;
+    for key in bonded_keys.iter().skip(1) {
+        remove_pool_alias(deps.storage, key);
+    }
+    if let Some(params) = pool_info.migration_params {
+        remove_deprecated_staking_token(deps.storage, &params.deprecated_staking_token);
+    }
+
+    Ok(Response::new().add_attributes([
+        ("action", "unregister_asset"),
+        ("staking_token", staking_token.as_str()),
+    ]))
+}
+
+fn update_unbonding_period(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    unbonding_period: u64,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    // amounts already waiting keep their release time
+    pool_info.unbonding_period = unbonding_period;
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_unbonding_period"),
+        ("staking_token", staking_token.as_str()),
+        ("unbonding_period", &unbonding_period.to_string()),
+    ]))
+}
+
+fn update_instant_unbond_penalty(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    penalty_rate: Decimal,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    validate_instant_unbond_penalty(&config, penalty_rate)?;
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    pool_info.instant_unbond_penalty = penalty_rate;
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_instant_unbond_penalty"),
+        ("staking_token", staking_token.as_str()),
+        ("penalty_rate", &penalty_rate.to_string()),
+    ]))
+}
+
+fn validate_instant_unbond_penalty(
+    config: &Config,
+    penalty_rate: Decimal,
+) -> Result<(), ContractError> {
+    let max = Decimal::percent(MAX_INSTANT_UNBOND_PENALTY_PERCENT);
+    if penalty_rate > max {
+        return Err(ContractError::InstantUnbondPenaltyTooHigh { max });
+    }
+    // the penalty needs somewhere to go before it can be charged
+    if !penalty_rate.is_zero() && config.penalty_collector.is_none() {
+        return Err(ContractError::PenaltyCollectorNotSet {});
+    }
+    Ok(())
+}
+
+fn update_min_bond_amount(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    min_bond_amount: Uint128,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    pool_info.min_bond_amount = min_bond_amount;
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_min_bond_amount"),
+        ("staking_token", staking_token.as_str()),
+        ("min_bond_amount", &min_bond_amount.to_string()),
+    ]))
+}
+
+fn update_min_compound_reward(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    min_compound_reward: Uint128,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    pool_info.min_compound_reward = min_compound_reward;
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_min_compound_reward"),
+        ("staking_token", staking_token.as_str()),
+        ("min_compound_reward", &min_compound_reward.to_string()),
+    ]))
+}
+
+fn update_bond_cap(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    bond_cap: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    // a cap below the current total only stops new bonds
+    pool_info.bond_cap = bond_cap;
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_bond_cap"),
+        ("staking_token", staking_token.as_str()),
+        (
+            "bond_cap",
+            &bond_cap.map_or("none".to_string(), |bond_cap| bond_cap.to_string()),
+        ),
+    ]))
+}
+
+fn update_max_bond_per_staker(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    max_bond_per_staker: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    // positions already above the cap keep their bond, they can only unbond and withdraw
+    pool_info.max_bond_per_staker = max_bond_per_staker;
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_max_bond_per_staker"),
+        ("staking_token", staking_token.as_str()),
+        (
+            "max_bond_per_staker",
+            &max_bond_per_staker.map_or("none".to_string(), |cap| cap.to_string()),
+        ),
+    ]))
+}
+
+fn update_reward_window(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    reward_start_time: Option<u64>,
+    reward_end_time: Option<u64>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    validate_reward_window(reward_start_time, reward_end_time)?;
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    pool_info.reward_start_time = reward_start_time;
+    pool_info.reward_end_time = reward_end_time;
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    let format_time = |time: Option<u64>| time.map_or("none".to_string(), |time| time.to_string());
+    Ok(Response::new().add_attributes([
+        ("action", "update_reward_window"),
+        ("staking_token", staking_token.as_str()),
+        ("reward_start_time", &format_time(reward_start_time)),
+        ("reward_end_time", &format_time(reward_end_time)),
+    ]))
+}
+
+fn add_proxy_reward(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    reward_token: Addr,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    let reward_token_raw = deps.api.addr_canonicalize(reward_token.as_str())?;
+    if pool_info
+        .proxy_rewards
+        .iter()
+        .any(|proxy| proxy.reward_token == reward_token_raw)
+    {
+        return Err(ContractError::ProxyRewardRegistered {
+            reward_token: reward_token.to_string(),
+        });
+    }
+    if pool_info.proxy_rewards.len() >= MAX_PROXY_REWARDS {
+        return Err(ContractError::TooManyProxyRewards {
+            max: MAX_PROXY_REWARDS,
+        });
+    }
+
+    // the index starts at zero, so positions without an entry have earned nothing yet
+    pool_info.proxy_rewards.push(ProxyReward {
+        reward_token: reward_token_raw,
+        proxy_index: Decimal::zero(),
+        pending: Uint128::zero(),
+    });
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "add_proxy_reward"),
+        ("staking_token", staking_token.as_str()),
+        ("reward_token", reward_token.as_str()),
+    ]))
+}
+
+fn update_lock_tiers(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    lock_tiers: Vec<LockTier>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for (i, tier) in lock_tiers.iter().enumerate() {
+        if tier.duration == 0
+            || tier.multiplier < Decimal::one()
+            || lock_tiers[..i]
+                .iter()
+                .any(|other| other.duration == tier.duration)
+        {
+            return Err(ContractError::InvalidLockTier {
+                duration: tier.duration,
+            });
+        }
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    // bonds already locked keep the multiplier they were locked with
+    pool_info.lock_tiers = lock_tiers;
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_lock_tiers"),
+        ("staking_token", staking_token.as_str()),
+        (
+            "lock_tiers",
+            &pool_info
+                .lock_tiers
+                .iter()
+                .map(|tier| format!("{}:{}", tier.duration, tier.multiplier))
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
+    ]))
+}
+
+fn validate_reward_window(
+    reward_start_time: Option<u64>,
+    reward_end_time: Option<u64>,
+) -> Result<(), ContractError> {
+    if let (Some(start_time), Some(end_time)) = (reward_start_time, reward_end_time) {
+        if end_time <= start_time {
+            return Err(ContractError::InvalidRewardWindow {
+                start_time,
+                end_time,
+            });
+        }
+    }
+    Ok(())
+}
+
+// every field is validated before any is applied, so the update goes through whole or not at all
+fn update_pool_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    params: PoolConfigUpdate,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(penalty_rate) = params.instant_unbond_penalty {
+        validate_instant_unbond_penalty(&config, penalty_rate)?;
+    }
+    if let Some((reward_start_time, reward_end_time)) = params.reward_window {
+        validate_reward_window(reward_start_time, reward_end_time)?;
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    let format_option = |value: Option<String>| value.unwrap_or_else(|| "none".to_string());
+    let mut attributes = vec![
+        attr("action", "update_pool_config"),
+        attr("staking_token", staking_token.as_str()),
+    ];
+
+    if let Some(unbonding_period) = params.unbonding_period {
+        pool_info.unbonding_period = unbonding_period;
+        attributes.push(attr("unbonding_period", unbonding_period.to_string()));
+    }
+    if let Some(penalty_rate) = params.instant_unbond_penalty {
+        pool_info.instant_unbond_penalty = penalty_rate;
+        attributes.push(attr("instant_unbond_penalty", penalty_rate.to_string()));
+    }
+    if let Some(min_bond_amount) = params.min_bond_amount {
+        pool_info.min_bond_amount = min_bond_amount;
+        attributes.push(attr("min_bond_amount", min_bond_amount.to_string()));
+    }
+    if let Some(min_compound_reward) = params.min_compound_reward {
+        pool_info.min_compound_reward = min_compound_reward;
+        attributes.push(attr("min_compound_reward", min_compound_reward.to_string()));
+    }
+    if let Some(bond_cap) = params.bond_cap {
+        pool_info.bond_cap = bond_cap.value();
+        attributes.push(attr(
+            "bond_cap",
+            format_option(pool_info.bond_cap.map(|cap| cap.to_string())),
+        ));
+    }
+    if let Some(max_bond_per_staker) = params.max_bond_per_staker {
+        pool_info.max_bond_per_staker = max_bond_per_staker.value();
+        attributes.push(attr(
+            "max_bond_per_staker",
+            format_option(pool_info.max_bond_per_staker.map(|cap| cap.to_string())),
+        ));
+    }
+    if let Some(frozen) = params.frozen {
+        pool_info.frozen = frozen;
+        attributes.push(attr("frozen", frozen.to_string()));
+    }
+    if let Some(unbond_cooldown) = params.unbond_cooldown {
+        pool_info.unbond_cooldown = unbond_cooldown;
+        attributes.push(attr("unbond_cooldown", unbond_cooldown.to_string()));
+    }
+    if let Some(reward_stream_seconds) = params.reward_stream_seconds {
+        pool_info.reward_stream_seconds = reward_stream_seconds;
+        attributes.push(attr(
+            "reward_stream_seconds",
+            reward_stream_seconds.to_string(),
+        ));
+    }
+    if let Some(max_index_increase) = params.max_index_increase_per_deposit {
+        pool_info.max_index_increase_per_deposit = max_index_increase;
+        attributes.push(attr(
+            "max_index_increase_per_deposit",
+            max_index_increase.to_string(),
+        ));
+    }
+    if let Some((reward_start_time, reward_end_time)) = params.reward_window {
+        pool_info.reward_start_time = reward_start_time;
+        pool_info.reward_end_time = reward_end_time;
+        attributes.push(attr(
+            "reward_start_time",
+            format_option(reward_start_time.map(|time| time.to_string())),
+        ));
+        attributes.push(attr(
+            "reward_end_time",
+            format_option(reward_end_time.map(|time| time.to_string())),
+        ));
+    }
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+fn update_pool_status(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    frozen: bool,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    pool_info.frozen = frozen;
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_pool_status"),
+        ("staking_token", staking_token.as_str()),
+        ("frozen", &frozen.to_string()),
+    ]))
+}
+
+fn update_bond_checkpoint_retention(
+    deps: DepsMut,
+    info: MessageInfo,
+    retention_blocks: u64,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // existing checkpoints are pruned the next time their position changes
+    config.bond_checkpoint_retention = retention_blocks;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_bond_checkpoint_retention"),
+        ("retention_blocks", &retention_blocks.to_string()),
+    ]))
+}
+
+fn update_dust_threshold(
+    deps: DepsMut,
+    info: MessageInfo,
+    dust_threshold: Uint128,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.dust_threshold = dust_threshold;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_dust_threshold"),
+        ("dust_threshold", &dust_threshold.to_string()),
+    ]))
+}
+
+fn update_boost_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    boost_contract: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // positions keep their boost until they are refreshed against the new contract
+    config.boost_contract = boost_contract
+        .as_ref()
+        .map(|addr| deps.api.addr_canonicalize(addr.as_str()))
+        .transpose()?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_boost_contract"),
+        (
+            "boost_contract",
+            &boost_contract.map_or("none".to_string(), |addr| addr.to_string()),
+        ),
+    ]))
+}
+
+fn add_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract_addr: Addr,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let hook = deps.api.addr_canonicalize(contract_addr.as_str())?;
+    if config.hooks.contains(&hook) {
+        return Err(ContractError::HookRegistered {
+            contract_addr: contract_addr.to_string(),
+        });
+    }
+    if config.hooks.len() >= MAX_HOOKS {
+        return Err(ContractError::TooManyHooks { max: MAX_HOOKS });
+    }
+    config.hooks.push(hook);
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "add_hook"),
+        ("contract_addr", contract_addr.as_str()),
+    ]))
+}
+
+// works while paused, so a broken hook can be dropped before staking resumes
+fn remove_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract_addr: Addr,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let hook = deps.api.addr_canonicalize(contract_addr.as_str())?;
+    if !config.hooks.contains(&hook) {
+        return Err(ContractError::HookNotFound {
+            contract_addr: contract_addr.to_string(),
+        });
+    }
+    config.hooks.retain(|registered| *registered != hook);
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "remove_hook"),
+        ("contract_addr", contract_addr.as_str()),
+    ]))
+}
+
+fn rescue_funds(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: AssetInfo,
+    amount: Uint128,
+    recipient: Addr,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let free = rescuable_amount(deps.as_ref(), &env, &config, &asset)?;
+    if amount > free {
+        return Err(ContractError::RescueExceedsFree {
+            free,
+            requested: amount,
+        });
+    }
+
+    let recipient = deps.api.addr_validate(recipient.as_str())?;
+    let asset = Asset {
+        info: asset,
+        amount,
+    };
+    Ok(Response::new()
+        .add_message(asset.into_msg(None, &deps.querier, recipient.clone())?)
+        .add_attributes([
+            ("action", "rescue_funds"),
+            ("asset", &asset.to_string()),
+            ("recipient", recipient.as_str()),
+        ]))
+}
+
+// the balance of the asset not owed to anyone, computed from the state so nothing bonded or
+// earned is counted as free
+fn rescuable_amount(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    asset: &AssetInfo,
+) -> Result<Uint128, ContractError> {
+    let asset_key = asset.to_vec(deps.api)?;
+    let asset_raw = asset.to_raw(deps.api)?;
+    if read_deprecated_staking_token(deps.storage, &asset_key)?.is_some() {
+        return Err(ContractError::ProtectedAsset {
+            asset: asset.to_string(),
+        });
+    }
+    let mut is_reward_asset = false;
+    for (pool_key, pool_info) in read_all_pool_infos(deps.storage)? {
+        // every staking token a position can still hold, including deprecated ones
+        let protected = pool_key == asset_key
+            || pool_info.staking_token.as_slice() == asset_key.as_slice()
+            || pool_info
+                .transition_staking_tokens
+                .iter()
+                .any(|bucket| bucket.staking_token.as_slice() == asset_key.as_slice())
+            || pool_info.migration_params.as_ref().map_or(false, |params| {
+                params.deprecated_staking_token.as_slice() == asset_key.as_slice()
+            })
+            || pool_info
+                .proxy_rewards
+                .iter()
+                .any(|proxy| proxy.reward_token.as_slice() == asset_key.as_slice());
+        if protected {
+            return Err(ContractError::ProtectedAsset {
+                asset: asset.to_string(),
+            });
+        }
+        is_reward_asset |= read_rewards_per_sec(deps.storage, &pool_key)
+            .unwrap_or_default()
+            .iter()
+            .any(|rw| rw.info.eq(&asset_raw));
+    }
+
+    let balance = asset.query_pool(&deps.querier, env.contract.address.clone())?;
+    if !is_reward_asset {
+        return Ok(balance);
+    }
+    // the reward assets are only accounted summed over them, so all of it is held back for each,
+    // along with the part of the distribution schedule not released yet
+    let state = read_state(deps.storage)?.unwrap_or_default();
+    let owed = state
+        .total_deposited
+        .saturating_add(state.total_scheduled)
+        .saturating_sub(state.total_withdrawn)
+        .saturating_add(scheduled_amount(
+            &config.distribution_schedule,
+            config.last_distributed,
+            u64::MAX,
+        ));
+    Ok(balance.saturating_sub(owed))
+}
+
+// the surplus is recomputed from the balance at execution, so no more than it is ever sent
+fn sweep_excess(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_token: Addr,
+    recipient: Addr,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let recipient = deps.api.addr_validate(recipient.as_str())?;
+    let surplus = query_reconcile(deps.as_ref(), &env, staking_token.clone())?.surplus;
+    if surplus.is_zero() {
+        return Err(ContractError::NothingToSweep {
+            staking_token: staking_token.to_string(),
+        });
+    }
+
+    Ok(Response::new()
+        .add_message(transfer_staking_token(
+            deps.storage,
+            &staking_token,
+            &recipient,
+            surplus,
+        )?)
+        .add_attributes([
+            ("action", "sweep_excess"),
+            ("staking_token", staking_token.as_str()),
+            ("recipient", recipient.as_str()),
+            ("amount", &surplus.to_string()),
+        ]))
+}
+
+// the positions are only summed while the pool has no deprecated staking token, the deprecated
+// ones are not counted in total_bond_amount
+fn repair_pool_info(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_token: Addr,
+    patch: PoolInfoPatch,
+    reason: String,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !config.paused {
+        return Err(ContractError::NotPaused {});
+    }
+    if reason.trim().is_empty() {
+        return Err(ContractError::EmptyRepairReason {});
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?.to_vec();
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    let mut changes = vec![];
+    let mut change = |field: &str, old_value: String, new_value: String| {
+        if old_value != new_value {
+            changes.push(PoolFieldChange {
+                field: field.to_string(),
+                old_value,
+                new_value,
+            });
+        }
+    };
+
+    if let Some(reward_index) = patch.reward_index {
+        let current = index_to_decimal(pool_info.reward_index)?;
+        if reward_index < current {
+            return Err(ContractError::RepairIndexRegression {
+                current,
+                requested: reward_index,
+            });
+        }
+        change(
+            "reward_index",
+            current.to_string(),
+            reward_index.to_string(),
+        );
+        pool_info.reward_index = Decimal256::from(reward_index);
+    }
+    if let Some(total_bond_amount) = patch.total_bond_amount {
+        if pool_info.migration_params.is_none() {
+            let mut positions = Uint128::zero();
+            for item in stakers_read(deps.storage, &asset_key).range(None, None, Order::Ascending) {
+                let (staker, _) = item?;
+                if let Some(reward_info) =
+                    rewards_read(deps.storage, &staker).may_load(&asset_key)?
+                {
+                    positions = positions.checked_add(reward_info.bond_amount)?;
+                }
+            }
+            if total_bond_amount < positions {
+                return Err(ContractError::RepairBelowPositions {
+                    positions,
+                    requested: total_bond_amount,
+                });
+            }
+        }
+        change(
+            "total_bond_amount",
+            pool_info.total_bond_amount.to_string(),
+            total_bond_amount.to_string(),
+        );
+        pool_info.total_bond_amount = total_bond_amount;
+    }
+    if let Some(pending_reward) = patch.pending_reward {
+        change(
+            "pending_reward",
+            pool_info.pending_reward.to_string(),
+            pending_reward.to_string(),
+        );
+        pool_info.pending_reward = pending_reward;
+    }
+    if changes.is_empty() {
+        return Err(ContractError::NothingToRepair {});
+    }
+
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+    let attributes: Vec<(String, String)> = changes
+        .iter()
+        .map(|change| {
+            (
+                change.field.clone(),
+                format!("{} -> {}", change.old_value, change.new_value),
+            )
+        })
+        .collect();
+    let id = append_pool_repair(
+        deps.storage,
+        &asset_key,
+        &PoolRepair {
+            time: env.block.time.seconds(),
+            reason: reason.clone(),
+            changes,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attributes([
+            ("action", "repair_pool_info"),
+            ("staking_token", staking_token.as_str()),
+            ("repair_id", &id.to_string()),
+            ("reason", &reason),
+        ])
+        .add_attributes(attributes))
+}
+
+pub fn query_repair_history(
+    deps: Deps,
+    staking_token: Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Vec<PoolRepairResponse>, ContractError> {
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let limit = page_limit(
+        limit,
+        DEFAULT_REPAIR_HISTORY_LIMIT,
+        MAX_REPAIR_HISTORY_LIMIT,
+    );
+    let start = calc_range_start(start_after.map(|id| id.to_be_bytes().to_vec()));
+
+    pool_repairs_read(deps.storage, &asset_key)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (id_key, repair) = item?;
+            let id = u64::from_be_bytes(
+                id_key
+                    .try_into()
+                    .map_err(|_| StdError::generic_err("invalid repair key"))?,
+            );
+            Ok(PoolRepairResponse {
+                id,
+                time: repair.time,
+                reason: repair.reason,
+                changes: repair.changes,
+            })
+        })
+        .collect()
+}
+
+// adds and removes stakers of the pool whitelist or blacklist, `enabled` only applies to the
+// whitelist. Adding a listed staker or removing an unlisted one is a no-op
+fn update_staker_list(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    prefix: &[u8],
+    enabled: Option<bool>,
+    add: Vec<Addr>,
+    remove: Vec<Addr>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    let pool_key = resolve_pool_key(deps.storage, &asset_key);
+    let is_whitelist = prefix == PREFIX_POOL_WHITELIST;
+    let mut len = if is_whitelist {
+        pool_info.whitelist_len
+    } else {
+        pool_info.blacklist_len
+    };
+
+    for staker in remove.iter() {
+        let staker_raw = deps.api.addr_canonicalize(staker.as_str())?;
+        if is_on_staker_list(deps.storage, prefix, &pool_key, &staker_raw) {
+            staker_list_store(deps.storage, prefix, &pool_key).remove(&staker_raw);
+            len = len.saturating_sub(1);
+        }
+    }
+    for staker in add.iter() {
+        let staker_raw = deps.api.addr_canonicalize(staker.as_str())?;
+        if !is_on_staker_list(deps.storage, prefix, &pool_key, &staker_raw) {
+            if len >= MAX_STAKER_LIST_LEN {
+                return Err(ContractError::StakerListFull {
+                    max: MAX_STAKER_LIST_LEN,
+                });
+            }
+            staker_list_store(deps.storage, prefix, &pool_key).save(&staker_raw, &true)?;
+            len += 1;
+        }
+    }
+
+    if is_whitelist {
+        pool_info.whitelist_len = len;
+        if let Some(enabled) = enabled {
+            pool_info.whitelist_enabled = enabled;
+        }
+    } else {
+        pool_info.blacklist_len = len;
+    }
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        (
+            "action",
+            if is_whitelist {
+                "update_whitelist"
+            } else {
+                "update_blacklist"
+            },
+        ),
+        ("staking_token", staking_token.as_str()),
+        ("added", &add.len().to_string()),
+        ("removed", &remove.len().to_string()),
+        ("len", &len.to_string()),
+        (
+            "whitelist_enabled",
+            &pool_info.whitelist_enabled.to_string(),
+        ),
+    ]))
+}
+
+fn append_distribution_schedule(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    schedule: Vec<(u64, u64, Uint128)>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // the accrual only looks forward from the last one, so entries can not start in the past
+    let now = env.block.time.seconds();
+    for (start_time, end_time, _) in schedule.iter() {
+        if *start_time < now || end_time <= start_time {
+            return Err(ContractError::InvalidDistributionSchedule {
+                start_time: *start_time,
+                end_time: *end_time,
+            });
+        }
+    }
+
+    accrue_distribution(deps.storage, now)?;
+    let mut config = read_config(deps.storage)?;
+    let amount: Uint128 = schedule.iter().map(|(_, _, amount)| amount).sum();
+    config.distribution_schedule.extend(schedule);
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "append_distribution_schedule"),
+        ("amount", &amount.to_string()),
+    ]))
+}
+
+fn update_reward_weights(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    weights: Vec<(Addr, Decimal)>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    _update_reward_weights(deps, env, weights)
+}
+
+fn _update_reward_weights(
+    deps: DepsMut,
+    env: Env,
+    weights: Vec<(Addr, Decimal)>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+
+    // what has unlocked so far is split with the previous weights
+    accrue_distribution(deps.storage, env.block.time.seconds())?;
+
+    for (i, (staking_token, reward_weight)) in weights.iter().enumerate() {
+        if weights[..i].iter().any(|(other, _)| other == staking_token) {
+            return Err(ContractError::DuplicatedRewardWeight {
+                staking_token: staking_token.to_string(),
+            });
+        }
+        let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+        let mut pool_info = read_registered_pool_info(deps.storage, staking_token, &asset_key)?;
+        pool_info.reward_weight = *reward_weight;
+        store_pool_info(deps.storage, &asset_key, &pool_info)?;
+    }
+
+    Ok(Response::new().add_attribute("action", "update_reward_weights"))
+}
+
+fn update_pool_epoch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_token: Addr,
+    epoch_length: Option<u64>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    // make sure the pool is registered
+    read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+
+    // rewards of the running epoch are finalized before switching the accounting mode
+    let next_epoch_number = match finalize_pool_epoch(deps.storage, &asset_key)? {
+        Some(epoch) => {
+            remove_pool_epoch(deps.storage, &asset_key);
+            epoch.epoch_number + 1
+        }
+        None => 0,
+    };
+
+    let epoch_length = epoch_length.unwrap_or_default();
+    if epoch_length > 0 {
+        store_pool_epoch(
+            deps.storage,
+            &asset_key,
+            &EpochInfo {
+                epoch_length,
+                epoch_number: next_epoch_number,
+                start_time: env.block.time.seconds(),
+                accumulated_reward: Uint128::zero(),
+            },
+        )?;
+    }
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_pool_epoch"),
+        ("staking_token", staking_token.as_str()),
+        ("epoch_length", &epoch_length.to_string()),
+    ]))
+}
+
+// let both the pool staking token and the new one be bondable until the pool is deprecated,
+// rewards are shared across the combined total
+fn start_staking_token_transition(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_token: Addr,
+    new_staking_token: Addr,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?.to_vec();
+    let new_asset_key = deps
+        .api
+        .addr_canonicalize(new_staking_token.as_str())?
+        .to_vec();
+    let mut pool_info = read_pool_info(deps.storage, &asset_key)?;
+
+    if pool_info.staking_token.as_slice() != asset_key.as_slice()
+        || pool_info.migration_params.is_some()
+    {
+        return Err(ContractError::StakingTokenAlreadyMigrated {});
+    }
+    if !pool_info.transition_staking_tokens.is_empty() {
+        return Err(ContractError::StakingTokenTransitioning {});
+    }
+    if read_pool_info(deps.storage, &new_asset_key).is_ok() {
+        return Err(ContractError::StakingTokenRegistered {});
+    }
+
+    pool_info
+        .transition_staking_tokens
+        .push(StakingTokenBucket {
+            staking_token: CanonicalAddr::from(new_asset_key.clone()),
+            total_bond_amount: Uint128::zero(),
+        });
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+    store_pool_alias(deps.storage, &new_asset_key, &asset_key)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "start_staking_token_transition"),
+        ("staking_token", staking_token.as_str()),
+        ("new_staking_token", new_staking_token.as_str()),
+    ]))
+}
+
+fn deprecate_staking_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_token: Addr,
+    new_staking_token: Addr,
+    grace_period: Option<u64>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let grace_period_end = grace_period.map(|grace_period| env.block.time.seconds() + grace_period);
+    _deprecate_staking_token(deps, staking_token, new_staking_token, grace_period_end)
+}
+
+fn _deprecate_staking_token(
+    deps: DepsMut,
+    staking_token: Addr,
+    new_staking_token: Addr,
+    grace_period_end: Option<u64>,
+) -> Result<Response, ContractError> {
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?.to_vec();
+    // rewards of the running epoch belong to the deprecated positions
+    let epoch = finalize_pool_epoch(deps.storage, &asset_key)?;
+    let mut pool_info = read_pool_info(deps.storage, &asset_key)?;
+
+    if pool_info.migration_params.is_some() {
+        return Err(ContractError::StakingTokenAlreadyMigrated {});
+    }
+    if pool_info.staking_token.as_slice() != asset_key.as_slice() {
+        return Err(ContractError::NotCurrentStakingToken {});
+    }
+    let new_asset_key = deps
+        .api
+        .addr_canonicalize(new_staking_token.as_str())?
+        .to_vec();
+    // positions of a deprecated token stay keyed by it, so it can not name a pool again
+    let invalid_new_token = || ContractError::InvalidNewStakingToken {
+        new_staking_token: new_staking_token.to_string(),
+    };
+    if new_asset_key == asset_key {
+        return Err(invalid_new_token());
+    }
+    if read_pool_info(deps.storage, &new_asset_key).is_ok() {
+        return Err(ContractError::AssetRegistered {});
+    }
+    let already_deprecated = read_all_pool_infos(deps.storage)?.iter().any(|(_, other)| {
+        other.migration_params.as_ref().map_or(false, |params| {
+            params.deprecated_staking_token.as_slice() == new_asset_key.as_slice()
+        })
+    });
+    if already_deprecated {
+        return Err(invalid_new_token());
+    }
+    pool_info.symbol = Some(query_staking_token_symbol(
+        deps.as_ref(),
+        &new_staking_token,
+    )?);
+
+    let deprecated_staking_token = pool_info.staking_token;
+    let deprecated_token_addr = deps.api.addr_humanize(&deprecated_staking_token)?;
+    pool_info.staking_token = deps.api.addr_canonicalize(new_staking_token.as_str())?;
+
+    // finalizing a transition: positions of the new token keep earning, the old token becomes unbond-only
+    let mut deprecated_total_bond = pool_info.total_bond_amount;
+    if !pool_info.transition_staking_tokens.is_empty() {
+        let bucket = pool_info
+            .transition_staking_tokens
+            .iter()
+            .find(|bucket| bucket.staking_token.as_slice() == new_asset_key.as_slice())
+            .ok_or(ContractError::TransitionTokenMismatch {})?;
+        deprecated_total_bond = pool_info
+            .total_bond_amount
+            .checked_sub(bucket.total_bond_amount)?;
+        pool_info.total_bond_amount = bucket.total_bond_amount;
+    } else {
+        // the old positions are only counted in deprecated_total_bond, so they take no share of
+        // what the pool distributes to the new token, and their locks go when they unbond
+        pool_info.total_bond_amount = Uint128::zero();
+        pool_info.total_lock_boost = Uint128::zero();
+        pool_info.total_boost_shortfall = Uint128::zero();
+    }
+    for bucket in pool_info.transition_staking_tokens.drain(..) {
+        remove_pool_alias(deps.storage, &bucket.staking_token);
+    }
+
+    // nobody holds the deprecated token, so the pool just takes the new one
+    let has_stakers = stakers_read(deps.storage, &asset_key)
+        .range(None, None, Order::Ascending)
+        .next()
+        .is_some();
+    if has_stakers || !deprecated_total_bond.is_zero() {
+        // the old staking token keeps resolving to the pool while its positions are open, the
+        // positions left under its key settle against the snapshot and unbond the old token
+        store_deprecated_staking_token(deps.storage, &deprecated_staking_token, &new_asset_key)?;
+        store_pool_alias(deps.storage, &deprecated_staking_token, &new_asset_key)?;
+        // mark old pool as migration
+        pool_info.migration_params = Some(MigrationParams {
+            index_snapshot: pool_info.reward_index,
+            deprecated_staking_token,
+            deprecated_total_bond,
+            proxy_index_snapshots: pool_info.proxy_indices(false),
+            grace_period_end,
+        });
+    }
+    // remove old pool
+    remove_pool_info(deps.storage, &asset_key)?;
+    store_pool_info(deps.storage, &new_asset_key, &pool_info)?;
+    // new positions keep the reward assets of the pool
+    if let Ok(rewards_per_sec) = read_rewards_per_sec(deps.storage, &asset_key) {
+        store_rewards_per_sec(deps.storage, &new_asset_key, rewards_per_sec)?;
+    }
+    if let Some(epoch) = epoch {
+        remove_pool_epoch(deps.storage, &asset_key);
+        store_pool_epoch(deps.storage, &new_asset_key, &epoch)?;
+    }
+    let deposits = read_pool_deposits(deps.storage, &asset_key)?;
+    if !deposits.is_empty() {
+        remove_pool_deposits(deps.storage, &asset_key);
+        store_pool_deposits(deps.storage, &new_asset_key, &deposits)?;
+    }
+    move_reward_index_history(deps.storage, &asset_key, Some(&new_asset_key))?;
+    move_staker_lists(deps.storage, &asset_key, Some(&new_asset_key))?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "deprecate_staking_token"),
+        ("staking_token", &staking_token.as_str()),
+        (
+            "deprecated_staking_token",
+            &deprecated_token_addr.to_string(),
+        ),
+        ("new_staking_token", &new_staking_token.to_string()),
+    ]))
+}
+
+// a failed hook is only recorded, its own changes are reverted and the bond change goes through
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match (msg.id, msg.result) {
+        (HOOK_REPLY_ID, SubMsgResult::Err(err)) => {
+            Ok(Response::new().add_attributes([("action", "hook_failed"), ("error", &err)]))
+        }
+        (id, _) => Err(StdError::generic_err(format!("unknown reply id {}", id)).into()),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
+        QueryMsg::State {} => Ok(to_binary(&query_state(deps, env)?)?),
+        QueryMsg::PoolInfo { staking_token } => {
+            Ok(to_binary(&query_pool_info(deps, staking_token)?)?)
+        }
+        QueryMsg::RewardsPerSec { staking_token } => {
+            Ok(to_binary(&query_rewards_per_sec(deps, staking_token)?)?)
+        }
+        QueryMsg::RewardInfo {
+            staker_addr,
+            staking_token,
+        } => Ok(to_binary(&query_reward_info(
+            deps,
+            env,
+            staker_addr,
+            staking_token,
+        )?)?),
+        QueryMsg::RewardInfos {
+            staking_token,
+            start_after,
+            limit,
+            order,
+        } => Ok(to_binary(&query_all_reward_infos(
+            deps,
+            env,
+            staking_token,
+            start_after,
+            limit,
+            order,
+        )?)?),
+        QueryMsg::GetPoolsInformation {} => Ok(to_binary(&query_get_pools_infomation(deps)?)?),
+        QueryMsg::DeprecatedBonds { staking_token } => {
+            Ok(to_binary(&query_deprecated_bonds(deps, staking_token)?)?)
+        }
+        QueryMsg::Reconcile { staking_token } => {
+            Ok(to_binary(&query_reconcile(deps, &env, staking_token)?)?)
+        }
+        QueryMsg::RepairHistory {
+            staking_token,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_repair_history(
+            deps,
+            staking_token,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::MigrationStatus { start_after, limit } => Ok(to_binary(
+            &query_migration_status(deps, start_after, limit)?,
+        )?),
+        QueryMsg::DistributionSchedule {} => {
+            Ok(to_binary(&query_distribution_schedule(deps, env)?)?)
+        }
+        QueryMsg::DistributionInfo {} => Ok(to_binary(&query_distribution_info(deps, env)?)?),
+        QueryMsg::PoolEpoch { staking_token } => {
+            Ok(to_binary(&query_pool_epoch(deps, env, staking_token)?)?)
+        }
+        QueryMsg::PoolRewardRate { staking_token } => Ok(to_binary(&query_pool_reward_rate(
+            deps,
+            env,
+            staking_token,
+        )?)?),
+        QueryMsg::SimulateBond {
+            staking_token,
+            amount,
+        } => Ok(to_binary(&query_simulate_bond(
+            deps,
+            env,
+            staking_token,
+            amount,
+        )?)?),
+        QueryMsg::SimulateWithdraw {
+            staker_addr,
+            staking_token,
+        } => Ok(to_binary(&query_simulate_withdraw(
+            deps,
+            env,
+            staker_addr,
+            staking_token,
+        )?)?),
+        QueryMsg::Operator { staker_addr } => Ok(to_binary(&query_operator(deps, staker_addr)?)?),
+        QueryMsg::CanBond {
+            staking_token,
+            staker_addr,
+        } => Ok(to_binary(&query_can_bond(
+            deps,
+            staking_token,
+            staker_addr,
+        )?)?),
+        QueryMsg::ExportState {
+            segment,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_export_state(
+            deps,
+            segment,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::ReferralStats {
+            referrer,
+            start_after,
+            limit,
+            order,
+        } => Ok(to_binary(&query_referral_stats(
+            deps,
+            referrer,
+            start_after,
+            limit,
+            order,
+        )?)?),
+        QueryMsg::Unbonding {
+            staker_addr,
+            staking_token,
+        } => Ok(to_binary(&query_unbonding(
+            deps,
+            env,
+            staker_addr,
+            staking_token,
+        )?)?),
+        QueryMsg::QueryOldStore { store_type } => Ok(query_old_store(deps, store_type)?),
+        QueryMsg::ContractInfo {} => Ok(to_binary(&query_contract_info(deps)?)?),
+        QueryMsg::StakerInfo {
+            staker_addr,
+            staking_token,
+        } => Ok(to_binary(&query_staker_info(
+            deps,
+            env,
+            staker_addr,
+            staking_token,
+        )?)?),
+        QueryMsg::UnbondingClaims {
+            staker_addr,
+            staking_token,
+            start_after,
+            limit,
+            order,
+        } => Ok(to_binary(&query_unbonding_claims(
+            deps,
+            env,
+            staker_addr,
+            staking_token,
+            start_after,
+            limit,
+            order,
+        )?)?),
+        QueryMsg::Stakers {
+            staking_token,
+            start_after,
+            limit,
+            order,
+        } => Ok(to_binary(&query_stakers(
+            deps,
+            staking_token,
+            start_after,
+            limit,
+            order,
+        )?)?),
+        QueryMsg::StaleStakers {
+            staking_token,
+            older_than,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_stale_stakers(
+            deps,
+            staking_token,
+            older_than,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::StakerRewardInfos {
+            staker_addr,
+            start_after,
+            limit,
+            order,
+        } => Ok(to_binary(&query_staker_reward_infos(
+            deps,
+            env,
+            staker_addr,
+            start_after,
+            limit,
+            order,
+        )?)?),
+        QueryMsg::BondAmountAt {
+            staker_addr,
+            staking_token,
+            height,
+        } => Ok(to_binary(&query_bond_amount_at(
+            deps,
+            staker_addr,
+            staking_token,
+            height,
+        )?)?),
+        QueryMsg::RewardIndexHistory {
+            staking_token,
+            start_after_time,
+            limit,
+        } => Ok(to_binary(&query_reward_index_history(
+            deps,
+            staking_token,
+            start_after_time,
+            limit,
+        )?)?),
+        QueryMsg::BatchRewardInfo {
+            stakers,
+            staking_token,
+        } => Ok(to_binary(&query_batch_reward_info(
+            deps,
+            env,
+            stakers,
+            staking_token,
+        )?)?),
+        QueryMsg::PoolInfos {
+            start_after,
+            limit,
+            order,
+        } => Ok(to_binary(&query_pool_infos(
+            deps,
+            start_after,
+            limit,
+            order,
+        )?)?),
+        QueryMsg::OrphanedRewards {
+            start_after,
+            limit,
+            order,
+        } => Ok(to_binary(&query_orphaned_rewards(
+            deps,
+            start_after,
+            limit,
+            order,
+        )?)?),
+        QueryMsg::PoolInfoByStakingToken { staking_token } => Ok(to_binary(
+            &query_pool_info_by_staking_token(deps, staking_token)?,
+        )?),
+        QueryMsg::RewardInfoByStakingToken {
+            staker_addr,
+            staking_token,
+        } => Ok(to_binary(&query_reward_info_by_staking_token(
+            deps,
+            env,
+            staker_addr,
+            staking_token,
+        )?)?),
+        QueryMsg::MigrationProgress {} => Ok(to_binary(&query_migration_progress(deps)?)?),
     }
 }
 
-pub fn update_config(
-    deps: DepsMut,
-    info: MessageInfo,
-    owner: Option<Addr>,
-    rewarder: Option<Addr>,
-    migrate_store_status: Option<bool>,
-) -> StdResult<Response> {
-    let mut config = read_config(deps.storage)?;
-
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
-    }
-
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(owner.as_str())?;
-    }
-
-    if let Some(rewarder) = rewarder {
-        config.rewarder = deps.api.addr_canonicalize(rewarder.as_str())?;
-    }
-
-    if let Some(migrate_store_status) = migrate_store_status {
-        store_finish_migrate_store_status(deps.storage, migrate_store_status)?;
-    }
+pub fn query_migration_progress(deps: Deps) -> StdResult<MigrationProgressResponse> {
+    let cursor = read_migration_cursor(deps.storage)?;
+    Ok(MigrationProgressResponse {
+        migrating: cursor.is_some(),
+        migrated_pools: cursor.as_ref().map_or(0, |cursor| cursor.migrated_pools),
+        migrated_stakers: cursor.as_ref().map_or(0, |cursor| cursor.migrated_stakers),
+        legacy_pools: old_read_all_pool_info_keys(deps.storage)?.len() as u64,
+    })
+}
 
-    store_config(deps.storage, &config)?;
-    Ok(Response::new().add_attribute("action", "update_config"))
+pub fn query_contract_info(deps: Deps) -> StdResult<ContractInfoResponse> {
+    let contract_version = get_contract_version(deps.storage)?;
+    Ok(ContractInfoResponse {
+        name: contract_version.contract,
+        version: contract_version.version,
+    })
 }
 
-// need to withdraw all rewards of the stakers belong to the pool
-// may need to call withdraw from backend side by querying all stakers with pagination in case out of gas
-fn update_rewards_per_sec(
-    deps: DepsMut,
-    info: MessageInfo,
-    staking_token: Addr,
-    assets: Vec<Asset>,
-) -> StdResult<Response> {
-    validate_migrate_store_status(deps.storage)?;
-    let config: Config = read_config(deps.storage)?;
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let state = read_config(deps.storage)?;
+    let proposal = read_ownership_proposal(deps.storage)?;
+    let resp = ConfigResponse {
+        owner: deps.api.addr_humanize(&state.owner)?,
+        rewarder: deps.api.addr_humanize(&state.rewarder)?,
+        oracle_addr: deps.api.addr_humanize(&state.oracle_addr)?,
+        factory_addr: deps.api.addr_humanize(&state.factory_addr)?,
+        base_denom: state.base_denom,
+        paused: state.paused,
+        pending_owner: proposal
+            .as_ref()
+            .map(|proposal| deps.api.addr_humanize(&proposal.owner))
+            .transpose()?,
+        pending_owner_expires_at: proposal.map(|proposal| proposal.expires_at),
+        penalty_collector: state
+            .penalty_collector
+            .map(|collector| deps.api.addr_humanize(&collector))
+            .transpose()?,
+        reward_distributors: state
+            .reward_distributors
+            .iter()
+            .map(|distributor| deps.api.addr_humanize(distributor))
+            .collect::<StdResult<_>>()?,
+        withdraw_fee_rate: state.withdraw_fee_rate,
+        fee_collector: state
+            .fee_collector
+            .map(|collector| deps.api.addr_humanize(&collector))
+            .transpose()?,
+        bond_checkpoint_retention: state.bond_checkpoint_retention,
+        dust_threshold: state.dust_threshold,
+        boost_contract: state
+            .boost_contract
+            .map(|contract| deps.api.addr_humanize(&contract))
+            .transpose()?,
+        hooks: state
+            .hooks
+            .iter()
+            .map(|hook| deps.api.addr_humanize(hook))
+            .collect::<StdResult<_>>()?,
+        community_pool: state
+            .community_pool
+            .map(|community_pool| deps.api.addr_humanize(&community_pool))
+            .transpose()?,
+    };
 
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
-    }
+    Ok(resp)
+}
 
-    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?.to_vec();
+pub fn query_state(deps: Deps, env: Env) -> StdResult<StateResponse> {
+    let state = read_state(deps.storage)?.unwrap_or_default();
 
-    // withdraw all rewards for all stakers from this pool
-    let staker_addrs = stakers_read(deps.storage, &asset_key)
-        .range(None, None, Order::Ascending)
-        .map(|item| {
-            let (k, _) = item?;
-            Ok(CanonicalAddr::from(k))
+    // one balance query per reward asset still owed, however many pools pay it
+    let total_outstanding_rewards = state
+        .total_outstanding_rewards
+        .iter()
+        .map(|ra| ra.to_normal(deps.api))
+        .collect::<StdResult<Vec<Asset>>>()?;
+    let reward_reserves = total_outstanding_rewards
+        .iter()
+        .map(|asset| {
+            Ok(Asset {
+                info: asset.info.clone(),
+                amount: asset
+                    .info
+                    .query_pool(&deps.querier, env.contract.address.clone())?,
+            })
         })
-        .collect::<StdResult<Vec<CanonicalAddr>>>()?;
-
-    // let mut messages: Vec<CosmosMsg> = vec![];
+        .collect::<StdResult<Vec<Asset>>>()?;
 
-    // withdraw reward for each staker
-    for staker_addr_raw in staker_addrs {
-        process_reward_assets(
-            deps.storage,
-            &staker_addr_raw,
-            &Some(asset_key.clone()),
-            false,
-        )?;
-    }
+    Ok(StateResponse {
+        pool_count: state.pool_count,
+        total_deposited: state.total_deposited,
+        total_scheduled: state.total_scheduled,
+        total_withdrawn: state.total_withdrawn,
+        total_outstanding_rewards,
+        reward_reserves,
+        pending_reward: state.total_pending_reward,
+        last_deposit_time: state.last_deposit_time,
+    })
+}
 
-    // convert assets to raw_assets
-    let raw_assets = assets
-        .into_iter()
-        .map(|w| Ok(w.to_raw(deps.api)?))
-        .collect::<StdResult<Vec<AssetRaw>>>()?;
+pub fn query_pool_info(deps: Deps, staking_token: Addr) -> StdResult<PoolInfoResponse> {
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let pool_info = read_pool_info(deps.storage, &asset_key)?;
+    pool_info_response(deps, pool_info)
+}
 
-    store_rewards_per_sec(deps.storage, &asset_key, raw_assets)?;
+pub fn query_pool_infos(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    order: Option<i32>,
+) -> StdResult<Vec<PoolInfoResponse>> {
+    let order = parse_order(order)?;
+    let start_after = start_after
+        .map(|staking_token| staking_token_key(deps.storage, deps.api, staking_token.as_str()))
+        .transpose()?
+        .map(|key| key.to_vec());
+    let limit = page_limit(limit, DEFAULT_POOL_INFOS_LIMIT, MAX_POOL_INFOS_LIMIT);
 
-    Ok(Response::new().add_attribute("action", "update_rewards_per_sec"))
+    read_range_prefixed(deps.storage, &[PREFIX_POOL_INFO], start_after, limit, order)?
+        .into_iter()
+        .map(|(_, pool_info)| pool_info_response(deps, pool_info))
+        .collect()
 }
 
-fn register_asset(deps: DepsMut, info: MessageInfo, staking_token: Addr) -> StdResult<Response> {
-    validate_migrate_store_status(deps.storage)?;
-    let config: Config = read_config(deps.storage)?;
-
-    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
-        return Err(StdError::generic_err("unauthorized"));
-    }
+pub fn query_orphaned_rewards(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    order: Option<i32>,
+) -> StdResult<Vec<OrphanedRewardResponse>> {
+    let order = parse_order(order)?;
+    let start_after = start_after
+        .map(|staking_token| staking_token_key(deps.storage, deps.api, staking_token.as_str()))
+        .transpose()?
+        .map(|key| key.to_vec());
+    let limit = page_limit(limit, DEFAULT_POOL_INFOS_LIMIT, MAX_POOL_INFOS_LIMIT);
 
-    // query asset_key from AssetInfo
-    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?;
-    if read_pool_info(deps.storage, &asset_key).is_ok() {
-        return Err(StdError::generic_err("Asset was already registered"));
-    }
+    read_range_prefixed::<PoolInfo>(deps.storage, &[PREFIX_POOL_INFO], start_after, limit, order)?
+        .into_iter()
+        .map(|(key, pool_info)| {
+            Ok(OrphanedRewardResponse {
+                staking_token: staking_token_addr(deps.storage, deps.api, &key)?,
+                orphaned_reward: pool_info.orphaned_reward.min(pool_info.pending_reward),
+                pending_reward: pool_info.pending_reward,
+                total_bond_amount: pool_info.total_bond_amount,
+            })
+        })
+        .collect()
+}
 
-    store_pool_info(
-        deps.storage,
-        &asset_key.clone(),
-        &PoolInfo {
-            staking_token: asset_key,
-            total_bond_amount: Uint128::zero(),
-            reward_index: Decimal::zero(),
-            pending_reward: Uint128::zero(),
-            migration_params: None,
-        },
-    )?;
+fn pool_info_response(deps: Deps, pool_info: PoolInfo) -> StdResult<PoolInfoResponse> {
+    Ok(PoolInfoResponse {
+        staking_token: staking_token_addr(deps.storage, deps.api, &pool_info.staking_token)?,
+        total_bond_amount: pool_info.total_bond_amount,
+        reward_index: index_to_decimal(pool_info.reward_index)?,
+        pending_reward: pool_info.pending_reward,
+        // a malformed stored address fails the query instead of aborting it
+        migration_deprecated_staking_token: pool_info
+            .migration_params
+            .as_ref()
+            .map(|params| deps.api.addr_humanize(&params.deprecated_staking_token))
+            .transpose()?,
+        migration_index_snapshot: pool_info
+            .migration_params
+            .as_ref()
+            .map(|params| index_to_decimal(params.index_snapshot))
+            .transpose()?,
+        migration_deprecated_total_bond: pool_info
+            .migration_params
+            .as_ref()
+            .map(|params| params.deprecated_total_bond),
+        migration_grace_period_end: pool_info
+            .migration_params
+            .as_ref()
+            .and_then(|params| params.grace_period_end),
+        frozen: pool_info.frozen,
+        instant_unbond_penalty: pool_info.instant_unbond_penalty,
+        min_bond_amount: pool_info.min_bond_amount,
+        min_compound_reward: pool_info.min_compound_reward,
+        bond_cap: pool_info.bond_cap,
+        remaining_bond_capacity: pool_info.remaining_bond_capacity(),
+        max_bond_per_staker: pool_info.max_bond_per_staker,
+        total_weighted_bond: pool_info.total_weighted_bond(),
+        lock_tiers: pool_info.lock_tiers,
+        reward_weight: pool_info.reward_weight,
+        reward_start_time: pool_info.reward_start_time,
+        reward_end_time: pool_info.reward_end_time,
+        proxy_rewards: pool_info
+            .proxy_rewards
+            .into_iter()
+            .map(|proxy| {
+                Ok(ProxyRewardResponse {
+                    reward_token: deps.api.addr_humanize(&proxy.reward_token)?,
+                    proxy_index: proxy.proxy_index,
+                    pending: proxy.pending,
+                })
+            })
+            .collect::<StdResult<_>>()?,
+        symbol: pool_info.symbol,
+        staker_count: pool_info.staker_count,
+        lifetime_bonded: pool_info.lifetime_bonded,
+        lifetime_unbonded: pool_info.lifetime_unbonded,
+        lifetime_rewards_deposited: pool_info.lifetime_rewards_deposited,
+        lifetime_rewards_claimed: pool_info.lifetime_rewards_claimed,
+        unbonding_period: pool_info.unbonding_period,
+        whitelist_enabled: pool_info.whitelist_enabled,
+        unbond_cooldown: pool_info.unbond_cooldown,
+        max_index_increase_per_deposit: pool_info.max_index_increase_per_deposit,
+        reward_stream_seconds: pool_info.reward_stream_seconds,
+        streaming_reward: pool_info.streaming_reward(),
+        deposit_epoch: pool_info.deposit_epoch,
+    })
+}
 
-    Ok(Response::new().add_attributes([
-        ("action", "register_asset"),
-        ("staking_token", staking_token.as_str()),
-    ]))
+// resolves the pool of any staking token it accepts, deprecated ones included
+pub fn query_pool_info_by_staking_token(
+    deps: Deps,
+    staking_token: Addr,
+) -> Result<PoolInfoByStakingTokenResponse, ContractError> {
+    let (pool_token, deprecated) = resolve_staking_token(deps, &staking_token)?;
+    Ok(PoolInfoByStakingTokenResponse {
+        pool_info: query_pool_info(deps, pool_token)?,
+        deprecated,
+    })
 }
 
-fn deprecate_staking_token(
-    deps: DepsMut,
-    info: MessageInfo,
+pub fn query_reward_info_by_staking_token(
+    deps: Deps,
+    env: Env,
+    staker_addr: Addr,
     staking_token: Addr,
-    new_staking_token: Addr,
-) -> StdResult<Response> {
-    validate_migrate_store_status(deps.storage)?;
-    let config: Config = read_config(deps.storage)?;
+) -> Result<RewardInfoByStakingTokenResponse, ContractError> {
+    let (pool_token, deprecated) = resolve_staking_token(deps, &staking_token)?;
+    // positions left on a deprecated token stay keyed by it, migrated ones moved to the pool
+    let mut reward_info =
+        query_reward_info(deps, env.clone(), staker_addr.clone(), Some(staking_token))?;
+    if deprecated && reward_info.reward_infos.is_empty() {
+        reward_info = query_reward_info(deps, env, staker_addr, Some(pool_token))?;
+    }
+    Ok(RewardInfoByStakingTokenResponse {
+        reward_info,
+        deprecated,
+    })
+}
 
-    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
-        return Err(StdError::generic_err("unauthorized"));
+// the staking token of the pool bonded with the token, true when the token is the deprecated
+// one the pool took over from
+fn resolve_staking_token(deps: Deps, staking_token: &Addr) -> Result<(Addr, bool), ContractError> {
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    // the deprecated and transition tokens of a pool resolve to it through its aliases
+    if let Ok(pool_info) = read_pool_info(deps.storage, &asset_key) {
+        let deprecated = pool_info.migration_params.map_or(false, |params| {
+            params.deprecated_staking_token.as_slice() == asset_key.as_slice()
+        });
+        let pool_token = staking_token_addr(deps.storage, deps.api, &pool_info.staking_token)?;
+        return Ok((pool_token, deprecated));
     }
+    let pool_key = read_deprecated_staking_token(deps.storage, &asset_key)?.ok_or_else(|| {
+        ContractError::NoPoolForStakingToken {
+            staking_token: staking_token.to_string(),
+        }
+    })?;
+    Ok((staking_token_addr(deps.storage, deps.api, &pool_key)?, true))
+}
 
-    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?.to_vec();
-    let mut pool_info = read_pool_info(deps.storage, &asset_key)?;
+pub fn query_deprecated_bonds(
+    deps: Deps,
+    staking_token: Addr,
+) -> Result<DeprecatedBondsResponse, ContractError> {
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?.to_vec();
+    let pool_info = read_pool_info(deps.storage, &asset_key)?;
+    let params = pool_info
+        .migration_params
+        .ok_or(ContractError::StakingTokenNotDeprecated {})?;
 
-    if pool_info.migration_params.is_some() {
-        return Err(StdError::generic_err(
-            "This asset LP token has already been migrated",
-        ));
+    // old positions either stay under the deprecated key or, when carried over by the
+    // store migration, live under the pool key without being marked as migrated
+    let mut stakers: Vec<Vec<u8>> = vec![];
+    for key in [params.deprecated_staking_token.as_slice(), &asset_key] {
+        for item in stakers_read(deps.storage, key).range(None, None, Order::Ascending) {
+            let (staker, _) = item?;
+            if key == asset_key.as_slice() && read_is_migrated(deps.storage, key, &staker) {
+                continue;
+            }
+            if let Some(reward_info) = rewards_read(deps.storage, &staker).may_load(key)? {
+                if !reward_info.bond_amount.is_zero() {
+                    stakers.push(staker);
+                }
+            }
+        }
     }
-    let deprecated_staking_token = pool_info.staking_token;
-    let deprecated_token_addr = deps.api.addr_humanize(&deprecated_staking_token)?;
+    stakers.sort();
+    stakers.dedup();
 
-    pool_info.staking_token = deps.api.addr_canonicalize(new_staking_token.as_str())?;
+    Ok(DeprecatedBondsResponse {
+        staking_token,
+        deprecated_staking_token: deps.api.addr_humanize(&params.deprecated_staking_token)?,
+        deprecated_total_bond: params.deprecated_total_bond,
+        staker_count: stakers.len() as u64,
+    })
+}
 
-    // mark old pool as migration
-    pool_info.migration_params = Some(MigrationParams {
-        index_snapshot: pool_info.reward_index,
-        deprecated_staking_token,
-    });
-    let new_asset_key = deps
-        .api
-        .addr_canonicalize(new_staking_token.as_str())?
-        .to_vec();
-    // remove old pool
-    remove_pool_info(deps.storage, &asset_key);
-    store_pool_info(deps.storage, &new_asset_key, &pool_info)?;
+// the amounts of the staking token are split by the key the positions bonded it under, a
+// transition token only has its bucket while the pool token has the rest of the pool total
+pub fn query_can_bond(
+    deps: Deps,
+    staking_token: Addr,
+    staker_addr: Addr,
+) -> Result<CanBondResponse, ContractError> {
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    let staker_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
 
-    Ok(Response::new().add_attributes([
-        ("action", "depcrecate_staking_token"),
-        ("staking_token", &staking_token.as_str()),
-        (
-            "deprecated_staking_token",
-            &deprecated_token_addr.to_string(),
+    Ok(CanBondResponse {
+        can_bond: may_bond(deps.storage, &asset_key, &pool_info, &staker_raw),
+        whitelist_enabled: pool_info.whitelist_enabled,
+        whitelisted: is_on_staker_list(
+            deps.storage,
+            PREFIX_POOL_WHITELIST,
+            &asset_key,
+            &staker_raw,
         ),
-        ("new_staking_token", &new_staking_token.to_string()),
-    ]))
+        blacklisted: is_on_staker_list(
+            deps.storage,
+            PREFIX_POOL_BLACKLIST,
+            &asset_key,
+            &staker_raw,
+        ),
+    })
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::PoolInfo { staking_token } => to_binary(&query_pool_info(deps, staking_token)?),
-        QueryMsg::RewardsPerSec { staking_token } => {
-            to_binary(&query_rewards_per_sec(deps, staking_token)?)
-        }
-        QueryMsg::RewardInfo {
-            staker_addr,
-            staking_token,
-        } => to_binary(&query_reward_info(deps, staker_addr, staking_token)?),
-        QueryMsg::RewardInfos {
-            staking_token,
-            start_after,
+pub fn query_export_state(
+    deps: Deps,
+    segment: ExportSegment,
+    start_after: Option<Binary>,
+    limit: Option<u32>,
+) -> StdResult<ExportStateResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_EXPORT_LIMIT)
+        .clamp(1, MAX_EXPORT_LIMIT) as usize;
+    let start_after = start_after.map(|key| key.to_vec());
+
+    let records = match segment {
+        ExportSegment::Pools => read_pool_infos(
+            deps.storage,
+            calc_range_start(start_after).as_deref(),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (key, pool_info) = item?;
+            let rewards_per_sec = read_rewards_per_sec(deps.storage, &key)
+                .unwrap_or_default()
+                .iter()
+                .map(|asset| asset.to_normal(deps.api))
+                .collect::<StdResult<Vec<Asset>>>()?;
+            Ok(ExportRecord::Pool {
+                key: key.into(),
+                reward_index: pool_info.reward_index,
+                pool_info: pool_info_response(deps, pool_info)?,
+                rewards_per_sec,
+            })
+        })
+        .collect::<StdResult<Vec<ExportRecord>>>()?,
+        ExportSegment::RewardInfos => read_staker_records::<RewardInfo>(
+            deps.storage,
+            PREFIX_REWARD,
+            start_after.as_deref(),
             limit,
-            order,
-        } => to_binary(&query_all_reward_infos(
-            deps,
-            staking_token,
-            start_after,
+        )?
+        .into_iter()
+        .map(|(key, staker, asset_key, reward_info)| {
+            Ok(ExportRecord::RewardInfo {
+                key: key.into(),
+                staker_addr: deps.api.addr_humanize(&staker)?,
+                staking_token: staking_token_addr(deps.storage, deps.api, &asset_key)?,
+                reward_info: RewardInfoExport {
+                    native_token: reward_info.native_token,
+                    index: reward_info.index,
+                    bond_amount: reward_info.bond_amount,
+                    pending_reward: reward_info.pending_reward,
+                    pending_withdraw: reward_info
+                        .pending_withdraw
+                        .iter()
+                        .map(|asset| asset.to_normal(deps.api))
+                        .collect::<StdResult<_>>()?,
+                    locks: reward_info.locks,
+                    proxy_indices: reward_info
+                        .proxy_indices
+                        .iter()
+                        .map(|(reward_token, index)| {
+                            Ok((deps.api.addr_humanize(reward_token)?, *index))
+                        })
+                        .collect::<StdResult<_>>()?,
+                    total_claimed: reward_info.total_claimed,
+                    boost_shortfall: reward_info.boost_shortfall,
+                    last_updated: reward_info.last_updated,
+                    last_bond_time: reward_info.last_bond_time,
+                },
+            })
+        })
+        .collect::<StdResult<Vec<ExportRecord>>>()?,
+        ExportSegment::UnbondingClaims => read_staker_records::<Vec<UnbondingEntry>>(
+            deps.storage,
+            PREFIX_UNBONDING,
+            start_after.as_deref(),
             limit,
-            order,
-        )?),
-        QueryMsg::GetPoolsInformation {} => to_binary(&query_get_pools_infomation(deps)?),
-        QueryMsg::QueryOldStore { store_type } => query_old_store(deps, store_type),
-    }
-}
+        )?
+        .into_iter()
+        .map(|(key, staker, asset_key, entries)| {
+            Ok(ExportRecord::UnbondingClaims {
+                key: key.into(),
+                staker_addr: deps.api.addr_humanize(&staker)?,
+                staking_token: staking_token_addr(deps.storage, deps.api, &asset_key)?,
+                entries,
+            })
+        })
+        .collect::<StdResult<Vec<ExportRecord>>>()?,
+    };
 
-pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
-    let state = read_config(deps.storage)?;
-    let resp = ConfigResponse {
-        owner: deps.api.addr_humanize(&state.owner)?,
-        rewarder: deps.api.addr_humanize(&state.rewarder)?,
-        oracle_addr: deps.api.addr_humanize(&state.oracle_addr)?,
-        factory_addr: deps.api.addr_humanize(&state.factory_addr)?,
-        base_denom: state.base_denom,
+    // a short page is the end of the segment
+    let next_key = if records.len() < limit {
+        None
+    } else {
+        records.last().map(|record| match record {
+            ExportRecord::Pool { key, .. }
+            | ExportRecord::RewardInfo { key, .. }
+            | ExportRecord::UnbondingClaims { key, .. } => key.clone(),
+        })
     };
 
-    Ok(resp)
+    Ok(ExportStateResponse { records, next_key })
 }
 
-pub fn query_pool_info(deps: Deps, staking_token: Addr) -> StdResult<PoolInfoResponse> {
-    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?;
-    let pool_info = read_pool_info(deps.storage, &asset_key)?;
-    Ok(PoolInfoResponse {
-        staking_token: deps.api.addr_humanize(&pool_info.staking_token)?,
-        total_bond_amount: pool_info.total_bond_amount,
-        reward_index: pool_info.reward_index,
-        pending_reward: pool_info.pending_reward,
-        migration_deprecated_staking_token: pool_info.migration_params.clone().map(|params| {
-            deps.api
-                .addr_humanize(&params.deprecated_staking_token)
-                .unwrap()
-        }),
-        migration_index_snapshot: pool_info
-            .migration_params
-            .map(|params| params.index_snapshot),
+pub fn query_reconcile(
+    deps: Deps,
+    env: &Env,
+    staking_token: Addr,
+) -> Result<ReconcileResponse, ContractError> {
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?.to_vec();
+    let pool_info =
+        read_pool_info(deps.storage, &asset_key).map_err(|_| ContractError::PoolNotFound {
+            asset: staking_token.to_string(),
+        })?;
+
+    let transition_bond: Uint128 = pool_info
+        .transition_staking_tokens
+        .iter()
+        .map(|bucket| bucket.total_bond_amount)
+        .sum();
+    let total_bond_amount = match pool_info
+        .transition_staking_tokens
+        .iter()
+        .find(|bucket| bucket.staking_token.as_slice() == asset_key.as_slice())
+    {
+        Some(bucket) => bucket.total_bond_amount,
+        None if pool_info.staking_token.as_slice() == asset_key.as_slice() => {
+            pool_info.total_bond_amount.saturating_sub(transition_bond)
+        }
+        None => Uint128::zero(),
+    };
+    let deprecated_total_bond = match &pool_info.migration_params {
+        Some(params) if params.deprecated_staking_token.as_slice() == asset_key.as_slice() => {
+            params.deprecated_total_bond
+        }
+        _ => Uint128::zero(),
+    };
+    let total_unbonding = read_total_unbonding(deps.storage, &asset_key)?;
+
+    let balance = if is_native_staking_token(deps.storage, &asset_key) {
+        deps.querier
+            .query_balance(env.contract.address.clone(), staking_token.as_str())?
+            .amount
+    } else {
+        query_token_balance(
+            &deps.querier,
+            staking_token.clone(),
+            env.contract.address.clone(),
+        )?
+    };
+    let surplus = balance.saturating_sub(
+        total_bond_amount
+            .checked_add(deprecated_total_bond)?
+            .checked_add(total_unbonding)?,
+    );
+
+    Ok(ReconcileResponse {
+        staking_token,
+        balance,
+        total_bond_amount,
+        deprecated_total_bond,
+        total_unbonding,
+        surplus,
     })
 }
 
+pub fn query_migration_status(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PoolMigrationStatus>> {
+    let start_after = start_after
+        .map(|staking_token| staking_token_key(deps.storage, deps.api, staking_token.as_str()))
+        .transpose()?
+        .map(|key| key.to_vec());
+    let limit = limit
+        .unwrap_or(DEFAULT_POOL_INFOS_LIMIT)
+        .min(MAX_POOL_INFOS_LIMIT) as usize;
+
+    let start = calc_range_start(start_after);
+    let mut statuses = vec![];
+    for item in read_pool_infos(deps.storage, start.as_deref(), None, Order::Ascending) {
+        if statuses.len() == limit {
+            break;
+        }
+        let (_, pool_info) = item?;
+        if let Some(params) = pool_info.migration_params {
+            statuses.push(PoolMigrationStatus {
+                staking_token: staking_token_addr(
+                    deps.storage,
+                    deps.api,
+                    &pool_info.staking_token,
+                )?,
+                deprecated_staking_token: deps
+                    .api
+                    .addr_humanize(&params.deprecated_staking_token)?,
+                index_snapshot: index_to_decimal(params.index_snapshot)?,
+                deprecated_total_bond: params.deprecated_total_bond,
+            });
+        }
+    }
+
+    Ok(statuses)
+}
+
 pub fn query_rewards_per_sec(deps: Deps, staking_token: Addr) -> StdResult<RewardsPerSecResponse> {
-    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?.to_vec();
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?.to_vec();
 
     let raw_assets = read_rewards_per_sec(deps.storage, &asset_key)?;
 
@@ -370,24 +3420,32 @@ pub fn query_rewards_per_sec(deps: Deps, staking_token: Addr) -> StdResult<Rewar
         .map(|w| Ok(w.to_normal(deps.api)?))
         .collect::<StdResult<Vec<Asset>>>()?;
 
-    Ok(RewardsPerSecResponse { assets })
+    let reward_decimals = match read_pool_info(deps.storage, &asset_key) {
+        Ok(pool_info) => read_asset_decimals(deps.api, &pool_info, &assets)?,
+        Err(_) => vec![],
+    };
+
+    Ok(RewardsPerSecResponse {
+        assets,
+        reward_decimals,
+    })
 }
 
 pub fn parse_read_all_pool_infos(
+    storage: &dyn Storage,
     api: &dyn Api,
     pool_infos: Vec<(Vec<u8>, PoolInfo)>,
 ) -> StdResult<Vec<QueryPoolInfoResponse>> {
     pool_infos
         .into_iter()
         .map(|(key, pool_info)| {
-            let asset_key = CanonicalAddr::from(key);
-            let staking_token = api.addr_humanize(&asset_key)?;
+            let staking_token = staking_token_addr(storage, api, &key)?;
             Ok(QueryPoolInfoResponse {
                 asset_key: staking_token.to_string(),
                 pool_info: PoolInfoResponse {
                     staking_token,
                     total_bond_amount: pool_info.total_bond_amount,
-                    reward_index: pool_info.reward_index,
+                    reward_index: index_to_decimal(pool_info.reward_index)?,
                     pending_reward: pool_info.pending_reward,
                     migration_deprecated_staking_token: pool_info
                         .migration_params
@@ -398,7 +3456,53 @@ pub fn parse_read_all_pool_infos(
                         .transpose()?,
                     migration_index_snapshot: pool_info
                         .migration_params
-                        .map(|params| params.index_snapshot),
+                        .as_ref()
+                        .map(|params| index_to_decimal(params.index_snapshot))
+                        .transpose()?,
+                    migration_deprecated_total_bond: pool_info
+                        .migration_params
+                        .as_ref()
+                        .map(|params| params.deprecated_total_bond),
+                    migration_grace_period_end: pool_info
+                        .migration_params
+                        .as_ref()
+                        .and_then(|params| params.grace_period_end),
+                    frozen: pool_info.frozen,
+                    instant_unbond_penalty: pool_info.instant_unbond_penalty,
+                    min_bond_amount: pool_info.min_bond_amount,
+                    min_compound_reward: pool_info.min_compound_reward,
+                    bond_cap: pool_info.bond_cap,
+                    remaining_bond_capacity: pool_info.remaining_bond_capacity(),
+                    max_bond_per_staker: pool_info.max_bond_per_staker,
+                    total_weighted_bond: pool_info.total_weighted_bond(),
+                    lock_tiers: pool_info.lock_tiers,
+                    reward_weight: pool_info.reward_weight,
+                    reward_start_time: pool_info.reward_start_time,
+                    reward_end_time: pool_info.reward_end_time,
+                    proxy_rewards: pool_info
+                        .proxy_rewards
+                        .into_iter()
+                        .map(|proxy| {
+                            Ok(ProxyRewardResponse {
+                                reward_token: api.addr_humanize(&proxy.reward_token)?,
+                                proxy_index: proxy.proxy_index,
+                                pending: proxy.pending,
+                            })
+                        })
+                        .collect::<StdResult<_>>()?,
+                    symbol: pool_info.symbol,
+                    staker_count: pool_info.staker_count,
+                    lifetime_bonded: pool_info.lifetime_bonded,
+                    lifetime_unbonded: pool_info.lifetime_unbonded,
+                    lifetime_rewards_deposited: pool_info.lifetime_rewards_deposited,
+                    lifetime_rewards_claimed: pool_info.lifetime_rewards_claimed,
+                    unbonding_period: pool_info.unbonding_period,
+                    whitelist_enabled: pool_info.whitelist_enabled,
+                    unbond_cooldown: pool_info.unbond_cooldown,
+                    max_index_increase_per_deposit: pool_info.max_index_increase_per_deposit,
+                    reward_stream_seconds: pool_info.reward_stream_seconds,
+                    streaming_reward: pool_info.streaming_reward(),
+                    deposit_epoch: pool_info.deposit_epoch,
                 },
             })
         })
@@ -407,14 +3511,14 @@ pub fn parse_read_all_pool_infos(
 
 pub fn query_get_pools_infomation(deps: Deps) -> StdResult<Vec<QueryPoolInfoResponse>> {
     let pool_infos = read_all_pool_infos(deps.storage)?;
-    parse_read_all_pool_infos(deps.api, pool_infos)
+    parse_read_all_pool_infos(deps.storage, deps.api, pool_infos)
 }
 
 pub fn query_old_store(deps: Deps, old_store_type: OldStoreType) -> StdResult<Binary> {
     match old_store_type {
         OldStoreType::Pools {} => {
             let old_pool_infos = old_read_all_pool_infos(deps.storage)?;
-            let all_pools = parse_read_all_pool_infos(deps.api, old_pool_infos)?;
+            let all_pools = parse_read_all_pool_infos(deps.storage, deps.api, old_pool_infos)?;
             to_binary(&all_pools)
         }
         OldStoreType::Stakers { asset_info } => {
@@ -451,17 +3555,162 @@ pub fn query_old_store(deps: Deps, old_store_type: OldStoreType) -> StdResult<Bi
 
 // migrate contract
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    store_finish_migrate_store_status(deps.storage, false)?;
-    Ok(Response::default())
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    // stores written before the version was kept have no contract info yet
+    let stored_version = match get_contract_version(deps.storage) {
+        Ok(stored) => {
+            if stored.contract != CONTRACT_NAME
+                || parse_version(&stored.version) > parse_version(CONTRACT_VERSION)
+            {
+                return Err(ContractError::ContractVersionMismatch {
+                    stored: format!("{}@{}", stored.contract, stored.version),
+                    expected: format!("{}@{}", CONTRACT_NAME, CONTRACT_VERSION),
+                });
+            }
+            Some(stored.version)
+        }
+        Err(_) => None,
+    };
+
+    let deprecations = msg.deprecations.unwrap_or_default();
+    if deprecations.len() > MAX_MIGRATE_DEPRECATIONS {
+        return Err(ContractError::TooManyDeprecations {
+            max: MAX_MIGRATE_DEPRECATIONS,
+        });
+    }
+    let mut res = Response::new().add_attribute("action", "migrate");
+    for deprecation in deprecations {
+        // running the same migration again skips the pools it already moved
+        let asset_key = deps
+            .api
+            .addr_canonicalize(deprecation.staking_token.as_str())?;
+        let new_asset_key = deps
+            .api
+            .addr_canonicalize(deprecation.new_staking_token.as_str())?;
+        let moved = read_pool_info(deps.storage, &asset_key).is_err()
+            && read_pool_info(deps.storage, &new_asset_key)
+                .map_or(false, |pool_info| pool_info.staking_token == new_asset_key);
+        if moved {
+            res = res.add_attribute("skipped_deprecation", deprecation.staking_token);
+            continue;
+        }
+        let deprecated = _deprecate_staking_token(
+            deps.branch(),
+            deprecation.staking_token,
+            deprecation.new_staking_token,
+            None,
+        )?;
+        res = res.add_attributes(deprecated.attributes);
+    }
+
+    // positions untouched since the upgrade that started recording activity are idle from it
+    if let Ok(mut config) = read_config(deps.storage) {
+        if config.reward_activity_since == 0 {
+            config.reward_activity_since = env.block.time.seconds();
+            store_config(deps.storage, &config)?;
+        }
+    }
+
+    match stored_version.as_deref() {
+        // migrating to the running version again leaves the store as it is
+        Some(CONTRACT_VERSION) => return Ok(res.add_attribute("skipped", "already_migrated")),
+        // versioned stores already use the current layout, the totals start from the pools
+        Some(_) => {
+            let pool_infos = read_all_pool_infos(deps.storage)?;
+            // stores from before the pending total keep it at zero, so it is summed again
+            let total_pending_reward = pool_infos
+                .iter()
+                .fold(Uint128::zero(), |total, (_, pool_info)| {
+                    total.saturating_add(pool_info.pending_reward)
+                });
+            match read_state(deps.storage)? {
+                Some(mut state) => {
+                    state.total_pending_reward = total_pending_reward;
+                    store_state(deps.storage, &state)?;
+                }
+                None => store_state(
+                    deps.storage,
+                    &State {
+                        pool_count: pool_infos.len() as u64,
+                        total_pending_reward,
+                        ..State::default()
+                    },
+                )?,
+            }
+        }
+        // the unversioned layout is moved over by MigrateStep, staking stays closed until then
+        None => {
+            store_finish_migrate_store_status(deps.storage, false)?;
+            store_migration_cursor(deps.storage, &MigrationCursor::default())?;
+        }
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(res.add_attribute("version", CONTRACT_VERSION))
+}
+
+// anyone can move the legacy store on, the cursor keeps each call within the block gas limit
+fn migrate_step(deps: DepsMut, limit: Option<u32>) -> Result<Response, ContractError> {
+    if read_migration_cursor(deps.storage)?.is_none() {
+        return Err(ContractError::NotMigrating {});
+    }
+    let limit = limit
+        .unwrap_or(DEFAULT_MIGRATE_STEP_LIMIT)
+        .clamp(1, MAX_MIGRATE_STEP_LIMIT) as usize;
+    let (cursor, finished) = migrate_store_step(deps.storage, deps.api, limit)?;
+    if finished {
+        store_finish_migrate_store_status(deps.storage, true)?;
+        let pool_count = read_all_pool_infos(deps.storage)?.len() as u64;
+        update_state(deps.storage, |state| state.pool_count = pool_count)?;
+    }
+
+    Ok(Response::new().add_attributes([
+        ("action", "migrate_step"),
+        ("migrated_pools", &cursor.migrated_pools.to_string()),
+        ("migrated_stakers", &cursor.migrated_stakers.to_string()),
+        ("finished", &finished.to_string()),
+    ]))
+}
+
+// numeric components of a semver version, so 0.10.0 orders after 0.9.0
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or_default())
+        .collect()
 }
 
-pub fn validate_migrate_store_status(storage: &mut dyn Storage) -> StdResult<()> {
+// bonding and reward deposits stop while paused, unbond and withdraw keep working
+pub fn assert_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+    if read_config(storage)?.paused {
+        return Err(ContractError::Paused {});
+    }
+    Ok(())
+}
+
+pub fn validate_migrate_store_status(storage: &mut dyn Storage) -> Result<(), ContractError> {
     let migrate_store_status = read_finish_migrate_store_status(storage)?;
     if migrate_store_status {
         return Ok(());
     }
-    Err(StdError::generic_err(
-        ContractError::ContractUpgrade {}.to_string(),
-    ))
+    Err(ContractError::ContractUpgrade {})
+}
+
+// a missing pool is reported with its staking token instead of the raw storage error
+pub(crate) fn read_registered_pool_info(
+    storage: &dyn Storage,
+    staking_token: &Addr,
+    asset_key: &[u8],
+) -> Result<PoolInfo, ContractError> {
+    // a deprecated staking token only resolves to its pool for the positions still bonded in it
+    if read_deprecated_staking_token(storage, asset_key)?.is_some() {
+        return Err(ContractError::PoolNotFound {
+            asset: staking_token.to_string(),
+        });
+    }
+    read_pool_info(storage, asset_key).map_err(|err| match err {
+        StdError::NotFound { .. } => ContractError::PoolNotFound {
+            asset: staking_token.to_string(),
+        },
+        err => err.into(),
+    })
 }
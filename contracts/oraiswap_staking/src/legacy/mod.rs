@@ -1,2 +1,3 @@
+// storage layouts of previous versions, each one has a dump in testing/migrate_test.rs
 pub mod v1;
 pub mod v2;
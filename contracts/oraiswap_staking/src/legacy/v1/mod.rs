@@ -29,6 +29,20 @@ pub fn old_read_all_pool_info_keys(storage: &dyn Storage) -> StdResult<Vec<Vec<u
         .collect()
 }
 
+// first legacy pool key after start_after, in key order
+pub fn old_read_next_pool_info_key(
+    storage: &dyn Storage,
+    start_after: Option<&[u8]>,
+) -> StdResult<Option<Vec<u8>>> {
+    // the smallest key after start_after is start_after followed by a zero byte
+    let start = start_after.map(|key| [key, &[0u8]].concat());
+    ReadonlyBucket::<PoolInfo>::new(storage, PREFIX_POOL_INFO)
+        .range(start.as_deref(), None, cosmwasm_std::Order::Ascending)
+        .next()
+        .map(|bucket| bucket.map(|b| b.0))
+        .transpose()
+}
+
 pub fn old_read_all_pool_infos(storage: &dyn Storage) -> StdResult<Vec<(Vec<u8>, PoolInfo)>> {
     ReadonlyBucket::<PoolInfo>::new(storage, PREFIX_POOL_INFO)
         .range(None, None, cosmwasm_std::Order::Ascending)
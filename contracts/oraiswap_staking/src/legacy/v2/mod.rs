@@ -3,12 +3,13 @@ use oraiswap::asset::AssetInfo;
 
 use crate::{
     legacy::v1::{
-        old_read_all_is_migrated, old_read_pool_info, old_read_rewards_per_sec, old_rewards_read,
-        old_stakers_read,
+        old_read_all_is_migrated, old_read_next_pool_info_key, old_read_pool_info,
+        old_read_rewards_per_sec, old_rewards_read, old_stakers_read,
     },
     state::{
-        read_is_migrated, rewards_store, stakers_store, store_is_migrated, store_pool_info,
-        store_rewards_per_sec,
+        read_is_migrated, read_migration_cursor, remove_migration_cursor, rewards_store,
+        stakers_store, store_is_migrated, store_migration_cursor, store_pool_info,
+        store_rewards_per_sec, MigrationCursor, PoolInfo,
     },
 };
 
@@ -17,6 +18,29 @@ pub fn migrate_single_asset_key_to_lp_token(
     api: &dyn Api,
     asset_key: &[u8],
 ) -> StdResult<u64> {
+    let pool_info = migrate_pool_head(storage, api, asset_key)?;
+
+    let stakers = old_stakers_read(storage, asset_key)
+        .range(None, None, Order::Ascending)
+        // Get next_key
+        .collect::<StdResult<Vec<(Vec<u8>, bool)>>>()?;
+
+    #[cfg(debug_assertions)]
+    api.debug(&format!("stakers.len {:?} ", stakers.len()));
+
+    // Store stakers to new staking key token
+    for (staker, _) in stakers.iter() {
+        migrate_staker(storage, asset_key, &pool_info.staking_token, staker)?;
+    }
+    Ok(stakers.len() as u64)
+}
+
+// moves the pool info and the reward weights of a legacy pool to its staking token key
+fn migrate_pool_head(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    asset_key: &[u8],
+) -> StdResult<PoolInfo> {
     let pool_info = old_read_pool_info(storage, asset_key)?;
     // store pool_info to new key
     store_pool_info(storage, &pool_info.staking_token, &pool_info)?;
@@ -45,31 +69,80 @@ pub fn migrate_single_asset_key_to_lp_token(
         api.debug(&format!("rewards_per_sec {:?}", rewards_per_sec));
         store_rewards_per_sec(storage, &pool_info.staking_token, rewards_per_sec)?;
     }
+    Ok(pool_info)
+}
 
-    let stakers = old_stakers_read(storage, asset_key)
-        .range(None, None, Order::Ascending)
-        // Get next_key
-        .collect::<StdResult<Vec<(Vec<u8>, bool)>>>()?;
-
-    #[cfg(debug_assertions)]
-    api.debug(&format!("stakers.len {:?} ", stakers.len()));
+fn migrate_staker(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    staking_token: &[u8],
+    staker: &[u8],
+) -> StdResult<()> {
+    let all_is_migrated = old_read_all_is_migrated(storage, staker)?;
+    for (old_asset_key, old_is_migrated) in all_is_migrated {
+        let old_pool_info = old_read_pool_info(storage, &old_asset_key)?;
+        let new_is_migrated = read_is_migrated(storage, &old_pool_info.staking_token, staker);
+        if old_is_migrated && !new_is_migrated {
+            store_is_migrated(storage, &old_pool_info.staking_token, staker)?;
+        }
+    }
+    stakers_store(storage, staking_token).save(staker, &true)?;
+    if let Some(reward) = old_rewards_read(storage, staker).load(asset_key).ok() {
+        rewards_store(storage, staker).save(staking_token, &reward)?;
+    }
+    Ok(())
+}
 
-    // Store stakers to new staking key token
-    for (staker, _) in stakers.iter() {
-        let all_is_migrated = old_read_all_is_migrated(storage, staker)?;
-        for (old_asset_key, old_is_migrated) in all_is_migrated {
-            let old_pool_info = old_read_pool_info(storage, &old_asset_key)?;
-            let new_is_migrated = read_is_migrated(storage, &old_pool_info.staking_token, staker);
-            if old_is_migrated && !new_is_migrated {
-                store_is_migrated(storage, &old_pool_info.staking_token, staker)?;
+/// Moves at most `limit` legacy entries, a pool head or a staker each, from where the cursor
+/// stopped. Returns true once every legacy pool has been moved and the cursor removed
+pub fn migrate_store_step(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    limit: usize,
+) -> StdResult<(MigrationCursor, bool)> {
+    let mut cursor = read_migration_cursor(storage)?.unwrap_or_default();
+    let mut budget = limit;
+    while budget > 0 {
+        match cursor.pool.clone() {
+            Some(asset_key) if !cursor.pool_finished => {
+                let staking_token = old_read_pool_info(storage, &asset_key)?.staking_token;
+                let start = cursor
+                    .last_staker
+                    .as_ref()
+                    .map(|staker| [staker.as_slice(), &[0u8]].concat());
+                let stakers = old_stakers_read(storage, &asset_key)
+                    .range(start.as_deref(), None, Order::Ascending)
+                    .take(budget)
+                    .collect::<StdResult<Vec<(Vec<u8>, bool)>>>()?;
+                for (staker, _) in stakers.iter() {
+                    migrate_staker(storage, &asset_key, &staking_token, staker)?;
+                }
+                // a short page means the pool has no stakers left
+                cursor.pool_finished = stakers.len() < budget;
+                budget -= stakers.len();
+                cursor.migrated_stakers += stakers.len() as u64;
+                if let Some((staker, _)) = stakers.last() {
+                    cursor.last_staker = Some(staker.clone());
+                }
             }
-        }
-        stakers_store(storage, &pool_info.staking_token).save(staker, &true)?;
-        if let Some(reward) = old_rewards_read(storage, staker).load(asset_key).ok() {
-            rewards_store(storage, staker).save(&pool_info.staking_token, &reward)?;
+            _ => match old_read_next_pool_info_key(storage, cursor.pool.as_deref())? {
+                Some(asset_key) => {
+                    migrate_pool_head(storage, api, &asset_key)?;
+                    cursor.pool = Some(asset_key);
+                    cursor.last_staker = None;
+                    cursor.pool_finished = false;
+                    cursor.migrated_pools += 1;
+                    budget -= 1;
+                }
+                None => {
+                    remove_migration_cursor(storage);
+                    return Ok((cursor, true));
+                }
+            },
         }
     }
-    Ok(stakers.len() as u64)
+    store_migration_cursor(storage, &cursor)?;
+    Ok((cursor, false))
 }
 
 pub fn migrate_store(
@@ -1,5 +1,6 @@
 pub mod contract;
 mod legacy;
+mod reward_math;
 mod rewards;
 mod staking;
 mod state;
@@ -0,0 +1,85 @@
+use std::convert::TryFrom;
+
+use crate::state::{EpochInfo, PoolInfo, RewardInfo};
+use cosmwasm_std::{Decimal256, StdError, StdResult, Uint128, Uint256};
+
+// the reward math of the pools and positions, over the stored structs only. The handlers load
+// and save them around these, so every step is testable without storage
+
+pub fn add_reward_sum(sum: Uint128, amount: Uint128) -> StdResult<Uint128> {
+    sum.checked_add(amount)
+        .map_err(|_| StdError::generic_err("reward sum overflow"))
+}
+
+// add reward to the pool index, or keep it pending while nothing is bonded
+pub fn distribute_pool_reward(pool_info: &mut PoolInfo, amount: Uint128) -> StdResult<()> {
+    // normal rewards are array of Assets
+    let total_weighted_bond = pool_info.total_weighted_bond();
+    let normal_reward = add_reward_sum(pool_info.pending_reward, amount)?;
+    if total_weighted_bond.is_zero() {
+        pool_info.pending_reward = normal_reward;
+        pool_info.orphaned_reward = add_reward_sum(pool_info.orphaned_reward, amount)?;
+    } else {
+        let normal_reward_per_bond = Decimal256::from_ratio(normal_reward, total_weighted_bond);
+        pool_info.reward_index = pool_info.reward_index.checked_add(normal_reward_per_bond)?;
+        // forfeited dust in pending_reward was counted when it first reached the positions
+        pool_info.lifetime_rewards_deposited = pool_info
+            .lifetime_rewards_deposited
+            .saturating_add(amount)
+            .saturating_add(pool_info.orphaned_reward);
+        pool_info.pending_reward = Uint128::zero();
+        pool_info.orphaned_reward = Uint128::zero();
+    }
+    Ok(())
+}
+
+// what distributing amount alone would add to the reward index, zero while nothing is bonded
+pub fn index_increase(pool_info: &PoolInfo, amount: Uint128) -> Decimal256 {
+    let total_weighted_bond = pool_info.total_weighted_bond();
+    if total_weighted_bond.is_zero() {
+        Decimal256::zero()
+    } else {
+        Decimal256::from_ratio(amount, total_weighted_bond)
+    }
+}
+
+// returns the epoch running at `now`, and the rewards of the epoch that has ended meanwhile
+pub fn compute_epoch(mut epoch: EpochInfo, now: u64) -> (EpochInfo, Uint128) {
+    if now < epoch.start_time + epoch.epoch_length {
+        return (epoch, Uint128::zero());
+    }
+
+    let elapsed_epochs = (now - epoch.start_time) / epoch.epoch_length;
+    epoch.epoch_number += elapsed_epochs;
+    epoch.start_time += elapsed_epochs * epoch.epoch_length;
+    let closed_reward = epoch.accumulated_reward;
+    epoch.accumulated_reward = Uint128::zero();
+
+    (epoch, closed_reward)
+}
+
+// the index a position earns up to, a position still backed by a deprecated staking token
+// stops at the deprecation snapshot
+pub fn position_index(pool_info: &PoolInfo, position_migrated: bool) -> Decimal256 {
+    match &pool_info.migration_params {
+        Some(params) if !position_migrated => params.index_snapshot,
+        _ => pool_info.reward_index,
+    }
+}
+
+// withdraw reward to pending reward
+// the index delta is floored once, flooring both products separately could pay out more than deposited
+pub fn before_share_change(pool_index: Decimal256, reward_info: &mut RewardInfo) -> StdResult<()> {
+    // nothing was distributed since the position was last settled
+    if reward_info.index == pool_index {
+        return Ok(());
+    }
+    let pending_reward = Uint128::try_from(
+        Uint256::from(reward_info.weighted_bond_amount())
+            * pool_index.checked_sub(reward_info.index)?,
+    )?;
+
+    reward_info.index = pool_index;
+    reward_info.pending_reward = reward_info.pending_reward.checked_add(pending_reward)?;
+    Ok(())
+}
@@ -1,132 +1,1750 @@
 use std::convert::TryFrom;
 
-use crate::contract::validate_migrate_store_status;
+use crate::contract::{
+    assert_not_paused, read_registered_pool_info, validate_migrate_store_status,
+};
+use crate::reward_math::{
+    add_reward_sum, before_share_change, compute_epoch, distribute_pool_reward, index_increase,
+    position_index,
+};
+use crate::staking::swap_msg;
 use crate::state::{
-    read_config, read_is_migrated, read_pool_info, read_rewards_per_sec, rewards_read,
-    rewards_store, stakers_read, store_pool_info, PoolInfo, RewardInfo,
+    read_all_pool_infos, read_config, read_is_migrated, read_operator, read_pool_deposits,
+    read_pool_epoch, read_pool_info, read_range_prefixed, read_reward_index_history_len,
+    read_rewards_per_sec, remove_operator, resolve_pool_key, reward_index_history_read,
+    reward_index_history_store, rewards_read, rewards_store, staking_token_addr, staking_token_key,
+    store_config, store_operator, store_pool_deposits, store_pool_epoch, store_pool_info,
+    store_reward_index_history_len, unbonding_read, update_state, Config, EpochInfo, PoolInfo,
+    RewardIndexSnapshot, RewardInfo, PREFIX_REWARD, PREFIX_STAKER,
 };
 use cosmwasm_std::{
-    Addr, Api, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response,
-    StdError, StdResult, Storage, Uint128,
+    attr, coin, to_binary, Addr, Api, Attribute, BankMsg, CanonicalAddr, CosmosMsg, Decimal,
+    Decimal256, Deps, DepsMut, Env, IbcMsg, IbcTimeout, MessageInfo, Order, QuerierWrapper,
+    Response, StdError, StdResult, Storage, Uint128,
 };
-use oraiswap::asset::{Asset, AssetRaw};
-use oraiswap::querier::calc_range_start;
-use oraiswap::staking::{RewardInfoResponse, RewardInfoResponseItem, RewardMsg};
+use oraiswap::asset::{Asset, AssetInfo, AssetInfoRaw, AssetRaw};
+use oraiswap::error::ContractError;
+use oraiswap::querier::{calc_range_start, page_limit, parse_order, query_pair_info};
+use oraiswap::staking::{
+    AssetDecimals, BatchRewardInfoResponse, BoostQueryMsg, DistributionInfoResponse,
+    DistributionScheduleResponse, IbcRecipient, OperatorResponse, PoolDistributionInfo,
+    PoolEpochResponse, PoolRewardRateResponse, RewardIndexHistoryItem, RewardInfoResponse,
+    RewardInfoResponseItem, RewardMsg, SimulateBondResponse, SimulateWithdrawResponse,
+    SimulatedPoolWithdraw, StakerInfoResponse, StakerRewardInfo, VotingPowerResponse,
+    WithdrawResponse,
+};
+
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 30;
+
+// a reward entry costs about 5 reads and 1 write, the cap keeps a deposit well inside the block gas limit
+pub const MAX_DEPOSIT_REWARDS: usize = 100;
+
+// deposits kept per pool for its reward rate
+pub const MAX_RATE_DEPOSITS: usize = 10;
+
+// stakers of one BatchRewardInfo query
+pub const MAX_BATCH_STAKERS: usize = 30;
 
-const DEFAULT_LIMIT: u32 = 10;
-const MAX_LIMIT: u32 = 30;
+// reward index snapshots kept per pool, each new one past it evicts the oldest
+pub const MAX_REWARD_INDEX_SNAPSHOTS: u64 = 100;
+
+// a position must be idle for this long before its rewards can be swept, whatever the owner asks
+pub const MIN_ABANDONED_IDLE_SECONDS: u64 = 2 * 365 * 24 * 60 * 60;
+
+// withdrawing without a staking token settles every pool of the staker, at most this many
+pub const MAX_WITHDRAW_POOLS: usize = 30;
+
+// streams running on one pool at a time, deposits of one block over the same window are merged
+pub const MAX_REWARD_STREAMS: usize = 10;
+
+const MIN_IBC_TIMEOUT: u64 = 60;
+const MAX_IBC_TIMEOUT: u64 = 7 * 86400;
 
 // deposit_reward must be from reward token contract
 pub fn deposit_reward(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     rewards: Vec<RewardMsg>,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     validate_migrate_store_status(deps.storage)?;
+    assert_not_paused(deps.storage)?;
     let config = read_config(deps.storage)?;
 
-    // only rewarder can execute this message, rewarder may be a contract
-    if config.rewarder != deps.api.addr_canonicalize(info.sender.as_str())? {
-        return Err(StdError::generic_err("unauthorized"));
+    // only the rewarder or a reward distributor can execute this message, both may be contracts
+    assert_reward_depositor(deps.api, &config, &info.sender)?;
+    if rewards
+        .iter()
+        .all(|reward_msg| reward_msg.total_accumulation_amount.is_zero())
+    {
+        return Err(ContractError::ZeroRewardDeposit {});
     }
 
-    let mut rewards_amount = Uint128::zero();
+    // nothing was sent along, so the skipped amounts are only reported
+    let (rewards_amount, skipped_amount, deposit_epochs) =
+        _deposit_reward(deps, env, &rewards, true)?;
+
+    Ok(Response::new()
+        .add_attributes([
+            ("action", "deposit_reward"),
+            ("rewards_amount", &rewards_amount.to_string()),
+            ("skipped_amount", &skipped_amount.to_string()),
+        ])
+        .add_attributes(deposit_epochs))
+}
+
+pub fn owner_deposit_reward(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rewards: Vec<RewardMsg>,
+    force: bool,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    assert_not_paused(deps.storage)?;
+    let config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+    if rewards
+        .iter()
+        .all(|reward_msg| reward_msg.total_accumulation_amount.is_zero())
+    {
+        return Err(ContractError::ZeroRewardDeposit {});
+    }
+
+    let (rewards_amount, skipped_amount, deposit_epochs) =
+        _deposit_reward(deps, env, &rewards, !force)?;
+
+    Ok(Response::new()
+        .add_attributes([
+            ("action", "owner_deposit_reward"),
+            ("rewards_amount", &rewards_amount.to_string()),
+            ("skipped_amount", &skipped_amount.to_string()),
+            ("force", &force.to_string()),
+        ])
+        .add_attributes(deposit_epochs))
+}
+
+// rejects a deposit raising the reward index of the pool past its limit, a guard against an
+// amount mistyped by orders of magnitude
+fn assert_index_increase(
+    pool_info: &PoolInfo,
+    staking_token: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    if pool_info.max_index_increase_per_deposit.is_zero() {
+        return Ok(());
+    }
+    let increase = index_increase(pool_info, amount);
+    if increase > Decimal256::from(pool_info.max_index_increase_per_deposit) {
+        return Err(ContractError::RewardIndexIncreaseTooLarge {
+            staking_token: staking_token.to_string(),
+            increase,
+            limit: pool_info.max_index_increase_per_deposit,
+        });
+    }
+    Ok(())
+}
+
+// the funds must be exactly the summed rewards in the base denom, mixed denoms are rejected
+pub fn deposit_native_reward(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rewards: Vec<RewardMsg>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    assert_not_paused(deps.storage)?;
+    let config = read_config(deps.storage)?;
+
+    assert_reward_depositor(deps.api, &config, &info.sender)?;
+
+    let sent = match info.funds.as_slice() {
+        [fund] if fund.denom == config.base_denom => fund.amount,
+        _ => return Err(ContractError::InvalidFunds {}),
+    };
+    let expected = rewards
+        .iter()
+        .try_fold(Uint128::zero(), |sum, reward_msg| {
+            add_reward_sum(sum, reward_msg.total_accumulation_amount)
+        })?;
+    if sent != expected {
+        return Err(ContractError::NativeRewardMismatch { sent, expected });
+    }
 
+    // the deposit is split by the reward weights when withdrawn, so they must all be the denom
+    let reward_info = AssetInfoRaw::NativeToken {
+        denom: config.base_denom.clone(),
+    };
     for reward_msg in rewards.iter() {
-        let asset_key = deps
-            .api
-            .addr_canonicalize(reward_msg.staking_token.as_str())?;
-        let mut pool_info: PoolInfo = read_pool_info(deps.storage, &asset_key)?;
+        let asset_key =
+            staking_token_key(deps.storage, deps.api, reward_msg.staking_token.as_str())?;
+        let rewards_per_sec = read_rewards_per_sec(deps.storage, &asset_key)?;
+        let weights: Vec<&AssetRaw> = rewards_per_sec
+            .iter()
+            .filter(|rw| !rw.amount.is_zero())
+            .collect();
+        if weights.is_empty() || weights.iter().any(|rw| rw.info != reward_info) {
+            return Err(ContractError::InvalidNativeRewardPool {
+                staking_token: reward_msg.staking_token.to_string(),
+                denom: config.base_denom,
+            });
+        }
+    }
 
-        let mut normal_reward = reward_msg.total_accumulation_amount;
+    let (rewards_amount, skipped_amount, deposit_epochs) =
+        _deposit_reward(deps, env, &rewards, true)?;
 
-        // normal rewards are array of Assets
-        if pool_info.total_bond_amount.is_zero() {
-            pool_info.pending_reward += normal_reward;
-        } else {
-            normal_reward += pool_info.pending_reward;
-            let normal_reward_per_bond =
-                Decimal::from_ratio(normal_reward, pool_info.total_bond_amount);
-            pool_info.reward_index = pool_info.reward_index + normal_reward_per_bond;
-            pool_info.pending_reward = Uint128::zero();
+    // the share of the pools outside their reward window goes back to the depositor
+    let mut response = Response::new();
+    if !skipped_amount.is_zero() {
+        response = response.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![coin(skipped_amount.u128(), config.base_denom)],
+        });
+    }
+
+    Ok(response
+        .add_attributes([
+            ("action", "deposit_native_reward"),
+            ("rewards_amount", &rewards_amount.to_string()),
+            ("skipped_amount", &skipped_amount.to_string()),
+        ])
+        .add_attributes(deposit_epochs))
+}
+
+// deposit_reward_weights splits the received amount on chain, so the deposit is always conserved
+pub fn deposit_reward_weights(
+    mut deps: DepsMut,
+    env: Env,
+    token: Addr,
+    sender: Addr,
+    amount: Uint128,
+    weights: Vec<(Addr, Decimal)>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    assert_not_paused(deps.storage)?;
+    let config = read_config(deps.storage)?;
+
+    assert_reward_depositor(deps.api, &config, &sender)?;
+
+    let rewards = compute_weighted_rewards(amount, &weights)?;
+    let asset_keys = rewards
+        .iter()
+        .map(|reward_msg| {
+            Ok(
+                staking_token_key(deps.storage, deps.api, reward_msg.staking_token.as_str())?
+                    .to_vec(),
+            )
+        })
+        .collect::<StdResult<Vec<Vec<u8>>>>()?;
+    assert_reward_token(deps.storage, deps.api, &token, &asset_keys)?;
+    let (rewards_amount, skipped_amount, deposit_epochs) =
+        _deposit_reward(deps.branch(), env, &rewards, true)?;
+
+    // the share of the pools outside their reward window goes back to the depositor
+    let mut response = Response::new();
+    if !skipped_amount.is_zero() {
+        let refund = Asset {
+            info: AssetInfo::Token {
+                contract_addr: token,
+            },
+            amount: skipped_amount,
+        };
+        response = response.add_message(refund.into_msg(None, &deps.querier, sender)?);
+    }
+
+    Ok(response
+        .add_attributes([
+            ("action", "deposit_reward_weights"),
+            ("rewards_amount", &rewards_amount.to_string()),
+            ("skipped_amount", &skipped_amount.to_string()),
+        ])
+        .add_attributes(deposit_epochs))
+}
+
+// the rewarder or a listed distributor, the hooks also check the token with assert_reward_token
+fn assert_reward_depositor(
+    api: &dyn Api,
+    config: &Config,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let sender_raw = api.addr_canonicalize(sender.as_str())?;
+    if config.rewarder != sender_raw && !config.reward_distributors.contains(&sender_raw) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
+// the cw20 sender of a hook is whatever the caller of Receive wrote, only the token contract
+// vouches for the amount, so it must be a cw20 reward asset of every pool the deposit reaches
+fn assert_reward_token(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    token: &Addr,
+    asset_keys: &[Vec<u8>],
+) -> Result<(), ContractError> {
+    let token_info = AssetInfoRaw::Token {
+        contract_addr: api.addr_canonicalize(token.as_str())?,
+    };
+    for asset_key in asset_keys {
+        let rewards_per_sec = read_rewards_per_sec(storage, asset_key).unwrap_or_default();
+        if !rewards_per_sec
+            .iter()
+            .any(|rw| rw.info == token_info && !rw.amount.is_zero())
+        {
+            return Err(ContractError::Unauthorized {});
         }
+    }
 
-        store_pool_info(deps.storage, &asset_key, &pool_info)?;
+    Ok(())
+}
 
-        rewards_amount += reward_msg.total_accumulation_amount;
+// splits the received amount by the reward weights stored on the pools
+pub fn deposit_reward_by_weights(
+    deps: DepsMut,
+    env: Env,
+    token: Addr,
+    sender: Addr,
+    amount: Uint128,
+    stream_over_seconds: Option<u64>,
+    expected_epoch: Option<u64>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    assert_not_paused(deps.storage)?;
+    let config = read_config(deps.storage)?;
+
+    assert_reward_depositor(deps.api, &config, &sender)?;
+    if amount.is_zero() {
+        return Err(ContractError::ZeroRewardDeposit {});
     }
 
+    let now = env.block.time.seconds();
+    let shares = split_by_reward_weights(deps.storage, amount, now)?;
+    if shares.is_empty() {
+        return Err(ContractError::EmptyRewardWeights {});
+    }
+    let asset_keys: Vec<Vec<u8>> = shares.iter().map(|(key, _, _)| key.clone()).collect();
+    assert_reward_token(deps.storage, deps.api, &token, &asset_keys)?;
+    // a resent deposit is refused before any pool is touched
+    if let Some(expected_epoch) = expected_epoch {
+        for (asset_key, pool_info, _) in shares.iter() {
+            if pool_info.deposit_epoch + 1 != expected_epoch {
+                return Err(ContractError::UnexpectedDepositEpoch {
+                    staking_token: staking_token_addr(deps.storage, deps.api, asset_key)?
+                        .to_string(),
+                    expected: expected_epoch,
+                    next: pool_info.deposit_epoch + 1,
+                });
+            }
+        }
+    }
+    let mut deposit_epochs = vec![];
+    for (asset_key, pool_info, pool_amount) in shares {
+        let staking_token = staking_token_addr(deps.storage, deps.api, &asset_key)?;
+        assert_index_increase(&pool_info, &staking_token, pool_amount)?;
+        let deposit_epoch = deposit_pool_reward(
+            deps.storage,
+            &asset_key,
+            pool_amount,
+            now,
+            stream_over_seconds,
+        )?;
+        record_pool_deposit(deps.storage, &asset_key, now, pool_amount)?;
+        snapshot_reward_index(deps.storage, &asset_key, now)?;
+        deposit_epochs.push(("staking_token", staking_token.to_string()));
+        deposit_epochs.push(("deposit_epoch", deposit_epoch.to_string()));
+    }
+    // counted like any other deposit, see _deposit_reward
+    update_state(deps.storage, |state| {
+        state.total_deposited = state.total_deposited.saturating_add(amount);
+        state.last_deposit_time = Some(now);
+    })?;
+
+    Ok(Response::new()
+        .add_attributes([
+            ("action", "deposit_reward_by_weights"),
+            ("rewards_amount", &amount.to_string()),
+        ])
+        .add_attributes(deposit_epochs))
+}
+
+// sent by a proxy reward token of the pool, shared over the bonds like the reward index
+pub fn deposit_proxy_reward(
+    deps: DepsMut,
+    reward_token: Addr,
+    amount: Uint128,
+    staking_token: Addr,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    assert_not_paused(deps.storage)?;
+
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let mut pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    let reward_token_raw = deps.api.addr_canonicalize(reward_token.as_str())?;
+    let total_weighted_bond = pool_info.total_weighted_bond();
+    let proxy = pool_info
+        .proxy_rewards
+        .iter_mut()
+        .find(|proxy| proxy.reward_token == reward_token_raw)
+        .ok_or(ContractError::Unauthorized {})?;
+
+    let proxy_reward = add_reward_sum(proxy.pending, amount)?;
+    if total_weighted_bond.is_zero() {
+        proxy.pending = proxy_reward;
+    } else {
+        let reward_per_bond = Decimal::from_ratio(proxy_reward, total_weighted_bond);
+        proxy.proxy_index = proxy.proxy_index.checked_add(reward_per_bond)?;
+        proxy.pending = Uint128::zero();
+    }
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
     Ok(Response::new().add_attributes([
-        ("action", "deposit_reward"),
-        ("rewards_amount", &rewards_amount.to_string()),
+        ("action", "deposit_proxy_reward"),
+        ("staking_token", staking_token.as_str()),
+        ("reward_token", reward_token.as_str()),
+        ("amount", &amount.to_string()),
     ]))
 }
 
+// each pool gets the floor of its share, the rounding remainder goes to the first pool
+pub fn compute_weighted_rewards(
+    amount: Uint128,
+    weights: &[(Addr, Decimal)],
+) -> Result<Vec<RewardMsg>, ContractError> {
+    if weights.is_empty() {
+        return Err(ContractError::EmptyRewardWeights {});
+    }
+
+    let mut total_weight = Decimal::zero();
+    for (i, (staking_token, weight)) in weights.iter().enumerate() {
+        if weights[..i].iter().any(|(other, _)| other == staking_token) {
+            return Err(ContractError::DuplicatedRewardWeight {
+                staking_token: staking_token.to_string(),
+            });
+        }
+        total_weight = total_weight.checked_add(*weight)?;
+    }
+    if total_weight != Decimal::one() {
+        return Err(ContractError::InvalidRewardWeights { total_weight });
+    }
+
+    let mut rewards = weights
+        .iter()
+        .map(|(staking_token, weight)| RewardMsg {
+            staking_token: staking_token.clone(),
+            total_accumulation_amount: amount * *weight,
+        })
+        .collect::<Vec<RewardMsg>>();
+    let distributed = rewards.iter().try_fold(Uint128::zero(), |acc, r| {
+        acc.checked_add(r.total_accumulation_amount)
+    })?;
+    rewards[0].total_accumulation_amount = rewards[0]
+        .total_accumulation_amount
+        .checked_add(amount.checked_sub(distributed)?)?;
+
+    Ok(rewards)
+}
+
+// the max_index_increase_per_deposit of the pools is only skipped by a forced owner deposit
+fn _deposit_reward(
+    deps: DepsMut,
+    env: Env,
+    rewards: &[RewardMsg],
+    check_index_increase: bool,
+) -> Result<(Uint128, Uint128, Vec<(&'static str, String)>), ContractError> {
+    if rewards.len() > MAX_DEPOSIT_REWARDS {
+        return Err(ContractError::TooManyRewards {
+            max: MAX_DEPOSIT_REWARDS,
+        });
+    }
+
+    // every staking token must be registered before any pool is touched
+    let asset_keys = rewards
+        .iter()
+        .map(|reward_msg| {
+            let asset_key =
+                staking_token_key(deps.storage, deps.api, reward_msg.staking_token.as_str())?;
+            read_registered_pool_info(deps.storage, &reward_msg.staking_token, &asset_key)?;
+            Ok(asset_key)
+        })
+        .collect::<Result<Vec<CanonicalAddr>, ContractError>>()?;
+
+    let mut rewards_amount = Uint128::zero();
+    let mut skipped_amount = Uint128::zero();
+    // every deposit a pool takes in is numbered, a skipped one is not
+    let mut deposit_epochs = vec![];
+
+    for (reward_msg, asset_key) in rewards.iter().zip(asset_keys) {
+        // read again, the same pool may take several entries
+        let pool_info: PoolInfo = read_pool_info(deps.storage, &asset_key)?;
+        if !pool_info.accepts_reward(env.block.time.seconds()) {
+            skipped_amount = add_reward_sum(skipped_amount, reward_msg.total_accumulation_amount)?;
+            continue;
+        }
+        if check_index_increase {
+            assert_index_increase(
+                &pool_info,
+                &reward_msg.staking_token,
+                reward_msg.total_accumulation_amount,
+            )?;
+        }
+
+        let deposit_epoch = deposit_pool_reward(
+            deps.storage,
+            &asset_key,
+            reward_msg.total_accumulation_amount,
+            env.block.time.seconds(),
+            None,
+        )?;
+        deposit_epochs.push(("staking_token", reward_msg.staking_token.to_string()));
+        deposit_epochs.push(("deposit_epoch", deposit_epoch.to_string()));
+        record_pool_deposit(
+            deps.storage,
+            &asset_key,
+            env.block.time.seconds(),
+            reward_msg.total_accumulation_amount,
+        )?;
+        snapshot_reward_index(deps.storage, &asset_key, env.block.time.seconds())?;
+
+        rewards_amount = add_reward_sum(rewards_amount, reward_msg.total_accumulation_amount)?;
+    }
+
+    // the running totals only feed the State query, they saturate rather than fail a deposit
+    update_state(deps.storage, |state| {
+        state.total_deposited = state.total_deposited.saturating_add(rewards_amount);
+        state.last_deposit_time = Some(env.block.time.seconds());
+    })?;
+
+    Ok((rewards_amount, skipped_amount, deposit_epochs))
+}
+
+// deposits within one block are merged, so the rate never divides by a zero interval
+fn record_pool_deposit(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    now: u64,
+    amount: Uint128,
+) -> StdResult<()> {
+    let mut deposits = read_pool_deposits(storage, asset_key)?;
+    match deposits.last_mut() {
+        Some((time, total)) if *time == now => *total = add_reward_sum(*total, amount)?,
+        _ => deposits.push((now, amount)),
+    }
+    if deposits.len() > MAX_RATE_DEPOSITS {
+        deposits.drain(..deposits.len() - MAX_RATE_DEPOSITS);
+    }
+    store_pool_deposits(storage, asset_key, &deposits)
+}
+
+// a pool reward is owed in the reward assets of the pool, split at its current rates the way
+// settle_reward_info splits it. The positions round their part down, so the split never owes less
+fn owed_reward_assets(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+    amount: Uint128,
+) -> StdResult<Vec<AssetRaw>> {
+    let rewards_per_sec = read_rewards_per_sec(storage, asset_key).unwrap_or_default();
+    let total_amount = rewards_per_sec
+        .iter()
+        .try_fold(Uint128::zero(), |total, rw| total.checked_add(rw.amount))?;
+    Ok(rewards_per_sec
+        .into_iter()
+        .filter(|rw| !rw.amount.is_zero())
+        .map(|rw| AssetRaw {
+            amount: amount.multiply_ratio(rw.amount, total_amount),
+            info: rw.info,
+        })
+        .collect())
+}
+
+// the outstanding amounts only feed the State query, they saturate rather than fail a deposit
+fn add_outstanding_rewards(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    amount: Uint128,
+) -> StdResult<()> {
+    let owed = owed_reward_assets(storage, asset_key, amount)?;
+    if owed.iter().all(|ra| ra.amount.is_zero()) {
+        return Ok(());
+    }
+    update_state(storage, |state| {
+        for ra in owed {
+            match state
+                .total_outstanding_rewards
+                .iter_mut()
+                .find(|outstanding| outstanding.info == ra.info)
+            {
+                Some(outstanding) => {
+                    outstanding.amount = outstanding.amount.saturating_add(ra.amount)
+                }
+                None => state.total_outstanding_rewards.push(ra),
+            }
+        }
+    })
+}
+
+// assets no longer owed, an asset that is paid off is dropped
+fn remove_outstanding_rewards(outstanding_rewards: &mut Vec<AssetRaw>, assets: &[AssetRaw]) {
+    for ra in assets {
+        if let Some(outstanding) = outstanding_rewards
+            .iter_mut()
+            .find(|outstanding| outstanding.info == ra.info)
+        {
+            outstanding.amount = outstanding.amount.saturating_sub(ra.amount);
+        }
+    }
+    outstanding_rewards.retain(|outstanding| !outstanding.amount.is_zero());
+}
+
+// every reward payout leaves the State totals here, they saturate rather than fail a withdraw
+pub fn record_withdrawn_rewards(storage: &mut dyn Storage, assets: &[AssetRaw]) -> StdResult<()> {
+    if assets.iter().all(|ra| ra.amount.is_zero()) {
+        return Ok(());
+    }
+    update_state(storage, |state| {
+        for ra in assets {
+            state.total_withdrawn = state.total_withdrawn.saturating_add(ra.amount);
+        }
+        remove_outstanding_rewards(&mut state.total_outstanding_rewards, assets);
+    })
+}
+
+// a second deposit in the same block overwrites the snapshot of the block
+fn snapshot_reward_index(storage: &mut dyn Storage, asset_key: &[u8], now: u64) -> StdResult<()> {
+    let pool_info = read_pool_info(storage, asset_key)?;
+    let pool_key = resolve_pool_key(storage, asset_key);
+    let time_key = now.to_be_bytes();
+    let is_new = reward_index_history_read(storage, &pool_key)
+        .may_load(&time_key)?
+        .is_none();
+    reward_index_history_store(storage, &pool_key).save(
+        &time_key,
+        &RewardIndexSnapshot {
+            reward_index: pool_info.reward_index,
+            total_bond_amount: pool_info.total_bond_amount,
+            deposit_epoch: pool_info.deposit_epoch,
+        },
+    )?;
+    if !is_new {
+        return Ok(());
+    }
+
+    let mut len = read_reward_index_history_len(storage, &pool_key)? + 1;
+    if len > MAX_REWARD_INDEX_SNAPSHOTS {
+        let oldest = reward_index_history_read(storage, &pool_key)
+            .range(None, None, Order::Ascending)
+            .next()
+            .transpose()?;
+        if let Some((oldest_key, _)) = oldest {
+            reward_index_history_store(storage, &pool_key).remove(&oldest_key);
+            len -= 1;
+        }
+    }
+    store_reward_index_history_len(storage, &pool_key, len)
+}
+
+// in epoch mode, rewards are only rolled into the index when the epoch closes
+fn allocate_pool_reward(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    amount: Uint128,
+    now: u64,
+) -> StdResult<()> {
+    if let Some(mut epoch) = close_pool_epoch(storage, asset_key, now)? {
+        epoch.accumulated_reward = add_reward_sum(epoch.accumulated_reward, amount)?;
+        store_pool_epoch(storage, asset_key, &epoch)
+    } else {
+        // read after the close, it may have released the streams
+        let mut pool_info = read_pool_info(storage, asset_key)?;
+        distribute_pool_reward(&mut pool_info, amount)?;
+        store_pool_info(storage, asset_key, &pool_info)
+    }
+}
+
+// streams the amount over stream_over_seconds or the pool default, zero allocates it at once.
+// Returns the deposit_epoch the deposit took
+fn deposit_pool_reward(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    amount: Uint128,
+    now: u64,
+    stream_over_seconds: Option<u64>,
+) -> Result<u64, ContractError> {
+    let mut pool_info = read_pool_info(storage, asset_key)?;
+    pool_info.deposit_epoch += 1;
+    store_pool_info(storage, asset_key, &pool_info)?;
+    let deposit_epoch = pool_info.deposit_epoch;
+    add_outstanding_rewards(storage, asset_key, amount)?;
+
+    let stream_seconds = stream_over_seconds.unwrap_or(pool_info.reward_stream_seconds);
+    if stream_seconds == 0 {
+        allocate_pool_reward(storage, asset_key, amount, now)?;
+        return Ok(deposit_epoch);
+    }
+
+    // the running streams are released over the bonds they ran on before this one starts
+    close_pool_epoch(storage, asset_key, now)?;
+    let mut pool_info = read_pool_info(storage, asset_key)?;
+    let end_time = now.saturating_add(stream_seconds);
+    match pool_info
+        .reward_streams
+        .iter_mut()
+        .find(|(start_time, stream_end, _)| *start_time == now && *stream_end == end_time)
+    {
+        Some((_, _, total)) => *total = add_reward_sum(*total, amount)?,
+        None if pool_info.reward_streams.len() >= MAX_REWARD_STREAMS => {
+            return Err(ContractError::TooManyRewardStreams {
+                max: MAX_REWARD_STREAMS,
+            })
+        }
+        None => pool_info.reward_streams.push((now, end_time, amount)),
+    }
+    pool_info.last_streamed = now;
+    store_pool_info(storage, asset_key, &pool_info)?;
+    Ok(deposit_epoch)
+}
+
+// the part of the pool streams run since they were last released, ended streams are dropped.
+// None when no stream ran, the pool is left as it was
+fn take_streamed_reward(pool_info: &mut PoolInfo, now: u64) -> Option<Uint128> {
+    if pool_info.reward_streams.is_empty() || pool_info.last_streamed >= now {
+        return None;
+    }
+    let streamed = scheduled_amount(&pool_info.reward_streams, pool_info.last_streamed, now);
+    pool_info
+        .reward_streams
+        .retain(|(_, end_time, _)| *end_time > now);
+    pool_info.last_streamed = now;
+    Some(streamed)
+}
+
+// part of the entry unlocked by `time`, rounded down so the differences add up to the amount
+fn released_amount(entry: &(u64, u64, Uint128), time: u64) -> Uint128 {
+    let (start_time, end_time, amount) = *entry;
+    if time <= start_time {
+        Uint128::zero()
+    } else if time >= end_time {
+        amount
+    } else {
+        amount.multiply_ratio(time - start_time, end_time - start_time)
+    }
+}
+
+// amount unlocked between `from` and `to`, overlapping entries add up
+pub fn scheduled_amount(schedule: &[(u64, u64, Uint128)], from: u64, to: u64) -> Uint128 {
+    if to <= from {
+        return Uint128::zero();
+    }
+    schedule
+        .iter()
+        .map(|entry| released_amount(entry, to) - released_amount(entry, from))
+        .sum()
+}
+
+// the pools the schedule is shared between at `now` with the sum of their reward weights
+fn weighted_pools(
+    storage: &dyn Storage,
+    now: u64,
+) -> StdResult<(Vec<(Vec<u8>, PoolInfo)>, Decimal)> {
+    let pools: Vec<(Vec<u8>, PoolInfo)> = read_all_pool_infos(storage)?
+        .into_iter()
+        .filter(|(_, pool_info)| {
+            !pool_info.reward_weight.is_zero() && pool_info.accepts_reward(now)
+        })
+        .collect();
+    let total_weight = pools.iter().fold(Decimal::zero(), |total, (_, pool_info)| {
+        total + pool_info.reward_weight
+    });
+    Ok((pools, total_weight))
+}
+
+// rewards per second of the schedule entries running at `now`
+fn schedule_rate(schedule: &[(u64, u64, Uint128)], now: u64) -> Decimal {
+    schedule
+        .iter()
+        .filter(|(start_time, end_time, _)| *start_time <= now && now < *end_time)
+        .fold(Decimal::zero(), |rate, (start_time, end_time, amount)| {
+            rate + Decimal::from_ratio(*amount, end_time - start_time)
+        })
+}
+
+// share of each pool in the amount by reward weight, the rounding remainder goes to the last
+// pool. Pools outside their reward window count as unweighted, empty when no pool is weighted
+fn split_by_reward_weights(
+    storage: &dyn Storage,
+    amount: Uint128,
+    now: u64,
+) -> StdResult<Vec<(Vec<u8>, PoolInfo, Uint128)>> {
+    let (pools, total_weight) = weighted_pools(storage, now)?;
+
+    let mut remaining = amount;
+    let last = pools.len().saturating_sub(1);
+    Ok(pools
+        .into_iter()
+        .enumerate()
+        .map(|(i, (asset_key, pool_info))| {
+            let pool_amount = if i == last {
+                remaining
+            } else {
+                amount.multiply_ratio(pool_info.reward_weight.atomics(), total_weight.atomics())
+            };
+            remaining -= pool_amount;
+            (asset_key, pool_info, pool_amount)
+        })
+        .collect())
+}
+
+// adds the schedule amounts unlocked since the last accrual to the pools by their reward
+// weights, while no pool is weighted the unlocked amount waits for the next accrual
+pub fn accrue_distribution(storage: &mut dyn Storage, now: u64) -> StdResult<()> {
+    let mut config = read_config(storage)?;
+    let amount = scheduled_amount(&config.distribution_schedule, config.last_distributed, now);
+    if amount.is_zero() {
+        return Ok(());
+    }
+
+    let shares = split_by_reward_weights(storage, amount, now)?;
+    if shares.is_empty() {
+        return Ok(());
+    }
+    for (asset_key, _, pool_amount) in shares {
+        allocate_pool_reward(storage, &asset_key, pool_amount, now)?;
+        add_outstanding_rewards(storage, &asset_key, pool_amount)?;
+    }
+    update_state(storage, |state| {
+        state.total_scheduled = state.total_scheduled.saturating_add(amount)
+    })?;
+
+    config.last_distributed = now;
+    config
+        .distribution_schedule
+        .retain(|(_, end_time, _)| *end_time > now);
+    store_config(storage, &config)
+}
+
+pub fn query_distribution_schedule(
+    deps: Deps,
+    env: Env,
+) -> StdResult<DistributionScheduleResponse> {
+    let config = read_config(deps.storage)?;
+    let pending_amount = scheduled_amount(
+        &config.distribution_schedule,
+        config.last_distributed,
+        env.block.time.seconds(),
+    );
+
+    Ok(DistributionScheduleResponse {
+        distribution_schedule: config.distribution_schedule,
+        last_distributed: config.last_distributed,
+        pending_amount,
+    })
+}
+
+pub fn query_distribution_info(deps: Deps, env: Env) -> StdResult<DistributionInfoResponse> {
+    let config = read_config(deps.storage)?;
+    let now = env.block.time.seconds();
+    let rewards_per_sec = schedule_rate(&config.distribution_schedule, now);
+    let (pools, total_weight) = weighted_pools(deps.storage, now)?;
+
+    Ok(DistributionInfoResponse {
+        rewards_per_sec,
+        last_distributed: config.last_distributed,
+        pools: pools
+            .into_iter()
+            .map(|(asset_key, pool_info)| {
+                let share =
+                    Decimal::from_ratio(pool_info.reward_weight.atomics(), total_weight.atomics());
+                Ok(PoolDistributionInfo {
+                    staking_token: staking_token_addr(deps.storage, deps.api, &asset_key)?,
+                    reward_weight: pool_info.reward_weight,
+                    share,
+                    rewards_per_sec: rewards_per_sec * share,
+                })
+            })
+            .collect::<StdResult<_>>()?,
+    })
+}
+
+// scheduled and deposited rewards per second of the pool at `now`, both zero outside its reward window
+pub fn pool_reward_rates(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+    pool_info: &PoolInfo,
+    now: u64,
+) -> StdResult<(Decimal, Decimal)> {
+    if !pool_info.accepts_reward(now) {
+        return Ok((Decimal::zero(), Decimal::zero()));
+    }
+
+    let mut scheduled = Decimal::zero();
+    if !pool_info.reward_weight.is_zero() {
+        let config = read_config(storage)?;
+        let (_, total_weight) = weighted_pools(storage, now)?;
+        scheduled = schedule_rate(&config.distribution_schedule, now)
+            * Decimal::from_ratio(pool_info.reward_weight.atomics(), total_weight.atomics());
+    }
+
+    // a deposit pays for the time since the one before, so the oldest only opens the window
+    let deposits = read_pool_deposits(storage, asset_key)?;
+    let deposited = match deposits.first() {
+        Some((first_time, _)) if *first_time < now => Decimal::from_ratio(
+            deposits
+                .iter()
+                .skip(1)
+                .map(|(_, amount)| *amount)
+                .sum::<Uint128>(),
+            now - first_time,
+        ),
+        _ => Decimal::zero(),
+    };
+
+    Ok((scheduled, deposited))
+}
+
+// rewards per second of one unit of weighted bond, None while nothing is bonded
+pub fn rewards_per_sec_per_bond(
+    rewards_per_sec: Decimal,
+    total_weighted_bond: Uint128,
+) -> Option<Decimal> {
+    if total_weighted_bond.is_zero() {
+        return None;
+    }
+    // atomics over an integer keeps the 18 decimals without overflowing on large bonds
+    Some(Decimal::new(
+        rewards_per_sec.atomics() / total_weighted_bond,
+    ))
+}
+
+pub fn query_pool_reward_rate(
+    deps: Deps,
+    env: Env,
+    staking_token: Addr,
+) -> Result<PoolRewardRateResponse, ContractError> {
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    let (scheduled_rewards_per_sec, deposited_rewards_per_sec) = pool_reward_rates(
+        deps.storage,
+        &asset_key,
+        &pool_info,
+        env.block.time.seconds(),
+    )?;
+    let rewards_per_sec = scheduled_rewards_per_sec + deposited_rewards_per_sec;
+    let total_weighted_bond = pool_info.total_weighted_bond();
+
+    Ok(PoolRewardRateResponse {
+        staking_token: staking_token_addr(deps.storage, deps.api, &pool_info.staking_token)?,
+        total_bond_amount: pool_info.total_bond_amount,
+        total_weighted_bond,
+        scheduled_rewards_per_sec,
+        deposited_rewards_per_sec,
+        rewards_per_sec,
+        rewards_per_sec_per_bond: rewards_per_sec_per_bond(rewards_per_sec, total_weighted_bond),
+    })
+}
+
+pub fn query_simulate_bond(
+    deps: Deps,
+    env: Env,
+    staking_token: Addr,
+    amount: Uint128,
+) -> Result<SimulateBondResponse, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let pool_info = read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    let (scheduled_rewards_per_sec, deposited_rewards_per_sec) = pool_reward_rates(
+        deps.storage,
+        &asset_key,
+        &pool_info,
+        env.block.time.seconds(),
+    )?;
+    let rewards_per_sec = scheduled_rewards_per_sec + deposited_rewards_per_sec;
+    let total_weighted_bond = pool_info.total_weighted_bond();
+    let total_after = total_weighted_bond.checked_add(amount)?;
+    // the bond is not zero, so neither is the total after it
+    let rewards_per_sec_per_bond_after =
+        rewards_per_sec_per_bond(rewards_per_sec, total_after).unwrap_or_default();
+
+    Ok(SimulateBondResponse {
+        staking_token: staking_token_addr(deps.storage, deps.api, &pool_info.staking_token)?,
+        amount,
+        pool_share: Decimal::from_ratio(amount, total_after),
+        daily_reward: amount.checked_mul(Uint128::from(86400u128))?
+            * rewards_per_sec_per_bond_after,
+        rewards_per_sec_per_bond: rewards_per_sec_per_bond(rewards_per_sec, total_weighted_bond),
+        rewards_per_sec_per_bond_after,
+    })
+}
+
+// lazily roll the ended epoch into the pool index and release the streams run since the last
+// close, into the index or the running epoch. Returns None if the pool is not in epoch mode
+pub fn close_pool_epoch(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    now: u64,
+) -> StdResult<Option<EpochInfo>> {
+    let epoch = read_pool_epoch(storage, asset_key)?;
+    let mut streamed = Uint128::zero();
+    // a missing pool has nothing streaming, the caller reports it
+    if let Ok(mut pool_info) = read_pool_info(storage, asset_key) {
+        if let Some(released) = take_streamed_reward(&mut pool_info, now) {
+            if epoch.is_none() && !released.is_zero() {
+                distribute_pool_reward(&mut pool_info, released)?;
+            }
+            store_pool_info(storage, asset_key, &pool_info)?;
+            streamed = released;
+        }
+    }
+    let epoch = match epoch {
+        Some(epoch) => epoch,
+        None => return Ok(None),
+    };
+
+    let epoch_number = epoch.epoch_number;
+    let (mut epoch, closed_reward) = compute_epoch(epoch, now);
+    if !closed_reward.is_zero() {
+        // stakers who left during the epoch do not share these rewards
+        let mut pool_info = read_pool_info(storage, asset_key)?;
+        distribute_pool_reward(&mut pool_info, closed_reward)?;
+        store_pool_info(storage, asset_key, &pool_info)?;
+    }
+    // the streams run over the closed epochs are shared by the stakers of the running one
+    epoch.accumulated_reward = add_reward_sum(epoch.accumulated_reward, streamed)?;
+    if epoch.epoch_number != epoch_number || !streamed.is_zero() {
+        store_pool_epoch(storage, asset_key, &epoch)?;
+    }
+
+    Ok(Some(epoch))
+}
+
+// roll the running epoch into the pool index right away, used before changing the pool accounting
+pub fn finalize_pool_epoch(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+) -> StdResult<Option<EpochInfo>> {
+    let mut epoch = match read_pool_epoch(storage, asset_key)? {
+        Some(epoch) => epoch,
+        None => return Ok(None),
+    };
+
+    if !epoch.accumulated_reward.is_zero() {
+        let mut pool_info = read_pool_info(storage, asset_key)?;
+        distribute_pool_reward(&mut pool_info, epoch.accumulated_reward)?;
+        store_pool_info(storage, asset_key, &pool_info)?;
+        epoch.accumulated_reward = Uint128::zero();
+    }
+
+    Ok(Some(epoch))
+}
+
+// close ended epochs of the pools the staker will be settled against
+pub fn close_staker_pool_epochs(
+    storage: &mut dyn Storage,
+    staker_addr: &CanonicalAddr,
+    asset_key: &Option<Vec<u8>>,
+    now: u64,
+) -> StdResult<()> {
+    let asset_keys = if let Some(asset_key) = asset_key {
+        vec![asset_key.to_vec()]
+    } else {
+        rewards_read(storage, staker_addr)
+            .range(None, None, Order::Ascending)
+            .map(|item| item.map(|(k, _)| k))
+            .collect::<StdResult<Vec<Vec<u8>>>>()?
+    };
+
+    for asset_key in asset_keys {
+        close_pool_epoch(storage, &asset_key, now)?;
+    }
+
+    Ok(())
+}
+
+pub fn close_epoch(
+    deps: DepsMut,
+    env: Env,
+    staking_token: Addr,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+
+    let epoch = close_pool_epoch(deps.storage, &asset_key, env.block.time.seconds())?
+        .ok_or(ContractError::PoolNotInEpochMode {})?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "close_epoch"),
+        ("staking_token", staking_token.as_str()),
+        ("epoch_number", &epoch.epoch_number.to_string()),
+    ]))
+}
+
+pub fn query_pool_epoch(
+    deps: Deps,
+    env: Env,
+    staking_token: Addr,
+) -> Result<PoolEpochResponse, ContractError> {
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let epoch =
+        read_pool_epoch(deps.storage, &asset_key)?.ok_or(ContractError::PoolNotInEpochMode {})?;
+
+    let now = env.block.time.seconds();
+    let (epoch, _) = compute_epoch(epoch, now);
+
+    Ok(PoolEpochResponse {
+        epoch_length: epoch.epoch_length,
+        epoch_number: epoch.epoch_number,
+        accumulated_reward: epoch.accumulated_reward,
+        time_remaining: epoch.start_time + epoch.epoch_length - now,
+    })
+}
+
 // withdraw all rewards or single reward depending on asset_token
 pub fn withdraw_reward(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     staking_token: Option<Addr>,
-) -> StdResult<Response> {
+    ibc_recipient: Option<IbcRecipient>,
+    amounts: Option<Vec<Asset>>,
+    recipient: Option<Addr>,
+    staker_addr: Option<Addr>,
+) -> Result<Response, ContractError> {
     validate_migrate_store_status(deps.storage)?;
-    let staker_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
-    let asset_key = staking_token.map_or(None, |a| {
-        deps.api
-            .addr_canonicalize(a.as_str())
-            .map(|a| a.to_vec())
-            .ok()
-    });
+    if let Some(ibc_recipient) = &ibc_recipient {
+        validate_ibc_recipient(ibc_recipient)?;
+    }
+    if recipient.is_some() && ibc_recipient.is_some() {
+        return Err(ContractError::ConflictingRecipients {});
+    }
+    let mut operator = None;
+    let staker = match staker_addr {
+        Some(staker) if staker != info.sender => {
+            let staker_raw = deps.api.addr_canonicalize(staker.as_str())?;
+            let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+            if read_operator(deps.storage, &staker_raw)? != Some(sender_raw) {
+                return Err(ContractError::Unauthorized {});
+            }
+            // the operator may trigger the claim, the rewards still go to the staker
+            if recipient.is_some() || ibc_recipient.is_some() {
+                return Err(ContractError::OperatorRecipient {});
+            }
+            operator = Some(info.sender);
+            staker
+        }
+        _ => info.sender,
+    };
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(recipient.as_str())?,
+        None => staker.clone(),
+    };
+    if amounts.is_some() && staking_token.is_none() {
+        return Err(ContractError::PartialWithdrawWithoutStakingToken {});
+    }
+    let staker_addr = deps.api.addr_canonicalize(staker.as_str())?;
+    // a staking token that does not resolve fails instead of widening the withdraw to every pool
+    let asset_key = staking_token
+        .map(|a| staking_token_key(deps.storage, deps.api, a.as_str()).map(|a| a.to_vec()))
+        .transpose()?;
 
-    let reward_assets = process_reward_assets(deps.storage, &staker_addr, &asset_key, true)?;
+    if asset_key.is_none()
+        && rewards_read(deps.storage, &staker_addr)
+            .range(None, None, Order::Ascending)
+            .take(MAX_WITHDRAW_POOLS + 1)
+            .count()
+            > MAX_WITHDRAW_POOLS
+    {
+        return Err(ContractError::TooManyWithdrawPools {
+            max: MAX_WITHDRAW_POOLS,
+        });
+    }
+
+    close_staker_pool_epochs(
+        deps.storage,
+        &staker_addr,
+        &asset_key,
+        env.block.time.seconds(),
+    )?;
+
+    let pool_reward_assets = match (amounts, &asset_key) {
+        (Some(amounts), Some(asset_key)) => {
+            let amounts = amounts
+                .into_iter()
+                .map(|asset| Ok(asset.to_raw(deps.api)?))
+                .collect::<StdResult<Vec<AssetRaw>>>()?;
+            let reward_assets = process_partial_reward_assets(
+                deps.storage,
+                deps.api,
+                &staker_addr,
+                asset_key,
+                amounts,
+                env.block.time.seconds(),
+            )?;
+            vec![(asset_key.clone(), reward_assets)]
+        }
+        _ => process_reward_assets(
+            deps.storage,
+            &staker_addr,
+            &asset_key,
+            true,
+            env.block.time.seconds(),
+        )?,
+    };
 
-    let messages = reward_assets
+    // the amounts each pool paid, so the claim can be attributed per pool
+    let mut attributes = vec![];
+    let mut reward_assets: Vec<AssetRaw> = vec![];
+    for (pool_key, pool_assets) in pool_reward_assets {
+        refresh_boost(
+            deps.storage,
+            &deps.querier,
+            deps.api,
+            &staker_addr,
+            &pool_key,
+            env.block.time.seconds(),
+        )?;
+        if pool_assets.is_empty() {
+            continue;
+        }
+        let amounts = pool_assets
+            .iter()
+            .map(|ra| Ok(ra.to_normal(deps.api)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?;
+        attributes.push(attr(
+            "staking_token",
+            staking_token_addr(deps.storage, deps.api, &pool_key)?,
+        ));
+        attributes.push(attr("reward_assets", amounts.join(", ")));
+        for ra in pool_assets {
+            update_reward_assets_amount(&mut reward_assets, ra.clone(), ra.amount)?;
+        }
+    }
+
+    // the fee is taken from every asset, and is always sent to the collector on this chain
+    let reward_assets = reward_assets
         .into_iter()
-        .map(|ra| {
-            Ok(ra
-                .to_normal(deps.api)?
-                .into_msg(None, &deps.querier, info.sender.clone())?)
-        })
-        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+        .map(|ra| ra.to_normal(deps.api))
+        .collect::<StdResult<Vec<Asset>>>()?;
+    let (messages, net_assets, payout_attributes) =
+        payout_reward_assets(deps.as_ref(), &env, reward_assets, |asset| {
+            Ok(Some(match &ibc_recipient {
+                Some(ibc_recipient) => ibc_transfer_msg(&env, asset, ibc_recipient)?,
+                None => asset.into_msg(None, &deps.querier, recipient.clone())?,
+            }))
+        })?;
+    attributes.extend(payout_attributes);
+    if let Some(operator) = operator {
+        attributes.push(attr("operator", operator));
+    }
+
+    let recipient = match &ibc_recipient {
+        Some(ibc_recipient) => ibc_recipient.remote_address.clone(),
+        None => recipient.to_string(),
+    };
 
     Ok(Response::new()
         .add_messages(messages)
-        .add_attribute("action", "withdraw_reward"))
+        .add_attributes([
+            ("action", "withdraw_reward"),
+            ("staker_addr", staker.as_str()),
+            ("recipient", recipient.as_str()),
+        ])
+        .add_attributes(attributes)
+        .set_data(to_binary(&WithdrawResponse {
+            reward_assets: net_assets,
+        })?))
+}
+
+pub fn set_operator(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let staker_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    match &operator {
+        Some(operator) => store_operator(
+            deps.storage,
+            &staker_addr,
+            &deps.api.addr_canonicalize(operator.as_str())?,
+        )?,
+        None => remove_operator(deps.storage, &staker_addr),
+    }
+
+    Ok(Response::new().add_attributes([
+        ("action", "set_operator"),
+        ("staker_addr", info.sender.as_str()),
+        (
+            "operator",
+            &operator.map_or("none".to_string(), |operator| operator.to_string()),
+        ),
+    ]))
+}
+
+pub fn query_operator(deps: Deps, staker_addr: Addr) -> StdResult<OperatorResponse> {
+    let operator = read_operator(
+        deps.storage,
+        &deps.api.addr_canonicalize(staker_addr.as_str())?,
+    )?
+    .map(|operator| deps.api.addr_humanize(&operator))
+    .transpose()?;
+
+    Ok(OperatorResponse {
+        staker_addr,
+        operator,
+    })
+}
+
+// the pairs are looked up from a simulated withdraw, so a missing one fails before any settlement
+pub fn withdraw_and_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_token: Addr,
+    target_asset: AssetInfo,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config = read_config(deps.storage)?;
+    let factory_addr = deps.api.addr_humanize(&config.factory_addr)?;
+
+    let simulated = query_simulate_withdraw(
+        deps.as_ref(),
+        env.clone(),
+        info.sender.clone(),
+        Some(staking_token.clone()),
+    )?;
+    let mut pair_addrs = vec![];
+    for asset in simulated.reward_assets {
+        if asset.amount.is_zero() || asset.info.eq(&target_asset) {
+            continue;
+        }
+        let pair_info = query_pair_info(
+            &deps.querier,
+            factory_addr.clone(),
+            &[asset.info.clone(), target_asset.clone()],
+        )
+        .map_err(|_| ContractError::SwapPairNotFound {
+            offer: asset.info.to_string(),
+            ask: target_asset.to_string(),
+        })?;
+        pair_addrs.push((asset.info, pair_info.contract_addr));
+    }
+
+    let staker_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?.to_vec();
+    close_pool_epoch(deps.storage, &asset_key, env.block.time.seconds())?;
+    let pool_reward_assets = process_reward_assets(
+        deps.storage,
+        &staker_addr,
+        &Some(asset_key),
+        true,
+        env.block.time.seconds(),
+    )?;
+
+    let mut reward_assets = vec![];
+    for (_, pool_assets) in pool_reward_assets {
+        for ra in pool_assets {
+            if !ra.amount.is_zero() {
+                reward_assets.push(ra.to_normal(deps.api)?);
+            }
+        }
+    }
+    let amounts = reward_assets
+        .iter()
+        .map(|asset| asset.to_string())
+        .collect::<Vec<String>>();
+
+    // the fee is split off before the swap, so only the net asset is swapped
+    let (messages, _, payout_attributes) =
+        payout_reward_assets(deps.as_ref(), &env, reward_assets, |asset| {
+            Ok(Some(
+                match pair_addrs.iter().find(|(info, _)| info.eq(&asset.info)) {
+                    Some((_, pair_addr)) => swap_msg(
+                        pair_addr,
+                        asset,
+                        belief_price,
+                        max_spread,
+                        Some(info.sender.clone()),
+                    )?,
+                    None => asset.into_msg(None, &deps.querier, info.sender.clone())?,
+                },
+            ))
+        })?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes([
+            ("action", "withdraw_and_swap"),
+            ("staker_addr", info.sender.as_str()),
+            ("staking_token", staking_token.as_str()),
+            ("target_asset", &target_asset.to_string()),
+            ("reward_assets", &amounts.join(", ")),
+        ])
+        .add_attributes(payout_attributes))
+}
+
+// a payout above what the contract holds means the accounting or a rescue went wrong, a token
+// whose balance can not be queried is left to the transfer to report
+fn assert_reward_reserves(
+    querier: &QuerierWrapper,
+    contract_addr: &Addr,
+    asset: &Asset,
+) -> Result<(), ContractError> {
+    if let Ok(balance) = asset.info.query_pool(querier, contract_addr.clone()) {
+        if balance < asset.amount {
+            return Err(ContractError::InsufficientRewardReserves {
+                asset: asset.to_string(),
+                balance,
+            });
+        }
+    }
+    Ok(())
+}
+
+// every reward payout goes through here: the reserves must cover the gross asset, the withdraw
+// fee is sent to the collector on this chain and `deliver` builds the message for the net asset,
+// or none when the caller keeps it
+pub fn payout_reward_assets(
+    deps: Deps,
+    env: &Env,
+    assets: Vec<Asset>,
+    mut deliver: impl FnMut(Asset) -> Result<Option<CosmosMsg>, ContractError>,
+) -> Result<(Vec<CosmosMsg>, Vec<Asset>, Vec<Attribute>), ContractError> {
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut net_assets = vec![];
+    if assets.is_empty() {
+        return Ok((messages, net_assets, vec![]));
+    }
+
+    let config = read_config(deps.storage)?;
+    let fee_collector = config
+        .fee_collector
+        .map(|collector| deps.api.addr_humanize(&collector))
+        .transpose()?;
+    let (mut gross, mut fees, mut nets) = (vec![], vec![], vec![]);
+    for asset in assets {
+        assert_reward_reserves(&deps.querier, &env.contract.address, &asset)?;
+        gross.push(asset.to_string());
+        let (asset, fee) = split_withdraw_fee(asset, config.withdraw_fee_rate);
+        if let Some(fee_collector) = fee_collector.as_ref().filter(|_| !fee.is_zero()) {
+            let fee_asset = Asset {
+                info: asset.info.clone(),
+                amount: fee,
+            };
+            fees.push(fee_asset.to_string());
+            messages.push(fee_asset.into_msg(None, &deps.querier, fee_collector.clone())?);
+        }
+        nets.push(asset.to_string());
+        net_assets.push(asset.clone());
+        messages.extend(deliver(asset)?);
+    }
+
+    Ok((
+        messages,
+        net_assets,
+        vec![
+            attr("gross", gross.join(", ")),
+            attr("fee", fees.join(", ")),
+            attr("net", nets.join(", ")),
+        ],
+    ))
+}
+
+pub fn validate_ibc_recipient(ibc_recipient: &IbcRecipient) -> Result<(), ContractError> {
+    let valid_channel = ibc_recipient
+        .channel_id
+        .strip_prefix("channel-")
+        .map_or(false, |id| {
+            !id.is_empty() && id.chars().all(|c| c.is_ascii_digit())
+        });
+    if !valid_channel {
+        return Err(ContractError::InvalidIbcChannel {
+            channel_id: ibc_recipient.channel_id.clone(),
+        });
+    }
+
+    if ibc_recipient.remote_address.is_empty() {
+        return Err(ContractError::EmptyRemoteAddress {});
+    }
+
+    if ibc_recipient.timeout_seconds < MIN_IBC_TIMEOUT
+        || ibc_recipient.timeout_seconds > MAX_IBC_TIMEOUT
+    {
+        return Err(ContractError::InvalidIbcTimeout {
+            min: MIN_IBC_TIMEOUT,
+            max: MAX_IBC_TIMEOUT,
+        });
+    }
+
+    Ok(())
+}
+
+// only native rewards can be sent through ICS-20 directly
+fn ibc_transfer_msg(
+    env: &Env,
+    asset: Asset,
+    ibc_recipient: &IbcRecipient,
+) -> Result<CosmosMsg, ContractError> {
+    match asset.info {
+        AssetInfo::NativeToken { denom } => Ok(IbcMsg::Transfer {
+            channel_id: ibc_recipient.channel_id.clone(),
+            to_address: ibc_recipient.remote_address.clone(),
+            amount: coin(asset.amount.u128(), denom),
+            timeout: IbcTimeout::with_timestamp(
+                env.block.time.plus_seconds(ibc_recipient.timeout_seconds),
+            ),
+        }
+        .into()),
+        AssetInfo::Token { contract_addr } => Err(ContractError::IbcCw20Reward {
+            contract_addr: contract_addr.to_string(),
+        }),
+    }
 }
 
 pub fn withdraw_reward_others(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     staker_addrs: Vec<Addr>,
     staker_addr: Option<Addr>,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     validate_migrate_store_status(deps.storage)?;
     let config = read_config(deps.storage)?;
 
     // only admin can execute this message
     if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
-    let asset_key = staker_addr.map_or(None, |a| {
-        deps.api
-            .addr_canonicalize(a.as_str())
+    let asset_key = staker_addr.as_ref().map_or(None, |a| {
+        staking_token_key(deps.storage, deps.api, a.as_str())
             .map(|a| a.to_vec())
             .ok()
     });
     // let mut messages: Vec<CosmosMsg> = vec![];
 
-    // withdraw reward for each staker
-    for staker_addr in staker_addrs {
-        let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
-        process_reward_assets(deps.storage, &staker_addr_raw, &asset_key, false)?;
+    // withdraw reward for each staker
+    let staker_addrs_len = staker_addrs.len();
+    for staker_addr in staker_addrs {
+        let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+        process_reward_assets(
+            deps.storage,
+            &staker_addr_raw,
+            &asset_key,
+            false,
+            env.block.time.seconds(),
+        )?;
+    }
+
+    let res = Response::new().add_attributes([
+        ("action", "withdraw_reward_others"),
+        ("stakers", &staker_addrs_len.to_string()),
+    ]);
+    // the parameter names the staking token the rewards are settled for
+    Ok(match staker_addr {
+        Some(staking_token) => res.add_attribute("staking_token", staking_token),
+        None => res,
+    })
+}
+
+// sends the settled rewards of the idle positions among stakers to the community pool, their bond
+// and locks stay as they are. The sweep itself does not count as activity of the staker
+pub fn sweep_abandoned_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_token: Addr,
+    stakers: Vec<Addr>,
+    min_idle_seconds: u64,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+    let community_pool = match &config.community_pool {
+        Some(community_pool) => deps.api.addr_humanize(community_pool)?,
+        None => return Err(ContractError::CommunityPoolNotSet {}),
+    };
+
+    let now = env.block.time.seconds();
+    let min_idle_seconds = min_idle_seconds.max(MIN_ABANDONED_IDLE_SECONDS);
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    accrue_distribution(deps.storage, now)?;
+    close_pool_epoch(deps.storage, &asset_key, now)?;
+    let mut pool_info = read_pool_info(deps.storage, &asset_key)?;
+
+    let mut attributes = vec![];
+    let mut swept_assets: Vec<AssetRaw> = vec![];
+    let mut pool_changed = false;
+    for staker_addr in stakers {
+        let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+        let reward_info = rewards_read(deps.storage, &staker_addr_raw).may_load(&asset_key)?;
+        let mut reward_info = match reward_info {
+            Some(reward_info)
+                if now.saturating_sub(reward_info.last_active(config.reward_activity_since))
+                    >= min_idle_seconds =>
+            {
+                reward_info
+            }
+            _ => {
+                attributes.push(("skipped", staker_addr.to_string()));
+                continue;
+            }
+        };
+        settle_reward_info(
+            deps.storage,
+            &staker_addr_raw,
+            &asset_key,
+            &pool_info,
+            &mut reward_info,
+        )?;
+        // settled like any withdraw, so the locks that expired stop boosting the position
+        pool_changed |= release_expired_locks(
+            deps.storage,
+            &staker_addr_raw,
+            &asset_key,
+            &mut pool_info,
+            &mut reward_info,
+            now,
+        )?;
+        let pending_withdraw = std::mem::take(&mut reward_info.pending_withdraw);
+        rewards_store(deps.storage, &staker_addr_raw).save(&asset_key, &reward_info)?;
+
+        let amounts = pending_withdraw
+            .iter()
+            .filter(|ra| !ra.amount.is_zero())
+            .map(|ra| Ok(ra.to_normal(deps.api)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?;
+        attributes.push(("staker_addr", staker_addr.to_string()));
+        attributes.push(("swept", amounts.join(", ")));
+        for ra in pending_withdraw {
+            update_reward_assets_amount(&mut swept_assets, ra.clone(), ra.amount)?;
+        }
+    }
+
+    if pool_changed {
+        store_pool_info(deps.storage, &asset_key, &pool_info)?;
+    }
+
+    let swept = swept_assets
+        .iter()
+        .fold(Uint128::zero(), |sum, ra| sum.saturating_add(ra.amount));
+    record_withdrawn_rewards(deps.storage, &swept_assets)?;
+    record_pool_claim(deps.storage, &asset_key, swept)?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for ra in swept_assets {
+        if ra.amount.is_zero() {
+            continue;
+        }
+        let asset = ra.to_normal(deps.api)?;
+        assert_reward_reserves(&deps.querier, &env.contract.address, &asset)?;
+        messages.push(asset.into_msg(None, &deps.querier, community_pool.clone())?);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes([
+            ("action", "sweep_abandoned_rewards"),
+            ("staking_token", staking_token.as_str()),
+            ("community_pool", community_pool.as_str()),
+        ])
+        .add_attributes(attributes))
+}
+
+// only the reward deposited while the pool had no bond moves, what the positions earned stays
+pub fn reallocate_orphaned_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    from_asset: Addr,
+    to_assets: Vec<Addr>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+    if to_assets.is_empty() {
+        return Err(ContractError::EmptyReallocationTargets {});
+    }
+
+    let now = env.block.time.seconds();
+    accrue_distribution(deps.storage, now)?;
+    let from_key = staking_token_key(deps.storage, deps.api, from_asset.as_str())?.to_vec();
+    close_pool_epoch(deps.storage, &from_key, now)?;
+    let mut from_pool = read_registered_pool_info(deps.storage, &from_asset, &from_key)?;
+    let amount = from_pool.orphaned_reward.min(from_pool.pending_reward);
+    if amount.is_zero() {
+        return Err(ContractError::NoOrphanedReward {
+            asset: from_asset.to_string(),
+        });
+    }
+
+    // a pool without bond would only orphan the reward again
+    let mut targets: Vec<(Vec<u8>, PoolInfo)> = vec![];
+    for to_asset in to_assets.iter() {
+        let asset_key = staking_token_key(deps.storage, deps.api, to_asset.as_str())?.to_vec();
+        let pool_info = read_registered_pool_info(deps.storage, to_asset, &asset_key)?;
+        if asset_key == from_key
+            || targets.iter().any(|(key, _)| key == &asset_key)
+            || pool_info.total_weighted_bond().is_zero()
+        {
+            return Err(ContractError::InvalidReallocationTarget {
+                asset: to_asset.to_string(),
+            });
+        }
+        targets.push((asset_key, pool_info));
+    }
+
+    from_pool.pending_reward = from_pool.pending_reward.checked_sub(amount)?;
+    from_pool.orphaned_reward = Uint128::zero();
+    store_pool_info(deps.storage, &from_key, &from_pool)?;
+    // the reward is owed in the assets of the pools it moves to
+    let owed = owed_reward_assets(deps.storage, &from_key, amount)?;
+    update_state(deps.storage, |state| {
+        remove_outstanding_rewards(&mut state.total_outstanding_rewards, &owed)
+    })?;
+
+    // the rounding remainder goes to the last pool
+    let share = amount.multiply_ratio(1u128, targets.len() as u128);
+    let mut remaining = amount;
+    let mut attributes = vec![];
+    for (i, ((asset_key, pool_info), to_asset)) in
+        targets.into_iter().zip(to_assets.iter()).enumerate()
+    {
+        let pool_amount = if i + 1 == to_assets.len() {
+            remaining
+        } else {
+            share
+        };
+        remaining = remaining.checked_sub(pool_amount)?;
+        allocate_pool_reward(deps.storage, &asset_key, pool_amount, now)?;
+        add_outstanding_rewards(deps.storage, &asset_key, pool_amount)?;
+        attributes.push(("to_asset", to_asset.to_string()));
+        attributes.push(("amount", pool_amount.to_string()));
     }
 
-    Ok(Response::new().add_attribute("action", "withdraw_reward_others"))
+    Ok(Response::new()
+        .add_attributes([
+            ("action", "reallocate_orphaned_rewards"),
+            ("from_asset", from_asset.as_str()),
+            ("reallocated", &amount.to_string()),
+        ])
+        .add_attributes(attributes))
 }
 
-fn update_reward_assets_amount(reward_assets: &mut Vec<AssetRaw>, rw: AssetRaw, amount: Uint128) {
+fn update_reward_assets_amount(
+    reward_assets: &mut Vec<AssetRaw>,
+    rw: AssetRaw,
+    amount: Uint128,
+) -> StdResult<()> {
     match reward_assets.iter_mut().find(|ra| ra.info.eq(&rw.info)) {
         None => {
             reward_assets.push(AssetRaw {
@@ -135,18 +1753,21 @@ fn update_reward_assets_amount(reward_assets: &mut Vec<AssetRaw>, rw: AssetRaw,
             });
         }
         Some(reward_asset) => {
-            reward_asset.amount += amount;
+            reward_asset.amount = reward_asset.amount.checked_add(amount)?;
         }
     }
+    Ok(())
 }
 
-// this function will return total asset to reward, then later can be updated as pending_withdraw, or send to client
+// this function will return the assets to reward per pool, then later can be updated as pending_withdraw, or send to client
 pub fn process_reward_assets(
     storage: &mut dyn Storage,
     staker_addr: &CanonicalAddr,
     asset_key: &Option<Vec<u8>>,
     do_withdraw: bool,
-) -> StdResult<Vec<AssetRaw>> {
+    now: u64,
+) -> StdResult<Vec<(Vec<u8>, Vec<AssetRaw>)>> {
+    accrue_distribution(storage, now)?;
     let rewards_bucket = rewards_read(storage, staker_addr);
 
     // single reward withdraw, using Vec to store reference variable in local function
@@ -164,52 +1785,49 @@ pub fn process_reward_assets(
     };
 
     // only has value when do_withdraw
-    let mut reward_assets: Vec<AssetRaw> = vec![];
+    let mut pool_reward_assets: Vec<(Vec<u8>, Vec<AssetRaw>)> = vec![];
 
     for reward_pair in reward_pairs {
         let (asset_key, mut reward_info) = reward_pair;
-        let pool_info: PoolInfo = read_pool_info(storage, &asset_key)?;
-
-        // Withdraw reward to pending reward
-        // if the lp token was migrated, and the user did not close their position yet, cap the reward at the snapshot
-        let pool_index = if pool_info.migration_params.is_some()
-            && !read_is_migrated(storage, &asset_key, staker_addr)
-        {
-            pool_info.migration_params.unwrap().index_snapshot
-        } else {
-            pool_info.reward_index
-        };
-
-        before_share_change(pool_index, &mut reward_info)?;
-
-        if !reward_info.pending_reward.is_zero() {
-            // calculate and accumulate the reward amount
-            let rewards_per_sec = read_rewards_per_sec(storage, &asset_key)?;
-            // now calculate weight
-            let total_amount: Uint128 = rewards_per_sec.iter().map(|rw| rw.amount).sum();
-
-            for rw in rewards_per_sec {
-                // ignore empty weight
-                if rw.amount.is_zero() {
-                    continue;
-                }
-                let amount =
-                    reward_info.pending_reward * Decimal::from_ratio(rw.amount, total_amount);
-
-                // update pending_withdraw, first time push it, later update the amount
-                update_reward_assets_amount(&mut reward_info.pending_withdraw, rw, amount);
-            }
+        let mut pool_info: PoolInfo = read_pool_info(storage, &asset_key)?;
 
-            // reset pending_reward
-            reward_info.pending_reward = Uint128::zero();
-        }
+        settle_reward_info(
+            storage,
+            staker_addr,
+            &asset_key,
+            &pool_info,
+            &mut reward_info,
+        )?;
+        let mut pool_changed = release_expired_locks(
+            storage,
+            staker_addr,
+            &asset_key,
+            &mut pool_info,
+            &mut reward_info,
+            now,
+        )?;
 
         // if withdraw, then update reward_assets to create MsgSend
         if do_withdraw {
+            let mut reward_assets: Vec<AssetRaw> = vec![];
             for rw in reward_info.pending_withdraw {
-                update_reward_assets_amount(&mut reward_assets, rw.clone(), rw.amount);
+                update_reward_assets_amount(&mut reward_assets, rw.clone(), rw.amount)?;
             }
+            let claimed = reward_assets
+                .iter()
+                .fold(Uint128::zero(), |sum, ra| sum.saturating_add(ra.amount));
+            reward_info.total_claimed = reward_info.total_claimed.saturating_add(claimed);
+            if !claimed.is_zero() {
+                pool_info.lifetime_rewards_claimed =
+                    pool_info.lifetime_rewards_claimed.saturating_add(claimed);
+                pool_changed = true;
+            }
+            pool_reward_assets.push((asset_key.clone(), reward_assets));
             reward_info.pending_withdraw = vec![];
+            reward_info.last_updated = now;
+        }
+        if pool_changed {
+            store_pool_info(storage, &asset_key, &pool_info)?;
         }
 
         // Update rewards info, if empty bond_amount and withdraw then remove
@@ -220,28 +1838,584 @@ pub fn process_reward_assets(
         }
     }
 
+    let withdrawn: Vec<AssetRaw> = pool_reward_assets
+        .iter()
+        .flat_map(|(_, reward_assets)| reward_assets.iter().cloned())
+        .collect();
+    record_withdrawn_rewards(storage, &withdrawn)?;
+
+    Ok(pool_reward_assets)
+}
+
+// settles the pool like a full withdraw, then takes only the requested amounts out of pending_withdraw
+fn process_partial_reward_assets(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    staker_addr: &CanonicalAddr,
+    asset_key: &[u8],
+    amounts: Vec<AssetRaw>,
+    now: u64,
+) -> Result<Vec<AssetRaw>, ContractError> {
+    accrue_distribution(storage, now)?;
+    let mut reward_info = match rewards_read(storage, staker_addr).may_load(asset_key)? {
+        Some(mut reward_info) => {
+            let mut pool_info = read_pool_info(storage, asset_key)?;
+            settle_reward_info(
+                storage,
+                staker_addr,
+                asset_key,
+                &pool_info,
+                &mut reward_info,
+            )?;
+            if release_expired_locks(
+                storage,
+                staker_addr,
+                asset_key,
+                &mut pool_info,
+                &mut reward_info,
+                now,
+            )? {
+                store_pool_info(storage, asset_key, &pool_info)?;
+            }
+            reward_info
+        }
+        None => RewardInfo {
+            native_token: false,
+            index: Decimal256::zero(),
+            bond_amount: Uint128::zero(),
+            pending_reward: Uint128::zero(),
+            pending_withdraw: vec![],
+            locks: vec![],
+            proxy_indices: vec![],
+            total_claimed: Uint128::zero(),
+            boost_shortfall: Uint128::zero(),
+            last_updated: 0,
+            last_bond_time: 0,
+        },
+    };
+
+    // the same asset requested twice is withdrawn once with the summed amount
+    let mut requested: Vec<AssetRaw> = vec![];
+    for ra in amounts {
+        update_reward_assets_amount(&mut requested, ra.clone(), ra.amount)?;
+    }
+
+    let mut reward_assets = vec![];
+    for ra in requested {
+        if ra.amount.is_zero() {
+            continue;
+        }
+        match reward_info
+            .pending_withdraw
+            .iter_mut()
+            .find(|pw| pw.info.eq(&ra.info))
+        {
+            Some(pw) if ra.amount <= pw.amount => pw.amount -= ra.amount,
+            pw => {
+                return Err(ContractError::WithdrawExceedsClaimable {
+                    asset: ra.info.to_normal(api)?.to_string(),
+                    claimable: pw.map_or(Uint128::zero(), |pw| pw.amount),
+                    requested: ra.amount,
+                })
+            }
+        }
+        reward_assets.push(ra);
+    }
+    reward_info
+        .pending_withdraw
+        .retain(|pw| !pw.amount.is_zero());
+    let withdrawn = reward_assets
+        .iter()
+        .fold(Uint128::zero(), |sum, ra| sum.saturating_add(ra.amount));
+    reward_info.total_claimed = reward_info.total_claimed.saturating_add(withdrawn);
+    reward_info.last_updated = now;
+    record_pool_claim(storage, asset_key, withdrawn)?;
+
+    if reward_info.bond_amount.is_zero() && reward_info.pending_withdraw.is_empty() {
+        rewards_store(storage, staker_addr).remove(asset_key);
+    } else {
+        rewards_store(storage, staker_addr).save(asset_key, &reward_info)?;
+    }
+    record_withdrawn_rewards(storage, &reward_assets)?;
+
     Ok(reward_assets)
 }
 
-// withdraw reward to pending reward
-pub fn before_share_change(pool_index: Decimal, reward_info: &mut RewardInfo) -> StdResult<()> {
-    let pending_reward = (reward_info.bond_amount * pool_index)
-        .checked_sub(reward_info.bond_amount * reward_info.index)?;
+// the lifetime claims only feed the pool queries, they saturate rather than fail a withdraw
+fn record_pool_claim(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    amount: Uint128,
+) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+    let mut pool_info = read_pool_info(storage, asset_key)?;
+    pool_info.lifetime_rewards_claimed = pool_info.lifetime_rewards_claimed.saturating_add(amount);
+    store_pool_info(storage, asset_key, &pool_info)
+}
+
+// move the rewards earned since the last settlement into pending_withdraw
+fn settle_reward_info(
+    storage: &dyn Storage,
+    staker_addr: &CanonicalAddr,
+    asset_key: &[u8],
+    pool_info: &PoolInfo,
+    reward_info: &mut RewardInfo,
+) -> StdResult<()> {
+    // Withdraw reward to pending reward
+    // if the lp token was migrated, and the user did not close their position yet, cap the reward at the snapshot
+    let pool_index = position_index(pool_info, read_is_migrated(storage, asset_key, staker_addr));
+
+    before_share_change(pool_index, reward_info)?;
+    before_proxy_share_change(
+        &pool_info.proxy_indices(
+            pool_info.migration_params.is_some()
+                && !read_is_migrated(storage, asset_key, staker_addr),
+        ),
+        reward_info,
+    )?;
+
+    if !reward_info.pending_reward.is_zero() {
+        // calculate and accumulate the reward amount
+        let rewards_per_sec = read_rewards_per_sec(storage, asset_key)?;
+        // now calculate weight
+        let total_amount: Uint128 = rewards_per_sec.iter().map(|rw| rw.amount).sum();
+
+        for rw in rewards_per_sec {
+            // ignore empty weight
+            if rw.amount.is_zero() {
+                continue;
+            }
+            let amount = reward_info.pending_reward * Decimal::from_ratio(rw.amount, total_amount);
+
+            // update pending_withdraw, first time push it, later update the amount
+            update_reward_assets_amount(&mut reward_info.pending_withdraw, rw, amount)?;
+        }
+
+        // reset pending_reward
+        reward_info.pending_reward = Uint128::zero();
+    }
+
+    Ok(())
+}
+
+// pool info with the streams run so far released, without storing anything. While an epoch
+// runs they are released into the epoch, so the index it reads stays as stored
+fn read_streamed_pool_info(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+    now: u64,
+) -> StdResult<PoolInfo> {
+    let mut pool_info = read_pool_info(storage, asset_key)?;
+    let streamed = take_streamed_reward(&mut pool_info, now).unwrap_or_default();
+    if !streamed.is_zero() && read_pool_epoch(storage, asset_key)?.is_none() {
+        distribute_pool_reward(&mut pool_info, streamed)?;
+    }
+
+    Ok(pool_info)
+}
+
+// pool info as it will be once the ended epoch is closed, without storing anything
+fn read_settled_pool_info(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+    now: u64,
+) -> StdResult<PoolInfo> {
+    let mut pool_info = read_streamed_pool_info(storage, asset_key, now)?;
+    if let Some(epoch) = read_pool_epoch(storage, asset_key)? {
+        let (_, closed_reward) = compute_epoch(epoch, now);
+        if !closed_reward.is_zero() {
+            distribute_pool_reward(&mut pool_info, closed_reward)?;
+        }
+    }
+
+    Ok(pool_info)
+}
+
+// read_settled_pool_info with the share of the distribution schedule a withdraw would accrue first
+fn read_accrued_pool_info(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+    now: u64,
+) -> StdResult<PoolInfo> {
+    let mut pool_info = read_settled_pool_info(storage, asset_key, now)?;
+    // while an epoch runs the scheduled share waits in the epoch
+    if read_pool_epoch(storage, asset_key)?.is_some() {
+        return Ok(pool_info);
+    }
+    let config = read_config(storage)?;
+    let amount = scheduled_amount(&config.distribution_schedule, config.last_distributed, now);
+    if amount.is_zero() {
+        return Ok(pool_info);
+    }
+    if let Some((_, _, share)) = split_by_reward_weights(storage, amount, now)?
+        .into_iter()
+        .find(|(key, _, _)| key.as_slice() == asset_key)
+    {
+        distribute_pool_reward(&mut pool_info, share)?;
+    }
+
+    Ok(pool_info)
+}
+
+// the reward assets a withdraw would pay right now, split into the withdraw fees sent to the fee
+// collector and the net assets the recipient gets
+pub fn query_simulate_withdraw(
+    deps: Deps,
+    env: Env,
+    staker_addr: Addr,
+    staking_token: Option<Addr>,
+) -> StdResult<SimulateWithdrawResponse> {
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let rewards_bucket = rewards_read(deps.storage, &staker_addr_raw);
+
+    let reward_pairs = match staking_token {
+        Some(staking_token) => {
+            let asset_key =
+                staking_token_key(deps.storage, deps.api, staking_token.as_str())?.to_vec();
+            match rewards_bucket.may_load(&asset_key)? {
+                Some(reward_info) => vec![(asset_key, reward_info)],
+                None => vec![],
+            }
+        }
+        None => rewards_bucket
+            .range(None, None, Order::Ascending)
+            .collect::<StdResult<Vec<(Vec<u8>, RewardInfo)>>>()?,
+    };
+
+    // the pools are settled as withdraw_reward settles them, distribution schedule included
+    let mut reward_assets: Vec<AssetRaw> = vec![];
+    let mut pools = vec![];
+    for (asset_key, mut reward_info) in reward_pairs {
+        let pool_info = read_accrued_pool_info(deps.storage, &asset_key, env.block.time.seconds())?;
+        settle_reward_info(
+            deps.storage,
+            &staker_addr_raw,
+            &asset_key,
+            &pool_info,
+            &mut reward_info,
+        )?;
+
+        let mut pool_assets: Vec<AssetRaw> = vec![];
+        for rw in reward_info.pending_withdraw {
+            update_reward_assets_amount(&mut pool_assets, rw.clone(), rw.amount)?;
+        }
+        for ra in pool_assets.iter() {
+            update_reward_assets_amount(&mut reward_assets, ra.clone(), ra.amount)?;
+        }
+        pools.push(SimulatedPoolWithdraw {
+            staking_token: staking_token_addr(deps.storage, deps.api, &asset_key)?,
+            reward_assets: pool_assets
+                .into_iter()
+                .map(|ra| ra.to_normal(deps.api))
+                .collect::<StdResult<Vec<Asset>>>()?,
+        });
+    }
+
+    let reward_assets = reward_assets
+        .into_iter()
+        .map(|ra| ra.to_normal(deps.api))
+        .collect::<StdResult<Vec<Asset>>>()?;
+    let withdraw_fee_rate = read_config(deps.storage)?.withdraw_fee_rate;
+    let (mut withdraw_fees, mut net_assets) = (vec![], vec![]);
+    for asset in reward_assets.iter() {
+        let (net, fee) = split_withdraw_fee(asset.clone(), withdraw_fee_rate);
+        if !fee.is_zero() {
+            withdraw_fees.push(Asset {
+                info: asset.info.clone(),
+                amount: fee,
+            });
+        }
+        net_assets.push(net);
+    }
+
+    Ok(SimulateWithdrawResponse {
+        staker_addr,
+        reward_assets,
+        withdraw_fees,
+        net_assets,
+        pools,
+    })
+}
+
+// the part of a withdrawn asset the recipient gets and the withdraw fee taken from it
+pub fn split_withdraw_fee(mut asset: Asset, withdraw_fee_rate: Decimal) -> (Asset, Uint128) {
+    let fee = asset.amount * withdraw_fee_rate;
+    asset.amount -= fee;
+    (asset, fee)
+}
+
+// one position with what a Withdraw in this block would pay for it, after the withdraw fee
+pub fn query_staker_info(
+    deps: Deps,
+    env: Env,
+    staker_addr: Addr,
+    staking_token: Addr,
+) -> StdResult<StakerInfoResponse> {
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?.to_vec();
+    let now = env.block.time.seconds();
+    let pool_info = read_accrued_pool_info(deps.storage, &asset_key, now)?;
+
+    let mut reward_info = rewards_read(deps.storage, &staker_addr_raw)
+        .may_load(&asset_key)?
+        .unwrap_or_else(|| RewardInfo {
+            native_token: false,
+            index: pool_info.reward_index,
+            bond_amount: Uint128::zero(),
+            pending_reward: Uint128::zero(),
+            pending_withdraw: vec![],
+            locks: vec![],
+            proxy_indices: vec![],
+            total_claimed: Uint128::zero(),
+            boost_shortfall: Uint128::zero(),
+            last_updated: 0,
+            last_bond_time: 0,
+        });
+    settle_reward_info(
+        deps.storage,
+        &staker_addr_raw,
+        &asset_key,
+        &pool_info,
+        &mut reward_info,
+    )?;
+    // the position is still on the deprecated token until it is closed or migrated
+    let deprecated_staking_token = match &pool_info.migration_params {
+        Some(params) if !read_is_migrated(deps.storage, &asset_key, &staker_addr_raw) => {
+            Some(deps.api.addr_humanize(&params.deprecated_staking_token)?)
+        }
+        _ => None,
+    };
+
+    let withdraw_fee_rate = read_config(deps.storage)?.withdraw_fee_rate;
+    let (mut pending_rewards, mut withdraw_fees) = (vec![], vec![]);
+    for ra in reward_info.pending_withdraw.iter() {
+        let (asset, fee) = split_withdraw_fee(ra.to_normal(deps.api)?, withdraw_fee_rate);
+        if !fee.is_zero() {
+            withdraw_fees.push(Asset {
+                info: asset.info.clone(),
+                amount: fee,
+            });
+        }
+        pending_rewards.push(asset);
+    }
+
+    let unbonding = unbonding_read(deps.storage, &staker_addr_raw)
+        .may_load(&asset_key)?
+        .unwrap_or_default();
+    let claimable_amount = unbonding
+        .iter()
+        .filter(|entry| entry.release_time <= now)
+        .map(|entry| entry.amount)
+        .sum();
+
+    Ok(StakerInfoResponse {
+        staker_addr,
+        staking_token: staking_token_addr(deps.storage, deps.api, &pool_info.staking_token)?,
+        deprecated_staking_token,
+        bond_amount: reward_info.bond_amount,
+        weighted_bond_amount: reward_info.weighted_bond_amount(),
+        locks: reward_info.locks,
+        pending_rewards,
+        withdraw_fees,
+        unbonding,
+        claimable_amount,
+    })
+}
+
+// ends the boost of the locks expired at `now`, the position must be settled at its old weight first
+// returns whether the pool total changed
+pub fn release_expired_locks(
+    storage: &dyn Storage,
+    staker_addr: &CanonicalAddr,
+    asset_key: &[u8],
+    pool_info: &mut PoolInfo,
+    reward_info: &mut RewardInfo,
+    now: u64,
+) -> StdResult<bool> {
+    let released_boost = reward_info.release_expired_locks(now);
+    let released_shortfall = reward_info.clamp_boost_shortfall();
+    // like its bond amount, the boost of a position not migrated yet left the pool total when
+    // the staking token was deprecated
+    if released_boost.is_zero()
+        || (pool_info.migration_params.is_some()
+            && !read_is_migrated(storage, asset_key, staker_addr))
+    {
+        return Ok(false);
+    }
+    pool_info.total_lock_boost = pool_info.total_lock_boost.checked_sub(released_boost)?;
+    pool_info.total_boost_shortfall = pool_info
+        .total_boost_shortfall
+        .checked_sub(released_shortfall)?;
+    Ok(true)
+}
+
+// a position keeps this share of its weight without any vote escrow balance
+const BOOST_BASE_PERCENT: u64 = 40;
+
+// the weight the position lacks for its vote escrow balance, counting it up to its full weight
+// at BOOST_BASE_PERCENT of it plus the rest of the pool weight in proportion to its voting power
+pub fn compute_boost_shortfall(
+    weight: Uint128,
+    pool_weight: Uint128,
+    voting_power: &VotingPowerResponse,
+) -> Uint128 {
+    let mut boosted_weight = weight * Decimal::percent(BOOST_BASE_PERCENT);
+    if !voting_power.total_voting_power.is_zero() {
+        boosted_weight += (pool_weight * Decimal::percent(100 - BOOST_BASE_PERCENT))
+            .multiply_ratio(voting_power.voting_power, voting_power.total_voting_power);
+    }
+    weight - boosted_weight.min(weight)
+}
+
+// settles the position at its old weight and moves it to the boost the boost contract reports now,
+// without a boost contract any boost the position had is dropped
+pub fn refresh_boost(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    api: &dyn Api,
+    staker_addr: &CanonicalAddr,
+    asset_key: &[u8],
+    now: u64,
+) -> StdResult<()> {
+    let reward_info = match rewards_read(storage, staker_addr).may_load(asset_key)? {
+        Some(reward_info) => reward_info,
+        None => return Ok(()),
+    };
+    let pool_info = read_pool_info(storage, asset_key)?;
+    // a position not migrated yet left the pool total when the staking token was deprecated
+    if pool_info.migration_params.is_some() && !read_is_migrated(storage, asset_key, staker_addr) {
+        return Ok(());
+    }
+    let boost_shortfall = match read_config(storage)?.boost_contract {
+        Some(boost_contract) => {
+            let voting_power: VotingPowerResponse = querier.query_wasm_smart(
+                api.addr_humanize(&boost_contract)?,
+                &BoostQueryMsg::VotingPower {
+                    address: api.addr_humanize(staker_addr)?.to_string(),
+                },
+            )?;
+            compute_boost_shortfall(
+                reward_info.unboosted_weight(),
+                pool_info.total_bond_amount + pool_info.total_lock_boost,
+                &voting_power,
+            )
+        }
+        None => Uint128::zero(),
+    };
+    if boost_shortfall == reward_info.boost_shortfall {
+        return Ok(());
+    }
+
+    accrue_distribution(storage, now)?;
+    close_pool_epoch(storage, asset_key, now)?;
+    let mut pool_info = read_pool_info(storage, asset_key)?;
+    let mut reward_info = reward_info;
+    settle_reward_info(
+        storage,
+        staker_addr,
+        asset_key,
+        &pool_info,
+        &mut reward_info,
+    )?;
+    pool_info.total_boost_shortfall = pool_info
+        .total_boost_shortfall
+        .checked_sub(reward_info.boost_shortfall)?
+        .checked_add(boost_shortfall)?;
+    reward_info.boost_shortfall = boost_shortfall;
+
+    rewards_store(storage, staker_addr).save(asset_key, &reward_info)?;
+    store_pool_info(storage, asset_key, &pool_info)
+}
+
+pub fn update_boost(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    staking_token: Addr,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    refresh_boost(
+        deps.storage,
+        &deps.querier,
+        deps.api,
+        &staker_addr_raw,
+        &asset_key,
+        env.block.time.seconds(),
+    )?;
+    let boost_shortfall = rewards_read(deps.storage, &staker_addr_raw)
+        .may_load(&asset_key)?
+        .map_or(Uint128::zero(), |reward_info| reward_info.boost_shortfall);
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_boost"),
+        ("staker_addr", staker_addr.as_str()),
+        ("staking_token", staking_token.as_str()),
+        ("boost_shortfall", &boost_shortfall.to_string()),
+    ]))
+}
+
+// indices accumulate as Decimal256, responses still report them as Decimal
+pub fn index_to_decimal(index: Decimal256) -> StdResult<Decimal> {
+    Decimal::try_from(index).map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+// proxy rewards go straight to pending_withdraw as their cw20 asset
+pub fn before_proxy_share_change(
+    proxy_indices: &[(CanonicalAddr, Decimal)],
+    reward_info: &mut RewardInfo,
+) -> StdResult<()> {
+    let weighted_bond_amount = reward_info.weighted_bond_amount();
+    for (reward_token, proxy_index) in proxy_indices {
+        let position = match reward_info
+            .proxy_indices
+            .iter()
+            .position(|(token, _)| token == reward_token)
+        {
+            Some(position) => position,
+            None => {
+                reward_info
+                    .proxy_indices
+                    .push((reward_token.clone(), Decimal::zero()));
+                reward_info.proxy_indices.len() - 1
+            }
+        };
+        let index = &mut reward_info.proxy_indices[position].1;
+        let amount = weighted_bond_amount * proxy_index.checked_sub(*index)?;
+        *index = *proxy_index;
+
+        if !amount.is_zero() {
+            let reward_asset = AssetRaw {
+                info: AssetInfoRaw::Token {
+                    contract_addr: reward_token.clone(),
+                },
+                amount,
+            };
+            update_reward_assets_amount(&mut reward_info.pending_withdraw, reward_asset, amount)?;
+        }
+    }
 
-    reward_info.index = pool_index;
-    reward_info.pending_reward += pending_reward;
     Ok(())
 }
 
 pub fn query_reward_info(
     deps: Deps,
+    env: Env,
     staker_addr: Addr,
     staking_token: Option<Addr>,
 ) -> StdResult<RewardInfoResponse> {
     let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
 
-    let reward_infos: Vec<RewardInfoResponseItem> =
-        _read_reward_infos_response(deps.api, deps.storage, &staker_addr_raw, &staking_token)?;
+    let reward_infos: Vec<RewardInfoResponseItem> = _read_reward_infos_response(
+        deps.api,
+        deps.storage,
+        &staker_addr_raw,
+        &staking_token,
+        env.block.time.seconds(),
+    )?;
 
     Ok(RewardInfoResponse {
         staker_addr,
@@ -249,96 +2423,272 @@ pub fn query_reward_info(
     })
 }
 
+// snapshots after start_after_time, oldest first
+pub fn query_reward_index_history(
+    deps: Deps,
+    staking_token: Addr,
+    start_after_time: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Vec<RewardIndexHistoryItem>, ContractError> {
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+    let pool_key = resolve_pool_key(deps.storage, &asset_key);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after_time.map(|time| time.to_be_bytes().to_vec()));
+
+    reward_index_history_read(deps.storage, &pool_key)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (time_key, snapshot) = item?;
+            let time = u64::from_be_bytes(
+                time_key
+                    .try_into()
+                    .map_err(|_| StdError::generic_err("invalid snapshot key"))?,
+            );
+            Ok(RewardIndexHistoryItem {
+                time,
+                reward_index: index_to_decimal(snapshot.reward_index)?,
+                total_bond_amount: snapshot.total_bond_amount,
+                deposit_epoch: snapshot.deposit_epoch,
+            })
+        })
+        .collect()
+}
+
+pub fn query_batch_reward_info(
+    deps: Deps,
+    env: Env,
+    stakers: Vec<Addr>,
+    staking_token: Addr,
+) -> Result<BatchRewardInfoResponse, ContractError> {
+    if stakers.len() > MAX_BATCH_STAKERS {
+        return Err(ContractError::TooManyStakers {
+            max: MAX_BATCH_STAKERS,
+        });
+    }
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?.to_vec();
+    read_registered_pool_info(deps.storage, &staking_token, &asset_key)?;
+
+    let mut reward_infos = vec![];
+    for staker_addr in stakers {
+        let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+        let reward_info = match rewards_read(deps.storage, &staker_addr_raw).may_load(&asset_key)? {
+            Some(reward_info) => {
+                let item = _reward_info_response_item(
+                    deps.api,
+                    deps.storage,
+                    &staker_addr_raw,
+                    staking_token.clone(),
+                    reward_info,
+                    env.block.time.seconds(),
+                )?;
+                StakerRewardInfo {
+                    staker_addr,
+                    bond_amount: item.bond_amount,
+                    pending_reward: item.pending_reward,
+                    pending_withdraw: item.pending_withdraw,
+                }
+            }
+            None => StakerRewardInfo {
+                staker_addr,
+                bond_amount: Uint128::zero(),
+                pending_reward: Uint128::zero(),
+                pending_withdraw: vec![],
+            },
+        };
+        reward_infos.push(reward_info);
+    }
+
+    Ok(BatchRewardInfoResponse {
+        staking_token,
+        reward_infos,
+    })
+}
+
 pub fn query_all_reward_infos(
     deps: Deps,
+    env: Env,
     staking_token: Addr,
     start_after: Option<Addr>,
     limit: Option<u32>,
     order: Option<i32>,
 ) -> StdResult<Vec<RewardInfoResponse>> {
-    // default is Ascending
-    let order_by = Order::try_from(order.unwrap_or(1))?;
-    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?;
+    let order = parse_order(order)?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
 
     let start_after = start_after
         .map_or(None, |a| deps.api.addr_canonicalize(a.as_str()).ok())
         .map(|c| c.to_vec());
 
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-
-    let (start, end) = match order_by {
-        Order::Ascending => (calc_range_start(start_after), None),
-        Order::Descending => (None, start_after),
-    };
+    let limit = page_limit(limit, DEFAULT_LIMIT, MAX_LIMIT);
 
-    let info_responses = stakers_read(deps.storage, &asset_key)
-        .range(start.as_deref(), end.as_deref(), order_by)
-        .take(limit)
-        .map(|item| {
-            let (k, _) = item?;
-            let staker_addr_raw = CanonicalAddr::from(k);
-            let reward_infos: Vec<RewardInfoResponseItem> = _read_reward_infos_response(
-                deps.api,
-                deps.storage,
-                &staker_addr_raw,
-                &Some(staking_token.clone()),
-            )?;
-            let staker_addr = deps.api.addr_humanize(&staker_addr_raw)?;
-            Ok(RewardInfoResponse {
-                staker_addr,
-                reward_infos,
-            })
+    let info_responses = read_range_prefixed::<bool>(
+        deps.storage,
+        &[PREFIX_STAKER, &asset_key],
+        start_after,
+        limit,
+        order,
+    )?
+    .into_iter()
+    .map(|(k, _)| {
+        let staker_addr_raw = CanonicalAddr::from(k);
+        let reward_infos: Vec<RewardInfoResponseItem> = _read_reward_infos_response(
+            deps.api,
+            deps.storage,
+            &staker_addr_raw,
+            &Some(staking_token.clone()),
+            env.block.time.seconds(),
+        )?;
+        let staker_addr = deps.api.addr_humanize(&staker_addr_raw)?;
+        Ok(RewardInfoResponse {
+            staker_addr,
+            reward_infos,
         })
-        .collect::<StdResult<Vec<RewardInfoResponse>>>()?;
+    })
+    .collect::<StdResult<Vec<RewardInfoResponse>>>()?;
 
     Ok(info_responses)
 }
 
+// the positions of a staker page by page, in staking token key order
+pub fn query_staker_reward_infos(
+    deps: Deps,
+    env: Env,
+    staker_addr: Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    order: Option<i32>,
+) -> StdResult<RewardInfoResponse> {
+    let order = parse_order(order)?;
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let start_after = start_after
+        .map(|staking_token| staking_token_key(deps.storage, deps.api, staking_token.as_str()))
+        .transpose()?
+        .map(|key| key.to_vec());
+    let limit = page_limit(limit, DEFAULT_LIMIT, MAX_LIMIT);
+
+    let reward_infos = read_range_prefixed::<RewardInfo>(
+        deps.storage,
+        &[PREFIX_REWARD, &staker_addr_raw],
+        start_after,
+        limit,
+        order,
+    )?
+    .into_iter()
+    .map(|(asset_key, reward_info)| {
+        let staking_token = staking_token_addr(deps.storage, deps.api, &asset_key)?;
+        _reward_info_response_item(
+            deps.api,
+            deps.storage,
+            &staker_addr_raw,
+            staking_token,
+            reward_info,
+            env.block.time.seconds(),
+        )
+    })
+    .collect::<StdResult<Vec<RewardInfoResponseItem>>>()?;
+
+    Ok(RewardInfoResponse {
+        staker_addr,
+        reward_infos,
+    })
+}
+
 fn _read_reward_infos_response(
     api: &dyn Api,
     storage: &dyn Storage,
     staker_addr: &CanonicalAddr,
     staking_token: &Option<Addr>,
+    now: u64,
 ) -> StdResult<Vec<RewardInfoResponseItem>> {
     let results = _read_reward_infos(api, storage, staker_addr, staking_token)?;
     let reward_infos: Vec<RewardInfoResponseItem> = results
         .into_iter()
-        .map(|(staking_token, mut reward_info)| {
-            let asset_key = api.addr_canonicalize(staking_token.as_str())?.to_vec();
-            let pool_info = read_pool_info(storage, &asset_key)?;
-
-            let (pool_index, should_migrate) = if pool_info.migration_params.is_some()
-                && !read_is_migrated(storage, &asset_key, staker_addr)
-            {
-                (
-                    pool_info.migration_params.unwrap().index_snapshot,
-                    Some(true),
-                )
-            } else {
-                (pool_info.reward_index, None)
-            };
-
-            before_share_change(pool_index, &mut reward_info)?;
-
-            let pending_withdraw = reward_info
-                .pending_withdraw
-                .into_iter()
-                .map(|pw| Ok(pw.to_normal(api)?))
-                .collect::<StdResult<Vec<Asset>>>()?;
-
-            Ok(RewardInfoResponseItem {
-                staking_token,
-                bond_amount: reward_info.bond_amount,
-                pending_reward: reward_info.pending_reward,
-                pending_withdraw,
-                should_migrate,
-            })
+        .map(|(staking_token, reward_info)| {
+            _reward_info_response_item(api, storage, staker_addr, staking_token, reward_info, now)
         })
         .collect::<StdResult<Vec<RewardInfoResponseItem>>>()?;
 
     Ok(reward_infos)
 }
 
+// settles the position read-only against the pool index a withdraw in this block would use, with
+// the streams, the ended epoch and the schedule share taken in, deprecated positions stop at the
+// snapshot
+fn _reward_info_response_item(
+    api: &dyn Api,
+    storage: &dyn Storage,
+    staker_addr: &CanonicalAddr,
+    staking_token: Addr,
+    mut reward_info: RewardInfo,
+    now: u64,
+) -> StdResult<RewardInfoResponseItem> {
+    let asset_key = staking_token_key(storage, api, staking_token.as_str())?.to_vec();
+    let pool_info = read_accrued_pool_info(storage, &asset_key, now)?;
+
+    let position_migrated = read_is_migrated(storage, &asset_key, staker_addr);
+    let pool_index = position_index(&pool_info, position_migrated);
+    let should_migrate = if pool_info.migration_params.is_some() && !position_migrated {
+        Some(true)
+    } else {
+        None
+    };
+
+    before_share_change(pool_index, &mut reward_info)?;
+    before_proxy_share_change(
+        &pool_info.proxy_indices(should_migrate.is_some()),
+        &mut reward_info,
+    )?;
+    let weighted_bond_amount = reward_info.weighted_bond_amount();
+
+    let pending_withdraw = reward_info
+        .pending_withdraw
+        .into_iter()
+        .map(|pw| Ok(pw.to_normal(api)?))
+        .collect::<StdResult<Vec<Asset>>>()?;
+
+    let reward_decimals = read_asset_decimals(api, &pool_info, &pending_withdraw)?;
+    let last_updated = reward_info.last_active(read_config(storage)?.reward_activity_since);
+
+    Ok(RewardInfoResponseItem {
+        staking_token,
+        bond_amount: reward_info.bond_amount,
+        weighted_bond_amount,
+        locks: reward_info.locks,
+        pending_reward: reward_info.pending_reward,
+        pending_withdraw,
+        reward_decimals,
+        should_migrate,
+        total_claimed: reward_info.total_claimed,
+        last_updated,
+    })
+}
+
+// cached decimals of the given assets, in the order of the assets
+pub fn read_asset_decimals(
+    api: &dyn Api,
+    pool_info: &PoolInfo,
+    assets: &[Asset],
+) -> StdResult<Vec<AssetDecimals>> {
+    let mut reward_decimals = vec![];
+    for asset in assets {
+        let info_raw = asset.info.to_raw(api)?;
+        if let Some(cached) = pool_info
+            .reward_decimals
+            .iter()
+            .find(|cached| cached.info.eq(&info_raw))
+        {
+            reward_decimals.push(AssetDecimals {
+                info: asset.info.clone(),
+                decimals: cached.decimals,
+            });
+        }
+    }
+
+    Ok(reward_decimals)
+}
+
 fn _read_reward_infos(
     api: &dyn Api,
     storage: &dyn Storage,
@@ -347,7 +2697,7 @@ fn _read_reward_infos(
 ) -> StdResult<Vec<(Addr, RewardInfo)>> {
     let rewards_bucket = rewards_read(storage, staker_addr);
     let results = if let Some(staking_token) = staking_token {
-        let asset_key = api.addr_canonicalize(staking_token.as_str())?.to_vec();
+        let asset_key = staking_token_key(storage, api, staking_token.as_str())?.to_vec();
 
         if let Some(reward_info) = rewards_bucket.may_load(&asset_key)? {
             vec![(staking_token.clone(), reward_info)]
@@ -361,7 +2711,7 @@ fn _read_reward_infos(
                 let (asset_key, reward_info) = item?;
 
                 // try convert to AssetInfo based on reward info
-                let staking_token = api.addr_humanize(&CanonicalAddr::from(asset_key))?;
+                let staking_token = staking_token_addr(storage, api, &asset_key)?;
                 api.debug(staking_token.as_str());
                 Ok((staking_token, reward_info))
             })
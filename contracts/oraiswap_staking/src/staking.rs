@@ -1,208 +1,1718 @@
-use crate::contract::validate_migrate_store_status;
-use crate::rewards::before_share_change;
+use crate::contract::{
+    assert_not_paused, read_registered_pool_info, validate_migrate_store_status, HOOK_REPLY_ID,
+};
+use crate::reward_math::before_share_change;
+use crate::rewards::{
+    accrue_distribution, before_proxy_share_change, close_pool_epoch, payout_reward_assets,
+    process_reward_assets, record_withdrawn_rewards, refresh_boost, release_expired_locks,
+    DEFAULT_LIMIT, MAX_LIMIT,
+};
 use crate::state::{
-    read_config, read_is_migrated, read_pool_info, rewards_read, rewards_store, stakers_store,
-    store_is_migrated, store_pool_info, Config, PoolInfo, RewardInfo,
+    bond_checkpoints_read, bond_checkpoints_store, is_native_staking_token, lock_boost, may_bond,
+    read_config, read_is_migrated, read_next_pool_info_key, read_pool_info, read_prune_cursor,
+    read_range_prefixed, read_total_unbonding, referral_store, remove_prune_cursor, rewards_read,
+    rewards_store, stakers_read, stakers_store, staking_token_addr, staking_token_key,
+    store_is_migrated, store_pool_info, store_prune_cursor, store_total_unbonding, unbonding_read,
+    unbonding_store, Config, PoolInfo, RewardInfo, StakingTokenBucket, PREFIX_REFERRAL,
+    PREFIX_STAKER, PREFIX_UNBONDING,
 };
 use cosmwasm_std::{
-    attr, to_binary, Addr, Api, CanonicalAddr, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo,
-    Response, StdError, StdResult, Storage, Uint128, WasmMsg,
+    attr, coins, to_binary, Addr, Api, BankMsg, CanonicalAddr, Coin, CosmosMsg, Decimal,
+    Decimal256, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult, Storage,
+    SubMsg, Uint128, WasmMsg,
 };
-use cw20::Cw20ExecuteMsg;
+use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
 use oraiswap::asset::{Asset, AssetInfo, PairInfo};
-use oraiswap::pair::ExecuteMsg as PairExecuteMsg;
-use oraiswap::querier::{query_pair_info, query_token_balance};
-use oraiswap::staking::ExecuteMsg;
+use oraiswap::error::ContractError;
+use oraiswap::pair::{
+    Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg, PairResponse, PoolResponse,
+    QueryMsg as PairQueryMsg,
+};
+use oraiswap::querier::{page_limit, parse_order, query_pair_info, query_token_balance};
+use oraiswap::staking::{
+    BondAmountAtResponse, BondLock, BondResponse, ExecuteMsg, ReferralStat, ReferralStatsResponse,
+    StakerBond, StakersResponse, StakingHookMsg, StaleStaker, StaleStakersResponse, UnbondResponse,
+    UnbondSendMsg, UnbondingClaim, UnbondingClaimsResponse, UnbondingEntry, UnbondingResponse,
+};
+
+// a staker can not have more unbondings waiting per staking token than this
+pub const MAX_UNBONDING_ENTRIES: usize = 10;
+
+// nor more locked bonds per staking token than this
+pub const MAX_BOND_LOCKS: usize = 10;
+
+// bond checkpoints kept per position, the oldest go first past it
+pub const MAX_BOND_CHECKPOINTS: usize = 100;
+
+// positions PruneRewardInfos checks per call
+const DEFAULT_PRUNE_LIMIT: u32 = 30;
+const MAX_PRUNE_LIMIT: u32 = 100;
+
+pub fn bond(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    staking_token: Addr,
+    amount: Uint128,
+    lock_duration: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage)?;
+    let staker_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    close_pool_epoch(deps.storage, &asset_key, env.block.time.seconds())?;
+    _increase_bond_amount(
+        deps.storage,
+        deps.api,
+        &staker_addr_raw,
+        staking_token.clone(),
+        amount,
+        env.block.time.seconds(),
+        env.block.height,
+        lock_duration,
+    )?;
+    refresh_boost(
+        deps.storage,
+        &deps.querier,
+        deps.api,
+        &staker_addr_raw,
+        &asset_key,
+        env.block.time.seconds(),
+    )?;
+
+    let hook_msgs = hook_msgs(
+        deps.storage,
+        deps.api,
+        &staker_addr,
+        &staking_token,
+        &asset_key,
+    )?;
+
+    let res = Response::new()
+        .add_submessages(hook_msgs)
+        .add_attributes([
+            ("action", "bond"),
+            ("staker_addr", staker_addr.as_str()),
+            ("staking_token", staking_token.as_str()),
+            ("amount", &amount.to_string()),
+        ])
+        .set_data(to_binary(&BondResponse {
+            staker_addr,
+            staking_token,
+            amount,
+        })?);
+    Ok(match lock_duration {
+        Some(lock_duration) => res.add_attribute("lock_duration", lock_duration.to_string()),
+        None => res,
+    })
+}
+
+pub fn bond_native(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_token: Addr,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    if !is_native_staking_token(deps.storage, staking_token.as_bytes()) {
+        return Err(ContractError::PoolNotFound {
+            asset: staking_token.to_string(),
+        });
+    }
+
+    let amount = match info.funds.as_slice() {
+        [coin] if coin.denom == staking_token.as_str() && !coin.amount.is_zero() => coin.amount,
+        [coin] if coin.denom != staking_token.as_str() => {
+            return Err(ContractError::InvalidStakingDenom {
+                expected: staking_token.to_string(),
+                denom: coin.denom.clone(),
+            })
+        }
+        _ => return Err(ContractError::InvalidFunds {}),
+    };
+
+    bond(deps, env, info.sender, staking_token, amount, None)
+}
+
+pub fn unbond(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    staking_token: Addr,
+    amount: Option<Uint128>,
+    withdraw_rewards: bool,
+    recipient: Option<Addr>,
+    unbond_msg: Option<UnbondSendMsg>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let staker_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let unbonding_period = read_pool_info(deps.storage, &asset_key)?.unbonding_period;
+    let recipient = recipient
+        .map(|recipient| deps.api.addr_validate(recipient.as_str()))
+        .transpose()?;
+    let unbond_msg = unbond_msg
+        .map(|unbond_msg| -> StdResult<_> {
+            Ok(UnbondSendMsg {
+                contract: deps.api.addr_validate(unbond_msg.contract.as_str())?,
+                msg: unbond_msg.msg,
+            })
+        })
+        .transpose()?;
+    if recipient.is_some() && unbond_msg.is_some() {
+        return Err(ContractError::ConflictingRecipients {});
+    }
+    if (recipient.is_some() || unbond_msg.is_some()) && unbonding_period != 0 {
+        return Err(ContractError::UnbondRecipientWithUnbondingPeriod {});
+    }
+    assert_unbond_cooldown(
+        deps.storage,
+        &staker_addr_raw,
+        &asset_key,
+        env.block.time.seconds(),
+    )?;
+    // close the ended epoch first so the staker still shares it
+    close_pool_epoch(deps.storage, &asset_key, env.block.time.seconds())?;
+    let amount = match amount {
+        Some(amount) if amount.is_zero() => return Err(ContractError::ZeroUnbondAmount {}),
+        Some(amount) => amount,
+        // the locked part of the position stays bonded
+        None => {
+            let unlocked_amount = rewards_read(deps.storage, &staker_addr_raw)
+                .may_load(&asset_key)?
+                .map_or(Uint128::zero(), |reward_info| {
+                    reward_info.bond_amount - reward_info.locked_amount(env.block.time.seconds())
+                });
+            if unlocked_amount.is_zero() {
+                return Err(ContractError::NothingToUnbond {});
+            }
+            unlocked_amount
+        }
+    };
+    let (staking_token, mut reward_assets) = _decrease_bond_amount(
+        deps.storage,
+        deps.api,
+        &staker_addr_raw,
+        &staking_token,
+        amount,
+        env.block.time.seconds(),
+        env.block.height,
+    )?;
+    refresh_boost(
+        deps.storage,
+        &deps.querier,
+        deps.api,
+        &staker_addr_raw,
+        &asset_key,
+        env.block.time.seconds(),
+    )?;
+
+    let staking_token_addr = staking_token_addr(deps.storage, deps.api, &staking_token)?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attributes = vec![];
+    let release_time = if unbonding_period == 0 {
+        messages.push(match (recipient, unbond_msg) {
+            (_, Some(unbond_msg)) => {
+                attributes.push(attr("recipient", unbond_msg.contract.as_str()));
+                send_staking_token(deps.storage, &staking_token_addr, unbond_msg, amount)?
+            }
+            (Some(recipient), None) => {
+                attributes.push(attr("recipient", recipient.as_str()));
+                transfer_staking_token(deps.storage, &staking_token_addr, &recipient, amount)?
+            }
+            (None, None) => {
+                transfer_staking_token(deps.storage, &staking_token_addr, &staker_addr, amount)?
+            }
+        });
+        None
+    } else {
+        let release_time = env.block.time.seconds() + unbonding_period;
+        queue_unbonding(
+            deps.storage,
+            &staker_addr_raw,
+            &staking_token,
+            amount,
+            release_time,
+        )?;
+        attributes.push(attr("release_time", release_time.to_string()));
+        Some(release_time)
+    };
+
+    // the unbond already settled the reward index, so this pays out all the pool owes the staker
+    if withdraw_rewards {
+        let pool_reward_assets = process_reward_assets(
+            deps.storage,
+            &staker_addr_raw,
+            &Some(asset_key.to_vec()),
+            true,
+            env.block.time.seconds(),
+        )?;
+        for (_, pool_assets) in pool_reward_assets {
+            for ra in pool_assets {
+                if !ra.amount.is_zero() {
+                    reward_assets.push(ra.to_normal(deps.api)?);
+                }
+            }
+        }
+        if !reward_assets.is_empty() {
+            let amounts: Vec<String> = reward_assets.iter().map(|a| a.to_string()).collect();
+            attributes.push(attr("reward_assets", amounts.join(", ")));
+        }
+    }
+
+    // withdraw pending_withdraw assets (accumulated when changing reward_per_sec)
+    let (reward_messages, _, payout_attributes) =
+        payout_reward_assets(deps.as_ref(), &env, reward_assets, |asset| {
+            Ok(Some(asset.into_msg(
+                None,
+                &deps.querier,
+                staker_addr.clone(),
+            )?))
+        })?;
+    messages.extend(reward_messages);
+    attributes.extend(payout_attributes);
+
+    let hook_msgs = hook_msgs(
+        deps.storage,
+        deps.api,
+        &staker_addr,
+        &staking_token_addr,
+        &asset_key,
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes([
+            attr("action", "unbond"),
+            attr("staker_addr", staker_addr.as_str()),
+            attr("amount", &amount.to_string()),
+            attr("staking_token", staking_token_addr.as_str()),
+        ])
+        .add_attributes(attributes)
+        .set_data(to_binary(&UnbondResponse {
+            staker_addr,
+            staking_token: staking_token_addr,
+            amount,
+            release_time,
+        })?))
+}
+
+// pays out right away, keeping the pool penalty for the collector, a pool without a penalty
+// unbonds as usual
+pub fn instant_unbond(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    staking_token: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let penalty_rate = read_pool_info(deps.storage, &asset_key)?.instant_unbond_penalty;
+    if penalty_rate.is_zero() {
+        return unbond(
+            deps,
+            env,
+            staker_addr,
+            staking_token,
+            Some(amount),
+            false,
+            None,
+            None,
+        );
+    }
+    let collector = read_config(deps.storage)?
+        .penalty_collector
+        .ok_or(ContractError::PenaltyCollectorNotSet {})?;
+    let collector = deps.api.addr_humanize(&collector)?;
+
+    let staker_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    assert_unbond_cooldown(
+        deps.storage,
+        &staker_addr_raw,
+        &asset_key,
+        env.block.time.seconds(),
+    )?;
+    close_pool_epoch(deps.storage, &asset_key, env.block.time.seconds())?;
+    let (staking_token, reward_assets) = _decrease_bond_amount(
+        deps.storage,
+        deps.api,
+        &staker_addr_raw,
+        &staking_token,
+        amount,
+        env.block.time.seconds(),
+        env.block.height,
+    )?;
+    refresh_boost(
+        deps.storage,
+        &deps.querier,
+        deps.api,
+        &staker_addr_raw,
+        &asset_key,
+        env.block.time.seconds(),
+    )?;
+
+    let penalty = amount * penalty_rate;
+    let staking_token_addr = staking_token_addr(deps.storage, deps.api, &staking_token)?;
+    let mut messages = vec![transfer_staking_token(
+        deps.storage,
+        &staking_token_addr,
+        &staker_addr,
+        amount.checked_sub(penalty)?,
+    )?];
+    if !penalty.is_zero() {
+        messages.push(transfer_staking_token(
+            deps.storage,
+            &staking_token_addr,
+            &collector,
+            penalty,
+        )?);
+    }
+    let (reward_messages, _, payout_attributes) =
+        payout_reward_assets(deps.as_ref(), &env, reward_assets, |asset| {
+            Ok(Some(asset.into_msg(
+                None,
+                &deps.querier,
+                staker_addr.clone(),
+            )?))
+        })?;
+    messages.extend(reward_messages);
+
+    let hook_msgs = hook_msgs(
+        deps.storage,
+        deps.api,
+        &staker_addr,
+        &staking_token_addr,
+        &asset_key,
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes([
+            attr("action", "instant_unbond"),
+            attr("staker_addr", staker_addr.as_str()),
+            attr("amount", &amount.to_string()),
+            attr("penalty", &penalty.to_string()),
+            attr("staking_token", staking_token_addr.as_str()),
+        ])
+        .add_attributes(payout_attributes))
+}
+
+// a position bonded into within the unbond cooldown of the pool can not leave it yet
+fn assert_unbond_cooldown(
+    storage: &dyn Storage,
+    staker_addr: &CanonicalAddr,
+    asset_key: &[u8],
+    now: u64,
+) -> Result<(), ContractError> {
+    let unbond_cooldown = read_pool_info(storage, asset_key)?.unbond_cooldown;
+    if unbond_cooldown == 0 {
+        return Ok(());
+    }
+    let last_bond_time = rewards_read(storage, staker_addr)
+        .may_load(asset_key)?
+        .map_or(0, |reward_info| reward_info.last_bond_time);
+    let ready_time = last_bond_time.saturating_add(unbond_cooldown);
+    if now < ready_time {
+        return Err(ContractError::UnbondCooldown {
+            remaining_seconds: ready_time - now,
+        });
+    }
+    Ok(())
+}
+
+// moves unlocked bonded principal to the recipient without unbonding it. The pending reward of
+// the sender stays with the sender, and the pool totals are unchanged
+pub fn transfer_bond(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    staking_token: Addr,
+    amount: Uint128,
+    recipient: Addr,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    assert_not_paused(deps.storage)?;
+    let recipient = deps.api.addr_validate(recipient.as_str())?;
+    if recipient == staker_addr {
+        return Err(ContractError::TransferBondToSelf {});
+    }
+    if amount.is_zero() {
+        return Err(ContractError::ZeroTransferAmount {});
+    }
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let recipient_raw = deps.api.addr_canonicalize(recipient.as_str())?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+
+    // a position on the deprecated staking token has to be closed, it can not move to the new one
+    let pool_info = read_pool_info(deps.storage, &asset_key)?;
+    if pool_info.migration_params.is_some()
+        && !read_is_migrated(deps.storage, &asset_key, &staker_addr_raw)
+    {
+        return Err(ContractError::StakingTokenDeprecated {});
+    }
+
+    close_pool_epoch(deps.storage, &asset_key, env.block.time.seconds())?;
+    // locked parts of the position fail the decrease, unbonding amounts are no longer bonded
+    let (_, reward_assets) = _decrease_bond_amount(
+        deps.storage,
+        deps.api,
+        &staker_addr_raw,
+        &staking_token,
+        amount,
+        env.block.time.seconds(),
+        env.block.height,
+    )?;
+    _increase_bond_amount(
+        deps.storage,
+        deps.api,
+        &recipient_raw,
+        staking_token.clone(),
+        amount,
+        env.block.time.seconds(),
+        env.block.height,
+        None,
+    )?;
+    // nothing entered or left the pool
+    let mut pool_info = read_pool_info(deps.storage, &asset_key)?;
+    pool_info.lifetime_bonded = pool_info.lifetime_bonded.checked_sub(amount)?;
+    pool_info.lifetime_unbonded = pool_info.lifetime_unbonded.checked_sub(amount)?;
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    for staker in [&staker_addr_raw, &recipient_raw] {
+        refresh_boost(
+            deps.storage,
+            &deps.querier,
+            deps.api,
+            staker,
+            &asset_key,
+            env.block.time.seconds(),
+        )?;
+    }
+
+    // a position closed by the transfer pays out its pending_withdraw assets
+    let (messages, _, payout_attributes) =
+        payout_reward_assets(deps.as_ref(), &env, reward_assets, |asset| {
+            Ok(Some(asset.into_msg(
+                None,
+                &deps.querier,
+                staker_addr.clone(),
+            )?))
+        })?;
+    let mut submessages = hook_msgs(
+        deps.storage,
+        deps.api,
+        &staker_addr,
+        &staking_token,
+        &asset_key,
+    )?;
+    submessages.extend(hook_msgs(
+        deps.storage,
+        deps.api,
+        &recipient,
+        &staking_token,
+        &asset_key,
+    )?);
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(submessages)
+        .add_attributes([
+            attr("action", "transfer_bond"),
+            attr("staker_addr", staker_addr.as_str()),
+            attr("recipient", recipient.as_str()),
+            attr("staking_token", staking_token.as_str()),
+            attr("amount", amount.to_string()),
+        ])
+        .add_attributes(payout_attributes))
+}
+
+// tells every hook the bond of the position now, a hook that fails is caught in reply
+fn hook_msgs(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    staker_addr: &Addr,
+    staking_token: &Addr,
+    asset_key: &[u8],
+) -> StdResult<Vec<SubMsg>> {
+    let hooks = read_config(storage)?.hooks;
+    if hooks.is_empty() {
+        return Ok(vec![]);
+    }
+    let new_amount = rewards_read(storage, &api.addr_canonicalize(staker_addr.as_str())?)
+        .may_load(asset_key)?
+        .map_or(Uint128::zero(), |reward_info| reward_info.bond_amount);
+    let msg = to_binary(&StakingHookMsg::BondChanged {
+        staker_addr: staker_addr.clone(),
+        staking_token: staking_token.clone(),
+        new_amount,
+    })?;
+
+    hooks
+        .iter()
+        .map(|hook| {
+            Ok(SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: api.addr_humanize(hook)?.to_string(),
+                    msg: msg.clone(),
+                    funds: vec![],
+                },
+                HOOK_REPLY_ID,
+            ))
+        })
+        .collect()
+}
+
+// native staking tokens go back through the bank module, cw20 ones with a transfer
+pub fn transfer_staking_token(
+    storage: &dyn Storage,
+    staking_token: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    if is_native_staking_token(storage, staking_token.as_bytes()) {
+        return Ok(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), staking_token.as_str()),
+        }
+        .into());
+    }
+    Ok(WasmMsg::Execute {
+        contract_addr: staking_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    }
+    .into())
+}
+
+// the staking token with a message for the receiving contract, see UnbondSendMsg
+fn send_staking_token(
+    storage: &dyn Storage,
+    staking_token: &Addr,
+    unbond_msg: UnbondSendMsg,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    if is_native_staking_token(storage, staking_token.as_bytes()) {
+        return Ok(WasmMsg::Execute {
+            contract_addr: unbond_msg.contract.to_string(),
+            msg: unbond_msg.msg,
+            funds: coins(amount.u128(), staking_token.as_str()),
+        }
+        .into());
+    }
+    Ok(WasmMsg::Execute {
+        contract_addr: staking_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: unbond_msg.contract.to_string(),
+            amount,
+            msg: unbond_msg.msg,
+        })?,
+        funds: vec![],
+    }
+    .into())
+}
+
+fn queue_unbonding(
+    storage: &mut dyn Storage,
+    staker_addr: &CanonicalAddr,
+    staking_token: &CanonicalAddr,
+    amount: Uint128,
+    release_time: u64,
+) -> Result<(), ContractError> {
+    let mut unbonding_bucket = unbonding_store(storage, staker_addr);
+    let mut entries = unbonding_bucket
+        .may_load(staking_token)?
+        .unwrap_or_default();
+
+    match entries.last_mut() {
+        // unbonds in the same block are released together
+        Some(entry) if entry.release_time == release_time => {
+            entry.amount = entry.amount.checked_add(amount)?
+        }
+        _ => {
+            if entries.len() >= MAX_UNBONDING_ENTRIES {
+                return Err(ContractError::TooManyUnbondingEntries {
+                    max: MAX_UNBONDING_ENTRIES,
+                });
+            }
+            entries.push(UnbondingEntry {
+                amount,
+                release_time,
+            });
+        }
+    }
+
+    unbonding_bucket.save(staking_token, &entries)?;
+    let total_unbonding = read_total_unbonding(storage, staking_token)?;
+    store_total_unbonding(storage, staking_token, total_unbonding.checked_add(amount)?)?;
+    Ok(())
+}
+
+pub fn claim(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    staking_token: Addr,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+
+    let now = env.block.time.seconds();
+    let (released, waiting): (Vec<UnbondingEntry>, Vec<UnbondingEntry>) =
+        unbonding_read(deps.storage, &staker_addr_raw)
+            .may_load(&asset_key)?
+            .unwrap_or_default()
+            .into_iter()
+            .partition(|entry| entry.release_time <= now);
+
+    let amount: Uint128 = released.iter().map(|entry| entry.amount).sum();
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    if waiting.is_empty() {
+        unbonding_store(deps.storage, &staker_addr_raw).remove(&asset_key);
+    } else {
+        unbonding_store(deps.storage, &staker_addr_raw).save(&asset_key, &waiting)?;
+    }
+    // unbondings queued before the total was kept are not in it
+    let total_unbonding = read_total_unbonding(deps.storage, &asset_key)?;
+    store_total_unbonding(
+        deps.storage,
+        &asset_key,
+        total_unbonding.saturating_sub(amount),
+    )?;
+
+    Ok(Response::new()
+        .add_message(transfer_staking_token(
+            deps.storage,
+            &staking_token,
+            &staker_addr,
+            amount,
+        )?)
+        .add_attributes([
+            attr("action", "claim"),
+            attr("staker_addr", staker_addr.as_str()),
+            attr("amount", &amount.to_string()),
+            attr("staking_token", staking_token.as_str()),
+        ]))
+}
+
+// adds a referred bond to the volume of the referrer in the pool
+pub fn record_referral(
+    deps: DepsMut,
+    referrer: &Addr,
+    staker_addr: &str,
+    staking_token: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let referrer = deps.api.addr_validate(referrer.as_str())?;
+    if referrer.as_str() == staker_addr {
+        return Err(ContractError::SelfReferral {});
+    }
+
+    let referrer_raw = deps.api.addr_canonicalize(referrer.as_str())?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    referral_store(deps.storage, &referrer_raw).update(&asset_key, |volume| -> StdResult<_> {
+        Ok(volume.unwrap_or_default() + amount)
+    })?;
+
+    Ok(())
+}
+
+pub fn query_referral_stats(
+    deps: Deps,
+    referrer: Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    order: Option<i32>,
+) -> StdResult<ReferralStatsResponse> {
+    let order = parse_order(order)?;
+    let referrer_raw = deps.api.addr_canonicalize(referrer.as_str())?;
+    let start_after = start_after
+        .map(|staking_token| staking_token_key(deps.storage, deps.api, staking_token.as_str()))
+        .transpose()?;
+    let limit = page_limit(limit, DEFAULT_LIMIT, MAX_LIMIT);
+
+    let stats = read_range_prefixed::<Uint128>(
+        deps.storage,
+        &[PREFIX_REFERRAL, &referrer_raw],
+        start_after,
+        limit,
+        order,
+    )?
+    .into_iter()
+    .map(|(asset_key, bond_volume)| {
+        Ok(ReferralStat {
+            staking_token: staking_token_addr(deps.storage, deps.api, &asset_key)?,
+            bond_volume,
+        })
+    })
+    .collect::<StdResult<Vec<ReferralStat>>>()?;
+
+    Ok(ReferralStatsResponse { referrer, stats })
+}
+
+// the stakers index of the pool is written on the first bond and cleared on the full unbond
+pub fn query_stakers(
+    deps: Deps,
+    staking_token: Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    order: Option<i32>,
+) -> StdResult<StakersResponse> {
+    let order = parse_order(order)?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let start_after = start_after
+        .map(|staker| deps.api.addr_canonicalize(staker.as_str()))
+        .transpose()?
+        .map(|staker| staker.to_vec());
+    let limit = page_limit(limit, DEFAULT_LIMIT, MAX_LIMIT);
+
+    let stakers = read_range_prefixed::<bool>(
+        deps.storage,
+        &[PREFIX_STAKER, &asset_key],
+        start_after,
+        limit,
+        order,
+    )?
+    .into_iter()
+    .map(|(staker, _)| {
+        // a withdraw removes the reward info of an unbonded staker but keeps it listed
+        let bond_amount = rewards_read(deps.storage, &staker)
+            .may_load(&asset_key)?
+            .map_or(Uint128::zero(), |reward_info| reward_info.bond_amount);
+        Ok(StakerBond {
+            staker_addr: deps.api.addr_humanize(&staker.into())?,
+            bond_amount,
+        })
+    })
+    .collect::<StdResult<Vec<StakerBond>>>()?;
+
+    Ok(StakersResponse {
+        staking_token,
+        stakers,
+    })
+}
+
+pub fn query_stale_stakers(
+    deps: Deps,
+    staking_token: Addr,
+    older_than: u64,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<StaleStakersResponse> {
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let reward_activity_since = read_config(deps.storage)?.reward_activity_since;
+    let start_after = start_after
+        .map(|staker| deps.api.addr_canonicalize(staker.as_str()))
+        .transpose()?
+        .map(|staker| staker.to_vec());
+    let limit = page_limit(limit, DEFAULT_LIMIT, MAX_LIMIT);
+
+    // the page bounds the stakers scanned rather than the stale ones found, so a pool of active
+    // stakers can not make the query walk all of it
+    let scanned = read_range_prefixed::<bool>(
+        deps.storage,
+        &[PREFIX_STAKER, &asset_key],
+        start_after,
+        limit,
+        Order::Ascending,
+    )?;
+    let next_start_after = match scanned.last() {
+        Some((staker, _)) if scanned.len() == limit => {
+            Some(deps.api.addr_humanize(&staker.clone().into())?)
+        }
+        _ => None,
+    };
+
+    let mut stakers = vec![];
+    for (staker, _) in scanned {
+        // a withdraw removes the reward info of an unbonded staker but keeps it listed
+        let reward_info = match rewards_read(deps.storage, &staker).may_load(&asset_key)? {
+            Some(reward_info) => reward_info,
+            None => continue,
+        };
+        let last_updated = reward_info.last_active(reward_activity_since);
+        if last_updated < older_than {
+            stakers.push(StaleStaker {
+                staker_addr: deps.api.addr_humanize(&staker.into())?,
+                bond_amount: reward_info.bond_amount,
+                last_updated,
+            });
+        }
+    }
+
+    Ok(StaleStakersResponse {
+        staking_token,
+        stakers,
+        next_start_after,
+    })
+}
+
+// queued unbondings of the staker, at most limit pools after start_after unless one pool is given
+pub fn query_unbonding_claims(
+    deps: Deps,
+    env: Env,
+    staker_addr: Addr,
+    staking_token: Option<Addr>,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    order: Option<i32>,
+) -> StdResult<UnbondingClaimsResponse> {
+    let order = parse_order(order)?;
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let unbondings = unbonding_read(deps.storage, &staker_addr_raw);
+    let pools = match staking_token {
+        Some(staking_token) => {
+            let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+            unbondings
+                .may_load(&asset_key)?
+                .map(|entries| vec![(asset_key.to_vec(), entries)])
+                .unwrap_or_default()
+        }
+        None => {
+            let start_after = start_after
+                .map(|staking_token| {
+                    staking_token_key(deps.storage, deps.api, staking_token.as_str())
+                })
+                .transpose()?
+                .map(|key| key.to_vec());
+            read_range_prefixed(
+                deps.storage,
+                &[PREFIX_UNBONDING, &staker_addr_raw],
+                start_after,
+                page_limit(limit, DEFAULT_LIMIT, MAX_LIMIT),
+                order,
+            )?
+        }
+    };
+
+    let now = env.block.time.seconds();
+    let mut claims = vec![];
+    for (asset_key, entries) in pools {
+        let staking_token = staking_token_addr(deps.storage, deps.api, &asset_key)?;
+        claims.extend(entries.into_iter().map(|entry| UnbondingClaim {
+            staking_token: staking_token.clone(),
+            amount: entry.amount,
+            release_time: entry.release_time,
+            claimable: entry.release_time <= now,
+        }));
+    }
+    let total_claimable = claims
+        .iter()
+        .filter(|claim| claim.claimable)
+        .map(|claim| claim.amount)
+        .sum();
+
+    Ok(UnbondingClaimsResponse {
+        staker_addr,
+        claims,
+        total_claimable,
+    })
+}
+
+pub fn query_unbonding(
+    deps: Deps,
+    env: Env,
+    staker_addr: Addr,
+    staking_token: Addr,
+) -> Result<UnbondingResponse, ContractError> {
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+
+    let entries = unbonding_read(deps.storage, &staker_addr_raw)
+        .may_load(&asset_key)?
+        .unwrap_or_default();
+    let now = env.block.time.seconds();
+    let claimable_amount = entries
+        .iter()
+        .filter(|entry| entry.release_time <= now)
+        .map(|entry| entry.amount)
+        .sum();
+    // a deprecated staking token has no pool of its own and reports a zero period
+    let unbonding_period = read_pool_info(deps.storage, &asset_key)
+        .map(|pool_info| pool_info.unbonding_period)
+        .unwrap_or_default();
+
+    Ok(UnbondingResponse {
+        staker_addr,
+        staking_token,
+        unbonding_period,
+        entries,
+        claimable_amount,
+    })
+}
+
+pub fn auto_stake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    mut assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+    minimum_lp_amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    assert_not_paused(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+    let factory_addr = deps.api.addr_humanize(&config.factory_addr)?;
+
+    // query pair info to obtain pair contract address
+    let asset_infos: [AssetInfo; 2] = [assets[0].info.clone(), assets[1].info.clone()];
+    let oraiswap_pair: PairInfo = query_pair_info(&deps.querier, factory_addr, &asset_infos)?;
+
+    let staking_token = deps
+        .api
+        .addr_canonicalize(oraiswap_pair.liquidity_token.as_str())?;
+    let asset_key = staking_token.as_slice();
+
+    // the LP token the factory pair mints must be the one the pool was registered with, so a
+    // wrong registration fails before any funds move
+    let pool_info =
+        read_registered_pool_info(deps.storage, &oraiswap_pair.liquidity_token, asset_key)?;
+    if pool_info.staking_token != staking_token {
+        return Err(ContractError::StakingTokenMismatch {
+            staking_token: deps
+                .api
+                .addr_humanize(&pool_info.staking_token)?
+                .to_string(),
+            liquidity_token: oraiswap_pair.liquidity_token.to_string(),
+        });
+    }
+    if pool_info.frozen {
+        return Err(ContractError::PoolFrozen {
+            asset: oraiswap_pair.liquidity_token.to_string(),
+        });
+    }
+    // checked again on bond, but refused here before the assets are pulled in
+    if !may_bond(
+        deps.storage,
+        asset_key,
+        &pool_info,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+    ) {
+        return Err(ContractError::StakerNotAllowed {
+            staker: info.sender.to_string(),
+            asset: oraiswap_pair.liquidity_token.to_string(),
+        });
+    }
+
+    // funds must match the native assets before anything is pulled from the sender
+    if info.funds.iter().any(|coin| {
+        !assets.iter().any(
+            |asset| matches!(&asset.info, AssetInfo::NativeToken { denom } if denom == &coin.denom),
+        )
+    }) {
+        return Err(ContractError::InvalidFunds {});
+    }
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    for asset in assets.iter() {
+        match &asset.info {
+            AssetInfo::NativeToken { .. } => asset.assert_sent_native_token_balance(&info)?,
+            AssetInfo::Token { contract_addr } => {
+                // pulled in full, provide_and_stake_msgs sends back what the pool ratio leaves over
+                msgs.push(
+                    WasmMsg::Execute {
+                        contract_addr: contract_addr.to_string(),
+                        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                            owner: info.sender.to_string(),
+                            recipient: env.contract.address.to_string(),
+                            amount: asset.amount,
+                        })?,
+                        funds: vec![],
+                    }
+                    .into(),
+                );
+            }
+        }
+    }
+
+    msgs.extend(provide_and_stake_msgs(
+        deps.as_ref(),
+        &env,
+        &info.sender,
+        &oraiswap_pair.liquidity_token,
+        &oraiswap_pair.contract_addr,
+        &mut assets,
+        slippage_tolerance,
+        minimum_lp_amount,
+    )?);
+
+    Ok(Response::new().add_messages(msgs).add_attributes([
+        ("action", "auto_stake"),
+        ("staker_addr", info.sender.as_str()),
+        ("staking_token", oraiswap_pair.liquidity_token.as_str()),
+    ]))
+}
+
+pub fn auto_stake_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_token: Addr,
+    staker_addr: Addr,
+    prev_staking_token_amount: Uint128,
+    minimum_lp_amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    // only can be called by itself
+    validate_migrate_store_status(deps.storage)?;
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // stake all lp tokens received, compare with staking token amount before liquidity provision was executed
+    let current_staking_token_amount = query_token_balance(
+        &deps.querier,
+        staking_token.clone(),
+        env.contract.address.clone(),
+    )?;
+    let amount_to_stake = current_staking_token_amount.checked_sub(prev_staking_token_amount)?;
+    if amount_to_stake.is_zero() {
+        return Err(ContractError::ZeroBondAmount {});
+    }
+    if let Some(minimum_lp_amount) = minimum_lp_amount {
+        if amount_to_stake < minimum_lp_amount {
+            return Err(ContractError::LpAmountBelowMinimum {
+                expected: minimum_lp_amount,
+                received: amount_to_stake,
+            });
+        }
+    }
+
+    bond(deps, env, staker_addr, staking_token, amount_to_stake, None)
+}
+
+// withdraws the pool rewards of the sender for reinvesting, the pair assets reach compound_hook
+// once the swap has settled
+pub fn compound(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    slippage_tolerance: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    assert_not_paused(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
+    let factory_addr = deps.api.addr_humanize(&config.factory_addr)?;
+    let oraiswap_pair: PairInfo = query_pair_info(&deps.querier, factory_addr, &asset_infos)?;
+
+    let staking_token = oraiswap_pair.liquidity_token.clone();
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let pool_info = read_pool_info(deps.storage, &asset_key)?;
+    if pool_info.frozen {
+        return Err(ContractError::PoolFrozen {
+            asset: staking_token.to_string(),
+        });
+    }
+
+    let staker_addr_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    close_pool_epoch(deps.storage, &asset_key, env.block.time.seconds())?;
+    let pool_reward_assets = process_reward_assets(
+        deps.storage,
+        &staker_addr_raw,
+        &Some(asset_key.to_vec()),
+        true,
+        env.block.time.seconds(),
+    )?;
+
+    let mut reward_assets = vec![];
+    for (_, pool_assets) in pool_reward_assets {
+        for ra in pool_assets {
+            if !ra.amount.is_zero() {
+                reward_assets.push(ra.to_normal(deps.api)?);
+            }
+        }
+    }
+
+    // the withdraw fee is taken before anything is reinvested
+    let mut assets = asset_infos.map(|info| Asset {
+        info,
+        amount: Uint128::zero(),
+    });
+    let (mut messages, _, payout_attributes) =
+        payout_reward_assets(deps.as_ref(), &env, reward_assets, |reward_asset| {
+            match assets
+                .iter_mut()
+                .find(|asset| asset.info.eq(&reward_asset.info))
+            {
+                Some(asset) => {
+                    asset.amount = asset.amount.checked_add(reward_asset.amount)?;
+                    Ok(None)
+                }
+                // rewards outside of the pair are paid out like a withdraw
+                None => Ok(Some(reward_asset.into_msg(
+                    None,
+                    &deps.querier,
+                    info.sender.clone(),
+                )?)),
+            }
+        })?;
+
+    let reward = assets[0].amount + assets[1].amount;
+    if reward.is_zero() || reward < pool_info.min_compound_reward {
+        return Err(ContractError::CompoundRewardTooLow {
+            reward,
+            min_compound_reward: pool_info.min_compound_reward,
+        });
+    }
+    let reward_assets = assets
+        .iter()
+        .map(|asset| asset.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    // a reward in one of the pair assets buys the other one with half of it
+    let mut prev_ask_balance = None;
+    let swap = match (assets[0].amount.is_zero(), assets[1].amount.is_zero()) {
+        (false, true) => Some((0, 1)),
+        (true, false) => Some((1, 0)),
+        _ => None,
+    };
+    if let Some((offer, ask)) = swap {
+        let offer_asset = Asset {
+            info: assets[offer].info.clone(),
+            amount: assets[offer].amount.multiply_ratio(1u128, 2u128),
+        };
+        assets[offer].amount = assets[offer].amount.checked_sub(offer_asset.amount)?;
+        prev_ask_balance = Some(
+            assets[ask]
+                .info
+                .query_pool(&deps.querier, env.contract.address.clone())?,
+        );
+        messages.push(swap_msg(
+            &oraiswap_pair.contract_addr,
+            offer_asset,
+            None,
+            slippage_tolerance,
+            None,
+        )?);
+    }
 
-pub fn bond(
+    messages.push(
+        WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::CompoundHook {
+                staker_addr: info.sender.clone(),
+                staking_token: staking_token.clone(),
+                pair_addr: oraiswap_pair.contract_addr,
+                assets,
+                prev_ask_balance,
+                slippage_tolerance,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    );
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes([
+            ("action", "compound"),
+            ("staker_addr", info.sender.as_str()),
+            ("staking_token", staking_token.as_str()),
+            ("reward_assets", &reward_assets),
+        ])
+        .add_attributes(payout_attributes))
+}
+
+// swaps offer_asset through the pair, the output goes to `to` or else back to this contract
+pub fn swap_msg(
+    pair_addr: &Addr,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+) -> StdResult<CosmosMsg> {
+    let msg = match &offer_asset.info {
+        AssetInfo::NativeToken { denom } => WasmMsg::Execute {
+            contract_addr: pair_addr.to_string(),
+            msg: to_binary(&PairExecuteMsg::Swap {
+                offer_asset: offer_asset.clone(),
+                belief_price,
+                max_spread,
+                to,
+            })?,
+            funds: coins(offer_asset.amount.u128(), denom),
+        },
+        AssetInfo::Token { contract_addr } => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: pair_addr.to_string(),
+                amount: offer_asset.amount,
+                msg: to_binary(&PairCw20HookMsg::Swap {
+                    belief_price,
+                    max_spread,
+                    to: to.map(|to| to.to_string()),
+                })?,
+            })?,
+            funds: vec![],
+        },
+    };
+    Ok(msg.into())
+}
+
+// provides the compounded assets at the pool ratio and bonds the minted LP tokens for the staker
+#[allow(clippy::too_many_arguments)]
+pub fn compound_hook(
     deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
     staker_addr: Addr,
     staking_token: Addr,
-    amount: Uint128,
-) -> StdResult<Response> {
-    let staker_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(staker_addr.as_str())?;
-    _increase_bond_amount(
-        deps.storage,
-        deps.api,
-        &staker_addr_raw,
-        staking_token.clone(),
-        amount,
+    pair_addr: Addr,
+    mut assets: [Asset; 2],
+    prev_ask_balance: Option<Uint128>,
+    slippage_tolerance: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    // only can be called by itself
+    validate_migrate_store_status(deps.storage)?;
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // the swap paid out what the contract holds of the bought asset above its old balance
+    if let Some(prev_ask_balance) = prev_ask_balance {
+        if let Some(ask_asset) = assets.iter_mut().find(|asset| asset.amount.is_zero()) {
+            ask_asset.amount = ask_asset
+                .info
+                .query_pool(&deps.querier, env.contract.address.clone())?
+                .checked_sub(prev_ask_balance)?;
+        }
+    }
+
+    let messages = provide_and_stake_msgs(
+        deps.as_ref(),
+        &env,
+        &staker_addr,
+        &staking_token,
+        &pair_addr,
+        &mut assets,
+        slippage_tolerance,
+        None,
     )?;
 
-    Ok(Response::new().add_attributes([
-        ("action", "bond"),
+    Ok(Response::new().add_messages(messages).add_attributes([
+        ("action", "compound_hook"),
         ("staker_addr", staker_addr.as_str()),
         ("staking_token", staking_token.as_str()),
+        (
+            "provided_assets",
+            &assets
+                .iter()
+                .map(|asset| asset.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+        ),
+    ]))
+}
+
+// moves a position still on the deprecated staking token into the new one. Nothing is stored here,
+// the liquidity of the deprecated pair is withdrawn first and migrate_bond_hook settles the position
+pub fn migrate_bond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_token: Addr,
+    slippage_tolerance: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    validate_migrate_store_status(deps.storage)?;
+    assert_not_paused(deps.storage)?;
+    let staker_addr_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let pool_info = read_pool_info(deps.storage, &asset_key)?;
+    let params = pool_info
+        .migration_params
+        .ok_or(ContractError::StakingTokenNotDeprecated {})?;
+    let amount = match rewards_read(deps.storage, &staker_addr_raw).may_load(&asset_key)? {
+        Some(reward_info)
+            if !reward_info.bond_amount.is_zero()
+                && !read_is_migrated(deps.storage, &asset_key, &staker_addr_raw) =>
+        {
+            reward_info.bond_amount
+        }
+        _ => return Err(ContractError::NothingToUnbond {}),
+    };
+
+    let deprecated_token = deps.api.addr_humanize(&params.deprecated_staking_token)?;
+    let (withdraw_msg, pair_addr, prev_balances) = withdraw_deprecated_liquidity(
+        deps.as_ref(),
+        &env,
+        &deprecated_token,
+        &staking_token,
+        amount,
+    )?;
+    let messages: Vec<CosmosMsg> = vec![
+        withdraw_msg,
+        WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::MigrateBondHook {
+                staker_addr: info.sender.clone(),
+                staking_token: staking_token.clone(),
+                pair_addr,
+                amount,
+                prev_balances,
+                slippage_tolerance,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    ];
+
+    Ok(Response::new().add_messages(messages).add_attributes([
+        ("action", "migrate_bond"),
+        ("staker_addr", info.sender.as_str()),
+        ("staking_token", staking_token.as_str()),
+        ("deprecated_staking_token", deprecated_token.as_str()),
         ("amount", &amount.to_string()),
     ]))
 }
 
-pub fn unbond(
+// sends amount of the deprecated LP token held by the contract to its pair to withdraw the
+// liquidity. Returns the message, the pair of the new staking token, resolved by the factory, and
+// the balances of the contract in the pair assets before the withdraw
+fn withdraw_deprecated_liquidity(
+    deps: Deps,
+    env: &Env,
+    deprecated_token: &Addr,
+    staking_token: &Addr,
+    amount: Uint128,
+) -> Result<(CosmosMsg, Addr, [Asset; 2]), ContractError> {
+    // the deprecated LP token is minted by its pair, the factory resolves the pair it moved to
+    let deprecated_pair = deps
+        .querier
+        .query_wasm_smart::<Option<MinterResponse>>(
+            deprecated_token.to_string(),
+            &Cw20QueryMsg::Minter {},
+        )?
+        .ok_or(ContractError::InvalidStakingToken {})?
+        .minter;
+    let PairResponse {
+        info: deprecated_pair_info,
+    } = deps
+        .querier
+        .query_wasm_smart(deprecated_pair.clone(), &PairQueryMsg::Pair {})?;
+    let config: Config = read_config(deps.storage)?;
+    let oraiswap_pair = query_pair_info(
+        &deps.querier,
+        deps.api.addr_humanize(&config.factory_addr)?,
+        &deprecated_pair_info.asset_infos,
+    )?;
+    if oraiswap_pair.liquidity_token != *staking_token {
+        return Err(ContractError::InvalidStakingToken {});
+    }
+
+    // the hook takes the withdrawn amounts from the balances of the contract
+    let [info_0, info_1] = deprecated_pair_info.asset_infos;
+    let prev_balances = [
+        Asset {
+            amount: info_0.query_pool(&deps.querier, env.contract.address.clone())?,
+            info: info_0,
+        },
+        Asset {
+            amount: info_1.query_pool(&deps.querier, env.contract.address.clone())?,
+            info: info_1,
+        },
+    ];
+
+    let withdraw_msg = WasmMsg::Execute {
+        contract_addr: deprecated_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: deprecated_pair,
+            amount,
+            msg: to_binary(&PairCw20HookMsg::WithdrawLiquidity {})?,
+        })?,
+        funds: vec![],
+    }
+    .into();
+
+    Ok((withdraw_msg, oraiswap_pair.contract_addr, prev_balances))
+}
+
+// closes the deprecated position at the index snapshot and provides the withdrawn liquidity to
+// the new pair, its LP tokens are bonded by the auto stake hook
+#[allow(clippy::too_many_arguments)]
+pub fn migrate_bond_hook(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
+    info: MessageInfo,
     staker_addr: Addr,
     staking_token: Addr,
+    pair_addr: Addr,
     amount: Uint128,
-) -> StdResult<Response> {
+    prev_balances: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    // only can be called by itself
     validate_migrate_store_status(deps.storage)?;
-    let staker_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(staker_addr.as_str())?;
-    let (staking_token, reward_assets) = _decrease_bond_amount(
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let (_, reward_assets) = _decrease_bond_amount(
         deps.storage,
         deps.api,
         &staker_addr_raw,
         &staking_token,
         amount,
+        env.block.time.seconds(),
+        env.block.height,
     )?;
 
-    let staking_token_addr = deps.api.addr_humanize(&staking_token)?;
-    let mut messages = vec![WasmMsg::Execute {
-        contract_addr: staking_token_addr.to_string(),
-        msg: to_binary(&Cw20ExecuteMsg::Transfer {
-            recipient: staker_addr.to_string(),
-            amount,
+    let mut assets = withdrawn_assets(deps.as_ref(), &env, prev_balances)?;
+    let (mut messages, _, payout_attributes) =
+        payout_reward_assets(deps.as_ref(), &env, reward_assets, |asset| {
+            Ok(Some(asset.into_msg(
+                None,
+                &deps.querier,
+                staker_addr.clone(),
+            )?))
+        })?;
+    messages.extend(provide_and_stake_msgs(
+        deps.as_ref(),
+        &env,
+        &staker_addr,
+        &staking_token,
+        &pair_addr,
+        &mut assets,
+        slippage_tolerance,
+        None,
+    )?);
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes([
+            ("action", "migrate_bond_hook"),
+            ("staker_addr", staker_addr.as_str()),
+            ("staking_token", staking_token.as_str()),
+            (
+                "provided_assets",
+                &assets
+                    .iter()
+                    .map(|asset| asset.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            ),
+        ])
+        .add_attributes(payout_attributes))
+}
+
+// accepts the deprecated token sent with the Bond hook during the grace period of its pool. The
+// liquidity is withdrawn from the deprecated pair, and migrate_received_bond_hook provides it to
+// the new pair and bonds the LP tokens for the staker, unlocked
+pub fn migrate_received_bond(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    staking_token: Addr,
+    amount: Uint128,
+    deprecated_token: Addr,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage)?;
+    let (withdraw_msg, pair_addr, prev_balances) = withdraw_deprecated_liquidity(
+        deps.as_ref(),
+        &env,
+        &deprecated_token,
+        &staking_token,
+        amount,
+    )?;
+    let hook_msg = WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::MigrateReceivedBondHook {
+            staker_addr: staker_addr.clone(),
+            staking_token: staking_token.clone(),
+            pair_addr,
+            prev_balances,
         })?,
         funds: vec![],
-    }
-    .into()];
-
-    // withdraw pending_withdraw assets (accumulated when changing reward_per_sec)
-    messages.extend(
-        reward_assets
-            .into_iter()
-            .map(|ra| Ok(ra.into_msg(None, &deps.querier, staker_addr.clone())?))
-            .collect::<StdResult<Vec<CosmosMsg>>>()?,
-    );
+    };
 
-    Ok(Response::new().add_messages(messages).add_attributes([
-        attr("action", "unbond"),
-        attr("staker_addr", staker_addr.as_str()),
-        attr("amount", &amount.to_string()),
-        attr("staking_token", staking_token_addr.as_str()),
-    ]))
+    Ok(Response::new()
+        .add_message(withdraw_msg)
+        .add_message(hook_msg)
+        .add_attributes([
+            ("action", "migrate_received_bond"),
+            ("staker_addr", staker_addr.as_str()),
+            ("staking_token", staking_token.as_str()),
+            ("deprecated_staking_token", deprecated_token.as_str()),
+            ("amount", &amount.to_string()),
+        ]))
 }
 
-pub fn auto_stake(
+pub fn migrate_received_bond_hook(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    assets: [Asset; 2],
-    slippage_tolerance: Option<Decimal>,
-) -> StdResult<Response> {
+    staker_addr: Addr,
+    staking_token: Addr,
+    pair_addr: Addr,
+    prev_balances: [Asset; 2],
+) -> Result<Response, ContractError> {
+    // only can be called by itself
     validate_migrate_store_status(deps.storage)?;
-    let config: Config = read_config(deps.storage)?;
-    let factory_addr = deps.api.addr_humanize(&config.factory_addr)?;
-
-    // query pair info to obtain pair contract address
-    let asset_infos: [AssetInfo; 2] = [assets[0].info.clone(), assets[1].info.clone()];
-    let oraiswap_pair: PairInfo = query_pair_info(&deps.querier, factory_addr, &asset_infos)?;
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
 
-    let staking_token = deps
-        .api
-        .addr_canonicalize(oraiswap_pair.liquidity_token.as_str())?;
-    let asset_key = staking_token.as_slice();
+    let mut assets = withdrawn_assets(deps.as_ref(), &env, prev_balances)?;
+    let messages = provide_and_stake_msgs(
+        deps.as_ref(),
+        &env,
+        &staker_addr,
+        &staking_token,
+        &pair_addr,
+        &mut assets,
+        None,
+        None,
+    )?;
 
-    // assert the token and lp token match with pool info
-    let pool_info = read_pool_info(deps.storage, asset_key)?;
+    Ok(Response::new().add_messages(messages).add_attributes([
+        ("action", "migrate_received_bond_hook"),
+        ("staker_addr", staker_addr.as_str()),
+        ("staking_token", staking_token.as_str()),
+        (
+            "provided_assets",
+            &assets
+                .iter()
+                .map(|asset| asset.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+        ),
+    ]))
+}
 
-    if pool_info.staking_token != staking_token {
-        return Err(StdError::generic_err("Invalid staking token"));
+// what the contract holds of the pair assets above prev_balances
+fn withdrawn_assets(deps: Deps, env: &Env, prev_balances: [Asset; 2]) -> StdResult<[Asset; 2]> {
+    let mut assets = prev_balances.clone();
+    for (asset, prev_balance) in assets.iter_mut().zip(prev_balances) {
+        asset.amount = asset
+            .info
+            .query_pool(&deps.querier, env.contract.address.clone())?
+            .checked_sub(prev_balance.amount)?;
     }
+    Ok(assets)
+}
 
-    // get current lp token amount to later compute the recived amount
-    let prev_staking_token_amount = query_token_balance(
-        &deps.querier,
-        oraiswap_pair.liquidity_token.clone(),
-        env.contract.address.clone(),
-    )?;
+// provides `assets` to the pair at its pool ratio, sends the rest back to the staker and bonds
+// the minted LP tokens for them, `assets` is left with the amounts provided
+#[allow(clippy::too_many_arguments)]
+fn provide_and_stake_msgs(
+    deps: Deps,
+    env: &Env,
+    staker_addr: &Addr,
+    staking_token: &Addr,
+    pair_addr: &Addr,
+    assets: &mut [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+    minimum_lp_amount: Option<Uint128>,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    // whatever the pool ratio leaves over goes back to the staker instead of to the pool
+    let pool: PoolResponse = deps
+        .querier
+        .query_wasm_smart(pair_addr.clone(), &PairQueryMsg::Pool {})?;
+    let provide_amounts = balanced_provide_amounts(assets, &pool);
 
-    let mut msgs = vec![];
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut dust_messages: Vec<CosmosMsg> = vec![];
     let mut funds = vec![];
-
-    for asset in assets.iter() {
-        match asset.info.clone() {
-            AssetInfo::NativeToken { .. } => {
-                asset.assert_sent_native_token_balance(&info)?;
-                funds.push(Coin {
-                    denom: asset.info.to_string(),
-                    amount: asset.amount,
-                });
+    for (asset, amount) in assets.iter_mut().zip(provide_amounts) {
+        let dust = Asset {
+            info: asset.info.clone(),
+            amount: asset.amount.checked_sub(amount)?,
+        };
+        if !dust.amount.is_zero() {
+            dust_messages.push(dust.into_msg(None, &deps.querier, staker_addr.clone())?);
+        }
+        asset.amount = amount;
+        match &asset.info {
+            AssetInfo::NativeToken { denom } => {
+                if !amount.is_zero() {
+                    funds.push(Coin {
+                        denom: denom.clone(),
+                        amount,
+                    });
+                }
             }
-            AssetInfo::Token { contract_addr } => {
-                // 1. Transfer token asset to staking contract
-                // 2. Increase allowance of token for pair contract
-                // require transfer and increase allowance
-                msgs.push(WasmMsg::Execute {
-                    contract_addr: contract_addr.to_string(),
-                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                        owner: info.sender.to_string(),
-                        recipient: env.contract.address.to_string(),
-                        amount: asset.amount,
-                    })?,
-                    funds: vec![],
-                });
-                msgs.push(WasmMsg::Execute {
+            AssetInfo::Token { contract_addr } => messages.push(
+                WasmMsg::Execute {
                     contract_addr: contract_addr.to_string(),
                     msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
-                        spender: oraiswap_pair.contract_addr.to_string(),
-                        amount: asset.amount,
+                        spender: pair_addr.to_string(),
+                        amount,
                         expires: None,
                     })?,
                     funds: vec![],
-                });
-            }
+                }
+                .into(),
+            ),
         }
     }
 
-    // 3. Provide liquidity
-    // provide liquidity with funds from native tokens, run first
-    msgs.push(WasmMsg::Execute {
-        contract_addr: oraiswap_pair.contract_addr.to_string(),
-        msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
-            assets: assets.clone(),
-            slippage_tolerance,
-            receiver: None,
-        })?,
-        funds,
-    });
-
-    // 4. Execute staking hook, will stake in the name of the sender
-    // then auto stake hoook
-    msgs.push(WasmMsg::Execute {
-        contract_addr: env.contract.address.to_string(),
-        msg: to_binary(&ExecuteMsg::AutoStakeHook {
-            staking_token: oraiswap_pair.liquidity_token.clone(),
-            staker_addr: info.sender,
-            prev_staking_token_amount,
-        })?,
-        funds: vec![],
-    });
+    let prev_staking_token_amount = query_token_balance(
+        &deps.querier,
+        staking_token.clone(),
+        env.contract.address.clone(),
+    )?;
+    messages.push(
+        WasmMsg::Execute {
+            contract_addr: pair_addr.to_string(),
+            msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+                assets: assets.clone(),
+                slippage_tolerance,
+                receiver: None,
+            })?,
+            funds,
+        }
+        .into(),
+    );
+    messages.extend(dust_messages);
+    // the bond of the hook reports the LP amount provided
+    messages.push(
+        WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::AutoStakeHook {
+                staking_token: staking_token.clone(),
+                staker_addr: staker_addr.clone(),
+                prev_staking_token_amount,
+                minimum_lp_amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    );
 
-    Ok(Response::new().add_messages(msgs).add_attributes([
-        ("action", "auto_stake"),
-        ("staking_token", oraiswap_pair.liquidity_token.as_str()),
-    ]))
+    Ok(messages)
 }
 
-pub fn auto_stake_hook(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    staking_token: Addr,
-    staker_addr: Addr,
-    prev_staking_token_amount: Uint128,
-) -> StdResult<Response> {
-    // only can be called by itself
-    validate_migrate_store_status(deps.storage)?;
-    if info.sender != env.contract.address {
-        return Err(StdError::generic_err("unauthorized"));
+// the most of `assets` the pool takes at its current ratio, an empty pool takes all of them
+fn balanced_provide_amounts(assets: &[Asset; 2], pool: &PoolResponse) -> [Uint128; 2] {
+    let reserve = |asset: &Asset| {
+        pool.assets
+            .iter()
+            .find(|pool_asset| pool_asset.info.eq(&asset.info))
+            .map_or(Uint128::zero(), |pool_asset| pool_asset.amount)
+    };
+    let (reserve_0, reserve_1) = (reserve(&assets[0]), reserve(&assets[1]));
+    if pool.total_share.is_zero() || reserve_0.is_zero() || reserve_1.is_zero() {
+        return [assets[0].amount, assets[1].amount];
     }
 
-    // stake all lp tokens received, compare with staking token amount before liquidity provision was executed
-    let current_staking_token_amount =
-        query_token_balance(&deps.querier, staking_token.clone(), env.contract.address)?;
-    let amount_to_stake = current_staking_token_amount.checked_sub(prev_staking_token_amount)?;
-
-    bond(deps, staker_addr, staking_token, amount_to_stake)
+    let amount_1 = assets[0].amount.multiply_ratio(reserve_1, reserve_0);
+    if amount_1 <= assets[1].amount {
+        [assets[0].amount, amount_1]
+    } else {
+        [
+            assets[1].amount.multiply_ratio(reserve_0, reserve_1),
+            assets[1].amount,
+        ]
+    }
 }
 
 fn _increase_bond_amount(
@@ -211,17 +1721,38 @@ fn _increase_bond_amount(
     staker_addr: &CanonicalAddr,
     staking_token: Addr,
     amount: Uint128,
-) -> StdResult<()> {
-    let asset_key = api.addr_canonicalize(staking_token.as_str())?.to_vec();
+    now: u64,
+    height: u64,
+    lock_duration: Option<u64>,
+) -> Result<(), ContractError> {
+    accrue_distribution(storage, now)?;
+    let asset_key = staking_token_key(storage, api, staking_token.as_str())?.to_vec();
     let mut pool_info = read_pool_info(storage, &asset_key)?;
+    if pool_info.frozen {
+        return Err(ContractError::PoolFrozen {
+            asset: staking_token.to_string(),
+        });
+    }
+    if !may_bond(storage, &asset_key, &pool_info, staker_addr) {
+        return Err(ContractError::StakerNotAllowed {
+            staker: api.addr_humanize(staker_addr)?.to_string(),
+            asset: staking_token.to_string(),
+        });
+    }
     let mut reward_info: RewardInfo = rewards_read(storage, staker_addr)
         .load(&asset_key)
         .unwrap_or_else(|_| RewardInfo {
             native_token: false,
-            index: Decimal::zero(),
+            index: Decimal256::zero(),
             bond_amount: Uint128::zero(),
             pending_reward: Uint128::zero(),
             pending_withdraw: vec![],
+            locks: vec![],
+            proxy_indices: vec![],
+            total_claimed: Uint128::zero(),
+            boost_shortfall: Uint128::zero(),
+            last_updated: 0,
+            last_bond_time: 0,
         });
 
     // check if the position should be migrated
@@ -229,7 +1760,7 @@ fn _increase_bond_amount(
     if pool_info.migration_params.is_some() {
         // the pool has been migrated, if position is not migrated and has tokens bonded, return error
         if !reward_info.bond_amount.is_zero() && !is_position_migrated {
-            return Err(StdError::generic_err("The LP token for this asset has been deprecated, withdraw all your deprecated tokens to migrate your position"));
+            return Err(ContractError::StakingTokenDeprecated {});
         } else if !is_position_migrated {
             // if the position is not migrated, but bond amount is zero, it means it's a new position, so store it as migrated
             store_is_migrated(storage, &asset_key, staker_addr)?;
@@ -238,14 +1769,87 @@ fn _increase_bond_amount(
 
     // Withdraw reward to pending reward; before changing share
     before_share_change(pool_info.reward_index, &mut reward_info)?;
+    before_proxy_share_change(&pool_info.proxy_indices(false), &mut reward_info)?;
+    release_expired_locks(
+        storage,
+        staker_addr,
+        &asset_key,
+        &mut pool_info,
+        &mut reward_info,
+        now,
+    )?;
+
+    if let Some(remaining) = pool_info.remaining_bond_capacity() {
+        if amount > remaining {
+            return Err(ContractError::BondCapExceeded { remaining });
+        }
+    }
+    if let Some(max_bond_per_staker) = pool_info.max_bond_per_staker {
+        let remaining = max_bond_per_staker.saturating_sub(reward_info.bond_amount);
+        if amount > remaining {
+            return Err(ContractError::StakerBondCapExceeded {
+                bond_amount: reward_info.bond_amount,
+                max_bond_per_staker,
+                remaining,
+            });
+        }
+    }
+
+    if reward_info.bond_amount.is_zero() {
+        if let Some(staker_count) = pool_info.staker_count.as_mut() {
+            *staker_count += 1;
+        }
+    }
+    pool_info.lifetime_bonded = pool_info.lifetime_bonded.checked_add(amount)?;
 
     // Increase total bond amount
-    pool_info.total_bond_amount += amount;
+    pool_info.total_bond_amount = pool_info.total_bond_amount.checked_add(amount)?;
+    if let Some(bucket) = transition_bucket(&mut pool_info, &asset_key) {
+        bucket.total_bond_amount = bucket.total_bond_amount.checked_add(amount)?;
+        // positions of the new staking token are kept when the transition is finalized
+        if !is_position_migrated {
+            store_is_migrated(storage, &asset_key, staker_addr)?;
+        }
+    }
+
+    reward_info.bond_amount = reward_info.bond_amount.checked_add(amount)?;
+    if reward_info.bond_amount < pool_info.min_bond_amount {
+        return Err(ContractError::BondBelowMinimum {
+            min_bond_amount: pool_info.min_bond_amount,
+        });
+    }
 
-    reward_info.bond_amount += amount;
+    if let Some(lock_duration) = lock_duration {
+        let tier = pool_info
+            .lock_tiers
+            .iter()
+            .find(|tier| tier.duration == lock_duration)
+            .ok_or(ContractError::InvalidLockDuration { lock_duration })?;
+        if reward_info.locks.len() >= MAX_BOND_LOCKS {
+            return Err(ContractError::TooManyBondLocks {
+                max: MAX_BOND_LOCKS,
+            });
+        }
+        let lock = BondLock {
+            amount,
+            unlock_time: now + lock_duration,
+            multiplier: tier.multiplier,
+        };
+        pool_info.total_lock_boost = pool_info.total_lock_boost.checked_add(lock_boost(&lock))?;
+        reward_info.locks.push(lock);
+    }
 
+    reward_info.last_updated = now;
+    reward_info.last_bond_time = now;
     rewards_store(storage, staker_addr).save(&asset_key, &reward_info)?;
     store_pool_info(storage, &asset_key, &pool_info)?;
+    record_bond_checkpoint(
+        storage,
+        staker_addr,
+        &asset_key,
+        height,
+        reward_info.bond_amount,
+    )?;
 
     // mark this staker belong to the pool the first time
     let mut stakers_bucket = stakers_store(storage, &asset_key);
@@ -262,14 +1866,28 @@ fn _decrease_bond_amount(
     staker_addr: &CanonicalAddr,
     staking_token: &Addr,
     amount: Uint128,
-) -> StdResult<(CanonicalAddr, Vec<Asset>)> {
-    let asset_key = api.addr_canonicalize(staking_token.as_str())?.to_vec();
+    now: u64,
+    height: u64,
+) -> Result<(CanonicalAddr, Vec<Asset>), ContractError> {
+    accrue_distribution(storage, now)?;
+    let asset_key = staking_token_key(storage, api, staking_token.as_str())?.to_vec();
     let mut pool_info: PoolInfo = read_pool_info(storage, &asset_key)?;
-    let mut reward_info: RewardInfo = rewards_read(storage, staker_addr).load(&asset_key)?;
+    let reward_info = rewards_read(storage, staker_addr).may_load(&asset_key)?;
+    let available = reward_info
+        .as_ref()
+        .map_or(Uint128::zero(), |reward_info| reward_info.bond_amount);
+    let mut reward_info: RewardInfo = match reward_info {
+        Some(reward_info) if amount <= available => reward_info,
+        _ => {
+            return Err(ContractError::InsufficientBond {
+                staker: api.addr_humanize(staker_addr)?.to_string(),
+                asset: staking_token.to_string(),
+                available,
+                requested: amount,
+            })
+        }
+    };
     let mut reward_assets = vec![];
-    if reward_info.bond_amount < amount {
-        return Err(StdError::generic_err("Cannot unbond more than bond amount"));
-    }
 
     // if the lp token was migrated, and the user did not close their position yet, cap the reward at the snapshot
     let should_migrate =
@@ -281,27 +1899,98 @@ fn _decrease_bond_amount(
             migraton_params.deprecated_staking_token,
         )
     } else {
-        (pool_info.reward_index, pool_info.staking_token.clone())
+        // the staking token bonded for this position, which differs from the pool staking token
+        // during a transition
+        (
+            pool_info.reward_index,
+            CanonicalAddr::from(asset_key.clone()),
+        )
     };
 
     // Distribute reward to pending reward; before changing share
     before_share_change(pool_index, &mut reward_info)?;
+    before_proxy_share_change(&pool_info.proxy_indices(should_migrate), &mut reward_info)?;
+    release_expired_locks(
+        storage,
+        staker_addr,
+        &asset_key,
+        &mut pool_info,
+        &mut reward_info,
+        now,
+    )?;
+    if should_migrate {
+        // the old pool no longer accrues, so its locks have nothing left to hold
+        reward_info.locks.clear();
+    } else {
+        let unlocked = reward_info
+            .bond_amount
+            .checked_sub(reward_info.locked_amount(now))?;
+        if unlocked < amount {
+            return Err(ContractError::BondLocked {
+                unlocked,
+                requested: amount,
+            });
+        }
+    }
 
     // Decrease total bond amount
     if !should_migrate {
         // if it should migrate, we dont need to decrease from the current total bond amount
-        pool_info.total_bond_amount = pool_info.total_bond_amount.checked_sub(amount)?;
+        pool_info.total_bond_amount = settle_unbond(pool_info.total_bond_amount, amount)?;
+        if let Some(bucket) = transition_bucket(&mut pool_info, &asset_key) {
+            bucket.total_bond_amount = settle_unbond(bucket.total_bond_amount, amount)?;
+        }
+    } else if let Some(params) = pool_info.migration_params.as_mut() {
+        // pools deprecated before the figure was tracked start from zero
+        params.deprecated_total_bond = params.deprecated_total_bond.saturating_sub(amount);
     }
 
     // Update rewards info
     reward_info.bond_amount = reward_info.bond_amount.checked_sub(amount)?;
+    if should_migrate {
+        // the boost left the pool total with the bond when the staking token was deprecated
+        pool_info.total_boost_shortfall = pool_info
+            .total_boost_shortfall
+            .saturating_sub(reward_info.boost_shortfall);
+        reward_info.boost_shortfall = Uint128::zero();
+    } else {
+        let released_shortfall = reward_info.clamp_boost_shortfall();
+        pool_info.total_boost_shortfall = pool_info
+            .total_boost_shortfall
+            .checked_sub(released_shortfall)?;
+    }
+    if reward_info.bond_amount.is_zero() && !amount.is_zero() {
+        if let Some(staker_count) = pool_info.staker_count.as_mut() {
+            *staker_count = staker_count.saturating_sub(1);
+        }
+    }
+    pool_info.lifetime_unbonded = pool_info.lifetime_unbonded.checked_add(amount)?;
+
+    // rounding dust would keep an exited position around, it goes to the next distribution instead
+    if reward_info.bond_amount.is_zero()
+        && !reward_info.pending_reward.is_zero()
+        && reward_info.pending_reward < read_config(storage)?.dust_threshold
+    {
+        pool_info.pending_reward = pool_info
+            .pending_reward
+            .checked_add(reward_info.pending_reward)?;
+        reward_info.pending_reward = Uint128::zero();
+    }
 
     if reward_info.bond_amount.is_zero() && should_migrate {
         store_is_migrated(storage, &asset_key, staker_addr)?;
     }
+    record_bond_checkpoint(
+        storage,
+        staker_addr,
+        &asset_key,
+        height,
+        reward_info.bond_amount,
+    )?;
 
     if reward_info.pending_reward.is_zero() && reward_info.bond_amount.is_zero() {
         // if pending_withdraw is not empty, then return reward_assets to withdraw money
+        record_withdrawn_rewards(storage, &reward_info.pending_withdraw)?;
         reward_assets = reward_info
             .pending_withdraw
             .into_iter()
@@ -312,6 +2001,7 @@ fn _decrease_bond_amount(
         // remove staker from the pool
         stakers_store(storage, &asset_key).remove(staker_addr);
     } else {
+        reward_info.last_updated = now;
         rewards_store(storage, staker_addr).save(&asset_key, &reward_info)?;
     }
 
@@ -320,3 +2010,153 @@ fn _decrease_bond_amount(
 
     Ok((staking_token, reward_assets))
 }
+
+// Checks at most `limit` positions from where the last call stopped and removes the empty ones
+// along with their staker entry, a staker entry without a position is removed too. Once every
+// pool has been scanned the cursor is dropped and the next call starts over
+pub fn prune_reward_infos(deps: DepsMut, limit: Option<u32>) -> Result<Response, ContractError> {
+    let limit = limit
+        .unwrap_or(DEFAULT_PRUNE_LIMIT)
+        .clamp(1, MAX_PRUNE_LIMIT) as usize;
+    let mut cursor = read_prune_cursor(deps.storage)?.unwrap_or_default();
+    let mut budget = limit;
+    let mut pruned = 0u64;
+    let mut finished = false;
+    while budget > 0 {
+        let asset_key = match cursor.pool.clone() {
+            Some(asset_key) => asset_key,
+            None => match read_next_pool_info_key(deps.storage, None)? {
+                Some(asset_key) => {
+                    cursor.pool = Some(asset_key.clone());
+                    asset_key
+                }
+                None => {
+                    finished = true;
+                    break;
+                }
+            },
+        };
+        let start = cursor
+            .last_staker
+            .as_ref()
+            .map(|staker| [staker.as_slice(), &[0u8]].concat());
+        let stakers = stakers_read(deps.storage, &asset_key)
+            .range(start.as_deref(), None, Order::Ascending)
+            .take(budget)
+            .map(|item| item.map(|(staker, _)| staker))
+            .collect::<StdResult<Vec<Vec<u8>>>>()?;
+        for staker in stakers.iter() {
+            let reward_info = rewards_read(deps.storage, staker).may_load(&asset_key)?;
+            if reward_info.map_or(true, |reward_info| reward_info.is_empty()) {
+                rewards_store(deps.storage, staker).remove(&asset_key);
+                stakers_store(deps.storage, &asset_key).remove(staker);
+                pruned += 1;
+            }
+        }
+        budget -= stakers.len();
+        cursor.last_staker = stakers.last().cloned().or(cursor.last_staker);
+
+        // a short page means the pool has no stakers left
+        if budget > 0 {
+            match read_next_pool_info_key(deps.storage, Some(&asset_key))? {
+                Some(next) => {
+                    cursor.pool = Some(next);
+                    cursor.last_staker = None;
+                }
+                None => {
+                    finished = true;
+                    break;
+                }
+            }
+        }
+    }
+    if finished {
+        remove_prune_cursor(deps.storage);
+    } else {
+        store_prune_cursor(deps.storage, &cursor)?;
+    }
+
+    Ok(Response::new().add_attributes([
+        ("action", "prune_reward_infos"),
+        ("checked", &(limit - budget).to_string()),
+        ("pruned", &pruned.to_string()),
+        ("finished", &finished.to_string()),
+    ]))
+}
+
+// the position was already checked, failing here means the pool total is out of step with it
+fn settle_unbond(total_bond_amount: Uint128, amount: Uint128) -> StdResult<Uint128> {
+    total_bond_amount.checked_sub(amount).map_err(|_| {
+        StdError::generic_err(format!(
+            "unbond amount exceeds bonded {}",
+            total_bond_amount
+        ))
+    })
+}
+
+// the newest checkpoint at or before the retention cutoff is kept, it still answers the heights after it
+fn record_bond_checkpoint(
+    storage: &mut dyn Storage,
+    staker_addr: &CanonicalAddr,
+    asset_key: &[u8],
+    height: u64,
+    bond_amount: Uint128,
+) -> StdResult<()> {
+    let mut checkpoints = bond_checkpoints_read(storage, staker_addr)
+        .may_load(asset_key)?
+        .unwrap_or_default();
+    match checkpoints.last_mut() {
+        Some((last_height, amount)) if *last_height == height => *amount = bond_amount,
+        _ => checkpoints.push((height, bond_amount)),
+    }
+
+    let retention = read_config(storage)?.bond_checkpoint_retention;
+    if retention > 0 {
+        let cutoff = height.saturating_sub(retention);
+        let base =
+            checkpoints.partition_point(|(checkpoint_height, _)| *checkpoint_height <= cutoff);
+        if base > 1 {
+            checkpoints.drain(..base - 1);
+        }
+    }
+    if checkpoints.len() > MAX_BOND_CHECKPOINTS {
+        checkpoints.drain(..checkpoints.len() - MAX_BOND_CHECKPOINTS);
+    }
+    bond_checkpoints_store(storage, staker_addr).save(asset_key, &checkpoints)
+}
+
+// zero before the first checkpoint kept for the position
+pub fn query_bond_amount_at(
+    deps: Deps,
+    staker_addr: Addr,
+    staking_token: Addr,
+    height: u64,
+) -> StdResult<BondAmountAtResponse> {
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = staking_token_key(deps.storage, deps.api, staking_token.as_str())?;
+    let checkpoints = bond_checkpoints_read(deps.storage, &staker_addr_raw)
+        .may_load(&asset_key)?
+        .unwrap_or_default();
+    let index = checkpoints.partition_point(|(checkpoint_height, _)| *checkpoint_height <= height);
+    let bond_amount = match index {
+        0 => Uint128::zero(),
+        index => checkpoints[index - 1].1,
+    };
+
+    Ok(BondAmountAtResponse {
+        staker_addr,
+        staking_token,
+        height,
+        bond_amount,
+    })
+}
+
+fn transition_bucket<'a>(
+    pool_info: &'a mut PoolInfo,
+    asset_key: &[u8],
+) -> Option<&'a mut StakingTokenBucket> {
+    pool_info
+        .transition_staking_tokens
+        .iter_mut()
+        .find(|bucket| bucket.staking_token.as_slice() == asset_key)
+}
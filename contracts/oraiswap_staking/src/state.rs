@@ -1,17 +1,56 @@
 use cosmwasm_schema::cw_serde;
-use oraiswap::asset::AssetRaw;
+use oraiswap::asset::{AssetInfoRaw, AssetRaw};
+use oraiswap::querier::calc_range;
+use oraiswap::staking::{BondLock, LockTier, PoolFieldChange, UnbondingEntry};
 
-use cosmwasm_std::{CanonicalAddr, Decimal, StdResult, Storage, Uint128};
-use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use cosmwasm_schema::serde::de::DeserializeOwned;
+use cosmwasm_schema::serde::Serialize;
+use cosmwasm_std::{
+    from_slice, Addr, Api, CanonicalAddr, Decimal, Decimal256, StdError, StdResult, Storage,
+    Uint128,
+};
+use cosmwasm_storage::{singleton, singleton_read, to_length_prefixed, Bucket, ReadonlyBucket};
 
 pub static KEY_CONFIG: &[u8] = b"config_v2";
+pub static KEY_OWNERSHIP_PROPOSAL: &[u8] = b"ownership_proposal";
 pub static PREFIX_POOL_INFO: &[u8] = b"pool_info_v3";
 pub static PREFIX_REWARD: &[u8] = b"reward_v3";
 pub static PREFIX_STAKER: &[u8] = b"staker_v3";
 pub static PREFIX_IS_MIGRATED: &[u8] = b"is_migrated_v3";
 pub static PREFIX_REWARDS_PER_SEC: &[u8] = b"rewards_per_sec_v3";
+pub static PREFIX_POOL_EPOCH: &[u8] = b"pool_epoch";
+// (block time, amount) of the latest deposits of each pool, the reward rate averages them
+pub static PREFIX_POOL_DEPOSITS: &[u8] = b"pool_deposits";
+// reward index of each pool after its deposits, keyed by block time, and the number kept
+pub static PREFIX_REWARD_INDEX_HISTORY: &[u8] = b"reward_index_history";
+pub static PREFIX_REWARD_INDEX_HISTORY_LEN: &[u8] = b"reward_index_history_len";
+// owner repairs of each pool keyed by their id, and the number made
+pub static PREFIX_POOL_REPAIR: &[u8] = b"pool_repair";
+pub static PREFIX_POOL_REPAIR_LEN: &[u8] = b"pool_repair_len";
+pub static PREFIX_UNBONDING: &[u8] = b"unbonding";
+// sum of the unbondings waiting for release, keyed by the staking token they pay out
+pub static PREFIX_TOTAL_UNBONDING: &[u8] = b"total_unbonding";
+pub static PREFIX_REFERRAL: &[u8] = b"referral";
+// the address each staker approved to withdraw its rewards
+pub static PREFIX_OPERATOR: &[u8] = b"operator";
+// stakers the owner allowed into, or barred from, bonding into each pool
+pub static PREFIX_POOL_WHITELIST: &[u8] = b"pool_whitelist";
+pub static PREFIX_POOL_BLACKLIST: &[u8] = b"pool_blacklist";
+// (block height, bond amount) after each change of a position
+pub static PREFIX_BOND_CHECKPOINTS: &[u8] = b"bond_checkpoints";
+// maps a staking token bondable during a transition to the key of the pool it belongs to
+pub static PREFIX_POOL_ALIAS: &[u8] = b"pool_alias";
+// maps a deprecated staking token to the key of the pool that took it over
+pub static PREFIX_DEPRECATED_STAKING_TOKEN: &[u8] = b"deprecated_staking_token";
+// denoms registered as native staking tokens, their pools are keyed by the denom bytes
+pub static PREFIX_NATIVE_STAKING_TOKEN: &[u8] = b"native_staking_token";
 // a key to validate if we have finished migrating the store. Only allow staking functionalities when we have finished migrating
 pub static KEY_MIGRATE_STORE_CHECK: &[u8] = b"migrate_store_check";
+// present while MigrateStep is moving the legacy store over
+pub static KEY_MIGRATION_CURSOR: &[u8] = b"migration_cursor";
+pub static KEY_PRUNE_CURSOR: &[u8] = b"prune_cursor";
+// totals across all pools, kept up to date by the operations that change them
+pub static KEY_STATE: &[u8] = b"state";
 
 #[cw_serde]
 pub struct Config {
@@ -20,6 +59,43 @@ pub struct Config {
     pub oracle_addr: CanonicalAddr,
     pub factory_addr: CanonicalAddr,
     pub base_denom: String,
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub penalty_collector: Option<CanonicalAddr>,
+    // allowed to deposit rewards besides the rewarder
+    #[serde(default)]
+    pub reward_distributors: Vec<CanonicalAddr>,
+    // (start_time, end_time, amount) entries, each amount unlocks linearly over its time range
+    #[serde(default)]
+    pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+    // block time the unlocked schedule amounts were last added to the pools
+    #[serde(default)]
+    pub last_distributed: u64,
+    // share of every reward withdraw sent to the fee collector
+    #[serde(default)]
+    pub withdraw_fee_rate: Decimal,
+    #[serde(default)]
+    pub fee_collector: Option<CanonicalAddr>,
+    // blocks a bond checkpoint is kept for once a newer one covers its height, zero keeps them
+    #[serde(default)]
+    pub bond_checkpoint_retention: u64,
+    // pending reward a full exit below this folds back into the pool, zero keeps it owed
+    #[serde(default)]
+    pub dust_threshold: Uint128,
+    // answers BoostQueryMsg, None leaves every position weighted by its bond and locks
+    #[serde(default)]
+    pub boost_contract: Option<CanonicalAddr>,
+    // sent a StakingHookMsg on each bond change
+    #[serde(default)]
+    pub hooks: Vec<CanonicalAddr>,
+    // receives the rewards swept from abandoned positions
+    #[serde(default)]
+    pub community_pool: Option<CanonicalAddr>,
+    // block time positions started to record last_updated, the idle time of older ones counts
+    // from here
+    #[serde(default)]
+    pub reward_activity_since: u64,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -30,6 +106,27 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
 
+#[cw_serde]
+pub struct OwnershipProposal {
+    pub owner: CanonicalAddr,
+    pub expires_at: u64,
+}
+
+pub fn store_ownership_proposal(
+    storage: &mut dyn Storage,
+    proposal: &OwnershipProposal,
+) -> StdResult<()> {
+    singleton(storage, KEY_OWNERSHIP_PROPOSAL).save(proposal)
+}
+
+pub fn read_ownership_proposal(storage: &dyn Storage) -> StdResult<Option<OwnershipProposal>> {
+    singleton_read(storage, KEY_OWNERSHIP_PROPOSAL).may_load()
+}
+
+pub fn remove_ownership_proposal(storage: &mut dyn Storage) {
+    singleton::<OwnershipProposal>(storage, KEY_OWNERSHIP_PROPOSAL).remove()
+}
+
 pub fn store_finish_migrate_store_status(
     storage: &mut dyn Storage,
     has_finished: bool,
@@ -41,35 +138,457 @@ pub fn read_finish_migrate_store_status(storage: &dyn Storage) -> StdResult<bool
     singleton_read(storage, KEY_MIGRATE_STORE_CHECK).load()
 }
 
+#[cw_serde]
+#[derive(Default)]
+pub struct MigrationCursor {
+    // legacy key of the pool being moved, its stakers are moved after last_staker
+    pub pool: Option<Vec<u8>>,
+    pub last_staker: Option<Vec<u8>>,
+    pub pool_finished: bool,
+    pub migrated_pools: u64,
+    pub migrated_stakers: u64,
+}
+
+pub fn store_migration_cursor(
+    storage: &mut dyn Storage,
+    cursor: &MigrationCursor,
+) -> StdResult<()> {
+    singleton(storage, KEY_MIGRATION_CURSOR).save(cursor)
+}
+
+pub fn read_migration_cursor(storage: &dyn Storage) -> StdResult<Option<MigrationCursor>> {
+    singleton_read(storage, KEY_MIGRATION_CURSOR).may_load()
+}
+
+pub fn remove_migration_cursor(storage: &mut dyn Storage) {
+    singleton::<MigrationCursor>(storage, KEY_MIGRATION_CURSOR).remove()
+}
+
+// where PruneRewardInfos stopped, the stakers of `pool` after last_staker are checked next
+#[cw_serde]
+#[derive(Default)]
+pub struct PruneCursor {
+    pub pool: Option<Vec<u8>>,
+    pub last_staker: Option<Vec<u8>>,
+}
+
+pub fn store_prune_cursor(storage: &mut dyn Storage, cursor: &PruneCursor) -> StdResult<()> {
+    singleton(storage, KEY_PRUNE_CURSOR).save(cursor)
+}
+
+pub fn read_prune_cursor(storage: &dyn Storage) -> StdResult<Option<PruneCursor>> {
+    singleton_read(storage, KEY_PRUNE_CURSOR).may_load()
+}
+
+pub fn remove_prune_cursor(storage: &mut dyn Storage) {
+    singleton::<PruneCursor>(storage, KEY_PRUNE_CURSOR).remove()
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct State {
+    pub pool_count: u64,
+    // reward amounts taken in by deposits and by the distribution schedule
+    pub total_deposited: Uint128,
+    pub total_scheduled: Uint128,
+    // reward assets paid out by withdraws and compounds, fees included, and by sweeps
+    pub total_withdrawn: Uint128,
+    pub last_deposit_time: Option<u64>,
+    // what the stakers are owed per reward asset, counted in when a pool takes the reward and out
+    // when it is paid
+    #[serde(default)]
+    pub total_outstanding_rewards: Vec<AssetRaw>,
+    // pending_reward of all the pools, kept in step by store_pool_info and remove_pool_info
+    #[serde(default)]
+    pub total_pending_reward: Uint128,
+}
+
+pub fn store_state(storage: &mut dyn Storage, state: &State) -> StdResult<()> {
+    singleton(storage, KEY_STATE).save(state)
+}
+
+pub fn read_state(storage: &dyn Storage) -> StdResult<Option<State>> {
+    singleton_read(storage, KEY_STATE).may_load()
+}
+
+pub fn update_state<F: FnOnce(&mut State)>(storage: &mut dyn Storage, update: F) -> StdResult<()> {
+    let mut state = read_state(storage)?.unwrap_or_default();
+    update(&mut state);
+    store_state(storage, &state)
+}
+
 #[cw_serde]
 pub struct PoolInfo {
     pub staking_token: CanonicalAddr,
     pub pending_reward: Uint128, // not distributed amount due to zero bonding
     pub total_bond_amount: Uint128,
-    pub reward_index: Decimal,
+    // both decimals serialize as the same string, so indices stored as Decimal load unchanged
+    pub reward_index: Decimal256,
     pub migration_params: Option<MigrationParams>,
+    // other staking tokens bondable into this pool while it transitions to a new token,
+    // total_bond_amount is the combined total of them and the pool staking token
+    #[serde(default)]
+    pub transition_staking_tokens: Vec<StakingTokenBucket>,
+    // decimals of the reward assets, cached when the rewards per sec are updated
+    #[serde(default)]
+    pub reward_decimals: Vec<AssetDecimalsRaw>,
+    // seconds an unbonded amount waits before it can be claimed, zero transfers it right away
+    #[serde(default)]
+    pub unbonding_period: u64,
+    // set by the owner to stop new bonds into this pool only
+    #[serde(default)]
+    pub frozen: bool,
+    // share of an instant unbond sent to the penalty collector
+    #[serde(default)]
+    pub instant_unbond_penalty: Decimal,
+    // smallest position a bond can leave, zero has no minimum
+    #[serde(default)]
+    pub min_bond_amount: Uint128,
+    // least pending reward, summed over the pair assets, a compound reinvests
+    #[serde(default)]
+    pub min_compound_reward: Uint128,
+    // most total_bond_amount can reach through bonds, None is uncapped
+    #[serde(default)]
+    pub bond_cap: Option<Uint128>,
+    // most bond_amount a single position can reach through bonds, None is uncapped
+    #[serde(default)]
+    pub max_bond_per_staker: Option<Uint128>,
+    // durations a bond can be locked for, with the multiplier each gives
+    #[serde(default)]
+    pub lock_tiers: Vec<LockTier>,
+    // extra weight of the locked bonds over their amount, rewards are shared over
+    // total_bond_amount plus this
+    #[serde(default)]
+    pub total_lock_boost: Uint128,
+    // sum of the boost_shortfall of the positions, taken off the weight rewards are shared over
+    #[serde(default)]
+    pub total_boost_shortfall: Uint128,
+    // share of the distribution schedule, relative to the weights of the other pools
+    #[serde(default)]
+    pub reward_weight: Decimal,
+    // block times the pool takes rewards between, deposits outside of them are skipped
+    #[serde(default)]
+    pub reward_start_time: Option<u64>,
+    #[serde(default)]
+    pub reward_end_time: Option<u64>,
+    // cw20 tokens deposited on their own on top of the reward assets, each with its own index
+    #[serde(default)]
+    pub proxy_rewards: Vec<ProxyReward>,
+    // symbol of the cw20 staking token, None for native pools and pools registered before it was kept
+    #[serde(default)]
+    pub symbol: Option<String>,
+    // positions with a bond, None for pools registered before it was counted
+    #[serde(default)]
+    pub staker_count: Option<u64>,
+    // lifetime amounts bonded into and unbonded from the pool
+    #[serde(default)]
+    pub lifetime_bonded: Uint128,
+    #[serde(default)]
+    pub lifetime_unbonded: Uint128,
+    // lifetime rewards that reached the positions, and paid out of the pool. They stay with the
+    // pool when its staking token is deprecated
+    #[serde(default)]
+    pub lifetime_rewards_deposited: Uint128,
+    #[serde(default)]
+    pub lifetime_rewards_claimed: Uint128,
+    // only the stakers on the pool whitelist can bond while set
+    #[serde(default)]
+    pub whitelist_enabled: bool,
+    // entries on the pool whitelist and blacklist, the lists themselves are kept in buckets
+    #[serde(default)]
+    pub whitelist_len: u32,
+    #[serde(default)]
+    pub blacklist_len: u32,
+    // part of pending_reward deposited while nothing was bonded, no position is owed it
+    #[serde(default)]
+    pub orphaned_reward: Uint128,
+    // most a single deposit can raise reward_index by, zero has no limit
+    #[serde(default)]
+    pub max_index_increase_per_deposit: Decimal,
+    // seconds after its last bond a position can not unbond for, zero has no cooldown
+    #[serde(default)]
+    pub unbond_cooldown: u64,
+    // streamed deposits as (start_time, end_time, amount), released into the index by the next
+    // bond, unbond or withdraw and dropped once ended
+    #[serde(default)]
+    pub reward_streams: Vec<(u64, u64, Uint128)>,
+    // block time reward_streams were last released up to
+    #[serde(default)]
+    pub last_streamed: u64,
+    // seconds a deposit is streamed over when the depositor does not say, zero adds it at once
+    #[serde(default)]
+    pub reward_stream_seconds: u64,
+    // deposits the pool took in, the latest one is numbered by it
+    #[serde(default)]
+    pub deposit_epoch: u64,
+}
+
+impl PoolInfo {
+    // part of reward_streams not released into the index yet
+    pub fn streaming_reward(&self) -> Uint128 {
+        self.reward_streams
+            .iter()
+            .map(|(start_time, end_time, amount)| {
+                if self.last_streamed <= *start_time {
+                    *amount
+                } else if self.last_streamed >= *end_time {
+                    Uint128::zero()
+                } else {
+                    *amount
+                        - amount
+                            .multiply_ratio(self.last_streamed - start_time, end_time - start_time)
+                }
+            })
+            .sum()
+    }
+
+    pub fn remaining_bond_capacity(&self) -> Option<Uint128> {
+        self.bond_cap
+            .map(|bond_cap| bond_cap.saturating_sub(self.total_bond_amount))
+    }
+
+    pub fn accepts_reward(&self, now: u64) -> bool {
+        self.reward_start_time
+            .map_or(true, |start_time| now >= start_time)
+            && self.reward_end_time.map_or(true, |end_time| now < end_time)
+    }
+
+    // proxy indices a position earns up to, capped at the deprecation snapshot like reward_index
+    pub fn proxy_indices(&self, should_migrate: bool) -> Vec<(CanonicalAddr, Decimal)> {
+        match &self.migration_params {
+            Some(params) if should_migrate => params.proxy_index_snapshots.clone(),
+            _ => self
+                .proxy_rewards
+                .iter()
+                .map(|proxy| (proxy.reward_token.clone(), proxy.proxy_index))
+                .collect(),
+        }
+    }
+
+    pub fn total_weighted_bond(&self) -> Uint128 {
+        (self.total_bond_amount + self.total_lock_boost).saturating_sub(self.total_boost_shortfall)
+    }
+}
+
+#[cw_serde]
+pub struct AssetDecimalsRaw {
+    pub info: AssetInfoRaw,
+    pub decimals: u8,
+}
+
+#[cw_serde]
+pub struct StakingTokenBucket {
+    pub staking_token: CanonicalAddr,
+    pub total_bond_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct ProxyReward {
+    pub reward_token: CanonicalAddr,
+    pub proxy_index: Decimal,
+    // deposited while nothing was bonded
+    pub pending: Uint128,
 }
 
 #[cw_serde]
 pub struct MigrationParams {
-    pub index_snapshot: Decimal,
+    pub index_snapshot: Decimal256,
     pub deprecated_staking_token: CanonicalAddr,
+    // total bond amount at deprecation time, decreased when old positions are closed
+    #[serde(default)]
+    pub deprecated_total_bond: Uint128,
+    // proxy_index of every proxy reward at deprecation time
+    #[serde(default)]
+    pub proxy_index_snapshots: Vec<(CanonicalAddr, Decimal)>,
+    // block time the Bond hook stops moving the deprecated token into the new pair
+    #[serde(default)]
+    pub grace_period_end: Option<u64>,
 }
 
-pub fn remove_pool_info(storage: &mut dyn Storage, asset_key: &[u8]) {
-    Bucket::<PoolInfo>::new(storage, PREFIX_POOL_INFO).remove(&asset_key);
+pub fn remove_pool_info(storage: &mut dyn Storage, asset_key: &[u8]) -> StdResult<()> {
+    let mut bucket = Bucket::<PoolInfo>::new(storage, PREFIX_POOL_INFO);
+    let removed = bucket.may_load(asset_key)?;
+    bucket.remove(asset_key);
+    match removed {
+        Some(pool_info) if !pool_info.pending_reward.is_zero() => update_state(storage, |state| {
+            state.total_pending_reward = state
+                .total_pending_reward
+                .saturating_sub(pool_info.pending_reward)
+        }),
+        _ => Ok(()),
+    }
 }
 
+// pool info, rewards per sec and epochs are stored under the pool key, stakers and rewards
+// stay under the key of the staking token they bonded
 pub fn store_pool_info(
     storage: &mut dyn Storage,
     asset_key: &[u8],
     pool_info: &PoolInfo,
 ) -> StdResult<()> {
-    Bucket::new(storage, PREFIX_POOL_INFO).save(asset_key, pool_info)
+    let pool_key = resolve_pool_key(storage, asset_key);
+    let mut bucket = Bucket::<PoolInfo>::new(storage, PREFIX_POOL_INFO);
+    let prev_pending_reward = bucket
+        .may_load(&pool_key)?
+        .map(|prev| prev.pending_reward)
+        .unwrap_or_default();
+    bucket.save(&pool_key, pool_info)?;
+    if prev_pending_reward == pool_info.pending_reward {
+        return Ok(());
+    }
+    update_state(storage, |state| {
+        state.total_pending_reward = state
+            .total_pending_reward
+            .saturating_sub(prev_pending_reward)
+            .saturating_add(pool_info.pending_reward)
+    })
 }
 
 pub fn read_pool_info(storage: &dyn Storage, asset_key: &[u8]) -> StdResult<PoolInfo> {
-    ReadonlyBucket::new(storage, PREFIX_POOL_INFO).load(asset_key)
+    ReadonlyBucket::new(storage, PREFIX_POOL_INFO).load(&resolve_pool_key(storage, asset_key))
+}
+
+pub fn store_pool_alias(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    pool_key: &[u8],
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_POOL_ALIAS).save(asset_key, &pool_key.to_vec())
+}
+
+pub fn remove_pool_alias(storage: &mut dyn Storage, asset_key: &[u8]) {
+    Bucket::<Vec<u8>>::new(storage, PREFIX_POOL_ALIAS).remove(asset_key);
+}
+
+pub fn store_deprecated_staking_token(
+    storage: &mut dyn Storage,
+    deprecated_key: &[u8],
+    pool_key: &[u8],
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_DEPRECATED_STAKING_TOKEN).save(deprecated_key, &pool_key.to_vec())
+}
+
+pub fn read_deprecated_staking_token(
+    storage: &dyn Storage,
+    deprecated_key: &[u8],
+) -> StdResult<Option<Vec<u8>>> {
+    ReadonlyBucket::new(storage, PREFIX_DEPRECATED_STAKING_TOKEN).may_load(deprecated_key)
+}
+
+pub fn remove_deprecated_staking_token(storage: &mut dyn Storage, deprecated_key: &[u8]) {
+    Bucket::<Vec<u8>>::new(storage, PREFIX_DEPRECATED_STAKING_TOKEN).remove(deprecated_key);
+}
+
+pub fn store_operator(
+    storage: &mut dyn Storage,
+    staker_addr: &CanonicalAddr,
+    operator: &CanonicalAddr,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_OPERATOR).save(staker_addr, operator)
+}
+
+pub fn read_operator(
+    storage: &dyn Storage,
+    staker_addr: &CanonicalAddr,
+) -> StdResult<Option<CanonicalAddr>> {
+    ReadonlyBucket::new(storage, PREFIX_OPERATOR).may_load(staker_addr)
+}
+
+pub fn remove_operator(storage: &mut dyn Storage, staker_addr: &CanonicalAddr) {
+    Bucket::<CanonicalAddr>::new(storage, PREFIX_OPERATOR).remove(staker_addr);
+}
+
+pub fn staker_list_store<'a>(
+    storage: &'a mut dyn Storage,
+    prefix: &[u8],
+    pool_key: &[u8],
+) -> Bucket<'a, bool> {
+    Bucket::multilevel(storage, &[prefix, pool_key])
+}
+
+pub fn staker_list_read<'a>(
+    storage: &'a dyn Storage,
+    prefix: &[u8],
+    pool_key: &[u8],
+) -> ReadonlyBucket<'a, bool> {
+    ReadonlyBucket::multilevel(storage, &[prefix, pool_key])
+}
+
+pub fn is_on_staker_list(
+    storage: &dyn Storage,
+    prefix: &[u8],
+    asset_key: &[u8],
+    staker_addr: &CanonicalAddr,
+) -> bool {
+    staker_list_read(storage, prefix, &resolve_pool_key(storage, asset_key))
+        .may_load(staker_addr)
+        .ok()
+        .flatten()
+        .unwrap_or(false)
+}
+
+// whether the lists of the pool let the staker bond, a blacklisted staker never can
+pub fn may_bond(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+    pool_info: &PoolInfo,
+    staker_addr: &CanonicalAddr,
+) -> bool {
+    if is_on_staker_list(storage, PREFIX_POOL_BLACKLIST, asset_key, staker_addr) {
+        return false;
+    }
+    !pool_info.whitelist_enabled
+        || is_on_staker_list(storage, PREFIX_POOL_WHITELIST, asset_key, staker_addr)
+}
+
+// moves the whitelist and blacklist of a pool to its new key, or drops them when `to` is None
+pub fn move_staker_lists(
+    storage: &mut dyn Storage,
+    from: &[u8],
+    to: Option<&[u8]>,
+) -> StdResult<()> {
+    for prefix in [PREFIX_POOL_WHITELIST, PREFIX_POOL_BLACKLIST] {
+        let stakers = staker_list_read(storage, prefix, from)
+            .range(None, None, cosmwasm_std::Order::Ascending)
+            .collect::<StdResult<Vec<(Vec<u8>, bool)>>>()?;
+        for (staker, _) in stakers.iter() {
+            staker_list_store(storage, prefix, from).remove(staker);
+            if let Some(to) = to {
+                staker_list_store(storage, prefix, to).save(staker, &true)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn resolve_pool_key(storage: &dyn Storage, asset_key: &[u8]) -> Vec<u8> {
+    ReadonlyBucket::<Vec<u8>>::new(storage, PREFIX_POOL_ALIAS)
+        .may_load(asset_key)
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| asset_key.to_vec())
+}
+
+pub fn read_pool_infos<'a>(
+    storage: &'a dyn Storage,
+    start: Option<&[u8]>,
+    end: Option<&[u8]>,
+    order: cosmwasm_std::Order,
+) -> Box<dyn Iterator<Item = StdResult<(Vec<u8>, PoolInfo)>> + 'a> {
+    ReadonlyBucket::<PoolInfo>::new(storage, PREFIX_POOL_INFO).range(start, end, order)
+}
+
+// first pool key after start_after, in key order
+pub fn read_next_pool_info_key(
+    storage: &dyn Storage,
+    start_after: Option<&[u8]>,
+) -> StdResult<Option<Vec<u8>>> {
+    let start = start_after.map(|key| [key, &[0u8]].concat());
+    ReadonlyBucket::<PoolInfo>::new(storage, PREFIX_POOL_INFO)
+        .range(start.as_deref(), None, cosmwasm_std::Order::Ascending)
+        .next()
+        .map(|bucket| bucket.map(|b| b.0))
+        .transpose()
 }
 
 pub fn read_all_pool_infos(storage: &dyn Storage) -> StdResult<Vec<(Vec<u8>, PoolInfo)>> {
@@ -81,11 +600,90 @@ pub fn read_all_pool_infos(storage: &dyn Storage) -> StdResult<Vec<(Vec<u8>, Poo
 #[cw_serde]
 pub struct RewardInfo {
     pub native_token: bool,
-    pub index: Decimal,
+    pub index: Decimal256,
     pub bond_amount: Uint128,
     pub pending_reward: Uint128,
     // this is updated by the owner of this contract, when changing the reward_per_sec
     pub pending_withdraw: Vec<AssetRaw>,
+    // parts of bond_amount that can not be unbonded before their unlock_time
+    #[serde(default)]
+    pub locks: Vec<BondLock>,
+    // proxy_index of each proxy reward at the last settlement, missing ones start at zero
+    #[serde(default)]
+    pub proxy_indices: Vec<(CanonicalAddr, Decimal)>,
+    // reward assets withdrawn or compounded from the position, summed over them. Counts from the
+    // upgrade that added it, and is gone with the reward info once the position is closed
+    #[serde(default)]
+    pub total_claimed: Uint128,
+    // weight the position gives up for the vote escrow balance it lacks, set by the boost contract
+    #[serde(default)]
+    pub boost_shortfall: Uint128,
+    // block time of the last bond, unbond or withdraw of the staker, zero for positions untouched
+    // since the upgrade that added it
+    #[serde(default)]
+    pub last_updated: u64,
+    // block time of the last bond into the position, every bond restarts the unbond cooldown of
+    // the whole position rather than only of the amount it added
+    #[serde(default)]
+    pub last_bond_time: u64,
+}
+
+// extra weight a lock adds over its amount
+pub fn lock_boost(lock: &BondLock) -> Uint128 {
+    lock.amount * lock.multiplier - lock.amount
+}
+
+impl RewardInfo {
+    // last_updated, counted from reward_activity_since for positions untouched since the upgrade
+    pub fn last_active(&self, reward_activity_since: u64) -> u64 {
+        self.last_updated.max(reward_activity_since)
+    }
+
+    // nothing bonded, owed or claimed, the indices alone are not worth keeping
+    pub fn is_empty(&self) -> bool {
+        self.bond_amount.is_zero()
+            && self.pending_reward.is_zero()
+            && self.pending_withdraw.iter().all(|ra| ra.amount.is_zero())
+            && self.locks.is_empty()
+            && self.total_claimed.is_zero()
+    }
+
+    // the share of the position in the reward index
+    pub fn weighted_bond_amount(&self) -> Uint128 {
+        self.unboosted_weight().saturating_sub(self.boost_shortfall)
+    }
+
+    // bond_amount with the locks counted at their multiplier
+    pub fn unboosted_weight(&self) -> Uint128 {
+        self.bond_amount + self.locks.iter().map(lock_boost).sum::<Uint128>()
+    }
+
+    // keeps the shortfall within the weight once the weight drops, returns what was given back
+    pub fn clamp_boost_shortfall(&mut self) -> Uint128 {
+        let excess = self.boost_shortfall.saturating_sub(self.unboosted_weight());
+        self.boost_shortfall -= excess;
+        excess
+    }
+
+    pub fn locked_amount(&self, now: u64) -> Uint128 {
+        self.locks
+            .iter()
+            .filter(|lock| lock.unlock_time > now)
+            .map(|lock| lock.amount)
+            .sum()
+    }
+
+    // drops the locks expired at `now`, returns the boost they carried
+    pub fn release_expired_locks(&mut self, now: u64) -> Uint128 {
+        let released_boost = self
+            .locks
+            .iter()
+            .filter(|lock| lock.unlock_time <= now)
+            .map(lock_boost)
+            .sum();
+        self.locks.retain(|lock| lock.unlock_time > now);
+        released_boost
+    }
 }
 
 /// returns a bucket with all rewards owned by this staker (query it by staker)
@@ -110,6 +708,202 @@ pub fn stakers_read<'a>(storage: &'a dyn Storage, asset_key: &[u8]) -> ReadonlyB
     ReadonlyBucket::multilevel(storage, &[PREFIX_STAKER, asset_key])
 }
 
+/// returns a bucket with the unbondings of this staker waiting for release, keyed by the staking
+/// token they pay out
+pub fn unbonding_store<'a>(
+    storage: &'a mut dyn Storage,
+    staker: &[u8],
+) -> Bucket<'a, Vec<UnbondingEntry>> {
+    Bucket::multilevel(storage, &[PREFIX_UNBONDING, staker])
+}
+
+pub fn unbonding_read<'a>(
+    storage: &'a dyn Storage,
+    staker: &[u8],
+) -> ReadonlyBucket<'a, Vec<UnbondingEntry>> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_UNBONDING, staker])
+}
+
+// one page of the bucket under namespaces, the keys after start_after in the order. Every list
+// query pages through its bucket with this
+pub fn read_range_prefixed<T: Serialize + DeserializeOwned>(
+    storage: &dyn Storage,
+    namespaces: &[&[u8]],
+    start_after: Option<Vec<u8>>,
+    limit: usize,
+    order: cosmwasm_std::Order,
+) -> StdResult<Vec<(Vec<u8>, T)>> {
+    let (start, end) = calc_range(start_after, order);
+    ReadonlyBucket::multilevel(storage, namespaces)
+        .range(start.as_deref(), end.as_deref(), order)
+        .take(limit)
+        .collect()
+}
+
+// (key, staker, asset_key, value) of the buckets of a prefix keyed by staker, across every staker
+// in store order. The key is [u16 length of staker][staker][asset_key]
+pub fn read_staker_records<T: DeserializeOwned>(
+    storage: &dyn Storage,
+    prefix: &[u8],
+    start_after: Option<&[u8]>,
+    limit: usize,
+) -> StdResult<Vec<StakerRecord<T>>> {
+    let namespace = to_length_prefixed(prefix);
+    let start = match start_after {
+        Some(key) => [namespace.as_slice(), key, &[0u8]].concat(),
+        None => namespace.clone(),
+    };
+    storage
+        .range(Some(&start), None, cosmwasm_std::Order::Ascending)
+        .take_while(|(key, _)| key.starts_with(&namespace))
+        .take(limit)
+        .map(|(key, value)| {
+            let key = key[namespace.len()..].to_vec();
+            let staker_len = match key.get(..2) {
+                Some(len) => u16::from_be_bytes([len[0], len[1]]) as usize,
+                None => return Err(StdError::generic_err("invalid staker record key")),
+            };
+            if key.len() < 2 + staker_len {
+                return Err(StdError::generic_err("invalid staker record key"));
+            }
+            let staker = CanonicalAddr::from(&key[2..2 + staker_len]);
+            let asset_key = key[2 + staker_len..].to_vec();
+            Ok((key, staker, asset_key, from_slice(&value)?))
+        })
+        .collect()
+}
+
+pub type StakerRecord<T> = (Vec<u8>, CanonicalAddr, Vec<u8>, T);
+
+pub fn read_total_unbonding(storage: &dyn Storage, staking_token: &[u8]) -> StdResult<Uint128> {
+    Ok(ReadonlyBucket::new(storage, PREFIX_TOTAL_UNBONDING)
+        .may_load(staking_token)?
+        .unwrap_or_default())
+}
+
+pub fn store_total_unbonding(
+    storage: &mut dyn Storage,
+    staking_token: &[u8],
+    total_unbonding: Uint128,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_TOTAL_UNBONDING).save(staking_token, &total_unbonding)
+}
+
+/// returns a bucket with the bond checkpoints of this staker in each pool
+pub fn bond_checkpoints_store<'a>(
+    storage: &'a mut dyn Storage,
+    staker: &[u8],
+) -> Bucket<'a, Vec<(u64, Uint128)>> {
+    Bucket::multilevel(storage, &[PREFIX_BOND_CHECKPOINTS, staker])
+}
+
+pub fn bond_checkpoints_read<'a>(
+    storage: &'a dyn Storage,
+    staker: &[u8],
+) -> ReadonlyBucket<'a, Vec<(u64, Uint128)>> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_BOND_CHECKPOINTS, staker])
+}
+
+/// returns a bucket with the cumulative amount this referrer brought into each pool
+pub fn referral_store<'a>(storage: &'a mut dyn Storage, referrer: &[u8]) -> Bucket<'a, Uint128> {
+    Bucket::multilevel(storage, &[PREFIX_REFERRAL, referrer])
+}
+
+pub fn referral_read<'a>(storage: &'a dyn Storage, referrer: &[u8]) -> ReadonlyBucket<'a, Uint128> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_REFERRAL, referrer])
+}
+
+#[cw_serde]
+pub struct RewardIndexSnapshot {
+    pub reward_index: Decimal256,
+    pub total_bond_amount: Uint128,
+    // deposit_epoch of the pool after the deposit of the snapshot
+    #[serde(default)]
+    pub deposit_epoch: u64,
+}
+
+/// returns a bucket with the reward index snapshots of this pool, keyed by big endian block time
+pub fn reward_index_history_store<'a>(
+    storage: &'a mut dyn Storage,
+    pool_key: &[u8],
+) -> Bucket<'a, RewardIndexSnapshot> {
+    Bucket::multilevel(storage, &[PREFIX_REWARD_INDEX_HISTORY, pool_key])
+}
+
+pub fn reward_index_history_read<'a>(
+    storage: &'a dyn Storage,
+    pool_key: &[u8],
+) -> ReadonlyBucket<'a, RewardIndexSnapshot> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_REWARD_INDEX_HISTORY, pool_key])
+}
+
+pub fn store_reward_index_history_len(
+    storage: &mut dyn Storage,
+    pool_key: &[u8],
+    len: u64,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_REWARD_INDEX_HISTORY_LEN).save(pool_key, &len)
+}
+
+pub fn read_reward_index_history_len(storage: &dyn Storage, pool_key: &[u8]) -> StdResult<u64> {
+    Ok(
+        ReadonlyBucket::new(storage, PREFIX_REWARD_INDEX_HISTORY_LEN)
+            .may_load(pool_key)?
+            .unwrap_or_default(),
+    )
+}
+
+// an owner correction of pool fields, kept with the reason it was made
+#[cw_serde]
+pub struct PoolRepair {
+    pub time: u64,
+    pub reason: String,
+    pub changes: Vec<PoolFieldChange>,
+}
+
+pub fn pool_repairs_read<'a>(
+    storage: &'a dyn Storage,
+    pool_key: &[u8],
+) -> ReadonlyBucket<'a, PoolRepair> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_POOL_REPAIR, pool_key])
+}
+
+// repairs are only ever appended, the new one gets the next id
+pub fn append_pool_repair(
+    storage: &mut dyn Storage,
+    pool_key: &[u8],
+    repair: &PoolRepair,
+) -> StdResult<u64> {
+    let id = ReadonlyBucket::<u64>::new(storage, PREFIX_POOL_REPAIR_LEN)
+        .may_load(pool_key)?
+        .unwrap_or_default();
+    Bucket::multilevel(storage, &[PREFIX_POOL_REPAIR, pool_key]).save(&id.to_be_bytes(), repair)?;
+    Bucket::new(storage, PREFIX_POOL_REPAIR_LEN).save(pool_key, &(id + 1))?;
+    Ok(id)
+}
+
+// moves the snapshots of a pool to its new key, or drops them when `to` is None
+pub fn move_reward_index_history(
+    storage: &mut dyn Storage,
+    from: &[u8],
+    to: Option<&[u8]>,
+) -> StdResult<()> {
+    let snapshots = reward_index_history_read(storage, from)
+        .range(None, None, cosmwasm_std::Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, RewardIndexSnapshot)>>>()?;
+    for (time_key, snapshot) in snapshots.iter() {
+        reward_index_history_store(storage, from).remove(time_key);
+        if let Some(to) = to {
+            reward_index_history_store(storage, to).save(time_key, snapshot)?;
+        }
+    }
+    Bucket::<u64>::new(storage, PREFIX_REWARD_INDEX_HISTORY_LEN).remove(from);
+    if let Some(to) = to {
+        store_reward_index_history_len(storage, to, snapshots.len() as u64)?;
+    }
+    Ok(())
+}
+
 pub fn store_is_migrated(
     storage: &mut dyn Storage,
     asset_key: &[u8],
@@ -124,17 +918,111 @@ pub fn read_is_migrated(storage: &dyn Storage, asset_key: &[u8], staker: &[u8])
         .unwrap_or(false)
 }
 
+pub fn store_native_staking_token(storage: &mut dyn Storage, denom: &str) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_NATIVE_STAKING_TOKEN).save(denom.as_bytes(), &true)
+}
+
+pub fn is_native_staking_token(storage: &dyn Storage, asset_key: &[u8]) -> bool {
+    ReadonlyBucket::<bool>::new(storage, PREFIX_NATIVE_STAKING_TOKEN)
+        .may_load(asset_key)
+        .ok()
+        .flatten()
+        .unwrap_or(false)
+}
+
+// the storage key of a staking token given as a cw20 address or as a native denom
+pub fn staking_token_key(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    staking_token: &str,
+) -> StdResult<CanonicalAddr> {
+    if is_native_staking_token(storage, staking_token.as_bytes()) {
+        return Ok(CanonicalAddr::from(staking_token.as_bytes()));
+    }
+    api.addr_canonicalize(staking_token)
+}
+
+// the staking token of a storage key, a native denom is returned as is
+pub fn staking_token_addr(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    asset_key: &[u8],
+) -> StdResult<Addr> {
+    if is_native_staking_token(storage, asset_key) {
+        let denom = String::from_utf8(asset_key.to_vec())
+            .map_err(|_| StdError::invalid_utf8("native staking token"))?;
+        return Ok(Addr::unchecked(denom));
+    }
+    api.addr_humanize(&CanonicalAddr::from(asset_key))
+}
+
 pub fn store_rewards_per_sec(
     storage: &mut dyn Storage,
     asset_key: &[u8],
     assets: Vec<AssetRaw>,
 ) -> StdResult<()> {
+    let pool_key = resolve_pool_key(storage, asset_key);
     let mut weight_bucket: Bucket<Vec<AssetRaw>> = Bucket::new(storage, PREFIX_REWARDS_PER_SEC);
-    weight_bucket.save(asset_key, &assets)
+    weight_bucket.save(&pool_key, &assets)
+}
+
+pub fn remove_rewards_per_sec(storage: &mut dyn Storage, asset_key: &[u8]) {
+    let pool_key = resolve_pool_key(storage, asset_key);
+    Bucket::<Vec<AssetRaw>>::new(storage, PREFIX_REWARDS_PER_SEC).remove(&pool_key);
 }
 
 pub fn read_rewards_per_sec(storage: &dyn Storage, asset_key: &[u8]) -> StdResult<Vec<AssetRaw>> {
     let weight_bucket: ReadonlyBucket<Vec<AssetRaw>> =
         ReadonlyBucket::new(storage, PREFIX_REWARDS_PER_SEC);
-    weight_bucket.load(asset_key)
+    weight_bucket.load(&resolve_pool_key(storage, asset_key))
+}
+
+#[cw_serde]
+pub struct EpochInfo {
+    pub epoch_length: u64,
+    pub epoch_number: u64,
+    pub start_time: u64,
+    // rewards deposited during the current epoch, rolled into the reward index when it closes
+    pub accumulated_reward: Uint128,
+}
+
+pub fn store_pool_epoch(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    epoch: &EpochInfo,
+) -> StdResult<()> {
+    let pool_key = resolve_pool_key(storage, asset_key);
+    Bucket::new(storage, PREFIX_POOL_EPOCH).save(&pool_key, epoch)
+}
+
+pub fn read_pool_epoch(storage: &dyn Storage, asset_key: &[u8]) -> StdResult<Option<EpochInfo>> {
+    ReadonlyBucket::new(storage, PREFIX_POOL_EPOCH).may_load(&resolve_pool_key(storage, asset_key))
+}
+
+pub fn remove_pool_epoch(storage: &mut dyn Storage, asset_key: &[u8]) {
+    let pool_key = resolve_pool_key(storage, asset_key);
+    Bucket::<EpochInfo>::new(storage, PREFIX_POOL_EPOCH).remove(&pool_key);
+}
+
+pub fn store_pool_deposits(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    deposits: &Vec<(u64, Uint128)>,
+) -> StdResult<()> {
+    let pool_key = resolve_pool_key(storage, asset_key);
+    Bucket::new(storage, PREFIX_POOL_DEPOSITS).save(&pool_key, deposits)
+}
+
+pub fn read_pool_deposits(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+) -> StdResult<Vec<(u64, Uint128)>> {
+    Ok(ReadonlyBucket::new(storage, PREFIX_POOL_DEPOSITS)
+        .may_load(&resolve_pool_key(storage, asset_key))?
+        .unwrap_or_default())
+}
+
+pub fn remove_pool_deposits(storage: &mut dyn Storage, asset_key: &[u8]) {
+    let pool_key = resolve_pool_key(storage, asset_key);
+    Bucket::<Vec<(u64, Uint128)>>::new(storage, PREFIX_POOL_DEPOSITS).remove(&pool_key);
 }
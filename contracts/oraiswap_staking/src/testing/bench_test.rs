@@ -0,0 +1,178 @@
+use crate::contract::{execute, instantiate, query};
+use crate::rewards::MAX_DEPOSIT_REWARDS;
+use crate::testing::env::mock_staking_tokens;
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockQuerier};
+use cosmwasm_std::{from_binary, Addr, OwnedDeps, Uint128};
+use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
+use oraiswap::error::ContractError;
+use oraiswap::staking::{ExecuteMsg, InstantiateMsg, QueryMsg, RewardInfoResponse, RewardMsg};
+use oraiswap::testing::staking::bond_receive_msg;
+use oraiswap::testing::{mock_counting_dependencies, CountingStorage};
+use std::time::Instant;
+
+// instantiates the contract with pool_count registered pools, each with 100 bonded by addr
+fn setup_pools(pool_count: usize) -> (OwnedDeps<CountingStorage, MockApi, MockQuerier>, Vec<Addr>) {
+    let mut deps = mock_counting_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: None,
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+
+    let staking_tokens = (0..pool_count)
+        .map(|i| Addr::unchecked(format!("staking{}", i)))
+        .collect::<Vec<Addr>>();
+    mock_staking_tokens(
+        &mut deps.querier,
+        &staking_tokens
+            .iter()
+            .map(Addr::as_str)
+            .collect::<Vec<&str>>(),
+    );
+    for staking_token in staking_tokens.iter() {
+        let msg = ExecuteMsg::UpdateRewardsPerSec {
+            staking_token: staking_token.clone(),
+            assets: vec![Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: 100u128.into(),
+            }],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::RegisterAsset {
+            staking_token: staking_token.clone(),
+            min_bond_amount: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let msg = bond_receive_msg("addr", Uint128::from(100u128), None).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(staking_token.as_str(), &[]),
+            msg,
+        )
+        .unwrap();
+    }
+    (deps, staking_tokens)
+}
+
+// cargo test bench_deposit_reward -- --nocapture prints the storage cost per pool count
+#[test]
+fn bench_deposit_reward() {
+    for pool_count in [10usize, 100, 500] {
+        let (mut deps, staking_tokens) = setup_pools(pool_count);
+
+        let rewards = staking_tokens
+            .iter()
+            .map(|staking_token| RewardMsg {
+                staking_token: staking_token.clone(),
+                total_accumulation_amount: Uint128::from(100u128),
+            })
+            .collect::<Vec<RewardMsg>>();
+
+        if pool_count > MAX_DEPOSIT_REWARDS {
+            let msg = ExecuteMsg::DepositReward {
+                rewards: rewards.clone(),
+            };
+            let err =
+                execute(deps.as_mut(), mock_env(), mock_info("rewarder", &[]), msg).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::TooManyRewards {
+                    max: MAX_DEPOSIT_REWARDS
+                }
+            );
+        }
+
+        // above the cap the distributor has to split the deposit over several messages
+        deps.storage.reset_counters();
+        let start = Instant::now();
+        for chunk in rewards.chunks(MAX_DEPOSIT_REWARDS) {
+            let msg = ExecuteMsg::DepositReward {
+                rewards: chunk.to_vec(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("rewarder", &[]), msg).unwrap();
+        }
+        let elapsed = start.elapsed();
+
+        println!(
+            "deposit_reward over {} pools: {} reads, {} writes, {:?}",
+            pool_count,
+            deps.storage.reads(),
+            deps.storage.writes(),
+            elapsed
+        );
+        // the cost grows linearly with the pool count
+        assert!(deps.storage.writes() <= 2 * pool_count as u64);
+        assert!(deps.storage.reads() <= 6 * pool_count as u64);
+    }
+}
+
+// cargo test bench_bond -- --nocapture prints the storage cost of a bond
+#[test]
+fn bench_bond() {
+    let mut costs = vec![];
+    for pool_count in [1usize, 50] {
+        let (mut deps, staking_tokens) = setup_pools(pool_count);
+        let staking_token = staking_tokens[0].clone();
+
+        let msg = ExecuteMsg::DepositReward {
+            rewards: vec![RewardMsg {
+                staking_token: staking_token.clone(),
+                total_accumulation_amount: Uint128::from(100u128),
+            }],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("rewarder", &[]), msg).unwrap();
+
+        // the first bond settles the deposit, the second finds the position already settled
+        let mut counts = vec![];
+        let mut pending_rewards = vec![];
+        for _ in 0..2 {
+            deps.storage.reset_counters();
+            let msg = bond_receive_msg("addr", Uint128::from(100u128), None).unwrap();
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(staking_token.as_str(), &[]),
+                msg,
+            )
+            .unwrap();
+            counts.push((deps.storage.reads(), deps.storage.writes()));
+
+            let res: RewardInfoResponse = from_binary(
+                &query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::RewardInfo {
+                        staker_addr: Addr::unchecked("addr"),
+                        staking_token: Some(staking_token.clone()),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            pending_rewards.push(res.reward_infos[0].pending_reward);
+        }
+
+        println!(
+            "bond with {} pools: settling {:?}, settled {:?} (reads, writes)",
+            pool_count, counts[0], counts[1]
+        );
+        // settling twice in the same block does not change the reward
+        assert!(!pending_rewards[0].is_zero());
+        assert_eq!(pending_rewards[0], pending_rewards[1]);
+        assert!(counts[1].0 <= counts[0].0);
+        assert!(counts[1].1 <= counts[0].1);
+        costs.push(counts);
+    }
+    // the bond only touches its own pool
+    assert_eq!(costs[0], costs[1]);
+}
@@ -1,14 +1,23 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, query, sudo};
+use crate::testing::env::{default_rewards_per_sec, mock_staking_tokens, StakingTestEnv, OWNER};
 use cosmwasm_std::testing::{
     mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
 };
-use cosmwasm_std::{attr, coin, from_binary, to_binary, Addr, Decimal, Order, StdError, Uint128};
-use cw20::Cw20ReceiveMsg;
-use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
+use cosmwasm_std::{
+    attr, coin, from_binary, to_binary, Addr, BankMsg, ContractResult, CosmosMsg, Decimal, Order,
+    SystemError, SystemResult, Uint128, WasmQuery,
+};
+use cw20::{Cw20ReceiveMsg, TokenInfoResponse};
+use oraiswap::asset::{Asset, AssetInfo, PairInfo, ORAI_DENOM};
+use oraiswap::error::ContractError;
+use oraiswap::factory::QueryMsg as FactoryQueryMsg;
+use oraiswap::pair::PairResponse;
 use oraiswap::staking::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolInfoResponse, QueryMsg,
-    RewardInfoResponse,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolFieldChange, PoolInfoPatch,
+    PoolInfoResponse, PoolRepairResponse, QueryMsg, RegisterAssetEntry, RewardInfoResponse,
+    RewardMsg, SudoMsg,
 };
+use oraiswap::testing::ATOM_DENOM;
 
 #[test]
 fn proper_initialization() {
@@ -38,6 +47,18 @@ fn proper_initialization() {
             oracle_addr: Addr::unchecked("oracle"),
             factory_addr: Addr::unchecked("factory"),
             base_denom: ORAI_DENOM.to_string(),
+            paused: false,
+            pending_owner: None,
+            pending_owner_expires_at: None,
+            penalty_collector: None,
+            reward_distributors: vec![],
+            withdraw_fee_rate: Decimal::zero(),
+            fee_collector: None,
+            bond_checkpoint_retention: 0,
+            dust_threshold: Uint128::zero(),
+            boost_contract: None,
+            hooks: vec![],
+            community_pool: None,
         },
         config
     );
@@ -59,12 +80,19 @@ fn update_config() {
     let info = mock_info("addr", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // update rewarder
     let info = mock_info("owner", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: Some(Addr::unchecked("owner2")),
-        rewarder: None,
+        rewarder: Some(Addr::unchecked("reward2")),
         migrate_store_status: Some(true),
+        penalty_collector: None,
+        reward_distributors: None,
+        withdraw_fee_rate: None,
+        fee_collector: None,
+        oracle_addr: None,
+        factory_addr: None,
+        base_denom: None,
+        community_pool: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -75,33 +103,416 @@ fn update_config() {
     let config: ConfigResponse = from_binary(&res).unwrap();
     assert_eq!(
         ConfigResponse {
-            owner: Addr::unchecked("owner2"),
-            rewarder: Addr::unchecked("reward"),
+            owner: Addr::unchecked("owner"),
+            rewarder: Addr::unchecked("reward2"),
             oracle_addr: Addr::unchecked("oracle"),
             factory_addr: Addr::unchecked("factory"),
             base_denom: ORAI_DENOM.to_string(),
+            paused: false,
+            pending_owner: None,
+            pending_owner_expires_at: None,
+            penalty_collector: None,
+            reward_distributors: vec![],
+            withdraw_fee_rate: Decimal::zero(),
+            fee_collector: None,
+            bond_checkpoint_retention: 0,
+            dust_threshold: Uint128::zero(),
+            boost_contract: None,
+            hooks: vec![],
+            community_pool: None,
         },
         config
     );
 
     // unauthorized err
-    let info = mock_info("owner", &[]);
+    let info = mock_info("addr", &[]);
     let msg = ExecuteMsg::UpdateConfig {
         rewarder: None,
-        owner: None,
         migrate_store_status: None,
+        penalty_collector: None,
+        reward_distributors: None,
+        withdraw_fee_rate: None,
+        fee_collector: None,
+        oracle_addr: None,
+        factory_addr: None,
+        base_denom: None,
+        community_pool: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        Err(ContractError::Unauthorized {}) => {}
         _ => panic!("Must return unauthorized error"),
     }
 }
 
+#[test]
+fn test_update_oracle_and_factory() {
+    let mut test_env = StakingTestEnv::new().build();
+    let msg = ExecuteMsg::UpdateConfig {
+        rewarder: None,
+        migrate_store_status: None,
+        penalty_collector: None,
+        reward_distributors: None,
+        withdraw_fee_rate: None,
+        fee_collector: None,
+        oracle_addr: Some(Addr::unchecked("oracle2")),
+        factory_addr: None,
+        base_denom: None,
+        community_pool: None,
+    };
+
+    let err = test_env.execute("addr", msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // only the changed address is named in the attributes
+    let res = test_env.execute("owner", msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_config"),
+            attr("oracle_addr", "oracle2")
+        ]
+    );
+
+    let config: ConfigResponse = test_env.query(QueryMsg::Config {});
+    assert_eq!(config.oracle_addr, Addr::unchecked("oracle2"));
+    assert_eq!(config.factory_addr, Addr::unchecked("factory"));
+
+    let res = test_env
+        .execute(
+            "owner",
+            ExecuteMsg::UpdateConfig {
+                rewarder: None,
+                migrate_store_status: None,
+                penalty_collector: None,
+                reward_distributors: None,
+                withdraw_fee_rate: None,
+                fee_collector: None,
+                oracle_addr: None,
+                factory_addr: Some(Addr::unchecked("factory2")),
+                base_denom: None,
+                community_pool: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_config"),
+            attr("factory_addr", "factory2")
+        ]
+    );
+    let config: ConfigResponse = test_env.query(QueryMsg::Config {});
+    assert_eq!(config.oracle_addr, Addr::unchecked("oracle2"));
+    assert_eq!(config.factory_addr, Addr::unchecked("factory2"));
+}
+
+#[test]
+fn test_ibc_base_denom() {
+    const USDT_DENOM: &str = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+    let instantiate_msg = |base_denom: &str| InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("reward"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: Some(base_denom.to_string()),
+    };
+    for base_denom in [
+        "ibc/27394fb092d2eccd56123c74f36e4c1f926001ceada9ca97ea622b25f41e5eb2",
+        "ibc/27394FB092D2ECCD",
+        "ibc/",
+        "1orai",
+        "or",
+        "orai token",
+    ] {
+        let err = instantiate(
+            mock_dependencies().as_mut(),
+            mock_env(),
+            mock_info("addr", &[]),
+            instantiate_msg(base_denom),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidBaseDenom {
+                denom: base_denom.to_string()
+            }
+        );
+    }
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr", &[]),
+        instantiate_msg(USDT_DENOM),
+    )
+    .unwrap();
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.base_denom, USDT_DENOM);
+
+    let usdt_rewards = vec![Asset {
+        info: AssetInfo::NativeToken {
+            denom: USDT_DENOM.to_string(),
+        },
+        amount: 100u128.into(),
+    }];
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", usdt_rewards)
+        .with_bond("addr", "staking", 100)
+        .build();
+    let update_msg = |base_denom: &str| ExecuteMsg::UpdateConfig {
+        rewarder: None,
+        migrate_store_status: None,
+        penalty_collector: None,
+        reward_distributors: None,
+        withdraw_fee_rate: None,
+        fee_collector: None,
+        oracle_addr: None,
+        factory_addr: None,
+        base_denom: Some(base_denom.to_string()),
+        community_pool: None,
+    };
+    let err = test_env
+        .execute("owner", update_msg("ibc/27394FB092"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidBaseDenom {
+            denom: "ibc/27394FB092".to_string()
+        }
+    );
+    let res = test_env.execute("owner", update_msg(USDT_DENOM)).unwrap();
+    assert!(res.attributes.contains(&attr("base_denom", USDT_DENOM)));
+
+    // native deposits and the withdraw are paid in the ibc denom
+    let deposit_msg = ExecuteMsg::DepositNativeReward {
+        rewards: vec![RewardMsg {
+            staking_token: Addr::unchecked("staking"),
+            total_accumulation_amount: Uint128::from(300u128),
+        }],
+    };
+    let err = test_env
+        .execute_with_funds(
+            "rewarder",
+            &[coin(300u128, ORAI_DENOM)],
+            deposit_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFunds {});
+    test_env
+        .execute_with_funds("rewarder", &[coin(300u128, USDT_DENOM)], deposit_msg)
+        .unwrap();
+
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: None,
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<CosmosMsg>>(),
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr".to_string(),
+            amount: vec![coin(300u128, USDT_DENOM)],
+        })]
+    );
+}
+
+#[test]
+fn test_sudo() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .build();
+
+    let res = sudo(
+        test_env.deps.as_mut(),
+        test_env.env.clone(),
+        SudoMsg::Pause {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "pause"), attr("authority", "sudo")]
+    );
+    let config: ConfigResponse = test_env.query(QueryMsg::Config {});
+    assert!(config.paused);
+
+    // the owner path keeps working next to sudo
+    let res = test_env.execute("owner", ExecuteMsg::Unpause {}).unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "unpause")]);
+    let config: ConfigResponse = test_env.query(QueryMsg::Config {});
+    assert!(!config.paused);
+
+    sudo(
+        test_env.deps.as_mut(),
+        test_env.env.clone(),
+        SudoMsg::UpdateRewardWeights {
+            weights: vec![(Addr::unchecked("staking"), Decimal::percent(40))],
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        test_env.pool_info("staking").reward_weight,
+        Decimal::percent(40)
+    );
+}
+
+#[test]
+fn transfer_ownership() {
+    let mut deps = mock_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("reward"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+
+    let now = mock_env().block.time.seconds();
+    let propose_msg = ExecuteMsg::ProposeNewOwner {
+        owner: Addr::unchecked("owner2"),
+        expires_at: now + 100,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr", &[]),
+        propose_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecuteMsg::ProposeNewOwner {
+            owner: Addr::unchecked("owner2"),
+            expires_at: now,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::InvalidOwnershipExpiry {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        propose_msg.clone(),
+    )
+    .unwrap();
+
+    // the old owner keeps its permissions until the proposal is accepted
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.owner, Addr::unchecked("owner"));
+    assert_eq!(config.pending_owner, Some(Addr::unchecked("owner2")));
+    assert_eq!(config.pending_owner_expires_at, Some(now + 100));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr", &[]),
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::NoOwnershipProposal {});
+
+    // cancel, then accepting fails
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecuteMsg::CancelOwnershipProposal {},
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner2", &[]),
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::NoOwnershipProposal {});
+
+    // an expired proposal can not be accepted
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        propose_msg,
+    )
+    .unwrap();
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(101);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("owner2", &[]),
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::OwnershipProposalExpired {
+            expires_at: now + 100,
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner2", &[]),
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.owner, Addr::unchecked("owner2"));
+    assert_eq!(config.pending_owner, None);
+    assert_eq!(config.pending_owner_expires_at, None);
+
+    // the previous owner lost its permissions
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            rewarder: None,
+            migrate_store_status: None,
+            penalty_collector: None,
+            reward_distributors: None,
+            withdraw_fee_rate: None,
+            fee_collector: None,
+            oracle_addr: None,
+            factory_addr: None,
+            base_denom: None,
+            community_pool: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+}
+
 #[test]
 fn test_register() {
     let mut deps = mock_dependencies();
+    mock_staking_tokens(&mut deps.querier, &["staking"]);
 
     let msg = InstantiateMsg {
         owner: Some(Addr::unchecked("owner")),
@@ -119,13 +530,14 @@ fn test_register() {
 
     let msg = ExecuteMsg::RegisterAsset {
         staking_token: Addr::unchecked("staking"),
+        min_bond_amount: None,
     };
 
     // failed with unauthorized error
     let info = mock_info("addr", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
     match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "unauthorized"),
+        ContractError::Unauthorized {} => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
 
@@ -156,7 +568,315 @@ fn test_register() {
             reward_index: Decimal::zero(),
             pending_reward: Uint128::zero(),
             migration_deprecated_staking_token: None,
+            migration_deprecated_total_bond: None,
+            migration_grace_period_end: None,
             migration_index_snapshot: None,
+            frozen: false,
+            instant_unbond_penalty: Decimal::zero(),
+            min_bond_amount: Uint128::zero(),
+            min_compound_reward: Uint128::zero(),
+            bond_cap: None,
+            max_bond_per_staker: None,
+            remaining_bond_capacity: None,
+            total_weighted_bond: Uint128::zero(),
+            lock_tiers: vec![],
+            reward_weight: Decimal::zero(),
+            reward_start_time: None,
+            reward_end_time: None,
+            proxy_rewards: vec![],
+            symbol: Some("STAKING".to_string()),
+            staker_count: Some(0),
+            lifetime_bonded: Uint128::zero(),
+            lifetime_unbonded: Uint128::zero(),
+            lifetime_rewards_deposited: Uint128::zero(),
+            lifetime_rewards_claimed: Uint128::zero(),
+            unbonding_period: 0,
+            whitelist_enabled: false,
+            unbond_cooldown: 0,
+            max_index_increase_per_deposit: Decimal::zero(),
+            reward_stream_seconds: 0,
+            streaming_reward: Uint128::zero(),
+            deposit_epoch: 0,
+        }
+    );
+}
+
+#[test]
+fn test_unregister() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_pool("unbonded", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .with_reward_deposit("unbonded", 300)
+        .build();
+
+    let msg = ExecuteMsg::UnregisterAsset {
+        staking_token: Addr::unchecked("staking"),
+    };
+    let res = test_env.execute("addr", msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = test_env.execute("owner", msg.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::PoolHasBonds {
+            total_bond_amount: Uint128::from(100u128)
+        }
+    );
+
+    // the reward stays with the staker after unbonding
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(100u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    let res = test_env.execute("owner", msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::PoolHasStakers {});
+
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    let res = test_env.execute("owner", msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unregister_asset"),
+            attr("staking_token", "staking"),
+        ]
+    );
+
+    let res = query(
+        test_env.deps.as_ref(),
+        test_env.env.clone(),
+        QueryMsg::PoolInfo {
+            staking_token: Addr::unchecked("staking"),
+        },
+    );
+    assert!(res.is_err());
+    let res = test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(300u128),
+                }],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::PoolNotFound {
+            asset: "staking".to_string(),
+        }
+    );
+
+    // nothing was ever bonded here, so the deposit is still waiting in the pool
+    let res = test_env
+        .execute(
+            "owner",
+            ExecuteMsg::UnregisterAsset {
+                staking_token: Addr::unchecked("unbonded"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::PoolHasPendingReward {
+            pending_reward: Uint128::from(300u128)
+        }
+    );
+}
+
+#[test]
+fn test_register_checks_token_info() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .build();
+    assert_eq!(
+        test_env.pool_info("staking").symbol,
+        Some("STAKING".to_string())
+    );
+
+    // no cw20 contract answers at a mistyped address
+    let err = test_env
+        .execute(
+            "owner",
+            ExecuteMsg::RegisterAsset {
+                staking_token: Addr::unchecked("stakign"),
+                min_bond_amount: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidStakingTokenContract {
+            staking_token: "stakign".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_register_assets() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .build();
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "lp1", "lp2"]);
+    let msg = |staking_tokens: &[&str]| ExecuteMsg::RegisterAssets {
+        assets: staking_tokens
+            .iter()
+            .map(|staking_token| RegisterAssetEntry {
+                staking_token: Addr::unchecked(*staking_token),
+                min_bond_amount: None,
+            })
+            .collect(),
+    };
+
+    let err = test_env.execute("addr", msg(&["lp1"])).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = test_env.execute(OWNER, msg(&[])).unwrap_err();
+    assert_eq!(err, ContractError::InvalidRegisterAssetsLength { max: 20 });
+
+    // the first invalid entry fails the batch before any pool is stored
+    for (staking_tokens, reason) in [
+        (["lp1", "lp1"], "lp1 is listed twice"),
+        (["lp1", "staking"], "Asset was already registered"),
+        (
+            ["lp1", "lp3"],
+            "Staking token lp3 does not answer the cw20 TokenInfo query with a symbol and decimals",
+        ),
+    ] {
+        let err = test_env.execute(OWNER, msg(&staking_tokens)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidRegisterAssetsEntry {
+                index: 1,
+                reason: reason.to_string(),
+            }
+        );
+    }
+    assert!(query(
+        test_env.deps.as_ref(),
+        test_env.env.clone(),
+        QueryMsg::PoolInfo {
+            staking_token: Addr::unchecked("lp1"),
+        },
+    )
+    .is_err());
+
+    let res = test_env.execute(OWNER, msg(&["lp1", "lp2"])).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_assets"),
+            attr("staking_token", "lp1"),
+            attr("staking_token", "lp2"),
+        ]
+    );
+    assert_eq!(test_env.pool_info("lp1").symbol, Some("LP1".to_string()));
+    assert_eq!(test_env.pool_info("lp2").symbol, Some("LP2".to_string()));
+}
+
+#[test]
+fn test_register_asset_from_factory() {
+    let mut test_env = StakingTestEnv::new().build();
+    let token = AssetInfo::Token {
+        contract_addr: Addr::unchecked("token"),
+    };
+    let orai = AssetInfo::NativeToken {
+        denom: ORAI_DENOM.to_string(),
+    };
+    let pair_info = PairInfo {
+        asset_infos: [token.clone(), orai.clone()],
+        contract_addr: Addr::unchecked("pair"),
+        liquidity_token: Addr::unchecked("lp"),
+        oracle_addr: Addr::unchecked("oracle"),
+        commission_rate: "0.003".to_string(),
+    };
+    // the factory only knows the token/orai pair
+    test_env.deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, msg } => {
+            let res = match contract_addr.as_str() {
+                "factory" => match from_binary::<FactoryQueryMsg>(msg).unwrap() {
+                    FactoryQueryMsg::Pair { asset_infos }
+                        if asset_infos == pair_info.asset_infos =>
+                    {
+                        to_binary(&pair_info)
+                    }
+                    _ => return SystemResult::Ok(ContractResult::Err("no pair".to_string())),
+                },
+                "pair" => to_binary(&PairResponse {
+                    info: pair_info.clone(),
+                }),
+                "lp" => to_binary(&TokenInfoResponse {
+                    name: "lp".to_string(),
+                    symbol: "LP".to_string(),
+                    decimals: 6,
+                    total_supply: Uint128::zero(),
+                }),
+                _ => {
+                    return SystemResult::Err(SystemError::NoSuchContract {
+                        addr: contract_addr.clone(),
+                    })
+                }
+            };
+            SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+        }
+        _ => panic!("unexpected query"),
+    });
+    let msg = |quote_asset: Option<AssetInfo>| ExecuteMsg::RegisterAssetFromFactory {
+        asset_info: token.clone(),
+        quote_asset,
+        min_bond_amount: None,
+    };
+
+    let err = test_env.execute("addr", msg(None)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the quote asset defaults to the base denom
+    let res = test_env.execute(OWNER, msg(None)).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_asset_from_factory"),
+            attr("pair", "pair"),
+            attr("staking_token", "lp"),
+        ]
+    );
+    assert_eq!(test_env.pool_info("lp").symbol, Some("LP".to_string()));
+    let err = test_env.execute(OWNER, msg(Some(orai))).unwrap_err();
+    assert_eq!(err, ContractError::AssetRegistered {});
+
+    let err = test_env
+        .execute(
+            OWNER,
+            msg(Some(AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            })),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::FactoryPairNotFound {
+            asset: "token".to_string(),
+            quote: ATOM_DENOM.to_string(),
         }
     );
 }
@@ -164,6 +884,7 @@ fn test_register() {
 #[test]
 fn test_query_staker_pagination() {
     let mut deps = mock_dependencies_with_balance(&[coin(10000000000u128, ORAI_DENOM)]);
+    mock_staking_tokens(&mut deps.querier, &["staking"]);
 
     let msg = InstantiateMsg {
         owner: Some(Addr::unchecked("owner")),
@@ -193,6 +914,7 @@ fn test_query_staker_pagination() {
 
     let msg = ExecuteMsg::RegisterAsset {
         staking_token: Addr::unchecked("staking"),
+        min_bond_amount: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -203,7 +925,11 @@ fn test_query_staker_pagination() {
         let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
             sender: format!("addr{}", i),
             amount: Uint128::from(100u128),
-            msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                lock_duration: None,
+                referrer: None,
+            })
+            .unwrap(),
         });
         let info = mock_info("staking", &[]);
         let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -231,3 +957,157 @@ fn test_query_staker_pagination() {
         println!("{:?}", staker_addrs);
     }
 }
+
+#[test]
+fn test_query_pool_infos() {
+    let test_env = StakingTestEnv::new()
+        .with_pool("staking_a", default_rewards_per_sec())
+        .with_pool("staking_b", default_rewards_per_sec())
+        .with_pool("staking_c", default_rewards_per_sec())
+        .with_bond("addr", "staking_b", 100)
+        .build();
+    let pool_infos = |start_after: Option<Addr>, limit: u32, order: Order| {
+        test_env.query::<Vec<PoolInfoResponse>>(QueryMsg::PoolInfos {
+            start_after,
+            limit: Some(limit),
+            order: Some(order.into()),
+        })
+    };
+
+    // the same responses as the single pool query
+    let all = pool_infos(None, 10, Order::Ascending);
+    assert_eq!(all.len(), 3);
+    for pool_info in all.iter() {
+        assert_eq!(
+            pool_info,
+            &test_env.pool_info(pool_info.staking_token.as_str())
+        );
+    }
+
+    let first_page = pool_infos(None, 2, Order::Ascending);
+    assert_eq!(first_page, all[..2].to_vec());
+    let second_page = pool_infos(
+        Some(first_page[1].staking_token.clone()),
+        2,
+        Order::Ascending,
+    );
+    assert_eq!(second_page, all[2..].to_vec());
+
+    let mut descending = pool_infos(None, 10, Order::Descending);
+    descending.reverse();
+    assert_eq!(descending, all);
+    assert_eq!(
+        pool_infos(Some(all[1].staking_token.clone()), 10, Order::Descending),
+        vec![all[0].clone()]
+    );
+}
+
+#[test]
+fn test_repair_pool_info() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 100)
+        .build();
+    let current_index = test_env.pool_info("staking").reward_index;
+    assert!(!current_index.is_zero());
+    let repair = |patch: PoolInfoPatch, reason: &str| ExecuteMsg::RepairPoolInfo {
+        staking_token: Addr::unchecked("staking"),
+        patch,
+        reason: reason.to_string(),
+    };
+    let patch = |reward_index: Option<Decimal>, total_bond_amount: Option<u128>| PoolInfoPatch {
+        reward_index,
+        total_bond_amount: total_bond_amount.map(Uint128::from),
+        pending_reward: None,
+    };
+
+    // a break-glass tool, only for the owner of a paused contract
+    let err = test_env
+        .execute(OWNER, repair(patch(None, Some(150)), "fix"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotPaused {});
+    test_env.execute(OWNER, ExecuteMsg::Pause {}).unwrap();
+    let err = test_env
+        .execute("addr", repair(patch(None, Some(150)), "fix"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = test_env
+        .execute(OWNER, repair(patch(None, Some(150)), " "))
+        .unwrap_err();
+    assert_eq!(err, ContractError::EmptyRepairReason {});
+
+    let err = test_env
+        .execute(OWNER, repair(patch(Some(Decimal::zero()), None), "fix"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RepairIndexRegression {
+            current: current_index,
+            requested: Decimal::zero(),
+        }
+    );
+    let err = test_env
+        .execute(OWNER, repair(patch(None, Some(99)), "fix"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RepairBelowPositions {
+            positions: Uint128::from(100u128),
+            requested: Uint128::from(99u128),
+        }
+    );
+    let err = test_env
+        .execute(OWNER, repair(patch(Some(current_index), Some(100)), "fix"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::NothingToRepair {});
+
+    let new_index = current_index + Decimal::one();
+    let res = test_env
+        .execute(
+            OWNER,
+            repair(patch(Some(new_index), Some(150)), "index regressed"),
+        )
+        .unwrap();
+    assert!(res.attributes.contains(&attr("repair_id", "0")));
+    let pool_info = test_env.pool_info("staking");
+    assert_eq!(pool_info.reward_index, new_index);
+    assert_eq!(pool_info.total_bond_amount, Uint128::from(150u128));
+
+    test_env
+        .execute(OWNER, repair(patch(None, Some(100)), "total restored"))
+        .unwrap();
+    let history = |start_after: Option<u64>| -> Vec<PoolRepairResponse> {
+        test_env.query(QueryMsg::RepairHistory {
+            staking_token: Addr::unchecked("staking"),
+            start_after,
+            limit: None,
+        })
+    };
+    let res = history(None);
+    assert_eq!(res.len(), 2);
+    assert_eq!(res[0].reason, "index regressed");
+    assert_eq!(
+        res[0].changes,
+        vec![
+            PoolFieldChange {
+                field: "reward_index".to_string(),
+                old_value: current_index.to_string(),
+                new_value: new_index.to_string(),
+            },
+            PoolFieldChange {
+                field: "total_bond_amount".to_string(),
+                old_value: "100".to_string(),
+                new_value: "150".to_string(),
+            },
+        ]
+    );
+    assert_eq!(res[0].time, test_env.env.block.time.seconds());
+    assert_eq!(
+        history(Some(0))
+            .into_iter()
+            .map(|item| (item.id, item.reason))
+            .collect::<Vec<_>>(),
+        vec![(1, "total restored".to_string())]
+    );
+}
@@ -1,13 +1,26 @@
 use crate::contract::{execute, instantiate, query};
-use crate::state::{read_pool_info, store_pool_info};
+use crate::state::{
+    read_pool_info, rewards_read, rewards_store, stakers_store, staking_token_key, store_pool_info,
+};
+use crate::testing::env::{
+    default_rewards_per_sec, mock_staking_tokens, StakingTestEnv, OWNER, REWARDER,
+};
 use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info};
-use cosmwasm_std::{coin, from_binary, to_binary, Addr, Api, Decimal, SubMsg, Uint128, WasmMsg};
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
-use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
+use cosmwasm_std::{
+    coin, from_binary, to_binary, Addr, Api, CanonicalAddr, ContractResult, Decimal, SubMsg,
+    SystemError, SystemResult, Uint128, WasmMsg, WasmQuery,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse, TokenInfoResponse};
+use oraiswap::asset::{Asset, AssetInfo, PairInfo, ORAI_DENOM};
+use oraiswap::error::ContractError;
+use oraiswap::pair::{Cw20HookMsg as PairCw20HookMsg, PairResponse};
 use oraiswap::staking::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolInfoResponse, QueryMsg, RewardInfoResponse,
-    RewardInfoResponseItem, RewardMsg,
+    AssetDecimals, Cw20HookMsg, DeprecatedBondsResponse, ExecuteMsg, InstantiateMsg,
+    PoolInfoByStakingTokenResponse, PoolInfoResponse, PoolMigrationStatus, QueryMsg,
+    RewardInfoByStakingTokenResponse, RewardInfoResponse, RewardInfoResponseItem, RewardMsg,
+    RewardsPerSecResponse, WithdrawResponse,
 };
+use oraiswap::testing::staking::bond_receive_msg;
 use oraiswap::testing::ATOM_DENOM;
 
 #[test]
@@ -16,6 +29,7 @@ fn test_deprecate() {
         coin(10000000000u128, ORAI_DENOM),
         coin(20000000000u128, ATOM_DENOM),
     ]);
+    mock_staking_tokens(&mut deps.querier, &["staking", "new_staking"]);
 
     let msg = InstantiateMsg {
         owner: Some(Addr::unchecked("owner")),
@@ -31,6 +45,7 @@ fn test_deprecate() {
 
     let msg = ExecuteMsg::RegisterAsset {
         staking_token: Addr::unchecked("staking"),
+        min_bond_amount: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -66,7 +81,11 @@ fn test_deprecate() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
     });
     let info = mock_info("staking", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -102,6 +121,8 @@ fn test_deprecate() {
             reward_index: Decimal::from_ratio(100u128, 100u128),
             migration_index_snapshot: None,
             migration_deprecated_staking_token: None,
+            migration_deprecated_total_bond: None,
+            migration_grace_period_end: None,
             ..res
         }
     );
@@ -122,9 +143,14 @@ fn test_deprecate() {
             reward_infos: vec![RewardInfoResponseItem {
                 staking_token: Addr::unchecked("staking"),
                 bond_amount: Uint128::from(100u128),
+                weighted_bond_amount: Uint128::from(100u128),
+                locks: vec![],
                 pending_reward: Uint128::from(100u128),
                 pending_withdraw: vec![],
+                reward_decimals: vec![],
                 should_migrate: None,
+                total_claimed: Uint128::zero(),
+                last_updated: mock_env().block.time.seconds(),
             }],
         }
     );
@@ -133,6 +159,7 @@ fn test_deprecate() {
     let msg = ExecuteMsg::DeprecateStakingToken {
         staking_token: Addr::unchecked("staking"),
         new_staking_token: Addr::unchecked("new_staking"),
+        grace_period: None,
     };
     let info = mock_info("owner", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -165,7 +192,7 @@ fn test_deprecate() {
         res_cmp,
         PoolInfoResponse {
             staking_token: Addr::unchecked("new_staking"),
-            total_bond_amount: Uint128::from(100u128), // reset
+            total_bond_amount: Uint128::zero(), // the old positions are counted apart
             reward_index: Decimal::from_ratio(100u128, 100u128), // stays the same
             migration_index_snapshot: Some(Decimal::from_ratio(100u128, 100u128)),
             migration_deprecated_staking_token: Some(Addr::unchecked("staking")),
@@ -195,7 +222,11 @@ fn test_deprecate() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
     });
     let info = mock_info("staking", &[]);
     let _err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
@@ -205,7 +236,10 @@ fn test_deprecate() {
     // unbond all the old tokens
     let msg = ExecuteMsg::Unbond {
         staking_token: Addr::unchecked("new_staking"),
-        amount: Uint128::from(100u128),
+        amount: Some(Uint128::from(100u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
     };
     let info = mock_info("addr", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -244,7 +278,11 @@ fn test_deprecate() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
     });
     let info = mock_info("new_staking", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -260,23 +298,44 @@ fn test_deprecate() {
     let info = mock_info("rewarder", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
 
-    // expect to have 80 * 3 rewards
-    // initial + deposit after deprecation + deposit after bonding again
-    let _data = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::RewardInfo {
-            staking_token: None,
-            staker_addr: Addr::unchecked("addr"),
-        },
+    // the position left on the old token still holds the rewards up to the snapshot
+    let res: RewardInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RewardInfo {
+                staking_token: Some(Addr::unchecked("staking")),
+                staker_addr: Addr::unchecked("addr"),
+            },
+        )
+        .unwrap(),
     )
-    .unwrap_err();
+    .unwrap();
+    assert_eq!(
+        res.reward_infos,
+        vec![RewardInfoResponseItem {
+            staking_token: Addr::unchecked("staking"),
+            bond_amount: Uint128::from(100u128),
+            weighted_bond_amount: Uint128::from(100u128),
+            locks: vec![],
+            pending_reward: Uint128::from(100u128),
+            pending_withdraw: vec![],
+            reward_decimals: vec![],
+            should_migrate: Some(true),
+            total_claimed: Uint128::zero(),
+            last_updated: mock_env().block.time.seconds(),
+        }]
+    );
 
     // completely new users can bond
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "newaddr".into(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
     });
     let info = mock_info("new_staking", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -298,10 +357,1261 @@ fn test_deprecate() {
             reward_infos: vec![RewardInfoResponseItem {
                 staking_token: Addr::unchecked("new_staking"),
                 bond_amount: Uint128::from(100u128),
+                weighted_bond_amount: Uint128::from(100u128),
+                locks: vec![],
                 pending_reward: Uint128::zero(),
                 pending_withdraw: vec![],
+                reward_decimals: vec![],
                 should_migrate: None,
+                total_claimed: Uint128::zero(),
+                last_updated: mock_env().block.time.seconds(),
             },],
         }
     );
 }
+
+#[test]
+fn test_deprecated_bonds() {
+    let mut deps = mock_dependencies_with_balance(&[coin(10000000000u128, ORAI_DENOM)]);
+    mock_staking_tokens(&mut deps.querier, &["staking", "new_staking"]);
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        staking_token: Addr::unchecked("staking"),
+        min_bond_amount: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // bond 100 tokens for each staker
+    for staker in ["addr", "addr2"] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                lock_duration: None,
+                referrer: None,
+            })
+            .unwrap(),
+        });
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("staking", &[]), msg).unwrap();
+    }
+
+    // not deprecated yet
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::DeprecatedBonds {
+            staking_token: Addr::unchecked("staking"),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::StakingTokenNotDeprecated {});
+
+    let msg = ExecuteMsg::DeprecateStakingToken {
+        staking_token: Addr::unchecked("staking"),
+        new_staking_token: Addr::unchecked("new_staking"),
+        grace_period: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let res: DeprecatedBondsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::DeprecatedBonds {
+                staking_token: Addr::unchecked("new_staking"),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        DeprecatedBondsResponse {
+            staking_token: Addr::unchecked("new_staking"),
+            deprecated_staking_token: Addr::unchecked("staking"),
+            deprecated_total_bond: Uint128::from(200u128),
+            staker_count: 2,
+        }
+    );
+
+    // move addr2 position under the pool key, as the store migration does for old positions
+    let old_key = deps.api.addr_canonicalize("staking").unwrap();
+    let new_key = deps.api.addr_canonicalize("new_staking").unwrap();
+    let staker = deps.api.addr_canonicalize("addr2").unwrap();
+    let reward_info = rewards_read(&deps.storage, &staker).load(&old_key).unwrap();
+    rewards_store(&mut deps.storage, &staker).remove(&old_key);
+    rewards_store(&mut deps.storage, &staker)
+        .save(&new_key, &reward_info)
+        .unwrap();
+    stakers_store(&mut deps.storage, &old_key).remove(&staker);
+    stakers_store(&mut deps.storage, &new_key)
+        .save(&staker, &true)
+        .unwrap();
+
+    // addr2 unbonds the old position and receives the deprecated lp tokens
+    let msg = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("new_staking"),
+        amount: Some(Uint128::from(100u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr2", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".into(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr2".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    let res: PoolInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PoolInfo {
+                staking_token: Addr::unchecked("new_staking"),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.migration_deprecated_total_bond,
+        Some(Uint128::from(100u128))
+    );
+
+    let res: DeprecatedBondsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::DeprecatedBonds {
+                staking_token: Addr::unchecked("new_staking"),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.deprecated_total_bond, Uint128::from(100u128));
+    assert_eq!(res.staker_count, 1);
+}
+
+#[test]
+fn test_staking_token_transition() {
+    let mut deps = mock_dependencies_with_balance(&[coin(10000000000u128, ORAI_DENOM)]);
+    mock_staking_tokens(&mut deps.querier, &["staking", "new_staking"]);
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateRewardsPerSec {
+        staking_token: Addr::unchecked("staking"),
+        assets: vec![Asset {
+            info: AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            amount: 100u128.into(),
+        }],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        staking_token: Addr::unchecked("staking"),
+        min_bond_amount: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let bond_msg = |staker: &str, amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                lock_duration: None,
+                referrer: None,
+            })
+            .unwrap(),
+        })
+    };
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("staking", &[]),
+        bond_msg("addr", 100),
+    )
+    .unwrap();
+
+    // new staking token is not bondable before the transition starts
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_staking", &[]),
+        bond_msg("addr2", 100),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::PoolNotFound {
+            asset: "new_staking".to_string()
+        }
+    );
+
+    let msg = ExecuteMsg::StartStakingTokenTransition {
+        staking_token: Addr::unchecked("staking"),
+        new_staking_token: Addr::unchecked("new_staking"),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // both tokens are bondable into the same pool
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_staking", &[]),
+        bond_msg("addr2", 100),
+    )
+    .unwrap();
+
+    let asset_key = deps.api.addr_canonicalize("staking").unwrap();
+    let pool_info = read_pool_info(&deps.storage, &asset_key).unwrap();
+    assert_eq!(pool_info.total_bond_amount, Uint128::from(200u128));
+    assert_eq!(
+        pool_info.transition_staking_tokens[0].total_bond_amount,
+        Uint128::from(100u128)
+    );
+
+    // rewards are shared across the combined total
+    let msg = ExecuteMsg::DepositReward {
+        rewards: vec![RewardMsg {
+            staking_token: Addr::unchecked("staking"),
+            total_accumulation_amount: Uint128::from(200u128),
+        }],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("rewarder", &[]), msg).unwrap();
+
+    for (staker, staking_token) in [("addr", "staking"), ("addr2", "new_staking")] {
+        let res: RewardInfoResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::RewardInfo {
+                    staking_token: None,
+                    staker_addr: Addr::unchecked(staker),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.reward_infos,
+            vec![RewardInfoResponseItem {
+                staking_token: Addr::unchecked(staking_token),
+                bond_amount: Uint128::from(100u128),
+                weighted_bond_amount: Uint128::from(100u128),
+                locks: vec![],
+                pending_reward: Uint128::from(100u128),
+                pending_withdraw: vec![],
+                reward_decimals: vec![],
+                should_migrate: None,
+                total_claimed: Uint128::zero(),
+                last_updated: mock_env().block.time.seconds(),
+            }]
+        );
+    }
+
+    // unbonding returns the staking token that was bonded
+    let msg = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("new_staking"),
+        amount: Some(Uint128::from(50u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr2", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "new_staking".into(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr2".to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    // finalize the transition
+    let msg = ExecuteMsg::DeprecateStakingToken {
+        staking_token: Addr::unchecked("staking"),
+        new_staking_token: Addr::unchecked("new_staking"),
+        grace_period: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let res: PoolInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PoolInfo {
+                staking_token: Addr::unchecked("new_staking"),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.total_bond_amount, Uint128::from(50u128));
+    assert_eq!(
+        res.migration_deprecated_total_bond,
+        Some(Uint128::from(100u128))
+    );
+
+    // the old token is not bondable anymore
+    let _err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("staking", &[]),
+        bond_msg("addr", 100),
+    )
+    .unwrap_err();
+
+    // positions of the new token keep earning rewards after finalization
+    let msg = ExecuteMsg::DepositReward {
+        rewards: vec![RewardMsg {
+            staking_token: Addr::unchecked("new_staking"),
+            total_accumulation_amount: Uint128::from(100u128),
+        }],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("rewarder", &[]), msg).unwrap();
+
+    let res: RewardInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RewardInfo {
+                staking_token: Some(Addr::unchecked("new_staking")),
+                staker_addr: Addr::unchecked("addr2"),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.reward_infos,
+        vec![RewardInfoResponseItem {
+            staking_token: Addr::unchecked("new_staking"),
+            bond_amount: Uint128::from(50u128),
+            weighted_bond_amount: Uint128::from(50u128),
+            locks: vec![],
+            pending_reward: Uint128::from(200u128),
+            pending_withdraw: vec![],
+            reward_decimals: vec![],
+            should_migrate: None,
+            total_claimed: Uint128::zero(),
+            last_updated: mock_env().block.time.seconds(),
+        }]
+    );
+}
+
+#[test]
+fn test_reward_decimals_survive_deprecation() {
+    let mut deps = mock_dependencies_with_balance(&[coin(10000000000u128, ORAI_DENOM)]);
+    mock_staking_tokens(&mut deps.querier, &["staking", "new_staking"]);
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        staking_token: Addr::unchecked("staking"),
+        min_bond_amount: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // the reward token answers TokenInfo once, when the rewards per sec are set
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { .. } => SystemResult::Ok(ContractResult::Ok(
+            to_binary(&TokenInfoResponse {
+                name: "reward".to_string(),
+                symbol: "RWD".to_string(),
+                decimals: 18,
+                total_supply: Uint128::zero(),
+            })
+            .unwrap(),
+        )),
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "wasm".to_string(),
+        }),
+    });
+
+    let msg = ExecuteMsg::UpdateRewardsPerSec {
+        staking_token: Addr::unchecked("staking"),
+        assets: vec![
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: 100u128.into(),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("reward"),
+                },
+                amount: 200u128.into(),
+            },
+        ],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // queries are served from the cache, only the new staking token still answers
+    mock_staking_tokens(&mut deps.querier, &["new_staking"]);
+
+    let expected_decimals = vec![
+        AssetDecimals {
+            info: AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            decimals: 6,
+        },
+        AssetDecimals {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("reward"),
+            },
+            decimals: 18,
+        },
+    ];
+    let res: RewardsPerSecResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RewardsPerSec {
+                staking_token: Addr::unchecked("staking"),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.reward_decimals, expected_decimals);
+
+    let msg = ExecuteMsg::DeprecateStakingToken {
+        staking_token: Addr::unchecked("staking"),
+        new_staking_token: Addr::unchecked("new_staking"),
+        grace_period: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let res: RewardsPerSecResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RewardsPerSec {
+                staking_token: Addr::unchecked("new_staking"),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.reward_decimals, expected_decimals);
+}
+
+#[test]
+fn test_migrate_bond_requires_deprecated_position() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "new_staking"]);
+
+    let migrate_msg = |staking_token: &str| ExecuteMsg::MigrateBond {
+        staking_token: Addr::unchecked(staking_token),
+        slippage_tolerance: None,
+    };
+
+    let err = test_env
+        .execute("addr", migrate_msg("staking"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::StakingTokenNotDeprecated {});
+
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::DeprecateStakingToken {
+                staking_token: Addr::unchecked("staking"),
+                new_staking_token: Addr::unchecked("new_staking"),
+                grace_period: None,
+            },
+        )
+        .unwrap();
+
+    // addr2 never bonded the deprecated token
+    let err = test_env
+        .execute("addr2", migrate_msg("new_staking"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::NothingToUnbond {});
+
+    // only the contract itself closes the deprecated position
+    let err = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::MigrateBondHook {
+                staker_addr: Addr::unchecked("addr"),
+                staking_token: Addr::unchecked("new_staking"),
+                pair_addr: Addr::unchecked("pair"),
+                amount: Uint128::from(100u128),
+                prev_balances: [
+                    Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: ORAI_DENOM.to_string(),
+                        },
+                        amount: Uint128::zero(),
+                    },
+                    Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: ATOM_DENOM.to_string(),
+                        },
+                        amount: Uint128::zero(),
+                    },
+                ],
+                slippage_tolerance: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_deprecate_checks_new_staking_token() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_pool("other", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    mock_staking_tokens(
+        &mut test_env.deps.querier,
+        &["staking", "other", "new_staking", "empty_staking"],
+    );
+    let deprecate_msg =
+        |staking_token: &str, new_staking_token: &str| ExecuteMsg::DeprecateStakingToken {
+            staking_token: Addr::unchecked(staking_token),
+            new_staking_token: Addr::unchecked(new_staking_token),
+            grace_period: None,
+        };
+
+    let err = test_env
+        .execute(OWNER, deprecate_msg("staking", "staking"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidNewStakingToken {
+            new_staking_token: "staking".to_string(),
+        }
+    );
+    let err = test_env
+        .execute(OWNER, deprecate_msg("staking", "other"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::AssetRegistered {});
+    let err = test_env
+        .execute(OWNER, deprecate_msg("staking", "missing"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidStakingTokenContract {
+            staking_token: "missing".to_string(),
+        }
+    );
+
+    test_env
+        .execute(OWNER, deprecate_msg("staking", "new_staking"))
+        .unwrap();
+    let pool_info = test_env.pool_info("new_staking");
+    assert_eq!(
+        pool_info.migration_deprecated_staking_token,
+        Some(Addr::unchecked("staking"))
+    );
+    assert_eq!(pool_info.symbol, Some("NEW_STAKING".to_string()));
+
+    // deprecated positions of "staking" are still keyed by it
+    let err = test_env
+        .execute(OWNER, deprecate_msg("other", "staking"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidNewStakingToken {
+            new_staking_token: "staking".to_string(),
+        }
+    );
+
+    // nobody bonded "other", it just takes the new token
+    test_env
+        .execute(OWNER, deprecate_msg("other", "empty_staking"))
+        .unwrap();
+    let pool_info = test_env.pool_info("empty_staking");
+    assert_eq!(pool_info.migration_deprecated_staking_token, None);
+    assert_eq!(pool_info.migration_deprecated_total_bond, None);
+}
+
+#[test]
+fn test_pool_info_by_staking_token() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "new_staking"]);
+    let pool_info_by_staking_token = |test_env: &StakingTestEnv, staking_token: &str| {
+        query(
+            test_env.deps.as_ref(),
+            test_env.env.clone(),
+            QueryMsg::PoolInfoByStakingToken {
+                staking_token: Addr::unchecked(staking_token),
+            },
+        )
+        .map(|res| from_binary::<PoolInfoByStakingTokenResponse>(&res).unwrap())
+    };
+
+    let res = pool_info_by_staking_token(&test_env, "staking").unwrap();
+    assert!(!res.deprecated);
+    assert_eq!(res.pool_info, test_env.pool_info("staking"));
+    assert!(pool_info_by_staking_token(&test_env, "new_staking").is_err());
+
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::DeprecateStakingToken {
+                staking_token: Addr::unchecked("staking"),
+                new_staking_token: Addr::unchecked("new_staking"),
+                grace_period: None,
+            },
+        )
+        .unwrap();
+
+    // the deprecated token resolves to the pool that took it over
+    let res = pool_info_by_staking_token(&test_env, "staking").unwrap();
+    assert!(res.deprecated);
+    assert_eq!(res.pool_info, test_env.pool_info("new_staking"));
+    assert_eq!(
+        res.pool_info.migration_deprecated_staking_token,
+        Some(Addr::unchecked("staking"))
+    );
+    let res = pool_info_by_staking_token(&test_env, "new_staking").unwrap();
+    assert!(!res.deprecated);
+    assert!(pool_info_by_staking_token(&test_env, "other").is_err());
+}
+
+#[test]
+fn test_reward_info_by_staking_token() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "new_staking"]);
+    let reward_info_by_staking_token = |test_env: &StakingTestEnv, staking_token: &str| {
+        query(
+            test_env.deps.as_ref(),
+            test_env.env.clone(),
+            QueryMsg::RewardInfoByStakingToken {
+                staker_addr: Addr::unchecked("addr"),
+                staking_token: Addr::unchecked(staking_token),
+            },
+        )
+        .map(|res| from_binary::<RewardInfoByStakingTokenResponse>(&res).unwrap())
+    };
+
+    let res = reward_info_by_staking_token(&test_env, "staking").unwrap();
+    assert!(!res.deprecated);
+    assert_eq!(
+        res.reward_info,
+        test_env.reward_info("addr", Some("staking"))
+    );
+
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::DeprecateStakingToken {
+                staking_token: Addr::unchecked("staking"),
+                new_staking_token: Addr::unchecked("new_staking"),
+                grace_period: None,
+            },
+        )
+        .unwrap();
+
+    // the old LP token still finds the position left on it
+    let res = reward_info_by_staking_token(&test_env, "staking").unwrap();
+    assert!(res.deprecated);
+    assert_eq!(
+        res.reward_info,
+        test_env.reward_info("addr", Some("staking"))
+    );
+    assert_eq!(
+        res.reward_info.reward_infos[0].bond_amount,
+        Uint128::from(100u128)
+    );
+    assert_eq!(res.reward_info.reward_infos[0].should_migrate, Some(true));
+    assert!(
+        !reward_info_by_staking_token(&test_env, "new_staking")
+            .unwrap()
+            .deprecated
+    );
+
+    assert_eq!(
+        reward_info_by_staking_token(&test_env, "other").unwrap_err(),
+        ContractError::NoPoolForStakingToken {
+            staking_token: "other".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_query_migration_status() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking_a", default_rewards_per_sec())
+        .with_pool("staking_b", default_rewards_per_sec())
+        .with_pool("staking_c", default_rewards_per_sec())
+        .with_bond("addr", "staking_a", 100)
+        .with_bond("addr", "staking_c", 50)
+        .with_reward_deposit("staking_a", 300)
+        .build();
+    mock_staking_tokens(
+        &mut test_env.deps.querier,
+        &["staking_a", "staking_b", "staking_c", "new_a", "new_c"],
+    );
+    let migration_status = |test_env: &StakingTestEnv, start_after: Option<Addr>| {
+        test_env.query::<Vec<PoolMigrationStatus>>(QueryMsg::MigrationStatus {
+            start_after,
+            limit: Some(1),
+        })
+    };
+    assert_eq!(migration_status(&test_env, None), vec![]);
+
+    for (staking_token, new_staking_token) in [("staking_a", "new_a"), ("staking_c", "new_c")] {
+        test_env
+            .execute(
+                OWNER,
+                ExecuteMsg::DeprecateStakingToken {
+                    staking_token: Addr::unchecked(staking_token),
+                    new_staking_token: Addr::unchecked(new_staking_token),
+                    grace_period: None,
+                },
+            )
+            .unwrap();
+    }
+
+    // staking_b has no migration params and is left out
+    let mut statuses = migration_status(&test_env, None);
+    statuses.extend(migration_status(
+        &test_env,
+        Some(statuses[0].staking_token.clone()),
+    ));
+    assert!(migration_status(&test_env, Some(statuses[1].staking_token.clone())).is_empty());
+    statuses.sort_by(|a, b| a.staking_token.cmp(&b.staking_token));
+    assert_eq!(
+        statuses,
+        vec![
+            PoolMigrationStatus {
+                staking_token: Addr::unchecked("new_a"),
+                deprecated_staking_token: Addr::unchecked("staking_a"),
+                index_snapshot: Decimal::from_ratio(3u128, 1u128),
+                deprecated_total_bond: Uint128::from(100u128),
+            },
+            PoolMigrationStatus {
+                staking_token: Addr::unchecked("new_c"),
+                deprecated_staking_token: Addr::unchecked("staking_c"),
+                index_snapshot: Decimal::zero(),
+                deprecated_total_bond: Uint128::from(50u128),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_unmigrated_position_settles_at_snapshot() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "new_staking"]);
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::DeprecateStakingToken {
+                staking_token: Addr::unchecked("staking"),
+                new_staking_token: Addr::unchecked("new_staking"),
+                grace_period: None,
+            },
+        )
+        .unwrap();
+
+    // rewards of the new pool only go to its own positions
+    let bond_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr2".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
+    });
+    test_env.execute("new_staking", bond_msg).unwrap();
+    test_env
+        .execute(
+            REWARDER,
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("new_staking"),
+                    total_accumulation_amount: Uint128::from(600u128),
+                }],
+            },
+        )
+        .unwrap();
+    test_env.assert_pending("addr", "staking", 300);
+    test_env.assert_pending("addr2", "new_staking", 600);
+
+    // deposits can not name the old token anymore
+    let err = test_env
+        .execute(
+            REWARDER,
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(100u128),
+                }],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::PoolNotFound {
+            asset: "staking".to_string()
+        }
+    );
+
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    // the snapshot reward, each share of the 1:2 split is floored
+    let data: WithdrawResponse = from_binary(&res.data.unwrap()).unwrap();
+    assert_eq!(
+        data.reward_assets,
+        vec![
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(99u128),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ATOM_DENOM.to_string(),
+                },
+                amount: Uint128::from(199u128),
+            },
+        ]
+    );
+    test_env.assert_pending("addr", "staking", 0);
+
+    // unbonding the old position returns the deprecated token
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: None,
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".into(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    let pool_info = test_env.pool_info("new_staking");
+    assert_eq!(pool_info.total_bond_amount, Uint128::from(100u128));
+    assert_eq!(
+        pool_info.migration_deprecated_total_bond,
+        Some(Uint128::zero())
+    );
+    test_env.assert_pending("addr2", "new_staking", 600);
+}
+
+#[test]
+fn test_deprecated_unbond_keeps_new_pool_total() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_bond("addr3", "staking", 50)
+        .build();
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "new_staking"]);
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::DeprecateStakingToken {
+                staking_token: Addr::unchecked("staking"),
+                new_staking_token: Addr::unchecked("new_staking"),
+                grace_period: None,
+            },
+        )
+        .unwrap();
+    let pool_info = test_env.pool_info("new_staking");
+    assert_eq!(pool_info.total_bond_amount, Uint128::zero());
+    assert_eq!(
+        pool_info.migration_deprecated_total_bond,
+        Some(Uint128::from(150u128))
+    );
+
+    let bond_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr2".to_string(),
+        amount: Uint128::from(40u128),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
+    });
+    test_env.execute("new_staking", bond_msg).unwrap();
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(60u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    let pool_info = test_env.pool_info("new_staking");
+    assert_eq!(pool_info.total_bond_amount, Uint128::from(40u128));
+    assert_eq!(
+        pool_info.migration_deprecated_total_bond,
+        Some(Uint128::from(90u128))
+    );
+
+    // the new position takes the whole deposit
+    test_env
+        .execute(
+            REWARDER,
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("new_staking"),
+                    total_accumulation_amount: Uint128::from(400u128),
+                }],
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        test_env.pool_info("new_staking").reward_index,
+        Decimal::from_ratio(10u128, 1u128)
+    );
+    test_env.assert_pending("addr2", "new_staking", 400);
+    test_env.assert_pending("addr", "staking", 0);
+}
+
+#[test]
+fn test_deprecated_position_unbond_sequences() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "new_staking"]);
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::DeprecateStakingToken {
+                staking_token: Addr::unchecked("staking"),
+                new_staking_token: Addr::unchecked("new_staking"),
+                grace_period: None,
+            },
+        )
+        .unwrap();
+    let unbond = |staking_token: &str, amount: u128| ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked(staking_token),
+        amount: Some(Uint128::from(amount)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let transfer = |token: &str, amount: u128| {
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: token.into(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr".to_string(),
+                amount: Uint128::from(amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    };
+
+    // part of the old position, paid in the deprecated token the contract holds for it
+    let res = test_env.execute("addr", unbond("staking", 40)).unwrap();
+    assert_eq!(res.messages, transfer("staking", 40));
+    let pool_info = test_env.pool_info("new_staking");
+    assert_eq!(pool_info.total_bond_amount, Uint128::zero());
+    assert_eq!(
+        pool_info.migration_deprecated_total_bond,
+        Some(Uint128::from(60u128))
+    );
+
+    // the new token can not be added to a position still backed by the old one
+    let err = test_env
+        .execute(
+            "new_staking",
+            bond_receive_msg("addr", Uint128::from(10u128), None).unwrap(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::StakingTokenDeprecated {});
+
+    // the position decides the token, whichever one the unbond names
+    let res = test_env.execute("addr", unbond("new_staking", 60)).unwrap();
+    assert_eq!(res.messages, transfer("staking", 60));
+
+    // once closed the staker bonds and unbonds the new token
+    test_env
+        .execute(
+            "new_staking",
+            bond_receive_msg("addr", Uint128::from(30u128), None).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        test_env.pool_info("new_staking").total_bond_amount,
+        Uint128::from(30u128)
+    );
+    let err = test_env.execute("addr", unbond("staking", 40)).unwrap_err();
+    assert!(matches!(err, ContractError::InsufficientBond { .. }));
+    let res = test_env.execute("addr", unbond("new_staking", 30)).unwrap();
+    assert_eq!(res.messages, transfer("new_staking", 30));
+    let pool_info = test_env.pool_info("new_staking");
+    assert_eq!(pool_info.total_bond_amount, Uint128::zero());
+    assert_eq!(
+        pool_info.migration_deprecated_total_bond,
+        Some(Uint128::zero())
+    );
+}
+
+#[test]
+fn test_malformed_deprecated_token_fails_pool_query() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "new_staking"]);
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::DeprecateStakingToken {
+                staking_token: Addr::unchecked("staking"),
+                new_staking_token: Addr::unchecked("new_staking"),
+                grace_period: None,
+            },
+        )
+        .unwrap();
+
+    let asset_key =
+        staking_token_key(&test_env.deps.storage, &test_env.deps.api, "new_staking").unwrap();
+    let mut pool_info = read_pool_info(&test_env.deps.storage, &asset_key).unwrap();
+    pool_info
+        .migration_params
+        .as_mut()
+        .unwrap()
+        .deprecated_staking_token = CanonicalAddr::from(vec![1u8; 3]);
+    store_pool_info(&mut test_env.deps.storage, &asset_key, &pool_info).unwrap();
+
+    // the corrupted pool answers with an error, it does not take the query down with a panic
+    let res = query(
+        test_env.deps.as_ref(),
+        test_env.env.clone(),
+        QueryMsg::PoolInfo {
+            staking_token: Addr::unchecked("new_staking"),
+        },
+    );
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_bond_deprecated_token_in_grace_period() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "new_staking"]);
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::DeprecateStakingToken {
+                staking_token: Addr::unchecked("staking"),
+                new_staking_token: Addr::unchecked("new_staking"),
+                grace_period: Some(100),
+            },
+        )
+        .unwrap();
+    let grace_period_end = test_env.env.block.time.seconds() + 100;
+    assert_eq!(
+        test_env.pool_info("new_staking").migration_grace_period_end,
+        Some(grace_period_end)
+    );
+
+    // the deprecated LP token is minted by the old pair, the factory knows the new one
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: ATOM_DENOM.to_string(),
+        },
+    ];
+    let pair_info = |pair: &str, liquidity_token: &str| PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked(pair),
+        liquidity_token: Addr::unchecked(liquidity_token),
+        oracle_addr: Addr::unchecked("oracle"),
+        commission_rate: "0.003".to_string(),
+    };
+    let (old_pair, new_pair) = (
+        pair_info("old_pair", "staking"),
+        pair_info("new_pair", "new_staking"),
+    );
+    test_env.deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } => SystemResult::Ok(ContractResult::Ok(
+            match contract_addr.as_str() {
+                "staking" => to_binary(&Some(MinterResponse {
+                    minter: "old_pair".to_string(),
+                    cap: None,
+                })),
+                "old_pair" => to_binary(&PairResponse {
+                    info: old_pair.clone(),
+                }),
+                _ => to_binary(&new_pair),
+            }
+            .unwrap(),
+        )),
+        _ => panic!("unexpected query"),
+    });
+
+    let res = test_env
+        .execute(
+            "staking",
+            bond_receive_msg("addr2", Uint128::from(50u128), None).unwrap(),
+        )
+        .unwrap();
+    let prev_balances = [
+        Asset {
+            info: asset_infos[0].clone(),
+            amount: Uint128::from(10000000000u128),
+        },
+        Asset {
+            info: asset_infos[1].clone(),
+            amount: Uint128::from(20000000000u128),
+        },
+    ];
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "staking".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "old_pair".to_string(),
+                    amount: Uint128::from(50u128),
+                    msg: to_binary(&PairCw20HookMsg::WithdrawLiquidity {}).unwrap(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: test_env.env.contract.address.to_string(),
+                msg: to_binary(&ExecuteMsg::MigrateReceivedBondHook {
+                    staker_addr: Addr::unchecked("addr2"),
+                    staking_token: Addr::unchecked("new_staking"),
+                    pair_addr: Addr::unchecked("new_pair"),
+                    prev_balances: prev_balances.clone(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+    // nothing is bonded until the hook provides the liquidity
+    test_env.assert_bond("addr2", "new_staking", 0);
+
+    let err = test_env
+        .execute(
+            "addr2",
+            ExecuteMsg::MigrateReceivedBondHook {
+                staker_addr: Addr::unchecked("addr2"),
+                staking_token: Addr::unchecked("new_staking"),
+                pair_addr: Addr::unchecked("new_pair"),
+                prev_balances,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // past the grace period the deprecated token is refused again
+    test_env.advance_seconds(100);
+    let err = test_env
+        .execute(
+            "staking",
+            bond_receive_msg("addr2", Uint128::from(50u128), None).unwrap(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::StakingTokenMigrated {
+            new_token: "new_staking".to_string(),
+        }
+    );
+}
@@ -0,0 +1,250 @@
+use crate::contract::{execute, instantiate, query};
+use cosmwasm_schema::serde::de::DeserializeOwned;
+use cosmwasm_std::testing::{
+    mock_dependencies_with_balance, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    coin, from_binary, to_binary, Addr, Coin, ContractResult, Env, OwnedDeps, Response,
+    SystemError, SystemResult, Uint128, WasmQuery,
+};
+use cw20::TokenInfoResponse;
+use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
+use oraiswap::error::ContractError;
+use oraiswap::staking::{
+    ExecuteMsg, InstantiateMsg, PoolInfoResponse, QueryMsg, RewardInfoResponse, RewardMsg,
+};
+use oraiswap::testing::staking::bond_receive_msg;
+use oraiswap::testing::ATOM_DENOM;
+
+pub const OWNER: &str = "owner";
+pub const REWARDER: &str = "rewarder";
+pub const REWARD_TOKEN: &str = "reward_token";
+
+/// ORAI 100 / ATOM 200, the weights most staking tests register their pool with
+pub fn default_rewards_per_sec() -> Vec<Asset> {
+    vec![
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            amount: 100u128.into(),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+            amount: 200u128.into(),
+        },
+    ]
+}
+
+/// the REWARD_TOKEN cw20 alone, for the pools funded through the reward hooks
+pub fn reward_token_rewards_per_sec() -> Vec<Asset> {
+    vec![Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked(REWARD_TOKEN),
+        },
+        amount: 100u128.into(),
+    }]
+}
+
+/// Answers the TokenInfo query of `staking_tokens` the way their cw20 contracts would, with the
+/// uppercased address as symbol, so `RegisterAsset` accepts them. Every other contract is missing
+pub fn mock_staking_tokens(querier: &mut MockQuerier, staking_tokens: &[&str]) {
+    let staking_tokens: Vec<String> = staking_tokens.iter().map(|t| t.to_string()).collect();
+    querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } if staking_tokens.contains(contract_addr) => {
+            SystemResult::Ok(ContractResult::Ok(
+                to_binary(&TokenInfoResponse {
+                    name: contract_addr.clone(),
+                    symbol: contract_addr.to_uppercase(),
+                    decimals: 6,
+                    total_supply: Uint128::zero(),
+                })
+                .unwrap(),
+            ))
+        }
+        WasmQuery::Smart { contract_addr, .. } => SystemResult::Err(SystemError::NoSuchContract {
+            addr: contract_addr.clone(),
+        }),
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "wasm".to_string(),
+        }),
+    });
+}
+
+/// Instantiated staking contract over mock dependencies, built by `StakingTestEnv::new()`
+pub struct StakingTestEnv {
+    pub deps: OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    pub env: Env,
+}
+
+/// Steps are replayed in the order they were added, so a bond added before a deposit
+/// is already counted in the pool when the reward is distributed
+pub struct StakingTestEnvBuilder {
+    steps: Vec<(String, ExecuteMsg)>,
+}
+
+impl StakingTestEnv {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> StakingTestEnvBuilder {
+        StakingTestEnvBuilder { steps: vec![] }
+    }
+
+    pub fn execute(&mut self, sender: &str, msg: ExecuteMsg) -> Result<Response, ContractError> {
+        execute(
+            self.deps.as_mut(),
+            self.env.clone(),
+            mock_info(sender, &[]),
+            msg,
+        )
+    }
+
+    pub fn execute_with_funds(
+        &mut self,
+        sender: &str,
+        funds: &[Coin],
+        msg: ExecuteMsg,
+    ) -> Result<Response, ContractError> {
+        execute(
+            self.deps.as_mut(),
+            self.env.clone(),
+            mock_info(sender, funds),
+            msg,
+        )
+    }
+
+    pub fn advance_seconds(&mut self, seconds: u64) {
+        self.env.block.time = self.env.block.time.plus_seconds(seconds);
+    }
+
+    pub fn query<T: DeserializeOwned>(&self, msg: QueryMsg) -> T {
+        from_binary(&query(self.deps.as_ref(), self.env.clone(), msg).unwrap()).unwrap()
+    }
+
+    pub fn pool_info(&self, staking_token: &str) -> PoolInfoResponse {
+        self.query(QueryMsg::PoolInfo {
+            staking_token: Addr::unchecked(staking_token),
+        })
+    }
+
+    pub fn reward_info(&self, staker: &str, staking_token: Option<&str>) -> RewardInfoResponse {
+        self.query(QueryMsg::RewardInfo {
+            staker_addr: Addr::unchecked(staker),
+            staking_token: staking_token.map(Addr::unchecked),
+        })
+    }
+
+    pub fn assert_bond(&self, staker: &str, staking_token: &str, amount: u128) {
+        let res = self.reward_info(staker, Some(staking_token));
+        let bond_amount = res
+            .reward_infos
+            .first()
+            .map_or(Uint128::zero(), |item| item.bond_amount);
+        assert_eq!(bond_amount, Uint128::from(amount));
+    }
+
+    /// pending_reward of the staker as the RewardInfo query reports it, zero when not bonded
+    pub fn assert_pending(&self, staker: &str, staking_token: &str, amount: u128) {
+        let res = self.reward_info(staker, Some(staking_token));
+        let pending_reward = res
+            .reward_infos
+            .first()
+            .map_or(Uint128::zero(), |item| item.pending_reward);
+        assert_eq!(pending_reward, Uint128::from(amount));
+    }
+}
+
+impl StakingTestEnvBuilder {
+    /// sets the reward weights of the pool and registers its staking token
+    pub fn with_pool(mut self, staking_token: &str, rewards_per_sec: Vec<Asset>) -> Self {
+        self.steps.push((
+            OWNER.to_string(),
+            ExecuteMsg::UpdateRewardsPerSec {
+                staking_token: Addr::unchecked(staking_token),
+                assets: rewards_per_sec,
+            },
+        ));
+        self.steps.push((
+            OWNER.to_string(),
+            ExecuteMsg::RegisterAsset {
+                staking_token: Addr::unchecked(staking_token),
+                min_bond_amount: None,
+            },
+        ));
+        self
+    }
+
+    pub fn with_unbonding_period(mut self, staking_token: &str, unbonding_period: u64) -> Self {
+        self.steps.push((
+            OWNER.to_string(),
+            ExecuteMsg::UpdateUnbondingPeriod {
+                staking_token: Addr::unchecked(staking_token),
+                unbonding_period,
+            },
+        ));
+        self
+    }
+
+    pub fn with_bond(mut self, staker: &str, staking_token: &str, amount: u128) -> Self {
+        self.steps.push((
+            staking_token.to_string(),
+            bond_receive_msg(staker, Uint128::from(amount), None).unwrap(),
+        ));
+        self
+    }
+
+    pub fn with_reward_deposit(mut self, staking_token: &str, amount: u128) -> Self {
+        self.steps.push((
+            REWARDER.to_string(),
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked(staking_token),
+                    total_accumulation_amount: Uint128::from(amount),
+                }],
+            },
+        ));
+        self
+    }
+
+    pub fn build(self) -> StakingTestEnv {
+        let mut test_env = StakingTestEnv {
+            deps: mock_dependencies_with_balance(&[
+                coin(10000000000u128, ORAI_DENOM),
+                coin(20000000000u128, ATOM_DENOM),
+            ]),
+            env: mock_env(),
+        };
+        let staking_tokens: Vec<&str> = self
+            .steps
+            .iter()
+            .filter_map(|(_, msg)| match msg {
+                ExecuteMsg::RegisterAsset { staking_token, .. } => Some(staking_token.as_str()),
+                _ => None,
+            })
+            .collect();
+        mock_staking_tokens(&mut test_env.deps.querier, &staking_tokens);
+
+        let msg = InstantiateMsg {
+            owner: Some(Addr::unchecked(OWNER)),
+            rewarder: Addr::unchecked(REWARDER),
+            minter: Some(Addr::unchecked("mint")),
+            oracle_addr: Addr::unchecked("oracle"),
+            factory_addr: Addr::unchecked("factory"),
+            base_denom: None,
+        };
+        instantiate(
+            test_env.deps.as_mut(),
+            test_env.env.clone(),
+            mock_info("addr", &[]),
+            msg,
+        )
+        .unwrap();
+
+        for (sender, msg) in self.steps {
+            test_env.execute(&sender, msg).unwrap();
+        }
+
+        test_env
+    }
+}
@@ -0,0 +1,295 @@
+use cosmwasm_std::{coin, to_binary, Addr, Coin, Uint128};
+use cw20::Cw20ExecuteMsg;
+use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
+use oraiswap::create_entry_points_testing;
+use oraiswap::pair::PairResponse;
+use oraiswap::querier::query_token_balance;
+use oraiswap::staking::{ExecuteMsg, InstantiateMsg, RewardMsg, StakingContract, UnbondSendMsg};
+use oraiswap::testing::MockApp;
+
+// deploys token, oracle, factory, pair and staking, then walks the full staking flow
+#[test]
+fn test_staking_flow() {
+    let mut app = MockApp::new(&[(&"addr".to_string(), &[coin(10000000000u128, ORAI_DENOM)])]);
+
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_factory_and_pair_contract(
+        Box::new(
+            create_entry_points_testing!(oraiswap_factory)
+                .with_reply(oraiswap_factory::contract::reply),
+        ),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair).with_reply(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let asset_addr = app.create_token("asset");
+    app.set_token_balances(&[(
+        &"asset".to_string(),
+        &[(&"addr".to_string(), &Uint128::from(10000000000u128))],
+    )]);
+
+    // create pair
+    let pair_addr = app
+        .create_pair([
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: asset_addr.clone(),
+            },
+        ])
+        .unwrap();
+    let PairResponse { info: pair_info } = app
+        .query(pair_addr.clone(), &oraiswap::pair::QueryMsg::Pair {})
+        .unwrap();
+    let lp_token = pair_info.liquidity_token.clone();
+
+    // provide liquidity
+    app.execute(
+        Addr::unchecked("addr"),
+        asset_addr.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: pair_addr.to_string(),
+            amount: Uint128::from(1000u128),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked("addr"),
+        pair_addr.clone(),
+        &oraiswap::pair::ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: asset_addr.clone(),
+                    },
+                    amount: Uint128::from(1000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+        },
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    )
+    .unwrap();
+    let lp_balance =
+        query_token_balance(&app.as_querier(), lp_token.clone(), Addr::unchecked("addr")).unwrap();
+    assert!(lp_balance > Uint128::from(100u128));
+
+    // the staking contract holds the orai rewards
+    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+    let staking_addr = app
+        .instantiate(
+            code_id,
+            Addr::unchecked("addr"),
+            &InstantiateMsg {
+                owner: Some(Addr::unchecked("owner")),
+                rewarder: Addr::unchecked("rewarder"),
+                minter: None,
+                oracle_addr: app.oracle_addr.clone(),
+                factory_addr: app.factory_addr.clone(),
+                base_denom: None,
+            },
+            &[coin(1000u128, ORAI_DENOM)],
+            "staking",
+        )
+        .unwrap();
+
+    let staking = StakingContract(staking_addr.clone());
+
+    // register asset
+    app.execute(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::RegisterAsset {
+            staking_token: lp_token.clone(),
+            min_bond_amount: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::UpdateRewardsPerSec {
+            staking_token: lp_token.clone(),
+            assets: vec![Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(100u128),
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // bond lp
+    app.execute_msg(
+        Addr::unchecked("addr"),
+        staking
+            .bond_msg(&lp_token, Uint128::from(100u128), None)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        query_token_balance(&app.as_querier(), lp_token.clone(), staking_addr.clone()).unwrap(),
+        Uint128::from(100u128)
+    );
+    assert_eq!(
+        query_token_balance(&app.as_querier(), lp_token.clone(), Addr::unchecked("addr")).unwrap(),
+        lp_balance - Uint128::from(100u128)
+    );
+
+    // deposit reward
+    app.execute_msg(
+        Addr::unchecked("rewarder"),
+        staking
+            .deposit_reward_msg(vec![RewardMsg {
+                staking_token: lp_token.clone(),
+                total_accumulation_amount: Uint128::from(300u128),
+            }])
+            .unwrap(),
+    )
+    .unwrap();
+
+    // withdraw
+    let orai_balance = app
+        .query_balance(Addr::unchecked("addr"), ORAI_DENOM.to_string())
+        .unwrap();
+    app.execute_msg(Addr::unchecked("addr"), staking.withdraw_msg(None).unwrap())
+        .unwrap();
+    assert_eq!(
+        app.query_balance(Addr::unchecked("addr"), ORAI_DENOM.to_string())
+            .unwrap(),
+        orai_balance + Uint128::from(300u128)
+    );
+    assert_eq!(
+        app.query_balance(staking_addr.clone(), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(700u128)
+    );
+
+    // a downstream contract that fails reverts the whole unbond
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &ExecuteMsg::Unbond {
+            staking_token: lp_token.clone(),
+            amount: Some(Uint128::from(100u128)),
+            withdraw_rewards: None,
+            recipient: None,
+            unbond_msg: Some(UnbondSendMsg {
+                contract: pair_addr.clone(),
+                msg: to_binary(&"not a pair hook").unwrap(),
+            }),
+        },
+        &[],
+    );
+    assert!(res.is_err());
+    assert_eq!(
+        query_token_balance(&app.as_querier(), lp_token.clone(), staking_addr.clone()).unwrap(),
+        Uint128::from(100u128)
+    );
+    let res = staking
+        .query_reward_info(
+            &app.as_querier(),
+            &Addr::unchecked("addr"),
+            Some(lp_token.clone()),
+        )
+        .unwrap();
+    assert_eq!(res.reward_infos[0].bond_amount, Uint128::from(100u128));
+
+    // unbond
+    app.execute_msg(
+        Addr::unchecked("addr"),
+        staking
+            .unbond_msg(&lp_token, Some(Uint128::from(100u128)))
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        query_token_balance(&app.as_querier(), lp_token.clone(), Addr::unchecked("addr")).unwrap(),
+        lp_balance
+    );
+    assert_eq!(
+        query_token_balance(&app.as_querier(), lp_token.clone(), staking_addr.clone()).unwrap(),
+        Uint128::zero()
+    );
+
+    // auto stake provides liquidity through the pair and bonds the minted lp for the sender
+    app.execute(
+        Addr::unchecked("addr"),
+        asset_addr.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: staking_addr.to_string(),
+            amount: Uint128::from(100u128),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &ExecuteMsg::AutoStake {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(100u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: asset_addr.clone(),
+                    },
+                    amount: Uint128::from(100u128),
+                },
+            ],
+            slippage_tolerance: None,
+            minimum_lp_amount: None,
+        },
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    )
+    .unwrap();
+
+    let staked =
+        query_token_balance(&app.as_querier(), lp_token.clone(), staking_addr.clone()).unwrap();
+    assert!(!staked.is_zero());
+    // the minted lp is not sent to the staker
+    assert_eq!(
+        query_token_balance(&app.as_querier(), lp_token.clone(), Addr::unchecked("addr")).unwrap(),
+        lp_balance
+    );
+
+    let res = staking
+        .query_reward_info(
+            &app.as_querier(),
+            &Addr::unchecked("addr"),
+            Some(lp_token.clone()),
+        )
+        .unwrap();
+    assert_eq!(res.reward_infos[0].bond_amount, staked);
+
+    let pool_info = staking
+        .query_pool_info(&app.as_querier(), &lp_token)
+        .unwrap();
+    assert_eq!(pool_info.total_bond_amount, staked);
+}
@@ -0,0 +1,485 @@
+use crate::contract::{execute, instantiate, query};
+use crate::testing::env::mock_staking_tokens;
+use cosmwasm_schema::serde::de::DeserializeOwned;
+use cosmwasm_std::testing::{
+    mock_dependencies_with_balance, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    coin, from_binary, to_binary, Addr, BankMsg, CosmosMsg, OwnedDeps, Response, Uint128, WasmMsg,
+};
+use cw20::Cw20ReceiveMsg;
+use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
+use oraiswap::staking::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolInfoResponse, QueryMsg, RewardInfoResponse,
+    RewardMsg, SimulateWithdrawResponse,
+};
+
+const POOLS: [&str; 2] = ["staking1", "staking2"];
+// what a pool moves to when it is deprecated
+const NEW_POOLS: [&str; 2] = ["new_staking1", "new_staking2"];
+const STAKERS: [&str; 3] = ["addr1", "addr2", "addr3"];
+
+// cargo test --features=property-tests runs the full generator budget
+#[cfg(feature = "property-tests")]
+const CASES: u64 = 2000;
+#[cfg(not(feature = "property-tests"))]
+const CASES: u64 = 30;
+
+#[cfg(feature = "property-tests")]
+const MAX_OPS: u64 = 80;
+#[cfg(not(feature = "property-tests"))]
+const MAX_OPS: u64 = 25;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Bond {
+        staker: usize,
+        pool: usize,
+        amount: u128,
+    },
+    Unbond {
+        staker: usize,
+        pool: usize,
+        amount: u128,
+    },
+    Deposit {
+        pool: usize,
+        amount: u128,
+    },
+    Withdraw {
+        staker: usize,
+    },
+    Deprecate {
+        pool: usize,
+    },
+}
+
+impl Op {
+    fn halve(&self) -> Option<Op> {
+        let mut op = self.clone();
+        match &mut op {
+            Op::Bond { amount, .. } | Op::Unbond { amount, .. } | Op::Deposit { amount, .. }
+                if *amount > 1 =>
+            {
+                *amount /= 2;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+}
+
+// xorshift, a failing seed always replays the same sequence
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+}
+
+fn generate(seed: u64) -> Vec<Op> {
+    let mut rng = Rng(seed.wrapping_mul(0x9E3779B97F4A7C15) | 1);
+    let len = 1 + rng.next(MAX_OPS);
+    (0..len)
+        .map(|_| {
+            let staker = rng.next(STAKERS.len() as u64) as usize;
+            let pool = rng.next(POOLS.len() as u64) as usize;
+            let amount = 1 + rng.next(1000) as u128;
+            match rng.next(13) {
+                0..=2 => Op::Bond {
+                    staker,
+                    pool,
+                    amount,
+                },
+                3..=5 => Op::Unbond {
+                    staker,
+                    pool,
+                    amount,
+                },
+                6..=8 => Op::Deposit { pool, amount },
+                9..=11 => Op::Withdraw { staker },
+                _ => Op::Deprecate { pool },
+            }
+        })
+        .collect()
+}
+
+fn sent_amount(res: &Response) -> u128 {
+    res.messages
+        .iter()
+        .map(|msg| match &msg.msg {
+            CosmosMsg::Bank(BankMsg::Send { amount, .. }) => {
+                amount.iter().map(|c| c.amount.u128()).sum()
+            }
+            _ => 0,
+        })
+        .sum()
+}
+
+fn query_as<T: DeserializeOwned>(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    msg: QueryMsg,
+) -> Result<T, String> {
+    let res = query(deps.as_ref(), mock_env(), msg).map_err(|err| err.to_string())?;
+    from_binary(&res).map_err(|err| err.to_string())
+}
+
+fn setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies_with_balance(&[coin(10u128.pow(18), ORAI_DENOM)]);
+    mock_staking_tokens(&mut deps.querier, &[POOLS, NEW_POOLS].concat());
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: None,
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+
+    for pool in POOLS {
+        let msg = ExecuteMsg::UpdateRewardsPerSec {
+            staking_token: Addr::unchecked(pool),
+            assets: vec![Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: 100u128.into(),
+            }],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::RegisterAsset {
+            staking_token: Addr::unchecked(pool),
+            min_bond_amount: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    }
+
+    deps
+}
+
+// the reward a deprecated position can still withdraw, pending and settled
+fn deprecated_entitlement(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    staker: &str,
+    pool: &str,
+) -> Result<(u128, u128), String> {
+    let res: RewardInfoResponse = query_as(
+        deps,
+        QueryMsg::RewardInfo {
+            staker_addr: Addr::unchecked(staker),
+            staking_token: Some(Addr::unchecked(pool)),
+        },
+    )?;
+    Ok(res
+        .reward_infos
+        .iter()
+        .fold((0, 0), |(bond, reward), item| {
+            (
+                bond + item.bond_amount.u128(),
+                reward
+                    + item.pending_reward.u128()
+                    + item
+                        .pending_withdraw
+                        .iter()
+                        .map(|pw| pw.amount.u128())
+                        .sum::<u128>(),
+            )
+        }))
+}
+
+// runs the ops against a fresh contract, checking the invariants after every step
+fn run(ops: &[Op]) -> Result<(), String> {
+    let mut deps = setup();
+    let mut bonds = [[0u128; POOLS.len()]; STAKERS.len()];
+    // bonds left on the old token of a deprecated pool
+    let mut deprecated_bonds = [[0u128; POOLS.len()]; STAKERS.len()];
+    let mut deprecated = [false; POOLS.len()];
+    // a deprecated position never gains rewards, it only loses what is withdrawn
+    let mut entitlements = [[0u128; POOLS.len()]; STAKERS.len()];
+    let mut deposited = 0u128;
+    let mut withdrawn = 0u128;
+    let token = |deprecated: &[bool], pool: usize| {
+        if deprecated[pool] {
+            NEW_POOLS[pool]
+        } else {
+            POOLS[pool]
+        }
+    };
+
+    for (step, op) in ops.iter().enumerate() {
+        let res = match op {
+            Op::Bond {
+                staker,
+                pool,
+                amount,
+            } => {
+                bonds[*staker][*pool] += amount;
+                let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                    sender: STAKERS[*staker].to_string(),
+                    amount: Uint128::from(*amount),
+                    msg: to_binary(&Cw20HookMsg::Bond {
+                        lock_duration: None,
+                        referrer: None,
+                    })
+                    .unwrap(),
+                });
+                let sender = token(&deprecated, *pool);
+                execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+            }
+            Op::Unbond {
+                staker,
+                pool,
+                amount,
+            } => {
+                // the old position goes first, it can only be closed
+                let (staking_token, bonded) = match deprecated_bonds[*staker][*pool] {
+                    0 => (token(&deprecated, *pool), &mut bonds[*staker][*pool]),
+                    _ => (POOLS[*pool], &mut deprecated_bonds[*staker][*pool]),
+                };
+                // shrinking can drop the bond an unbond relied on, only unbond what is there
+                let amount = (*amount).min(*bonded);
+                if amount == 0 {
+                    continue;
+                }
+                *bonded -= amount;
+                let msg = ExecuteMsg::Unbond {
+                    staking_token: Addr::unchecked(staking_token),
+                    amount: Some(Uint128::from(amount)),
+                    withdraw_rewards: None,
+                    recipient: None,
+                    unbond_msg: None,
+                };
+                let res = execute(
+                    deps.as_mut(),
+                    mock_env(),
+                    mock_info(STAKERS[*staker], &[]),
+                    msg,
+                );
+                // the lp token returned is the one the position bonded
+                if let Ok(res) = &res {
+                    let returned = res.messages.iter().find_map(|msg| match &msg.msg {
+                        CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => {
+                            Some(contract_addr.as_str())
+                        }
+                        _ => None,
+                    });
+                    if returned != Some(staking_token) {
+                        return Err(format!(
+                            "step {} {:?}: returned {:?} instead of {}",
+                            step, op, returned, staking_token
+                        ));
+                    }
+                }
+                res
+            }
+            Op::Deposit { pool, amount } => {
+                deposited += amount;
+                let msg = ExecuteMsg::DepositReward {
+                    rewards: vec![RewardMsg {
+                        staking_token: Addr::unchecked(token(&deprecated, *pool)),
+                        total_accumulation_amount: Uint128::from(*amount),
+                    }],
+                };
+                execute(deps.as_mut(), mock_env(), mock_info("rewarder", &[]), msg)
+            }
+            Op::Withdraw { staker } => {
+                let msg = ExecuteMsg::Withdraw {
+                    staking_token: None,
+                    ibc_recipient: None,
+                    amounts: None,
+                    recipient: None,
+                    staker_addr: None,
+                };
+                execute(
+                    deps.as_mut(),
+                    mock_env(),
+                    mock_info(STAKERS[*staker], &[]),
+                    msg,
+                )
+            }
+            Op::Deprecate { pool } => {
+                if deprecated[*pool] {
+                    continue;
+                }
+                deprecated[*pool] = true;
+                for staker in 0..STAKERS.len() {
+                    deprecated_bonds[staker][*pool] = bonds[staker][*pool];
+                    bonds[staker][*pool] = 0;
+                }
+                let msg = ExecuteMsg::DeprecateStakingToken {
+                    staking_token: Addr::unchecked(POOLS[*pool]),
+                    new_staking_token: Addr::unchecked(NEW_POOLS[*pool]),
+                    grace_period: None,
+                };
+                let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg);
+                for (staker_idx, staker) in STAKERS.iter().enumerate() {
+                    entitlements[staker_idx][*pool] =
+                        deprecated_entitlement(&deps, staker, POOLS[*pool])?.1;
+                }
+                res
+            }
+        }
+        .map_err(|err| format!("step {} {:?} failed: {}", step, op, err))?;
+        withdrawn += sent_amount(&res);
+
+        let mut pending = 0u128;
+        for pool_idx in 0..POOLS.len() {
+            let pool = token(&deprecated, pool_idx);
+            let pool_info: PoolInfoResponse = query_as(
+                &deps,
+                QueryMsg::PoolInfo {
+                    staking_token: Addr::unchecked(pool),
+                },
+            )?;
+            let total_bond: u128 = bonds.iter().map(|staker| staker[pool_idx]).sum();
+            if pool_info.total_bond_amount.u128() != total_bond {
+                return Err(format!(
+                    "step {}: total bond of {} is {}, stakers bonded {}",
+                    step, pool, pool_info.total_bond_amount, total_bond
+                ));
+            }
+            let deprecated_total: u128 = deprecated_bonds.iter().map(|s| s[pool_idx]).sum();
+            let reported = pool_info
+                .migration_deprecated_total_bond
+                .map_or(0, |total| total.u128());
+            if reported != deprecated_total {
+                return Err(format!(
+                    "step {}: deprecated bond of {} is {}, stakers left {}",
+                    step, pool, reported, deprecated_total
+                ));
+            }
+            pending += pool_info.pending_reward.u128();
+        }
+
+        let mut claimable = 0u128;
+        for (staker_idx, staker) in STAKERS.iter().enumerate() {
+            for pool_idx in 0..POOLS.len() {
+                let pool = token(&deprecated, pool_idx);
+                let res: RewardInfoResponse = query_as(
+                    &deps,
+                    QueryMsg::RewardInfo {
+                        staker_addr: Addr::unchecked(*staker),
+                        staking_token: Some(Addr::unchecked(pool)),
+                    },
+                )?;
+                let bond: u128 = res.reward_infos.iter().map(|r| r.bond_amount.u128()).sum();
+                if bond != bonds[staker_idx][pool_idx] {
+                    return Err(format!(
+                        "step {}: {} has {} bonded in {}, expected {}",
+                        step, staker, bond, pool, bonds[staker_idx][pool_idx]
+                    ));
+                }
+
+                if !deprecated[pool_idx] {
+                    continue;
+                }
+                let (bond, entitlement) = deprecated_entitlement(&deps, staker, POOLS[pool_idx])?;
+                if bond != deprecated_bonds[staker_idx][pool_idx] {
+                    return Err(format!(
+                        "step {}: {} has {} bonded in deprecated {}, expected {}",
+                        step, staker, bond, POOLS[pool_idx], deprecated_bonds[staker_idx][pool_idx]
+                    ));
+                }
+                if entitlement > entitlements[staker_idx][pool_idx] {
+                    return Err(format!(
+                        "step {}: deprecated position of {} in {} grew from {} to {}",
+                        step,
+                        staker,
+                        POOLS[pool_idx],
+                        entitlements[staker_idx][pool_idx],
+                        entitlement
+                    ));
+                }
+                entitlements[staker_idx][pool_idx] = entitlement;
+            }
+
+            let res: SimulateWithdrawResponse = query_as(
+                &deps,
+                QueryMsg::SimulateWithdraw {
+                    staker_addr: Addr::unchecked(*staker),
+                    staking_token: None,
+                },
+            )?;
+            claimable += res
+                .reward_assets
+                .iter()
+                .map(|ra| ra.amount.u128())
+                .sum::<u128>();
+        }
+
+        // every deposit is either paid out, claimable or pending in its pool
+        let accounted = withdrawn + claimable + pending;
+        if accounted > deposited {
+            return Err(format!(
+                "step {}: withdrawn {} + claimable {} + pending {} exceed deposits {}",
+                step, withdrawn, claimable, pending, deposited
+            ));
+        }
+        // each settlement floors at most one unit per position, a staker has two per pool
+        // once it is deprecated
+        let dust_bound = ((step + 1) * STAKERS.len() * POOLS.len() * 4) as u128;
+        if deposited - accounted > dust_bound {
+            return Err(format!(
+                "step {}: {} of the deposits {} are unaccounted",
+                step,
+                deposited - accounted,
+                deposited
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// drop ops and halve amounts while the sequence still fails
+fn shrink(mut ops: Vec<Op>) -> Vec<Op> {
+    loop {
+        let candidates = (0..ops.len())
+            .map(|i| {
+                let mut candidate = ops.clone();
+                candidate.remove(i);
+                candidate
+            })
+            .chain((0..ops.len()).filter_map(|i| {
+                ops[i].halve().map(|op| {
+                    let mut candidate = ops.clone();
+                    candidate[i] = op;
+                    candidate
+                })
+            }))
+            .collect::<Vec<Vec<Op>>>();
+
+        match candidates
+            .into_iter()
+            .find(|candidate| run(candidate).is_err())
+        {
+            Some(candidate) => ops = candidate,
+            None => return ops,
+        }
+    }
+}
+
+// deprecations run in the same sequences, so rewards are checked across them too
+#[test]
+fn test_reward_index_conservation() {
+    for seed in 0..CASES {
+        let ops = generate(seed);
+        if let Err(err) = run(&ops) {
+            let minimal = shrink(ops);
+            panic!(
+                "seed {} failed: {}\nminimal sequence {:?} fails with: {}",
+                seed,
+                err,
+                minimal,
+                run(&minimal).unwrap_err()
+            );
+        }
+    }
+}
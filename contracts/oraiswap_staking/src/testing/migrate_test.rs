@@ -1,15 +1,26 @@
-use crate::contract::{instantiate, validate_migrate_store_status};
-use crate::state::{rewards_read, rewards_store, RewardInfo};
+use crate::contract::{
+    execute as contract_execute, instantiate, migrate, query, validate_migrate_store_status,
+};
+use crate::legacy::v2::migrate_store;
+use crate::state::{
+    read_config, read_is_migrated, read_pool_info, read_rewards_per_sec, rewards_read,
+    rewards_store, stakers_read, RewardInfo,
+};
+use crate::testing::env::{default_rewards_per_sec, mock_staking_tokens, StakingTestEnv};
 
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{
+    attr, from_binary, Addr, Api, Binary, Decimal, Decimal256, DepsMut, Storage, Uint128,
+};
+use cosmwasm_storage::to_length_prefixed_nested;
+use cw2::{get_contract_version, set_contract_version};
 use cw20::Cw20ReceiveMsg;
+use oraiswap::asset::{Asset, AssetInfo, AssetInfoRaw, AssetRaw};
 use oraiswap::error::ContractError;
-
-use crate::contract::execute as contract_execute;
-use cosmwasm_std::{Addr, Decimal, Uint128};
-use cosmwasm_std::{Binary, StdError};
-use oraiswap::asset::{Asset, AssetInfo, AssetRaw};
-use oraiswap::staking::{ExecuteMsg, InstantiateMsg};
+use oraiswap::staking::{
+    ContractInfoResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, MigrationProgressResponse,
+    QueryMsg, RewardInfoResponse, StakingTokenDeprecation,
+};
 
 #[test]
 fn test_rewards_store_with_pending_withdraw() {
@@ -22,7 +33,7 @@ fn test_rewards_store_with_pending_withdraw() {
             asset_key.as_bytes(),
             &RewardInfo {
                 native_token: false,
-                index: Decimal::zero(),
+                index: Decimal256::zero(),
                 bond_amount: Uint128::zero(),
                 pending_reward: Uint128::zero(),
                 pending_withdraw: vec![
@@ -39,6 +50,12 @@ fn test_rewards_store_with_pending_withdraw() {
                         amount: Uint128::zero(),
                     },
                 ],
+                locks: vec![],
+                proxy_indices: vec![],
+                total_claimed: Uint128::zero(),
+                boost_shortfall: Uint128::zero(),
+                last_updated: 0,
+                last_bond_time: 0,
             },
         )
         .unwrap();
@@ -80,17 +97,196 @@ fn test_validate_migrate_store_status() {
         owner.clone(),
         ExecuteMsg::UpdateConfig {
             rewarder: None,
-            owner: None,
             migrate_store_status: Some(false),
+            penalty_collector: None,
+            reward_distributors: None,
+            withdraw_fee_rate: None,
+            fee_collector: None,
+            oracle_addr: None,
+            factory_addr: None,
+            base_denom: None,
+            community_pool: None,
         },
     )
     .unwrap();
     // assert
     assert_eq!(
         validate_migrate_store_status(deps.as_mut().storage),
-        Err(StdError::generic_err(
-            ContractError::ContractUpgrade {}.to_string()
-        ))
+        Err(ContractError::ContractUpgrade {})
+    );
+}
+
+#[test]
+fn test_migrate_checks_contract_version() {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        owner: None,
+        rewarder: Addr::unchecked("rewarder"),
+        minter: None,
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let version = get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.contract, "crates.io:oraiswap_staking");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // the store already is at the running version, staking stays open
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { deprecations: None }).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("skipped", "already_migrated")
+        ]
+    );
+    assert_eq!(validate_migrate_store_status(deps.as_mut().storage), Ok(()));
+
+    let expected = format!("crates.io:oraiswap_staking@{}", env!("CARGO_PKG_VERSION"));
+    set_contract_version(deps.as_mut().storage, "crates.io:oraiswap_pair", "0.1.0").unwrap();
+    assert_eq!(
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { deprecations: None }),
+        Err(ContractError::ContractVersionMismatch {
+            stored: "crates.io:oraiswap_pair@0.1.0".to_string(),
+            expected: expected.clone(),
+        })
+    );
+    set_contract_version(
+        deps.as_mut().storage,
+        "crates.io:oraiswap_staking",
+        "99.0.0",
+    )
+    .unwrap();
+    assert_eq!(
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { deprecations: None }),
+        Err(ContractError::ContractVersionMismatch {
+            stored: "crates.io:oraiswap_staking@99.0.0".to_string(),
+            expected,
+        })
+    );
+
+    // an older version is migrated and written over, its layout needs no MigrateStore
+    set_contract_version(deps.as_mut().storage, "crates.io:oraiswap_staking", "0.0.1").unwrap();
+    migrate(deps.as_mut(), mock_env(), MigrateMsg { deprecations: None }).unwrap();
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        env!("CARGO_PKG_VERSION")
+    );
+    assert_eq!(validate_migrate_store_status(deps.as_mut().storage), Ok(()));
+}
+
+#[test]
+fn test_migrate_unversioned_store() {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        owner: None,
+        rewarder: Addr::unchecked("rewarder"),
+        minter: None,
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    // stores written before the version was kept
+    deps.storage.remove(b"contract_info");
+    assert!(query(deps.as_ref(), mock_env(), QueryMsg::ContractInfo {}).is_err());
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { deprecations: None }).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("version", env!("CARGO_PKG_VERSION"))
+        ]
+    );
+    let res: ContractInfoResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ContractInfo {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        ContractInfoResponse {
+            name: "crates.io:oraiswap_staking".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    );
+    // the legacy layout still has to be moved by MigrateStore
+    assert_eq!(
+        validate_migrate_store_status(deps.as_mut().storage),
+        Err(ContractError::ContractUpgrade {})
+    );
+}
+
+#[test]
+fn test_migrate_with_deprecations() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "new_staking"]);
+    let deprecations = || {
+        Some(vec![StakingTokenDeprecation {
+            staking_token: Addr::unchecked("staking"),
+            new_staking_token: Addr::unchecked("new_staking"),
+        }])
+    };
+
+    let too_many = (0..11)
+        .map(|_| StakingTokenDeprecation {
+            staking_token: Addr::unchecked("staking"),
+            new_staking_token: Addr::unchecked("new_staking"),
+        })
+        .collect();
+    assert_eq!(
+        migrate(
+            test_env.deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                deprecations: Some(too_many)
+            }
+        ),
+        Err(ContractError::TooManyDeprecations { max: 10 })
+    );
+
+    let res = migrate(
+        test_env.deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            deprecations: deprecations(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("action", "deprecate_staking_token"),
+            attr("staking_token", "staking"),
+            attr("deprecated_staking_token", "staking"),
+            attr("new_staking_token", "new_staking"),
+            attr("skipped", "already_migrated"),
+        ]
+    );
+    assert_eq!(
+        test_env.pool_info("new_staking").symbol,
+        Some("NEW_STAKING".to_string())
+    );
+
+    // the same migration run again leaves the moved pool alone
+    let res = migrate(
+        test_env.deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            deprecations: deprecations(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("skipped_deprecation", "staking"),
+            attr("skipped", "already_migrated"),
+        ]
     );
 }
 
@@ -116,8 +312,15 @@ fn test_validate_migrate_store_status_with_execute_msg() {
         owner.clone(),
         ExecuteMsg::UpdateConfig {
             rewarder: None,
-            owner: None,
             migrate_store_status: Some(false),
+            penalty_collector: None,
+            reward_distributors: None,
+            withdraw_fee_rate: None,
+            fee_collector: None,
+            oracle_addr: None,
+            factory_addr: None,
+            base_denom: None,
+            community_pool: None,
         },
     )
     .unwrap();
@@ -129,9 +332,7 @@ fn test_validate_migrate_store_status_with_execute_msg() {
             owner.clone(),
             ExecuteMsg::DepositReward { rewards: vec![] }
         ),
-        Err(StdError::generic_err(
-            ContractError::ContractUpgrade {}.to_string()
-        ))
+        Err(ContractError::ContractUpgrade {})
     );
     assert_eq!(
         contract_execute(
@@ -153,12 +354,11 @@ fn test_validate_migrate_store_status_with_execute_msg() {
                         }
                     }
                 ],
-                slippage_tolerance: None
+                slippage_tolerance: None,
+                minimum_lp_amount: None
             }
         ),
-        Err(StdError::generic_err(
-            ContractError::ContractUpgrade {}.to_string()
-        ))
+        Err(ContractError::ContractUpgrade {})
     );
     assert_eq!(
         contract_execute(
@@ -168,12 +368,11 @@ fn test_validate_migrate_store_status_with_execute_msg() {
             ExecuteMsg::AutoStakeHook {
                 staking_token: empty_addr.clone(),
                 staker_addr: empty_addr.clone(),
-                prev_staking_token_amount: Uint128::zero()
+                prev_staking_token_amount: Uint128::zero(),
+                minimum_lp_amount: None
             }
         ),
-        Err(StdError::generic_err(
-            ContractError::ContractUpgrade {}.to_string()
-        ))
+        Err(ContractError::ContractUpgrade {})
     );
     assert_eq!(
         contract_execute(
@@ -182,12 +381,11 @@ fn test_validate_migrate_store_status_with_execute_msg() {
             owner.clone(),
             ExecuteMsg::DeprecateStakingToken {
                 staking_token: empty_addr.clone(),
-                new_staking_token: empty_addr.clone()
+                new_staking_token: empty_addr.clone(),
+                grace_period: None,
             }
         ),
-        Err(StdError::generic_err(
-            ContractError::ContractUpgrade {}.to_string()
-        ))
+        Err(ContractError::ContractUpgrade {})
     );
     assert_eq!(
         contract_execute(
@@ -200,9 +398,7 @@ fn test_validate_migrate_store_status_with_execute_msg() {
                 msg: Binary::default()
             })
         ),
-        Err(StdError::generic_err(
-            ContractError::ContractUpgrade {}.to_string()
-        ))
+        Err(ContractError::ContractUpgrade {})
     );
     assert_eq!(
         contract_execute(
@@ -210,12 +406,11 @@ fn test_validate_migrate_store_status_with_execute_msg() {
             mock_env(),
             owner.clone(),
             ExecuteMsg::RegisterAsset {
-                staking_token: empty_addr.clone()
+                staking_token: empty_addr.clone(),
+                min_bond_amount: None,
             }
         ),
-        Err(StdError::generic_err(
-            ContractError::ContractUpgrade {}.to_string()
-        ))
+        Err(ContractError::ContractUpgrade {})
     );
     assert_eq!(
         contract_execute(
@@ -224,12 +419,13 @@ fn test_validate_migrate_store_status_with_execute_msg() {
             owner.clone(),
             ExecuteMsg::Unbond {
                 staking_token: empty_addr.clone(),
-                amount: Uint128::zero()
+                amount: Some(Uint128::zero()),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
             }
         ),
-        Err(StdError::generic_err(
-            ContractError::ContractUpgrade {}.to_string()
-        ))
+        Err(ContractError::ContractUpgrade {})
     );
     assert_eq!(
         contract_execute(
@@ -241,9 +437,7 @@ fn test_validate_migrate_store_status_with_execute_msg() {
                 assets: vec![]
             }
         ),
-        Err(StdError::generic_err(
-            ContractError::ContractUpgrade {}.to_string()
-        ))
+        Err(ContractError::ContractUpgrade {})
     );
     assert_eq!(
         contract_execute(
@@ -251,12 +445,14 @@ fn test_validate_migrate_store_status_with_execute_msg() {
             mock_env(),
             owner.clone(),
             ExecuteMsg::Withdraw {
-                staking_token: None
+                staking_token: None,
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
             }
         ),
-        Err(StdError::generic_err(
-            ContractError::ContractUpgrade {}.to_string()
-        ))
+        Err(ContractError::ContractUpgrade {})
     );
     assert_eq!(
         contract_execute(
@@ -268,12 +464,269 @@ fn test_validate_migrate_store_status_with_execute_msg() {
                 staker_addrs: vec![]
             }
         ),
-        Err(StdError::generic_err(
-            ContractError::ContractUpgrade {}.to_string()
-        ))
+        Err(ContractError::ContractUpgrade {})
     );
 }
 
+#[test]
+fn test_migrate_step_resumes() {
+    let mut deps = mock_dependencies();
+    load_fixture(deps.as_mut(), V1_FIXTURE);
+    let staking_canon = deps.api.addr_canonicalize("staking").unwrap();
+    let staker_canon = deps.api.addr_canonicalize("staker").unwrap();
+    let step = |deps: DepsMut, limit: u32| {
+        contract_execute(
+            deps,
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::MigrateStep { limit: Some(limit) },
+        )
+    };
+    let progress = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> MigrationProgressResponse {
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::MigrationProgress {}).unwrap())
+            .unwrap()
+    };
+
+    // nothing to move before the migrate
+    assert_eq!(step(deps.as_mut(), 10), Err(ContractError::NotMigrating {}));
+    migrate(deps.as_mut(), mock_env(), MigrateMsg { deprecations: None }).unwrap();
+    assert_eq!(
+        progress(&deps),
+        MigrationProgressResponse {
+            migrating: true,
+            migrated_pools: 0,
+            migrated_stakers: 0,
+            legacy_pools: 1,
+        }
+    );
+
+    // the pool head first, its staker on the next call
+    let res = step(deps.as_mut(), 1).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_step"),
+            attr("migrated_pools", "1"),
+            attr("migrated_stakers", "0"),
+            attr("finished", "false"),
+        ]
+    );
+    assert!(read_pool_info(deps.as_ref().storage, &staking_canon).is_ok());
+    assert!(rewards_read(deps.as_ref().storage, &staker_canon)
+        .load(&staking_canon)
+        .is_err());
+    // other handlers wait for the migration
+    assert_eq!(
+        contract_execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::Pause {}
+        ),
+        Err(ContractError::ContractUpgrade {})
+    );
+
+    step(deps.as_mut(), 1).unwrap();
+    assert_eq!(progress(&deps).migrated_stakers, 1);
+    assert!(rewards_read(deps.as_ref().storage, &staker_canon)
+        .load(&staking_canon)
+        .is_ok());
+    assert_eq!(
+        validate_migrate_store_status(deps.as_mut().storage),
+        Err(ContractError::ContractUpgrade {})
+    );
+
+    let res = step(deps.as_mut(), 1).unwrap();
+    assert_eq!(res.attributes.last(), Some(&attr("finished", "true")));
+    assert!(!progress(&deps).migrating);
+    assert_eq!(validate_migrate_store_status(deps.as_mut().storage), Ok(()));
+    assert_eq!(step(deps.as_mut(), 1), Err(ContractError::NotMigrating {}));
+}
+
+// a bucket level dump of a v1 deployment: (namespaces, key, json value). `@name` stands for the
+// MockApi canonical address of name, in key segments and in values. Every storage layout change
+// moves the keys it replaces into `legacy` and adds the dump of the version before it here.
+type StorageFixture = &'static [(&'static [&'static str], &'static str, &'static str)];
+
+const V1_FIXTURE: StorageFixture = &[
+    (
+        &["config_v2"],
+        "",
+        r#"{"owner":"@owner","rewarder":"@rewarder","oracle_addr":"@oracle","factory_addr":"@factory","base_denom":"orai"}"#,
+    ),
+    (&["migrate_store_check"], "", "true"),
+    (
+        &["pool_info_v2"],
+        "orai",
+        r#"{"staking_token":"@staking","pending_reward":"0","total_bond_amount":"100","reward_index":"2.5","migration_params":null}"#,
+    ),
+    (
+        &["rewards_per_sec"],
+        "orai",
+        r#"[{"info":{"native_token":{"denom":"orai"}},"amount":"100"}]"#,
+    ),
+    (&["staker", "orai"], "@staker", "true"),
+    (
+        &["reward_v2", "@staker"],
+        "orai",
+        r#"{"native_token":true,"index":"2","bond_amount":"100","pending_reward":"25","pending_withdraw":[]}"#,
+    ),
+    (&["is_migrated", "@staker"], "orai", "true"),
+];
+
+fn load_fixture(deps: DepsMut, fixture: StorageFixture) {
+    let canonical = |segment: &str| -> Vec<u8> {
+        match segment.strip_prefix('@') {
+            Some(name) => deps.api.addr_canonicalize(name).unwrap().to_vec(),
+            None => segment.as_bytes().to_vec(),
+        }
+    };
+
+    for (namespaces, key, value) in fixture {
+        let namespaces = namespaces
+            .iter()
+            .map(|segment| canonical(segment))
+            .collect::<Vec<Vec<u8>>>();
+        let mut raw_key =
+            to_length_prefixed_nested(&namespaces.iter().map(Vec::as_slice).collect::<Vec<_>>());
+        raw_key.extend(canonical(key));
+
+        // values are json, an address only ever appears as a whole string
+        let raw_value = value
+            .split('"')
+            .enumerate()
+            .map(|(i, part)| match part.strip_prefix('@') {
+                Some(_) if i % 2 == 1 => Binary::from(canonical(part)).to_base64(),
+                _ => part.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join("\"");
+
+        deps.storage.set(&raw_key, raw_value.as_bytes());
+    }
+}
+
+#[test]
+fn test_migrate_v1_fixture() {
+    let mut deps = mock_dependencies();
+    load_fixture(deps.as_mut(), V1_FIXTURE);
+    let staking_canon = deps.api.addr_canonicalize("staking").unwrap();
+    let staker_canon = deps.api.addr_canonicalize("staker").unwrap();
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg { deprecations: None }).unwrap();
+    // staking is closed until the store has been moved over
+    assert_eq!(
+        validate_migrate_store_status(deps.as_mut().storage),
+        Err(ContractError::ContractUpgrade {})
+    );
+
+    let deps_mut = deps.as_mut();
+    let res = migrate_store(
+        deps_mut.storage,
+        deps_mut.api,
+        AssetInfo::NativeToken {
+            denom: "orai".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_store"),
+            attr("asset_info", "orai"),
+            attr("staker_count", "1"),
+        ]
+    );
+
+    let config = read_config(deps.as_ref().storage).unwrap();
+    assert_eq!(config.owner, deps.api.addr_canonicalize("owner").unwrap());
+    assert_eq!(
+        config.rewarder,
+        deps.api.addr_canonicalize("rewarder").unwrap()
+    );
+    assert_eq!(
+        config.oracle_addr,
+        deps.api.addr_canonicalize("oracle").unwrap()
+    );
+    assert_eq!(
+        config.factory_addr,
+        deps.api.addr_canonicalize("factory").unwrap()
+    );
+    assert_eq!(config.base_denom, "orai");
+    assert!(!config.paused);
+
+    let pool_info = read_pool_info(deps.as_ref().storage, &staking_canon).unwrap();
+    assert_eq!(pool_info.staking_token, staking_canon);
+    assert_eq!(pool_info.pending_reward, Uint128::zero());
+    assert_eq!(pool_info.total_bond_amount, Uint128::from(100u128));
+    assert_eq!(pool_info.reward_index, Decimal256::from_ratio(5u128, 2u128));
+    assert_eq!(pool_info.migration_params, None);
+    assert_eq!(pool_info.transition_staking_tokens, vec![]);
+    assert_eq!(pool_info.reward_decimals, vec![]);
+    assert_eq!(pool_info.unbonding_period, 0);
+
+    assert_eq!(
+        read_rewards_per_sec(deps.as_ref().storage, &staking_canon).unwrap(),
+        vec![AssetRaw {
+            info: AssetInfoRaw::NativeToken {
+                denom: "orai".to_string(),
+            },
+            amount: Uint128::from(100u128),
+        }]
+    );
+
+    assert!(stakers_read(deps.as_ref().storage, &staking_canon)
+        .load(&staker_canon)
+        .unwrap());
+    assert!(read_is_migrated(
+        deps.as_ref().storage,
+        &staking_canon,
+        &staker_canon
+    ));
+    let reward_info = rewards_read(deps.as_ref().storage, &staker_canon)
+        .load(&staking_canon)
+        .unwrap();
+    assert!(reward_info.native_token);
+    assert_eq!(reward_info.index, Decimal256::from_ratio(2u128, 1u128));
+    assert_eq!(reward_info.bond_amount, Uint128::from(100u128));
+    assert_eq!(reward_info.pending_reward, Uint128::from(25u128));
+    assert_eq!(reward_info.pending_withdraw, vec![]);
+
+    // reopen staking, the migrated position keeps earning from the same index
+    contract_execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            rewarder: None,
+            migrate_store_status: Some(true),
+            penalty_collector: None,
+            reward_distributors: None,
+            withdraw_fee_rate: None,
+            fee_collector: None,
+            oracle_addr: None,
+            factory_addr: None,
+            base_denom: None,
+            community_pool: None,
+        },
+    )
+    .unwrap();
+    let res: RewardInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RewardInfo {
+                staker_addr: Addr::unchecked("staker"),
+                staking_token: Some(Addr::unchecked("staking")),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.reward_infos[0].bond_amount, Uint128::from(100u128));
+    assert_eq!(res.reward_infos[0].pending_reward, Uint128::from(75u128));
+}
+
 // #[test]
 // fn test_migration() {
 //     // fixture
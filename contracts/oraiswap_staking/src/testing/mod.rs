@@ -1,5 +1,11 @@
+mod bench_test;
 mod contract_test;
 mod deprecate_test;
+pub(crate) mod env;
+mod integration_test;
+mod invariant_test;
 mod migrate_test;
+mod reward_math_test;
 mod reward_test;
 mod staking_test;
+mod state_test;
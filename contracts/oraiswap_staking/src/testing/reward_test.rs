@@ -1,14 +1,35 @@
 use crate::contract::{execute, instantiate, query};
-use crate::state::{read_pool_info, rewards_read, store_pool_info, PoolInfo, RewardInfo};
-use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info};
-use cosmwasm_std::{coin, from_binary, to_binary, Addr, Api, Decimal, Uint128};
-use cw20::Cw20ReceiveMsg;
+use crate::rewards::{
+    MAX_REWARD_INDEX_SNAPSHOTS, MAX_REWARD_STREAMS, MAX_WITHDRAW_POOLS, MIN_ABANDONED_IDLE_SECONDS,
+};
+use crate::state::{
+    read_pool_info, read_reward_index_history_len, rewards_read, store_pool_info, PoolInfo,
+    RewardInfo,
+};
+use crate::testing::env::{
+    default_rewards_per_sec, mock_staking_tokens, reward_token_rewards_per_sec, StakingTestEnv,
+    OWNER, REWARD_TOKEN,
+};
+use cosmwasm_std::testing::{
+    mock_dependencies_with_balance, mock_env, mock_info, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    attr, coin, from_binary, to_binary, Addr, Api, BankMsg, CosmosMsg, Decimal, Decimal256, IbcMsg,
+    IbcTimeout, StdError, SubMsg, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
 use oraiswap::create_entry_points_testing;
+use oraiswap::error::ContractError;
 use oraiswap::staking::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolInfoResponse, QueryMsg, RewardInfoResponse,
-    RewardInfoResponseItem, RewardMsg,
+    AssetDecimals, BatchRewardInfoResponse, ConfigResponse, Cw20HookMsg, DistributionInfoResponse,
+    DistributionScheduleResponse, ExecuteMsg, IbcRecipient, InstantiateMsg, OrphanedRewardResponse,
+    PoolConfigUpdate, PoolEpochResponse, PoolInfoResponse, PoolRewardRateResponse, QueryMsg,
+    RewardIndexHistoryItem, RewardInfoResponse, RewardInfoResponseItem, RewardMsg,
+    SimulateBondResponse, SimulateWithdrawResponse, StakerInfoResponse, StakerRewardInfo,
+    StateResponse, WithdrawResponse,
 };
+use oraiswap::testing::staking::bond_receive_msg;
 use oraiswap::testing::{MockApp, ATOM_DENOM};
 
 #[test]
@@ -17,6 +38,7 @@ fn test_deposit_reward() {
         coin(10000000000u128, ORAI_DENOM),
         coin(20000000000u128, ATOM_DENOM),
     ]);
+    mock_staking_tokens(&mut deps.querier, &["staking"]);
     let staking_token = Addr::unchecked("staking");
 
     let msg = InstantiateMsg {
@@ -54,6 +76,7 @@ fn test_deposit_reward() {
 
     let msg = ExecuteMsg::RegisterAsset {
         staking_token: staking_token.clone(),
+        min_bond_amount: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -70,7 +93,11 @@ fn test_deposit_reward() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr".into(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
     });
     let info = mock_info("staking", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -112,7 +139,7 @@ fn test_deposit_reward() {
         &mut deps.storage,
         &staking_token_cannonical,
         &PoolInfo {
-            reward_index: Decimal::zero(),
+            reward_index: Decimal256::zero(),
             ..pool_info
         },
     )
@@ -142,12 +169,409 @@ fn test_deposit_reward() {
     );
 }
 
+#[test]
+fn test_deposit_reward_from_distributor() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_pool("staking2", reward_token_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_bond("addr", "staking2", 100)
+        .build();
+
+    let deposit_msg = || ExecuteMsg::DepositReward {
+        rewards: vec![RewardMsg {
+            staking_token: Addr::unchecked("staking"),
+            total_accumulation_amount: Uint128::from(300u128),
+        }],
+    };
+
+    // only the rewarder deposits while the list is empty
+    let err = test_env.execute("distributor", deposit_msg()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let msg = ExecuteMsg::UpdateConfig {
+        rewarder: None,
+        migrate_store_status: None,
+        penalty_collector: None,
+        reward_distributors: Some(vec![Addr::unchecked("distributor")]),
+        withdraw_fee_rate: None,
+        fee_collector: None,
+        oracle_addr: None,
+        factory_addr: None,
+        base_denom: None,
+        community_pool: None,
+    };
+    let err = test_env.execute("distributor", msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    test_env.execute(OWNER, msg).unwrap();
+
+    test_env.execute("distributor", deposit_msg()).unwrap();
+    test_env.execute("rewarder", deposit_msg()).unwrap();
+    test_env.assert_pending("addr", "staking", 600);
+
+    let err = test_env.execute("other", deposit_msg()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // through the hooks the token contract must still be the reward token of the pools
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateRewardWeights {
+                weights: vec![(Addr::unchecked("staking2"), Decimal::one())],
+            },
+        )
+        .unwrap();
+    let hook_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "distributor".to_string(),
+        amount: Uint128::from(300u128),
+        msg: to_binary(&Cw20HookMsg::DepositReward {
+            stream_over_seconds: None,
+            expected_epoch: None,
+        })
+        .unwrap(),
+    });
+    let err = test_env
+        .execute("other_token", hook_msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    test_env.execute(REWARD_TOKEN, hook_msg).unwrap();
+    test_env.assert_pending("addr", "staking2", 300);
+}
+
+#[test]
+fn test_deposit_native_reward() {
+    let orai_rewards = vec![Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: 100u128.into(),
+    }];
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", orai_rewards)
+        .with_pool("staking2", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+
+    let deposit_msg = |staking_token: &str| ExecuteMsg::DepositNativeReward {
+        rewards: vec![RewardMsg {
+            staking_token: Addr::unchecked(staking_token),
+            total_accumulation_amount: Uint128::from(300u128),
+        }],
+    };
+
+    // mixed or other denoms are rejected
+    let err = test_env
+        .execute_with_funds(
+            "rewarder",
+            &[coin(300u128, ATOM_DENOM)],
+            deposit_msg("staking"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFunds {});
+    let err = test_env
+        .execute_with_funds(
+            "rewarder",
+            &[coin(300u128, ORAI_DENOM), coin(300u128, ATOM_DENOM)],
+            deposit_msg("staking"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFunds {});
+
+    let err = test_env
+        .execute_with_funds(
+            "rewarder",
+            &[coin(200u128, ORAI_DENOM)],
+            deposit_msg("staking"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NativeRewardMismatch {
+            sent: Uint128::from(200u128),
+            expected: Uint128::from(300u128),
+        }
+    );
+
+    // staking2 is also rewarded in ATOM
+    let err = test_env
+        .execute_with_funds(
+            "rewarder",
+            &[coin(300u128, ORAI_DENOM)],
+            deposit_msg("staking2"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidNativeRewardPool {
+            staking_token: "staking2".to_string(),
+            denom: ORAI_DENOM.to_string(),
+        }
+    );
+
+    test_env
+        .execute_with_funds(
+            "rewarder",
+            &[coin(300u128, ORAI_DENOM)],
+            deposit_msg("staking"),
+        )
+        .unwrap();
+    test_env.assert_pending("addr", "staking", 300);
+}
+
+#[test]
+fn test_reward_window() {
+    let orai_rewards = vec![Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: 100u128.into(),
+    }];
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", orai_rewards)
+        .with_bond("addr", "staking", 100)
+        .build();
+    let now = test_env.env.block.time.seconds();
+
+    let window_msg =
+        |reward_start_time: u64, reward_end_time: u64| ExecuteMsg::UpdateRewardWindow {
+            staking_token: Addr::unchecked("staking"),
+            reward_start_time: Some(reward_start_time),
+            reward_end_time: Some(reward_end_time),
+        };
+    let err = test_env
+        .execute(OWNER, window_msg(now + 100, now + 100))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidRewardWindow {
+            start_time: now + 100,
+            end_time: now + 100,
+        }
+    );
+    test_env
+        .execute(OWNER, window_msg(now + 100, now + 200))
+        .unwrap();
+    let res = test_env.pool_info("staking");
+    assert_eq!(res.reward_start_time, Some(now + 100));
+    assert_eq!(res.reward_end_time, Some(now + 200));
+
+    let rewards = vec![RewardMsg {
+        staking_token: Addr::unchecked("staking"),
+        total_accumulation_amount: Uint128::from(300u128),
+    }];
+
+    // the campaign has not started, the native deposit goes back to the depositor
+    let res = test_env
+        .execute_with_funds(
+            "rewarder",
+            &[coin(300u128, ORAI_DENOM)],
+            ExecuteMsg::DepositNativeReward {
+                rewards: rewards.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "rewarder".to_string(),
+            amount: vec![coin(300u128, ORAI_DENOM)],
+        })]
+    );
+    assert_eq!(res.attributes[2], attr("skipped_amount", "300"));
+    test_env.assert_pending("addr", "staking", 0);
+
+    test_env.advance_seconds(100);
+    let res = test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: rewards.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.attributes[2], attr("skipped_amount", "0"));
+    test_env.assert_pending("addr", "staking", 300);
+
+    // the end time is excluded
+    test_env.advance_seconds(100);
+    let res = test_env
+        .execute("rewarder", ExecuteMsg::DepositReward { rewards })
+        .unwrap();
+    assert_eq!(res.attributes[2], attr("skipped_amount", "300"));
+    test_env.assert_pending("addr", "staking", 300);
+}
+
+#[test]
+fn test_distribution_schedule() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_pool("staking2", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_bond("addr2", "staking2", 100)
+        .build();
+    let now = test_env.env.block.time.seconds();
+
+    let err = test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::AppendDistributionSchedule {
+                schedule: vec![(now - 1, now + 100, Uint128::from(400u128))],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidDistributionSchedule {
+            start_time: now - 1,
+            end_time: now + 100,
+        }
+    );
+
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateRewardWeights {
+                weights: vec![
+                    (Addr::unchecked("staking"), Decimal::one()),
+                    (
+                        Addr::unchecked("staking2"),
+                        Decimal::from_ratio(3u128, 1u128),
+                    ),
+                ],
+            },
+        )
+        .unwrap();
+    // the second entry overlaps the second half of the first one
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::AppendDistributionSchedule {
+                schedule: vec![
+                    (now, now + 100, Uint128::from(400u128)),
+                    (now + 50, now + 150, Uint128::from(400u128)),
+                ],
+            },
+        )
+        .unwrap();
+
+    test_env.advance_seconds(100);
+    let res: DistributionScheduleResponse = test_env.query(QueryMsg::DistributionSchedule {});
+    assert_eq!(res.pending_amount, Uint128::from(600u128));
+    assert_eq!(res.last_distributed, 0);
+
+    // the next bond accrues the unlocked amount into both pools by weight
+    test_env
+        .execute(
+            "staking",
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "addr3".to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&Cw20HookMsg::Bond {
+                    lock_duration: None,
+                    referrer: None,
+                })
+                .unwrap(),
+            }),
+        )
+        .unwrap();
+    test_env.assert_pending("addr", "staking", 150);
+    test_env.assert_pending("addr2", "staking2", 450);
+    test_env.assert_pending("addr3", "staking", 0);
+
+    let res: DistributionScheduleResponse = test_env.query(QueryMsg::DistributionSchedule {});
+    assert_eq!(
+        res,
+        DistributionScheduleResponse {
+            distribution_schedule: vec![(now + 50, now + 150, Uint128::from(400u128))],
+            last_distributed: now + 100,
+            pending_amount: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn test_distribution_info() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_pool("staking2", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_bond("addr2", "staking2", 100)
+        .build();
+    let now = test_env.env.block.time.seconds();
+
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateRewardWeights {
+                weights: vec![
+                    (Addr::unchecked("staking"), Decimal::one()),
+                    (
+                        Addr::unchecked("staking2"),
+                        Decimal::from_ratio(3u128, 1u128),
+                    ),
+                ],
+            },
+        )
+        .unwrap();
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::AppendDistributionSchedule {
+                schedule: vec![(now, now + 100, Uint128::from(400u128))],
+            },
+        )
+        .unwrap();
+
+    let res: DistributionInfoResponse = test_env.query(QueryMsg::DistributionInfo {});
+    assert_eq!(res.rewards_per_sec, Decimal::from_ratio(4u128, 1u128));
+    assert_eq!(res.last_distributed, 0);
+    let mut shares: Vec<(&str, Decimal)> = res
+        .pools
+        .iter()
+        .map(|pool| (pool.staking_token.as_str(), pool.share))
+        .collect();
+    shares.sort();
+    assert_eq!(
+        shares,
+        vec![
+            ("staking", Decimal::percent(25)),
+            ("staking2", Decimal::percent(75)),
+        ]
+    );
+
+    let before: Vec<PoolInfoResponse> = res
+        .pools
+        .iter()
+        .map(|pool| test_env.pool_info(pool.staking_token.as_str()))
+        .collect();
+    test_env.advance_seconds(40);
+    // the next bond accrues the 40 seconds into both pools
+    test_env
+        .execute(
+            "staking",
+            bond_receive_msg("addr3", Uint128::from(100u128), None).unwrap(),
+        )
+        .unwrap();
+
+    // each pool gained its rate over the seconds, spread over its 100 bonded
+    for (pool, before) in res.pools.iter().zip(before) {
+        let after = test_env.pool_info(pool.staking_token.as_str());
+        assert_eq!(
+            after.reward_index - before.reward_index,
+            pool.rewards_per_sec * Decimal::from_ratio(40u128, 100u128)
+        );
+    }
+    let res: DistributionInfoResponse = test_env.query(QueryMsg::DistributionInfo {});
+    assert_eq!(res.last_distributed, now + 40);
+}
+
 #[test]
 fn test_deposit_reward_when_no_bonding() {
     let mut deps = mock_dependencies_with_balance(&[
         coin(10000000000u128, ORAI_DENOM),
         coin(20000000000u128, ATOM_DENOM),
     ]);
+    mock_staking_tokens(&mut deps.querier, &["staking"]);
 
     let msg = InstantiateMsg {
         owner: Some(Addr::unchecked("owner")),
@@ -184,6 +608,7 @@ fn test_deposit_reward_when_no_bonding() {
 
     let msg = ExecuteMsg::RegisterAsset {
         staking_token: Addr::unchecked("staking"),
+        min_bond_amount: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -266,6 +691,7 @@ fn test_before_share_changes() {
         coin(10000000000u128, ORAI_DENOM),
         coin(20000000000u128, ATOM_DENOM),
     ]);
+    mock_staking_tokens(&mut deps.querier, &["staking"]);
 
     let msg = InstantiateMsg {
         owner: Some(Addr::unchecked("owner")),
@@ -302,6 +728,7 @@ fn test_before_share_changes() {
 
     let msg = ExecuteMsg::RegisterAsset {
         staking_token: Addr::unchecked("staking"),
+        min_bond_amount: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -315,7 +742,11 @@ fn test_before_share_changes() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr".into(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
     });
     let info = mock_info("staking", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -338,9 +769,15 @@ fn test_before_share_changes() {
         RewardInfo {
             pending_reward: Uint128::zero(),
             bond_amount: Uint128::from(100u128),
-            index: Decimal::zero(),
+            index: Decimal256::zero(),
             native_token: false,
             pending_withdraw: vec![],
+            locks: vec![],
+            proxy_indices: vec![],
+            total_claimed: Uint128::zero(),
+            boost_shortfall: Uint128::zero(),
+            last_updated: mock_env().block.time.seconds(),
+            last_bond_time: mock_env().block.time.seconds(),
         },
         reward_info
     );
@@ -349,7 +786,11 @@ fn test_before_share_changes() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr".into(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
     });
     let info = mock_info("staking", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -360,9 +801,15 @@ fn test_before_share_changes() {
         RewardInfo {
             pending_reward: Uint128::from(100u128),
             bond_amount: Uint128::from(200u128),
-            index: Decimal::from_ratio(100u128, 100u128),
+            index: Decimal256::from_ratio(100u128, 100u128),
             native_token: false,
             pending_withdraw: vec![],
+            locks: vec![],
+            proxy_indices: vec![],
+            total_claimed: Uint128::zero(),
+            boost_shortfall: Uint128::zero(),
+            last_updated: mock_env().block.time.seconds(),
+            last_bond_time: mock_env().block.time.seconds(),
         },
         reward_info
     );
@@ -380,7 +827,10 @@ fn test_before_share_changes() {
     // unbond
     let msg = ExecuteMsg::Unbond {
         staking_token: Addr::unchecked("staking"),
-        amount: Uint128::from(100u128),
+        amount: Some(Uint128::from(100u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
     };
     let info = mock_info("addr", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -391,9 +841,15 @@ fn test_before_share_changes() {
         RewardInfo {
             pending_reward: Uint128::from(200u128),
             bond_amount: Uint128::from(100u128),
-            index: Decimal::from_ratio(150u128, 100u128),
+            index: Decimal256::from_ratio(150u128, 100u128),
             native_token: false,
             pending_withdraw: vec![],
+            locks: vec![],
+            proxy_indices: vec![],
+            total_claimed: Uint128::zero(),
+            boost_shortfall: Uint128::zero(),
+            last_updated: mock_env().block.time.seconds(),
+            last_bond_time: mock_env().block.time.seconds(),
         },
         reward_info
     );
@@ -511,6 +967,7 @@ fn test_withdraw() {
 
     let msg = ExecuteMsg::RegisterAsset {
         staking_token: lp_addr.clone(),
+        min_bond_amount: None,
     };
 
     let _res = app
@@ -521,7 +978,11 @@ fn test_withdraw() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr".into(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
     });
 
     let _res = app
@@ -554,6 +1015,10 @@ fn test_withdraw() {
 
     let msg = ExecuteMsg::Withdraw {
         staking_token: Some(Addr::unchecked("staking")),
+        ibc_recipient: None,
+        amounts: None,
+        recipient: None,
+        staker_addr: None,
     };
 
     let res = app
@@ -563,6 +1028,461 @@ fn test_withdraw() {
     println!("{:?}", res);
 }
 
+#[test]
+fn test_withdraw_native_rewards() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    test_env.assert_pending("addr", "staking", 300);
+
+    let msg = ExecuteMsg::Withdraw {
+        staking_token: Some(Addr::unchecked("staking")),
+        ibc_recipient: None,
+        amounts: None,
+        recipient: None,
+        staker_addr: None,
+    };
+    let res = test_env.execute("addr", msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(99u128, ORAI_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(199u128, ATOM_DENOM)],
+            }))
+        ]
+    );
+
+    // the bond stays, only the reward is paid out
+    test_env.assert_pending("addr", "staking", 0);
+    test_env.assert_bond("addr", "staking", 100);
+}
+
+#[test]
+fn test_withdraw_fee_bounds() {
+    let mut test_env = StakingTestEnv::new().build();
+    let config_msg = |withdraw_fee_rate: Option<Decimal>, fee_collector: Option<&str>| {
+        ExecuteMsg::UpdateConfig {
+            rewarder: None,
+            migrate_store_status: None,
+            penalty_collector: None,
+            reward_distributors: None,
+            withdraw_fee_rate,
+            fee_collector: fee_collector.map(Addr::unchecked),
+            oracle_addr: None,
+            factory_addr: None,
+            base_denom: None,
+            community_pool: None,
+        }
+    };
+
+    // a zero rate needs no collector
+    test_env
+        .execute(OWNER, config_msg(Some(Decimal::zero()), None))
+        .unwrap();
+    let err = test_env
+        .execute(
+            OWNER,
+            config_msg(Some(Decimal::from_atomics(1u128, 18).unwrap()), None),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::FeeCollectorNotSet {});
+
+    let just_above_max = Decimal::percent(10) + Decimal::from_atomics(1u128, 18).unwrap();
+    let err = test_env
+        .execute(OWNER, config_msg(Some(just_above_max), Some("collector")))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::WithdrawFeeTooHigh {
+            max: Decimal::percent(10),
+        }
+    );
+
+    // the rate and its collector can arrive in separate updates, collector first
+    test_env
+        .execute(OWNER, config_msg(None, Some("collector")))
+        .unwrap();
+    test_env
+        .execute(OWNER, config_msg(Some(Decimal::percent(10)), None))
+        .unwrap();
+    let config: ConfigResponse = test_env.query(QueryMsg::Config {});
+    assert_eq!(config.withdraw_fee_rate, Decimal::percent(10));
+    assert_eq!(config.fee_collector, Some(Addr::unchecked("collector")));
+}
+
+#[test]
+fn test_withdraw_fee() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+
+    let config_msg =
+        |withdraw_fee_rate: Decimal, fee_collector: Option<&str>| ExecuteMsg::UpdateConfig {
+            rewarder: None,
+            migrate_store_status: None,
+            penalty_collector: None,
+            reward_distributors: None,
+            withdraw_fee_rate: Some(withdraw_fee_rate),
+            fee_collector: fee_collector.map(Addr::unchecked),
+            oracle_addr: None,
+            factory_addr: None,
+            base_denom: None,
+            community_pool: None,
+        };
+    let err = test_env
+        .execute(OWNER, config_msg(Decimal::percent(10), None))
+        .unwrap_err();
+    assert_eq!(err, ContractError::FeeCollectorNotSet {});
+    let err = test_env
+        .execute(OWNER, config_msg(Decimal::percent(11), Some("collector")))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::WithdrawFeeTooHigh {
+            max: Decimal::percent(10),
+        }
+    );
+    test_env
+        .execute(OWNER, config_msg(Decimal::percent(10), Some("collector")))
+        .unwrap();
+
+    let msg = ExecuteMsg::Withdraw {
+        staking_token: Some(Addr::unchecked("staking")),
+        ibc_recipient: None,
+        amounts: None,
+        recipient: None,
+        staker_addr: None,
+    };
+    let res = test_env.execute("addr", msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: vec![coin(9u128, ORAI_DENOM)],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(90u128, ORAI_DENOM)],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: vec![coin(19u128, ATOM_DENOM)],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(180u128, ATOM_DENOM)],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes[res.attributes.len() - 3..],
+        [
+            attr("gross", format!("99{}, 199{}", ORAI_DENOM, ATOM_DENOM)),
+            attr("fee", format!("9{}, 19{}", ORAI_DENOM, ATOM_DENOM)),
+            attr("net", format!("90{}, 180{}", ORAI_DENOM, ATOM_DENOM)),
+        ]
+    );
+}
+
+#[test]
+fn test_withdraw_partial_amounts() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    let orai = |amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: amount.into(),
+    };
+    let atom = |amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: ATOM_DENOM.to_string(),
+        },
+        amount: amount.into(),
+    };
+
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: None,
+                ibc_recipient: None,
+                amounts: Some(vec![orai(50)]),
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(res, ContractError::PartialWithdrawWithoutStakingToken {});
+
+    // a staking token that is not an address is refused, not read as a withdraw of everything
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("st")),
+                ibc_recipient: None,
+                amounts: Some(vec![orai(50)]),
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap_err();
+    assert!(matches!(res, ContractError::Std(_)));
+    test_env.assert_pending("addr", "staking", 300);
+
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: Some(vec![orai(50)]),
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr".to_string(),
+            amount: vec![coin(50u128, ORAI_DENOM)],
+        }))]
+    );
+
+    // the rest stays pending
+    let res = test_env.reward_info("addr", Some("staking"));
+    assert_eq!(res.reward_infos[0].pending_reward, Uint128::zero());
+    assert_eq!(
+        res.reward_infos[0].pending_withdraw,
+        vec![orai(49), atom(199)]
+    );
+
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: Some(vec![atom(200)]),
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::WithdrawExceedsClaimable {
+            asset: ATOM_DENOM.to_string(),
+            claimable: Uint128::from(199u128),
+            requested: Uint128::from(200u128),
+        }
+    );
+
+    // no amounts withdraws everything left
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(49u128, ORAI_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(199u128, ATOM_DENOM)],
+            }))
+        ]
+    );
+}
+
+#[test]
+fn test_withdraw_to_recipient() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+
+    // an invalid recipient is rejected before the rewards are settled
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: Some(Addr::unchecked("")),
+                staker_addr: None,
+            },
+        )
+        .unwrap_err();
+    assert!(matches!(res, ContractError::Std(_)));
+    test_env.assert_pending("addr", "staking", 300);
+
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: Some(Addr::unchecked("treasury")),
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "treasury".to_string(),
+                amount: vec![coin(99u128, ORAI_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "treasury".to_string(),
+                amount: vec![coin(199u128, ATOM_DENOM)],
+            }))
+        ]
+    );
+    assert_eq!(
+        res.attributes[..3],
+        [
+            attr("action", "withdraw_reward"),
+            attr("staker_addr", "addr"),
+            attr("recipient", "treasury"),
+        ]
+    );
+
+    // the rewards were accounted to the staker
+    test_env.assert_pending("addr", "staking", 0);
+    test_env.assert_bond("addr", "staking", 100);
+}
+
+#[test]
+fn test_withdraw_all_pools() {
+    let orai_rewards = vec![Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: 100u128.into(),
+    }];
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking1", default_rewards_per_sec())
+        .with_pool("staking2", orai_rewards)
+        .with_bond("addr", "staking1", 100)
+        .with_bond("addr", "staking2", 100)
+        .with_reward_deposit("staking1", 300)
+        .with_reward_deposit("staking2", 300)
+        .build();
+
+    let msg = ExecuteMsg::Withdraw {
+        staking_token: None,
+        ibc_recipient: None,
+        amounts: None,
+        recipient: None,
+        staker_addr: None,
+    };
+    let res = test_env.execute("addr", msg).unwrap();
+
+    // one transfer per reward asset, summed over the pools
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(399u128, ORAI_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(199u128, ATOM_DENOM)],
+            }))
+        ]
+    );
+    assert!(res.attributes.contains(&attr(
+        "reward_assets",
+        format!("99{}, 199{}", ORAI_DENOM, ATOM_DENOM)
+    )));
+    assert!(res
+        .attributes
+        .contains(&attr("reward_assets", format!("300{}", ORAI_DENOM))));
+    assert!(res.attributes.contains(&attr("staking_token", "staking1")));
+    assert!(res.attributes.contains(&attr("staking_token", "staking2")));
+
+    test_env.assert_pending("addr", "staking1", 0);
+    test_env.assert_pending("addr", "staking2", 0);
+}
+
+#[test]
+fn test_withdraw_all_pools_is_bounded() {
+    let mut builder = StakingTestEnv::new();
+    for i in 0..=MAX_WITHDRAW_POOLS {
+        let staking_token = format!("staking{}", i);
+        builder = builder
+            .with_pool(&staking_token, default_rewards_per_sec())
+            .with_bond("addr", &staking_token, 100);
+    }
+    let mut test_env = builder.build();
+
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: None,
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::TooManyWithdrawPools {
+            max: MAX_WITHDRAW_POOLS,
+        }
+    );
+
+    // a single pool can still be withdrawn
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking0")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+}
+
 #[test]
 fn test_cannonical_with_to_vec() {
     let deps = mock_dependencies_with_balance(&[
@@ -584,6 +1504,7 @@ fn test_update_rewards_per_sec() {
         coin(10000000000u128, ORAI_DENOM),
         coin(20000000000u128, ATOM_DENOM),
     ]);
+    mock_staking_tokens(&mut deps.querier, &["staking_token"]);
 
     let msg = InstantiateMsg {
         owner: Some(Addr::unchecked("owner")),
@@ -621,6 +1542,7 @@ fn test_update_rewards_per_sec() {
 
     let msg = ExecuteMsg::RegisterAsset {
         staking_token: staking_token.clone(),
+        min_bond_amount: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -634,7 +1556,11 @@ fn test_update_rewards_per_sec() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr".into(),
         amount: Uint128::from(300u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
     });
     let info = mock_info(staking_token.as_str(), &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -702,6 +1628,8 @@ fn test_update_rewards_per_sec() {
             reward_infos: vec![RewardInfoResponseItem {
                 staking_token: staking_token.clone(),
                 bond_amount: Uint128::from(300u128),
+                weighted_bond_amount: Uint128::from(300u128),
+                locks: vec![],
                 pending_reward: Uint128::from(99u128),
                 pending_withdraw: vec![
                     Asset {
@@ -717,7 +1645,23 @@ fn test_update_rewards_per_sec() {
                         amount: Uint128::from(199u128)
                     }
                 ],
+                reward_decimals: vec![
+                    AssetDecimals {
+                        info: AssetInfo::NativeToken {
+                            denom: ORAI_DENOM.to_string()
+                        },
+                        decimals: 6
+                    },
+                    AssetDecimals {
+                        info: AssetInfo::NativeToken {
+                            denom: ATOM_DENOM.to_string()
+                        },
+                        decimals: 6
+                    }
+                ],
                 should_migrate: None,
+                total_claimed: Uint128::zero(),
+                last_updated: mock_env().block.time.seconds(),
             },],
         }
     );
@@ -729,6 +1673,7 @@ fn test_update_rewards_per_sec_with_multiple_bond() {
         coin(10000000000u128, ORAI_DENOM),
         coin(20000000000u128, ATOM_DENOM),
     ]);
+    mock_staking_tokens(&mut deps.querier, &["staking"]);
 
     let msg = InstantiateMsg {
         owner: Some(Addr::unchecked("owner")),
@@ -765,6 +1710,7 @@ fn test_update_rewards_per_sec_with_multiple_bond() {
 
     let msg = ExecuteMsg::RegisterAsset {
         staking_token: Addr::unchecked("staking"),
+        min_bond_amount: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -778,7 +1724,11 @@ fn test_update_rewards_per_sec_with_multiple_bond() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr".into(),
         amount: Uint128::from(300u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
     });
     let info = mock_info("staking", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -822,7 +1772,11 @@ fn test_update_rewards_per_sec_with_multiple_bond() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr1".into(),
         amount: Uint128::from(300u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
     });
     let info = mock_info("staking", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -844,9 +1798,14 @@ fn test_update_rewards_per_sec_with_multiple_bond() {
             reward_infos: vec![RewardInfoResponseItem {
                 staking_token: Addr::unchecked("staking"),
                 bond_amount: Uint128::from(300u128),
+                weighted_bond_amount: Uint128::from(300u128),
+                locks: vec![],
                 pending_reward: Uint128::zero(),
                 pending_withdraw: vec![],
+                reward_decimals: vec![],
                 should_migrate: None,
+                total_claimed: Uint128::zero(),
+                last_updated: mock_env().block.time.seconds(),
             },],
         }
     );
@@ -879,6 +1838,8 @@ fn test_update_rewards_per_sec_with_multiple_bond() {
             reward_infos: vec![RewardInfoResponseItem {
                 staking_token: Addr::unchecked("staking"),
                 bond_amount: Uint128::from(300u128),
+                weighted_bond_amount: Uint128::from(300u128),
+                locks: vec![],
                 pending_reward: Uint128::from(49u128),
                 pending_withdraw: vec![
                     Asset {
@@ -894,7 +1855,23 @@ fn test_update_rewards_per_sec_with_multiple_bond() {
                         amount: Uint128::from(199u128)
                     }
                 ],
-                should_migrate: None,
+                reward_decimals: vec![
+                    AssetDecimals {
+                        info: AssetInfo::NativeToken {
+                            denom: ORAI_DENOM.to_string()
+                        },
+                        decimals: 6
+                    },
+                    AssetDecimals {
+                        info: AssetInfo::NativeToken {
+                            denom: ATOM_DENOM.to_string()
+                        },
+                        decimals: 6
+                    }
+                ],
+                should_migrate: None,
+                total_claimed: Uint128::zero(),
+                last_updated: mock_env().block.time.seconds(),
             },],
         }
     );
@@ -917,10 +1894,2363 @@ fn test_update_rewards_per_sec_with_multiple_bond() {
             reward_infos: vec![RewardInfoResponseItem {
                 staking_token: Addr::unchecked("staking"),
                 bond_amount: Uint128::from(300u128),
+                weighted_bond_amount: Uint128::from(300u128),
+                locks: vec![],
                 pending_reward: Uint128::from(49u128),
                 pending_withdraw: vec![],
+                reward_decimals: vec![],
                 should_migrate: None,
+                total_claimed: Uint128::zero(),
+                last_updated: mock_env().block.time.seconds(),
             },],
         }
     );
 }
+
+#[test]
+fn test_epoch_reward_accounting() {
+    let mut deps = mock_dependencies_with_balance(&[coin(10000000000u128, ORAI_DENOM)]);
+    mock_staking_tokens(&mut deps.querier, &["staking"]);
+    let staking_token = Addr::unchecked("staking");
+    let mut env = mock_env();
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    let _res = instantiate(deps.as_mut(), env.clone(), mock_info("addr", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateRewardsPerSec {
+        staking_token: staking_token.clone(),
+        assets: vec![Asset {
+            info: AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            amount: 100u128.into(),
+        }],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        staking_token: staking_token.clone(),
+        min_bond_amount: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // only owner can switch the pool to epoch mode
+    let msg = ExecuteMsg::UpdatePoolEpoch {
+        staking_token: staking_token.clone(),
+        epoch_length: Some(100),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // bond 100 tokens for each staker
+    for staker in ["addr", "addr2"] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                lock_duration: None,
+                referrer: None,
+            })
+            .unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("staking", &[]), msg).unwrap();
+    }
+
+    // deposit in the middle of the first epoch
+    env.block.time = env.block.time.plus_seconds(10);
+    let msg = ExecuteMsg::DepositReward {
+        rewards: vec![RewardMsg {
+            staking_token: staking_token.clone(),
+            total_accumulation_amount: Uint128::from(200u128),
+        }],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("rewarder", &[]), msg).unwrap();
+
+    let epoch: PoolEpochResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PoolEpoch {
+                staking_token: staking_token.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        epoch,
+        PoolEpochResponse {
+            epoch_length: 100,
+            epoch_number: 0,
+            accumulated_reward: Uint128::from(200u128),
+            time_remaining: 90,
+        }
+    );
+
+    // rewards are not in the index until the epoch closes
+    let pool_info: PoolInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PoolInfo {
+                staking_token: staking_token.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pool_info.reward_index, Decimal::zero());
+
+    // addr2 unbonds mid-epoch and forfeits its share, only the lp tokens are returned
+    let msg = ExecuteMsg::Unbond {
+        staking_token: staking_token.clone(),
+        amount: Some(Uint128::from(100u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    // close the epoch, all rewards go to the remaining staker
+    env.block.time = env.block.time.plus_seconds(100);
+    let msg = ExecuteMsg::CloseEpoch {
+        staking_token: staking_token.clone(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+
+    let pool_info: PoolInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PoolInfo {
+                staking_token: staking_token.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        pool_info.reward_index,
+        Decimal::from_ratio(200u128, 100u128)
+    );
+
+    let epoch: PoolEpochResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PoolEpoch {
+                staking_token: staking_token.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        epoch,
+        PoolEpochResponse {
+            epoch_length: 100,
+            epoch_number: 1,
+            accumulated_reward: Uint128::zero(),
+            time_remaining: 90,
+        }
+    );
+
+    // deposit into the second epoch, then withdraw after it ended: first interaction closes it
+    let msg = ExecuteMsg::DepositReward {
+        rewards: vec![RewardMsg {
+            staking_token: staking_token.clone(),
+            total_accumulation_amount: Uint128::from(100u128),
+        }],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("rewarder", &[]), msg).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(150);
+    let msg = ExecuteMsg::Withdraw {
+        staking_token: Some(staking_token.clone()),
+        ibc_recipient: None,
+        amounts: None,
+        recipient: None,
+        staker_addr: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr".to_string(),
+            amount: vec![coin(300u128, ORAI_DENOM)],
+        }))]
+    );
+}
+
+#[test]
+fn test_deposit_reward_weights() {
+    let mut deps = mock_dependencies_with_balance(&[]);
+    mock_staking_tokens(&mut deps.querier, &["staking1", "staking2", "staking3"]);
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+
+    for staking_token in ["staking1", "staking2", "staking3"] {
+        let msg = ExecuteMsg::UpdateRewardsPerSec {
+            staking_token: Addr::unchecked(staking_token),
+            assets: reward_token_rewards_per_sec(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let msg = ExecuteMsg::RegisterAsset {
+            staking_token: Addr::unchecked(staking_token),
+            min_bond_amount: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    }
+
+    let deposit = |weights: Vec<(Addr, Decimal)>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "rewarder".to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::DepositRewardWeights { weights }).unwrap(),
+        })
+    };
+
+    // weights must sum to exactly 1
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("reward_token", &[]),
+        deposit(vec![
+            (Addr::unchecked("staking1"), Decimal::percent(50)),
+            (Addr::unchecked("staking2"), Decimal::percent(40)),
+        ]),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidRewardWeights {
+            total_weight: Decimal::percent(90)
+        }
+    );
+
+    // only rewarder can deposit
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::DepositRewardWeights {
+            weights: vec![(Addr::unchecked("staking1"), Decimal::one())],
+        })
+        .unwrap(),
+    });
+    let _err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("reward_token", &[]),
+        msg,
+    )
+    .unwrap_err();
+
+    // a foreign cw20 forwarding the rewarder as sender is no reward asset of the pools
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("foreign_token", &[]),
+        deposit(vec![(Addr::unchecked("staking1"), Decimal::one())]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // thirds can not be split evenly, the remainder goes to the first pool
+    let third = Decimal::from_ratio(1u128, 3u128);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("reward_token", &[]),
+        deposit(vec![
+            (Addr::unchecked("staking1"), third),
+            (Addr::unchecked("staking2"), third),
+            (Addr::unchecked("staking3"), Decimal::one() - third - third),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_reward_weights"),
+            attr("rewards_amount", "100"),
+            attr("skipped_amount", "0"),
+            attr("staking_token", "staking1"),
+            attr("deposit_epoch", "1"),
+            attr("staking_token", "staking2"),
+            attr("deposit_epoch", "1"),
+            attr("staking_token", "staking3"),
+            attr("deposit_epoch", "1"),
+        ]
+    );
+
+    let mut total = Uint128::zero();
+    for (staking_token, expected) in [("staking1", 34u128), ("staking2", 33), ("staking3", 33)] {
+        let res: PoolInfoResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PoolInfo {
+                    staking_token: Addr::unchecked(staking_token),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.pending_reward, Uint128::from(expected));
+        total += res.pending_reward;
+    }
+    assert_eq!(total, Uint128::from(100u128));
+}
+
+#[test]
+fn test_deposit_reward_by_weights() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", reward_token_rewards_per_sec())
+        .with_pool("staking2", reward_token_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_bond("addr2", "staking2", 100)
+        .build();
+
+    let deposit_msg = |sender: &str| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::from(400u128),
+            msg: to_binary(&Cw20HookMsg::DepositReward {
+                stream_over_seconds: None,
+                expected_epoch: None,
+            })
+            .unwrap(),
+        })
+    };
+
+    // no pool is weighted yet
+    let err = test_env
+        .execute("reward_token", deposit_msg("rewarder"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::EmptyRewardWeights {});
+
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateRewardWeights {
+                weights: vec![
+                    (Addr::unchecked("staking"), Decimal::one()),
+                    (
+                        Addr::unchecked("staking2"),
+                        Decimal::from_ratio(3u128, 1u128),
+                    ),
+                ],
+            },
+        )
+        .unwrap();
+
+    let err = test_env
+        .execute(REWARD_TOKEN, deposit_msg("addr"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // a Receive sent straight to the staking contract names the rewarder but moved no tokens
+    let err = test_env
+        .execute("addr", deposit_msg("rewarder"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    test_env.assert_pending("addr", "staking", 0);
+
+    test_env
+        .execute(REWARD_TOKEN, deposit_msg("rewarder"))
+        .unwrap();
+    test_env.assert_pending("addr", "staking", 100);
+    test_env.assert_pending("addr2", "staking2", 300);
+
+    // the split deposit is counted like a vector one, for the state and the pool rates
+    let res: StateResponse = test_env.query(QueryMsg::State {});
+    assert_eq!(res.total_deposited, Uint128::from(400u128));
+    assert_eq!(
+        res.last_deposit_time,
+        Some(test_env.env.block.time.seconds())
+    );
+    test_env.advance_seconds(100);
+    test_env
+        .execute(REWARD_TOKEN, deposit_msg("rewarder"))
+        .unwrap();
+    let res: PoolRewardRateResponse = test_env.query(QueryMsg::PoolRewardRate {
+        staking_token: Addr::unchecked("staking2"),
+    });
+    assert_eq!(
+        res.deposited_rewards_per_sec,
+        Decimal::from_ratio(3u128, 1u128)
+    );
+}
+
+#[test]
+fn test_proxy_reward() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+
+    let add_msg = ExecuteMsg::AddProxyReward {
+        staking_token: Addr::unchecked("staking"),
+        reward_token: Addr::unchecked("proxy_token"),
+    };
+    test_env.execute(OWNER, add_msg.clone()).unwrap();
+    let err = test_env.execute(OWNER, add_msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ProxyRewardRegistered {
+            reward_token: "proxy_token".to_string(),
+        }
+    );
+
+    let deposit_msg = |amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "project".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::DepositProxyReward {
+                staking_token: Addr::unchecked("staking"),
+            })
+            .unwrap(),
+        })
+    };
+    // only registered proxy tokens are accepted
+    let err = test_env
+        .execute("other_token", deposit_msg(500))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    test_env.execute("proxy_token", deposit_msg(500)).unwrap();
+    test_env
+        .execute(
+            "staking",
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "addr2".to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&Cw20HookMsg::Bond {
+                    lock_duration: None,
+                    referrer: None,
+                })
+                .unwrap(),
+            }),
+        )
+        .unwrap();
+    test_env.execute("proxy_token", deposit_msg(200)).unwrap();
+
+    let proxy_asset = |amount: u128| Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("proxy_token"),
+        },
+        amount: Uint128::from(amount),
+    };
+    let res = test_env.reward_info("addr2", Some("staking"));
+    assert_eq!(res.reward_infos[0].pending_withdraw, vec![proxy_asset(100)]);
+
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "proxy_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr".to_string(),
+                amount: Uint128::from(600u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+}
+
+#[test]
+fn test_withdraw_to_ibc_recipient() {
+    let mut deps = mock_dependencies_with_balance(&[coin(10000000000u128, ORAI_DENOM)]);
+    mock_staking_tokens(&mut deps.querier, &["staking", "staking2"]);
+    let env = mock_env();
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    let _res = instantiate(deps.as_mut(), env.clone(), mock_info("addr", &[]), msg).unwrap();
+
+    // native rewards for "staking", cw20 rewards for "staking2"
+    for (staking_token, info) in [
+        (
+            "staking",
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+        ),
+        (
+            "staking2",
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("reward"),
+            },
+        ),
+    ] {
+        let msg = ExecuteMsg::UpdateRewardsPerSec {
+            staking_token: Addr::unchecked(staking_token),
+            assets: vec![Asset {
+                info,
+                amount: 100u128.into(),
+            }],
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::RegisterAsset {
+            staking_token: Addr::unchecked(staking_token),
+            min_bond_amount: None,
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr".to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                lock_duration: None,
+                referrer: None,
+            })
+            .unwrap(),
+        });
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(staking_token, &[]),
+            msg,
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::DepositReward {
+            rewards: vec![RewardMsg {
+                staking_token: Addr::unchecked(staking_token),
+                total_accumulation_amount: Uint128::from(100u128),
+            }],
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("rewarder", &[]), msg).unwrap();
+    }
+
+    let ibc_recipient = IbcRecipient {
+        channel_id: "channel-15".to_string(),
+        remote_address: "cosmos1remote".to_string(),
+        timeout_seconds: 600,
+    };
+
+    // invalid channel id and timeout are rejected
+    for invalid in [
+        IbcRecipient {
+            channel_id: "channel-".to_string(),
+            ..ibc_recipient.clone()
+        },
+        IbcRecipient {
+            channel_id: "connection-0".to_string(),
+            ..ibc_recipient.clone()
+        },
+        IbcRecipient {
+            timeout_seconds: 10,
+            ..ibc_recipient.clone()
+        },
+    ] {
+        let msg = ExecuteMsg::Withdraw {
+            staking_token: Some(Addr::unchecked("staking")),
+            ibc_recipient: Some(invalid),
+            amounts: None,
+            recipient: None,
+            staker_addr: None,
+        };
+        let _err = execute(deps.as_mut(), env.clone(), mock_info("addr", &[]), msg).unwrap_err();
+    }
+
+    // cw20 rewards can not be sent through ibc
+    let msg = ExecuteMsg::Withdraw {
+        staking_token: Some(Addr::unchecked("staking2")),
+        ibc_recipient: Some(ibc_recipient.clone()),
+        amounts: None,
+        recipient: None,
+        staker_addr: None,
+    };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("addr", &[]), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::IbcCw20Reward {
+            contract_addr: "reward".to_string()
+        }
+    );
+
+    let msg = ExecuteMsg::Withdraw {
+        staking_token: Some(Addr::unchecked("staking")),
+        ibc_recipient: Some(ibc_recipient),
+        amounts: None,
+        recipient: None,
+        staker_addr: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Ibc(IbcMsg::Transfer {
+            channel_id: "channel-15".to_string(),
+            to_address: "cosmos1remote".to_string(),
+            amount: coin(100u128, ORAI_DENOM),
+            timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(600)),
+        }))]
+    );
+}
+
+#[test]
+fn test_simulate_withdraw() {
+    let mut deps = mock_dependencies_with_balance(&[
+        coin(10000000000u128, ORAI_DENOM),
+        coin(20000000000u128, ATOM_DENOM),
+    ]);
+    mock_staking_tokens(&mut deps.querier, &["staking", "staking2"]);
+    let mut env = mock_env();
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    let _res = instantiate(deps.as_mut(), env.clone(), mock_info("addr", &[]), msg).unwrap();
+
+    // a continuous pool and an epoch pool
+    for staking_token in ["staking", "staking2"] {
+        let msg = ExecuteMsg::UpdateRewardsPerSec {
+            staking_token: Addr::unchecked(staking_token),
+            assets: vec![
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: 100u128.into(),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: 200u128.into(),
+                },
+            ],
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::RegisterAsset {
+            staking_token: Addr::unchecked(staking_token),
+            min_bond_amount: None,
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    }
+    let msg = ExecuteMsg::UpdatePoolEpoch {
+        staking_token: Addr::unchecked("staking2"),
+        epoch_length: Some(100),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    for staking_token in ["staking", "staking2"] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr".to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                lock_duration: None,
+                referrer: None,
+            })
+            .unwrap(),
+        });
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(staking_token, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    let msg = ExecuteMsg::DepositReward {
+        rewards: vec![
+            RewardMsg {
+                staking_token: Addr::unchecked("staking"),
+                total_accumulation_amount: Uint128::from(301u128),
+            },
+            RewardMsg {
+                staking_token: Addr::unchecked("staking2"),
+                total_accumulation_amount: Uint128::from(150u128),
+            },
+        ],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("rewarder", &[]), msg).unwrap();
+
+    // the epoch has ended but nobody closed it yet
+    env.block.time = env.block.time.plus_seconds(150);
+
+    let res: SimulateWithdrawResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateWithdraw {
+                staker_addr: Addr::unchecked("addr"),
+                staking_token: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    // RewardInfo counts the ended epoch in as well, like the withdraw will
+    let reward_info: RewardInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::RewardInfo {
+                staker_addr: Addr::unchecked("addr"),
+                staking_token: Some(Addr::unchecked("staking2")),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        reward_info.reward_infos[0].pending_reward,
+        Uint128::from(150u128)
+    );
+
+    let msg = ExecuteMsg::Withdraw {
+        staking_token: None,
+        ibc_recipient: None,
+        amounts: None,
+        recipient: None,
+        staker_addr: None,
+    };
+    let withdraw_res = execute(deps.as_mut(), env.clone(), mock_info("addr", &[]), msg).unwrap();
+
+    let sent = withdraw_res
+        .messages
+        .into_iter()
+        .map(|msg| match msg.msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                assert_eq!(to_address, "addr");
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: amount[0].denom.clone(),
+                    },
+                    amount: amount[0].amount,
+                }
+            }
+            _ => panic!("unexpected message"),
+        })
+        .collect::<Vec<Asset>>();
+    assert_eq!(res.reward_assets, sent);
+    assert_eq!(
+        res.reward_assets,
+        vec![
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(150u128),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ATOM_DENOM.to_string(),
+                },
+                amount: Uint128::from(300u128),
+            },
+        ]
+    );
+
+    // nothing left to withdraw
+    let res: SimulateWithdrawResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::SimulateWithdraw {
+                staker_addr: Addr::unchecked("addr"),
+                staking_token: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.reward_assets.iter().all(|ra| ra.amount.is_zero()));
+}
+
+#[test]
+fn test_query_staker_reward_infos() {
+    let test_env = StakingTestEnv::new()
+        .with_pool("staking_a", default_rewards_per_sec())
+        .with_pool("staking_b", default_rewards_per_sec())
+        .with_pool("staking_c", default_rewards_per_sec())
+        .with_bond("addr", "staking_a", 100)
+        .with_bond("addr", "staking_b", 200)
+        .with_bond("addr", "staking_c", 300)
+        .with_reward_deposit("staking_b", 100)
+        .build();
+    let staker_reward_infos = |start_after: Option<Addr>, limit: u32| {
+        test_env.query::<RewardInfoResponse>(QueryMsg::StakerRewardInfos {
+            staker_addr: Addr::unchecked("addr"),
+            start_after,
+            limit: Some(limit),
+            order: None,
+        })
+    };
+
+    // pages settle pending rewards the same way as RewardInfo
+    let all = test_env.reward_info("addr", None);
+    assert_eq!(all.reward_infos.len(), 3);
+    let first_page = staker_reward_infos(None, 2);
+    assert_eq!(first_page.staker_addr, Addr::unchecked("addr"));
+    assert_eq!(first_page.reward_infos, all.reward_infos[..2].to_vec());
+    let second_page =
+        staker_reward_infos(Some(first_page.reward_infos[1].staking_token.clone()), 2);
+    assert_eq!(second_page.reward_infos, all.reward_infos[2..].to_vec());
+    assert!(all
+        .reward_infos
+        .iter()
+        .any(|item| item.pending_reward == Uint128::from(100u128)));
+}
+
+#[test]
+fn test_query_staker_info() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_unbonding_period("staking", 100)
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateConfig {
+                rewarder: None,
+                migrate_store_status: None,
+                penalty_collector: None,
+                reward_distributors: None,
+                withdraw_fee_rate: Some(Decimal::percent(10)),
+                fee_collector: Some(Addr::unchecked("collector")),
+                oracle_addr: None,
+                factory_addr: None,
+                base_denom: None,
+                community_pool: None,
+            },
+        )
+        .unwrap();
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(40u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+
+    let res: StakerInfoResponse = test_env.query(QueryMsg::StakerInfo {
+        staker_addr: Addr::unchecked("addr"),
+        staking_token: Addr::unchecked("staking"),
+    });
+    assert_eq!(res.staking_token, Addr::unchecked("staking"));
+    assert_eq!(res.deprecated_staking_token, None);
+    assert_eq!(res.bond_amount, Uint128::from(60u128));
+    assert_eq!(res.unbonding.len(), 1);
+    assert_eq!(res.claimable_amount, Uint128::zero());
+
+    // the query nets the fee the same way the withdraw does
+    let res_withdraw = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    let (mut sent, mut fees) = (vec![], vec![]);
+    for msg in res_withdraw.messages {
+        match msg.msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                let asset = Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: amount[0].denom.clone(),
+                    },
+                    amount: amount[0].amount,
+                };
+                if to_address == "collector" {
+                    fees.push(asset);
+                } else {
+                    sent.push(asset);
+                }
+            }
+            _ => panic!("unexpected message"),
+        }
+    }
+    assert_eq!(res.pending_rewards, sent);
+    assert_eq!(res.withdraw_fees, fees);
+
+    test_env.advance_seconds(100);
+    let res: StakerInfoResponse = test_env.query(QueryMsg::StakerInfo {
+        staker_addr: Addr::unchecked("addr"),
+        staking_token: Addr::unchecked("staking"),
+    });
+    assert!(res.pending_rewards.iter().all(|ra| ra.amount.is_zero()));
+    assert_eq!(res.claimable_amount, Uint128::from(40u128));
+}
+
+#[test]
+fn test_query_pool_reward_rate() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_pool("staking2", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    let now = test_env.env.block.time.seconds();
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateRewardWeights {
+                weights: vec![
+                    (Addr::unchecked("staking"), Decimal::one()),
+                    (
+                        Addr::unchecked("staking2"),
+                        Decimal::from_ratio(3u128, 1u128),
+                    ),
+                ],
+            },
+        )
+        .unwrap();
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::AppendDistributionSchedule {
+                schedule: vec![(now, now + 200, Uint128::from(800u128))],
+            },
+        )
+        .unwrap();
+
+    // a single deposit has no interval to average over yet
+    let res: PoolRewardRateResponse = test_env.query(QueryMsg::PoolRewardRate {
+        staking_token: Addr::unchecked("staking"),
+    });
+    assert_eq!(res.scheduled_rewards_per_sec, Decimal::one());
+    assert_eq!(res.deposited_rewards_per_sec, Decimal::zero());
+
+    test_env.advance_seconds(100);
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(200u128),
+                }],
+            },
+        )
+        .unwrap();
+    let res: PoolRewardRateResponse = test_env.query(QueryMsg::PoolRewardRate {
+        staking_token: Addr::unchecked("staking"),
+    });
+    assert_eq!(
+        res,
+        PoolRewardRateResponse {
+            staking_token: Addr::unchecked("staking"),
+            total_bond_amount: Uint128::from(100u128),
+            total_weighted_bond: Uint128::from(100u128),
+            scheduled_rewards_per_sec: Decimal::one(),
+            deposited_rewards_per_sec: Decimal::from_ratio(2u128, 1u128),
+            rewards_per_sec: Decimal::from_ratio(3u128, 1u128),
+            rewards_per_sec_per_bond: Some(Decimal::percent(3)),
+        }
+    );
+
+    // nothing bonded, the rate per bond is undefined
+    let res: PoolRewardRateResponse = test_env.query(QueryMsg::PoolRewardRate {
+        staking_token: Addr::unchecked("staking2"),
+    });
+    assert_eq!(
+        res.scheduled_rewards_per_sec,
+        Decimal::from_ratio(3u128, 1u128)
+    );
+    assert_eq!(res.rewards_per_sec_per_bond, None);
+
+    test_env.advance_seconds(100);
+    let res: PoolRewardRateResponse = test_env.query(QueryMsg::PoolRewardRate {
+        staking_token: Addr::unchecked("staking"),
+    });
+    assert_eq!(res.scheduled_rewards_per_sec, Decimal::zero());
+    assert_eq!(res.deposited_rewards_per_sec, Decimal::one());
+
+    let err = query(
+        test_env.deps.as_ref(),
+        test_env.env.clone(),
+        QueryMsg::PoolRewardRate {
+            staking_token: Addr::unchecked("unknown"),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::PoolNotFound {
+            asset: "unknown".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_query_simulate_bond() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    test_env.advance_seconds(100);
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(200u128),
+                }],
+            },
+        )
+        .unwrap();
+
+    let res: SimulateBondResponse = test_env.query(QueryMsg::SimulateBond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Uint128::from(100u128),
+    });
+    assert_eq!(
+        res,
+        SimulateBondResponse {
+            staking_token: Addr::unchecked("staking"),
+            amount: Uint128::from(100u128),
+            pool_share: Decimal::percent(50),
+            daily_reward: Uint128::from(86400u128),
+            rewards_per_sec_per_bond: Some(Decimal::percent(2)),
+            rewards_per_sec_per_bond_after: Decimal::percent(1),
+        }
+    );
+    // the rate before the bond is the one PoolRewardRate reports
+    let rate: PoolRewardRateResponse = test_env.query(QueryMsg::PoolRewardRate {
+        staking_token: Addr::unchecked("staking"),
+    });
+    assert_eq!(rate.rewards_per_sec_per_bond, res.rewards_per_sec_per_bond);
+
+    let simulate = |staking_token: &str, amount: u128| {
+        query(
+            test_env.deps.as_ref(),
+            test_env.env.clone(),
+            QueryMsg::SimulateBond {
+                staking_token: Addr::unchecked(staking_token),
+                amount: Uint128::from(amount),
+            },
+        )
+        .unwrap_err()
+    };
+    assert_eq!(simulate("staking", 0), ContractError::InvalidZeroAmount {});
+    assert_eq!(
+        simulate("unknown", 100),
+        ContractError::PoolNotFound {
+            asset: "unknown".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_query_state() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_pool("staking2", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    let now = test_env.env.block.time.seconds();
+    let assets = |orai: u128, atom: u128| {
+        vec![
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(orai),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ATOM_DENOM.to_string(),
+                },
+                amount: Uint128::from(atom),
+            },
+        ]
+    };
+
+    let res: StateResponse = test_env.query(QueryMsg::State {});
+    assert_eq!(
+        res,
+        StateResponse {
+            pool_count: 2,
+            total_deposited: Uint128::from(300u128),
+            total_scheduled: Uint128::zero(),
+            total_withdrawn: Uint128::zero(),
+            // the deposit is owed at the 100 orai / 200 atom rates of the pool
+            total_outstanding_rewards: assets(100, 200),
+            reward_reserves: assets(10000000000, 20000000000),
+            pending_reward: Uint128::zero(),
+            last_deposit_time: Some(now),
+        }
+    );
+
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateRewardWeights {
+                weights: vec![(Addr::unchecked("staking"), Decimal::one())],
+            },
+        )
+        .unwrap();
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::AppendDistributionSchedule {
+                schedule: vec![(now, now + 100, Uint128::from(100u128))],
+            },
+        )
+        .unwrap();
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UnregisterAsset {
+                staking_token: Addr::unchecked("staking2"),
+            },
+        )
+        .unwrap();
+    test_env.advance_seconds(100);
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: None,
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+
+    // the schedule added orai 33 and atom 66, the position was the only one, so nothing is owed
+    let res: StateResponse = test_env.query(QueryMsg::State {});
+    assert_eq!(
+        res,
+        StateResponse {
+            pool_count: 1,
+            total_deposited: Uint128::from(300u128),
+            total_scheduled: Uint128::from(100u128),
+            total_withdrawn: Uint128::from(399u128),
+            total_outstanding_rewards: vec![],
+            reward_reserves: vec![],
+            pending_reward: Uint128::zero(),
+            last_deposit_time: Some(now),
+        }
+    );
+}
+
+#[test]
+fn test_query_state_pending_reward() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_pool("staking2", default_rewards_per_sec())
+        .with_reward_deposit("staking", 300)
+        .with_reward_deposit("staking2", 100)
+        .build();
+
+    // nothing is bonded, so both deposits wait in the pools
+    let res: StateResponse = test_env.query(QueryMsg::State {});
+    assert_eq!(res.pending_reward, Uint128::from(400u128));
+
+    test_env
+        .execute(
+            "staking",
+            bond_receive_msg("addr", Uint128::from(100u128), None).unwrap(),
+        )
+        .unwrap();
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(100u128),
+                }],
+            },
+        )
+        .unwrap();
+
+    // the bonded pool handed its pending reward to the position, staking2 still holds its own
+    assert_eq!(
+        test_env.pool_info("staking").pending_reward,
+        Uint128::zero()
+    );
+    let res: StateResponse = test_env.query(QueryMsg::State {});
+    assert_eq!(res.pending_reward, Uint128::from(100u128));
+    assert_eq!(
+        res.pending_reward,
+        test_env.pool_info("staking2").pending_reward
+    );
+}
+
+#[test]
+fn test_insufficient_reward_reserves() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    // a rescue took out more than the claims left
+    test_env.deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![coin(50u128, ORAI_DENOM), coin(200u128, ATOM_DENOM)],
+    );
+
+    // each asset is held against what is owed of it
+    let assets = |orai: u128, atom: u128| {
+        vec![
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(orai),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ATOM_DENOM.to_string(),
+                },
+                amount: Uint128::from(atom),
+            },
+        ]
+    };
+    let res: StateResponse = test_env.query(QueryMsg::State {});
+    assert_eq!(res.total_outstanding_rewards, assets(100, 200));
+    assert_eq!(res.reward_reserves, assets(50, 200));
+
+    // the 100 orai share of the claim is not covered, the atom share is
+    let err = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: None,
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientRewardReserves {
+            asset: format!("100{}", ORAI_DENOM),
+            balance: Uint128::from(50u128),
+        }
+    );
+}
+
+#[test]
+fn test_query_batch_reward_info() {
+    let test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_bond("addr2", "staking", 300)
+        .with_reward_deposit("staking", 400)
+        .build();
+
+    // the output lines up with the input, a staker without a position comes back as zeros
+    let res: BatchRewardInfoResponse = test_env.query(QueryMsg::BatchRewardInfo {
+        stakers: vec![
+            Addr::unchecked("addr2"),
+            Addr::unchecked("nobody"),
+            Addr::unchecked("addr"),
+        ],
+        staking_token: Addr::unchecked("staking"),
+    });
+    assert_eq!(
+        res,
+        BatchRewardInfoResponse {
+            staking_token: Addr::unchecked("staking"),
+            reward_infos: vec![
+                StakerRewardInfo {
+                    staker_addr: Addr::unchecked("addr2"),
+                    bond_amount: Uint128::from(300u128),
+                    pending_reward: Uint128::from(300u128),
+                    pending_withdraw: vec![],
+                },
+                StakerRewardInfo {
+                    staker_addr: Addr::unchecked("nobody"),
+                    bond_amount: Uint128::zero(),
+                    pending_reward: Uint128::zero(),
+                    pending_withdraw: vec![],
+                },
+                StakerRewardInfo {
+                    staker_addr: Addr::unchecked("addr"),
+                    bond_amount: Uint128::from(100u128),
+                    pending_reward: Uint128::from(100u128),
+                    pending_withdraw: vec![],
+                },
+            ],
+        }
+    );
+
+    let batch_reward_info = |stakers: Vec<Addr>, staking_token: &str| {
+        query(
+            test_env.deps.as_ref(),
+            test_env.env.clone(),
+            QueryMsg::BatchRewardInfo {
+                stakers,
+                staking_token: Addr::unchecked(staking_token),
+            },
+        )
+        .unwrap_err()
+    };
+    assert_eq!(
+        batch_reward_info(vec![Addr::unchecked("addr"); 31], "staking"),
+        ContractError::TooManyStakers { max: 30 }
+    );
+    assert_eq!(
+        batch_reward_info(vec![Addr::unchecked("addr")], "unknown"),
+        ContractError::PoolNotFound {
+            asset: "unknown".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_total_claimed() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    let total_claimed = |test_env: &StakingTestEnv| {
+        test_env.reward_info("addr", Some("staking")).reward_infos[0].total_claimed
+    };
+    assert_eq!(total_claimed(&test_env), Uint128::zero());
+
+    let withdraw = |amounts: Option<Vec<Asset>>| ExecuteMsg::Withdraw {
+        staking_token: Some(Addr::unchecked("staking")),
+        ibc_recipient: None,
+        amounts,
+        recipient: None,
+        staker_addr: None,
+    };
+    test_env.execute("addr", withdraw(None)).unwrap();
+    assert_eq!(total_claimed(&test_env), Uint128::from(298u128));
+
+    // a partial withdraw counts only the requested amounts
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(300u128),
+                }],
+            },
+        )
+        .unwrap();
+    test_env
+        .execute(
+            "addr",
+            withdraw(Some(vec![Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(50u128),
+            }])),
+        )
+        .unwrap();
+    assert_eq!(total_claimed(&test_env), Uint128::from(348u128));
+}
+
+#[test]
+fn test_query_reward_index_history() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    let start = test_env.env.block.time.seconds();
+    let deposit = |test_env: &mut StakingTestEnv, amount: u128| {
+        test_env
+            .execute(
+                "rewarder",
+                ExecuteMsg::DepositReward {
+                    rewards: vec![RewardMsg {
+                        staking_token: Addr::unchecked("staking"),
+                        total_accumulation_amount: Uint128::from(amount),
+                    }],
+                },
+            )
+            .unwrap();
+    };
+    let history = |test_env: &StakingTestEnv, start_after_time: Option<u64>| {
+        test_env.query::<Vec<RewardIndexHistoryItem>>(QueryMsg::RewardIndexHistory {
+            staking_token: Addr::unchecked("staking"),
+            start_after_time,
+            limit: None,
+        })
+    };
+
+    deposit(&mut test_env, 100);
+    test_env.advance_seconds(10);
+    // the second deposit of the block overwrites its snapshot
+    deposit(&mut test_env, 100);
+    deposit(&mut test_env, 100);
+    assert_eq!(
+        history(&test_env, None),
+        vec![
+            RewardIndexHistoryItem {
+                time: start,
+                reward_index: Decimal::one(),
+                total_bond_amount: Uint128::from(100u128),
+                deposit_epoch: 1,
+            },
+            RewardIndexHistoryItem {
+                time: start + 10,
+                reward_index: Decimal::from_ratio(3u128, 1u128),
+                total_bond_amount: Uint128::from(100u128),
+                deposit_epoch: 3,
+            },
+        ]
+    );
+    assert_eq!(history(&test_env, Some(start)).len(), 1);
+
+    // past the bound every new snapshot evicts the oldest
+    for _ in 0..MAX_REWARD_INDEX_SNAPSHOTS {
+        test_env.advance_seconds(10);
+        deposit(&mut test_env, 100);
+    }
+    let pool_key = test_env.deps.api.addr_canonicalize("staking").unwrap();
+    assert_eq!(
+        read_reward_index_history_len(&test_env.deps.storage, pool_key.as_slice()).unwrap(),
+        MAX_REWARD_INDEX_SNAPSHOTS
+    );
+    assert_eq!(history(&test_env, None)[0].time, start + 20);
+}
+
+#[test]
+fn test_deposit_epoch() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", reward_token_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateRewardWeights {
+                weights: vec![(Addr::unchecked("staking"), Decimal::one())],
+            },
+        )
+        .unwrap();
+    let deposit_msg = |expected_epoch: Option<u64>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "rewarder".to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::DepositReward {
+                stream_over_seconds: None,
+                expected_epoch,
+            })
+            .unwrap(),
+        })
+    };
+
+    let res = test_env
+        .execute("reward_token", deposit_msg(Some(1)))
+        .unwrap();
+    assert!(res.attributes.contains(&attr("deposit_epoch", "1")));
+    assert_eq!(test_env.pool_info("staking").deposit_epoch, 1);
+
+    // the same run sent twice is refused, the pool is left as it was
+    let err = test_env
+        .execute("reward_token", deposit_msg(Some(1)))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnexpectedDepositEpoch {
+            staking_token: "staking".to_string(),
+            expected: 1,
+            next: 2,
+        }
+    );
+    test_env.assert_pending("addr", "staking", 100);
+
+    // every deposit path numbers the deposits of the pool
+    test_env.advance_seconds(10);
+    let res = test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(100u128),
+                }],
+            },
+        )
+        .unwrap();
+    assert!(res.attributes.contains(&attr("deposit_epoch", "2")));
+    test_env.advance_seconds(10);
+    test_env.execute("reward_token", deposit_msg(None)).unwrap();
+
+    let history: Vec<RewardIndexHistoryItem> = test_env.query(QueryMsg::RewardIndexHistory {
+        staking_token: Addr::unchecked("staking"),
+        start_after_time: None,
+        limit: None,
+    });
+    assert_eq!(
+        history
+            .iter()
+            .map(|item| item.deposit_epoch)
+            .collect::<Vec<u64>>(),
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn test_small_deposits_into_large_pool() {
+    let mut builder = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 600_000_000_000)
+        .with_bond("addr2", "staking", 400_000_000_000);
+    for _ in 0..100 {
+        builder = builder.with_reward_deposit("staking", 1);
+    }
+    let test_env = builder.build();
+
+    // each deposit moves the index by 10^-12, none of it is left behind
+    assert_eq!(
+        test_env.pool_info("staking").reward_index,
+        Decimal::from_ratio(100u128, 1_000_000_000_000u128)
+    );
+    test_env.assert_pending("addr", "staking", 60);
+    test_env.assert_pending("addr2", "staking", 40);
+}
+
+#[test]
+fn test_deposit_reward_overflow() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking_a", default_rewards_per_sec())
+        .with_pool("staking_b", default_rewards_per_sec())
+        .with_pool("staking_c", default_rewards_per_sec())
+        .with_bond("addr", "staking_a", 100)
+        .build();
+    let deposit = |staking_token: &str, amount: Uint128| RewardMsg {
+        staking_token: Addr::unchecked(staking_token),
+        total_accumulation_amount: amount,
+    };
+    let half = Uint128::MAX / Uint128::from(2u128) + Uint128::one();
+
+    let err = test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![deposit("staking_a", half), deposit("staking_b", half)],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("reward sum overflow"))
+    );
+
+    // nothing is bonded to staking_c, so its deposits add up in the pending reward
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![deposit("staking_c", Uint128::MAX)],
+            },
+        )
+        .unwrap();
+    let err = test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![deposit("staking_c", Uint128::one())],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("reward sum overflow"))
+    );
+}
+
+#[test]
+fn test_zero_reward_deposit() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+
+    let msg = ExecuteMsg::DepositReward {
+        rewards: vec![RewardMsg {
+            staking_token: Addr::unchecked("staking"),
+            total_accumulation_amount: Uint128::zero(),
+        }],
+    };
+    let err = test_env.execute("rewarder", msg).unwrap_err();
+    assert_eq!(err, ContractError::ZeroRewardDeposit {});
+
+    // deposited through the reward token, split by the reward weights
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "rewarder".to_string(),
+        amount: Uint128::zero(),
+        msg: to_binary(&Cw20HookMsg::DepositReward {
+            stream_over_seconds: None,
+            expected_epoch: None,
+        })
+        .unwrap(),
+    });
+    let err = test_env.execute("reward_token", msg).unwrap_err();
+    assert_eq!(err, ContractError::ZeroCw20HookAmount {});
+    test_env.assert_pending("addr", "staking", 0);
+}
+
+#[test]
+fn test_deposit_reward_to_unregistered_pool() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking_a", default_rewards_per_sec())
+        .with_pool("staking_c", default_rewards_per_sec())
+        .with_bond("addr", "staking_a", 100)
+        .with_bond("addr", "staking_c", 100)
+        .build();
+    let pool_a = test_env.pool_info("staking_a");
+    let pool_c = test_env.pool_info("staking_c");
+
+    let msg = ExecuteMsg::DepositReward {
+        rewards: ["staking_a", "bogus", "staking_c"]
+            .iter()
+            .map(|staking_token| RewardMsg {
+                staking_token: Addr::unchecked(*staking_token),
+                total_accumulation_amount: Uint128::from(100u128),
+            })
+            .collect(),
+    };
+    let err = test_env.execute("rewarder", msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::PoolNotFound {
+            asset: "bogus".to_string(),
+        }
+    );
+
+    // the entry before the bogus one was not applied either, and no pool was created
+    assert_eq!(test_env.pool_info("staking_a"), pool_a);
+    assert_eq!(test_env.pool_info("staking_c"), pool_c);
+    assert!(query(
+        test_env.deps.as_ref(),
+        test_env.env.clone(),
+        QueryMsg::PoolInfo {
+            staking_token: Addr::unchecked("bogus"),
+        },
+    )
+    .is_err());
+}
+
+#[test]
+fn test_simulate_withdraw_matches_withdraw() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking_a", default_rewards_per_sec())
+        .with_pool("staking_b", default_rewards_per_sec())
+        .with_bond("addr", "staking_a", 100)
+        .with_bond("addr", "staking_b", 300)
+        .with_reward_deposit("staking_a", 301)
+        .build();
+    let now = test_env.env.block.time.seconds();
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateConfig {
+                rewarder: None,
+                migrate_store_status: None,
+                penalty_collector: None,
+                reward_distributors: None,
+                withdraw_fee_rate: Some(Decimal::percent(7)),
+                fee_collector: Some(Addr::unchecked("collector")),
+                oracle_addr: None,
+                factory_addr: None,
+                base_denom: None,
+                community_pool: None,
+            },
+        )
+        .unwrap();
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateRewardWeights {
+                weights: vec![
+                    (Addr::unchecked("staking_a"), Decimal::one()),
+                    (Addr::unchecked("staking_b"), Decimal::one()),
+                ],
+            },
+        )
+        .unwrap();
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::AppendDistributionSchedule {
+                schedule: vec![(now, now + 100, Uint128::from(999u128))],
+            },
+        )
+        .unwrap();
+    // the unlocked part of the schedule is only accrued by the withdraw itself
+    test_env.advance_seconds(33);
+
+    let res: SimulateWithdrawResponse = test_env.query(QueryMsg::SimulateWithdraw {
+        staker_addr: Addr::unchecked("addr"),
+        staking_token: None,
+    });
+    let withdraw_res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: None,
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+
+    let withdrawn: WithdrawResponse = from_binary(&withdraw_res.data.unwrap()).unwrap();
+    assert_eq!(res.net_assets, withdrawn.reward_assets);
+    let (mut fees, mut nets) = (vec![], vec![]);
+    for msg in withdraw_res.messages {
+        match msg.msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                let asset = Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: amount[0].denom.clone(),
+                    },
+                    amount: amount[0].amount,
+                };
+                match to_address.as_str() {
+                    "collector" => fees.push(asset),
+                    "addr" => nets.push(asset),
+                    _ => panic!("unexpected recipient {}", to_address),
+                }
+            }
+            _ => panic!("unexpected message"),
+        }
+    }
+    assert!(!fees.is_empty());
+    assert_eq!(res.withdraw_fees, fees);
+    assert_eq!(res.net_assets, nets);
+
+    // the pools add up to the sums the fee is charged on
+    assert_eq!(res.pools.len(), 2);
+    for asset in res.reward_assets.iter() {
+        let pools_total: Uint128 = res
+            .pools
+            .iter()
+            .flat_map(|pool| pool.reward_assets.iter())
+            .filter(|ra| ra.info == asset.info)
+            .map(|ra| ra.amount)
+            .sum();
+        assert_eq!(pools_total, asset.amount);
+    }
+    assert!(withdraw_res.attributes.contains(&attr(
+        "gross",
+        res.reward_assets
+            .iter()
+            .map(|ra| ra.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    )));
+}
+
+#[test]
+fn test_sweep_abandoned_rewards() {
+    let orai_rewards = vec![Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: 100u128.into(),
+    }];
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", orai_rewards)
+        .with_bond("addr", "staking", 100)
+        .with_bond("active", "staking", 200)
+        .with_reward_deposit("staking", 300)
+        .build();
+    let sweep = |min_idle_seconds: u64| ExecuteMsg::SweepAbandonedRewards {
+        staking_token: Addr::unchecked("staking"),
+        stakers: vec![
+            Addr::unchecked("addr"),
+            Addr::unchecked("active"),
+            Addr::unchecked("nobody"),
+        ],
+        min_idle_seconds,
+    };
+    let withdraw = ExecuteMsg::Withdraw {
+        staking_token: Some(Addr::unchecked("staking")),
+        ibc_recipient: None,
+        amounts: None,
+        recipient: None,
+        staker_addr: None,
+    };
+
+    let err = test_env.execute(OWNER, sweep(0)).unwrap_err();
+    assert_eq!(err, ContractError::CommunityPoolNotSet {});
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateConfig {
+                rewarder: None,
+                migrate_store_status: None,
+                penalty_collector: None,
+                reward_distributors: None,
+                withdraw_fee_rate: None,
+                fee_collector: None,
+                oracle_addr: None,
+                factory_addr: None,
+                base_denom: None,
+                community_pool: Some(Addr::unchecked("community")),
+            },
+        )
+        .unwrap();
+    let err = test_env.execute("addr", sweep(0)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the two year floor applies whatever min_idle_seconds asks for
+    let res = test_env.execute(OWNER, sweep(0)).unwrap();
+    assert!(res.messages.is_empty());
+    for staker in ["addr", "active", "nobody"] {
+        assert!(res.attributes.contains(&attr("skipped", staker)));
+    }
+
+    // a withdraw a year in resets the clock of active
+    test_env.advance_seconds(MIN_ABANDONED_IDLE_SECONDS / 2);
+    test_env.execute("active", withdraw.clone()).unwrap();
+    test_env.advance_seconds(MIN_ABANDONED_IDLE_SECONDS / 2);
+    let res = test_env
+        .execute(OWNER, sweep(MIN_ABANDONED_IDLE_SECONDS + 1))
+        .unwrap();
+    assert!(res.messages.is_empty());
+
+    let res = test_env.execute(OWNER, sweep(0)).unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<CosmosMsg>>(),
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            to_address: "community".to_string(),
+            amount: vec![coin(100u128, ORAI_DENOM)],
+        })]
+    );
+    assert!(res.attributes.contains(&attr("staker_addr", "addr")));
+    assert!(res.attributes.contains(&attr("swept", "100orai")));
+    assert!(res.attributes.contains(&attr("skipped", "active")));
+    assert!(res.attributes.contains(&attr("skipped", "nobody")));
+
+    // the bond stays, and the sweep did not count as activity
+    test_env.assert_bond("addr", "staking", 100);
+    let res = test_env.execute(OWNER, sweep(0)).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res.attributes.contains(&attr("swept", "")));
+    let res = test_env.execute("addr", withdraw).unwrap();
+    assert!(res.messages.is_empty());
+
+    // the withdraw was activity of the staker
+    let res = test_env.execute(OWNER, sweep(0)).unwrap();
+    assert!(res.attributes.contains(&attr("skipped", "addr")));
+}
+
+#[test]
+fn test_sweep_abandoned_rewards_reserves() {
+    let orai_rewards = vec![Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: 100u128.into(),
+    }];
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", orai_rewards)
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateConfig {
+                rewarder: None,
+                migrate_store_status: None,
+                penalty_collector: None,
+                reward_distributors: None,
+                withdraw_fee_rate: None,
+                fee_collector: None,
+                oracle_addr: None,
+                factory_addr: None,
+                base_denom: None,
+                community_pool: Some(Addr::unchecked("community")),
+            },
+        )
+        .unwrap();
+    test_env.advance_seconds(MIN_ABANDONED_IDLE_SECONDS);
+    test_env
+        .deps
+        .querier
+        .update_balance(MOCK_CONTRACT_ADDR, vec![coin(50u128, ORAI_DENOM)]);
+
+    // a sweep is a payout like any other, the reserves must cover it
+    let err = test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::SweepAbandonedRewards {
+                staking_token: Addr::unchecked("staking"),
+                stakers: vec![Addr::unchecked("addr")],
+                min_idle_seconds: 0,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientRewardReserves {
+            asset: format!("300{}", ORAI_DENOM),
+            balance: Uint128::from(50u128),
+        }
+    );
+}
+
+#[test]
+fn test_reallocate_orphaned_rewards() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_pool("staking2", default_rewards_per_sec())
+        .with_bond("addr", "staking2", 100)
+        .with_reward_deposit("staking", 100)
+        .build();
+    let orphaned_reward = |test_env: &StakingTestEnv, staking_token: &str| {
+        let res: Vec<OrphanedRewardResponse> = test_env.query(QueryMsg::OrphanedRewards {
+            start_after: None,
+            limit: None,
+            order: None,
+        });
+        res.into_iter()
+            .find(|item| item.staking_token == staking_token)
+            .unwrap()
+            .orphaned_reward
+    };
+    assert_eq!(
+        orphaned_reward(&test_env, "staking"),
+        Uint128::from(100u128)
+    );
+    assert_eq!(orphaned_reward(&test_env, "staking2"), Uint128::zero());
+
+    // the first staker of the empty pool would take the whole deposit at the next one
+    test_env
+        .execute(
+            "staking",
+            bond_receive_msg("bob", Uint128::from(100u128), None).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        orphaned_reward(&test_env, "staking"),
+        Uint128::from(100u128)
+    );
+
+    let reallocate = |to_assets: &[&str]| ExecuteMsg::ReallocateOrphanedRewards {
+        from_asset: Addr::unchecked("staking"),
+        to_assets: to_assets.iter().map(|t| Addr::unchecked(*t)).collect(),
+    };
+    let err = test_env
+        .execute("addr", reallocate(&["staking2"]))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = test_env.execute(OWNER, reallocate(&[])).unwrap_err();
+    assert_eq!(err, ContractError::EmptyReallocationTargets {});
+    let err = test_env
+        .execute(OWNER, reallocate(&["staking2", "staking"]))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidReallocationTarget {
+            asset: "staking".to_string()
+        }
+    );
+
+    let res = test_env.execute(OWNER, reallocate(&["staking2"])).unwrap();
+    assert!(res.attributes.contains(&attr("reallocated", "100")));
+    assert_eq!(orphaned_reward(&test_env, "staking"), Uint128::zero());
+    assert_eq!(
+        test_env.pool_info("staking").pending_reward,
+        Uint128::zero()
+    );
+    test_env.assert_pending("addr", "staking2", 100);
+
+    // the next deposit into the pool only pays what was deposited while bob was bonded
+    let err = test_env
+        .execute(OWNER, reallocate(&["staking2"]))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NoOrphanedReward {
+            asset: "staking".to_string()
+        }
+    );
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(50u128),
+                }],
+            },
+        )
+        .unwrap();
+    test_env.assert_pending("bob", "staking", 50);
+}
+
+#[test]
+fn test_pool_lifetime_reward_counters() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_reward_deposit("staking", 60)
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 240)
+        .build();
+
+    // the deposit made before the bond only counts once it reaches the position
+    let pool_info = test_env.pool_info("staking");
+    assert_eq!(pool_info.lifetime_rewards_deposited, Uint128::from(300u128));
+    assert_eq!(pool_info.lifetime_rewards_claimed, Uint128::zero());
+
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    let res: WithdrawResponse = from_binary(&res.data.unwrap()).unwrap();
+    let claimed: Uint128 = res.reward_assets.iter().map(|asset| asset.amount).sum();
+    assert!(!claimed.is_zero());
+    assert_eq!(
+        test_env.pool_info("staking").lifetime_rewards_claimed,
+        claimed
+    );
+
+    // the counters describe the pool, they move with it to the new staking token
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "new_staking"]);
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::DeprecateStakingToken {
+                staking_token: Addr::unchecked("staking"),
+                new_staking_token: Addr::unchecked("new_staking"),
+                grace_period: None,
+            },
+        )
+        .unwrap();
+    let pool_info = test_env.pool_info("new_staking");
+    assert_eq!(pool_info.lifetime_rewards_deposited, Uint128::from(300u128));
+    assert_eq!(pool_info.lifetime_rewards_claimed, claimed);
+    assert_eq!(pool_info.lifetime_bonded, Uint128::from(100u128));
+}
+
+#[test]
+fn test_max_index_increase_per_deposit() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    let deposit = |amount: u128| {
+        vec![RewardMsg {
+            staking_token: Addr::unchecked("staking"),
+            total_accumulation_amount: Uint128::from(amount),
+        }]
+    };
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdatePoolConfig {
+                staking_token: Addr::unchecked("staking"),
+                params: PoolConfigUpdate {
+                    max_index_increase_per_deposit: Some(Decimal::percent(500)),
+                    ..PoolConfigUpdate::default()
+                },
+            },
+        )
+        .unwrap();
+
+    // up to the limit, 5 per bonded token
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: deposit(500),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        test_env.pool_info("staking").reward_index,
+        Decimal::percent(500)
+    );
+
+    let res = test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: deposit(501),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::RewardIndexIncreaseTooLarge {
+            staking_token: "staking".to_string(),
+            increase: Decimal256::from_ratio(501u128, 100u128),
+            limit: Decimal::percent(500),
+        }
+    );
+
+    // the owner deposit keeps the limit unless forced
+    let res = test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::OwnerDepositReward {
+                rewards: deposit(501),
+                force: true,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    let res = test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::OwnerDepositReward {
+                rewards: deposit(1000),
+                force: false,
+            },
+        )
+        .unwrap_err();
+    assert!(matches!(
+        res,
+        ContractError::RewardIndexIncreaseTooLarge { .. }
+    ));
+    let res = test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::OwnerDepositReward {
+                rewards: deposit(1000),
+                force: true,
+            },
+        )
+        .unwrap();
+    assert!(res.attributes.contains(&attr("force", "true")));
+    assert_eq!(
+        test_env.pool_info("staking").reward_index,
+        Decimal::percent(1500)
+    );
+
+    // zero lifts the limit
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdatePoolConfig {
+                staking_token: Addr::unchecked("staking"),
+                params: PoolConfigUpdate {
+                    max_index_increase_per_deposit: Some(Decimal::zero()),
+                    ..PoolConfigUpdate::default()
+                },
+            },
+        )
+        .unwrap();
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: deposit(1000),
+            },
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_streamed_reward_deposit() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", reward_token_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateRewardWeights {
+                weights: vec![(Addr::unchecked("staking"), Decimal::one())],
+            },
+        )
+        .unwrap();
+    let deposit_msg = |amount: u128, stream_over_seconds: Option<u64>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "rewarder".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::DepositReward {
+                stream_over_seconds,
+                expected_epoch: None,
+            })
+            .unwrap(),
+        })
+    };
+
+    // released as the window runs, the queries see it before anything is stored
+    test_env
+        .execute("reward_token", deposit_msg(1000, Some(100)))
+        .unwrap();
+    test_env.assert_pending("addr", "staking", 0);
+    assert_eq!(
+        test_env.pool_info("staking").streaming_reward,
+        Uint128::from(1000u128)
+    );
+    test_env.advance_seconds(40);
+    test_env.assert_pending("addr", "staking", 400);
+
+    // a bond only shares what runs after it, overlapping streams add up
+    test_env
+        .execute(
+            "staking",
+            bond_receive_msg("addr2", Uint128::from(100u128), None).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        test_env.pool_info("staking").streaming_reward,
+        Uint128::from(600u128)
+    );
+    test_env
+        .execute("reward_token", deposit_msg(200, Some(20)))
+        .unwrap();
+    test_env.advance_seconds(20);
+    test_env.assert_pending("addr", "staking", 600);
+    test_env.assert_pending("addr2", "staking", 200);
+
+    // ended streams are dropped by the next withdraw
+    test_env.advance_seconds(40);
+    test_env.assert_pending("addr", "staking", 800);
+    test_env.assert_pending("addr2", "staking", 400);
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    let asset_key = test_env.deps.api.addr_canonicalize("staking").unwrap();
+    let pool_info = read_pool_info(&test_env.deps.storage, &asset_key).unwrap();
+    assert!(pool_info.reward_streams.is_empty());
+    assert_eq!(
+        test_env.pool_info("staking").streaming_reward,
+        Uint128::zero()
+    );
+
+    // without a window the deposit is added at once
+    test_env
+        .execute("reward_token", deposit_msg(100, None))
+        .unwrap();
+    test_env.assert_pending("addr2", "staking", 450);
+
+    // the pool default applies when the depositor does not say, zero keeps it instant
+    test_env
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdatePoolConfig {
+                staking_token: Addr::unchecked("staking"),
+                params: PoolConfigUpdate {
+                    reward_stream_seconds: Some(10),
+                    ..PoolConfigUpdate::default()
+                },
+            },
+        )
+        .unwrap();
+    test_env
+        .execute("reward_token", deposit_msg(100, None))
+        .unwrap();
+    test_env.assert_pending("addr2", "staking", 450);
+    test_env
+        .execute("reward_token", deposit_msg(100, Some(0)))
+        .unwrap();
+    test_env.assert_pending("addr2", "staking", 500);
+    test_env.advance_seconds(10);
+    test_env.assert_pending("addr2", "staking", 550);
+
+    // deposits over the same window in one block merge, the others are bounded
+    for stream_seconds in 1..=MAX_REWARD_STREAMS as u64 {
+        test_env
+            .execute("reward_token", deposit_msg(100, Some(stream_seconds)))
+            .unwrap();
+    }
+    test_env
+        .execute("reward_token", deposit_msg(100, Some(1)))
+        .unwrap();
+    let err = test_env
+        .execute(
+            "reward_token",
+            deposit_msg(100, Some(MAX_REWARD_STREAMS as u64 + 1)),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TooManyRewardStreams {
+            max: MAX_REWARD_STREAMS,
+        }
+    );
+}
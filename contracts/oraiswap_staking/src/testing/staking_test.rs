@@ -1,20 +1,30 @@
-use crate::contract::{execute, instantiate, query, query_get_pools_infomation};
-use crate::state::{store_pool_info, PoolInfo};
-use cosmwasm_std::testing::{
-    mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
+use crate::contract::{query_get_pools_infomation, reply, HOOK_REPLY_ID};
+use crate::staking::MAX_UNBONDING_ENTRIES;
+use crate::state::{
+    rewards_read, rewards_store, stakers_store, store_pool_info, PoolInfo, RewardInfo,
 };
+use crate::testing::env::{default_rewards_per_sec, mock_staking_tokens, StakingTestEnv};
+use cosmwasm_std::testing::{mock_dependencies, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, coin, from_binary, to_binary, Addr, Api, BankMsg, Coin, CosmosMsg, Decimal, StdError,
-    SubMsg, Uint128, WasmMsg,
+    attr, coin, coins, from_binary, to_binary, Addr, Api, BankMsg, Binary, Coin, ContractResult,
+    CosmosMsg, Decimal, Decimal256, Order, OverflowError, OverflowOperation, Reply, Response,
+    Storage, SubMsg, SubMsgResult, SystemError, SystemResult, Uint128, WasmMsg, WasmQuery,
 };
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
 use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
 use oraiswap::create_entry_points_testing;
+use oraiswap::error::ContractError;
 use oraiswap::pair::PairResponse;
 use oraiswap::staking::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolInfoResponse, QueryMsg, RewardInfoResponse,
-    RewardInfoResponseItem, RewardMsg,
+    BondAmountAtResponse, BondResponse, BoostQueryMsg, CanBondResponse, CapUpdate, ConfigResponse,
+    Cw20HookMsg, ExecuteMsg, ExportRecord, ExportSegment, ExportStateResponse, InstantiateMsg,
+    LockTier, OperatorResponse, PoolConfigUpdate, PoolInfoResponse, QueryMsg, ReconcileResponse,
+    ReferralStatsResponse, RewardInfoResponse, RewardInfoResponseItem, RewardMsg, StakersResponse,
+    StakingHookMsg, StaleStaker, StaleStakersResponse, UnbondResponse, UnbondSendMsg,
+    UnbondingClaim, UnbondingClaimsResponse, UnbondingEntry, UnbondingResponse,
+    VotingPowerResponse, WithdrawResponse,
 };
+use oraiswap::testing::staking::{bond_receive_msg, StakingQuerier};
 use oraiswap::testing::{AttributeUtil, MockApp, ATOM_DENOM};
 
 #[test]
@@ -46,8 +56,40 @@ fn test_query_all_pool_keys() {
             staking_token: staking_token.clone(),
             pending_reward: amount.clone(),
             total_bond_amount: amount.clone(),
-            reward_index: Decimal::zero(),
+            reward_index: Decimal256::zero(),
             migration_params: None,
+            transition_staking_tokens: vec![],
+            reward_decimals: vec![],
+            unbonding_period: 0,
+            frozen: false,
+            instant_unbond_penalty: Decimal::zero(),
+            min_bond_amount: Uint128::zero(),
+            min_compound_reward: Uint128::zero(),
+            bond_cap: None,
+            max_bond_per_staker: None,
+            lock_tiers: vec![],
+            total_lock_boost: Uint128::zero(),
+            total_boost_shortfall: Uint128::zero(),
+            reward_weight: Decimal::zero(),
+            reward_start_time: None,
+            reward_end_time: None,
+            proxy_rewards: vec![],
+            symbol: None,
+            staker_count: None,
+            lifetime_bonded: Uint128::zero(),
+            lifetime_unbonded: Uint128::zero(),
+            lifetime_rewards_deposited: Uint128::zero(),
+            lifetime_rewards_claimed: Uint128::zero(),
+            whitelist_enabled: false,
+            whitelist_len: 0,
+            blacklist_len: 0,
+            orphaned_reward: Uint128::zero(),
+            max_index_increase_per_deposit: Decimal::zero(),
+            unbond_cooldown: 0,
+            reward_streams: vec![],
+            last_streamed: 0,
+            reward_stream_seconds: 0,
+            deposit_epoch: 0,
         };
         store_pool_info(storage, &asset_key, &pool_info).unwrap();
     }
@@ -66,45 +108,12 @@ fn test_query_all_pool_keys() {
 
 #[test]
 fn test_bond_tokens() {
-    let mut deps = mock_dependencies();
-
-    let msg = InstantiateMsg {
-        owner: Some(Addr::unchecked("owner")),
-        rewarder: Addr::unchecked("rewarder"),
-        minter: Some(Addr::unchecked("mint")),
-        oracle_addr: Addr::unchecked("oracle"),
-        factory_addr: Addr::unchecked("factory"),
-        base_denom: None,
-    };
-
-    let info = mock_info("addr", &[]);
-    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    let msg = ExecuteMsg::RegisterAsset {
-        staking_token: Addr::unchecked("staking"),
-    };
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
 
-    let info = mock_info("owner", &[]);
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr".to_string(),
-        amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
-    });
-
-    let info = mock_info("staking", &[]);
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    let data = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::RewardInfo {
-            staking_token: Some(Addr::unchecked("staking")),
-            staker_addr: Addr::unchecked("addr"),
-        },
-    )
-    .unwrap();
-    let res: RewardInfoResponse = from_binary(&data).unwrap();
+    let res = test_env.reward_info("addr", Some("staking"));
     assert_eq!(
         res,
         RewardInfoResponse {
@@ -113,31 +122,54 @@ fn test_bond_tokens() {
                 staking_token: Addr::unchecked("staking"),
                 pending_reward: Uint128::zero(),
                 pending_withdraw: vec![],
+                reward_decimals: vec![],
                 bond_amount: Uint128::from(100u128),
+                weighted_bond_amount: Uint128::from(100u128),
+                locks: vec![],
                 should_migrate: None,
+                total_claimed: Uint128::zero(),
+                last_updated: test_env.env.block.time.seconds(),
             }],
         }
     );
 
-    let data = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::PoolInfo {
-            staking_token: Addr::unchecked("staking"),
-        },
-    )
-    .unwrap();
-
-    let pool_info: PoolInfoResponse = from_binary(&data).unwrap();
     assert_eq!(
-        pool_info,
+        test_env.pool_info("staking"),
         PoolInfoResponse {
             staking_token: Addr::unchecked("staking"),
             total_bond_amount: Uint128::from(100u128),
             reward_index: Decimal::zero(),
             pending_reward: Uint128::zero(),
             migration_deprecated_staking_token: None,
+            migration_deprecated_total_bond: None,
+            migration_grace_period_end: None,
             migration_index_snapshot: None,
+            frozen: false,
+            instant_unbond_penalty: Decimal::zero(),
+            min_bond_amount: Uint128::zero(),
+            min_compound_reward: Uint128::zero(),
+            bond_cap: None,
+            max_bond_per_staker: None,
+            remaining_bond_capacity: None,
+            total_weighted_bond: Uint128::from(100u128),
+            lock_tiers: vec![],
+            reward_weight: Decimal::zero(),
+            reward_start_time: None,
+            reward_end_time: None,
+            proxy_rewards: vec![],
+            symbol: Some("STAKING".to_string()),
+            staker_count: Some(1),
+            lifetime_bonded: Uint128::from(100u128),
+            lifetime_unbonded: Uint128::zero(),
+            lifetime_rewards_deposited: Uint128::zero(),
+            lifetime_rewards_claimed: Uint128::zero(),
+            unbonding_period: 0,
+            whitelist_enabled: false,
+            unbond_cooldown: 0,
+            max_index_increase_per_deposit: Decimal::zero(),
+            reward_stream_seconds: 0,
+            streaming_reward: Uint128::zero(),
+            deposit_epoch: 0,
         }
     );
 
@@ -145,98 +177,64 @@ fn test_bond_tokens() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr2".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
     });
-    let info = mock_info("staking", &[]);
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    test_env.execute("staking", msg).unwrap();
 
-    let data = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::PoolInfo {
-            staking_token: Addr::unchecked("staking"),
-        },
-    )
-    .unwrap();
-    let pool_info: PoolInfoResponse = from_binary(&data).unwrap();
     assert_eq!(
-        pool_info,
+        test_env.pool_info("staking"),
         PoolInfoResponse {
             staking_token: Addr::unchecked("staking"),
             total_bond_amount: Uint128::from(200u128),
             reward_index: Decimal::zero(),
             pending_reward: Uint128::zero(),
             migration_deprecated_staking_token: None,
+            migration_deprecated_total_bond: None,
+            migration_grace_period_end: None,
             migration_index_snapshot: None,
+            frozen: false,
+            instant_unbond_penalty: Decimal::zero(),
+            min_bond_amount: Uint128::zero(),
+            min_compound_reward: Uint128::zero(),
+            bond_cap: None,
+            max_bond_per_staker: None,
+            remaining_bond_capacity: None,
+            total_weighted_bond: Uint128::from(200u128),
+            lock_tiers: vec![],
+            reward_weight: Decimal::zero(),
+            reward_start_time: None,
+            reward_end_time: None,
+            proxy_rewards: vec![],
+            symbol: Some("STAKING".to_string()),
+            staker_count: Some(2),
+            lifetime_bonded: Uint128::from(200u128),
+            lifetime_unbonded: Uint128::zero(),
+            lifetime_rewards_deposited: Uint128::zero(),
+            lifetime_rewards_claimed: Uint128::zero(),
+            unbonding_period: 0,
+            whitelist_enabled: false,
+            unbond_cooldown: 0,
+            max_index_increase_per_deposit: Decimal::zero(),
+            reward_stream_seconds: 0,
+            streaming_reward: Uint128::zero(),
+            deposit_epoch: 0,
         }
     );
+    test_env.assert_bond("addr2", "staking", 100);
 }
 
 #[test]
 fn test_unbond() {
-    let mut deps = mock_dependencies_with_balance(&[
-        coin(10000000000u128, ORAI_DENOM),
-        coin(20000000000u128, ATOM_DENOM),
-    ]);
-
-    let msg = InstantiateMsg {
-        owner: Some(Addr::unchecked("owner")),
-        rewarder: Addr::unchecked("rewarder"),
-        minter: Some(Addr::unchecked("mint")),
-        oracle_addr: Addr::unchecked("oracle"),
-        factory_addr: Addr::unchecked("factory"),
-        base_denom: None,
-    };
-
-    let info = mock_info("addr", &[]);
-    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    // will also add to the index the pending rewards from before the migration
-    let msg = ExecuteMsg::UpdateRewardsPerSec {
-        staking_token: Addr::unchecked("staking"),
-        assets: vec![
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: ORAI_DENOM.to_string(),
-                },
-                amount: 100u128.into(),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: ATOM_DENOM.to_string(),
-                },
-                amount: 200u128.into(),
-            },
-        ],
-    };
-    let info = mock_info("owner", &[]);
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    // register asset
-    let msg = ExecuteMsg::RegisterAsset {
-        staking_token: Addr::unchecked("staking"),
-    };
-
-    let info = mock_info("owner", &[]);
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    // bond 100 tokens
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr".to_string(),
-        amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
-    });
-    let info = mock_info("staking", &[]);
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    let msg = ExecuteMsg::DepositReward {
-        rewards: vec![RewardMsg {
-            staking_token: Addr::unchecked("staking"),
-            total_accumulation_amount: Uint128::from(300u128),
-        }],
-    };
-    let info = mock_info("rewarder", &[]);
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    test_env.assert_pending("addr", "staking", 300);
 
     // will also add to the index the pending rewards from before the migration
     let msg = ExecuteMsg::UpdateRewardsPerSec {
@@ -256,32 +254,40 @@ fn test_unbond() {
             },
         ],
     };
-    let info = mock_info("owner", &[]);
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    test_env.execute("owner", msg).unwrap();
 
     // unbond 150 tokens; failed
     let msg = ExecuteMsg::Unbond {
         staking_token: Addr::unchecked("staking"),
-        amount: Uint128::from(150u128),
+        amount: Some(Uint128::from(150u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
     };
-
-    let info = mock_info("addr", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => {
-            assert_eq!(msg, "Cannot unbond more than bond amount");
+    let res = test_env.execute("addr", msg).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::InsufficientBond {
+            staker: "addr".to_string(),
+            asset: "staking".to_string(),
+            available: Uint128::from(100u128),
+            requested: Uint128::from(150u128),
         }
-        _ => panic!("Must return generic error"),
-    };
+    );
+    assert_eq!(
+        res.to_string(),
+        "Cannot unbond 150: only 100 bonded by addr for staking"
+    );
 
     // normal unbond
     let msg = ExecuteMsg::Unbond {
         staking_token: Addr::unchecked("staking"),
-        amount: Uint128::from(100u128),
+        amount: Some(Uint128::from(100u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
     };
-
-    let info = mock_info("addr", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = test_env.execute("addr", msg).unwrap();
     assert_eq!(
         res.messages,
         vec![
@@ -305,39 +311,48 @@ fn test_unbond() {
         ]
     );
 
-    let data = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::PoolInfo {
-            staking_token: Addr::unchecked("staking"),
-        },
-    )
-    .unwrap();
-    let pool_info: PoolInfoResponse = from_binary(&data).unwrap();
     assert_eq!(
-        pool_info,
+        test_env.pool_info("staking"),
         PoolInfoResponse {
             staking_token: Addr::unchecked("staking"),
             total_bond_amount: Uint128::zero(),
             reward_index: Decimal::from_ratio(300u128, 100u128),
             pending_reward: Uint128::zero(),
             migration_deprecated_staking_token: None,
+            migration_deprecated_total_bond: None,
+            migration_grace_period_end: None,
             migration_index_snapshot: None,
+            frozen: false,
+            instant_unbond_penalty: Decimal::zero(),
+            min_bond_amount: Uint128::zero(),
+            min_compound_reward: Uint128::zero(),
+            bond_cap: None,
+            max_bond_per_staker: None,
+            remaining_bond_capacity: None,
+            total_weighted_bond: Uint128::zero(),
+            lock_tiers: vec![],
+            reward_weight: Decimal::zero(),
+            reward_start_time: None,
+            reward_end_time: None,
+            proxy_rewards: vec![],
+            symbol: Some("STAKING".to_string()),
+            staker_count: Some(0),
+            lifetime_bonded: Uint128::from(100u128),
+            lifetime_unbonded: Uint128::from(100u128),
+            lifetime_rewards_deposited: Uint128::from(300u128),
+            lifetime_rewards_claimed: Uint128::from(298u128),
+            unbonding_period: 0,
+            whitelist_enabled: false,
+            unbond_cooldown: 0,
+            max_index_increase_per_deposit: Decimal::zero(),
+            reward_stream_seconds: 0,
+            streaming_reward: Uint128::zero(),
+            deposit_epoch: 1,
         }
     );
 
-    let data = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::RewardInfo {
-            staking_token: None,
-            staker_addr: Addr::unchecked("addr"),
-        },
-    )
-    .unwrap();
-    let res: RewardInfoResponse = from_binary(&data).unwrap();
     assert_eq!(
-        res,
+        test_env.reward_info("addr", None),
         RewardInfoResponse {
             staker_addr: Addr::unchecked("addr"),
             reward_infos: vec![],
@@ -346,276 +361,4093 @@ fn test_unbond() {
 }
 
 #[test]
-fn test_auto_stake() {
-    let mut app = MockApp::new(&[(&"addr".to_string(), &[coin(10000000000u128, ORAI_DENOM)])]);
-
-    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
-
-    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+fn test_unbond_all() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_bond("addr", "staking", 30)
+        .build();
 
-    app.set_factory_and_pair_contract(
-        Box::new(
-            create_entry_points_testing!(oraiswap_factory)
-                .with_reply(oraiswap_factory::contract::reply),
-        ),
-        Box::new(
-            create_entry_points_testing!(oraiswap_pair).with_reply(oraiswap_pair::contract::reply),
-        ),
+    let msg = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: None,
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let res = test_env.execute("addr", msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr".to_string(),
+                amount: Uint128::from(130u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    // nothing was pending either, so the position is gone
+    assert_eq!(
+        test_env.reward_info("addr", Some("staking")).reward_infos,
+        vec![]
     );
 
-    let asset_addr = app.create_token("asset");
-    let reward_addr = app.create_token("reward");
-    // update other contract token balance
-    app.set_token_balances(&[
-        (
-            &"reward".to_string(),
-            &[(&"addr".to_string(), &Uint128::from(10000000000u128))],
-        ),
-        (
-            &"asset".to_string(),
-            &[(&"addr".to_string(), &Uint128::from(10000000000u128))],
-        ),
-    ]);
-
-    let asset_infos = [
-        AssetInfo::NativeToken {
-            denom: ORAI_DENOM.to_string(),
-        },
-        AssetInfo::Token {
-            contract_addr: asset_addr.clone(),
-        },
-    ];
+    let res = test_env.execute("addr", msg).unwrap_err();
+    assert_eq!(res, ContractError::NothingToUnbond {});
+}
 
-    // create pair
-    let pair_addr = app.create_pair(asset_infos.clone()).unwrap();
+#[test]
+fn test_unbond_withdraw_rewards() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
 
-    let PairResponse { info: pair_info } = app
-        .query(pair_addr.clone(), &oraiswap::pair::QueryMsg::Pair {})
-        .unwrap();
+    let msg = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::from(50u128)),
+        withdraw_rewards: Some(true),
+        recipient: None,
+        unbond_msg: None,
+    };
+    let res = test_env.execute("addr", msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "staking".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr".to_string(),
+                    amount: Uint128::from(50u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(99u128, ORAI_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(199u128, ATOM_DENOM)],
+            }))
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unbond"),
+            attr("staker_addr", "addr"),
+            attr("amount", "50"),
+            attr("staking_token", "staking"),
+            attr(
+                "reward_assets",
+                format!("99{}, 199{}", ORAI_DENOM, ATOM_DENOM)
+            ),
+            attr("gross", format!("99{}, 199{}", ORAI_DENOM, ATOM_DENOM)),
+            attr("fee", ""),
+            attr("net", format!("99{}, 199{}", ORAI_DENOM, ATOM_DENOM)),
+        ]
+    );
+    test_env.assert_bond("addr", "staking", 50);
+    test_env.assert_pending("addr", "staking", 0);
 
-    // set allowance
-    app.execute(
-        Addr::unchecked("addr"),
-        asset_addr.clone(),
-        &cw20::Cw20ExecuteMsg::IncreaseAllowance {
-            spender: pair_addr.to_string(),
-            amount: Uint128::from(100u128),
-            expires: None,
-        },
-        &[],
-    )
-    .unwrap();
+    // nothing earned since, so only the staking token goes back
+    let res = test_env.execute("addr", msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.attributes.len(), 4);
+}
 
-    // provide liquidity
-    // successfully provide liquidity for the exist pool
-    let msg = oraiswap::pair::ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: ORAI_DENOM.to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: asset_addr.clone(),
-                },
-                amount: Uint128::from(100u128),
+#[test]
+fn test_unbond_withdraw_rewards_fee() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::UpdateConfig {
+                rewarder: None,
+                migrate_store_status: None,
+                penalty_collector: None,
+                reward_distributors: None,
+                withdraw_fee_rate: Some(Decimal::percent(10)),
+                fee_collector: Some(Addr::unchecked("collector")),
+                oracle_addr: None,
+                factory_addr: None,
+                base_denom: None,
+                community_pool: None,
             },
-        ],
-        slippage_tolerance: None,
-        receiver: None,
-    };
+        )
+        .unwrap();
 
-    let _res = app
+    // the rewards withdrawn with the unbond pay the same fee a Withdraw does
+    let res = test_env
         .execute(
-            Addr::unchecked("addr"),
-            pair_addr.clone(),
-            &msg,
-            &[Coin {
-                denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
-            }],
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(50u128)),
+                withdraw_rewards: Some(true),
+                recipient: None,
+                unbond_msg: None,
+            },
         )
         .unwrap();
+    assert_eq!(
+        res.messages[1..],
+        [
+            SubMsg::new(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: vec![coin(9u128, ORAI_DENOM)],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(90u128, ORAI_DENOM)],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: vec![coin(19u128, ATOM_DENOM)],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(180u128, ATOM_DENOM)],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes[res.attributes.len() - 3..],
+        [
+            attr("gross", format!("99{}, 199{}", ORAI_DENOM, ATOM_DENOM)),
+            attr("fee", format!("9{}, 19{}", ORAI_DENOM, ATOM_DENOM)),
+            attr("net", format!("90{}, 180{}", ORAI_DENOM, ATOM_DENOM)),
+        ]
+    );
+}
 
-    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+#[test]
+fn test_instant_unbond() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_unbonding_period("staking", 100)
+        .with_bond("addr", "staking", 100)
+        .build();
 
-    let msg = InstantiateMsg {
-        owner: Some(Addr::unchecked("owner")),
-        rewarder: reward_addr.clone(),
-        minter: Some(Addr::unchecked("mint")),
-        oracle_addr: app.oracle_addr.clone(),
-        factory_addr: app.factory_addr.clone(),
-        base_denom: None,
+    let penalty_msg = |penalty_rate| ExecuteMsg::UpdateInstantUnbondPenalty {
+        staking_token: Addr::unchecked("staking"),
+        penalty_rate,
     };
+    let res = test_env
+        .execute("owner", penalty_msg(Decimal::percent(10)))
+        .unwrap_err();
+    assert_eq!(res, ContractError::PenaltyCollectorNotSet {});
 
-    let staking_addr = app
-        .instantiate(code_id, Addr::unchecked("addr"), &msg, &[], "staking")
-        .unwrap();
+    // without a penalty it is an ordinary unbond, waiting for the unbonding period
+    let instant_unbond_msg = |amount: u128| ExecuteMsg::InstantUnbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Uint128::from(amount),
+    };
+    let res = test_env.execute("addr", instant_unbond_msg(20)).unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(res.attributes[0], attr("action", "unbond"));
+    test_env.assert_bond("addr", "staking", 80);
 
-    // set allowance
-    app.execute(
-        Addr::unchecked("addr"),
-        asset_addr.clone(),
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::UpdateConfig {
+                rewarder: None,
+                migrate_store_status: None,
+                penalty_collector: Some(Addr::unchecked("collector")),
+                reward_distributors: None,
+                withdraw_fee_rate: None,
+                fee_collector: None,
+                oracle_addr: None,
+                factory_addr: None,
+                base_denom: None,
+                community_pool: None,
+            },
+        )
+        .unwrap();
+    let res = test_env
+        .execute("owner", penalty_msg(Decimal::percent(25)))
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::InstantUnbondPenaltyTooHigh {
+            max: Decimal::percent(20)
+        }
+    );
+    test_env
+        .execute("owner", penalty_msg(Decimal::percent(10)))
+        .unwrap();
+    assert_eq!(
+        test_env.pool_info("staking").instant_unbond_penalty,
+        Decimal::percent(10)
+    );
+
+    let res = test_env.execute("addr", instant_unbond_msg(50)).unwrap();
+    let transfer = |recipient: &str, amount: u128| {
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: Uint128::from(amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+    assert_eq!(
+        res.messages,
+        vec![transfer("addr", 45), transfer("collector", 5)]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "instant_unbond"),
+            attr("staker_addr", "addr"),
+            attr("amount", "50"),
+            attr("penalty", "5"),
+            attr("staking_token", "staking"),
+        ]
+    );
+    test_env.assert_bond("addr", "staking", 30);
+}
+
+#[test]
+fn test_min_bond_amount() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 10)
+        .build();
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "other"]);
+
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::RegisterAsset {
+                staking_token: Addr::unchecked("other"),
+                min_bond_amount: Some(Uint128::from(100u128)),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        test_env.pool_info("other").min_bond_amount,
+        Uint128::from(100u128)
+    );
+
+    let msg = ExecuteMsg::UpdateMinBondAmount {
+        staking_token: Addr::unchecked("staking"),
+        min_bond_amount: Uint128::from(50u128),
+    };
+    let res = test_env.execute("addr", msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    test_env.execute("owner", msg).unwrap();
+
+    let bond_msg = |amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                lock_duration: None,
+                referrer: None,
+            })
+            .unwrap(),
+        })
+    };
+    let res = test_env.execute("staking", bond_msg(20)).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::BondBelowMinimum {
+            min_bond_amount: Uint128::from(50u128)
+        }
+    );
+    test_env.execute("staking", bond_msg(40)).unwrap();
+    test_env.assert_bond("addr", "staking", 50);
+
+    // a position can still shrink below the minimum
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(45u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    test_env.assert_bond("addr", "staking", 5);
+}
+
+#[test]
+fn test_bond_cap() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 60)
+        .build();
+
+    let cap_msg = |bond_cap: Option<u128>| ExecuteMsg::UpdateBondCap {
+        staking_token: Addr::unchecked("staking"),
+        bond_cap: bond_cap.map(Uint128::from),
+    };
+    let res = test_env.execute("addr", cap_msg(Some(100))).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    test_env.execute("owner", cap_msg(Some(100))).unwrap();
+    let pool_info = test_env.pool_info("staking");
+    assert_eq!(pool_info.bond_cap, Some(Uint128::from(100u128)));
+    assert_eq!(
+        pool_info.remaining_bond_capacity,
+        Some(Uint128::from(40u128))
+    );
+
+    let bond_msg = |amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                lock_duration: None,
+                referrer: None,
+            })
+            .unwrap(),
+        })
+    };
+    let res = test_env.execute("staking", bond_msg(50)).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::BondCapExceeded {
+            remaining: Uint128::from(40u128)
+        }
+    );
+    test_env.execute("staking", bond_msg(40)).unwrap();
+    assert_eq!(
+        test_env.pool_info("staking").remaining_bond_capacity,
+        Some(Uint128::zero())
+    );
+
+    // clearing the cap takes bonds again right away
+    test_env.execute("owner", cap_msg(None)).unwrap();
+    test_env.execute("staking", bond_msg(50)).unwrap();
+    test_env.assert_bond("addr", "staking", 150);
+    assert_eq!(test_env.pool_info("staking").remaining_bond_capacity, None);
+}
+
+#[test]
+fn test_max_bond_per_staker() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 60)
+        .build();
+
+    let cap_msg = |cap: Option<u128>| ExecuteMsg::UpdateMaxBondPerStaker {
+        staking_token: Addr::unchecked("staking"),
+        max_bond_per_staker: cap.map(Uint128::from),
+    };
+    let res = test_env.execute("addr", cap_msg(Some(100))).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    test_env.execute("owner", cap_msg(Some(100))).unwrap();
+    assert_eq!(
+        test_env.pool_info("staking").max_bond_per_staker,
+        Some(Uint128::from(100u128))
+    );
+
+    let bond_msg = |staker: &str, amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                lock_duration: None,
+                referrer: None,
+            })
+            .unwrap(),
+        })
+    };
+    let res = test_env
+        .execute("staking", bond_msg("addr", 50))
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::StakerBondCapExceeded {
+            bond_amount: Uint128::from(60u128),
+            max_bond_per_staker: Uint128::from(100u128),
+            remaining: Uint128::from(40u128),
+        }
+    );
+    test_env.execute("staking", bond_msg("addr", 40)).unwrap();
+    // the cap is per position, others still bond up to it
+    test_env.execute("staking", bond_msg("addr2", 100)).unwrap();
+
+    // a lowered cap leaves the positions above it able to unbond
+    test_env.execute("owner", cap_msg(Some(30))).unwrap();
+    let res = test_env
+        .execute("staking", bond_msg("addr", 1))
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::StakerBondCapExceeded {
+            bond_amount: Uint128::from(100u128),
+            max_bond_per_staker: Uint128::from(30u128),
+            remaining: Uint128::zero(),
+        }
+    );
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(80u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    test_env.execute("staking", bond_msg("addr", 10)).unwrap();
+    test_env.assert_bond("addr", "staking", 30);
+}
+
+#[test]
+fn test_referral_stats() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_pool("staking2", default_rewards_per_sec())
+        .build();
+
+    let bond_msg = |staker: &str, amount: u128, referrer: &str| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                lock_duration: None,
+                referrer: Some(Addr::unchecked(referrer)),
+            })
+            .unwrap(),
+        })
+    };
+
+    let err = test_env
+        .execute("staking", bond_msg("addr", 100, "addr"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::SelfReferral {});
+
+    test_env
+        .execute("staking", bond_msg("addr", 100, "frontend"))
+        .unwrap();
+    test_env
+        .execute("staking", bond_msg("addr2", 50, "frontend"))
+        .unwrap();
+    test_env
+        .execute("staking2", bond_msg("addr", 30, "frontend"))
+        .unwrap();
+    test_env.assert_bond("addr", "staking", 100);
+
+    let res: ReferralStatsResponse = test_env.query(QueryMsg::ReferralStats {
+        referrer: Addr::unchecked("frontend"),
+        start_after: None,
+        limit: None,
+        order: None,
+    });
+    assert_eq!(res.stats.len(), 2);
+    let volume = |staking_token: &str| {
+        res.stats
+            .iter()
+            .find(|stat| stat.staking_token == staking_token)
+            .unwrap()
+            .bond_volume
+    };
+    assert_eq!(volume("staking"), Uint128::from(150u128));
+    assert_eq!(volume("staking2"), Uint128::from(30u128));
+
+    // the next page starts after the first staking token
+    let next: ReferralStatsResponse = test_env.query(QueryMsg::ReferralStats {
+        referrer: Addr::unchecked("frontend"),
+        start_after: Some(res.stats[0].staking_token.clone()),
+        limit: Some(1),
+        order: None,
+    });
+    assert_eq!(next.stats, res.stats[1..].to_vec());
+}
+
+#[test]
+fn test_lock_tiers() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+
+    let tiers_msg = |multiplier: &str| ExecuteMsg::UpdateLockTiers {
+        staking_token: Addr::unchecked("staking"),
+        lock_tiers: vec![LockTier {
+            duration: 100,
+            multiplier: multiplier.parse().unwrap(),
+        }],
+    };
+    let res = test_env.execute("owner", tiers_msg("0.5")).unwrap_err();
+    assert_eq!(res, ContractError::InvalidLockTier { duration: 100 });
+    test_env.execute("owner", tiers_msg("2")).unwrap();
+
+    let bond_msg = |amount: u128, lock_duration: Option<u64>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr1".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                lock_duration,
+                referrer: None,
+            })
+            .unwrap(),
+        })
+    };
+    let res = test_env
+        .execute("staking", bond_msg(100, Some(50)))
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::InvalidLockDuration { lock_duration: 50 }
+    );
+    test_env.execute("staking", bond_msg(50, None)).unwrap();
+    test_env
+        .execute("staking", bond_msg(100, Some(100)))
+        .unwrap();
+
+    // addr1 weighs 50 + 100 * 2 against the 100 of addr
+    let res = test_env.reward_info("addr1", Some("staking"));
+    assert_eq!(
+        res.reward_infos[0].weighted_bond_amount,
+        Uint128::from(250u128)
+    );
+    assert_eq!(
+        test_env.pool_info("staking").total_weighted_bond,
+        Uint128::from(350u128)
+    );
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(700u128),
+                }],
+            },
+        )
+        .unwrap();
+    test_env.assert_pending("addr", "staking", 200);
+    test_env.assert_pending("addr1", "staking", 500);
+
+    let unbond_msg = |amount: Option<u128>| ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: amount.map(Uint128::from),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let res = test_env.execute("addr1", unbond_msg(Some(60))).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::BondLocked {
+            unlocked: Uint128::from(50u128),
+            requested: Uint128::from(60u128),
+        }
+    );
+    // without an amount only the unlocked part is unbonded
+    test_env.execute("addr1", unbond_msg(None)).unwrap();
+    test_env.assert_bond("addr1", "staking", 100);
+    let res = test_env.execute("addr1", unbond_msg(None)).unwrap_err();
+    assert_eq!(res, ContractError::NothingToUnbond {});
+
+    // once expired the lock is released at the next action and weighs 1x again
+    test_env.advance_seconds(100);
+    test_env.execute("addr1", unbond_msg(Some(40))).unwrap();
+    let res = test_env.reward_info("addr1", Some("staking"));
+    assert_eq!(
+        res.reward_infos[0].weighted_bond_amount,
+        Uint128::from(60u128)
+    );
+    assert!(res.reward_infos[0].locks.is_empty());
+    assert_eq!(
+        test_env.pool_info("staking").total_weighted_bond,
+        Uint128::from(160u128)
+    );
+    test_env.assert_pending("addr1", "staking", 500);
+}
+
+#[test]
+fn test_pause() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+
+    let res = test_env.execute("addr", ExecuteMsg::Pause {}).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    test_env.execute("owner", ExecuteMsg::Pause {}).unwrap();
+    let config: ConfigResponse = test_env.query(QueryMsg::Config {});
+    assert!(config.paused);
+
+    let bond_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
+    });
+    let res = test_env.execute("staking", bond_msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Paused {});
+
+    let res = test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(300u128),
+                }],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(res, ContractError::Paused {});
+
+    // stakers can still leave with their rewards
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(50u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    test_env.assert_bond("addr", "staking", 50);
+
+    test_env.execute("owner", ExecuteMsg::Unpause {}).unwrap();
+    test_env.execute("staking", bond_msg).unwrap();
+    test_env.assert_bond("addr", "staking", 150);
+}
+
+#[test]
+fn test_freeze_pool() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+
+    let freeze_msg = ExecuteMsg::UpdatePoolStatus {
+        staking_token: Addr::unchecked("staking"),
+        frozen: true,
+    };
+    let res = test_env.execute("addr", freeze_msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    test_env.execute("owner", freeze_msg).unwrap();
+    assert!(test_env.pool_info("staking").frozen);
+
+    let bond_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
+    });
+    let res = test_env.execute("staking", bond_msg.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::PoolFrozen {
+            asset: "staking".to_string()
+        }
+    );
+
+    // the bonded stakers keep earning and can leave
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(300u128),
+                }],
+            },
+        )
+        .unwrap();
+    test_env.assert_pending("addr", "staking", 300);
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(50u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    test_env.assert_bond("addr", "staking", 50);
+
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::UpdatePoolStatus {
+                staking_token: Addr::unchecked("staking"),
+                frozen: false,
+            },
+        )
+        .unwrap();
+    test_env.execute("staking", bond_msg).unwrap();
+    test_env.assert_bond("addr", "staking", 150);
+}
+
+#[test]
+fn test_bond_for() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .build();
+
+    let res = test_env
+        .execute(
+            "staking",
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "vault".to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&Cw20HookMsg::BondFor {
+                    staker_addr: Addr::unchecked("addr"),
+                })
+                .unwrap(),
+            }),
+        )
+        .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "bond"),
+            attr("staker_addr", "addr"),
+            attr("staking_token", "staking"),
+            attr("amount", "100"),
+            attr("funder", "vault"),
+        ]
+    );
+    test_env.assert_bond("addr", "staking", 100);
+    test_env.assert_bond("vault", "staking", 0);
+
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(300u128),
+                }],
+            },
+        )
+        .unwrap();
+    test_env.assert_pending("addr", "staking", 300);
+
+    // only the beneficiary can take the position out
+    let unbond_msg = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::from(100u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    test_env.execute("vault", unbond_msg.clone()).unwrap_err();
+    let res = test_env.execute("addr", unbond_msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+}
+
+#[test]
+fn test_bond_from_allowance() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .build();
+
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::BondFromAllowance {
+                staking_token: Addr::unchecked("staking"),
+                amount: Uint128::zero(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(res, ContractError::ZeroBondAmount {});
+
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::BondFromAllowance {
+                staking_token: Addr::unchecked("other"),
+                amount: Uint128::from(100u128),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::PoolNotFound {
+            asset: "other".to_string(),
+        }
+    );
+
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::BondFromAllowance {
+                staking_token: Addr::unchecked("staking"),
+                amount: Uint128::from(100u128),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: "addr".to_string(),
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    // same event as a bond through the cw20 hook
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "bond"),
+            attr("staker_addr", "addr"),
+            attr("staking_token", "staking"),
+            attr("amount", "100"),
+        ]
+    );
+    test_env.assert_bond("addr", "staking", 100);
+}
+
+#[test]
+fn test_bond_native() {
+    let mut test_env = StakingTestEnv::new().build();
+
+    let msg = ExecuteMsg::RegisterNativeAsset {
+        denom: "native_lp".to_string(),
+        min_bond_amount: None,
+    };
+    let res = test_env.execute("addr", msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    test_env.execute("owner", msg).unwrap();
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::UpdateRewardsPerSec {
+                staking_token: Addr::unchecked("native_lp"),
+                assets: default_rewards_per_sec(),
+            },
+        )
+        .unwrap();
+
+    let res = test_env
+        .execute(
+            "owner",
+            ExecuteMsg::RegisterAsset {
+                staking_token: Addr::unchecked("native_lp"),
+                min_bond_amount: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(res, ContractError::AssetRegistered {});
+
+    let bond_msg = ExecuteMsg::BondNative {
+        staking_token: Addr::unchecked("native_lp"),
+    };
+    let res = test_env
+        .execute_with_funds("addr", &coins(100, ORAI_DENOM), bond_msg.clone())
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::InvalidStakingDenom {
+            expected: "native_lp".to_string(),
+            denom: ORAI_DENOM.to_string(),
+        }
+    );
+    let res = test_env
+        .execute_with_funds("addr", &[], bond_msg.clone())
+        .unwrap_err();
+    assert_eq!(res, ContractError::InvalidFunds {});
+
+    test_env
+        .execute_with_funds("addr", &coins(100, "native_lp"), bond_msg)
+        .unwrap();
+    test_env.assert_bond("addr", "native_lp", 100);
+    assert_eq!(
+        test_env.pool_info("native_lp").staking_token,
+        Addr::unchecked("native_lp")
+    );
+
+    // the unbonded amount goes back through the bank module
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("native_lp"),
+                amount: Some(Uint128::from(40u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr".to_string(),
+            amount: coins(40, "native_lp"),
+        })]
+    );
+    test_env.assert_bond("addr", "native_lp", 60);
+}
+
+#[test]
+fn test_unbond_with_unbonding_period() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_unbonding_period("staking", 100)
+        .with_bond("addr", "staking", 100)
+        .build();
+    let release_time = test_env.env.block.time.seconds() + 100;
+
+    // the lp tokens wait in the queue instead of being transferred
+    let msg = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::from(30u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let res = test_env.execute("addr", msg).unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(
+        res.attributes.last(),
+        Some(&attr("release_time", release_time.to_string()))
+    );
+    test_env.assert_bond("addr", "staking", 70);
+    assert_eq!(
+        test_env.pool_info("staking").total_bond_amount,
+        Uint128::from(70u128)
+    );
+
+    // unbonds in the same block share an entry
+    let msg = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::from(20u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    test_env.execute("addr", msg).unwrap();
+
+    let unbonding_query = QueryMsg::Unbonding {
+        staker_addr: Addr::unchecked("addr"),
+        staking_token: Addr::unchecked("staking"),
+    };
+    let res: UnbondingResponse = test_env.query(unbonding_query.clone());
+    assert_eq!(
+        res,
+        UnbondingResponse {
+            staker_addr: Addr::unchecked("addr"),
+            staking_token: Addr::unchecked("staking"),
+            unbonding_period: 100,
+            entries: vec![UnbondingEntry {
+                amount: Uint128::from(50u128),
+                release_time,
+            }],
+            claimable_amount: Uint128::zero(),
+        }
+    );
+
+    let claim_msg = ExecuteMsg::Claim {
+        staking_token: Addr::unchecked("staking"),
+    };
+    let res = test_env.execute("addr", claim_msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::NothingToClaim {});
+
+    test_env.advance_seconds(100);
+    let msg = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::from(10u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    test_env.execute("addr", msg).unwrap();
+
+    // only the released entry is paid out, the later one keeps waiting
+    let res = test_env.execute("addr", claim_msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr".to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    let res: UnbondingResponse = test_env.query(unbonding_query);
+    assert_eq!(
+        res.entries,
+        vec![UnbondingEntry {
+            amount: Uint128::from(10u128),
+            release_time: release_time + 100,
+        }]
+    );
+    test_env.assert_bond("addr", "staking", 60);
+}
+
+#[test]
+fn test_unbonding_queue_is_bounded() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_unbonding_period("staking", 100)
+        .with_bond("addr", "staking", 100)
+        .build();
+
+    let msg = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::from(1u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    for _ in 0..MAX_UNBONDING_ENTRIES {
+        test_env.execute("addr", msg.clone()).unwrap();
+        test_env.advance_seconds(1);
+    }
+    let res = test_env.execute("addr", msg.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::TooManyUnbondingEntries {
+            max: MAX_UNBONDING_ENTRIES,
+        }
+    );
+
+    // claiming the released entries makes room again
+    test_env.advance_seconds(100);
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Claim {
+                staking_token: Addr::unchecked("staking"),
+            },
+        )
+        .unwrap();
+    test_env.execute("addr", msg).unwrap();
+}
+
+#[test]
+fn test_update_unbonding_period() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .build();
+
+    let msg = ExecuteMsg::UpdateUnbondingPeriod {
+        staking_token: Addr::unchecked("staking"),
+        unbonding_period: 100,
+    };
+    let res = test_env.execute("addr", msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = test_env
+        .execute(
+            "owner",
+            ExecuteMsg::UpdateUnbondingPeriod {
+                staking_token: Addr::unchecked("other"),
+                unbonding_period: 100,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::PoolNotFound {
+            asset: "other".to_string(),
+        }
+    );
+
+    test_env.execute("owner", msg).unwrap();
+    let res: UnbondingResponse = test_env.query(QueryMsg::Unbonding {
+        staker_addr: Addr::unchecked("addr"),
+        staking_token: Addr::unchecked("staking"),
+    });
+    assert_eq!(res.unbonding_period, 100);
+}
+
+#[test]
+fn test_auto_stake_hook_only_from_contract() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .build();
+
+    // a stale prev_staking_token_amount would credit LP tokens held by the contract
+    for sender in ["addr", "staking", "owner"] {
+        let err = test_env
+            .execute(
+                sender,
+                ExecuteMsg::AutoStakeHook {
+                    staking_token: Addr::unchecked("staking"),
+                    staker_addr: Addr::unchecked(sender),
+                    prev_staking_token_amount: Uint128::zero(),
+                    minimum_lp_amount: None,
+                },
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+    test_env.assert_bond("addr", "staking", 0);
+}
+
+#[test]
+fn test_auto_stake() {
+    let mut app = MockApp::new(&[(
+        &"addr".to_string(),
+        &[coin(10000000000u128, ORAI_DENOM), coin(100u128, ATOM_DENOM)],
+    )]);
+
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(
+            create_entry_points_testing!(oraiswap_factory)
+                .with_reply(oraiswap_factory::contract::reply),
+        ),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair).with_reply(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let asset_addr = app.create_token("asset");
+    let reward_addr = app.create_token("reward");
+    // update other contract token balance
+    app.set_token_balances(&[
+        (
+            &"reward".to_string(),
+            &[(&"addr".to_string(), &Uint128::from(10000000000u128))],
+        ),
+        (
+            &"asset".to_string(),
+            &[(&"addr".to_string(), &Uint128::from(10000000000u128))],
+        ),
+    ]);
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: asset_addr.clone(),
+        },
+    ];
+
+    // create pair
+    let pair_addr = app.create_pair(asset_infos.clone()).unwrap();
+
+    let PairResponse { info: pair_info } = app
+        .query(pair_addr.clone(), &oraiswap::pair::QueryMsg::Pair {})
+        .unwrap();
+
+    // set allowance
+    app.execute(
+        Addr::unchecked("addr"),
+        asset_addr.clone(),
+        &cw20::Cw20ExecuteMsg::IncreaseAllowance {
+            spender: pair_addr.to_string(),
+            amount: Uint128::from(100u128),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // provide liquidity
+    // successfully provide liquidity for the exist pool
+    let msg = oraiswap::pair::ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: asset_addr.clone(),
+                },
+                amount: Uint128::from(100u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+    };
+
+    let _res = app
+        .execute(
+            Addr::unchecked("addr"),
+            pair_addr.clone(),
+            &msg,
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        )
+        .unwrap();
+
+    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: reward_addr.clone(),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: app.oracle_addr.clone(),
+        factory_addr: app.factory_addr.clone(),
+        base_denom: None,
+    };
+
+    let staking_addr = app
+        .instantiate(code_id, Addr::unchecked("addr"), &msg, &[], "staking")
+        .unwrap();
+
+    // set allowance
+    app.execute(
+        Addr::unchecked("addr"),
+        asset_addr.clone(),
         &cw20::Cw20ExecuteMsg::IncreaseAllowance {
             spender: staking_addr.to_string(),
             amount: Uint128::from(100u128),
-            expires: None,
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the pair LP token is not registered yet, nothing is pulled from the sender
+    let msg = ExecuteMsg::AutoStake {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: asset_addr.clone(),
+                },
+                amount: Uint128::from(1u128),
+            },
+        ],
+        slippage_tolerance: None,
+        minimum_lp_amount: None,
+    };
+    let token_balances = app.query_token_balances(Addr::unchecked("addr")).unwrap();
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &msg,
+        &[coin(100u128, ORAI_DENOM)],
+    );
+    app.assert_fail(res);
+    assert_eq!(
+        app.query_token_balances(Addr::unchecked("addr")).unwrap(),
+        token_balances
+    );
+
+    let msg = ExecuteMsg::RegisterAsset {
+        staking_token: pair_info.liquidity_token.clone(),
+        min_bond_amount: None,
+    };
+
+    let _res = app
+        .execute(Addr::unchecked("owner"), staking_addr.clone(), &msg, &[])
+        .unwrap();
+
+    // no token asset
+    let msg = ExecuteMsg::AutoStake {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+        ],
+        slippage_tolerance: None,
+        minimum_lp_amount: None,
+    };
+
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &msg,
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+
+    app.assert_fail(res);
+
+    // no native asset
+    let msg = ExecuteMsg::AutoStake {
+        assets: [
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: asset_addr.clone(),
+                },
+                amount: Uint128::from(1u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: asset_addr.clone(),
+                },
+                amount: Uint128::from(1u128),
+            },
+        ],
+        slippage_tolerance: None,
+        minimum_lp_amount: None,
+    };
+
+    let res = app.execute(Addr::unchecked("addr"), staking_addr.clone(), &msg, &[]);
+
+    app.assert_fail(res);
+
+    let msg = ExecuteMsg::AutoStake {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: asset_addr.clone(),
+                },
+                amount: Uint128::from(1u128),
+            },
+        ],
+        slippage_tolerance: None,
+        minimum_lp_amount: None,
+    };
+
+    // attempt with no coins
+    let res = app.execute(Addr::unchecked("addr"), staking_addr.clone(), &msg, &[]);
+    app.assert_fail(res);
+
+    // a denom that is not one of the assets
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &msg,
+        &[coin(100u128, ORAI_DENOM), coin(100u128, ATOM_DENOM)],
+    );
+    app.assert_fail(res);
+
+    // 1 token only mints 1 LP token from the 100:100 pool
+    let min_lp_msg = ExecuteMsg::AutoStake {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: asset_addr.clone(),
+                },
+                amount: Uint128::from(1u128),
+            },
+        ],
+        slippage_tolerance: None,
+        minimum_lp_amount: Some(Uint128::from(2u128)),
+    };
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &min_lp_msg,
+        &[coin(100u128, ORAI_DENOM)],
+    );
+    app.assert_fail(res);
+
+    let orai_balance = app
+        .query_balance(Addr::unchecked("addr"), ORAI_DENOM.to_string())
+        .unwrap();
+    let _res = app
+        .execute(
+            Addr::unchecked("addr"),
+            staking_addr.clone(),
+            &msg,
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        )
+        .unwrap();
+    // the 100:100 pool takes 1 ORAI with the 1 token, the other 99 ORAI are sent back
+    assert_eq!(
+        app.query_balance(Addr::unchecked("addr"), ORAI_DENOM.to_string())
+            .unwrap(),
+        orai_balance - Uint128::from(1u128)
+    );
+
+    // wrong asset
+    let msg = ExecuteMsg::AutoStakeHook {
+        staking_token: pair_info.liquidity_token.clone(),
+        staker_addr: Addr::unchecked("addr"),
+        prev_staking_token_amount: Uint128::zero(),
+        minimum_lp_amount: None,
+    };
+    let _res = app.execute(staking_addr.clone(), staking_addr.clone(), &msg, &[]);
+
+    // valid msg
+    let msg = ExecuteMsg::AutoStakeHook {
+        staking_token: pair_info.liquidity_token.clone(),
+        staker_addr: Addr::unchecked("addr"),
+        prev_staking_token_amount: Uint128::zero(),
+        minimum_lp_amount: None,
+    };
+
+    // unauthorized attempt
+    let res = app.execute(Addr::unchecked("addr"), staking_addr.clone(), &msg, &[]);
+    app.assert_fail(res);
+
+    // successfull attempt
+
+    let res = app
+        .execute(staking_addr.clone(), staking_addr.clone(), &msg, &[])
+        .unwrap();
+    assert_eq!(
+        // first attribute is _contract_addr
+        res.get_attributes(1),
+        vec![
+            attr("action", "bond"),
+            attr("staker_addr", "addr"),
+            attr("staking_token", pair_info.liquidity_token.as_str()),
+            attr("amount", "1"),
+        ]
+    );
+
+    let pool_info: PoolInfoResponse = app
+        .query(
+            staking_addr.clone(),
+            &QueryMsg::PoolInfo {
+                staking_token: pair_info.liquidity_token.clone(),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        pool_info,
+        PoolInfoResponse {
+            staking_token: pair_info.liquidity_token.clone(),
+            total_bond_amount: Uint128::from(3u128),
+            reward_index: Decimal::zero(),
+            pending_reward: Uint128::zero(),
+            migration_deprecated_staking_token: None,
+            migration_deprecated_total_bond: None,
+            migration_grace_period_end: None,
+            migration_index_snapshot: None,
+            frozen: false,
+            instant_unbond_penalty: Decimal::zero(),
+            min_bond_amount: Uint128::zero(),
+            min_compound_reward: Uint128::zero(),
+            bond_cap: None,
+            max_bond_per_staker: None,
+            remaining_bond_capacity: None,
+            total_weighted_bond: Uint128::from(3u128),
+            lock_tiers: vec![],
+            reward_weight: Decimal::zero(),
+            reward_start_time: None,
+            reward_end_time: None,
+            proxy_rewards: vec![],
+            symbol: Some("uLP".to_string()),
+            staker_count: Some(1),
+            lifetime_bonded: Uint128::from(3u128),
+            lifetime_unbonded: Uint128::zero(),
+            lifetime_rewards_deposited: Uint128::zero(),
+            lifetime_rewards_claimed: Uint128::zero(),
+            unbonding_period: 0,
+            whitelist_enabled: false,
+            unbond_cooldown: 0,
+            max_index_increase_per_deposit: Decimal::zero(),
+            reward_stream_seconds: 0,
+            streaming_reward: Uint128::zero(),
+            deposit_epoch: 0,
+        }
+    );
+
+    // the hook fails when nothing arrived since the provide, even without a minimum
+    let lp_balance: cw20::BalanceResponse = app
+        .query(
+            pair_info.liquidity_token.clone(),
+            &cw20::Cw20QueryMsg::Balance {
+                address: staking_addr.to_string(),
+            },
+        )
+        .unwrap();
+    let res = app.execute(
+        staking_addr.clone(),
+        staking_addr.clone(),
+        &ExecuteMsg::AutoStakeHook {
+            staking_token: pair_info.liquidity_token.clone(),
+            staker_addr: Addr::unchecked("addr"),
+            prev_staking_token_amount: lp_balance.balance,
+            minimum_lp_amount: None,
+        },
+        &[],
+    );
+    app.assert_fail(res);
+}
+
+// an ORAI/ATOM pair whose LP pool pays the given rewards, "addr" bonded 1000 LP before a
+// deposit of 10000
+fn setup_pair_pool(rewards_per_sec: Vec<Asset>) -> (MockApp, Addr, Addr) {
+    let mut app = MockApp::new(&[(
+        &"addr".to_string(),
+        &[
+            coin(10000000000u128, ORAI_DENOM),
+            coin(10000000000u128, ATOM_DENOM),
+        ],
+    )]);
+
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(
+            create_entry_points_testing!(oraiswap_factory)
+                .with_reply(oraiswap_factory::contract::reply),
+        ),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair).with_reply(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: ATOM_DENOM.to_string(),
+        },
+    ];
+    let pair_addr = app.create_pair(asset_infos.clone()).unwrap();
+    let PairResponse { info: pair_info } = app
+        .query(pair_addr.clone(), &oraiswap::pair::QueryMsg::Pair {})
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked("addr"),
+        pair_addr.clone(),
+        &oraiswap::pair::ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: asset_infos[0].clone(),
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: asset_infos[1].clone(),
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+        },
+        &[coin(1000000u128, ORAI_DENOM), coin(1000000u128, ATOM_DENOM)],
+    )
+    .unwrap();
+
+    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: app.oracle_addr.clone(),
+        factory_addr: app.factory_addr.clone(),
+        base_denom: None,
+    };
+    let staking_addr = app
+        .instantiate(code_id, Addr::unchecked("addr"), &msg, &[], "staking")
+        .unwrap();
+    app.set_balances_from(
+        Addr::unchecked("addr"),
+        &[
+            (
+                &ORAI_DENOM.to_string(),
+                &[(&staking_addr.to_string(), &Uint128::from(100000u128))],
+            ),
+            (
+                &ATOM_DENOM.to_string(),
+                &[(&staking_addr.to_string(), &Uint128::from(100000u128))],
+            ),
+        ],
+    );
+
+    let staking_token = pair_info.liquidity_token.clone();
+    for msg in [
+        ExecuteMsg::UpdateRewardsPerSec {
+            staking_token: staking_token.clone(),
+            assets: rewards_per_sec,
+        },
+        ExecuteMsg::RegisterAsset {
+            staking_token: staking_token.clone(),
+            min_bond_amount: None,
+        },
+    ] {
+        app.execute(Addr::unchecked("owner"), staking_addr.clone(), &msg, &[])
+            .unwrap();
+    }
+
+    app.execute(
+        Addr::unchecked("addr"),
+        staking_token.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: staking_addr.to_string(),
+            amount: Uint128::from(1000u128),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                lock_duration: None,
+                referrer: None,
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked("rewarder"),
+        staking_addr.clone(),
+        &ExecuteMsg::DepositReward {
+            rewards: vec![RewardMsg {
+                staking_token: staking_token.clone(),
+                total_accumulation_amount: Uint128::from(10000u128),
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+
+    (app, staking_addr, staking_token)
+}
+
+#[test]
+fn test_compound() {
+    let (mut app, staking_addr, staking_token) = setup_pair_pool(vec![Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: 100u128.into(),
+    }]);
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: ATOM_DENOM.to_string(),
+        },
+    ];
+    app.execute(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::UpdateMinCompoundReward {
+            staking_token: staking_token.clone(),
+            min_compound_reward: Uint128::from(20000u128),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let compound_msg = ExecuteMsg::Compound {
+        asset_infos: asset_infos.clone(),
+        slippage_tolerance: None,
+    };
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &compound_msg,
+        &[],
+    );
+    app.assert_fail(res);
+
+    app.execute(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::UpdateMinCompoundReward {
+            staking_token: staking_token.clone(),
+            min_compound_reward: Uint128::zero(),
+        },
+        &[],
+    )
+    .unwrap();
+    // the ORAI reward is half swapped for ATOM and provided back into the pair
+    app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &compound_msg,
+        &[],
+    )
+    .unwrap();
+
+    let res: RewardInfoResponse = app
+        .query(
+            staking_addr.clone(),
+            &QueryMsg::RewardInfo {
+                staker_addr: Addr::unchecked("addr"),
+                staking_token: Some(staking_token.clone()),
+            },
+        )
+        .unwrap();
+    let reward_info = &res.reward_infos[0];
+    assert!(reward_info.bond_amount > Uint128::from(1000u128));
+    assert_eq!(reward_info.pending_reward, Uint128::zero());
+    assert!(reward_info.pending_withdraw.is_empty());
+
+    // nothing is left to compound
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &compound_msg,
+        &[],
+    );
+    app.assert_fail(res);
+}
+
+#[test]
+fn test_withdraw_and_swap() {
+    let (mut app, staking_addr, staking_token) = setup_pair_pool(default_rewards_per_sec());
+
+    // no pair swaps the rewards for the token
+    let token_addr = app.create_token("token");
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &ExecuteMsg::WithdrawAndSwap {
+            staking_token: staking_token.clone(),
+            target_asset: AssetInfo::Token {
+                contract_addr: token_addr,
+            },
+            belief_price: None,
+            max_spread: None,
+        },
+        &[],
+    );
+    app.assert_fail(res);
+
+    // ORAI 3333 is paid as is, ATOM 6666 is swapped for ORAI through the pair
+    let orai_balance = app
+        .query_balance(Addr::unchecked("addr"), ORAI_DENOM.to_string())
+        .unwrap();
+    let atom_balance = app
+        .query_balance(Addr::unchecked("addr"), ATOM_DENOM.to_string())
+        .unwrap();
+    app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &ExecuteMsg::WithdrawAndSwap {
+            staking_token: staking_token.clone(),
+            target_asset: AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            belief_price: None,
+            max_spread: Some(Decimal::percent(5)),
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(
+        app.query_balance(Addr::unchecked("addr"), ORAI_DENOM.to_string())
+            .unwrap()
+            > orai_balance + Uint128::from(3333u128)
+    );
+    assert_eq!(
+        app.query_balance(Addr::unchecked("addr"), ATOM_DENOM.to_string())
+            .unwrap(),
+        atom_balance
+    );
+
+    let res: RewardInfoResponse = app
+        .query(
+            staking_addr.clone(),
+            &QueryMsg::RewardInfo {
+                staker_addr: Addr::unchecked("addr"),
+                staking_token: Some(staking_token),
+            },
+        )
+        .unwrap();
+    assert!(res.reward_infos[0].pending_withdraw.is_empty());
+}
+
+#[test]
+fn test_compound_fee() {
+    let (mut app, staking_addr, staking_token) = setup_pair_pool(vec![Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: 100u128.into(),
+    }]);
+    app.execute(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::UpdateConfig {
+            rewarder: None,
+            migrate_store_status: None,
+            penalty_collector: None,
+            reward_distributors: None,
+            withdraw_fee_rate: Some(Decimal::percent(10)),
+            fee_collector: Some(Addr::unchecked("collector")),
+            oracle_addr: None,
+            factory_addr: None,
+            base_denom: None,
+            community_pool: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the fee on the ORAI 10000 reward is taken before the rest is reinvested
+    app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &ExecuteMsg::Compound {
+            asset_infos: [
+                AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                AssetInfo::NativeToken {
+                    denom: ATOM_DENOM.to_string(),
+                },
+            ],
+            slippage_tolerance: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        app.query_balance(Addr::unchecked("collector"), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(1000u128)
+    );
+
+    let res: RewardInfoResponse = app
+        .query(
+            staking_addr,
+            &QueryMsg::RewardInfo {
+                staker_addr: Addr::unchecked("addr"),
+                staking_token: Some(staking_token),
+            },
+        )
+        .unwrap();
+    assert!(res.reward_infos[0].bond_amount > Uint128::from(1000u128));
+}
+
+#[test]
+fn test_withdraw_and_swap_fee() {
+    let (mut app, staking_addr, staking_token) = setup_pair_pool(default_rewards_per_sec());
+    app.execute(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::UpdateConfig {
+            rewarder: None,
+            migrate_store_status: None,
+            penalty_collector: None,
+            reward_distributors: None,
+            withdraw_fee_rate: Some(Decimal::percent(10)),
+            fee_collector: Some(Addr::unchecked("collector")),
+            oracle_addr: None,
+            factory_addr: None,
+            base_denom: None,
+            community_pool: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the collector gets its part of ORAI 3333 and ATOM 6666, only the rest of the ATOM is swapped
+    let atom_balance = app
+        .query_balance(Addr::unchecked("addr"), ATOM_DENOM.to_string())
+        .unwrap();
+    app.execute(
+        Addr::unchecked("addr"),
+        staking_addr,
+        &ExecuteMsg::WithdrawAndSwap {
+            staking_token,
+            target_asset: AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            belief_price: None,
+            max_spread: Some(Decimal::percent(5)),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        app.query_balance(Addr::unchecked("collector"), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(333u128)
+    );
+    assert_eq!(
+        app.query_balance(Addr::unchecked("collector"), ATOM_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(666u128)
+    );
+    assert_eq!(
+        app.query_balance(Addr::unchecked("addr"), ATOM_DENOM.to_string())
+            .unwrap(),
+        atom_balance
+    );
+}
+
+#[test]
+fn test_bond_smoke() {
+    let mut app = MockApp::new(&[]);
+
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let staking_token = app.create_token("LPT");
+    app.set_token_balances(&[(
+        &"LPT".to_string(),
+        &[(&"addr".to_string(), &Uint128::from(1000u128))],
+    )]);
+
+    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    let staking_addr = app
+        .instantiate(code_id, Addr::unchecked("addr"), &msg, &[], "staking")
+        .unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        staking_token: staking_token.clone(),
+        min_bond_amount: None,
+    };
+    let _res = app
+        .execute(Addr::unchecked("owner"), staking_addr.clone(), &msg, &[])
+        .unwrap();
+
+    // bond through the lp token contract
+    let _res = app
+        .execute(
+            Addr::unchecked("addr"),
+            staking_token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: staking_addr.to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&Cw20HookMsg::Bond {
+                    lock_duration: None,
+                    referrer: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let res: RewardInfoResponse = app
+        .query(
+            staking_addr.clone(),
+            &QueryMsg::RewardInfo {
+                staker_addr: Addr::unchecked("addr"),
+                staking_token: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.reward_infos,
+        vec![RewardInfoResponseItem {
+            staking_token: staking_token.clone(),
+            bond_amount: Uint128::from(100u128),
+            weighted_bond_amount: Uint128::from(100u128),
+            locks: vec![],
+            pending_reward: Uint128::zero(),
+            pending_withdraw: vec![],
+            reward_decimals: vec![],
+            should_migrate: None,
+            total_claimed: Uint128::zero(),
+            // the block time of the bond, the app moves it every block
+            last_updated: res.reward_infos[0].last_updated,
+        }]
+    );
+
+    let res: PoolInfoResponse = app
+        .query(
+            staking_addr.clone(),
+            &QueryMsg::PoolInfo {
+                staking_token: staking_token.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.total_bond_amount, Uint128::from(100u128));
+}
+
+#[test]
+fn test_query_stakers() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr1", "staking", 100)
+        .with_bond("addr2", "staking", 200)
+        .with_bond("addr3", "staking", 300)
+        .build();
+    let stakers_in =
+        |test_env: &StakingTestEnv, start_after: Option<Addr>, limit: u32, order: Option<i32>| {
+            test_env
+                .query::<StakersResponse>(QueryMsg::Stakers {
+                    staking_token: Addr::unchecked("staking"),
+                    start_after,
+                    limit: Some(limit),
+                    order,
+                })
+                .stakers
+        };
+    let stakers = |test_env: &StakingTestEnv, start_after: Option<Addr>, limit: u32| {
+        stakers_in(test_env, start_after, limit, None)
+    };
+
+    let all = stakers(&test_env, None, 10);
+    assert_eq!(all.len(), 3);
+    for staker in all.iter() {
+        test_env.assert_bond(
+            staker.staker_addr.as_str(),
+            "staking",
+            staker.bond_amount.u128(),
+        );
+    }
+    let first_page = stakers(&test_env, None, 2);
+    assert_eq!(first_page, all[..2].to_vec());
+    assert_eq!(
+        stakers(&test_env, Some(first_page[1].staker_addr.clone()), 2),
+        all[2..].to_vec()
+    );
+
+    // descending pages walk the same keys back, start_after still excluded
+    let mut reversed = all.clone();
+    reversed.reverse();
+    assert_eq!(
+        stakers_in(&test_env, None, 10, Some(Order::Descending.into())),
+        reversed
+    );
+    assert_eq!(
+        stakers_in(
+            &test_env,
+            Some(reversed[0].staker_addr.clone()),
+            10,
+            Some(Order::Descending.into())
+        ),
+        reversed[1..].to_vec()
+    );
+    assert!(stakers_in(
+        &test_env,
+        Some(reversed[2].staker_addr.clone()),
+        10,
+        Some(Order::Descending.into())
+    )
+    .is_empty());
+
+    // a full unbond takes the staker off the list
+    test_env
+        .execute(
+            "addr2",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(200u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    let remaining = stakers(&test_env, None, 10);
+    assert_eq!(remaining.len(), 2);
+    assert!(remaining
+        .iter()
+        .all(|staker| staker.staker_addr != Addr::unchecked("addr2")));
+}
+
+#[test]
+fn test_query_stale_stakers() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr1", "staking", 100)
+        .with_bond("addr2", "staking", 200)
+        .with_bond("addr3", "staking", 300)
+        .build();
+    let bonded_at = test_env.env.block.time.seconds();
+    let stale = |test_env: &StakingTestEnv, older_than: u64, start_after: Option<Addr>, limit| {
+        test_env.query::<StaleStakersResponse>(QueryMsg::StaleStakers {
+            staking_token: Addr::unchecked("staking"),
+            older_than,
+            start_after,
+            limit: Some(limit),
+        })
+    };
+
+    test_env.advance_seconds(100);
+    test_env
+        .execute(
+            "staking",
+            bond_receive_msg("addr1", Uint128::from(10u128), None).unwrap(),
+        )
+        .unwrap();
+    let res = test_env.reward_info("addr1", Some("staking"));
+    assert_eq!(res.reward_infos[0].last_updated, bonded_at + 100);
+
+    let res = stale(&test_env, bonded_at + 100, None, 10);
+    assert_eq!(res.next_start_after, None);
+    let mut stakers = res.stakers.clone();
+    stakers.sort_by(|a, b| a.staker_addr.cmp(&b.staker_addr));
+    assert_eq!(
+        stakers,
+        vec![
+            StaleStaker {
+                staker_addr: Addr::unchecked("addr2"),
+                bond_amount: Uint128::from(200u128),
+                last_updated: bonded_at,
+            },
+            StaleStaker {
+                staker_addr: Addr::unchecked("addr3"),
+                bond_amount: Uint128::from(300u128),
+                last_updated: bonded_at,
+            },
+        ]
+    );
+    // untouched since is strict
+    assert!(stale(&test_env, bonded_at, None, 10).stakers.is_empty());
+
+    // a page bounds the stakers scanned, the next one starts after the last of them
+    let first_page = stale(&test_env, bonded_at + 100, None, 2);
+    let start_after = first_page.next_start_after.clone();
+    assert!(start_after.is_some());
+    let second_page = stale(&test_env, bonded_at + 100, start_after, 2);
+    assert_eq!(second_page.next_start_after, None);
+    let mut paged = [first_page.stakers, second_page.stakers].concat();
+    paged.sort_by(|a, b| a.staker_addr.cmp(&b.staker_addr));
+    assert_eq!(paged, stakers);
+}
+
+#[test]
+fn test_pool_staker_count() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr1", "staking", 100)
+        .with_bond("addr2", "staking", 200)
+        .with_bond("addr1", "staking", 50)
+        .build();
+    let unbond = |amount: u128| ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::from(amount)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let pool_info = test_env.pool_info("staking");
+    assert_eq!(pool_info.staker_count, Some(2));
+    assert_eq!(pool_info.lifetime_bonded, Uint128::from(350u128));
+
+    // a partial unbond keeps the position counted
+    test_env.execute("addr1", unbond(100)).unwrap();
+    assert_eq!(test_env.pool_info("staking").staker_count, Some(2));
+    test_env.execute("addr1", unbond(50)).unwrap();
+    let pool_info = test_env.pool_info("staking");
+    assert_eq!(pool_info.staker_count, Some(1));
+    assert_eq!(pool_info.lifetime_bonded, Uint128::from(350u128));
+    assert_eq!(pool_info.lifetime_unbonded, Uint128::from(150u128));
+
+    // the counters move with the pool when its staking token is deprecated
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "new_staking"]);
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::DeprecateStakingToken {
+                staking_token: Addr::unchecked("staking"),
+                new_staking_token: Addr::unchecked("new_staking"),
+                grace_period: None,
+            },
+        )
+        .unwrap();
+    let pool_info = test_env.pool_info("new_staking");
+    assert_eq!(pool_info.staker_count, Some(1));
+    assert_eq!(pool_info.lifetime_unbonded, Uint128::from(150u128));
+}
+
+#[test]
+fn test_query_unbonding_claims() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking_a", default_rewards_per_sec())
+        .with_pool("staking_b", default_rewards_per_sec())
+        .with_unbonding_period("staking_a", 100)
+        .with_unbonding_period("staking_b", 200)
+        .with_bond("addr", "staking_a", 100)
+        .with_bond("addr", "staking_b", 100)
+        .build();
+    let now = test_env.env.block.time.seconds();
+    let unbond = |staking_token: &str, amount: u128| ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked(staking_token),
+        amount: Some(Uint128::from(amount)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let unbonding_claims = |test_env: &StakingTestEnv, staking_token: Option<&str>| {
+        test_env.query::<UnbondingClaimsResponse>(QueryMsg::UnbondingClaims {
+            staker_addr: Addr::unchecked("addr"),
+            staking_token: staking_token.map(Addr::unchecked),
+            start_after: None,
+            limit: None,
+            order: None,
+        })
+    };
+    test_env.execute("addr", unbond("staking_a", 30)).unwrap();
+    test_env.execute("addr", unbond("staking_b", 40)).unwrap();
+    test_env.advance_seconds(100);
+
+    let res = unbonding_claims(&test_env, Some("staking_a"));
+    assert_eq!(
+        res.claims,
+        vec![UnbondingClaim {
+            staking_token: Addr::unchecked("staking_a"),
+            amount: Uint128::from(30u128),
+            release_time: now + 100,
+            claimable: true,
+        }]
+    );
+    assert_eq!(res.total_claimable, Uint128::from(30u128));
+
+    // only the released claim counts towards the total
+    let res = unbonding_claims(&test_env, None);
+    assert_eq!(res.claims.len(), 2);
+    assert!(res.claims.contains(&UnbondingClaim {
+        staking_token: Addr::unchecked("staking_b"),
+        amount: Uint128::from(40u128),
+        release_time: now + 200,
+        claimable: false,
+    }));
+    assert_eq!(res.total_claimable, Uint128::from(30u128));
+
+    // claimed entries are gone
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Claim {
+                staking_token: Addr::unchecked("staking_a"),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        unbonding_claims(&test_env, Some("staking_a")).claims,
+        vec![]
+    );
+    let res = unbonding_claims(&test_env, None);
+    assert_eq!(res.claims.len(), 1);
+    assert_eq!(res.total_claimable, Uint128::zero());
+}
+
+fn bond_amount_at(test_env: &StakingTestEnv, height: u64) -> Uint128 {
+    let res: BondAmountAtResponse = test_env.query(QueryMsg::BondAmountAt {
+        staker_addr: Addr::unchecked("addr"),
+        staking_token: Addr::unchecked("staking"),
+        height,
+    });
+    res.bond_amount
+}
+
+fn bond_at(test_env: &mut StakingTestEnv, height: u64, amount: u128) {
+    test_env.env.block.height = height;
+    test_env
+        .execute(
+            "staking",
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "addr".to_string(),
+                amount: Uint128::from(amount),
+                msg: to_binary(&Cw20HookMsg::Bond {
+                    lock_duration: None,
+                    referrer: None,
+                })
+                .unwrap(),
+            }),
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_bond_amount_at() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .build();
+
+    bond_at(&mut test_env, 100, 50);
+    bond_at(&mut test_env, 200, 30);
+    test_env.env.block.height = 300;
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(20u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(bond_amount_at(&test_env, 99), Uint128::zero());
+    assert_eq!(bond_amount_at(&test_env, 100), Uint128::from(50u128));
+    assert_eq!(bond_amount_at(&test_env, 250), Uint128::from(80u128));
+    assert_eq!(bond_amount_at(&test_env, 300), Uint128::from(60u128));
+
+    // only the owner sets the retention
+    let msg = ExecuteMsg::UpdateBondCheckpointRetention {
+        retention_blocks: 150,
+    };
+    let err = test_env.execute("addr", msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    test_env.execute("owner", msg).unwrap();
+
+    // the checkpoint at 200 still covers the cutoff at 250, the one at 100 is dropped
+    bond_at(&mut test_env, 400, 10);
+    assert_eq!(bond_amount_at(&test_env, 150), Uint128::zero());
+    assert_eq!(bond_amount_at(&test_env, 250), Uint128::from(80u128));
+    assert_eq!(bond_amount_at(&test_env, 400), Uint128::from(70u128));
+}
+
+#[test]
+fn test_bond_overflow() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", u128::MAX - 1)
+        .build();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr2".to_string(),
+        amount: Uint128::from(2u128),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
+    });
+    let err = test_env.execute("staking", msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::OverflowError(OverflowError::new(
+            OverflowOperation::Add,
+            Uint128::MAX - Uint128::one(),
+            Uint128::from(2u128),
+        ))
+    );
+
+    let msg = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::MAX),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let err = test_env.execute("addr", msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientBond {
+            staker: "addr".to_string(),
+            asset: "staking".to_string(),
+            available: Uint128::MAX - Uint128::one(),
+            requested: Uint128::MAX,
+        }
+    );
+    test_env.assert_bond("addr", "staking", u128::MAX - 1);
+}
+
+#[test]
+fn test_full_exit_removes_position() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr2", "staking", 1000)
+        .build();
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::UpdateDustThreshold {
+                dust_threshold: Uint128::from(10u128),
+            },
+        )
+        .unwrap();
+    let bond = |test_env: &mut StakingTestEnv| {
+        test_env
+            .execute(
+                "staking",
+                ExecuteMsg::Receive(Cw20ReceiveMsg {
+                    sender: "addr".to_string(),
+                    amount: Uint128::from(100u128),
+                    msg: to_binary(&Cw20HookMsg::Bond {
+                        lock_duration: None,
+                        referrer: None,
+                    })
+                    .unwrap(),
+                }),
+            )
+            .unwrap();
+    };
+    let unbond = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::from(100u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let deposit = ExecuteMsg::DepositReward {
+        rewards: vec![RewardMsg {
+            staking_token: Addr::unchecked("staking"),
+            total_accumulation_amount: Uint128::from(11u128),
+        }],
+    };
+    let storage_keys = |test_env: &StakingTestEnv| {
+        test_env
+            .deps
+            .storage
+            .range(None, None, Order::Ascending)
+            .count()
+    };
+
+    let mut keys = None;
+    for _ in 0..10 {
+        // each cycle earns the position 1 unit, below the threshold
+        bond(&mut test_env);
+        test_env.execute("rewarder", deposit.clone()).unwrap();
+        test_env.assert_pending("addr", "staking", 1);
+        test_env.execute("addr", unbond.clone()).unwrap();
+
+        assert_eq!(test_env.reward_info("addr", None).reward_infos, vec![]);
+        let count = storage_keys(&test_env);
+        assert_eq!(*keys.get_or_insert(count), count);
+    }
+    // the dust of each exit went to the next deposit, the last one only addr2 shares
+    test_env.execute("rewarder", deposit).unwrap();
+    test_env.assert_pending("addr2", "staking", 120);
+    assert_eq!(
+        test_env.pool_info("staking").pending_reward,
+        Uint128::zero()
+    );
+
+    // above the threshold the reward stays owed
+    bond(&mut test_env);
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::UpdateDustThreshold {
+                dust_threshold: Uint128::zero(),
+            },
+        )
+        .unwrap();
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(11u128),
+                }],
+            },
+        )
+        .unwrap();
+    test_env.execute("addr", unbond).unwrap();
+    test_env.assert_pending("addr", "staking", 1);
+}
+
+#[test]
+fn test_prune_reward_infos() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("active", "staking", 100)
+        .build();
+    let asset_key = test_env.deps.api.addr_canonicalize("staking").unwrap();
+    let canon = |test_env: &StakingTestEnv, staker: &str| {
+        test_env.deps.api.addr_canonicalize(staker).unwrap()
+    };
+    let empty = RewardInfo {
+        native_token: false,
+        index: Decimal256::one(),
+        bond_amount: Uint128::zero(),
+        pending_reward: Uint128::zero(),
+        pending_withdraw: vec![],
+        locks: vec![],
+        proxy_indices: vec![],
+        total_claimed: Uint128::zero(),
+        boost_shortfall: Uint128::zero(),
+        last_updated: 0,
+        last_bond_time: 0,
+    };
+    // positions left behind before exits removed them, and one that claimed before
+    let mut positions = vec![];
+    for i in 0..5 {
+        positions.push((format!("zombie{}", i), empty.clone()));
+    }
+    positions.push((
+        "claimed".to_string(),
+        RewardInfo {
+            total_claimed: Uint128::from(5u128),
+            ..empty.clone()
+        },
+    ));
+    for (staker, reward_info) in positions {
+        let staker = canon(&test_env, &staker);
+        rewards_store(&mut test_env.deps.storage, &staker)
+            .save(&asset_key, &reward_info)
+            .unwrap();
+        stakers_store(&mut test_env.deps.storage, &asset_key)
+            .save(&staker, &true)
+            .unwrap();
+    }
+    // a staker entry without any position
+    let orphan = canon(&test_env, "orphan");
+    stakers_store(&mut test_env.deps.storage, &asset_key)
+        .save(&orphan, &true)
+        .unwrap();
+
+    let prune = |test_env: &mut StakingTestEnv| {
+        let res = test_env
+            .execute("anyone", ExecuteMsg::PruneRewardInfos { limit: Some(3) })
+            .unwrap();
+        let attribute = |key: &str| {
+            res.attributes
+                .iter()
+                .find(|attr| attr.key == key)
+                .unwrap()
+                .value
+                .clone()
+        };
+        (
+            attribute("pruned").parse::<u64>().unwrap(),
+            attribute("finished") == "true",
+        )
+    };
+    let (mut pruned, mut finished) = prune(&mut test_env);
+    // bonding goes on between the calls
+    test_env
+        .execute(
+            "staking",
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "late".to_string(),
+                amount: Uint128::from(50u128),
+                msg: to_binary(&Cw20HookMsg::Bond {
+                    lock_duration: None,
+                    referrer: None,
+                })
+                .unwrap(),
+            }),
+        )
+        .unwrap();
+    let mut calls = 1;
+    while !finished {
+        let (count, done) = prune(&mut test_env);
+        pruned += count;
+        finished = done;
+        calls += 1;
+        assert!(calls <= 5);
+    }
+    assert_eq!(pruned, 6);
+
+    test_env.assert_bond("active", "staking", 100);
+    test_env.assert_bond("late", "staking", 50);
+    let claimed = canon(&test_env, "claimed");
+    assert!(rewards_read(&test_env.deps.storage, &claimed)
+        .may_load(&asset_key)
+        .unwrap()
+        .is_some());
+    let zombie = canon(&test_env, "zombie0");
+    assert!(rewards_read(&test_env.deps.storage, &zombie)
+        .may_load(&asset_key)
+        .unwrap()
+        .is_none());
+
+    // the next call starts over and finds nothing left to prune
+    let (count, _) = prune(&mut test_env);
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_zero_bond_and_unbond() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr".to_string(),
+        amount: Uint128::zero(),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration: None,
+            referrer: None,
+        })
+        .unwrap(),
+    });
+    let err = test_env.execute("staking", msg).unwrap_err();
+    assert_eq!(err, ContractError::ZeroCw20HookAmount {});
+
+    let msg = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::zero()),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let err = test_env.execute("addr", msg).unwrap_err();
+    assert_eq!(err, ContractError::ZeroUnbondAmount {});
+    test_env.assert_bond("addr", "staking", 100);
+}
+
+#[test]
+fn test_malformed_hook_messages() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .build();
+    let mut receive = |msg: &[u8]| {
+        test_env
+            .execute(
+                "staking",
+                ExecuteMsg::Receive(Cw20ReceiveMsg {
+                    sender: "addr".to_string(),
+                    amount: Uint128::from(100u128),
+                    msg: Binary::from(msg),
+                }),
+            )
+            .unwrap_err()
+    };
+
+    let missing: [&[u8]; 4] = [b"", b"  ", b"null", b"{}"];
+    for msg in missing.iter() {
+        assert_eq!(
+            receive(msg).to_string(),
+            "missing hook message: expected Bond or DepositReward"
+        );
+    }
+    assert_eq!(
+        receive(br#"{"stake":{}}"#),
+        ContractError::UnknownCw20HookMessage {
+            variant: "stake".to_string()
+        }
+    );
+    assert_eq!(
+        receive(br#"{"stake":{}}"#).to_string(),
+        "unknown hook message stake"
+    );
+
+    // serde's error names the field that did not parse
+    let err = receive(br#"{"bond":{"lockDuration":10}}"#);
+    assert!(matches!(
+        &err,
+        ContractError::MalformedCw20HookMessage { variant, .. } if variant == "bond"
+    ));
+    let err = err.to_string();
+    assert!(err.starts_with("invalid bond hook message: "), "{}", err);
+    assert!(err.contains("lockDuration"), "{}", err);
+    let err = receive(br#"{"bond":{"lock_duration":"ten"}}"#).to_string();
+    assert!(err.starts_with("invalid bond hook message: "), "{}", err);
+
+    // not an object of a single variant
+    assert_eq!(receive(b"bond"), ContractError::InvalidCw20HookMessage {});
+    assert_eq!(
+        receive(br#"{"bond":{},"deposit_reward":{}}"#),
+        ContractError::InvalidCw20HookMessage {}
+    );
+}
+
+#[test]
+fn test_unbond_without_position() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .build();
+
+    let msg = ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::from(10u128)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+    let err = test_env.execute("addr", msg).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cannot unbond 10: only 0 bonded by addr for staking"
+    );
+}
+
+// indexers key on these names, a renamed attribute has to show up here
+#[test]
+fn test_handler_attribute_keys() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_unbonding_period("staking", 100)
+        .with_bond("staker", "staking", 100)
+        .with_reward_deposit("staking", 100)
+        .build();
+    let keys = |res: &Response| -> Vec<String> {
+        res.attributes.iter().map(|attr| attr.key.clone()).collect()
+    };
+
+    let res = test_env
+        .execute(
+            "staking",
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "staker".to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&Cw20HookMsg::Bond {
+                    lock_duration: None,
+                    referrer: None,
+                })
+                .unwrap(),
+            }),
+        )
+        .unwrap();
+    assert_eq!(
+        keys(&res),
+        vec!["action", "staker_addr", "staking_token", "amount"]
+    );
+
+    let res = test_env
+        .execute(
+            "staker",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(50u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        keys(&res),
+        vec![
+            "action",
+            "staker_addr",
+            "amount",
+            "staking_token",
+            "release_time"
+        ]
+    );
+
+    test_env.advance_seconds(100);
+    let res = test_env
+        .execute(
+            "staker",
+            ExecuteMsg::Claim {
+                staking_token: Addr::unchecked("staking"),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        keys(&res),
+        vec!["action", "staker_addr", "amount", "staking_token"]
+    );
+
+    let res = test_env
+        .execute(
+            "staker",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        keys(&res),
+        vec![
+            "action",
+            "staker_addr",
+            "recipient",
+            "staking_token",
+            "reward_assets",
+            "gross",
+            "fee",
+            "net"
+        ]
+    );
+
+    let res = test_env
+        .execute(
+            "owner",
+            ExecuteMsg::WithdrawOthers {
+                staking_token: Some(Addr::unchecked("staking")),
+                staker_addrs: vec![Addr::unchecked("staker")],
+            },
+        )
+        .unwrap();
+    assert_eq!(keys(&res), vec!["action", "stakers", "staking_token"]);
+}
+
+#[test]
+fn test_response_data() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_unbonding_period("staking", 100)
+        .with_bond("staker", "staking", 100)
+        .with_reward_deposit("staking", 100)
+        .build();
+
+    let res = test_env
+        .execute(
+            "staking",
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "staker".to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&Cw20HookMsg::Bond {
+                    lock_duration: None,
+                    referrer: None,
+                })
+                .unwrap(),
+            }),
+        )
+        .unwrap();
+    assert_eq!(
+        from_binary::<BondResponse>(&res.data.unwrap()).unwrap(),
+        BondResponse {
+            staker_addr: Addr::unchecked("staker"),
+            staking_token: Addr::unchecked("staking"),
+            amount: Uint128::from(100u128),
+        }
+    );
+
+    let res = test_env
+        .execute(
+            "staker",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(50u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        from_binary::<UnbondResponse>(&res.data.unwrap()).unwrap(),
+        UnbondResponse {
+            staker_addr: Addr::unchecked("staker"),
+            staking_token: Addr::unchecked("staking"),
+            amount: Uint128::from(50u128),
+            release_time: Some(test_env.env.block.time.seconds() + 100),
+        }
+    );
+
+    // what was sent, the same amounts the transfer messages carry
+    let res = test_env
+        .execute(
+            "staker",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
+            },
+        )
+        .unwrap();
+    let data: WithdrawResponse = from_binary(&res.data.unwrap()).unwrap();
+    assert_eq!(
+        data.reward_assets,
+        vec![
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(33u128),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ATOM_DENOM.to_string(),
+                },
+                amount: Uint128::from(66u128),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_vote_escrow_boost() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr2", "staking", 100)
+        .build();
+    let update_boost = |staker: &str| ExecuteMsg::UpdateBoost {
+        staker_addr: Addr::unchecked(staker),
+        staking_token: Addr::unchecked("staking"),
+    };
+
+    // without a boost contract the position keeps its full weight
+    let res = test_env.execute("anyone", update_boost("addr2")).unwrap();
+    assert!(res.attributes.contains(&attr("boost_shortfall", "0")));
+
+    let boost_contract_msg = ExecuteMsg::UpdateBoostContract {
+        boost_contract: Some(Addr::unchecked("boost")),
+    };
+    let res = test_env
+        .execute("addr", boost_contract_msg.clone())
+        .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    test_env.execute("owner", boost_contract_msg).unwrap();
+    let config: ConfigResponse = test_env.query(QueryMsg::Config {});
+    assert_eq!(config.boost_contract, Some(Addr::unchecked("boost")));
+
+    let mock_voting_power = |test_env: &mut StakingTestEnv, voting_powers: Vec<(&str, u128)>| {
+        let total_voting_power: u128 = voting_powers.iter().map(|(_, power)| power).sum();
+        let voting_powers: Vec<(String, u128)> = voting_powers
+            .into_iter()
+            .map(|(staker, power)| (staker.to_string(), power))
+            .collect();
+        test_env.deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "boost" => {
+                let BoostQueryMsg::VotingPower { address } = from_binary(msg).unwrap();
+                let voting_power = voting_powers
+                    .iter()
+                    .find(|(staker, _)| *staker == address)
+                    .map_or(0, |(_, power)| *power);
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&VotingPowerResponse {
+                        voting_power: Uint128::from(voting_power),
+                        total_voting_power: Uint128::from(total_voting_power),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+    };
+    mock_voting_power(&mut test_env, vec![("addr2", 100)]);
+
+    // no voting power leaves 40 of the 100 bonded, all of it boosts addr2 to its full weight
+    test_env
+        .execute(
+            "staking",
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "addr".to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&Cw20HookMsg::Bond {
+                    lock_duration: None,
+                    referrer: None,
+                })
+                .unwrap(),
+            }),
+        )
+        .unwrap();
+    let res = test_env.execute("anyone", update_boost("addr2")).unwrap();
+    assert!(res.attributes.contains(&attr("boost_shortfall", "0")));
+    let weight = |test_env: &StakingTestEnv, staker: &str| {
+        test_env.reward_info(staker, Some("staking")).reward_infos[0].weighted_bond_amount
+    };
+    assert_eq!(weight(&test_env, "addr"), Uint128::from(40u128));
+    assert_eq!(weight(&test_env, "addr2"), Uint128::from(100u128));
+
+    let deposit = |test_env: &mut StakingTestEnv, amount: u128| {
+        test_env
+            .execute(
+                "rewarder",
+                ExecuteMsg::DepositReward {
+                    rewards: vec![RewardMsg {
+                        staking_token: Addr::unchecked("staking"),
+                        total_accumulation_amount: Uint128::from(amount),
+                    }],
+                },
+            )
+            .unwrap();
+    };
+    deposit(&mut test_env, 140);
+    test_env.assert_pending("addr", "staking", 40);
+    test_env.assert_pending("addr2", "staking", 100);
+
+    // a third of the voting power adds 40 back, the reward owed so far is settled first
+    mock_voting_power(&mut test_env, vec![("addr", 50), ("addr2", 100)]);
+    let res = test_env.execute("anyone", update_boost("addr")).unwrap();
+    assert!(res.attributes.contains(&attr("boost_shortfall", "20")));
+    assert_eq!(
+        test_env.pool_info("staking").total_bond_amount,
+        Uint128::from(200u128)
+    );
+    test_env.assert_pending("addr", "staking", 0);
+    deposit(&mut test_env, 180);
+    test_env.assert_pending("addr", "staking", 80);
+    test_env.assert_pending("addr2", "staking", 200);
+
+    // unbonding shrinks the shortfall with the position
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(50u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(weight(&test_env, "addr"), Uint128::from(50u128));
+}
+
+#[test]
+fn test_withdraw_operator() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    let withdraw_msg = |recipient: Option<&str>| ExecuteMsg::Withdraw {
+        staking_token: Some(Addr::unchecked("staking")),
+        ibc_recipient: None,
+        amounts: None,
+        recipient: recipient.map(Addr::unchecked),
+        staker_addr: Some(Addr::unchecked("addr")),
+    };
+    let operator = |test_env: &StakingTestEnv| {
+        let res: OperatorResponse = test_env.query(QueryMsg::Operator {
+            staker_addr: Addr::unchecked("addr"),
+        });
+        res.operator
+    };
+
+    let res = test_env.execute("bot", withdraw_msg(None)).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::SetOperator {
+                operator: Some(Addr::unchecked("bot")),
+            },
+        )
+        .unwrap();
+    assert_eq!(operator(&test_env), Some(Addr::unchecked("bot")));
+
+    // the operator can neither redirect the rewards nor touch the bond or the approval
+    let res = test_env
+        .execute("bot", withdraw_msg(Some("bot")))
+        .unwrap_err();
+    assert_eq!(res, ContractError::OperatorRecipient {});
+    let res = test_env
+        .execute(
+            "bot",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(100u128)),
+                withdraw_rewards: Some(true),
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap_err();
+    assert!(matches!(res, ContractError::InsufficientBond { .. }));
+    test_env
+        .execute("bot", ExecuteMsg::SetOperator { operator: None })
+        .unwrap();
+    assert_eq!(operator(&test_env), Some(Addr::unchecked("bot")));
+
+    let res = test_env.execute("bot", withdraw_msg(None)).unwrap();
+    assert!(res.attributes.contains(&attr("staker_addr", "addr")));
+    assert!(res.attributes.contains(&attr("operator", "bot")));
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(99u128, ORAI_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr".to_string(),
+                amount: vec![coin(199u128, ATOM_DENOM)],
+            })),
+        ]
+    );
+    test_env.assert_bond("addr", "staking", 100);
+
+    test_env
+        .execute("addr", ExecuteMsg::SetOperator { operator: None })
+        .unwrap();
+    assert_eq!(operator(&test_env), None);
+    let res = test_env.execute("bot", withdraw_msg(None)).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_bond_hooks() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+
+    let add_hook = |hook: &str| ExecuteMsg::AddHook {
+        contract_addr: Addr::unchecked(hook),
+    };
+    let res = test_env.execute("addr", add_hook("hook0")).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    for i in 0..5 {
+        test_env
+            .execute("owner", add_hook(&format!("hook{}", i)))
+            .unwrap();
+    }
+    let res = test_env.execute("owner", add_hook("hook0")).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::HookRegistered {
+            contract_addr: "hook0".to_string()
+        }
+    );
+    let res = test_env.execute("owner", add_hook("hook5")).unwrap_err();
+    assert_eq!(res, ContractError::TooManyHooks { max: 5 });
+    for i in 1..5 {
+        test_env
+            .execute(
+                "owner",
+                ExecuteMsg::RemoveHook {
+                    contract_addr: Addr::unchecked(format!("hook{}", i)),
+                },
+            )
+            .unwrap();
+    }
+    let config: ConfigResponse = test_env.query(QueryMsg::Config {});
+    assert_eq!(config.hooks, vec![Addr::unchecked("hook0")]);
+
+    let hook_msg = |new_amount: u128| {
+        SubMsg::reply_on_error(
+            WasmMsg::Execute {
+                contract_addr: "hook0".to_string(),
+                msg: to_binary(&StakingHookMsg::BondChanged {
+                    staker_addr: Addr::unchecked("addr"),
+                    staking_token: Addr::unchecked("staking"),
+                    new_amount: Uint128::from(new_amount),
+                })
+                .unwrap(),
+                funds: vec![],
+            },
+            HOOK_REPLY_ID,
+        )
+    };
+    let res = test_env
+        .execute(
+            "staking",
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "addr".to_string(),
+                amount: Uint128::from(50u128),
+                msg: to_binary(&Cw20HookMsg::Bond {
+                    lock_duration: None,
+                    referrer: None,
+                })
+                .unwrap(),
+            }),
+        )
+        .unwrap();
+    assert_eq!(res.messages, vec![hook_msg(150)]);
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(150u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.messages[1..].to_vec(), vec![hook_msg(0)]);
+
+    // a failing hook is recorded without reverting the bond change
+    let res = reply(
+        test_env.deps.as_mut(),
+        test_env.env.clone(),
+        Reply {
+            id: HOOK_REPLY_ID,
+            result: SubMsgResult::Err("out of order".to_string()),
         },
-        &[],
     )
     .unwrap();
+    assert!(res.attributes.contains(&attr("action", "hook_failed")));
+
+    // the owner can drop it while staking is paused
+    test_env.execute("owner", ExecuteMsg::Pause {}).unwrap();
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::RemoveHook {
+                contract_addr: Addr::unchecked("hook0"),
+            },
+        )
+        .unwrap();
+    let res = test_env
+        .execute(
+            "owner",
+            ExecuteMsg::RemoveHook {
+                contract_addr: Addr::unchecked("hook0"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::HookNotFound {
+            contract_addr: "hook0".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_rescue_funds() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    test_env.deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![coin(1000u128, ORAI_DENOM), coin(50u128, "stray")],
+    );
+    let rescue_msg = |asset: AssetInfo, amount: u128| ExecuteMsg::RescueFunds {
+        asset,
+        amount: Uint128::from(amount),
+        recipient: Addr::unchecked("addr"),
+    };
+    let native = |denom: &str| AssetInfo::NativeToken {
+        denom: denom.to_string(),
+    };
+
+    let res = test_env
+        .execute("addr", rescue_msg(native("stray"), 50))
+        .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    let res = test_env
+        .execute("owner", rescue_msg(native("stray"), 51))
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::RescueExceedsFree {
+            free: Uint128::from(50u128),
+            requested: Uint128::from(51u128),
+        }
+    );
+    let res = test_env
+        .execute("owner", rescue_msg(native("stray"), 50))
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr".to_string(),
+            amount: vec![coin(50u128, "stray")],
+        })]
+    );
+
+    // the 300 deposited are still owed to the staker
+    let res = test_env
+        .execute("owner", rescue_msg(native(ORAI_DENOM), 701))
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::RescueExceedsFree {
+            free: Uint128::from(700u128),
+            requested: Uint128::from(701u128),
+        }
+    );
+    test_env
+        .execute("owner", rescue_msg(native(ORAI_DENOM), 700))
+        .unwrap();
+
+    // the bonded staking token is never free, not even once it is deprecated
+    let staking = AssetInfo::Token {
+        contract_addr: Addr::unchecked("staking"),
+    };
+    let res = test_env
+        .execute("owner", rescue_msg(staking, 1))
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::ProtectedAsset {
+            asset: "staking".to_string()
+        }
+    );
+    mock_staking_tokens(&mut test_env.deps.querier, &["staking", "new_staking"]);
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::DeprecateStakingToken {
+                staking_token: Addr::unchecked("staking"),
+                new_staking_token: Addr::unchecked("new_staking"),
+                grace_period: None,
+            },
+        )
+        .unwrap();
+    for token in ["staking", "new_staking"] {
+        let res = test_env
+            .execute(
+                "owner",
+                rescue_msg(
+                    AssetInfo::Token {
+                        contract_addr: Addr::unchecked(token),
+                    },
+                    1,
+                ),
+            )
+            .unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::ProtectedAsset {
+                asset: token.to_string()
+            }
+        );
+    }
+}
+
+#[test]
+fn test_reconcile_and_sweep_excess() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_unbonding_period("staking", 100)
+        .with_bond("addr", "staking", 100)
+        .build();
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(30u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+    let mock_balance = |test_env: &mut StakingTestEnv, balance: u128| {
+        test_env.deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "staking" => {
+                match from_binary(msg).unwrap() {
+                    Cw20QueryMsg::Balance { .. } => SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&Cw20BalanceResponse {
+                            balance: Uint128::from(balance),
+                        })
+                        .unwrap(),
+                    )),
+                    _ => panic!("unexpected query"),
+                }
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+    };
+    mock_balance(&mut test_env, 150);
+
+    let res: ReconcileResponse = test_env.query(QueryMsg::Reconcile {
+        staking_token: Addr::unchecked("staking"),
+    });
+    assert_eq!(
+        res,
+        ReconcileResponse {
+            staking_token: Addr::unchecked("staking"),
+            balance: Uint128::from(150u128),
+            total_bond_amount: Uint128::from(70u128),
+            deprecated_total_bond: Uint128::zero(),
+            total_unbonding: Uint128::from(30u128),
+            surplus: Uint128::from(50u128),
+        }
+    );
+
+    let sweep_msg = ExecuteMsg::SweepExcess {
+        staking_token: Addr::unchecked("staking"),
+        recipient: Addr::unchecked("treasury"),
+    };
+    let res = test_env.execute("addr", sweep_msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    let res = test_env.execute("owner", sweep_msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "treasury".to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    // the claim pays out the unbonding, what is left backs the bonds
+    test_env.advance_seconds(100);
+    test_env
+        .execute(
+            "addr",
+            ExecuteMsg::Claim {
+                staking_token: Addr::unchecked("staking"),
+            },
+        )
+        .unwrap();
+    mock_balance(&mut test_env, 70);
+    let res = test_env.execute("owner", sweep_msg).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::NothingToSweep {
+            staking_token: "staking".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_unbond_to_recipient() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_pool("staking2", default_rewards_per_sec())
+        .with_unbonding_period("staking2", 100)
+        .with_bond("vault", "staking", 100)
+        .with_bond("vault", "staking2", 100)
+        .build();
+    let unbond_msg = |staking_token: &str, recipient: &str| ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked(staking_token),
+        amount: Some(Uint128::from(40u128)),
+        withdraw_rewards: None,
+        recipient: Some(Addr::unchecked(recipient)),
+        unbond_msg: None,
+    };
+
+    let res = test_env
+        .execute("vault", unbond_msg("staking", ""))
+        .unwrap_err();
+    assert!(matches!(res, ContractError::Std(_)));
+    let res = test_env
+        .execute("vault", unbond_msg("staking2", "user"))
+        .unwrap_err();
+    assert_eq!(res, ContractError::UnbondRecipientWithUnbondingPeriod {});
+    test_env.assert_bond("vault", "staking", 100);
+    test_env.assert_bond("vault", "staking2", 100);
+
+    let res = test_env
+        .execute("vault", unbond_msg("staking", "user"))
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user".to_string(),
+                amount: Uint128::from(40u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert!(res.attributes.contains(&attr("staker_addr", "vault")));
+    assert!(res.attributes.contains(&attr("recipient", "user")));
+    test_env.assert_bond("vault", "staking", 60);
+    test_env.assert_bond("user", "staking", 0);
+}
 
-    let msg = ExecuteMsg::RegisterAsset {
-        staking_token: pair_info.liquidity_token.clone(),
+#[test]
+fn test_unbond_with_send_msg() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    let unbond_msg = |contract: &str, recipient: Option<&str>| ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::from(40u128)),
+        withdraw_rewards: None,
+        recipient: recipient.map(Addr::unchecked),
+        unbond_msg: Some(UnbondSendMsg {
+            contract: Addr::unchecked(contract),
+            msg: to_binary(&"withdraw_liquidity").unwrap(),
+        }),
     };
 
-    let _res = app
-        .execute(Addr::unchecked("owner"), staking_addr.clone(), &msg, &[])
-        .unwrap();
+    let res = test_env.execute("addr", unbond_msg("", None)).unwrap_err();
+    assert!(matches!(res, ContractError::Std(_)));
+    let res = test_env
+        .execute("addr", unbond_msg("pair", Some("user")))
+        .unwrap_err();
+    assert_eq!(res, ContractError::ConflictingRecipients {});
 
-    // no token asset
-    let msg = ExecuteMsg::AutoStake {
-        assets: [
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: ORAI_DENOM.to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: ORAI_DENOM.to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-        ],
-        slippage_tolerance: None,
+    let res = test_env.execute("addr", unbond_msg("pair", None)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "pair".to_string(),
+                amount: Uint128::from(40u128),
+                msg: to_binary(&"withdraw_liquidity").unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert!(res.attributes.contains(&attr("recipient", "pair")));
+    test_env.assert_bond("addr", "staking", 60);
+}
+
+#[test]
+fn test_staker_lists() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("alice", "staking", 100)
+        .with_reward_deposit("staking", 100)
+        .build();
+    let bond_msg = |staker: &str| bond_receive_msg(staker, Uint128::from(10u128), None).unwrap();
+    let whitelist_msg = |enabled: Option<bool>, add: &[&str]| ExecuteMsg::UpdateWhitelist {
+        staking_token: Addr::unchecked("staking"),
+        enabled,
+        add: add.iter().map(|addr| Addr::unchecked(*addr)).collect(),
+        remove: vec![],
     };
 
-    let res = app.execute(
-        Addr::unchecked("addr"),
-        staking_addr.clone(),
-        &msg,
-        &[Coin {
-            denom: ORAI_DENOM.to_string(),
-            amount: Uint128::from(100u128),
-        }],
+    let res = test_env
+        .execute("alice", whitelist_msg(Some(true), &["bob"]))
+        .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    let res = test_env
+        .execute("owner", whitelist_msg(Some(true), &["bob", "bob"]))
+        .unwrap();
+    assert!(res.attributes.contains(&attr("len", "1")));
+
+    let res = test_env.execute("staking", bond_msg("alice")).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::StakerNotAllowed {
+            staker: "alice".to_string(),
+            asset: "staking".to_string(),
+        }
     );
+    test_env.execute("staking", bond_msg("bob")).unwrap();
+    test_env.assert_bond("bob", "staking", 10);
 
-    app.assert_fail(res);
+    // the blacklist wins over the whitelist
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::UpdateBlacklist {
+                staking_token: Addr::unchecked("staking"),
+                add: vec![Addr::unchecked("bob")],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+    let res: CanBondResponse = test_env.query(QueryMsg::CanBond {
+        staking_token: Addr::unchecked("staking"),
+        staker_addr: Addr::unchecked("bob"),
+    });
+    assert_eq!(
+        res,
+        CanBondResponse {
+            can_bond: false,
+            whitelist_enabled: true,
+            whitelisted: true,
+            blacklisted: true,
+        }
+    );
+    let res = test_env.execute("staking", bond_msg("bob")).unwrap_err();
+    assert!(matches!(res, ContractError::StakerNotAllowed { .. }));
 
-    // no native asset
-    let msg = ExecuteMsg::AutoStake {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: asset_addr.clone(),
-                },
-                amount: Uint128::from(1u128),
+    // positions bonded before the lists can still leave
+    test_env
+        .execute(
+            "alice",
+            ExecuteMsg::Withdraw {
+                staking_token: Some(Addr::unchecked("staking")),
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+                staker_addr: None,
             },
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: asset_addr.clone(),
-                },
-                amount: Uint128::from(1u128),
+        )
+        .unwrap();
+    test_env
+        .execute(
+            "bob",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Some(Uint128::from(10u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
             },
-        ],
-        slippage_tolerance: None,
+        )
+        .unwrap();
+    test_env.assert_bond("bob", "staking", 0);
+
+    test_env
+        .execute("owner", whitelist_msg(Some(false), &[]))
+        .unwrap();
+    test_env.execute("staking", bond_msg("alice")).unwrap();
+    test_env.assert_bond("alice", "staking", 110);
+}
+
+#[test]
+fn test_update_pool_config() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .build();
+    let update_msg = |params: PoolConfigUpdate| ExecuteMsg::UpdatePoolConfig {
+        staking_token: Addr::unchecked("staking"),
+        params,
+    };
+    let keys = |res: &Response| -> Vec<String> {
+        res.attributes.iter().map(|attr| attr.key.clone()).collect()
     };
 
-    let res = app.execute(Addr::unchecked("addr"), staking_addr.clone(), &msg, &[]);
+    let res = test_env
+        .execute("addr", update_msg(PoolConfigUpdate::default()))
+        .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
 
-    app.assert_fail(res);
+    // an invalid field rejects the whole update
+    let res = test_env
+        .execute(
+            "owner",
+            update_msg(PoolConfigUpdate {
+                min_bond_amount: Some(Uint128::from(10u128)),
+                reward_window: Some((Some(200), Some(100))),
+                ..PoolConfigUpdate::default()
+            }),
+        )
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::InvalidRewardWindow {
+            start_time: 200,
+            end_time: 100,
+        }
+    );
+    let res = test_env
+        .execute(
+            "owner",
+            update_msg(PoolConfigUpdate {
+                instant_unbond_penalty: Some(Decimal::percent(5)),
+                ..PoolConfigUpdate::default()
+            }),
+        )
+        .unwrap_err();
+    assert_eq!(res, ContractError::PenaltyCollectorNotSet {});
+    assert_eq!(
+        test_env.pool_info("staking").min_bond_amount,
+        Uint128::zero()
+    );
 
-    let msg = ExecuteMsg::AutoStake {
-        assets: [
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: ORAI_DENOM.to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: asset_addr.clone(),
-                },
-                amount: Uint128::from(1u128),
-            },
-        ],
-        slippage_tolerance: None,
-    };
+    let res = test_env
+        .execute(
+            "owner",
+            update_msg(PoolConfigUpdate {
+                unbonding_period: Some(100),
+                min_bond_amount: Some(Uint128::from(10u128)),
+                bond_cap: Some(CapUpdate::Set(Uint128::from(1000u128))),
+                frozen: Some(true),
+                reward_window: Some((Some(100), None)),
+                ..PoolConfigUpdate::default()
+            }),
+        )
+        .unwrap();
+    assert_eq!(
+        keys(&res),
+        vec![
+            "action",
+            "staking_token",
+            "unbonding_period",
+            "min_bond_amount",
+            "bond_cap",
+            "frozen",
+            "reward_start_time",
+            "reward_end_time",
+        ]
+    );
+    assert!(res.attributes.contains(&attr("reward_end_time", "none")));
 
-    // attempt with no coins
-    let res = app.execute(Addr::unchecked("addr"), staking_addr.clone(), &msg, &[]);
-    app.assert_fail(res);
+    let pool_info = test_env.pool_info("staking");
+    assert_eq!(pool_info.unbonding_period, 100);
+    assert_eq!(pool_info.min_bond_amount, Uint128::from(10u128));
+    assert_eq!(pool_info.bond_cap, Some(Uint128::from(1000u128)));
+    assert!(pool_info.frozen);
+    assert_eq!(pool_info.reward_start_time, Some(100));
+    assert_eq!(pool_info.instant_unbond_penalty, Decimal::zero());
 
-    let _res = app
+    test_env
         .execute(
-            Addr::unchecked("addr"),
-            staking_addr.clone(),
-            &msg,
-            &[Coin {
-                denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
-            }],
+            "owner",
+            update_msg(PoolConfigUpdate {
+                bond_cap: Some(CapUpdate::Remove {}),
+                frozen: Some(false),
+                ..PoolConfigUpdate::default()
+            }),
         )
         .unwrap();
+    let pool_info = test_env.pool_info("staking");
+    assert_eq!(pool_info.bond_cap, None);
+    assert!(!pool_info.frozen);
+    assert_eq!(pool_info.unbonding_period, 100);
+}
 
-    // wrong asset
-    let msg = ExecuteMsg::AutoStakeHook {
-        staking_token: pair_info.liquidity_token.clone(),
-        staker_addr: Addr::unchecked("addr"),
-        prev_staking_token_amount: Uint128::zero(),
+#[test]
+fn test_export_state() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking_a", default_rewards_per_sec())
+        .with_pool("staking_b", default_rewards_per_sec())
+        .with_unbonding_period("staking_b", 100)
+        .with_bond("alice", "staking_a", 100)
+        .with_bond("bob", "staking_a", 50)
+        .with_bond("alice", "staking_b", 30)
+        .with_reward_deposit("staking_a", 300)
+        .build();
+    test_env
+        .execute(
+            "alice",
+            ExecuteMsg::Unbond {
+                staking_token: Addr::unchecked("staking_b"),
+                amount: Some(Uint128::from(10u128)),
+                withdraw_rewards: None,
+                recipient: None,
+                unbond_msg: None,
+            },
+        )
+        .unwrap();
+
+    // walks a segment two records at a time
+    let export = |test_env: &StakingTestEnv, segment: ExportSegment| -> Vec<ExportRecord> {
+        let mut records = vec![];
+        let mut start_after = None;
+        loop {
+            let res: ExportStateResponse = test_env.query(QueryMsg::ExportState {
+                segment: segment.clone(),
+                start_after,
+                limit: Some(2),
+            });
+            assert!(res.records.len() <= 2);
+            records.extend(res.records);
+            match res.next_key {
+                Some(next_key) => start_after = Some(next_key),
+                None => break records,
+            }
+        }
     };
-    let _res = app.execute(staking_addr.clone(), staking_addr.clone(), &msg, &[]);
 
-    // valid msg
-    let msg = ExecuteMsg::AutoStakeHook {
-        staking_token: pair_info.liquidity_token.clone(),
-        staker_addr: Addr::unchecked("addr"),
-        prev_staking_token_amount: Uint128::zero(),
+    let pools = export(&test_env, ExportSegment::Pools);
+    assert_eq!(pools.len(), 2);
+    // canonical addresses order the pools, not their names
+    assert!(pools.iter().any(|record| matches!(
+        record,
+        ExportRecord::Pool { pool_info, rewards_per_sec, .. }
+            if pool_info.staking_token == Addr::unchecked("staking_a")
+                && rewards_per_sec == &default_rewards_per_sec()
+    )));
+
+    let reward_infos = export(&test_env, ExportSegment::RewardInfos);
+    let mut positions: Vec<(String, String, Uint128)> = reward_infos
+        .iter()
+        .map(|record| match record {
+            ExportRecord::RewardInfo {
+                staker_addr,
+                staking_token,
+                reward_info,
+                ..
+            } => (
+                staker_addr.to_string(),
+                staking_token.to_string(),
+                reward_info.bond_amount,
+            ),
+            _ => panic!("unexpected record {:?}", record),
+        })
+        .collect();
+    positions.sort();
+    assert_eq!(
+        positions,
+        vec![
+            ("alice".to_string(), "staking_a".to_string(), 100u128.into()),
+            ("alice".to_string(), "staking_b".to_string(), 20u128.into()),
+            ("bob".to_string(), "staking_a".to_string(), 50u128.into()),
+        ]
+    );
+
+    let unbondings = export(&test_env, ExportSegment::UnbondingClaims);
+    assert_eq!(unbondings.len(), 1);
+    let (staker_addr, staking_token, entries) = match &unbondings[0] {
+        ExportRecord::UnbondingClaims {
+            staker_addr,
+            staking_token,
+            entries,
+            ..
+        } => (staker_addr, staking_token, entries),
+        record => panic!("unexpected record {:?}", record),
     };
+    assert_eq!(staker_addr, &Addr::unchecked("alice"));
+    assert_eq!(staking_token, &Addr::unchecked("staking_b"));
+    assert_eq!(
+        entries,
+        &vec![UnbondingEntry {
+            amount: Uint128::from(10u128),
+            release_time: test_env.env.block.time.seconds() + 100,
+        }]
+    );
+}
 
-    // unauthorized attempt
-    let res = app.execute(Addr::unchecked("addr"), staking_addr.clone(), &msg, &[]);
-    app.assert_fail(res);
+// the staking mock integrators test against answers with what the contract itself reports
+#[test]
+fn test_staking_querier_matches_contract() {
+    let test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("vault", "staking", 100)
+        .with_reward_deposit("staking", 300)
+        .build();
+    let pool_info = test_env.pool_info("staking");
+    let reward_info = test_env.reward_info("vault", Some("staking"));
 
-    // successfull attempt
+    let mut deps = mock_dependencies();
+    StakingQuerier::new("staking_contract")
+        .with_pool(pool_info.clone())
+        .with_reward_info("vault", reward_info.reward_infos[0].clone())
+        .install(&mut deps.querier);
+    let querier = deps.as_ref().querier;
 
-    let res = app
-        .execute(staking_addr.clone(), staking_addr.clone(), &msg, &[])
+    let res: PoolInfoResponse = querier
+        .query_wasm_smart(
+            "staking_contract",
+            &QueryMsg::PoolInfo {
+                staking_token: Addr::unchecked("staking"),
+            },
+        )
         .unwrap();
+    assert_eq!(res, pool_info);
+    let res: RewardInfoResponse = querier
+        .query_wasm_smart(
+            "staking_contract",
+            &QueryMsg::RewardInfo {
+                staker_addr: Addr::unchecked("vault"),
+                staking_token: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res, reward_info);
+}
+
+#[test]
+fn test_transfer_bond() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("alice", "staking", 100)
+        .with_reward_deposit("staking", 100)
+        .build();
+    let transfer_msg = |amount: u128, recipient: &str| ExecuteMsg::TransferBond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Uint128::from(amount),
+        recipient: Addr::unchecked(recipient),
+    };
+
+    let res = test_env
+        .execute("alice", transfer_msg(10, "alice"))
+        .unwrap_err();
+    assert_eq!(res, ContractError::TransferBondToSelf {});
+    let res = test_env
+        .execute("alice", transfer_msg(0, "bob"))
+        .unwrap_err();
+    assert_eq!(res, ContractError::ZeroTransferAmount {});
+    let res = test_env
+        .execute("alice", transfer_msg(150, "bob"))
+        .unwrap_err();
     assert_eq!(
-        // first attribute is _contract_addr
-        res.get_attributes(1),
+        res,
+        ContractError::InsufficientBond {
+            staker: "alice".to_string(),
+            asset: "staking".to_string(),
+            available: Uint128::from(100u128),
+            requested: Uint128::from(150u128),
+        }
+    );
+
+    let res = test_env.execute("alice", transfer_msg(40, "bob")).unwrap();
+    assert_eq!(
+        res.attributes,
         vec![
-            attr("action", "bond"),
-            attr("staker_addr", "addr"),
-            attr("staking_token", pair_info.liquidity_token.as_str()),
-            attr("amount", "1"),
+            attr("action", "transfer_bond"),
+            attr("staker_addr", "alice"),
+            attr("recipient", "bob"),
+            attr("staking_token", "staking"),
+            attr("amount", "40"),
         ]
     );
+    assert!(res.messages.is_empty());
+    test_env.assert_bond("alice", "staking", 60);
+    test_env.assert_bond("bob", "staking", 40);
+    // the reward earned before the transfer stays with the sender
+    test_env.assert_pending("alice", "staking", 100);
+    test_env.assert_pending("bob", "staking", 0);
+    let pool_info = test_env.pool_info("staking");
+    assert_eq!(pool_info.total_bond_amount, Uint128::from(100u128));
+    assert_eq!(pool_info.lifetime_bonded, Uint128::from(100u128));
+    assert_eq!(pool_info.lifetime_unbonded, Uint128::zero());
+    assert_eq!(pool_info.staker_count, Some(2));
 
-    let pool_info: PoolInfoResponse = app
-        .query(
-            staking_addr.clone(),
-            &QueryMsg::PoolInfo {
-                staking_token: pair_info.liquidity_token.clone(),
+    test_env
+        .execute(
+            "rewarder",
+            ExecuteMsg::DepositReward {
+                rewards: vec![RewardMsg {
+                    staking_token: Addr::unchecked("staking"),
+                    total_accumulation_amount: Uint128::from(100u128),
+                }],
             },
         )
         .unwrap();
+    test_env.assert_pending("alice", "staking", 160);
+    test_env.assert_pending("bob", "staking", 40);
 
+    // locked bonds stay with their position
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::UpdateLockTiers {
+                staking_token: Addr::unchecked("staking"),
+                lock_tiers: vec![LockTier {
+                    duration: 100,
+                    multiplier: Decimal::one(),
+                }],
+            },
+        )
+        .unwrap();
+    test_env
+        .execute(
+            "staking",
+            bond_receive_msg("bob", Uint128::from(50u128), Some(100)).unwrap(),
+        )
+        .unwrap();
+    let res = test_env
+        .execute("bob", transfer_msg(60, "alice"))
+        .unwrap_err();
     assert_eq!(
-        pool_info,
-        PoolInfoResponse {
-            staking_token: pair_info.liquidity_token.clone(),
-            total_bond_amount: Uint128::from(3u128),
-            reward_index: Decimal::zero(),
-            pending_reward: Uint128::zero(),
-            migration_deprecated_staking_token: None,
-            migration_index_snapshot: None,
+        res,
+        ContractError::BondLocked {
+            unlocked: Uint128::from(40u128),
+            requested: Uint128::from(60u128),
+        }
+    );
+}
+
+#[test]
+fn test_unbond_cooldown() {
+    let mut test_env = StakingTestEnv::new()
+        .with_pool("staking", default_rewards_per_sec())
+        .with_bond("addr", "staking", 100)
+        .build();
+    test_env
+        .execute(
+            "owner",
+            ExecuteMsg::UpdatePoolConfig {
+                staking_token: Addr::unchecked("staking"),
+                params: PoolConfigUpdate {
+                    unbond_cooldown: Some(100),
+                    ..PoolConfigUpdate::default()
+                },
+            },
+        )
+        .unwrap();
+    assert_eq!(test_env.pool_info("staking").unbond_cooldown, 100);
+    let unbond_msg = |amount: u128| ExecuteMsg::Unbond {
+        staking_token: Addr::unchecked("staking"),
+        amount: Some(Uint128::from(amount)),
+        withdraw_rewards: None,
+        recipient: None,
+        unbond_msg: None,
+    };
+
+    test_env.advance_seconds(40);
+    let res = test_env.execute("addr", unbond_msg(50)).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::UnbondCooldown {
+            remaining_seconds: 60
+        }
+    );
+    let res = test_env
+        .execute(
+            "addr",
+            ExecuteMsg::InstantUnbond {
+                staking_token: Addr::unchecked("staking"),
+                amount: Uint128::from(50u128),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::UnbondCooldown {
+            remaining_seconds: 60
+        }
+    );
+
+    // a new bond restarts the cooldown of the whole position
+    test_env
+        .execute(
+            "staking",
+            bond_receive_msg("addr", Uint128::from(10u128), None).unwrap(),
+        )
+        .unwrap();
+    test_env.advance_seconds(60);
+    let res = test_env.execute("addr", unbond_msg(50)).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::UnbondCooldown {
+            remaining_seconds: 40
         }
     );
+
+    test_env.advance_seconds(40);
+    test_env.execute("addr", unbond_msg(50)).unwrap();
+    test_env.assert_bond("addr", "staking", 60);
 }
@@ -0,0 +1,93 @@
+use crate::rewards::{DEFAULT_LIMIT, MAX_LIMIT};
+use crate::state::{read_range_prefixed, PREFIX_STAKER};
+use cosmwasm_std::testing::MockStorage;
+use cosmwasm_std::{Order, Uint128};
+use cosmwasm_storage::Bucket;
+use oraiswap::querier::{page_limit, parse_order};
+
+fn keys(page: Vec<(Vec<u8>, Uint128)>) -> Vec<Vec<u8>> {
+    page.into_iter().map(|(key, _)| key).collect()
+}
+
+#[test]
+fn test_read_range_prefixed() {
+    let mut storage = MockStorage::new();
+    let namespaces: &[&[u8]] = &[PREFIX_STAKER, b"pool"];
+    let stored: Vec<Vec<u8>> = vec![vec![1], vec![1, 0], vec![1, 5], vec![2], vec![255]];
+    for key in stored.iter() {
+        Bucket::multilevel(&mut storage, namespaces)
+            .save(key, &Uint128::from(key.len() as u128))
+            .unwrap();
+    }
+    // another pool under the same prefix is not part of the range
+    Bucket::multilevel(&mut storage, &[PREFIX_STAKER, b"pool2"])
+        .save(&[0], &Uint128::zero())
+        .unwrap();
+    let page = |start_after: Option<Vec<u8>>, limit: usize, order: Order| {
+        keys(read_range_prefixed(&storage, namespaces, start_after, limit, order).unwrap())
+    };
+
+    assert_eq!(page(None, 10, Order::Ascending), stored);
+    // the keys start_after is a prefix of come right after it
+    assert_eq!(
+        page(Some(vec![1]), 2, Order::Ascending),
+        vec![vec![1, 0], vec![1, 5]]
+    );
+    assert_eq!(
+        page(Some(vec![1, 5]), 10, Order::Ascending),
+        vec![vec![2], vec![255]]
+    );
+    assert!(page(Some(vec![255]), 10, Order::Ascending).is_empty());
+
+    let mut reversed = stored.clone();
+    reversed.reverse();
+    assert_eq!(page(None, 10, Order::Descending), reversed);
+    assert_eq!(page(Some(vec![1, 0]), 10, Order::Descending), vec![vec![1]]);
+    assert!(page(Some(vec![1]), 10, Order::Descending).is_empty());
+    // start_after does not have to be stored
+    assert_eq!(page(Some(vec![3]), 1, Order::Descending), vec![vec![2]]);
+
+    assert!(page(None, 0, Order::Ascending).is_empty());
+    assert!(keys(
+        read_range_prefixed(
+            &storage,
+            &[PREFIX_STAKER, b"pool3"],
+            None,
+            10,
+            Order::Ascending
+        )
+        .unwrap()
+    )
+    .is_empty());
+}
+
+#[test]
+fn test_page_limit_and_order() {
+    let mut storage = MockStorage::new();
+    for i in 0..40u8 {
+        Bucket::multilevel(&mut storage, &[PREFIX_STAKER, b"pool"])
+            .save(&[i], &Uint128::zero())
+            .unwrap();
+    }
+
+    assert_eq!(page_limit(None, DEFAULT_LIMIT, MAX_LIMIT), 10);
+    assert_eq!(page_limit(Some(20), DEFAULT_LIMIT, MAX_LIMIT), 20);
+    let limit = page_limit(Some(100), DEFAULT_LIMIT, MAX_LIMIT);
+    assert_eq!(limit, 30);
+    let page = read_range_prefixed::<Uint128>(
+        &storage,
+        &[PREFIX_STAKER, b"pool"],
+        None,
+        limit,
+        Order::Ascending,
+    )
+    .unwrap();
+    assert_eq!(page.len(), 30);
+
+    assert_eq!(parse_order(None).unwrap(), Order::Ascending);
+    assert_eq!(
+        parse_order(Some(Order::Descending.into())).unwrap(),
+        Order::Descending
+    );
+    assert!(parse_order(Some(3)).is_err());
+}
@@ -1,7 +1,13 @@
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+};
 
-use cw20::Cw20ExecuteMsg;
+use cw20::{
+    AllAllowancesResponse, AllSpenderAllowancesResponse, AllowanceInfo, Cw20ExecuteMsg,
+    SpenderAllowanceInfo,
+};
+use cw20_base::state::{ALLOWANCES, ALLOWANCES_SPENDER};
 use cw20_base::ContractError;
 use cw20_base::{
     contract::{
@@ -10,6 +16,10 @@ use cw20_base::{
     },
     msg::{InstantiateMsg, MigrateMsg, QueryMsg},
 };
+use cw_storage_plus::Bound;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -33,7 +43,95 @@ pub fn execute(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    cw20_query(deps, env, msg)
+    match msg {
+        // expired allowances are left out of the listings
+        QueryMsg::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_owner_allowances(
+            deps,
+            env,
+            owner,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::AllSpenderAllowances {
+            spender,
+            start_after,
+            limit,
+        } => to_binary(&query_spender_allowances(
+            deps,
+            env,
+            spender,
+            start_after,
+            limit,
+        )?),
+        _ => cw20_query(deps, env, msg),
+    }
+}
+
+pub fn query_owner_allowances(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllAllowancesResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into_bytes()));
+
+    let allowances = ALLOWANCES
+        .prefix(&owner_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, allowance)) => !allowance.expires.is_expired(&env.block),
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| {
+            item.map(|(spender, allowance)| AllowanceInfo {
+                spender: spender.into(),
+                allowance: allowance.allowance,
+                expires: allowance.expires,
+            })
+        })
+        .collect::<StdResult<Vec<AllowanceInfo>>>()?;
+
+    Ok(AllAllowancesResponse { allowances })
+}
+
+pub fn query_spender_allowances(
+    deps: Deps,
+    env: Env,
+    spender: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllSpenderAllowancesResponse> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into_bytes()));
+
+    // the spender index is kept in sync by cw20-base on every allowance write
+    let allowances = ALLOWANCES_SPENDER
+        .prefix(&spender_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, allowance)) => !allowance.expires.is_expired(&env.block),
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| {
+            item.map(|(owner, allowance)| SpenderAllowanceInfo {
+                owner: owner.into(),
+                allowance: allowance.allowance,
+                expires: allowance.expires,
+            })
+        })
+        .collect::<StdResult<Vec<SpenderAllowanceInfo>>>()?;
+
+    Ok(AllSpenderAllowancesResponse { allowances })
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -0,0 +1,204 @@
+use crate::contract::{execute, instantiate, query};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{from_binary, DepsMut, Uint128};
+use cw20::{
+    AllAllowancesResponse, AllSpenderAllowancesResponse, Cw20Coin, Cw20ExecuteMsg, Expiration,
+};
+use cw20_base::msg::{InstantiateMsg, QueryMsg};
+
+fn instantiate_with_balance(deps: DepsMut, owner: &str, amount: u128) {
+    instantiate(
+        deps,
+        mock_env(),
+        mock_info("creator", &[]),
+        InstantiateMsg {
+            name: "Oraiswap token".to_string(),
+            symbol: "ORAIX".to_string(),
+            decimals: 6,
+            initial_balances: vec![Cw20Coin {
+                address: owner.to_string(),
+                amount: amount.into(),
+            }],
+            mint: None,
+            marketing: None,
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_all_allowances_pagination() {
+    let mut deps = mock_dependencies();
+    instantiate_with_balance(deps.as_mut(), "owner", 1000000);
+
+    // 25 spenders approved by the owner, and 25 owners approving the same spender
+    for i in 0..25 {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            Cw20ExecuteMsg::IncreaseAllowance {
+                spender: format!("spender{:02}", i),
+                amount: Uint128::from(100u128 + i),
+                expires: None,
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&format!("owner{:02}", i), &[]),
+            Cw20ExecuteMsg::IncreaseAllowance {
+                spender: "spender".to_string(),
+                amount: Uint128::from(100u128 + i),
+                expires: None,
+            },
+        )
+        .unwrap();
+    }
+
+    let mut spenders = vec![];
+    let mut start_after = None;
+    loop {
+        let res: AllAllowancesResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::AllAllowances {
+                    owner: "owner".to_string(),
+                    start_after: start_after.clone(),
+                    limit: Some(10),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        if res.allowances.is_empty() {
+            break;
+        }
+        assert!(res.allowances.len() <= 10);
+        start_after = res.allowances.last().map(|info| info.spender.clone());
+        spenders.extend(res.allowances.into_iter().map(|info| info.spender));
+    }
+    assert_eq!(
+        spenders,
+        (0..25)
+            .map(|i| format!("spender{:02}", i))
+            .collect::<Vec<String>>()
+    );
+
+    let mut owners = vec![];
+    let mut start_after = None;
+    loop {
+        let res: AllSpenderAllowancesResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::AllSpenderAllowances {
+                    spender: "spender".to_string(),
+                    start_after: start_after.clone(),
+                    limit: Some(10),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        if res.allowances.is_empty() {
+            break;
+        }
+        start_after = res.allowances.last().map(|info| info.owner.clone());
+        owners.extend(res.allowances.into_iter().map(|info| info.owner));
+    }
+    assert_eq!(
+        owners,
+        (0..25)
+            .map(|i| format!("owner{:02}", i))
+            .collect::<Vec<String>>()
+    );
+}
+
+#[test]
+fn test_all_allowances_skip_expired() {
+    let mut deps = mock_dependencies();
+    instantiate_with_balance(deps.as_mut(), "owner", 1000000);
+
+    let env = mock_env();
+    for (spender, expires) in [
+        ("spender1", None),
+        (
+            "spender2",
+            Some(Expiration::AtHeight(env.block.height + 10)),
+        ),
+        (
+            "spender3",
+            Some(Expiration::AtHeight(env.block.height + 100)),
+        ),
+    ] {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            Cw20ExecuteMsg::IncreaseAllowance {
+                spender: spender.to_string(),
+                amount: Uint128::from(100u128),
+                expires,
+            },
+        )
+        .unwrap();
+    }
+
+    let mut later_env = mock_env();
+    later_env.block.height += 50;
+
+    let res: AllAllowancesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            later_env.clone(),
+            QueryMsg::AllAllowances {
+                owner: "owner".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.allowances
+            .into_iter()
+            .map(|info| info.spender)
+            .collect::<Vec<String>>(),
+        vec!["spender1".to_string(), "spender3".to_string()]
+    );
+
+    let res: AllSpenderAllowancesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            later_env,
+            QueryMsg::AllSpenderAllowances {
+                spender: "spender2".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.allowances.is_empty());
+
+    // still listed before it expires
+    let res: AllSpenderAllowancesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::AllSpenderAllowances {
+                spender: "spender2".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.allowances.len(), 1);
+}
@@ -1,5 +1,6 @@
 use cosmwasm_schema::cw_serde;
 use std::fmt;
+use std::str::FromStr;
 
 use crate::querier::query_token_balance;
 use crate::{error::ContractError, oracle::OracleContract};
@@ -18,9 +19,53 @@ pub struct Asset {
     pub amount: Uint128,
 }
 
+/// suffix used to tell cw20 tokens apart from native denoms in the text format
+const CW20_SUFFIX: &str = " (cw20)";
+
+/// native assets are rendered as "1000orai", cw20 assets as "500 orai1abc... (cw20)"
 impl fmt::Display for Asset {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}{}", self.amount, self.info)
+        match &self.info {
+            AssetInfo::NativeToken { denom } => write!(f, "{}{}", self.amount, denom),
+            AssetInfo::Token { contract_addr } => {
+                write!(f, "{} {}{}", self.amount, contract_addr, CW20_SUFFIX)
+            }
+        }
+    }
+}
+
+impl FromStr for Asset {
+    type Err = StdError;
+
+    fn from_str(s: &str) -> StdResult<Self> {
+        let invalid = || StdError::generic_err(format!("Invalid asset: {}", s));
+
+        if let Some(token) = s.strip_suffix(CW20_SUFFIX) {
+            let (amount, contract_addr) = token.split_once(' ').ok_or_else(invalid)?;
+            if contract_addr.is_empty() {
+                return Err(invalid());
+            }
+            return Ok(Asset {
+                info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked(contract_addr),
+                },
+                amount: Uint128::from_str(amount).map_err(|_| invalid())?,
+            });
+        }
+
+        // native denoms always start with a letter, so the amount is the leading digits
+        let split = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        let (amount, denom) = s.split_at(split);
+        if amount.is_empty() || !denom.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Err(invalid());
+        }
+
+        Ok(Asset {
+            info: AssetInfo::NativeToken {
+                denom: denom.to_string(),
+            },
+            amount: Uint128::from_str(amount).map_err(|_| invalid())?,
+        })
     }
 }
 
@@ -100,14 +145,20 @@ impl Asset {
                     if self.amount == coin.amount {
                         Ok(())
                     } else {
-                        Err(StdError::generic_err("Native token balance mismatch between the argument and the transferred"))
+                        Err(StdError::generic_err(format!(
+                            "Native token balance mismatch between the argument ({}) and the transferred ({}{})",
+                            self, coin.amount, coin.denom
+                        )))
                     }
                 }
                 None => {
                     if self.amount.is_zero() {
                         Ok(())
                     } else {
-                        Err(StdError::generic_err("Native token balance mismatch between the argument and the transferred"))
+                        Err(StdError::generic_err(format!(
+                            "Native token balance mismatch between the argument ({}) and the transferred (0{})",
+                            self, denom
+                        )))
                     }
                 }
             }
@@ -159,6 +210,8 @@ pub enum AssetInfo {
     NativeToken { denom: String },
 }
 
+/// AssetInfo is rendered as the bare denom or contract address because it is used
+/// as an identifier in attributes and lookups
 impl fmt::Display for AssetInfo {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -168,6 +221,26 @@ impl fmt::Display for AssetInfo {
     }
 }
 
+/// accepts "orai1abc... (cw20)" for cw20 tokens, anything else is treated as a native denom
+impl FromStr for AssetInfo {
+    type Err = StdError;
+
+    fn from_str(s: &str) -> StdResult<Self> {
+        if s.is_empty() {
+            return Err(StdError::generic_err("Invalid asset info: empty string"));
+        }
+        match s.strip_suffix(CW20_SUFFIX) {
+            Some("") => Err(StdError::generic_err(format!("Invalid asset info: {}", s))),
+            Some(contract_addr) => Ok(AssetInfo::Token {
+                contract_addr: Addr::unchecked(contract_addr),
+            }),
+            None => Ok(AssetInfo::NativeToken {
+                denom: s.to_string(),
+            }),
+        }
+    }
+}
+
 impl AssetInfo {
     pub fn to_vec(&self, api: &dyn Api) -> StdResult<Vec<u8>> {
         match self {
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Decimal, OverflowError, StdError, Uint128};
+use cosmwasm_std::{Decimal, Decimal256, OverflowError, StdError, Uint128};
 use cw_utils::PaymentError;
 use thiserror::Error;
 
@@ -22,8 +22,11 @@ pub enum ContractError {
     #[error("Max spread assertion")]
     MaxSpreadAssertion {},
 
-    #[error("Max slippage assertion")]
-    MaxSlippageAssertion {},
+    #[error("Max slippage assertion; deposit ratio: {deposit_ratio}, pool ratio: {pool_ratio}")]
+    MaxSlippageAssertion {
+        deposit_ratio: Decimal256,
+        pool_ratio: Decimal256,
+    },
 
     #[error("Slippage_tolerance cannot bigger than 1")]
     InvalidExceedOneSlippage {},
@@ -43,6 +46,18 @@ pub enum ContractError {
     #[error("Pair was already registered")]
     PairRegistered {},
 
+    #[error("Asset {asset} is not whitelisted for pair creation")]
+    AssetNotWhitelisted { asset: String },
+
+    #[error("Invalid guardian threshold {threshold} for {guardians} guardians")]
+    InvalidGuardianThreshold { threshold: u32, guardians: u32 },
+
+    #[error("Guardian action {action_id} has expired")]
+    GuardianActionExpired { action_id: u64 },
+
+    #[error("Guardians are set, propose it as a guardian action")]
+    GuardianActionRequired {},
+
     #[error(
         "Assertion failed; minimum receive amount: {minium_receive}, swap amount: {swap_amount}"
     )]
@@ -54,9 +69,342 @@ pub enum ContractError {
     #[error("must provide operations")]
     NoSwapOperation {},
 
+    #[error("No route registered from {offer} to {ask}, provide the swap operations explicitly")]
+    RouteNotFound { offer: String, ask: String },
+
+    #[error("Route does not swap {offer} to {ask}")]
+    InvalidRoute { offer: String, ask: String },
+
     #[error("invalid cw20 hook message")]
     InvalidCw20HookMessage {},
 
+    #[error("missing hook message: expected Bond or DepositReward")]
+    MissingCw20HookMessage {},
+
+    #[error("unknown hook message {variant}")]
+    UnknownCw20HookMessage { variant: String },
+
+    #[error("invalid {variant} hook message: {reason}")]
+    MalformedCw20HookMessage { variant: String, reason: String },
+
+    #[error("cw20 hook message must carry a non-zero amount")]
+    ZeroCw20HookAmount {},
+
+    #[error("Asset was already registered")]
+    AssetRegistered {},
+
+    #[error("Pool for {asset} is not registered")]
+    PoolNotFound { asset: String },
+
+    #[error("Pool is not in epoch mode")]
+    PoolNotInEpochMode {},
+
+    #[error("Invalid staking token")]
+    InvalidStakingToken {},
+
+    #[error("The staking token for this asset has been migrated to {new_token}")]
+    StakingTokenMigrated { new_token: String },
+
+    #[error("The LP token for this asset has been deprecated, withdraw all your deprecated tokens to migrate your position")]
+    StakingTokenDeprecated {},
+
+    #[error("This asset LP token has already been migrated")]
+    StakingTokenAlreadyMigrated {},
+
+    #[error("The staking token of this pool has not been deprecated")]
+    StakingTokenNotDeprecated {},
+
+    #[error("The new staking token is already registered")]
+    StakingTokenRegistered {},
+
+    #[error("This pool is already transitioning to a new staking token")]
+    StakingTokenTransitioning {},
+
+    #[error("This pool is transitioning to another staking token, finalize it with that token")]
+    TransitionTokenMismatch {},
+
+    #[error("Deprecate the pool through its current staking token")]
+    NotCurrentStakingToken {},
+
+    #[error("New staking token {new_staking_token} is the current or an already deprecated staking token")]
+    InvalidNewStakingToken { new_staking_token: String },
+
+    #[error("Cannot unbond {requested}: only {available} bonded by {staker} for {asset}")]
+    InsufficientBond {
+        staker: String,
+        asset: String,
+        available: Uint128,
+        requested: Uint128,
+    },
+
+    #[error("Too many rewards, at most {max} pools can be rewarded in one message")]
+    TooManyRewards { max: usize },
+
+    #[error("Ownership proposal must expire after the current block time")]
+    InvalidOwnershipExpiry {},
+
+    #[error("No ownership proposal for this address")]
+    NoOwnershipProposal {},
+
+    #[error("Ownership proposal expired at {expires_at}")]
+    OwnershipProposalExpired { expires_at: u64 },
+
+    #[error(
+        "Withdraw from at most {max} pools at once, give the staking token to withdraw the others"
+    )]
+    TooManyWithdrawPools { max: usize },
+
+    #[error(
+        "Insufficient reward reserves to pay {asset}, the contract holds {balance}, contact governance"
+    )]
+    InsufficientRewardReserves { asset: String, balance: Uint128 },
+
+    #[error("A pool streams at most {max} deposits at once")]
+    TooManyRewardStreams { max: usize },
+
+    #[error("Deposit expected epoch {expected} of {staking_token}, its next deposit is {next}")]
+    UnexpectedDepositEpoch {
+        staking_token: String,
+        expected: u64,
+        next: u64,
+    },
+
+    #[error("Give either a recipient or an ibc recipient, not both")]
+    ConflictingRecipients {},
+
+    #[error("An operator can only withdraw the rewards to the staker")]
+    OperatorRecipient {},
+
+    #[error("Unbonds of a pool with an unbonding period are claimed by the staker, no recipient or unbond message can be given")]
+    UnbondRecipientWithUnbondingPeriod {},
+
+    #[error("Hook {contract_addr} is already registered")]
+    HookRegistered { contract_addr: String },
+
+    #[error("Hook {contract_addr} is not registered")]
+    HookNotFound { contract_addr: String },
+
+    #[error("Too many hooks, at most {max} can be registered")]
+    TooManyHooks { max: usize },
+
+    #[error("{staker} is not allowed to bond into the {asset} pool")]
+    StakerNotAllowed { staker: String, asset: String },
+
+    #[error("Staker list is full, it holds at most {max} stakers")]
+    StakerListFull { max: u32 },
+
+    #[error("{asset} backs the positions and can not be rescued")]
+    ProtectedAsset { asset: String },
+
+    #[error("Cannot rescue {requested}: only {free} of the balance is free")]
+    RescueExceedsFree { free: Uint128, requested: Uint128 },
+
+    #[error("The balance of {staking_token} holds no more than the positions account for")]
+    NothingToSweep { staking_token: String },
+
+    #[error("Withdraw amounts can only be given together with a staking token")]
+    PartialWithdrawWithoutStakingToken {},
+
+    #[error("Cannot withdraw {requested} of {asset}, claimable: {claimable}")]
+    WithdrawExceedsClaimable {
+        asset: String,
+        claimable: Uint128,
+        requested: Uint128,
+    },
+
+    #[error("At most {max} unbondings can wait at once, claim the released ones first")]
+    TooManyUnbondingEntries { max: usize },
+
+    #[error("Nothing to claim yet")]
+    NothingToClaim {},
+
+    #[error("Nothing bonded to unbond")]
+    NothingToUnbond {},
+
+    #[error("Unbond amount must not be zero")]
+    ZeroUnbondAmount {},
+
+    #[error("Position can be unbonded in {remaining_seconds} seconds")]
+    UnbondCooldown { remaining_seconds: u64 },
+
+    #[error("Transfer amount must not be zero")]
+    ZeroTransferAmount {},
+
+    #[error("Bond can not be transferred to its own staker")]
+    TransferBondToSelf {},
+
+    #[error("Instant unbond penalty cannot be more than {max}")]
+    InstantUnbondPenaltyTooHigh { max: Decimal },
+
+    #[error("No penalty collector is set")]
+    PenaltyCollectorNotSet {},
+
+    #[error("Withdraw fee rate cannot be more than {max}")]
+    WithdrawFeeTooHigh { max: Decimal },
+
+    #[error("No fee collector is set")]
+    FeeCollectorNotSet {},
+
+    #[error("No community pool is set")]
+    CommunityPoolNotSet {},
+
+    #[error("A staker can not refer their own bond")]
+    SelfReferral {},
+
+    #[error(
+        "Pool is registered with staking token {staking_token}, the pair mints {liquidity_token}"
+    )]
+    StakingTokenMismatch {
+        staking_token: String,
+        liquidity_token: String,
+    },
+
+    #[error("No pool uses staking token {staking_token}")]
+    NoPoolForStakingToken { staking_token: String },
+
+    #[error("The factory has no pair of {asset} against {quote}")]
+    FactoryPairNotFound { asset: String, quote: String },
+
+    #[error("Staking token {staking_token} does not answer the cw20 TokenInfo query with a symbol and decimals")]
+    InvalidStakingTokenContract { staking_token: String },
+
+    #[error("Expected at least {expected} LP tokens from the pair, received {received}")]
+    LpAmountBelowMinimum {
+        expected: Uint128,
+        received: Uint128,
+    },
+
+    #[error("Invalid base denom {denom}")]
+    InvalidBaseDenom { denom: String },
+
+    #[error("Pool is bonded with {expected}, got {denom}")]
+    InvalidStakingDenom { expected: String, denom: String },
+
+    #[error("The bonded amount must be at least {min_bond_amount}")]
+    BondBelowMinimum { min_bond_amount: Uint128 },
+
+    #[error("Bond amount must not be zero")]
+    ZeroBondAmount {},
+
+    #[error("Pool cap reached, only {remaining} can still be bonded")]
+    BondCapExceeded { remaining: Uint128 },
+
+    #[error("Staker cap of {max_bond_per_staker} reached with {bond_amount} bonded, only {remaining} can still be bonded")]
+    StakerBondCapExceeded {
+        bond_amount: Uint128,
+        max_bond_per_staker: Uint128,
+        remaining: Uint128,
+    },
+
+    #[error("Reward of {reward} is below the compound minimum of {min_compound_reward}")]
+    CompoundRewardTooLow {
+        reward: Uint128,
+        min_compound_reward: Uint128,
+    },
+
+    #[error("No pair to swap {offer} for {ask}")]
+    SwapPairNotFound { offer: String, ask: String },
+
+    #[error("Invalid lock tier of {duration} seconds")]
+    InvalidLockTier { duration: u64 },
+
+    #[error("Pool has no lock tier of {lock_duration} seconds")]
+    InvalidLockDuration { lock_duration: u64 },
+
+    #[error("At most {max} locked bonds can be held per pool")]
+    TooManyBondLocks { max: usize },
+
+    #[error("Cannot unbond {requested}, only {unlocked} of the bond is unlocked")]
+    BondLocked {
+        unlocked: Uint128,
+        requested: Uint128,
+    },
+
+    #[error("Pool for {asset} is frozen")]
+    PoolFrozen { asset: String },
+
+    #[error("Pool still has {total_bond_amount} bonded")]
+    PoolHasBonds { total_bond_amount: Uint128 },
+
+    #[error("Pool still has {pending_reward} undistributed reward, sweep it first")]
+    PoolHasPendingReward { pending_reward: Uint128 },
+
+    #[error("Pool for {asset} has no reward deposited while nothing was bonded")]
+    NoOrphanedReward { asset: String },
+
+    #[error("Pool for {asset} can not take reallocated rewards")]
+    InvalidReallocationTarget { asset: String },
+
+    #[error("At least one pool must take the reallocated rewards")]
+    EmptyReallocationTargets {},
+
+    #[error("Pool still has stakers with unwithdrawn rewards")]
+    PoolHasStakers {},
+
+    #[error("Sent {sent} of the reward denom, the rewards sum to {expected}")]
+    NativeRewardMismatch { sent: Uint128, expected: Uint128 },
+
+    #[error("Reward deposit must not be zero")]
+    ZeroRewardDeposit {},
+
+    #[error("Deposit raises the reward index of {staking_token} by {increase}, over its limit of {limit}")]
+    RewardIndexIncreaseTooLarge {
+        staking_token: String,
+        increase: Decimal256,
+        limit: Decimal,
+    },
+
+    #[error("Pool {staking_token} is not rewarded in {denom} only")]
+    InvalidNativeRewardPool {
+        staking_token: String,
+        denom: String,
+    },
+
+    #[error("Invalid distribution schedule from {start_time} to {end_time}")]
+    InvalidDistributionSchedule { start_time: u64, end_time: u64 },
+
+    #[error("Reward window must end after it starts, got {start_time} to {end_time}")]
+    InvalidRewardWindow { start_time: u64, end_time: u64 },
+
+    #[error("Proxy reward {reward_token} is already registered for this pool")]
+    ProxyRewardRegistered { reward_token: String },
+
+    #[error("At most {max} proxy rewards can be registered per pool")]
+    TooManyProxyRewards { max: usize },
+
+    #[error("At most {max} pools can be deprecated in one migration")]
+    TooManyDeprecations { max: usize },
+
+    #[error("At most {max} stakers can be queried at once")]
+    TooManyStakers { max: usize },
+
+    #[error("Between 1 and {max} assets can be registered at once")]
+    InvalidRegisterAssetsLength { max: usize },
+
+    #[error("Asset {index} of the batch is invalid: {reason}")]
+    InvalidRegisterAssetsEntry { index: usize, reason: String },
+
+    #[error("Reward weights must not be empty")]
+    EmptyRewardWeights {},
+
+    #[error("Duplicated reward weight for {staking_token}")]
+    DuplicatedRewardWeight { staking_token: String },
+
+    #[error("Reward weights must sum to 1, got {total_weight}")]
+    InvalidRewardWeights { total_weight: Decimal },
+
+    #[error("Invalid ibc channel id: {channel_id}")]
+    InvalidIbcChannel { channel_id: String },
+
+    #[error("Remote address must not be empty")]
+    EmptyRemoteAddress {},
+
+    #[error("Ibc timeout must be between {min} and {max} seconds")]
+    InvalidIbcTimeout { min: u64, max: u64 },
+
+    #[error("Cw20 reward {contract_addr} can not be withdrawn through ibc, withdraw it without ibc_recipient")]
+    IbcCw20Reward { contract_addr: String },
+
     #[error("must provide native token")]
     MustProvideNativeToken {}, // only allowing buy token and sell token with native token
 
@@ -113,4 +461,31 @@ pub enum ContractError {
 
     #[error("Contract paused")]
     Paused {},
+
+    #[error("The contract must be paused first")]
+    NotPaused {},
+
+    #[error("Reward index can not move back from {current} to {requested}")]
+    RepairIndexRegression {
+        current: Decimal,
+        requested: Decimal,
+    },
+
+    #[error("Total bond {requested} is below the {positions} the positions hold")]
+    RepairBelowPositions {
+        positions: Uint128,
+        requested: Uint128,
+    },
+
+    #[error("The patch changes no field of the pool")]
+    NothingToRepair {},
+
+    #[error("A repair must give its reason")]
+    EmptyRepairReason {},
+
+    #[error("Stored contract version {stored} can not be migrated to {expected}")]
+    ContractVersionMismatch { stored: String, expected: String },
+
+    #[error("No store migration is in progress")]
+    NotMigrating {},
 }
@@ -19,6 +19,8 @@ pub enum ExecuteMsg {
         owner: Option<String>,
         token_code_id: Option<u64>,
         pair_code_id: Option<u64>,
+        // when enabled, CreatePair only accepts whitelisted assets
+        whitelist_enabled: Option<bool>,
     },
     /// CreatePair instantiates pair contract
     CreatePair {
@@ -34,6 +36,11 @@ pub enum ExecuteMsg {
         new_code_id: u64,
         msg: Binary,
     },
+    /// UpdateAssetWhitelist adds or removes assets allowed in CreatePair
+    UpdateAssetWhitelist {
+        add: Vec<AssetInfo>,
+        remove: Vec<AssetInfo>,
+    },
 }
 
 #[cw_serde]
@@ -48,6 +55,11 @@ pub enum QueryMsg {
         start_after: Option<[AssetInfo; 2]>,
         limit: Option<u32>,
     },
+    #[returns(AssetWhitelistResponse)]
+    AssetWhitelist {
+        start_after: Option<AssetInfo>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -57,6 +69,7 @@ pub struct ConfigResponse {
     pub oracle_addr: Addr,
     pub pair_code_id: u64,
     pub token_code_id: u64,
+    pub whitelist_enabled: bool,
 }
 
 /// We currently take no arguments for migrations
@@ -68,3 +81,8 @@ pub struct MigrateMsg {}
 pub struct PairsResponse {
     pub pairs: Vec<PairInfo>,
 }
+
+#[cw_serde]
+pub struct AssetWhitelistResponse {
+    pub asset_infos: Vec<AssetInfo>,
+}
@@ -19,3 +19,6 @@ pub use cw_multi_test;
 // for other to use, but not compile to wasm
 #[cfg(not(target_arch = "wasm32"))]
 pub mod testing;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod test_vectors;
@@ -36,6 +36,44 @@ pub enum ExecuteMsg {
     UpdateTaxRate {
         rate: Decimal,
     },
+    // set the guardians that can execute actions together, no guardians disables it. Once they
+    // are set, UpdateAdmin, DeleteExchangeRate and UpdateGuardians only pass as guardian actions
+    UpdateGuardians {
+        guardians: Vec<Addr>,
+        threshold: u32,
+        // seconds an action can gather approvals
+        approval_window: u64,
+    },
+    // the proposal counts as the first approval of the proposer
+    ProposeAction {
+        action: GuardianAction,
+    },
+    // the action is executed once threshold guardians approved it
+    ApproveAction {
+        action_id: u64,
+    },
+    // only the proposer can cancel the action
+    CancelAction {
+        action_id: u64,
+    },
+}
+
+/// GuardianAction is an admin action that guardians can execute without the admin key
+#[cw_serde]
+pub enum GuardianAction {
+    // rotate the admin key that feeds the exchange rates
+    UpdateAdmin {
+        admin: Addr,
+    },
+    DeleteExchangeRate {
+        denom: String,
+    },
+    // replace the guardian set, as UpdateGuardians does
+    UpdateGuardians {
+        guardians: Vec<Addr>,
+        threshold: u32,
+        approval_window: u64,
+    },
 }
 
 /// QueryMsg is defines available query datas
@@ -79,6 +117,10 @@ pub enum OracleContractQuery {
     ContractInfo {},
     #[returns(cosmwasm_std::Coin)]
     RewardPool { denom: String },
+    #[returns(GuardiansResponse)]
+    Guardians {},
+    #[returns(GuardianActionResponse)]
+    GuardianAction { action_id: u64 },
 }
 
 /// TaxRateResponse is data format returned from TreasuryRequest::TaxRate query
@@ -139,6 +181,24 @@ pub struct ContractInfoResponse {
     pub max_rate: Decimal,
 }
 
+/// GuardiansResponse is data format returned from WasmRequest::Guardians query
+#[cw_serde]
+pub struct GuardiansResponse {
+    pub guardians: Vec<Addr>,
+    pub threshold: u32,
+    pub approval_window: u64,
+}
+
+/// GuardianActionResponse is data format returned from WasmRequest::GuardianAction query
+#[cw_serde]
+pub struct GuardianActionResponse {
+    pub action_id: u64,
+    pub proposer: Addr,
+    pub action: GuardianAction,
+    pub approvals: Vec<Addr>,
+    pub expires_at: u64,
+}
+
 /// We currently take no arguments for migrations
 #[cw_serde]
 pub struct MigrateMsg {}
@@ -4,8 +4,9 @@ use crate::pair::{
     PairResponse, QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse,
 };
 
-use cosmwasm_std::{Addr, QuerierWrapper, StdResult, Uint128};
+use cosmwasm_std::{from_slice, Addr, Order, QuerierWrapper, StdError, StdResult, Uint128};
 use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+use cw20_base::state::{TokenInfo, BALANCES, TOKEN_INFO};
 
 pub fn query_token_balance(
     querier: &QuerierWrapper,
@@ -36,6 +37,26 @@ pub fn query_supply(querier: &QuerierWrapper, contract_addr: Addr) -> StdResult<
     query_token_info(querier, contract_addr).map(|token_info| token_info.total_supply)
 }
 
+// read the balance straight from the cw20-base storage, cheaper than a smart query
+pub fn query_token_balance_raw(
+    querier: &QuerierWrapper,
+    contract_addr: Addr,
+    account_addr: Addr,
+) -> StdResult<Uint128> {
+    let res = querier.query_wasm_raw(contract_addr, BALANCES.key(&account_addr).to_vec())?;
+
+    // an account that never held the token has no entry, same as the zero smart query result
+    res.map_or(Ok(Uint128::zero()), |balance| from_slice(&balance))
+}
+
+pub fn query_supply_raw(querier: &QuerierWrapper, contract_addr: Addr) -> StdResult<Uint128> {
+    let res = querier.query_wasm_raw(contract_addr.clone(), TOKEN_INFO.as_slice())?;
+    let token_info: TokenInfo = from_slice(
+        &res.ok_or_else(|| StdError::not_found(format!("token info of {}", contract_addr)))?,
+    )?;
+    Ok(token_info.total_supply)
+}
+
 pub fn query_pair_info(
     querier: &QuerierWrapper,
     factory_addr: Addr,
@@ -105,3 +126,32 @@ pub fn calc_range_start(start_after: Option<Vec<u8>>) -> Option<Vec<u8>> {
         input
     })
 }
+
+// the order of a list query, sent as the i32 of cosmwasm_std::Order: 1 ascending (the default),
+// 2 descending
+pub fn parse_order(order: Option<i32>) -> StdResult<Order> {
+    Order::try_from(order.unwrap_or(1))
+}
+
+// the page size of a list query, default when not given and at most max
+pub fn page_limit(limit: Option<u32>, default: u32, max: u32) -> usize {
+    limit.unwrap_or(default).min(max) as usize
+}
+
+// range bounds of the keys after start_after in the order, start_after itself excluded. Unlike
+// calc_range_start this keeps the longer keys start_after is a prefix of
+pub fn calc_range(
+    start_after: Option<Vec<u8>>,
+    order: Order,
+) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
+    match order {
+        Order::Ascending => (
+            start_after.map(|mut key| {
+                key.push(0);
+                key
+            }),
+            None,
+        ),
+        Order::Descending => (None, start_after),
+    }
+}
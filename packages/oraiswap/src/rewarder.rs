@@ -1,6 +1,6 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, StdResult, Uint128};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -19,6 +19,12 @@ pub enum ExecuteMsg {
         distribution_interval: Option<u64>,
     },
 
+    // replace the emission schedule of a pool, accrued rewards are distributed first
+    UpdateSchedule {
+        staking_token: Addr,
+        schedule: Vec<(u64, u64, Uint128)>,
+    },
+
     // distribute for a list of pools
     Distribute {
         staking_tokens: Vec<Addr>,
@@ -37,6 +43,8 @@ pub enum QueryMsg {
     DistributionInfo { staking_token: Addr },
     #[returns(RewardAmountPerSecondResponse)]
     RewardAmountPerSec { staking_token: Addr },
+    #[returns(ScheduleResponse)]
+    Schedule { staking_token: Addr },
 }
 
 // We define a custom struct for each query response
@@ -58,3 +66,50 @@ pub struct DistributionInfoResponse {
 pub struct RewardAmountPerSecondResponse {
     pub reward_amount: Uint128,
 }
+
+// We define a custom struct for each query response
+#[cw_serde]
+pub struct ScheduleResponse {
+    // list of (start_time, end_time, rate per second)
+    pub schedule: Vec<(u64, u64, Uint128)>,
+}
+
+/// emission rate halves every 90 days
+pub const HALVING_PERIOD: u64 = 90 * 86400;
+
+/// build a schedule of `periods` segments starting at `start`, each segment lasts HALVING_PERIOD
+/// and emits half the rate of the previous one
+pub fn genesis_halving_schedule(
+    start: u64,
+    initial_rate: Uint128,
+    periods: u32,
+) -> Vec<(u64, u64, Uint128)> {
+    let mut rate = initial_rate;
+    let mut schedule = vec![];
+    for i in 0..periods as u64 {
+        let start_time = start + i * HALVING_PERIOD;
+        schedule.push((start_time, start_time + HALVING_PERIOD, rate));
+        rate = Uint128::from(rate.u128() / 2);
+    }
+    schedule
+}
+
+/// integrate the emission rate of every segment overlapping the [from, to) window, an emission
+/// too large for Uint128 returns an error
+pub fn compute_schedule_emission(
+    schedule: &[(u64, u64, Uint128)],
+    from: u64,
+    to: u64,
+) -> StdResult<Uint128> {
+    schedule
+        .iter()
+        .try_fold(Uint128::zero(), |total, (start_time, end_time, rate)| {
+            let start = (*start_time).max(from);
+            let end = (*end_time).min(to);
+            if end <= start {
+                return Ok(total);
+            }
+            let emission = rate.checked_mul(Uint128::from(end - start))?;
+            Ok(total.checked_add(emission)?)
+        })
+}
@@ -12,7 +12,9 @@ pub struct InstantiateMsg {
 }
 
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    pub admin: Option<String>,
+}
 
 #[cw_serde]
 pub enum SwapOperation {
@@ -56,6 +58,26 @@ pub enum ExecuteMsg {
         minimum_receive: Uint128,
         receiver: Addr,
     },
+
+    /// Admin function, register the canonical route used to swap offer asset to ask asset
+    SetRoute {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+        operations: Vec<SwapOperation>,
+    },
+    /// Admin function, remove the registered route between offer asset and ask asset
+    RemoveRoute {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+    },
+    /// Swap the sent native token through the registered route
+    ExecuteSwap {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+        amount: Uint128,
+        minimum_receive: Option<Uint128>,
+        to: Option<Addr>,
+    },
 }
 
 #[cw_serde]
@@ -65,6 +87,12 @@ pub enum Cw20HookMsg {
         minimum_receive: Option<Uint128>,
         to: Option<String>,
     },
+    /// Swap the sent token through the registered route
+    ExecuteSwap {
+        ask_asset_info: AssetInfo,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+    },
 }
 
 #[cw_serde]
@@ -77,6 +105,11 @@ pub enum QueryMsg {
         offer_amount: Uint128,
         operations: Vec<SwapOperation>,
     },
+    #[returns(RouteResponse)]
+    Route {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+    },
 }
 
 // We define a custom struct for each query response
@@ -92,6 +125,11 @@ pub struct SimulateSwapOperationsResponse {
     pub amount: Uint128,
 }
 
+#[cw_serde]
+pub struct RouteResponse {
+    pub operations: Vec<SwapOperation>,
+}
+
 #[cw_serde]
 pub struct RouterController(pub String);
 
@@ -1,8 +1,12 @@
+use cosmwasm_schema::serde::de::DeserializeOwned;
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
 use crate::asset::{Asset, AssetInfo};
-use cosmwasm_std::{Addr, Decimal, Uint128};
-use cw20::Cw20ReceiveMsg;
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Decimal256, QuerierWrapper, StdResult,
+    Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -24,15 +28,71 @@ pub enum ExecuteMsg {
     ////////////////////////
     UpdateConfig {
         rewarder: Option<Addr>,
-        owner: Option<Addr>,
         migrate_store_status: Option<bool>,
+        // receives the penalties of instant unbonds
+        penalty_collector: Option<Addr>,
+        // replaces the addresses allowed to deposit rewards besides the rewarder
+        reward_distributors: Option<Vec<Addr>>,
+        // share of every reward withdraw sent to the fee collector, at most 10%
+        withdraw_fee_rate: Option<Decimal>,
+        fee_collector: Option<Addr>,
+        // repoints the contract at a redeployed oracle or factory
+        oracle_addr: Option<Addr>,
+        factory_addr: Option<Addr>,
+        // denom of native reward deposits, ibc/ denoms are accepted
+        base_denom: Option<String>,
+        // receives the rewards swept from abandoned positions
+        community_pool: Option<Addr>,
     },
+    // the owner only changes once the proposed owner accepts before expires_at (block time seconds)
+    ProposeNewOwner {
+        owner: Addr,
+        expires_at: u64,
+    },
+    CancelOwnershipProposal {},
+    // sent by the proposed owner
+    AcceptOwnership {},
+    // stops bonding and reward deposits, unbond and withdraw keep working
+    Pause {},
+    Unpause {},
     RegisterAsset {
         staking_token: Addr,
+        // smallest position a staker can bond up to, None has no minimum
+        min_bond_amount: Option<Uint128>,
+    },
+    // registers every pool or none, see RegisterAsset
+    RegisterAssets {
+        assets: Vec<RegisterAssetEntry>,
+    },
+    // registers the pool of the liquidity token of the factory pair of the asset against
+    // quote_asset, the base denom when not given
+    RegisterAssetFromFactory {
+        asset_info: AssetInfo,
+        quote_asset: Option<AssetInfo>,
+        min_bond_amount: Option<Uint128>,
+    },
+    // registers a pool bonded with a native denom, the other messages take the denom as its
+    // staking token
+    RegisterNativeAsset {
+        denom: String,
+        min_bond_amount: Option<Uint128>,
     },
+    // removes an empty pool with its reward weights, deposits for it fail afterwards
+    UnregisterAsset {
+        staking_token: Addr,
+    },
+    // grace_period is the seconds the deprecated token is still accepted by the Bond hook, it is
+    // then moved into the new pair and bonded there
     DeprecateStakingToken {
         staking_token: Addr,
         new_staking_token: Addr,
+        grace_period: Option<u64>,
+    },
+    // make new_staking_token bondable into the pool alongside its current staking token,
+    // DeprecateStakingToken with the same new token finalizes the transition
+    StartStakingTokenTransition {
+        staking_token: Addr,
+        new_staking_token: Addr,
     },
     // update rewards per second for an asset
     UpdateRewardsPerSec {
@@ -44,47 +104,450 @@ pub enum ExecuteMsg {
     DepositReward {
         rewards: Vec<RewardMsg>,
     },
+    // DepositReward for the owner, force skips the max_index_increase_per_deposit of the pools
+    // for a deliberate large top-up
+    OwnerDepositReward {
+        rewards: Vec<RewardMsg>,
+        force: bool,
+    },
+    // same as DepositReward, with the summed amount sent along in the base denom, every pool
+    // must be rewarded in the base denom only
+    DepositNativeReward {
+        rewards: Vec<RewardMsg>,
+    },
+    // switch a pool to epoch-based reward accounting, None goes back to continuous accrual
+    UpdatePoolEpoch {
+        staking_token: Addr,
+        epoch_length: Option<u64>,
+    },
+    // unbonded amounts wait this many seconds before they can be claimed, zero unbonds instantly
+    UpdateUnbondingPeriod {
+        staking_token: Addr,
+        unbonding_period: u64,
+    },
+    // share of an instant unbond kept as penalty, at most 20%
+    UpdateInstantUnbondPenalty {
+        staking_token: Addr,
+        penalty_rate: Decimal,
+    },
+    // bonds must leave the position of the staker at least at min_bond_amount, smaller
+    // positions can still unbond and withdraw
+    UpdateMinBondAmount {
+        staking_token: Addr,
+        min_bond_amount: Uint128,
+    },
+    // total_bond_amount can not grow past bond_cap, None removes the cap
+    UpdateBondCap {
+        staking_token: Addr,
+        bond_cap: Option<Uint128>,
+    },
+    // the bond of a single staker can not grow past max_bond_per_staker, None removes the cap
+    UpdateMaxBondPerStaker {
+        staking_token: Addr,
+        max_bond_per_staker: Option<Uint128>,
+    },
+    // Compound fails while the pending rewards of the pool, summed over the pair assets, are
+    // below this
+    UpdateMinCompoundReward {
+        staking_token: Addr,
+        min_compound_reward: Uint128,
+    },
+    // durations a bond can be locked for and the reward weight each gives the locked amount,
+    // bonds keep the multiplier they were locked with
+    UpdateLockTiers {
+        staking_token: Addr,
+        lock_tiers: Vec<LockTier>,
+    },
+    // appends (start_time, end_time, amount) entries, each amount unlocks linearly over its time
+    // range and is spread across the pools by their reward weights on bond, unbond and withdraw
+    AppendDistributionSchedule {
+        schedule: Vec<(u64, u64, Uint128)>,
+    },
+    // share of the distribution schedule each pool gets, relative to the weights of the others,
+    // pools not listed keep their weight
+    UpdateRewardWeights {
+        weights: Vec<(Addr, Decimal)>,
+    },
+    // deposits and scheduled rewards outside of [reward_start_time, reward_end_time) skip the
+    // pool, None leaves that side open
+    UpdateRewardWindow {
+        staking_token: Addr,
+        reward_start_time: Option<u64>,
+        reward_end_time: Option<u64>,
+    },
+    // lets reward_token be deposited for the pool with the DepositProxyReward hook, it is paid
+    // out on withdraw along with the reward assets
+    AddProxyReward {
+        staking_token: Addr,
+        reward_token: Addr,
+    },
+    // applies every field set in params at once, nothing changes when one of them is invalid
+    UpdatePoolConfig {
+        staking_token: Addr,
+        params: PoolConfigUpdate,
+    },
+    // a frozen pool takes no new bonds, its stakers still earn, unbond and withdraw
+    UpdatePoolStatus {
+        staking_token: Addr,
+        frozen: bool,
+    },
+    // bond checkpoints older than retention_blocks are pruned as their position changes, zero
+    // keeps every checkpoint up to the per position bound
+    UpdateBondCheckpointRetention {
+        retention_blocks: u64,
+    },
+    // a full unbond leaving less pending reward than this folds it into the pool pending reward,
+    // so the position is removed instead of kept for the dust
+    UpdateDustThreshold {
+        dust_threshold: Uint128,
+    },
+    // positions are weighted by the vote escrow balance this contract reports, None weights
+    // them by their bond alone
+    UpdateBoostContract {
+        boost_contract: Option<Addr>,
+    },
+    // contracts sent a StakingHookMsg each time a bond amount changes, at most 5. Hooks are best
+    // effort: a failing one does not revert the bond change, and the owner should remove it
+    AddHook {
+        contract_addr: Addr,
+    },
+    RemoveHook {
+        contract_addr: Addr,
+    },
+    // sends out an asset transferred to the contract by mistake. Staking tokens, proxy reward
+    // tokens and the reward assets owed to stakers can not be rescued
+    RescueFunds {
+        asset: AssetInfo,
+        amount: Uint128,
+        recipient: Addr,
+    },
+    // sends out the balance of the staking token above what the positions account for, see
+    // the Reconcile query
+    SweepExcess {
+        staking_token: Addr,
+        recipient: Addr,
+    },
+    /// Break-glass fix of a corrupted pool, only while the contract is paused. The reward index
+    /// can only move forward and the total bond not below the bonds of the positions. Every
+    /// repair is kept with its reason in the RepairHistory of the pool
+    RepairPoolInfo {
+        staking_token: Addr,
+        patch: PoolInfoPatch,
+        reason: String,
+    },
+    // while enabled only the whitelisted stakers can bond into the pool, and a blacklisted staker
+    // never can. Unbond and withdraw keep working for everyone. Each list holds at most 500
+    UpdateWhitelist {
+        staking_token: Addr,
+        enabled: Option<bool>,
+        add: Vec<Addr>,
+        remove: Vec<Addr>,
+    },
+    UpdateBlacklist {
+        staking_token: Addr,
+        add: Vec<Addr>,
+        remove: Vec<Addr>,
+    },
 
     ////////////////////////
     /// User operations ///
     ////////////////////////
+    /// Bond the native staking token sent with the message
+    BondNative {
+        staking_token: Addr,
+    },
+    /// Bond a cw20 staking token through an allowance given to this contract
+    BondFromAllowance {
+        staking_token: Addr,
+        amount: Uint128,
+    },
     Unbond {
+        staking_token: Addr,
+        // None unbonds the whole position
+        amount: Option<Uint128>,
+        // if true, the pending rewards of the pool are withdrawn in the same response
+        withdraw_rewards: Option<bool>,
+        // if given, the staking token is sent to this address, rewards still go to the sender.
+        // Only for pools without an unbonding period
+        recipient: Option<Addr>,
+        // if given, the staking token is sent to this contract with the message instead, like
+        // recipient. A failing contract reverts the unbond
+        unbond_msg: Option<UnbondSendMsg>,
+    },
+    /// Unbond without waiting for the unbonding period, the pool penalty goes to the collector
+    InstantUnbond {
+        staking_token: Addr,
+        amount: Uint128,
+    },
+    /// Move unlocked bonded tokens to the position of the recipient without unbonding them, the
+    /// pending rewards stay with the sender. The recipient must be allowed to bond into the pool
+    TransferBond {
         staking_token: Addr,
         amount: Uint128,
+        recipient: Addr,
+    },
+    /// Transfer the unbonded amounts whose unbonding period has passed
+    Claim {
+        staking_token: Addr,
+    },
+    /// Recompute the boost of a position after its vote escrow balance changed, callable by anyone
+    UpdateBoost {
+        staker_addr: Addr,
+        staking_token: Addr,
     },
     /// Withdraw pending rewards
     Withdraw {
-        // If the asset token is not given, then all rewards are withdrawn
+        // If the asset token is not given, then the rewards of all pools are withdrawn in one
+        // transfer per reward asset, for stakers of at most 30 pools
         staking_token: Option<Addr>,
+        // If given, native rewards are sent to a remote chain address through ICS-20
+        ibc_recipient: Option<IbcRecipient>,
+        // If given, only these amounts of the pool rewards are withdrawn and the rest stays
+        // pending, requires the staking token
+        amounts: Option<Vec<Asset>>,
+        // If given, the rewards are sent to this address instead of the staker, the accounting
+        // stays with the staker
+        recipient: Option<Addr>,
+        // If given, the operator this staker approved withdraws on its behalf, the rewards are
+        // then always sent to the staker
+        staker_addr: Option<Addr>,
+    },
+    /// Approve an address to withdraw the rewards of the sender, it can not touch the bond.
+    /// None revokes the operator
+    SetOperator {
+        operator: Option<Addr>,
+    },
+    /// Withdraw the pending rewards of the pool with each reward asset swapped for target_asset
+    /// through its pair, belief_price and max_spread apply to every swap
+    WithdrawAndSwap {
+        staking_token: Addr,
+        target_asset: AssetInfo,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
     },
     // Withdraw for others in this pool, such as when rewards per second are changed for the pool
     WithdrawOthers {
         staking_token: Option<Addr>,
         staker_addrs: Vec<Addr>,
     },
+    /// Send the settled rewards of the listed stakers of the pool to the community pool, for the
+    /// positions no staker action touched for min_idle_seconds and at least two years. The bond
+    /// stays with the staker
+    SweepAbandonedRewards {
+        staking_token: Addr,
+        stakers: Vec<Addr>,
+        min_idle_seconds: u64,
+    },
+    /// Move the reward deposited into from_asset while nothing was bonded to it into the reward
+    /// of the to_assets pools, split evenly between them. Each of them must have a bond
+    ReallocateOrphanedRewards {
+        from_asset: Addr,
+        to_assets: Vec<Addr>,
+    },
+    /// Roll the ended epoch of a pool into its reward index, anyone can call it
+    CloseEpoch {
+        staking_token: Addr,
+    },
+    /// Move at most limit legacy pools and stakers into the current store after a migrate,
+    /// anyone can call it. Staking reopens once the last entry has been moved
+    MigrateStep {
+        limit: Option<u32>,
+    },
+    /// Remove at most limit positions with nothing bonded, owed or claimed, resuming where the
+    /// last call stopped, anyone can call it
+    PruneRewardInfos {
+        limit: Option<u32>,
+    },
 
     /// Provides liquidity and automatically stakes the LP tokens
     AutoStake {
         assets: [Asset; 2],
         slippage_tolerance: Option<Decimal>,
+        // the whole auto stake reverts when the pair mints fewer LP tokens than this
+        minimum_lp_amount: Option<Uint128>,
     },
     /// Hook to stake the minted LP tokens
     AutoStakeHook {
         staking_token: Addr,
         staker_addr: Addr,
         prev_staking_token_amount: Uint128,
+        minimum_lp_amount: Option<Uint128>,
+    },
+    /// Moves the position of the sender on the deprecated staking token into the new one:
+    /// the deprecated liquidity is withdrawn from its pair, provided to the pair of the new
+    /// staking token and the LP tokens are bonded again
+    MigrateBond {
+        staking_token: Addr,
+        slippage_tolerance: Option<Decimal>,
+    },
+    /// Hook to close the deprecated position once its liquidity has been withdrawn
+    MigrateBondHook {
+        staker_addr: Addr,
+        staking_token: Addr,
+        pair_addr: Addr,
+        amount: Uint128,
+        // balances of the contract in the pair assets before the withdraw
+        prev_balances: [Asset; 2],
+        slippage_tolerance: Option<Decimal>,
+    },
+    /// Hook to provide the liquidity withdrawn for a deprecated token bonded during the grace
+    /// period, the LP tokens of the new pair are bonded for the staker
+    MigrateReceivedBondHook {
+        staker_addr: Addr,
+        staking_token: Addr,
+        pair_addr: Addr,
+        // balances of the contract in the pair assets before the withdraw
+        prev_balances: [Asset; 2],
+    },
+    /// Reinvests the pending rewards of the pair pool into its LP tokens, a reward in only one
+    /// of the pair assets is half swapped for the other first. Rewards in other assets are
+    /// withdrawn as usual
+    Compound {
+        asset_infos: [AssetInfo; 2],
+        slippage_tolerance: Option<Decimal>,
+    },
+    /// Hook to provide the compounded rewards once the swap has settled
+    CompoundHook {
+        staker_addr: Addr,
+        staking_token: Addr,
+        pair_addr: Addr,
+        assets: [Asset; 2],
+        // balance of the bought asset before the swap, None if nothing was swapped
+        prev_ask_balance: Option<Uint128>,
+        slippage_tolerance: Option<Decimal>,
     },
 }
 
 #[cw_serde]
 pub enum Cw20HookMsg {
-    // this call from LP token contract
-    Bond {},
+    // this call from LP token contract, lock_duration must be one of the pool lock tiers. The
+    // amount is added to the referred volume of referrer, who can not be the staker
+    Bond {
+        lock_duration: Option<u64>,
+        referrer: Option<Addr>,
+    },
+    // bonds the sent tokens into the position of staker_addr, such as for a vault bonding for
+    // its depositors
+    BondFor {
+        staker_addr: Addr,
+    },
+    // sent by the rewarder or a reward distributor, the amount is split between pools by weights summing to 1
+    DepositRewardWeights {
+        weights: Vec<(Addr, Decimal)>,
+    },
+    // sent by the rewarder or a reward distributor, the amount is split between the pools by the
+    // reward weights set with UpdateRewardWeights. stream_over_seconds releases each share into its
+    // pool over that many seconds, None takes the reward_stream_seconds of each pool and zero adds
+    // the shares at once. expected_epoch fails the deposit unless it is the next deposit_epoch
+    // of every pool it reaches, so a distributor sending the same run twice is refused
+    DepositReward {
+        stream_over_seconds: Option<u64>,
+        expected_epoch: Option<u64>,
+    },
+    // sent by a proxy reward token of the pool, see AddProxyReward
+    DepositProxyReward {
+        staking_token: Addr,
+    },
 }
 
-/// We currently take no arguments for migrations
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct RegisterAssetEntry {
+    pub staking_token: Addr,
+    pub min_bond_amount: Option<Uint128>,
+}
+
+// fields left as None keep their value, each one is validated like its own update message
+#[cw_serde]
+#[derive(Default)]
+pub struct PoolConfigUpdate {
+    pub unbonding_period: Option<u64>,
+    pub instant_unbond_penalty: Option<Decimal>,
+    pub min_bond_amount: Option<Uint128>,
+    pub min_compound_reward: Option<Uint128>,
+    pub bond_cap: Option<CapUpdate>,
+    pub max_bond_per_staker: Option<CapUpdate>,
+    pub frozen: Option<bool>,
+    // zero lifts the limit
+    pub max_index_increase_per_deposit: Option<Decimal>,
+    pub unbond_cooldown: Option<u64>,
+    pub reward_stream_seconds: Option<u64>,
+    // (reward_start_time, reward_end_time), replaced together
+    pub reward_window: Option<(Option<u64>, Option<u64>)>,
+}
+
+#[cw_serde]
+pub enum CapUpdate {
+    Set(Uint128),
+    Remove {},
+}
+
+impl CapUpdate {
+    pub fn value(&self) -> Option<Uint128> {
+        match self {
+            CapUpdate::Set(cap) => Some(*cap),
+            CapUpdate::Remove {} => None,
+        }
+    }
+}
+
+// a cw20 Send of the unbonded staking token, native staking tokens are sent as the funds of
+// an execute of the contract with msg
+#[cw_serde]
+pub struct UnbondSendMsg {
+    pub contract: Addr,
+    pub msg: Binary,
+}
+
+// sent to the hook contracts after a bond or unbond, new_amount is the bond of the position after it
+#[cw_serde]
+pub enum StakingHookMsg {
+    BondChanged {
+        staker_addr: Addr,
+        staking_token: Addr,
+        new_amount: Uint128,
+    },
+}
+
+// executed by the chain governance module, each variant matches the owner-only ExecuteMsg of the
+// same name
+#[cw_serde]
+pub enum SudoMsg {
+    Pause {},
+    Unpause {},
+    UpdateRewardsPerSec {
+        staking_token: Addr,
+        assets: Vec<Asset>,
+    },
+    UpdateRewardWeights {
+        weights: Vec<(Addr, Decimal)>,
+    },
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    // pools deprecated as part of the migration, None only migrates the store
+    pub deprecations: Option<Vec<StakingTokenDeprecation>>,
+}
+
+#[cw_serde]
+pub struct StakingTokenDeprecation {
+    pub staking_token: Addr,
+    pub new_staking_token: Addr,
+}
+
+// the query a boost contract answers, the vote escrow balance of address and the total of them
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum BoostQueryMsg {
+    #[returns(VotingPowerResponse)]
+    VotingPower { address: String },
+}
+
+#[cw_serde]
+pub struct VotingPowerResponse {
+    pub voting_power: Uint128,
+    pub total_voting_power: Uint128,
+}
 
 /// We currently take no arguments for migrations
 #[cw_serde]
@@ -99,8 +562,28 @@ pub struct AmountInfo {
 pub enum QueryMsg {
     #[returns(ConfigResponse)]
     Config {},
+    // totals across all pools
+    #[returns(StateResponse)]
+    State {},
     #[returns(PoolInfoResponse)]
     PoolInfo { staking_token: Addr },
+    // registered pools in staking token key order, fields as in PoolInfo
+    #[returns(Vec<PoolInfoResponse>)]
+    PoolInfos {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        order: Option<i32>,
+    },
+    // reward of the registered pools deposited while nothing was bonded, paged as PoolInfos
+    #[returns(Vec<OrphanedRewardResponse>)]
+    OrphanedRewards {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        order: Option<i32>,
+    },
+    // also resolves a deprecated staking token to the pool that took it over
+    #[returns(PoolInfoByStakingTokenResponse)]
+    PoolInfoByStakingToken { staking_token: Addr },
     #[returns(RewardsPerSecResponse)]
     RewardsPerSec { staking_token: Addr },
     #[returns(RewardInfoResponse)]
@@ -108,6 +591,64 @@ pub enum QueryMsg {
         staker_addr: Addr,
         staking_token: Option<Addr>,
     },
+    // RewardInfo of the pool bonded with the staking token, a deprecated staking token resolves
+    // to the pool that took it over
+    #[returns(RewardInfoByStakingTokenResponse)]
+    RewardInfoByStakingToken {
+        staker_addr: Addr,
+        staking_token: Addr,
+    },
+    // the position of the staker in one pool, its rewards and its unbondings
+    #[returns(StakerInfoResponse)]
+    StakerInfo {
+        staker_addr: Addr,
+        staking_token: Addr,
+    },
+    // positions of the staker across its pools, for at most limit pools after start_after
+    #[returns(RewardInfoResponse)]
+    StakerRewardInfos {
+        staker_addr: Addr,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        order: Option<i32>,
+    },
+    // bond amount of the position at the end of block `height`, zero before its first checkpoint
+    #[returns(BondAmountAtResponse)]
+    BondAmountAt {
+        staker_addr: Addr,
+        staking_token: Addr,
+        height: u64,
+    },
+    // reward index and total bond of the pool after its deposits, at most one per block
+    #[returns(Vec<RewardIndexHistoryItem>)]
+    RewardIndexHistory {
+        staking_token: Addr,
+        start_after_time: Option<u64>,
+        limit: Option<u32>,
+    },
+    // one entry per given staker in the same order, zeros for stakers without a position
+    #[returns(BatchRewardInfoResponse)]
+    BatchRewardInfo {
+        stakers: Vec<Addr>,
+        staking_token: Addr,
+    },
+    // bonded stakers of the pool with their bond amount
+    #[returns(StakersResponse)]
+    Stakers {
+        staking_token: Addr,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        order: Option<i32>,
+    },
+    // stakers of the pool among the next `limit` after start_after whose position has not been
+    // bonded, unbonded or withdrawn since older_than
+    #[returns(StaleStakersResponse)]
+    StaleStakers {
+        staking_token: Addr,
+        older_than: u64,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
     #[returns(Vec<RewardInfoResponse>)]
     // Query all staker belong to the pool
     RewardInfos {
@@ -119,8 +660,115 @@ pub enum QueryMsg {
     },
     #[returns(Vec<QueryPoolInfoResponse>)]
     GetPoolsInformation {},
+    #[returns(PoolEpochResponse)]
+    PoolEpoch { staking_token: Addr },
+    #[returns(DistributionScheduleResponse)]
+    DistributionSchedule {},
+    // the schedule rate running now and how the accrual splits it between the pools
+    #[returns(DistributionInfoResponse)]
+    DistributionInfo {},
+    // rewards per second flowing to the pool now, in reward amount before the split by weights
+    #[returns(PoolRewardRateResponse)]
+    PoolRewardRate { staking_token: Addr },
+    // what an unlocked bond of `amount` would earn at the rates of PoolRewardRate
+    #[returns(SimulateBondResponse)]
+    SimulateBond {
+        staking_token: Addr,
+        amount: Uint128,
+    },
+    #[returns(DeprecatedBondsResponse)]
+    DeprecatedBonds { staking_token: Addr },
+    // the balance the contract holds of the staking token against the amounts bonded in it
+    #[returns(ReconcileResponse)]
+    Reconcile { staking_token: Addr },
+    // repairs of the pool in the order they were made, after the one with id start_after
+    #[returns(Vec<PoolRepairResponse>)]
+    RepairHistory {
+        staking_token: Addr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // pools with a deprecated staking token, in pool staking token order, others are left out
+    #[returns(Vec<PoolMigrationStatus>)]
+    MigrationStatus {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    // the rewards a Withdraw would pay right now, per pool and summed, with the withdraw fee
+    #[returns(SimulateWithdrawResponse)]
+    SimulateWithdraw {
+        staker_addr: Addr,
+        staking_token: Option<Addr>,
+    },
+    // the address the staker approved to withdraw its rewards, if any
+    #[returns(OperatorResponse)]
+    Operator { staker_addr: Addr },
+    // whether the whitelist and blacklist of the pool let the staker bond
+    #[returns(CanBondResponse)]
+    CanBond {
+        staking_token: Addr,
+        staker_addr: Addr,
+    },
+    // walks one segment of the store in key order, at most 30 records a page. start_after is
+    // the next_key of the previous page
+    #[returns(ExportStateResponse)]
+    ExportState {
+        segment: ExportSegment,
+        start_after: Option<Binary>,
+        limit: Option<u32>,
+    },
+    // amounts bonded with referrer, per staking token
+    #[returns(ReferralStatsResponse)]
+    ReferralStats {
+        referrer: Addr,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        order: Option<i32>,
+    },
+    #[returns(UnbondingResponse)]
+    Unbonding {
+        staker_addr: Addr,
+        staking_token: Addr,
+    },
+    // queued unbondings of the staker in one pool, or in at most limit pools after start_after
+    #[returns(UnbondingClaimsResponse)]
+    UnbondingClaims {
+        staker_addr: Addr,
+        staking_token: Option<Addr>,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        order: Option<i32>,
+    },
     #[returns(cosmwasm_std::Binary)]
     QueryOldStore { store_type: OldStoreType },
+    // name and version of the code that last wrote the store
+    #[returns(ContractInfoResponse)]
+    ContractInfo {},
+    #[returns(MigrationProgressResponse)]
+    MigrationProgress {},
+}
+
+// set as the data of the execute responses, for contracts calling the staking contract
+#[cw_serde]
+pub struct BondResponse {
+    pub staker_addr: Addr,
+    pub staking_token: Addr,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct UnbondResponse {
+    pub staker_addr: Addr,
+    pub staking_token: Addr,
+    pub amount: Uint128,
+    // set when the amount waits out the unbonding period instead of being transferred
+    pub release_time: Option<u64>,
+}
+
+#[cw_serde]
+pub struct WithdrawResponse {
+    // sent to the recipient, after the withdraw fee
+    pub reward_assets: Vec<Asset>,
 }
 
 // We define a custom struct for each query response
@@ -131,11 +779,66 @@ pub struct ConfigResponse {
     pub oracle_addr: Addr,
     pub factory_addr: Addr,
     pub base_denom: String,
+    pub paused: bool,
+    // proposed owner that has not accepted yet, also shown once the proposal expired
+    pub pending_owner: Option<Addr>,
+    pub pending_owner_expires_at: Option<u64>,
+    pub penalty_collector: Option<Addr>,
+    pub reward_distributors: Vec<Addr>,
+    pub withdraw_fee_rate: Decimal,
+    pub fee_collector: Option<Addr>,
+    pub bond_checkpoint_retention: u64,
+    pub dust_threshold: Uint128,
+    pub boost_contract: Option<Addr>,
+    pub hooks: Vec<Addr>,
+    pub community_pool: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct StateResponse {
+    pub pool_count: u64,
+    pub total_deposited: Uint128,
+    // taken in from the distribution schedule
+    pub total_scheduled: Uint128,
+    // paid out by withdraws and compounds, fees included, and by sweeps
+    pub total_withdrawn: Uint128,
+    // what the stakers are owed per reward asset
+    pub total_outstanding_rewards: Vec<Asset>,
+    // what the contract holds of each of those assets, in the same order, an amount below the
+    // outstanding one means the claims on that asset are not covered
+    pub reward_reserves: Vec<Asset>,
+    // deposited while the pools had nothing bonded and not distributed yet, summed over the pools
+    pub pending_reward: Uint128,
+    // block time of the latest deposit call, None before the first
+    pub last_deposit_time: Option<u64>,
+}
+
+#[cw_serde]
+pub struct ContractInfoResponse {
+    pub name: String,
+    pub version: String,
+}
+
+#[cw_serde]
+pub struct MigrationProgressResponse {
+    // true until MigrateStep has moved every legacy pool
+    pub migrating: bool,
+    pub migrated_pools: u64,
+    pub migrated_stakers: u64,
+    pub legacy_pools: u64,
 }
 
 #[cw_serde]
 pub struct RewardsPerSecResponse {
     pub assets: Vec<Asset>,
+    // decimals of the reward assets, assets whose decimals are unknown are omitted
+    pub reward_decimals: Vec<AssetDecimals>,
+}
+
+#[cw_serde]
+pub struct AssetDecimals {
+    pub info: AssetInfo,
+    pub decimals: u8,
 }
 
 // We define a custom struct for each query response
@@ -147,6 +850,186 @@ pub struct PoolInfoResponse {
     pub pending_reward: Uint128,
     pub migration_index_snapshot: Option<Decimal>,
     pub migration_deprecated_staking_token: Option<Addr>,
+    pub migration_deprecated_total_bond: Option<Uint128>,
+    // the Bond hook moves the deprecated token into the new pair until then
+    pub migration_grace_period_end: Option<u64>,
+    pub frozen: bool,
+    pub instant_unbond_penalty: Decimal,
+    pub min_bond_amount: Uint128,
+    pub min_compound_reward: Uint128,
+    pub bond_cap: Option<Uint128>,
+    // amount that can still be bonded under the cap
+    pub remaining_bond_capacity: Option<Uint128>,
+    pub max_bond_per_staker: Option<Uint128>,
+    // total_bond_amount with the locked amounts counted at their multiplier, rewards are
+    // shared over it
+    pub total_weighted_bond: Uint128,
+    pub lock_tiers: Vec<LockTier>,
+    pub reward_weight: Decimal,
+    pub reward_start_time: Option<u64>,
+    pub reward_end_time: Option<u64>,
+    pub proxy_rewards: Vec<ProxyRewardResponse>,
+    pub symbol: Option<String>,
+    // positions with a bond, None when the pool was registered before they were counted
+    pub staker_count: Option<u64>,
+    pub lifetime_bonded: Uint128,
+    pub lifetime_unbonded: Uint128,
+    // lifetime rewards that reached the positions of the pool, and paid out of it
+    pub lifetime_rewards_deposited: Uint128,
+    pub lifetime_rewards_claimed: Uint128,
+    pub unbonding_period: u64,
+    pub whitelist_enabled: bool,
+    // seconds after its last bond a position can not unbond for, zero has no cooldown
+    pub unbond_cooldown: u64,
+    // most a single deposit can raise reward_index by, zero has no limit
+    pub max_index_increase_per_deposit: Decimal,
+    // seconds a deposit is streamed over when the depositor does not say, zero adds it at once
+    pub reward_stream_seconds: u64,
+    // streamed deposits not released into reward_index yet
+    pub streaming_reward: Uint128,
+    // deposits the pool took in, the next one is deposit_epoch + 1
+    pub deposit_epoch: u64,
+}
+
+#[cw_serde]
+pub struct PoolInfoByStakingTokenResponse {
+    pub pool_info: PoolInfoResponse,
+    // the staking token was deprecated, its positions can only be unbonded or migrated
+    pub deprecated: bool,
+}
+
+#[cw_serde]
+pub struct RewardInfoByStakingTokenResponse {
+    pub reward_info: RewardInfoResponse,
+    // the staking token is the deprecated one of the pool rather than its current one
+    pub deprecated: bool,
+}
+
+#[cw_serde]
+pub struct ProxyRewardResponse {
+    pub reward_token: Addr,
+    pub proxy_index: Decimal,
+    // deposited while nothing was bonded
+    pub pending: Uint128,
+}
+
+#[cw_serde]
+pub struct DistributionScheduleResponse {
+    pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+    pub last_distributed: u64,
+    // unlocked since last_distributed, added to the pools by the next bond, unbond or withdraw
+    pub pending_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct DistributionInfoResponse {
+    // sum of the distribution schedule entries running now
+    pub rewards_per_sec: Decimal,
+    pub last_distributed: u64,
+    // the pools the next accrual shares the schedule between
+    pub pools: Vec<PoolDistributionInfo>,
+}
+
+#[cw_serde]
+pub struct PoolDistributionInfo {
+    pub staking_token: Addr,
+    pub reward_weight: Decimal,
+    // reward_weight over the weights of all the pools listed
+    pub share: Decimal,
+    pub rewards_per_sec: Decimal,
+}
+
+#[cw_serde]
+pub struct DeprecatedBondsResponse {
+    pub staking_token: Addr,
+    pub deprecated_staking_token: Addr,
+    // amount still bonded against the deprecated staking token
+    pub deprecated_total_bond: Uint128,
+    // number of stakers that have not migrated their position yet
+    pub staker_count: u64,
+}
+
+// the pool fields a RepairPoolInfo can set, None keeps the stored value
+#[cw_serde]
+pub struct PoolInfoPatch {
+    pub reward_index: Option<Decimal>,
+    pub total_bond_amount: Option<Uint128>,
+    pub pending_reward: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct PoolFieldChange {
+    pub field: String,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+#[cw_serde]
+pub struct PoolRepairResponse {
+    pub id: u64,
+    pub time: u64,
+    pub reason: String,
+    pub changes: Vec<PoolFieldChange>,
+}
+
+#[cw_serde]
+pub struct ReconcileResponse {
+    pub staking_token: Addr,
+    pub balance: Uint128,
+    // bonded in this staking token by the positions still earning
+    pub total_bond_amount: Uint128,
+    // bonded by the positions left in it when it was deprecated
+    pub deprecated_total_bond: Uint128,
+    // unbonded and waiting to be claimed
+    pub total_unbonding: Uint128,
+    // balance above the three, what SweepExcess sends out
+    pub surplus: Uint128,
+}
+
+#[cw_serde]
+pub struct PoolMigrationStatus {
+    pub staking_token: Addr,
+    pub deprecated_staking_token: Addr,
+    pub index_snapshot: Decimal,
+    // amount still bonded against the deprecated staking token, as in DeprecatedBonds
+    pub deprecated_total_bond: Uint128,
+}
+
+#[cw_serde]
+pub struct PoolEpochResponse {
+    pub epoch_length: u64,
+    pub epoch_number: u64,
+    // rewards deposited during the current epoch, not distributed yet
+    pub accumulated_reward: Uint128,
+    // seconds until the current epoch closes
+    pub time_remaining: u64,
+}
+
+#[cw_serde]
+pub struct PoolRewardRateResponse {
+    pub staking_token: Addr,
+    pub total_bond_amount: Uint128,
+    pub total_weighted_bond: Uint128,
+    // the pool's reward weight share of the distribution schedule entries running now
+    pub scheduled_rewards_per_sec: Decimal,
+    // the latest deposits after the oldest of them, averaged over the seconds since the oldest
+    pub deposited_rewards_per_sec: Decimal,
+    pub rewards_per_sec: Decimal,
+    // rewards_per_sec earned by one unlocked bonded unit, None while nothing is bonded
+    pub rewards_per_sec_per_bond: Option<Decimal>,
+}
+
+#[cw_serde]
+pub struct SimulateBondResponse {
+    pub staking_token: Addr,
+    pub amount: Uint128,
+    // amount over the weighted bond of the pool once it is added
+    pub pool_share: Decimal,
+    // reward amount over the next 86400 seconds, before the split by the reward weights
+    pub daily_reward: Uint128,
+    // rewards_per_sec_per_bond of PoolRewardRate before and after the bond
+    pub rewards_per_sec_per_bond: Option<Decimal>,
+    pub rewards_per_sec_per_bond_after: Decimal,
 }
 
 // We define a custom struct for each query response
@@ -156,15 +1039,251 @@ pub struct RewardInfoResponse {
     pub reward_infos: Vec<RewardInfoResponseItem>,
 }
 
+#[cw_serde]
+pub struct BondAmountAtResponse {
+    pub staker_addr: Addr,
+    pub staking_token: Addr,
+    pub height: u64,
+    pub bond_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct RewardIndexHistoryItem {
+    pub time: u64,
+    pub reward_index: Decimal,
+    pub total_bond_amount: Uint128,
+    // deposit_epoch of the pool once the deposit was added, the latest of the block
+    pub deposit_epoch: u64,
+}
+
+#[cw_serde]
+pub struct BatchRewardInfoResponse {
+    pub staking_token: Addr,
+    pub reward_infos: Vec<StakerRewardInfo>,
+}
+
+#[cw_serde]
+pub struct StakerRewardInfo {
+    pub staker_addr: Addr,
+    pub bond_amount: Uint128,
+    // as in RewardInfoResponseItem, settled against the current pool index
+    pub pending_reward: Uint128,
+    pub pending_withdraw: Vec<Asset>,
+}
+
 #[cw_serde]
 pub struct RewardInfoResponseItem {
     pub staking_token: Addr,
     pub bond_amount: Uint128,
+    // bond_amount with the locked amounts counted at their multiplier
+    pub weighted_bond_amount: Uint128,
+    // locks that have not been released yet, an expired lock no longer holds its amount
+    pub locks: Vec<BondLock>,
     pub pending_reward: Uint128,
     pub pending_withdraw: Vec<Asset>,
+    // decimals of the pending withdraw assets, assets whose decimals are unknown are omitted
+    pub reward_decimals: Vec<AssetDecimals>,
     // returns true if the position should be closed to keep receiving rewards
     // with the new lp token
     pub should_migrate: Option<bool>,
+    // reward assets withdrawn or compounded from the position since the upgrade that added it
+    pub total_claimed: Uint128,
+    // block time of the last bond, unbond or withdraw, the upgrade that started recording it for
+    // positions untouched since
+    pub last_updated: u64,
+}
+
+#[cw_serde]
+pub struct SimulateWithdrawResponse {
+    pub staker_addr: Addr,
+    // summed over the pools, before the withdraw fee
+    pub reward_assets: Vec<Asset>,
+    // taken from each summed asset for the fee collector
+    pub withdraw_fees: Vec<Asset>,
+    // sent to the recipient, the reward_assets of the WithdrawResponse
+    pub net_assets: Vec<Asset>,
+    // the fee is charged on the sums, not per pool
+    pub pools: Vec<SimulatedPoolWithdraw>,
+}
+
+#[cw_serde]
+pub struct SimulatedPoolWithdraw {
+    pub staking_token: Addr,
+    pub reward_assets: Vec<Asset>,
+}
+
+#[cw_serde]
+pub struct StakerInfoResponse {
+    pub staker_addr: Addr,
+    // current staking token of the pool
+    pub staking_token: Addr,
+    // set while the position is still bonded with the deprecated staking token
+    pub deprecated_staking_token: Option<Addr>,
+    pub bond_amount: Uint128,
+    pub weighted_bond_amount: Uint128,
+    pub locks: Vec<BondLock>,
+    // what a Withdraw of the pool pays in this block, after the withdraw fee
+    pub pending_rewards: Vec<Asset>,
+    pub withdraw_fees: Vec<Asset>,
+    pub unbonding: Vec<UnbondingEntry>,
+    pub claimable_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct UnbondingEntry {
+    pub amount: Uint128,
+    // block time in seconds from which the amount can be claimed
+    pub release_time: u64,
+}
+
+#[cw_serde]
+pub struct LockTier {
+    // seconds the bonded amount stays locked
+    pub duration: u64,
+    // weight of the locked amount in the reward index, at least 1
+    pub multiplier: Decimal,
+}
+
+#[cw_serde]
+pub struct BondLock {
+    pub amount: Uint128,
+    // block time in seconds from which the amount can be unbonded
+    pub unlock_time: u64,
+    pub multiplier: Decimal,
+}
+
+#[cw_serde]
+pub struct UnbondingResponse {
+    pub staker_addr: Addr,
+    pub staking_token: Addr,
+    pub unbonding_period: u64,
+    pub entries: Vec<UnbondingEntry>,
+    // sum of the entries that can be claimed now
+    pub claimable_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct UnbondingClaimsResponse {
+    pub staker_addr: Addr,
+    pub claims: Vec<UnbondingClaim>,
+    // sum of the returned claims that can be claimed now
+    pub total_claimable: Uint128,
+}
+
+#[cw_serde]
+pub struct UnbondingClaim {
+    pub staking_token: Addr,
+    pub amount: Uint128,
+    pub release_time: u64,
+    pub claimable: bool,
+}
+
+#[cw_serde]
+pub struct OperatorResponse {
+    pub staker_addr: Addr,
+    pub operator: Option<Addr>,
+}
+
+#[cw_serde]
+pub enum ExportSegment {
+    Pools,
+    RewardInfos,
+    UnbondingClaims,
+}
+
+#[cw_serde]
+pub struct ExportStateResponse {
+    pub records: Vec<ExportRecord>,
+    // None once the segment has been walked to its end
+    pub next_key: Option<Binary>,
+}
+
+// key is the store key of the record, records come in ascending key order
+#[cw_serde]
+pub enum ExportRecord {
+    Pool {
+        key: Binary,
+        pool_info: PoolInfoResponse,
+        // the index pool_info reports as a Decimal, as it is stored
+        reward_index: Decimal256,
+        rewards_per_sec: Vec<Asset>,
+    },
+    RewardInfo {
+        key: Binary,
+        staker_addr: Addr,
+        staking_token: Addr,
+        reward_info: RewardInfoExport,
+    },
+    UnbondingClaims {
+        key: Binary,
+        staker_addr: Addr,
+        staking_token: Addr,
+        entries: Vec<UnbondingEntry>,
+    },
+}
+
+// the stored reward info of a position, with its addresses humanized
+#[cw_serde]
+pub struct RewardInfoExport {
+    pub native_token: bool,
+    pub index: Decimal256,
+    pub bond_amount: Uint128,
+    pub pending_reward: Uint128,
+    pub pending_withdraw: Vec<Asset>,
+    pub locks: Vec<BondLock>,
+    pub proxy_indices: Vec<(Addr, Decimal)>,
+    pub total_claimed: Uint128,
+    pub boost_shortfall: Uint128,
+    pub last_updated: u64,
+    pub last_bond_time: u64,
+}
+
+#[cw_serde]
+pub struct CanBondResponse {
+    pub can_bond: bool,
+    pub whitelist_enabled: bool,
+    pub whitelisted: bool,
+    pub blacklisted: bool,
+}
+
+#[cw_serde]
+pub struct ReferralStatsResponse {
+    pub referrer: Addr,
+    pub stats: Vec<ReferralStat>,
+}
+
+#[cw_serde]
+pub struct ReferralStat {
+    pub staking_token: Addr,
+    // cumulative, unbonds do not decrease it
+    pub bond_volume: Uint128,
+}
+
+#[cw_serde]
+pub struct StakersResponse {
+    pub staking_token: Addr,
+    pub stakers: Vec<StakerBond>,
+}
+
+#[cw_serde]
+pub struct StakerBond {
+    pub staker_addr: Addr,
+    pub bond_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct StaleStakersResponse {
+    pub staking_token: Addr,
+    pub stakers: Vec<StaleStaker>,
+    // last staker scanned, None once the page reached the end of the pool
+    pub next_start_after: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct StaleStaker {
+    pub staker_addr: Addr,
+    pub bond_amount: Uint128,
+    pub last_updated: u64,
 }
 
 #[cw_serde]
@@ -173,6 +1292,24 @@ pub struct RewardMsg {
     pub total_accumulation_amount: Uint128,
 }
 
+#[cw_serde]
+pub struct IbcRecipient {
+    // local channel id, such as channel-0
+    pub channel_id: String,
+    // bech32 address on the remote chain
+    pub remote_address: String,
+    pub timeout_seconds: u64,
+}
+
+#[cw_serde]
+pub struct OrphanedRewardResponse {
+    pub staking_token: Addr,
+    // the part of pending_reward no position is owed
+    pub orphaned_reward: Uint128,
+    pub pending_reward: Uint128,
+    pub total_bond_amount: Uint128,
+}
+
 #[cw_serde]
 pub struct QueryPoolInfoResponse {
     pub asset_key: String,
@@ -187,3 +1324,119 @@ pub enum OldStoreType {
     IsMigrated { staker: String },
     RewardsPerSec {},
 }
+
+/// StakingContract is a wrapper around Addr that builds the messages and queries of the staking
+/// contract, so integrators do not write the WasmMsg and WasmQuery plumbing by hand
+#[cw_serde]
+pub struct StakingContract(pub Addr);
+
+impl StakingContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn call(&self, msg: ExecuteMsg) -> StdResult<CosmosMsg> {
+        self.call_with_funds(msg, vec![])
+    }
+
+    pub fn call_with_funds(&self, msg: ExecuteMsg, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.0.to_string(),
+            msg: to_binary(&msg)?,
+            funds,
+        }
+        .into())
+    }
+
+    pub fn query<T: DeserializeOwned>(
+        &self,
+        querier: &QuerierWrapper,
+        req: QueryMsg,
+    ) -> StdResult<T> {
+        querier.query_wasm_smart(self.0.to_string(), &req)
+    }
+
+    /*** messages ***/
+
+    /// bonds amount of the cw20 staking token of the sender, the message goes to the token
+    pub fn bond_msg(
+        &self,
+        staking_token: &Addr,
+        amount: Uint128,
+        lock_duration: Option<u64>,
+    ) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: staking_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: self.0.to_string(),
+                amount,
+                msg: to_binary(&Cw20HookMsg::Bond {
+                    lock_duration,
+                    referrer: None,
+                })?,
+            })?,
+            funds: vec![],
+        }
+        .into())
+    }
+
+    /// unbonds amount of the position, None unbonds all of it. Rewards stay pending
+    pub fn unbond_msg(
+        &self,
+        staking_token: &Addr,
+        amount: Option<Uint128>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::Unbond {
+            staking_token: staking_token.clone(),
+            amount,
+            withdraw_rewards: None,
+            recipient: None,
+            unbond_msg: None,
+        })
+    }
+
+    /// withdraws the rewards of the pool to the sender, of every pool when None
+    pub fn withdraw_msg(&self, staking_token: Option<Addr>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::Withdraw {
+            staking_token,
+            ibc_recipient: None,
+            amounts: None,
+            recipient: None,
+            staker_addr: None,
+        })
+    }
+
+    pub fn deposit_reward_msg(&self, rewards: Vec<RewardMsg>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::DepositReward { rewards })
+    }
+
+    /*** queries ***/
+
+    pub fn query_pool_info(
+        &self,
+        querier: &QuerierWrapper,
+        staking_token: &Addr,
+    ) -> StdResult<PoolInfoResponse> {
+        self.query(
+            querier,
+            QueryMsg::PoolInfo {
+                staking_token: staking_token.clone(),
+            },
+        )
+    }
+
+    pub fn query_reward_info(
+        &self,
+        querier: &QuerierWrapper,
+        staker_addr: &Addr,
+        staking_token: Option<Addr>,
+    ) -> StdResult<RewardInfoResponse> {
+        self.query(
+            querier,
+            QueryMsg::RewardInfo {
+                staker_addr: staker_addr.clone(),
+                staking_token,
+            },
+        )
+    }
+}
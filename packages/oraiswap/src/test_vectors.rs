@@ -0,0 +1,70 @@
+//! Fixed input/output tables for the swap math, so every contract that prices against a pair
+//! pool checks itself against the same numbers. A change that moves any of these outputs has to
+//! update the table and therefore every suite that reads it.
+
+/// offer => ask through `compute_swap`, amounts are the ones returned after the commission
+pub struct SwapVector {
+    pub offer_pool: u128,
+    pub ask_pool: u128,
+    pub offer_amount: u128,
+    pub commission_rate: &'static str,
+    /// ask_pool / offer_pool
+    pub spot_price: (u128, u128),
+    pub return_amount: u128,
+    pub spread_amount: u128,
+    pub commission_amount: u128,
+}
+
+/// ask => offer through `compute_offer_amount`
+pub struct ReverseSwapVector {
+    pub offer_pool: u128,
+    pub ask_pool: u128,
+    pub ask_amount: u128,
+    pub commission_rate: &'static str,
+    pub offer_amount: u128,
+    pub spread_amount: u128,
+    pub commission_amount: u128,
+}
+
+pub const SWAP_VECTORS: &[SwapVector] = &[
+    SwapVector {
+        offer_pool: 1_000_000,
+        ask_pool: 1_000_000,
+        offer_amount: 1_000,
+        commission_rate: "0.003",
+        spot_price: (1, 1),
+        return_amount: 997,
+        spread_amount: 1,
+        commission_amount: 2,
+    },
+    SwapVector {
+        offer_pool: 1_000_000,
+        ask_pool: 2_000_000,
+        offer_amount: 10_000,
+        commission_rate: "0.003",
+        spot_price: (2, 1),
+        return_amount: 19_742,
+        spread_amount: 199,
+        commission_amount: 59,
+    },
+    SwapVector {
+        offer_pool: 5_000_000,
+        ask_pool: 1_000_000,
+        offer_amount: 500_000,
+        commission_rate: "0",
+        spot_price: (1, 5),
+        return_amount: 90_909,
+        spread_amount: 9_091,
+        commission_amount: 0,
+    },
+];
+
+pub const REVERSE_SWAP_VECTORS: &[ReverseSwapVector] = &[ReverseSwapVector {
+    offer_pool: 1_000_000,
+    ask_pool: 1_000_000,
+    ask_amount: 997,
+    commission_rate: "0.003",
+    offer_amount: 1_000,
+    spread_amount: 1,
+    commission_amount: 2,
+}];
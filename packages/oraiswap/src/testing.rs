@@ -1,16 +1,23 @@
 use cosmwasm_schema::serde::de::DeserializeOwned;
 use cosmwasm_schema::serde::Serialize;
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
 use cosmwasm_std::{
-    coin, Addr, AllBalanceResponse, Attribute, BalanceResponse, BankQuery, Coin, Decimal, Empty,
-    QuerierWrapper, QueryRequest, StdResult, Uint128,
+    coin, Addr, AllBalanceResponse, Attribute, BalanceResponse, BankQuery, Coin, CosmosMsg,
+    Decimal, Empty, Order, OwnedDeps, QuerierWrapper, QueryRequest, Record, StdResult, Storage,
+    Uint128,
 };
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::marker::PhantomData;
 
 use crate::asset::{AssetInfo, PairInfo, ORAI_DENOM};
 
 use crate::pair::DEFAULT_COMMISSION_RATE;
 use cw_multi_test::{next_block, App, AppResponse, Contract, Executor};
 
+// mock staking queries and staking messages for the contracts that integrate with it
+pub mod staking;
+
 pub const ATOM_DENOM: &str = "ibc/1777D03C5392415FE659F0E8ECB2CE553C6550542A68E4707D5D46949116790B";
 pub const APP_OWNER: &str = "admin";
 
@@ -123,6 +130,18 @@ impl MockApp {
         Ok(response)
     }
 
+    // executes a message built for the sender, such as the ones of a contract wrapper
+    pub fn execute_msg(&mut self, sender: Addr, msg: CosmosMsg) -> Result<AppResponse, String> {
+        let response = self
+            .app
+            .execute(sender, msg)
+            .map_err(|err| err.to_string())?;
+
+        self.app.update_block(next_block);
+
+        Ok(response)
+    }
+
     pub fn query<T: DeserializeOwned, U: Serialize>(
         &self,
         contract_addr: Addr,
@@ -269,6 +288,24 @@ impl MockApp {
         }
     }
 
+    // exchange rates are quoted against orai, 1 orai = 1 / exchange_rate denom
+    pub fn set_exchange_rates(&mut self, exchange_rates: &[(&String, Decimal)]) {
+        if !self.oracle_addr.as_str().is_empty() {
+            for (denom, exchange_rate) in exchange_rates.iter() {
+                self.execute(
+                    Addr::unchecked(APP_OWNER),
+                    self.oracle_addr.clone(),
+                    &crate::oracle::ExecuteMsg::UpdateExchangeRate {
+                        denom: denom.to_string(),
+                        exchange_rate: *exchange_rate,
+                    },
+                    &[],
+                )
+                .unwrap();
+            }
+        }
+    }
+
     pub fn query_balance(&self, account_addr: Addr, denom: String) -> StdResult<Uint128> {
         // load price form the oracle
         let balance: BalanceResponse =
@@ -423,16 +460,180 @@ impl MockApp {
     }
 }
 
+/// MockStorage that counts reads and writes, to measure the storage cost of hot paths
+#[derive(Default)]
+pub struct CountingStorage {
+    storage: MockStorage,
+    reads: Cell<u64>,
+    writes: u64,
+}
+
+impl CountingStorage {
+    pub fn reads(&self) -> u64 {
+        self.reads.get()
+    }
+
+    pub fn writes(&self) -> u64 {
+        self.writes
+    }
+
+    pub fn reset_counters(&mut self) {
+        self.reads.set(0);
+        self.writes = 0;
+    }
+}
+
+impl Storage for CountingStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.reads.set(self.reads.get() + 1);
+        self.storage.get(key)
+    }
+
+    // every record returned by the iterator counts as a read
+    fn range<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'a> {
+        let reads = &self.reads;
+        Box::new(
+            self.storage
+                .range(start, end, order)
+                .inspect(move |_| reads.set(reads.get() + 1)),
+        )
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes += 1;
+        self.storage.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.writes += 1;
+        self.storage.remove(key)
+    }
+}
+
+pub fn mock_counting_dependencies() -> OwnedDeps<CountingStorage, MockApi, MockQuerier> {
+    OwnedDeps {
+        storage: CountingStorage::default(),
+        api: MockApi::default(),
+        querier: MockQuerier::default(),
+        custom_query_type: PhantomData,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::{testing::MOCK_CONTRACT_ADDR, Addr, Coin, Uint128};
 
+    use std::str::FromStr;
+
     use crate::{
-        asset::AssetInfo,
-        querier::{query_supply, query_token_balance},
+        asset::{Asset, AssetInfo},
+        querier::{query_supply, query_supply_raw, query_token_balance, query_token_balance_raw},
         testing::MockApp,
     };
 
+    // every message must keep its externally tagged snake_case layout through serde
+    fn assert_round_trip<T>(msg: T, tag: &str)
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        let json = String::from_utf8(cosmwasm_std::to_vec(&msg).unwrap()).unwrap();
+        assert!(
+            json.starts_with(&format!("{{\"{}\":", tag)),
+            "unexpected layout {}",
+            json
+        );
+        assert_eq!(cosmwasm_std::from_slice::<T>(json.as_bytes()).unwrap(), msg);
+    }
+
+    #[test]
+    fn message_serde_round_trip() {
+        let orai = AssetInfo::NativeToken {
+            denom: "orai".to_string(),
+        };
+        let token = AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        };
+
+        assert_round_trip(
+            crate::staking::ExecuteMsg::Withdraw {
+                staking_token: None,
+                ibc_recipient: None,
+                amounts: None,
+                recipient: None,
+            },
+            "withdraw",
+        );
+        assert_round_trip(
+            crate::staking::Cw20HookMsg::Bond {
+                lock_duration: None,
+                referrer: None,
+            },
+            "bond",
+        );
+        assert_round_trip(
+            crate::staking::QueryMsg::SimulateWithdraw {
+                staker_addr: Addr::unchecked("addr0000"),
+                staking_token: Some(Addr::unchecked("staking")),
+            },
+            "simulate_withdraw",
+        );
+        assert_round_trip(
+            crate::pair::ExecuteMsg::ProvideLiquidity {
+                assets: [
+                    Asset {
+                        info: orai.clone(),
+                        amount: Uint128::from(100u128),
+                    },
+                    Asset {
+                        info: token.clone(),
+                        amount: Uint128::from(100u128),
+                    },
+                ],
+                slippage_tolerance: None,
+                receiver: None,
+            },
+            "provide_liquidity",
+        );
+        assert_round_trip(
+            crate::pair::Cw20HookMsg::WithdrawLiquidity {},
+            "withdraw_liquidity",
+        );
+        assert_round_trip(
+            crate::router::ExecuteMsg::ExecuteSwap {
+                offer_asset_info: orai.clone(),
+                ask_asset_info: token.clone(),
+                amount: Uint128::from(100u128),
+                minimum_receive: None,
+                to: None,
+            },
+            "execute_swap",
+        );
+        assert_round_trip(
+            crate::factory::QueryMsg::AssetWhitelist {
+                start_after: Some(token),
+                limit: None,
+            },
+            "asset_whitelist",
+        );
+        // nested oracle queries are tagged twice
+        assert_round_trip(
+            crate::oracle::QueryMsg::Contract(crate::oracle::OracleContractQuery::Guardians {}),
+            "contract",
+        );
+        assert_round_trip(
+            crate::oracle::QueryMsg::Exchange(crate::oracle::OracleExchangeQuery::ExchangeRate {
+                base_denom: None,
+                quote_denom: "orai".to_string(),
+            }),
+            "exchange",
+        );
+    }
+
     #[test]
     fn token_balance_querier() {
         let mut app = MockApp::new(&[]);
@@ -455,6 +656,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn token_raw_querier_parity() {
+        let mut app = MockApp::new(&[]);
+        app.set_token_contract(Box::new(crate::create_entry_points_testing!(cw20_base)));
+        app.set_token_balances(&[(
+            &"AIRI".to_string(),
+            &[
+                (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(123u128)),
+                (&"addr00000".to_string(), &Uint128::from(77u128)),
+            ],
+        )]);
+        let token_addr = app.get_token_addr("AIRI").unwrap();
+
+        let assert_parity = |app: &MockApp, account: &str, expected: u128| {
+            let smart = query_token_balance(
+                &app.as_querier(),
+                token_addr.clone(),
+                Addr::unchecked(account),
+            )
+            .unwrap();
+            let raw = query_token_balance_raw(
+                &app.as_querier(),
+                token_addr.clone(),
+                Addr::unchecked(account),
+            )
+            .unwrap();
+            assert_eq!(smart, Uint128::from(expected));
+            assert_eq!(raw, smart);
+        };
+
+        assert_parity(&app, MOCK_CONTRACT_ADDR, 123);
+        assert_parity(&app, "addr00000", 77);
+        // unknown accounts read as zero both ways
+        assert_parity(&app, "addr00001", 0);
+
+        // move the balances around and read again
+        app.execute(
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Transfer {
+                recipient: "addr00001".to_string(),
+                amount: Uint128::from(23u128),
+            },
+            &[],
+        )
+        .unwrap();
+        assert_parity(&app, MOCK_CONTRACT_ADDR, 100);
+        assert_parity(&app, "addr00001", 23);
+
+        assert_eq!(
+            query_supply_raw(&app.as_querier(), token_addr.clone()).unwrap(),
+            query_supply(&app.as_querier(), token_addr).unwrap()
+        );
+    }
+
     #[test]
     fn balance_querier() {
         let app = MockApp::new(&[(
@@ -571,4 +827,63 @@ mod tests {
             Uint128::from(123u128)
         );
     }
+
+    #[test]
+    fn test_asset_display_from_str() {
+        let native = Asset {
+            info: AssetInfo::NativeToken {
+                denom: "orai".to_string(),
+            },
+            amount: Uint128::from(1000u128),
+        };
+        let cw20 = Asset {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("orai1abc"),
+            },
+            amount: Uint128::from(500u128),
+        };
+        let ibc = Asset {
+            info: AssetInfo::NativeToken {
+                denom: "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+                    .to_string(),
+            },
+            amount: Uint128::from(42u128),
+        };
+
+        assert_eq!(native.to_string(), "1000orai");
+        assert_eq!(cw20.to_string(), "500 orai1abc (cw20)");
+        assert_eq!(
+            ibc.to_string(),
+            "42ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+
+        for asset in [native, cw20, ibc] {
+            assert_eq!(Asset::from_str(&asset.to_string()).unwrap(), asset);
+        }
+
+        assert_eq!(
+            AssetInfo::from_str("orai1abc (cw20)").unwrap(),
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("orai1abc"),
+            }
+        );
+        assert_eq!(
+            AssetInfo::from_str("orai").unwrap(),
+            AssetInfo::NativeToken {
+                denom: "orai".to_string(),
+            }
+        );
+
+        for invalid in [
+            "",
+            "orai",
+            "1000",
+            "100 (cw20)",
+            "abc orai1abc (cw20)",
+            "10/denom",
+        ] {
+            assert!(Asset::from_str(invalid).is_err());
+        }
+        assert!(AssetInfo::from_str(" (cw20)").is_err());
+    }
 }
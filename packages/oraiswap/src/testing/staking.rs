@@ -0,0 +1,349 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::testing::MockQuerier;
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, ContractResult, CosmosMsg, Decimal, QuerierResult, StdResult,
+    SystemError, SystemResult, Uint128, WasmMsg, WasmQuery,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+
+use crate::staking::{
+    Cw20HookMsg, ExecuteMsg, PoolInfoResponse, QueryMsg, RewardInfoResponse,
+    RewardInfoResponseItem, StakingContract,
+};
+
+/// Answers the PoolInfo, PoolInfos and RewardInfo queries of a staking contract from seeded
+/// responses, for the contracts that integrate with it
+#[derive(Clone, Debug, Default)]
+pub struct StakingQuerier {
+    staking_addr: String,
+    pools: BTreeMap<String, PoolInfoResponse>,
+    reward_infos: BTreeMap<String, Vec<RewardInfoResponseItem>>,
+}
+
+impl StakingQuerier {
+    pub fn new(staking_addr: &str) -> Self {
+        StakingQuerier {
+            staking_addr: staking_addr.to_string(),
+            ..StakingQuerier::default()
+        }
+    }
+
+    /// replaces the pool of the same staking token
+    pub fn with_pool(mut self, pool_info: PoolInfoResponse) -> Self {
+        self.pools
+            .insert(pool_info.staking_token.to_string(), pool_info);
+        self
+    }
+
+    /// replaces the position of the staker in the same pool
+    pub fn with_reward_info(
+        mut self,
+        staker_addr: &str,
+        reward_info: RewardInfoResponseItem,
+    ) -> Self {
+        let reward_infos = self
+            .reward_infos
+            .entry(staker_addr.to_string())
+            .or_default();
+        reward_infos.retain(|item| item.staking_token != reward_info.staking_token);
+        reward_infos.push(reward_info);
+        self
+    }
+
+    /// answers a query to the staking contract, None when the query is for another contract so
+    /// it can be chained with other mocks
+    pub fn handle(&self, query: &WasmQuery) -> Option<QuerierResult> {
+        match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == &self.staking_addr => {
+                Some(match from_binary(msg) {
+                    Ok(msg) => self.query(msg),
+                    Err(err) => SystemResult::Err(SystemError::InvalidRequest {
+                        error: err.to_string(),
+                        request: msg.clone(),
+                    }),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// answers the staking queries of the querier, every other contract is missing
+    pub fn install(self, querier: &mut MockQuerier) {
+        querier.update_wasm(move |query| {
+            self.handle(query).unwrap_or_else(|| match query {
+                WasmQuery::Smart { contract_addr, .. } => {
+                    SystemResult::Err(SystemError::NoSuchContract {
+                        addr: contract_addr.clone(),
+                    })
+                }
+                _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                    kind: "wasm".to_string(),
+                }),
+            })
+        });
+    }
+
+    fn query(&self, msg: QueryMsg) -> QuerierResult {
+        let res = match msg {
+            QueryMsg::PoolInfo { staking_token } => match self.pools.get(staking_token.as_str()) {
+                Some(pool_info) => to_binary(pool_info),
+                None => {
+                    return SystemResult::Ok(ContractResult::Err(format!(
+                        "Pool for {} not found",
+                        staking_token
+                    )))
+                }
+            },
+            QueryMsg::PoolInfos { .. } => {
+                to_binary(&self.pools.values().cloned().collect::<Vec<_>>())
+            }
+            QueryMsg::RewardInfo {
+                staker_addr,
+                staking_token,
+            } => to_binary(&RewardInfoResponse {
+                reward_infos: self
+                    .reward_infos
+                    .get(staker_addr.as_str())
+                    .map(|items| {
+                        items
+                            .iter()
+                            .filter(|item| {
+                                staking_token
+                                    .as_ref()
+                                    .map_or(true, |token| token == &item.staking_token)
+                            })
+                            .cloned()
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                staker_addr,
+            }),
+            _ => {
+                return SystemResult::Err(SystemError::UnsupportedRequest {
+                    kind: "staking".to_string(),
+                })
+            }
+        };
+        SystemResult::Ok(res.into())
+    }
+}
+
+/// a registered pool with nothing but its total bond set
+pub fn mock_pool_info(staking_token: &str, total_bond_amount: Uint128) -> PoolInfoResponse {
+    PoolInfoResponse {
+        staking_token: Addr::unchecked(staking_token),
+        total_bond_amount,
+        reward_index: Decimal::zero(),
+        pending_reward: Uint128::zero(),
+        migration_index_snapshot: None,
+        migration_deprecated_staking_token: None,
+        migration_deprecated_total_bond: None,
+        migration_grace_period_end: None,
+        frozen: false,
+        instant_unbond_penalty: Decimal::zero(),
+        min_bond_amount: Uint128::zero(),
+        min_compound_reward: Uint128::zero(),
+        bond_cap: None,
+        remaining_bond_capacity: None,
+        max_bond_per_staker: None,
+        total_weighted_bond: total_bond_amount,
+        lock_tiers: vec![],
+        reward_weight: Decimal::zero(),
+        reward_start_time: None,
+        reward_end_time: None,
+        proxy_rewards: vec![],
+        symbol: None,
+        staker_count: None,
+        lifetime_bonded: total_bond_amount,
+        lifetime_unbonded: Uint128::zero(),
+        lifetime_rewards_deposited: Uint128::zero(),
+        lifetime_rewards_claimed: Uint128::zero(),
+        unbonding_period: 0,
+        whitelist_enabled: false,
+        unbond_cooldown: 0,
+        max_index_increase_per_deposit: Decimal::zero(),
+        reward_stream_seconds: 0,
+        streaming_reward: Uint128::zero(),
+        deposit_epoch: 0,
+    }
+}
+
+/// an unlocked position with its pending reward not yet split into assets
+pub fn mock_reward_info(
+    staking_token: &str,
+    bond_amount: Uint128,
+    pending_reward: Uint128,
+) -> RewardInfoResponseItem {
+    RewardInfoResponseItem {
+        staking_token: Addr::unchecked(staking_token),
+        bond_amount,
+        weighted_bond_amount: bond_amount,
+        locks: vec![],
+        pending_reward,
+        pending_withdraw: vec![],
+        reward_decimals: vec![],
+        should_migrate: None,
+        total_claimed: Uint128::zero(),
+        last_updated: 0,
+    }
+}
+
+/// bonds amount of the cw20 staking token for the sender
+pub fn bond_send_msg(
+    staking_addr: &str,
+    staking_token: &str,
+    amount: Uint128,
+    lock_duration: Option<u64>,
+) -> StdResult<CosmosMsg> {
+    StakingContract(Addr::unchecked(staking_addr)).bond_msg(
+        &Addr::unchecked(staking_token),
+        amount,
+        lock_duration,
+    )
+}
+
+/// deposits amount of the cw20 reward token, split between the pools by their reward weights
+pub fn deposit_reward_send_msg(
+    staking_addr: &str,
+    reward_token: &str,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    cw20_send_msg(
+        staking_addr,
+        reward_token,
+        amount,
+        &Cw20HookMsg::DepositReward {
+            stream_over_seconds: None,
+            expected_epoch: None,
+        },
+    )
+}
+
+/// what the staking contract receives from the staking token for a bond_send_msg of sender
+pub fn bond_receive_msg(
+    sender: &str,
+    amount: Uint128,
+    lock_duration: Option<u64>,
+) -> StdResult<ExecuteMsg> {
+    Ok(ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: sender.to_string(),
+        amount,
+        msg: to_binary(&Cw20HookMsg::Bond {
+            lock_duration,
+            referrer: None,
+        })?,
+    }))
+}
+
+/// what the staking contract receives from the reward token for a deposit_reward_send_msg
+pub fn deposit_reward_receive_msg(sender: &str, amount: Uint128) -> StdResult<ExecuteMsg> {
+    Ok(ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: sender.to_string(),
+        amount,
+        msg: to_binary(&Cw20HookMsg::DepositReward {
+            stream_over_seconds: None,
+            expected_epoch: None,
+        })?,
+    }))
+}
+
+fn cw20_send_msg(
+    staking_addr: &str,
+    token: &str,
+    amount: Uint128,
+    hook_msg: &Cw20HookMsg,
+) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: staking_addr.to_string(),
+            amount,
+            msg: to_binary(hook_msg)?,
+        })?,
+        funds: vec![],
+    }
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::MockQuerier;
+    use cosmwasm_std::{Addr, Empty, QuerierWrapper, Uint128};
+
+    use super::{mock_pool_info, mock_reward_info, StakingQuerier};
+    use crate::staking::{PoolInfoResponse, QueryMsg, RewardInfoResponse};
+
+    #[test]
+    fn test_staking_querier() {
+        let mut querier: MockQuerier<Empty> = MockQuerier::new(&[]);
+        StakingQuerier::new("staking")
+            .with_pool(mock_pool_info("lp", Uint128::from(100u128)))
+            .with_reward_info(
+                "vault",
+                mock_reward_info("lp", Uint128::from(100u128), Uint128::from(5u128)),
+            )
+            .with_reward_info(
+                "vault",
+                mock_reward_info("lp2", Uint128::from(10u128), Uint128::zero()),
+            )
+            .install(&mut querier);
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+
+        let res: PoolInfoResponse = querier
+            .query_wasm_smart(
+                "staking",
+                &QueryMsg::PoolInfo {
+                    staking_token: Addr::unchecked("lp"),
+                },
+            )
+            .unwrap();
+        assert_eq!(res, mock_pool_info("lp", Uint128::from(100u128)));
+        assert!(querier
+            .query_wasm_smart::<PoolInfoResponse>(
+                "staking",
+                &QueryMsg::PoolInfo {
+                    staking_token: Addr::unchecked("lp3"),
+                },
+            )
+            .is_err());
+
+        let res: RewardInfoResponse = querier
+            .query_wasm_smart(
+                "staking",
+                &QueryMsg::RewardInfo {
+                    staker_addr: Addr::unchecked("vault"),
+                    staking_token: Some(Addr::unchecked("lp")),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            res.reward_infos,
+            vec![mock_reward_info(
+                "lp",
+                Uint128::from(100u128),
+                Uint128::from(5u128)
+            )]
+        );
+        let res: RewardInfoResponse = querier
+            .query_wasm_smart(
+                "staking",
+                &QueryMsg::RewardInfo {
+                    staker_addr: Addr::unchecked("other"),
+                    staking_token: None,
+                },
+            )
+            .unwrap();
+        assert!(res.reward_infos.is_empty());
+
+        // other contracts are missing
+        assert!(querier
+            .query_wasm_smart::<PoolInfoResponse>(
+                "pair",
+                &QueryMsg::PoolInfo {
+                    staking_token: Addr::unchecked("lp"),
+                },
+            )
+            .is_err());
+    }
+}